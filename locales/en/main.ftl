@@ -33,6 +33,7 @@ tt-broadcast-registration = User { $username } was registered.
 btn-yes = Yes
 btn-no = No (use username)
 btn-admin-verify = Yes
+btn-admin-verify-profile = Yes, as { $profile }
 btn-admin-reject = No
 btn-delete-user = Delete User
 btn-manage-banlist = Manage Ban List
@@ -55,6 +56,9 @@ admin-request-username = Username:
 admin-request-nickname = Nickname:
 admin-request-telegram-user = Telegram User:
 admin-request-approve = Approve registration?
+admin-web-request-title = Web registration request:
+admin-request-ip = IP address:
+admin-request-user-agent = User agent:
 admin-submit-error = An error occurred while submitting your registration for approval. Please try again later or contact an administrator.
 username-not-found = Error: Username not found. Please start over.
 invalid-choice = Invalid choice. Please try again.
@@ -83,16 +87,28 @@ admin-req-approved-alert = User { $username } registration approved.
 admin-req-rejected-alert = User { $username } registration declined.
 admin-req-not-found = Registration request not found, outdated, or already processed.
 admin-req-handled = This registration request has already been handled.
-admin-approve-failed-critical = CRITICAL: Registration for { $username } was approved, but the final registration step failed. Please check logs.
+admin-approve-failed-critical = CRITICAL: Registration for { $username } was approved, but creating the TeamTalk account failed: { $error }
 deeplink-generate-error = An error occurred while generating the deeplink.
 admin-decision-notify = Admin { $admin_name } ({ $admin_id }) has { $decision } the registration request for TeamTalk user '{ $teamtalk_username }' (Telegram ID: { $registrant_telegram_id }).
 admin-decision-telegram-username =  Telegram Username: @{ $registrant_tg_username }
 admin-decision-approved = approved
 admin-decision-rejected = rejected
+admin-web-decision-notify = Admin { $admin_name } ({ $admin_id }) has { $decision } the web registration request for TeamTalk user '{ $teamtalk_username }' (IP: { $ip_address }).
 
 # Files
 file-caption = Your .tt file for quick connection
+zip-password-random = The .tt file in the ZIP archive is protected with the password: { $password }
+zip-password-account = The .tt file in the ZIP archive is protected with your TeamTalk password.
+zip-readme =
+    { $server_name }: TeamTalk client
+    This archive contains a TeamTalk client that is already set up for your account "{ $username }".
+    How to connect:
+    1. Extract the whole archive to a folder of your choice.
+    2. Start TeamTalk from the extracted folder.
+    3. Open the file Client/{ $tt_file } with TeamTalk to connect to { $host }, port { $port }.
+    The .tt file contains your password. Do not share it with anyone.
 link-text = Or use this TT link:
+qr-caption = Scan this QR code on your phone to connect.
 file-send-error = Could not send the .tt file or link. Please contact an admin.
 
 # Commands
@@ -117,6 +133,7 @@ web-download-msg = You can now download your configuration:
 web-link-tt = Download .tt file
 web-link-zip = Download pre-configured TeamTalk Client (ZIP)
 web-quick-link = Quick Connect Link:
+web-qr-text = Or scan this code with your phone:
 web-countdown-text = You have <span id='countdown-timer'>10:00</span> to download your .tt file, client or use the quick connect link.
 web-expired = expired
 web-second = second
@@ -128,7 +145,7 @@ web-placeholder-nickname = Defaults to username if blank
 web-label-password = Password:
 web-show-password = Show Password
 web-btn-register = Register
-web-err-ip-limit = This IP address has already been used to register an account.
+web-err-ip-limit = Too many accounts have been registered from this IP address. Please try again later.
 web-err-username-taken = Sorry, this username is already taken. Please choose another one.
 web-err-username-invalid = Username is invalid. Please choose a different one.
 web-err-password-invalid = Password is invalid. Please choose a different one.
@@ -136,3 +153,357 @@ web-err-nickname-invalid = Nickname is invalid. Please choose a different one.
 web-err-timeout = Timeout waiting for TeamTalk server.
 web-err-file-not-found = File not found on disk
 web-err-invalid-link = Invalid or expired link
+web-err-submit = An error occurred while submitting your registration for approval. Please try again later.
+web-err-rate-limited = Too many requests. Please try again later.
+web-err-captcha = CAPTCHA verification failed. Please complete the check and try again.
+web-pending-title = Your registration request has been sent to the administrators for approval.
+web-status-info = You can check the status of your request at any time using this link:
+web-status-pending = Your registration request is still waiting for administrator approval.
+web-status-approved = Your registration has been approved. You can now log in to the TeamTalk server with the username and password you chose.
+web-status-rejected = Your registration request has been declined by the administrator.
+web-status-failed = Your registration was approved, but the account could not be created. Please contact an administrator.
+web-status-not-found = Registration request not found or expired.
+admin-web-nav-overview = Overview
+admin-web-nav-registrations = Registrations
+admin-web-nav-bans = Banlist
+admin-web-nav-pending = Pending approvals
+admin-web-nav-tokens = Download tokens
+admin-web-nav-teamtalk = TeamTalk users
+admin-web-title-overview = Admin dashboard
+admin-web-empty = Nothing here.
+admin-web-col-telegram-id = Telegram ID
+admin-web-col-username = Username
+admin-web-col-nickname = Nickname
+admin-web-col-date = Date
+admin-web-col-reason = Reason
+admin-web-col-ip = IP address
+admin-web-col-file = File
+admin-web-col-type = Type
+admin-web-col-expires = Expires
+admin-web-col-uses-left = Downloads left
+admin-web-uses-unlimited = Until expiry
+admin-web-col-channel = Channel ID
+admin-web-btn-delete-ban = Delete and ban
+admin-web-btn-ban = Ban
+admin-web-btn-delete = Delete
+admin-web-pending-telegram = Telegram requests
+admin-web-pending-web = Web requests
+admin-web-online-title = Online users
+admin-web-accounts-title = Server accounts
+admin-web-error = Database error. Check the server logs.
+admin-web-tt-unavailable = TeamTalk server is not available right now.
+admin-web-notice-deleted = Registration deleted and user banned.
+admin-web-notice-not-found = Nothing was changed: the record no longer exists.
+admin-web-notice-banned = User banned.
+admin-web-notice-unbanned = User unbanned.
+admin-web-notice-invalid-id = Invalid Telegram ID.
+admin-web-notice-tt-deleted = TeamTalk account deleted.
+admin-web-notice-tt-delete-failed = Failed to delete the TeamTalk account. Check the server logs.
+cancel-done = Registration cancelled. Send /start to begin again.
+cancel-nothing = There is nothing to cancel.
+myaccount-title = Your TeamTalk account
+myaccount-username = Username: { $username }
+myaccount-nickname = Nickname: { $nickname }
+myaccount-registered-at = Registered: { $date }
+myaccount-registered-unknown = Registered: unknown
+myaccount-not-registered = You don't have a registered TeamTalk account yet. Send /start to register.
+myaccount-password-note = The bot does not keep your password, so the file and link below leave it empty. Enter it in TeamTalk when connecting.
+btn-myaccount-file = Get .tt file
+btn-myaccount-link = Get TT link
+changepassword-prompt = Send the new password for your TeamTalk account. Send /cancel to keep the current one.
+changepassword-done = Your password has been changed. Here are your updated connection files.
+changepassword-failed = Could not change the password right now. Please try again later.
+help-title = Available commands:
+help-start = /start - register a TeamTalk account
+help-start-invite-only = /start - registration is available only through an invite link from an administrator
+help-myaccount = /myaccount - show your account and get the connection files again
+help-changepassword = /changepassword - change your TeamTalk password
+help-cancel = /cancel - abort the current step
+help-help = /help - show this message
+help-web-enabled = You can also register on the website.
+help-admin-title = Admin commands:
+help-adminpanel = /adminpanel - open the admin panel
+help-generate = /generate [lifetime] [uses] - create an invite link (default: 5m, single use)
+help-exit = /exit - stop the bot
+btn-online-users = Online Users
+btn-online-kick = Kick
+btn-online-message = Message
+admin-online-title = Online users: { $count }
+admin-online-entry = { $nickname } ({ $username }) - channel { $channel_id }, { $user_type }
+admin-online-type-admin = admin
+admin-online-type-default = user
+admin-online-empty = No users are online.
+admin-online-error = Could not get the list of online users from the TeamTalk server.
+admin-online-kick-fail = Could not kick user { $user_id }: { $error }
+admin-online-message-prompt = Send the text message for user { $user_id }.
+admin-online-message-sent = Message sent to user { $user_id }.
+admin-online-message-fail = Could not send the message to user { $user_id }: { $error }
+help-online = /online - list online TeamTalk users
+btn-online-ban = Ban
+btn-online-confirm-kick = Yes, kick
+btn-online-confirm-ban = Yes, ban
+admin-online-kick-prompt = Kick user { $user_id } from the server?
+admin-online-ban-prompt = Ban user { $user_id } on the server by username and IP address? The user will also be disconnected.
+admin-online-kick-done = User { $user_id } was kicked from the server.
+admin-online-ban-done = User { $user_id } was banned and disconnected.
+admin-online-ban-fail = Could not ban user { $user_id }: { $error }
+btn-tt-broadcast = Broadcast to TeamTalk
+broadcast-tt-usage = Usage: /broadcast <text>
+broadcast-tt-prompt = Send the message to broadcast to everyone on the TeamTalk server.
+broadcast-tt-sent = Broadcast sent to the TeamTalk server.
+broadcast-tt-failed = Could not send the broadcast: { $error }
+help-broadcast = /broadcast <text> - send a message to everyone on the TeamTalk server
+btn-tg-broadcast = Message Telegram Users
+btn-broadcast-send = Send
+broadcast-tg-prompt = Send the message to deliver to every registered Telegram user. Text, photos and other message types are copied as is.
+broadcast-tg-confirm = Send this message to { $count } registered users?
+broadcast-tg-expired = There is no message to send. Compose it again from the admin panel.
+broadcast-tg-progress = Sending: { $done } of { $total }…
+broadcast-tg-done = Broadcast finished: delivered { $sent } of { $total }, failed { $failed }.
+broadcast-tg-failed-blocked = Blocked the bot ({ $count }): { $ids }
+broadcast-tg-failed-deactivated = Deleted accounts ({ $count }): { $ids }
+broadcast-tg-failed-not-found = Chat not found ({ $count }): { $ids }
+broadcast-tg-failed-other = Other errors ({ $count }): { $ids }
+btn-list-search = Search
+btn-list-show-all = Show all
+admin-list-search-prompt = Send the beginning of a username or Telegram ID to search for.
+admin-list-filter = Filter: { $filter }
+admin-list-no-matches = Nothing matches the filter.
+btn-reason-skip = Skip reason
+decision-reason = Reason: { $reason }
+reason-prompt-reject = Send the reason for declining this request, or press "Skip reason".
+reason-prompt-ban = Send the reason for deleting and banning user { $tg_id }, or press "Skip reason".
+user-banned-notice = You have been banned by the administrator.
+admin-user-delete-missing = User { $tg_id } is no longer registered.
+btn-invites = Invite Links
+btn-invite-revoke = Revoke
+btn-invite-revoke-all = Revoke all
+admin-invites-title = Active invite links:
+admin-invites-empty = There are no active invite links.
+admin-invite-entry = #{ $id } { $token }… by { $creator }, expires { $expires }, uses left: { $uses }
+admin-invite-revoked = Invite link #{ $id } revoked.
+admin-invite-not-found = Invite link #{ $id } no longer exists.
+admin-invites-revoke-all-prompt = Revoke all unused invite links?
+admin-invites-revoked-all = Revoked { $count } invite links.
+deeplink-generate-usage = Usage: /generate [lifetime] [uses] [--server <id>], for example /generate 24h 10. Lifetime takes s, m, h or d (up to 30d); uses range from 1 to 1000. Without --server users pick the server themselves.
+deeplink-generated = Invite link for { $uses } registration(s), valid until { $expires }:
+    { $link }
+admin-owner-only = Only owners can do this.
+username-too-short = Username must be at least { $min } characters long.
+username-too-long = Username must be at most { $max } characters long.
+username-invalid-chars = Username may only contain letters, digits, dots, underscores and hyphens.
+username-reserved = This username is reserved. Please choose another one.
+btn-generate-password = Generate a strong password
+password-generated = Your generated password: { $password }
+    Save it somewhere safe; the bot will not show it again.
+password-too-short = Password must be at least { $min } characters long.
+password-too-long = Password must be at most { $max } characters long.
+password-missing-lowercase = Password must contain a lowercase letter.
+password-missing-uppercase = Password must contain an uppercase letter.
+password-missing-digit = Password must contain a digit.
+password-missing-symbol = Password must contain a symbol such as ! or @.
+bot-restart = Restarting...
+help-restart = /restart - stop the bot so its supervisor starts it again
+cmd-start = Register a TeamTalk account
+cmd-myaccount = Show your account and connection files
+cmd-changepassword = Change your TeamTalk password
+cmd-cancel = Abort the current step
+cmd-help = Show available commands
+cmd-adminpanel = Open the admin panel
+cmd-online = List online TeamTalk users
+cmd-broadcast = Message everyone on the TeamTalk server
+cmd-generate = Create an invite link
+cmd-exit = Stop the bot
+cmd-restart = Restart the bot
+help-register = /register <username> <password> [nickname] [--to <chat_id>] [--profile <name>] - create a TeamTalk account directly
+cmd-register = Create a TeamTalk account directly
+admin-register-usage = Usage: /register <username> <password> [nickname] [--to <chat_id>] [--profile <name>]
+admin-register-unknown-profile = Unknown rights profile { $profile }.
+admin-register-success = TeamTalk account { $username } created.
+admin-register-notify = Admin { $admin_id } created TeamTalk account { $username } with /register.
+admin-register-forwarded = Connection files sent to chat { $chat_id }.
+admin-register-forward-failed = Could not send the connection files to chat { $chat_id }; sending them here instead.
+tt-account-removed-user = Your TeamTalk account { $username } was deleted on the server, so you can no longer connect with it and can't register again through this bot. If you think this is a mistake, please contact the server administrators.
+admin-ban-propagate-prompt = The banned user has the TeamTalk account { $tt_username }. Delete it from the server too?
+admin-web-btn-delete-tt = Delete TeamTalk account
+btn-search = 🔍 Search
+btn-search-again = 🔍 New search
+btn-back-to-panel = ⬅️ Admin panel
+admin-search-prompt = Send part of a username, nickname or Telegram ID to search registrations, bans and TeamTalk accounts.
+admin-search-title = Search results for "{ $query }":
+admin-search-registrations = Registrations ({ $count }):
+admin-search-banned = Banned users ({ $count }):
+admin-search-accounts = TeamTalk accounts ({ $count }):
+admin-search-more = …and { $count } more, refine the search to see them.
+help-stats = /stats - show registration statistics
+cmd-stats = Show registration statistics
+help-ttstatus = /ttstatus - show the TeamTalk connection state
+cmd-ttstatus = Show the TeamTalk connection state
+admin-tt-status-unavailable = The TeamTalk worker did not answer.
+admin-tt-status-online = logged in
+admin-tt-status-connecting = connected, not logged in
+admin-tt-status-offline = disconnected
+admin-tt-status-none = —
+admin-tt-status =
+    🔌 TeamTalk status: { $state }
+    Server: { $server }
+    Uptime: { $uptime }
+    Reconnect attempts: { $attempts }
+    Pending commands: { $pending }
+    Last error: { $last_error }
+tt-conn-connected = 🔌 Connected to the TeamTalk server, logging in.
+tt-conn-logged-in = ✅ Logged in to the TeamTalk server { $server }.
+tt-conn-lost = ⚠️ Lost the connection to the TeamTalk server. Reconnecting.
+tt-conn-reconnecting = 🔄 Reconnecting to the TeamTalk server (attempt { $attempt }, next in { $delay } s).
+admin-stats-unavailable = unavailable
+admin-stats =
+    📊 Registration statistics (today since 00:00 UTC)
+    Telegram registrations: { $telegram_total } (today: { $telegram_today }, approved by admins: { $telegram_approved })
+    Web registrations: { $web_total } (today: { $web_today })
+    Web approvals: { $approved } (today: { $approved_today })
+    Web rejections: { $rejected } (today: { $rejected_today })
+    Awaiting approval: { $pending }
+    Active bans: { $banned }
+    Invite links: { $deeplinks_issued } issued, { $deeplinks_used } used up
+    Online on TeamTalk: { $online }
+help-audit = /audit [page] - show recent admin and system actions
+cmd-audit = Show the audit log
+admin-audit-usage = Usage: /audit [page]
+admin-audit-empty = The audit log has no entries here.
+admin-audit-title = Audit log, page { $page } (newest first):
+admin-web-nav-audit = Audit log
+admin-web-col-actor = Who
+admin-web-col-action = Action
+admin-web-col-target = Target
+admin-web-col-details = Details
+btn-export = 📤 Export registrations and bans
+admin-export-ready = Export of registrations and bans (CSV and JSON):
+admin-export-error = Failed to create the export. Check the server logs.
+help-backup = /backup - get a compressed snapshot of the database
+cmd-backup = Get a database backup
+admin-backup-ready = Database backup. Keep it private: it contains every registration.
+admin-backup-error = Failed to create the database backup. Check the server logs.
+admin-backup-unsupported = Built-in backups only work with SQLite. Back up PostgreSQL with pg_dump.
+
+register-queued = The TeamTalk server is temporarily unavailable. Your account { $username } will be created shortly; you will get a message with its files here.
+queued-account-taken = The name { $username } was taken on the TeamTalk server while your registration was waiting. Please register again with another name.
+queued-account-failed = Your TeamTalk account { $username } could not be created. Please try again later or contact an administrator.
+web-queued-title = The TeamTalk server is temporarily unavailable. Your account will be created shortly; sign in with the username and password you entered.
+admin-account-queued = TeamTalk is unavailable, so { $username } was queued. The account will be created once the bot is logged in again.
+admin-queued-account-created = Queued account { $username } was created on the TeamTalk server.
+admin-queued-account-taken = Queued account { $username } was dropped: the name is already taken on the TeamTalk server.
+admin-queued-account-failed = Queued account { $username } could not be created and was dropped. Please check logs.
+reconcile-title = TeamTalk accounts and the registration database differ:
+reconcile-vanished = Registrations whose TeamTalk account no longer exists ({ $count }): { $names }
+reconcile-banned = Banned users whose TeamTalk account still exists ({ $count }): { $names }
+reconcile-unlinked = TeamTalk accounts without a Telegram registration ({ $count }): { $names }
+reconcile-report-only = Nothing was changed. Set tt_reconcile_repair = true to remove stale registrations and delete banned users' accounts automatically.
+reconcile-repaired = Repaired: { $removed } stale registrations removed, { $deleted } accounts of banned users deleted, { $failed } failed. Unlinked accounts are left as they are.
+tt-conn-gave-up = ❌ Gave up reconnecting to the TeamTalk server after { $attempts } attempts. Check the server and restart the bot.
+tt-pm-help = Commands: help - this list; who - who is online; version - bot version.
+tt-pm-who = Online ({ $count }): { $users }
+tt-pm-version = TeamTalk registration bot { $version }
+tt-pm-unknown = Unknown command. Send "help" for the list of commands.
+tt-pm-help-register = register <username> <password> - create your own account on this server.
+tt-pm-register-usage = Send: register <username> <password>
+tt-pm-register-created = Account { $username } was created. Reconnect with it using the password you sent.
+tt-pm-register-failed = The account { $username } could not be created. Please try again later.
+tt-welcome = Welcome to { $server }, { $nickname }! Send me "help" in a private message to see what I can do.
+deeplink-unknown-server = Unknown TeamTalk server { $server }.
+server-prompt = Which server do you want an account on?
+admin-request-server = Server:
+web-label-server = Server:
+web-err-server-invalid = Unknown server. Please choose one from the list.
+tt-err-not-connected = The bot is not connected to TeamTalk right now.
+tt-err-account-exists = An account with this username already exists on the TeamTalk server.
+tt-err-invalid-username = The TeamTalk server does not accept this username.
+tt-err-not-authorized = The bot's TeamTalk account lacks the rights for this.
+tt-err-banned = The bot is banned from the TeamTalk server.
+tt-err-account-not-found = The account does not exist on the TeamTalk server.
+tt-err-failed = The TeamTalk server could not complete the command: { $reason }
+admin-register-failed = Could not create the account: { $error }
+admin-request-rights-profile = Requested rights:
+admin-request-source = Source:
+admin-request-source-telegram = Telegram bot
+admin-request-source-web = web form
+admin-request-source-teamtalk = TeamTalk private message
+rights-profile-prompt = Which rights do you want to request? Admins see your choice when approving.
+rights-profile-default = Default
+web-label-rights-profile = Requested rights:
+web-err-rights-profile-invalid = These rights cannot be requested. Please choose one from the list.
+btn-admin-verify-admin = Approve as admin
+btn-admin-rename = Edit username
+admin-rename-prompt = Send the username to register instead of { $username }.
+admin-rename-done = The request for { $old_username } will now register { $username }. Approve it?
+btn-pending-requests = Pending requests
+admin-pending-title = Requests awaiting approval ({ $count }):
+admin-pending-empty = No registration requests are awaiting approval.
+admin-pending-entry = { $username } ({ $source }), waiting { $age }
+pending-reminder-title = ⏰ { $count } registration requests have been waiting for approval for more than { $age }:
+pending-reminder-hint = Open /adminpanel → "Pending requests" to decide them.
+pending-expired = Your registration request expired before an administrator answered it. You can send /start to try again.
+web-err-form-expired = This form has expired. Please check your details and submit it again.
+web-err-invite-required = Registration on this site is by invitation only. Please use the invite link you were given.
+web-err-invite-invalid = This invite link is invalid, expired or already used up.
+deeplink-generated-web = On the web: add ?invite={ $token } to the registration page address.
+admin-web-login-title = Sign in
+admin-web-login-username = Username
+admin-web-login-password = Password
+admin-web-login-code = Authenticator code
+admin-web-login-submit = Sign in
+admin-web-login-failed = Wrong username, password or code.
+admin-web-login-locked = Too many failed attempts. Try again later.
+admin-web-logout = Sign out
+web-status-downloads-once = Download your files now: these links are shown only once.
+admin-request-location = Location:
+admin-web-col-source = Source
+registration-origin-direct = self-service
+registration-origin-approved = approved by an admin
+tt-account-removed-banned-unlinked = 🚫 User '{ $username }' removed from TT (No TG link found). Banned: { $bans }
+web-err-banned = Registration with this username or from this address is not allowed.
+admin-web-bans-entries = Username and IP bans
+admin-web-ban-kind-username = TeamTalk username
+admin-web-ban-kind-ip = IP address or network
+admin-web-col-ban-value = Username, IP or CIDR
+admin-web-notice-ban-added = Ban added.
+admin-web-notice-ban-removed = Ban removed.
+admin-web-notice-invalid-ban = Invalid username or IP network.
+btn-undo-ban = ↩️ Undo ban
+admin-bans-undone = Bans lifted: { $bans }
+admin-bans-undo-none = Nothing was changed: these bans were already lifted.
+btn-notifications = 🔔 Notifications
+admin-notify-title = Notifications sent to your private chat. Tap one to turn it on (🔔) or off (🔕).
+admin-notify-shared-chat = Notifications currently go to the shared admin chat, where these settings do not apply.
+notify-kind-account-created = Account created
+notify-kind-account-changed = Account changed
+notify-kind-account-removed = Account removed and auto-bans
+notify-kind-approvals = Telegram registration requests
+notify-kind-web-registrations = Web registration requests
+notify-kind-connection = TeamTalk connection status
+notify-kind-alert = Errors and reports
+help-quiethours = /quiethours [HH:MM-HH:MM|off|default] - hold notifications for a digest during set hours
+cmd-quiethours = Set notification quiet hours
+quiet-hours-usage = Usage: /quiethours <HH:MM-HH:MM|off|default>, e.g. /quiethours 22:00-07:00 (server time). Notifications other than errors arriving then are sent as one digest afterwards.
+quiet-hours-current = Your quiet hours: { $hours }.
+quiet-hours-set = Quiet hours set: { $hours }.
+quiet-hours-off = off
+admin-notify-digest-title = Notifications received during your quiet hours:
+value-unknown = Unknown
+value-not-available = N/A
+admin-banlist-entry = TG ID: { $tg_id } - TT User: { $tt_user } (Reason: { $reason })
+admin-db-sync-error = DB sync error: user { $username } (TG ID: { $tg_id }) was created on TeamTalk, but saving it to the local database failed: { $error }
+help-translations = /translations - compare every language's messages with English
+cmd-translations = Check translation coverage
+translations-report-title = Translation coverage against English ({ $count } keys):
+translations-lang-complete = { $lang }: complete.
+translations-lang-missing = { $lang } lacks { $count } keys: { $keys }
+translations-lang-extra = { $lang } has { $count } keys English does not: { $keys }
+translations-unused = { $count } English keys are not used in the code: { $keys }
+translations-unused-none = Every English key is used in the code.
+translations-unused-skipped = Unused keys were not checked: src/ and templates/ are not in the working directory.
+admin-web-col-download-link = Download link or token
+admin-web-notice-link-revoked = Download link revoked and its file deleted.
+admin-web-notice-invalid-link = Not a valid download link.
+myaccount-server-unknown = The server your account is on is no longer available. Please contact an administrator.
+admin-ban-propagate-other-server = The TeamTalk account { $tt_username } is on the server { $server }. The bot only deletes accounts on the main server, so remove it there yourself.
+reconcile-server = Server: { $server }
@@ -33,6 +33,7 @@ tt-broadcast-registration = Пользователь { $username } был зар
 btn-yes = Да
 btn-no = Нет (использовать имя пользователя)
 btn-admin-verify = Да
+btn-admin-verify-profile = Да, с профилем { $profile }
 btn-admin-reject = Нет
 btn-delete-user = Удалить пользователя
 btn-manage-banlist = Управление бан-листом
@@ -55,6 +56,9 @@ admin-request-username = Имя пользователя:
 admin-request-nickname = Никнейм (необязательно):
 admin-request-telegram-user = Пользователь Telegram
 admin-request-approve = Одобрить регистрацию?
+admin-web-request-title = Запрос на регистрацию через сайт:
+admin-request-ip = IP-адрес:
+admin-request-user-agent = Браузер (User-Agent):
 admin-submit-error = Ошибка регистрации. Пожалуйста, попробуйте позже или свяжитесь с администратором.
 username-not-found = Ошибка: имя пользователя не найдено. Пожалуйста, начните сначала.
 invalid-choice = Неверный выбор. Пожалуйста, попробуйте снова.
@@ -83,16 +87,28 @@ admin-req-approved-alert = Регистрация пользователя { $us
 admin-req-rejected-alert = Регистрация пользователя { $username } отклонена.
 admin-req-not-found = Запрос на регистрацию не найден или устарел.
 admin-req-handled = Этот запрос на регистрацию уже обработан.
-admin-approve-failed-critical = КРИТИЧЕСКАЯ ОШИБКА: Регистрация для { $username } была одобрена, но последний шаг регистрации не удался. Пожалуйста, проверьте логи.
+admin-approve-failed-critical = КРИТИЧЕСКАЯ ОШИБКА: Регистрация для { $username } была одобрена, но создать аккаунт TeamTalk не удалось: { $error }
 deeplink-generate-error = Произошла ошибка при генерации deeplink.
 admin-decision-notify = Администратор { $admin_name } ({ $admin_id }) { $decision } запрос на регистрацию для пользователя TeamTalk '{ $teamtalk_username }' (Telegram ID: { $registrant_telegram_id }).
 admin-decision-telegram-username =  Telegram Username: @{ $registrant_tg_username }
 admin-decision-approved = одобрил
 admin-decision-rejected = отклонил
+admin-web-decision-notify = Администратор { $admin_name } ({ $admin_id }) { $decision } заявку на регистрацию через сайт для пользователя TeamTalk '{ $teamtalk_username }' (IP: { $ip_address }).
 
 # Files
 file-caption = Ваш .tt файл для быстрого подключения
+zip-password-random = Файл .tt в ZIP-архиве защищён паролем: { $password }
+zip-password-account = Файл .tt в ZIP-архиве защищён вашим паролем TeamTalk.
+zip-readme =
+    { $server_name }: клиент TeamTalk
+    В этом архиве находится клиент TeamTalk, уже настроенный для вашей учётной записи «{ $username }».
+    Как подключиться:
+    1. Распакуйте весь архив в удобную для вас папку.
+    2. Запустите TeamTalk из распакованной папки.
+    3. Откройте файл Client/{ $tt_file } в TeamTalk, чтобы подключиться к { $host }, порт { $port }.
+    Файл .tt содержит ваш пароль. Никому его не передавайте.
 link-text = Или используйте эту TT ссылку:
+qr-caption = Отсканируйте этот QR-код телефоном, чтобы подключиться.
 file-send-error = Не удалось отправить .tt файл или ссылку. Пожалуйста, свяжитесь с администратором.
 
 # Commands
@@ -117,6 +133,7 @@ web-download-msg = Теперь вы можете скачать конфигу
 web-link-tt = Скачать .tt файл
 web-link-zip = Скачать преднастроенный клиент TeamTalk (ZIP)
 web-quick-link = Ссылка для быстрого подключения:
+web-qr-text = Или отсканируйте этот код телефоном:
 web-countdown-text = У вас есть <span id='countdown-timer'>10:00</span>, чтобы скачать ваш .tt файл, клиент или использовать ссылку для быстрого подключения.
 web-expired = истекло
 web-second = секунда
@@ -128,7 +145,7 @@ web-placeholder-nickname = По умолчанию будет использов
 web-label-password = Пароль:
 web-show-password = Показать пароль
 web-btn-register = Зарегистрироваться
-web-err-ip-limit = Этот IP адрес уже использовался для регистрации аккаунта.
+web-err-ip-limit = С этого IP адреса зарегистрировано слишком много аккаунтов. Пожалуйста, попробуйте позже.
 web-err-username-taken = Извините, это имя пользователя уже занято. Пожалуйста, выберите другое.
 web-err-username-invalid = Некорректное имя пользователя. Пожалуйста, выберите другое.
 web-err-password-invalid = Некорректный пароль. Пожалуйста, выберите другой.
@@ -136,3 +153,357 @@ web-err-nickname-invalid = Некорректный никнейм. Пожалу
 web-err-timeout = Таймаут ожидания сервера TeamTalk.
 web-err-file-not-found = Файл не найден на диске
 web-err-invalid-link = Ссылка недействительна или устарела
+web-err-submit = Произошла ошибка при отправке заявки на регистрацию. Пожалуйста, попробуйте позже.
+web-err-rate-limited = Слишком много запросов. Пожалуйста, попробуйте позже.
+web-err-captcha = Проверка CAPTCHA не пройдена. Пожалуйста, пройдите проверку и попробуйте снова.
+web-pending-title = Ваша заявка на регистрацию отправлена администраторам на рассмотрение.
+web-status-info = Вы можете проверить статус заявки в любое время по этой ссылке:
+web-status-pending = Ваша заявка на регистрацию всё ещё ожидает одобрения администратора.
+web-status-approved = Ваша регистрация одобрена. Теперь вы можете войти на сервер TeamTalk с выбранными именем пользователя и паролем.
+web-status-rejected = Ваша заявка на регистрацию отклонена администратором.
+web-status-failed = Ваша регистрация была одобрена, но создать учётную запись не удалось. Пожалуйста, свяжитесь с администратором.
+web-status-not-found = Заявка на регистрацию не найдена или устарела.
+admin-web-nav-overview = Обзор
+admin-web-nav-registrations = Регистрации
+admin-web-nav-bans = Бан-лист
+admin-web-nav-pending = Ожидают подтверждения
+admin-web-nav-tokens = Ссылки на скачивание
+admin-web-nav-teamtalk = Пользователи TeamTalk
+admin-web-title-overview = Панель администратора
+admin-web-empty = Здесь пока ничего нет.
+admin-web-col-telegram-id = Telegram ID
+admin-web-col-username = Имя пользователя
+admin-web-col-nickname = Никнейм
+admin-web-col-date = Дата
+admin-web-col-reason = Причина
+admin-web-col-ip = IP-адрес
+admin-web-col-file = Файл
+admin-web-col-type = Тип
+admin-web-col-expires = Истекает
+admin-web-col-uses-left = Осталось скачиваний
+admin-web-uses-unlimited = До истечения
+admin-web-col-channel = ID канала
+admin-web-btn-delete-ban = Удалить и забанить
+admin-web-btn-ban = Забанить
+admin-web-btn-delete = Удалить
+admin-web-pending-telegram = Заявки из Telegram
+admin-web-pending-web = Заявки с сайта
+admin-web-online-title = Пользователи онлайн
+admin-web-accounts-title = Учётные записи сервера
+admin-web-error = Ошибка базы данных. Проверьте логи сервера.
+admin-web-tt-unavailable = Сервер TeamTalk сейчас недоступен.
+admin-web-notice-deleted = Регистрация удалена, пользователь забанен.
+admin-web-notice-not-found = Ничего не изменено: запись уже не существует.
+admin-web-notice-banned = Пользователь забанен.
+admin-web-notice-unbanned = Пользователь разбанен.
+admin-web-notice-invalid-id = Неверный Telegram ID.
+admin-web-notice-tt-deleted = Учётная запись TeamTalk удалена.
+admin-web-notice-tt-delete-failed = Не удалось удалить учётную запись TeamTalk. Проверьте логи сервера.
+cancel-done = Регистрация отменена. Отправьте /start, чтобы начать заново.
+cancel-nothing = Нечего отменять.
+myaccount-title = Ваша учётная запись TeamTalk
+myaccount-username = Имя пользователя: { $username }
+myaccount-nickname = Никнейм: { $nickname }
+myaccount-registered-at = Зарегистрирована: { $date }
+myaccount-registered-unknown = Зарегистрирована: неизвестно
+myaccount-not-registered = У вас ещё нет зарегистрированной учётной записи TeamTalk. Отправьте /start, чтобы зарегистрироваться.
+myaccount-password-note = Бот не хранит ваш пароль, поэтому в файле и ссылке ниже он не указан. Введите его в TeamTalk при подключении.
+btn-myaccount-file = Получить .tt файл
+btn-myaccount-link = Получить TT-ссылку
+changepassword-prompt = Отправьте новый пароль для вашей учётной записи TeamTalk. Отправьте /cancel, чтобы оставить текущий.
+changepassword-done = Пароль изменён. Вот обновлённые файлы для подключения.
+changepassword-failed = Сейчас не удалось изменить пароль. Попробуйте позже.
+help-title = Доступные команды:
+help-start = /start - зарегистрировать учётную запись TeamTalk
+help-start-invite-only = /start - регистрация доступна только по пригласительной ссылке от администратора
+help-myaccount = /myaccount - показать учётную запись и снова получить файлы для подключения
+help-changepassword = /changepassword - сменить пароль TeamTalk
+help-cancel = /cancel - прервать текущий шаг
+help-help = /help - показать это сообщение
+help-web-enabled = Зарегистрироваться также можно на сайте.
+help-admin-title = Команды администратора:
+help-adminpanel = /adminpanel - открыть панель администратора
+help-generate = /generate [срок] [использований] - создать пригласительную ссылку (по умолчанию 5m, одно использование)
+help-exit = /exit - остановить бота
+btn-online-users = Пользователи онлайн
+btn-online-kick = Выгнать
+btn-online-message = Сообщение
+admin-online-title = Пользователей онлайн: { $count }
+admin-online-entry = { $nickname } ({ $username }) - канал { $channel_id }, { $user_type }
+admin-online-type-admin = администратор
+admin-online-type-default = пользователь
+admin-online-empty = На сервере никого нет.
+admin-online-error = Не удалось получить список пользователей онлайн с сервера TeamTalk.
+admin-online-kick-fail = Не удалось отключить пользователя { $user_id }: { $error }
+admin-online-message-prompt = Отправьте текст сообщения для пользователя { $user_id }.
+admin-online-message-sent = Сообщение пользователю { $user_id } отправлено.
+admin-online-message-fail = Не удалось отправить сообщение пользователю { $user_id }: { $error }
+help-online = /online - список пользователей TeamTalk онлайн
+btn-online-ban = Забанить
+btn-online-confirm-kick = Да, выгнать
+btn-online-confirm-ban = Да, забанить
+admin-online-kick-prompt = Отключить пользователя { $user_id } от сервера?
+admin-online-ban-prompt = Забанить пользователя { $user_id } на сервере по имени пользователя и IP-адресу? Пользователь также будет отключён.
+admin-online-kick-done = Пользователь { $user_id } отключён от сервера.
+admin-online-ban-done = Пользователь { $user_id } забанен и отключён.
+admin-online-ban-fail = Не удалось забанить пользователя { $user_id }: { $error }
+btn-tt-broadcast = Рассылка в TeamTalk
+broadcast-tt-usage = Использование: /broadcast <текст>
+broadcast-tt-prompt = Отправьте сообщение для рассылки всем пользователям сервера TeamTalk.
+broadcast-tt-sent = Сообщение отправлено всем на сервере TeamTalk.
+broadcast-tt-failed = Не удалось отправить рассылку: { $error }
+help-broadcast = /broadcast <текст> - отправить сообщение всем на сервере TeamTalk
+btn-tg-broadcast = Рассылка в Telegram
+btn-broadcast-send = Отправить
+broadcast-tg-prompt = Отправьте сообщение для всех зарегистрированных пользователей Telegram. Текст, фото и другие типы сообщений копируются как есть.
+broadcast-tg-confirm = Отправить это сообщение { $count } зарегистрированным пользователям?
+broadcast-tg-expired = Нет сообщения для отправки. Составьте его заново из панели администратора.
+broadcast-tg-progress = Отправка: { $done } из { $total }…
+broadcast-tg-done = Рассылка завершена: доставлено { $sent } из { $total }, ошибок { $failed }.
+broadcast-tg-failed-blocked = Заблокировали бота ({ $count }): { $ids }
+broadcast-tg-failed-deactivated = Удалённые аккаунты ({ $count }): { $ids }
+broadcast-tg-failed-not-found = Чат не найден ({ $count }): { $ids }
+broadcast-tg-failed-other = Другие ошибки ({ $count }): { $ids }
+btn-list-search = Поиск
+btn-list-show-all = Показать все
+admin-list-search-prompt = Отправьте начало имени пользователя или Telegram ID для поиска.
+admin-list-filter = Фильтр: { $filter }
+admin-list-no-matches = Ничего не найдено.
+btn-reason-skip = Без причины
+decision-reason = Причина: { $reason }
+reason-prompt-reject = Отправьте причину отклонения заявки или нажмите «Без причины».
+reason-prompt-ban = Отправьте причину удаления и блокировки пользователя { $tg_id } или нажмите «Без причины».
+user-banned-notice = Вы заблокированы администратором.
+admin-user-delete-missing = Пользователь { $tg_id } больше не зарегистрирован.
+btn-invites = Ссылки-приглашения
+btn-invite-revoke = Отозвать
+btn-invite-revoke-all = Отозвать все
+admin-invites-title = Активные ссылки-приглашения:
+admin-invites-empty = Активных ссылок-приглашений нет.
+admin-invite-entry = #{ $id } { $token }… создал { $creator }, истекает { $expires }, осталось использований: { $uses }
+admin-invite-revoked = Ссылка-приглашение #{ $id } отозвана.
+admin-invite-not-found = Ссылка-приглашение #{ $id } больше не существует.
+admin-invites-revoke-all-prompt = Отозвать все неиспользованные ссылки-приглашения?
+admin-invites-revoked-all = Отозвано ссылок-приглашений: { $count }.
+deeplink-generate-usage = Использование: /generate [срок] [использований] [--server <id>], например /generate 24h 10. Срок указывается с s, m, h или d (до 30d); использований от 1 до 1000. Без --server пользователи выбирают сервер сами.
+deeplink-generated = Ссылка-приглашение на { $uses } регистраций, действует до { $expires }:
+    { $link }
+admin-owner-only = Это доступно только владельцам.
+username-too-short = Имя пользователя должно содержать не менее { $min } символов.
+username-too-long = Имя пользователя должно содержать не более { $max } символов.
+username-invalid-chars = Имя пользователя может содержать только буквы, цифры, точки, подчёркивания и дефисы.
+username-reserved = Это имя пользователя зарезервировано. Пожалуйста, выберите другое.
+btn-generate-password = Сгенерировать надёжный пароль
+password-generated = Ваш сгенерированный пароль: { $password }
+    Сохраните его в надёжном месте: бот больше его не покажет.
+password-too-short = Пароль должен содержать не менее { $min } символов.
+password-too-long = Пароль должен содержать не более { $max } символов.
+password-missing-lowercase = Пароль должен содержать строчную букву.
+password-missing-uppercase = Пароль должен содержать заглавную букву.
+password-missing-digit = Пароль должен содержать цифру.
+password-missing-symbol = Пароль должен содержать символ, например ! или @.
+bot-restart = Перезапускаюсь...
+help-restart = /restart - остановить бота, чтобы супервизор запустил его снова
+cmd-start = Зарегистрировать учётную запись TeamTalk
+cmd-myaccount = Учётная запись и файлы для подключения
+cmd-changepassword = Сменить пароль TeamTalk
+cmd-cancel = Прервать текущий шаг
+cmd-help = Доступные команды
+cmd-adminpanel = Открыть панель администратора
+cmd-online = Пользователи TeamTalk онлайн
+cmd-broadcast = Сообщение всем на сервере TeamTalk
+cmd-generate = Создать пригласительную ссылку
+cmd-exit = Остановить бота
+cmd-restart = Перезапустить бота
+help-register = /register <логин> <пароль> [никнейм] [--to <chat_id>] [--profile <имя>] - сразу создать учётную запись TeamTalk
+cmd-register = Сразу создать учётную запись TeamTalk
+admin-register-usage = Использование: /register <логин> <пароль> [никнейм] [--to <chat_id>] [--profile <имя>]
+admin-register-unknown-profile = Неизвестный профиль прав { $profile }.
+admin-register-success = Учётная запись TeamTalk { $username } создана.
+admin-register-notify = Администратор { $admin_id } создал учётную запись TeamTalk { $username } командой /register.
+admin-register-forwarded = Файлы для подключения отправлены в чат { $chat_id }.
+admin-register-forward-failed = Не удалось отправить файлы для подключения в чат { $chat_id }; отправляю их сюда.
+tt-account-removed-user = Ваша учётная запись TeamTalk { $username } удалена на сервере: подключиться с ней больше нельзя, и зарегистрироваться заново через этого бота не получится. Если это ошибка, свяжитесь с администраторами сервера.
+admin-ban-propagate-prompt = У заблокированного пользователя есть учётная запись TeamTalk { $tt_username }. Удалить её и с сервера?
+admin-web-btn-delete-tt = Удалить учётную запись TeamTalk
+btn-search = 🔍 Поиск
+btn-search-again = 🔍 Новый поиск
+btn-back-to-panel = ⬅️ Панель администратора
+admin-search-prompt = Отправьте часть логина, никнейма или Telegram ID для поиска по регистрациям, блокировкам и учётным записям TeamTalk.
+admin-search-title = Результаты поиска «{ $query }»:
+admin-search-registrations = Регистрации ({ $count }):
+admin-search-banned = Заблокированные ({ $count }):
+admin-search-accounts = Учётные записи TeamTalk ({ $count }):
+admin-search-more = …и ещё { $count }, уточните запрос, чтобы увидеть их.
+help-stats = /stats - показать статистику регистраций
+cmd-stats = Статистика регистраций
+help-ttstatus = /ttstatus - показать состояние подключения к TeamTalk
+cmd-ttstatus = Состояние подключения к TeamTalk
+admin-tt-status-unavailable = Обработчик TeamTalk не ответил.
+admin-tt-status-online = авторизован
+admin-tt-status-connecting = подключён, не авторизован
+admin-tt-status-offline = отключён
+admin-tt-status-none = —
+admin-tt-status =
+    🔌 Состояние TeamTalk: { $state }
+    Сервер: { $server }
+    Время работы: { $uptime }
+    Попыток переподключения: { $attempts }
+    Команд в ожидании: { $pending }
+    Последняя ошибка: { $last_error }
+tt-conn-connected = 🔌 Подключение к серверу TeamTalk установлено, выполняется вход.
+tt-conn-logged-in = ✅ Выполнен вход на сервер TeamTalk { $server }.
+tt-conn-lost = ⚠️ Потеряно соединение с сервером TeamTalk. Переподключаюсь.
+tt-conn-reconnecting = 🔄 Переподключение к серверу TeamTalk (попытка { $attempt }, следующая через { $delay } с).
+admin-stats-unavailable = недоступно
+admin-stats =
+    📊 Статистика регистраций (сегодня — с 00:00 UTC)
+    Регистрации через Telegram: { $telegram_total } (сегодня: { $telegram_today }, одобрено администраторами: { $telegram_approved })
+    Регистрации через сайт: { $web_total } (сегодня: { $web_today })
+    Одобрено заявок с сайта: { $approved } (сегодня: { $approved_today })
+    Отклонено заявок с сайта: { $rejected } (сегодня: { $rejected_today })
+    Ожидают решения: { $pending }
+    Активные баны: { $banned }
+    Ссылки-приглашения: выдано { $deeplinks_issued }, израсходовано { $deeplinks_used }
+    Сейчас в TeamTalk: { $online }
+help-audit = /audit [страница] - показать последние действия администраторов и системы
+cmd-audit = Журнал действий
+admin-audit-usage = Использование: /audit [страница]
+admin-audit-empty = Здесь записей в журнале нет.
+admin-audit-title = Журнал действий, страница { $page } (сначала новые):
+admin-web-nav-audit = Журнал действий
+admin-web-col-actor = Кто
+admin-web-col-action = Действие
+admin-web-col-target = Объект
+admin-web-col-details = Подробности
+btn-export = 📤 Экспорт регистраций и банов
+admin-export-ready = Экспорт регистраций и банов (CSV и JSON):
+admin-export-error = Не удалось создать экспорт. Проверьте логи сервера.
+help-backup = /backup - получить сжатую копию базы данных
+cmd-backup = Резервная копия базы данных
+admin-backup-ready = Резервная копия базы данных. Не передавайте её посторонним: в ней все регистрации.
+admin-backup-error = Не удалось создать резервную копию базы данных. Проверьте логи сервера.
+admin-backup-unsupported = Встроенные резервные копии работают только с SQLite. Для PostgreSQL используйте pg_dump.
+
+register-queued = Сервер TeamTalk временно недоступен. Ваша учётная запись { $username } будет создана в ближайшее время; сообщение с её файлами придёт сюда.
+queued-account-taken = Пока ваша регистрация ожидала, имя { $username } заняли на сервере TeamTalk. Пожалуйста, зарегистрируйтесь снова с другим именем.
+queued-account-failed = Не удалось создать вашу учётную запись TeamTalk { $username }. Пожалуйста, попробуйте позже или свяжитесь с администратором.
+web-queued-title = Сервер TeamTalk временно недоступен. Ваша учётная запись будет создана в ближайшее время; входите с указанными именем пользователя и паролем.
+admin-account-queued = TeamTalk недоступен, поэтому { $username } поставлен в очередь. Учётная запись будет создана, когда бот снова войдёт на сервер.
+admin-queued-account-created = Учётная запись { $username } из очереди создана на сервере TeamTalk.
+admin-queued-account-taken = Учётная запись { $username } удалена из очереди: имя уже занято на сервере TeamTalk.
+admin-queued-account-failed = Не удалось создать учётную запись { $username } из очереди, она удалена из очереди. Пожалуйста, проверьте логи.
+reconcile-title = Учётные записи TeamTalk расходятся с базой регистраций:
+reconcile-vanished = Регистрации, чьей учётной записи TeamTalk больше нет ({ $count }): { $names }
+reconcile-banned = Заблокированные пользователи, чья учётная запись TeamTalk всё ещё существует ({ $count }): { $names }
+reconcile-unlinked = Учётные записи TeamTalk без регистрации в Telegram ({ $count }): { $names }
+reconcile-report-only = Ничего не изменено. Установите tt_reconcile_repair = true, чтобы автоматически удалять устаревшие регистрации и учётные записи заблокированных пользователей.
+reconcile-repaired = Исправлено: удалено устаревших регистраций: { $removed }, удалено учётных записей заблокированных пользователей: { $deleted }, ошибок: { $failed }. Учётные записи без регистрации оставлены как есть.
+tt-conn-gave-up = ❌ Переподключение к серверу TeamTalk прекращено после попыток: { $attempts }. Проверьте сервер и перезапустите бота.
+tt-pm-help = Команды: help - этот список; who - кто в сети; version - версия бота.
+tt-pm-who = В сети ({ $count }): { $users }
+tt-pm-version = Бот регистрации TeamTalk { $version }
+tt-pm-unknown = Неизвестная команда. Отправьте "help", чтобы увидеть список команд.
+tt-pm-help-register = register <имя> <пароль> - создать свою учётную запись на этом сервере.
+tt-pm-register-usage = Отправьте: register <имя> <пароль>
+tt-pm-register-created = Учётная запись { $username } создана. Переподключитесь с ней, используя отправленный пароль.
+tt-pm-register-failed = Не удалось создать учётную запись { $username }. Пожалуйста, попробуйте позже.
+tt-welcome = Добро пожаловать на { $server }, { $nickname }! Отправьте мне «help» личным сообщением, чтобы узнать, что я умею.
+deeplink-unknown-server = Неизвестный сервер TeamTalk { $server }.
+server-prompt = На каком сервере вы хотите создать аккаунт?
+admin-request-server = Сервер:
+web-label-server = Сервер:
+web-err-server-invalid = Неизвестный сервер. Пожалуйста, выберите сервер из списка.
+tt-err-not-connected = Бот сейчас не подключён к TeamTalk.
+tt-err-account-exists = Аккаунт с таким именем пользователя уже существует на сервере TeamTalk.
+tt-err-invalid-username = Сервер TeamTalk не принимает это имя пользователя.
+tt-err-not-authorized = У аккаунта бота в TeamTalk недостаточно прав для этого.
+tt-err-banned = Бот заблокирован на сервере TeamTalk.
+tt-err-account-not-found = Такого аккаунта нет на сервере TeamTalk.
+tt-err-failed = Сервер TeamTalk не смог выполнить команду: { $reason }
+admin-register-failed = Не удалось создать аккаунт: { $error }
+admin-request-rights-profile = Запрошенные права:
+admin-request-source = Источник:
+admin-request-source-telegram = Telegram-бот
+admin-request-source-web = веб-форма
+admin-request-source-teamtalk = личное сообщение в TeamTalk
+rights-profile-prompt = Какие права вы хотите запросить? Администраторы увидят ваш выбор при одобрении.
+rights-profile-default = По умолчанию
+web-label-rights-profile = Запрошенные права:
+web-err-rights-profile-invalid = Эти права нельзя запросить. Пожалуйста, выберите из списка.
+btn-admin-verify-admin = Одобрить как администратора
+btn-admin-rename = Изменить имя пользователя
+admin-rename-prompt = Отправьте имя пользователя, которое нужно зарегистрировать вместо { $username }.
+admin-rename-done = По запросу { $old_username } теперь будет зарегистрирован { $username }. Одобрить?
+btn-pending-requests = Ожидающие запросы
+admin-pending-title = Запросы, ожидающие одобрения ({ $count }):
+admin-pending-empty = Нет запросов на регистрацию, ожидающих одобрения.
+admin-pending-entry = { $username } ({ $source }), ждёт { $age }
+pending-reminder-title = ⏰ Запросов на регистрацию, ожидающих одобрения дольше { $age }: { $count }
+pending-reminder-hint = Откройте /adminpanel → «Ожидающие запросы», чтобы принять решение.
+pending-expired = Срок вашего запроса на регистрацию истёк, прежде чем администратор на него ответил. Отправьте /start, чтобы попробовать снова.
+web-err-form-expired = Срок действия формы истёк. Проверьте данные и отправьте её ещё раз.
+web-err-invite-required = Регистрация на этом сайте только по приглашениям. Воспользуйтесь выданной вам ссылкой-приглашением.
+web-err-invite-invalid = Эта ссылка-приглашение недействительна, истекла или уже использована.
+deeplink-generated-web = Для сайта: добавьте ?invite={ $token } к адресу страницы регистрации.
+admin-web-login-title = Вход
+admin-web-login-username = Имя пользователя
+admin-web-login-password = Пароль
+admin-web-login-code = Код из приложения-аутентификатора
+admin-web-login-submit = Войти
+admin-web-login-failed = Неверное имя пользователя, пароль или код.
+admin-web-login-locked = Слишком много неудачных попыток. Попробуйте позже.
+admin-web-logout = Выйти
+web-status-downloads-once = Скачайте файлы сейчас: эти ссылки показываются только один раз.
+admin-request-location = Местоположение:
+admin-web-col-source = Источник
+registration-origin-direct = самостоятельно
+registration-origin-approved = одобрена администратором
+tt-account-removed-banned-unlinked = 🚫 Пользователь '{ $username }' удален из TT (Связь с TG не найдена). Забанено: { $bans }
+web-err-banned = Регистрация с этим именем пользователя или с этого адреса запрещена.
+admin-web-bans-entries = Баны по имени пользователя и IP
+admin-web-ban-kind-username = Имя пользователя TeamTalk
+admin-web-ban-kind-ip = IP-адрес или сеть
+admin-web-col-ban-value = Имя, IP или CIDR
+admin-web-notice-ban-added = Бан добавлен.
+admin-web-notice-ban-removed = Бан снят.
+admin-web-notice-invalid-ban = Неверное имя пользователя или IP-сеть.
+btn-undo-ban = ↩️ Отменить бан
+admin-bans-undone = Баны сняты: { $bans }
+admin-bans-undo-none = Ничего не изменено: эти баны уже сняты.
+btn-notifications = 🔔 Уведомления
+admin-notify-title = Уведомления в ваш личный чат. Нажмите, чтобы включить (🔔) или выключить (🔕).
+admin-notify-shared-chat = Сейчас уведомления отправляются в общий чат администраторов, на него эти настройки не действуют.
+notify-kind-account-created = Учётная запись создана
+notify-kind-account-changed = Учётная запись изменена
+notify-kind-account-removed = Учётная запись удалена и автобаны
+notify-kind-approvals = Заявки на регистрацию из Telegram
+notify-kind-web-registrations = Заявки на регистрацию через веб
+notify-kind-connection = Состояние подключения к TeamTalk
+notify-kind-alert = Ошибки и отчёты
+help-quiethours = /quiethours [ЧЧ:ММ-ЧЧ:ММ|off|default] - копить уведомления в сводку в заданные часы
+cmd-quiethours = Настроить тихие часы уведомлений
+quiet-hours-usage = Использование: /quiethours <ЧЧ:ММ-ЧЧ:ММ|off|default>, например /quiethours 22:00-07:00 (время сервера). Уведомления, кроме ошибок, пришедшие в эти часы, отправляются потом одной сводкой.
+quiet-hours-current = Ваши тихие часы: { $hours }.
+quiet-hours-set = Тихие часы установлены: { $hours }.
+quiet-hours-off = выключены
+admin-notify-digest-title = Уведомления за время тихих часов:
+value-unknown = Неизвестно
+value-not-available = Н/Д
+admin-banlist-entry = TG ID: { $tg_id } - пользователь TT: { $tt_user } (причина: { $reason })
+admin-db-sync-error = Ошибка синхронизации БД: пользователь { $username } (TG ID: { $tg_id }) создан на сервере TeamTalk, но сохранить его в локальную базу не удалось: { $error }
+help-translations = /translations - сравнить сообщения каждого языка с английским
+cmd-translations = Проверить полноту переводов
+translations-report-title = Полнота переводов относительно английского ({ $count } ключей):
+translations-lang-complete = { $lang }: перевод полный.
+translations-lang-missing = В { $lang } не хватает ключей ({ $count }): { $keys }
+translations-lang-extra = В { $lang } есть ключи, которых нет в английском ({ $count }): { $keys }
+translations-unused = Английские ключи, не используемые в коде ({ $count }): { $keys }
+translations-unused-none = Все английские ключи используются в коде.
+translations-unused-skipped = Неиспользуемые ключи не проверены: в рабочем каталоге нет src/ и templates/.
+admin-web-col-download-link = Ссылка на скачивание или токен
+admin-web-notice-link-revoked = Ссылка на скачивание отозвана, файл удалён.
+admin-web-notice-invalid-link = Это не ссылка на скачивание.
+myaccount-server-unknown = Сервер, на котором находится ваша учётная запись, больше недоступен. Обратитесь к администратору.
+admin-ban-propagate-other-server = Учётная запись TeamTalk { $tt_username } находится на сервере { $server }. Бот удаляет учётные записи только на основном сервере, поэтому удалите её там самостоятельно.
+reconcile-server = Сервер: { $server }
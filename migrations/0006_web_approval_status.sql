@@ -0,0 +1,6 @@
+-- Track the admin decision for web registrations that require approval.
+ALTER TABLE pending_web_registrations ADD COLUMN status TEXT NOT NULL DEFAULT 'pending';
+ALTER TABLE pending_web_registrations ADD COLUMN decided_at DATETIME;
+
+CREATE INDEX IF NOT EXISTS ix_pending_web_registrations_status
+    ON pending_web_registrations(status);
@@ -0,0 +1,10 @@
+-- Clients blocked by the web rate limiter, kept so blocks survive restarts.
+CREATE TABLE IF NOT EXISTS web_rate_limit_offenders (
+    subject TEXT NOT NULL PRIMARY KEY,
+    blocked_until DATETIME NOT NULL,
+    hits INTEGER NOT NULL DEFAULT 1,
+    last_seen DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS ix_web_rate_limit_offenders_blocked_until
+    ON web_rate_limit_offenders(blocked_until);
@@ -0,0 +1,3 @@
+-- Store nickname and registration time for /myaccount; older rows stay NULL.
+ALTER TABLE telegram_registrations ADD COLUMN nickname TEXT;
+ALTER TABLE telegram_registrations ADD COLUMN created_at DATETIME;
@@ -0,0 +1,2 @@
+-- Optional admin-provided reason shown to web registrants on rejection.
+ALTER TABLE pending_web_registrations ADD COLUMN decision_reason TEXT;
@@ -0,0 +1,3 @@
+-- Multi-use invite links: each registration consumes one use.
+ALTER TABLE deeplink_tokens ADD COLUMN uses_remaining INTEGER NOT NULL DEFAULT 1;
+UPDATE deeplink_tokens SET uses_remaining = 0 WHERE is_used = 1;
@@ -0,0 +1,2 @@
+-- Remember the language a user chose so later messages use it; older rows stay NULL.
+ALTER TABLE telegram_registrations ADD COLUMN language TEXT;
@@ -0,0 +1,3 @@
+-- Record which admin is deciding a web registration so concurrent decisions are refused.
+ALTER TABLE pending_web_registrations ADD COLUMN claimed_by INTEGER;
+ALTER TABLE pending_web_registrations ADD COLUMN claimed_at DATETIME;
@@ -0,0 +1,12 @@
+-- Who did what: admin decisions, bans, account deletions, invites and web sign-ups.
+CREATE TABLE IF NOT EXISTS audit_log (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    actor TEXT NOT NULL,
+    action TEXT NOT NULL,
+    target TEXT NOT NULL,
+    details TEXT
+);
+
+CREATE INDEX IF NOT EXISTS ix_audit_log_created_at
+    ON audit_log(created_at);
@@ -0,0 +1,4 @@
+-- Multi-download links: each download consumes one use; NULL means the
+-- link only expires by time.
+ALTER TABLE fastapi_download_tokens ADD COLUMN uses_remaining INTEGER;
+UPDATE fastapi_download_tokens SET uses_remaining = CASE WHEN is_used = 1 THEN 0 ELSE 1 END;
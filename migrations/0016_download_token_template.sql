@@ -0,0 +1,2 @@
+-- Client template a ZIP download token was issued for.
+ALTER TABLE fastapi_download_tokens ADD COLUMN client_template TEXT;
@@ -0,0 +1,2 @@
+-- Readme bundled with client ZIPs generated on download.
+ALTER TABLE fastapi_download_tokens ADD COLUMN zip_readme TEXT;
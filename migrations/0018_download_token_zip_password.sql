@@ -0,0 +1,2 @@
+-- Sealed password of client ZIPs generated on download.
+ALTER TABLE fastapi_download_tokens ADD COLUMN zip_password TEXT;
@@ -0,0 +1,15 @@
+-- Registrations accepted while TeamTalk was unreachable, replayed once the
+-- bot is logged in again. The password is sealed like pending registrations.
+CREATE TABLE IF NOT EXISTS queued_registrations (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    username TEXT NOT NULL,
+    password TEXT NOT NULL,
+    nickname TEXT NOT NULL,
+    account_type TEXT NOT NULL,
+    rights_profile TEXT NOT NULL,
+    source TEXT NOT NULL,
+    source_info TEXT,
+    telegram_id INTEGER,
+    language TEXT
+);
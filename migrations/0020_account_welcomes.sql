@@ -0,0 +1,8 @@
+-- Accounts created by the bot that get a welcome private message the first
+-- time they log in to TeamTalk; welcomed_at is set once it was sent.
+CREATE TABLE IF NOT EXISTS account_welcomes (
+    username TEXT NOT NULL PRIMARY KEY,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    language TEXT NOT NULL,
+    welcomed_at DATETIME
+);
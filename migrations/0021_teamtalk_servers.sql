@@ -0,0 +1,43 @@
+-- Registrations belong to one of the configured TeamTalk servers; rows from
+-- before [[teamtalk_servers]] existed belong to the top-level "default" one.
+-- Usernames only have to be unique per server, which needs a table rebuild.
+CREATE TABLE telegram_registrations_new (
+    telegram_id INTEGER PRIMARY KEY,
+    teamtalk_username TEXT NOT NULL,
+    nickname TEXT,
+    created_at DATETIME,
+    language TEXT,
+    server_id TEXT NOT NULL DEFAULT 'default',
+    UNIQUE (server_id, teamtalk_username)
+);
+
+INSERT INTO telegram_registrations_new (telegram_id, teamtalk_username, nickname, created_at, language)
+SELECT telegram_id, teamtalk_username, nickname, created_at, language
+FROM telegram_registrations;
+
+DROP TABLE telegram_registrations;
+ALTER TABLE telegram_registrations_new RENAME TO telegram_registrations;
+
+CREATE INDEX IF NOT EXISTS idx_telegram_registrations_teamtalk_username
+    ON telegram_registrations(teamtalk_username);
+
+ALTER TABLE queued_registrations ADD COLUMN server_id TEXT NOT NULL DEFAULT 'default';
+
+-- Invites may send users to one server instead of letting them pick.
+ALTER TABLE deeplink_tokens ADD COLUMN server_id TEXT;
+
+CREATE TABLE account_welcomes_new (
+    server_id TEXT NOT NULL DEFAULT 'default',
+    username TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    language TEXT NOT NULL,
+    welcomed_at DATETIME,
+    PRIMARY KEY (server_id, username)
+);
+
+INSERT INTO account_welcomes_new (username, created_at, language, welcomed_at)
+SELECT username, created_at, language, welcomed_at
+FROM account_welcomes;
+
+DROP TABLE account_welcomes;
+ALTER TABLE account_welcomes_new RENAME TO account_welcomes;
@@ -0,0 +1,5 @@
+-- Approved registrations are journaled in queued_registrations until their
+-- account exists and is recorded. attempted_at marks rows whose create
+-- command may have reached TeamTalk, so a replay after a crash treats an
+-- existing account as created instead of taken.
+ALTER TABLE queued_registrations ADD COLUMN attempted_at DATETIME;
@@ -0,0 +1,2 @@
+-- How an account came about ('direct' or 'approved'); older rows stay NULL.
+ALTER TABLE telegram_registrations ADD COLUMN source TEXT;
@@ -0,0 +1,20 @@
+-- Keep a row per registration so web_registrations_per_ip can count more
+-- than one; the IP used to be the primary key.
+CREATE TABLE fastapi_registered_ips_new (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    ip_address TEXT NOT NULL,
+    username TEXT,
+    registration_timestamp DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO fastapi_registered_ips_new (ip_address, username, registration_timestamp)
+SELECT ip_address, username, registration_timestamp
+FROM fastapi_registered_ips;
+
+DROP TABLE fastapi_registered_ips;
+ALTER TABLE fastapi_registered_ips_new RENAME TO fastapi_registered_ips;
+
+CREATE INDEX IF NOT EXISTS idx_registered_ips_timestamp
+    ON fastapi_registered_ips(registration_timestamp);
+CREATE INDEX IF NOT EXISTS idx_registered_ips_ip_address
+    ON fastapi_registered_ips(ip_address, registration_timestamp);
@@ -0,0 +1,10 @@
+-- Bans that need no Telegram ID: TeamTalk usernames, and IP addresses or
+-- CIDR networks, checked by web and TeamTalk registrations.
+CREATE TABLE IF NOT EXISTS bans (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    value TEXT NOT NULL,
+    banned_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    reason TEXT,
+    UNIQUE (kind, value)
+);
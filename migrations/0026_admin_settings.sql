@@ -0,0 +1,6 @@
+-- Per-admin preferences. `muted_notifications` lists the notification kinds
+-- the admin turned off, comma-separated.
+CREATE TABLE IF NOT EXISTS admin_settings (
+    admin_id INTEGER NOT NULL PRIMARY KEY,
+    muted_notifications TEXT NOT NULL DEFAULT ''
+);
@@ -0,0 +1,9 @@
+-- Signed download links that were used or revoked, keyed by their signature.
+-- Links without a row are unused; `uses_remaining` is NULL for links that
+-- only expire by time, and `revoked_at` is set when an admin revoked one.
+CREATE TABLE IF NOT EXISTS signed_download_links (
+    token_id TEXT NOT NULL PRIMARY KEY,
+    uses_remaining INTEGER,
+    expires_at DATETIME NOT NULL,
+    revoked_at DATETIME
+);
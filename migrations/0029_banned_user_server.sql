@@ -0,0 +1,3 @@
+-- Server of a banned user's linked account, so deleting it goes to the right
+-- one. Rows from before it was recorded belong to the top-level "default" one.
+ALTER TABLE banned_users ADD COLUMN server_id TEXT;
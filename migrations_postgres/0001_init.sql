@@ -0,0 +1,113 @@
+-- PostgreSQL schema, equivalent to the SQLite migrations up to 0013.
+CREATE TABLE IF NOT EXISTS telegram_registrations (
+    telegram_id BIGINT PRIMARY KEY,
+    teamtalk_username TEXT NOT NULL UNIQUE,
+    nickname TEXT,
+    created_at TIMESTAMP,
+    language TEXT
+);
+
+CREATE TABLE IF NOT EXISTS pending_telegram_registrations (
+    id BIGSERIAL PRIMARY KEY,
+    request_key TEXT NOT NULL UNIQUE,
+    registrant_telegram_id BIGINT NOT NULL,
+    username TEXT NOT NULL,
+    password_cleartext TEXT NOT NULL,
+    nickname TEXT NOT NULL,
+    source_info TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'UTC')
+);
+
+CREATE TABLE IF NOT EXISTS pending_web_registrations (
+    id BIGSERIAL PRIMARY KEY,
+    request_key TEXT NOT NULL UNIQUE,
+    username TEXT NOT NULL,
+    password_cleartext TEXT NOT NULL,
+    nickname TEXT NOT NULL,
+    ip_address TEXT NOT NULL,
+    user_agent TEXT,
+    source_info TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'UTC'),
+    status TEXT NOT NULL DEFAULT 'pending',
+    decided_at TIMESTAMP,
+    decision_reason TEXT,
+    claimed_by BIGINT,
+    claimed_at TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS banned_users (
+    telegram_id BIGINT PRIMARY KEY,
+    teamtalk_username TEXT,
+    banned_at TIMESTAMP NOT NULL,
+    banned_by_admin_id BIGINT,
+    reason TEXT
+);
+
+CREATE TABLE IF NOT EXISTS fastapi_download_tokens (
+    token TEXT PRIMARY KEY,
+    filepath_on_server TEXT NOT NULL,
+    original_filename TEXT NOT NULL,
+    token_type TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    is_used BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE TABLE IF NOT EXISTS fastapi_registered_ips (
+    ip_address TEXT PRIMARY KEY,
+    username TEXT,
+    registration_timestamp TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'UTC')
+);
+
+CREATE TABLE IF NOT EXISTS deeplink_tokens (
+    id BIGSERIAL PRIMARY KEY,
+    token TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'UTC'),
+    expires_at TIMESTAMP NOT NULL,
+    is_used BOOLEAN NOT NULL DEFAULT FALSE,
+    uses_remaining BIGINT NOT NULL DEFAULT 1,
+    generated_by_admin_id BIGINT
+);
+
+CREATE TABLE IF NOT EXISTS web_rate_limit_offenders (
+    subject TEXT PRIMARY KEY,
+    blocked_until TIMESTAMP NOT NULL,
+    hits BIGINT NOT NULL DEFAULT 1,
+    last_seen TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'UTC')
+);
+
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    created_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'UTC'),
+    actor TEXT NOT NULL,
+    action TEXT NOT NULL,
+    target TEXT NOT NULL,
+    details TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_telegram_registrations_teamtalk_username
+    ON telegram_registrations(teamtalk_username);
+CREATE INDEX IF NOT EXISTS idx_banned_users_banned_at
+    ON banned_users(banned_at DESC);
+CREATE INDEX IF NOT EXISTS idx_download_tokens_expires_used
+    ON fastapi_download_tokens(expires_at, is_used);
+CREATE INDEX IF NOT EXISTS idx_registered_ips_timestamp
+    ON fastapi_registered_ips(registration_timestamp);
+CREATE INDEX IF NOT EXISTS idx_deeplink_tokens_expires_used
+    ON deeplink_tokens(expires_at, is_used);
+CREATE INDEX IF NOT EXISTS ix_pending_telegram_registrations_created_at
+    ON pending_telegram_registrations(created_at);
+CREATE INDEX IF NOT EXISTS ix_pending_telegram_registrations_registrant_telegram_id
+    ON pending_telegram_registrations(registrant_telegram_id);
+CREATE INDEX IF NOT EXISTS ix_pending_telegram_registrations_username
+    ON pending_telegram_registrations(username);
+CREATE INDEX IF NOT EXISTS ix_pending_web_registrations_username
+    ON pending_web_registrations(username);
+CREATE INDEX IF NOT EXISTS ix_pending_web_registrations_created_at
+    ON pending_web_registrations(created_at);
+CREATE INDEX IF NOT EXISTS ix_pending_web_registrations_status
+    ON pending_web_registrations(status);
+CREATE INDEX IF NOT EXISTS ix_web_rate_limit_offenders_blocked_until
+    ON web_rate_limit_offenders(blocked_until);
+CREATE INDEX IF NOT EXISTS ix_audit_log_created_at
+    ON audit_log(created_at);
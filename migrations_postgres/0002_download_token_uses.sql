@@ -0,0 +1,4 @@
+-- Multi-download links: each download consumes one use; NULL means the
+-- link only expires by time.
+ALTER TABLE fastapi_download_tokens ADD COLUMN IF NOT EXISTS uses_remaining BIGINT;
+UPDATE fastapi_download_tokens SET uses_remaining = CASE WHEN is_used THEN 0 ELSE 1 END;
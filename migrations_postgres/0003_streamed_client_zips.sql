@@ -0,0 +1,3 @@
+-- Client ZIPs generated on the fly keep their sealed .tt source here instead
+-- of a file in temp_dir.
+ALTER TABLE fastapi_download_tokens ADD COLUMN IF NOT EXISTS zip_source TEXT;
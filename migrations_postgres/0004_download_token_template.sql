@@ -0,0 +1,2 @@
+-- Client template a ZIP download token was issued for.
+ALTER TABLE fastapi_download_tokens ADD COLUMN IF NOT EXISTS client_template TEXT;
@@ -0,0 +1,16 @@
+-- Registrations belong to one of the configured TeamTalk servers; rows from
+-- before [[teamtalk_servers]] existed belong to the top-level "default" one.
+-- Usernames only have to be unique per server.
+ALTER TABLE telegram_registrations ADD COLUMN server_id TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE telegram_registrations DROP CONSTRAINT IF EXISTS telegram_registrations_teamtalk_username_key;
+ALTER TABLE telegram_registrations
+    ADD CONSTRAINT telegram_registrations_server_username_key UNIQUE (server_id, teamtalk_username);
+
+ALTER TABLE queued_registrations ADD COLUMN server_id TEXT NOT NULL DEFAULT 'default';
+
+-- Invites may send users to one server instead of letting them pick.
+ALTER TABLE deeplink_tokens ADD COLUMN server_id TEXT;
+
+ALTER TABLE account_welcomes ADD COLUMN server_id TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE account_welcomes DROP CONSTRAINT account_welcomes_pkey;
+ALTER TABLE account_welcomes ADD PRIMARY KEY (server_id, username);
@@ -0,0 +1,7 @@
+-- Keep a row per registration so web_registrations_per_ip can count more
+-- than one; the IP used to be the primary key.
+ALTER TABLE fastapi_registered_ips DROP CONSTRAINT fastapi_registered_ips_pkey;
+ALTER TABLE fastapi_registered_ips ADD COLUMN id BIGSERIAL PRIMARY KEY;
+
+CREATE INDEX IF NOT EXISTS idx_registered_ips_ip_address
+    ON fastapi_registered_ips(ip_address, registration_timestamp);
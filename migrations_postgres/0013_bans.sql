@@ -0,0 +1,10 @@
+-- Bans that need no Telegram ID: TeamTalk usernames, and IP addresses or
+-- CIDR networks, checked by web and TeamTalk registrations.
+CREATE TABLE IF NOT EXISTS bans (
+    id BIGSERIAL PRIMARY KEY,
+    kind TEXT NOT NULL,
+    value TEXT NOT NULL,
+    banned_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'UTC'),
+    reason TEXT,
+    UNIQUE (kind, value)
+);
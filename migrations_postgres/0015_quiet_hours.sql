@@ -0,0 +1,16 @@
+-- Quiet hours per admin: NULL follows `admin_quiet_hours`, 'off' disables
+-- them, otherwise an `HH:MM-HH:MM` window.
+ALTER TABLE admin_settings ADD COLUMN IF NOT EXISTS quiet_hours TEXT;
+
+-- Notifications held back during an admin's quiet hours, sent as a digest
+-- once they end.
+CREATE TABLE IF NOT EXISTS queued_notifications (
+    id BIGSERIAL PRIMARY KEY,
+    admin_id BIGINT NOT NULL,
+    kind TEXT NOT NULL,
+    text TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'UTC')
+);
+
+CREATE INDEX IF NOT EXISTS idx_queued_notifications_admin_id
+    ON queued_notifications (admin_id);
@@ -1,7 +1,10 @@
+use crate::domain::{PasswordPolicy, QuietHours, UsernamePolicy};
+use crate::files::unknown_tt_placeholders;
 use crate::types::LanguageCode;
 use crate::types::TelegramId;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -18,14 +21,115 @@ pub struct AppConfig {
     pub database: DatabaseConfig,
     #[serde(flatten)]
     pub logging: LoggingConfig,
+    #[serde(default)]
+    pub username_policy: UsernamePolicy,
+    #[serde(default)]
+    pub password_policy: PasswordPolicy,
+    /// Client ZIP variants; a legacy `teamtalk_client_template_dir` becomes
+    /// a single `default` entry on load.
+    #[serde(default)]
+    pub client_templates: Vec<ClientTemplate>,
+    /// Named sets of `TeamTalk` user rights admins pick from when approving;
+    /// `default` falls back to `teamtalk_default_user_rights`.
+    #[serde(default)]
+    pub rights_profiles: BTreeMap<String, Vec<String>>,
+    /// Further servers accounts can be registered on besides the top-level one.
+    #[serde(default)]
+    pub teamtalk_servers: Vec<TeamTalkServer>,
+    /// Languages to try, in order, when a message is missing in the key's
+    /// language; English is always tried last.
+    #[serde(default)]
+    pub language_fallbacks: HashMap<LanguageCode, Vec<LanguageCode>>,
+}
+
+/// Server the top-level connection settings describe.
+pub const DEFAULT_SERVER_ID: &str = "default";
+
+/// Longest server id; it travels in Telegram callback data.
+const MAX_SERVER_ID_LEN: usize = 15;
+
+/// A `[[teamtalk_servers]]` entry: another server with its own bot
+/// connection. Settings left out are taken from the top level.
+#[derive(Clone, Deserialize, Debug)]
+pub struct TeamTalkServer {
+    /// Short identifier stored with registrations and invites.
+    pub id: String,
+    pub host_name: String,
+    #[serde(default, rename = "port")]
+    pub tcp_port: Option<i32>,
+    #[serde(default)]
+    pub udp_port: Option<i32>,
+    #[serde(default)]
+    pub user_name: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub nick_name: Option<String>,
+    #[serde(default)]
+    pub encrypted: Option<bool>,
+    /// Name shown when users pick a server and in generated `.tt` files.
+    #[serde(default)]
+    pub server_name: Option<String>,
+    #[serde(default)]
+    pub tt_public_hostname: Option<String>,
+    #[serde(default)]
+    pub tt_join_channel: Option<String>,
+    #[serde(default)]
+    pub tt_join_channel_password: Option<String>,
+}
+
+/// Rights profile used unless an admin picks another.
+pub const DEFAULT_RIGHTS_PROFILE: &str = "default";
+
+/// Longest rights profile name; it travels in Telegram callback data.
+const MAX_RIGHTS_PROFILE_LEN: usize = 15;
+
+/// A `[[client_templates]]` entry: one client ZIP variant users can pick.
+#[derive(Clone, Deserialize, Debug)]
+pub struct ClientTemplate {
+    /// Short identifier stored with download tokens and used in file names.
+    pub name: String,
+    /// Template directory zipped together with the user's `.tt` file.
+    pub dir: String,
+    /// Text shown on download links and captions; defaults to `name`.
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+impl ClientTemplate {
+    /// Label shown to users.
+    pub fn label(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.name)
+    }
 }
 
 /// Telegram and admin settings.
 #[derive(Clone, Deserialize, Debug)]
 pub struct TelegramConfig {
     pub tg_bot_token: String,
+    /// Every owner and moderator, filled in on load. Entries under the
+    /// legacy `admin_ids` key are treated as owners.
     #[serde(default)]
     pub admin_ids: Vec<TelegramId>,
+    /// Admins with full control: bans, account deletion, invites and `/exit`.
+    #[serde(default)]
+    pub owner_ids: Vec<TelegramId>,
+    /// Admins who may approve or reject registrations and view lists.
+    #[serde(default)]
+    pub moderator_ids: Vec<TelegramId>,
+    /// Group that receives approval requests and admin notifications instead of private chats.
+    #[serde(default)]
+    pub admin_chat_id: Option<i64>,
+    /// Forum topic within `admin_chat_id` to post into.
+    #[serde(default)]
+    pub admin_topic_id: Option<i32>,
+    /// Public HTTPS URL Telegram posts updates to. Long polling is used when unset.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Dedicated port for the webhook listener. When unset the webhook is
+    /// served by the web server.
+    #[serde(default)]
+    pub webhook_port: Option<u16>,
     #[serde(default = "default_lang")]
     pub bot_admin_lang: LanguageCode,
     #[serde(default)]
@@ -34,11 +138,36 @@ pub struct TelegramConfig {
     pub telegram_deeplink_registration_enabled: bool,
     #[serde(default = "default_true")]
     pub telegram_public_registration_enabled: bool,
+    #[serde(default = "default_admin_page_size")]
+    pub admin_page_size: usize,
+    #[serde(default)]
+    pub ban_propagation: BanPropagation,
+    /// Server local time during which admins' private chats only get alerts;
+    /// everything else waits for a digest. Admins can override it with
+    /// `/quiethours`.
+    #[serde(default)]
+    pub admin_quiet_hours: Option<QuietHours>,
+}
+
+/// What happens to a linked `TeamTalk` account when its Telegram user is banned.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BanPropagation {
+    /// The ban only blocks the Telegram side.
+    #[default]
+    Off,
+    /// Ask the admin whether to delete the `TeamTalk` account as well.
+    Ask,
 }
 
 /// `TeamTalk` server settings.
 #[derive(Clone, Deserialize, Debug)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct TeamTalkConfig {
+    /// [`DEFAULT_SERVER_ID`], or the `[[teamtalk_servers]]` entry this
+    /// configuration was derived from.
+    #[serde(skip, default = "default_server_id")]
+    pub server_id: String,
     pub host_name: String,
     #[serde(rename = "port")]
     pub tcp_port: i32,
@@ -59,21 +188,108 @@ pub struct TeamTalkConfig {
     pub tt_join_channel: Option<String>,
     #[serde(default)]
     pub tt_join_channel_password: Option<String>,
+    /// `.tt` file template with `{{placeholder}}`s, relative to the config
+    /// file; the built-in layout is used when unset.
+    #[serde(default)]
+    pub tt_file_template: Option<String>,
+    /// Contents of `tt_file_template`, read at startup.
+    #[serde(skip)]
+    pub tt_file_template_content: Option<String>,
     #[serde(default = "default_status")]
     pub tt_status_text: String,
     #[serde(default = "default_gender")]
     pub tt_gender: String,
     #[serde(default)]
     pub teamtalk_default_user_rights: Vec<String>,
+    /// `rights_profiles` entries users may ask for while registering.
+    #[serde(default)]
+    pub requestable_rights_profiles: Vec<String>,
     #[serde(default = "default_true")]
     pub teamtalk_registration_broadcast_enabled: bool,
+    /// Tell the linked Telegram user when their account is deleted on the server.
+    #[serde(default = "default_true")]
+    pub teamtalk_notify_user_on_removal: bool,
+    /// Ban the linked Telegram user, or the username and its registration
+    /// addresses when there is no link, once an account is deleted on the server.
+    #[serde(default = "default_true")]
+    pub auto_ban_on_tt_delete: bool,
+    /// Seconds to wait before handling a deleted account; one recreated in
+    /// the meantime counts as updated instead.
+    #[serde(default = "default_auto_ban_debounce")]
+    pub auto_ban_debounce_seconds: u64,
+    /// Create a permanent channel named after each new account, with the
+    /// user as its operator, and join it from the `.tt` file.
+    #[serde(default)]
+    pub personal_channels_enabled: bool,
+    /// Channel path personal channels are created under.
+    #[serde(default = "default_personal_channel_parent")]
+    pub personal_channel_parent: String,
+    #[serde(default = "default_tt_command_queue_size")]
+    pub tt_command_queue_size: usize,
+    #[serde(default = "default_tt_command_timeout")]
+    pub tt_command_timeout_seconds: u64,
+    /// Minimum seconds between connection notices to admins; `0` disables them.
+    #[serde(default = "default_tt_connection_notify_interval")]
+    pub tt_connection_notify_interval_seconds: u64,
+    /// Wait before the first reconnect attempt, in milliseconds.
+    #[serde(default = "default_tt_reconnect_initial_delay")]
+    pub tt_reconnect_initial_delay_ms: u64,
+    /// Longest wait between reconnect attempts.
+    #[serde(default = "default_tt_reconnect_max_delay")]
+    pub tt_reconnect_max_delay_seconds: u64,
+    /// Factor the wait grows by after each attempt.
+    #[serde(default = "default_tt_reconnect_multiplier")]
+    pub tt_reconnect_multiplier: f64,
+    /// Attempts before giving up and alerting admins; `0` retries forever.
+    #[serde(default)]
+    pub tt_reconnect_max_attempts: u32,
+    /// Largest fraction each wait is shortened or stretched by at random.
+    #[serde(default)]
+    pub tt_reconnect_jitter: f64,
+    /// Seconds between full reloads of the cached account list used for
+    /// username checks; `0` only loads it after each login.
+    #[serde(default = "default_tt_account_cache_refresh")]
+    pub tt_account_cache_refresh_seconds: u64,
+    /// Keep registrations made while `TeamTalk` is unreachable and create the
+    /// accounts once the bot is logged in again, instead of refusing them.
+    #[serde(default)]
+    pub tt_offline_queue_enabled: bool,
+    /// Seconds between comparisons of the server accounts with the
+    /// registrations table, reported to admins; `0` disables them.
+    #[serde(default)]
+    pub tt_reconcile_interval_seconds: u64,
+    /// Fix what the comparison finds instead of only reporting it.
+    #[serde(default)]
+    pub tt_reconcile_repair: bool,
+    /// Let users on the server register by sending the bot
+    /// `register <username> <password>` as a private message.
+    #[serde(default)]
+    pub tt_pm_registration_enabled: bool,
+    /// Send those registrations to admins for approval first.
+    #[serde(default)]
+    pub tt_pm_verify_registration: bool,
+    /// Greet accounts the bot created with a private message the first time
+    /// they log in.
+    #[serde(default)]
+    pub tt_welcome_enabled: bool,
+    /// Welcome text per language code, replacing the built-in one;
+    /// `{{username}}`, `{{nickname}}` and `{{server}}` are filled in.
+    #[serde(default)]
+    pub tt_welcome_messages: BTreeMap<String, String>,
 }
 
 /// Web server settings.
 #[derive(Clone, Deserialize, Debug)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct WebConfig {
     #[serde(default)]
     pub web_registration_enabled: bool,
+    #[serde(default)]
+    pub web_verify_registration: bool,
+    /// When off, the web form and API only register visitors with an invite
+    /// token (`/register?invite=...`).
+    #[serde(default = "default_true")]
+    pub web_public_registration_enabled: bool,
     #[serde(default = "default_host")]
     pub web_app_host: String,
     #[serde(default = "default_port")]
@@ -84,15 +300,547 @@ pub struct WebConfig {
     pub web_app_ssl_cert_path: Option<String>,
     #[serde(default)]
     pub web_app_ssl_key_path: Option<String>,
+    /// How often the certificate and key files are checked for changes and
+    /// reloaded; 0 only reloads on `SIGHUP`.
+    #[serde(default = "default_ssl_reload")]
+    pub web_app_ssl_reload_seconds: u64,
+    /// Listen on this Unix socket instead of `web_app_host:web_app_port`.
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
+    pub web_app_uds_path: Option<String>,
+    /// Octal permissions of `web_app_uds_path`.
+    #[serde(default = "default_uds_mode")]
+    pub web_app_uds_mode: String,
     #[serde(default)]
     pub root_path: String,
+    /// Directory served under `/static`; relative to the config file.
+    #[serde(default)]
+    pub web_static_dir: Option<PathBuf>,
+    /// Directory with a Tera `register.html` replacing the built-in page;
+    /// relative to the config file.
+    #[serde(default)]
+    pub web_templates_dir: Option<PathBuf>,
     #[serde(default)]
     pub web_app_proxy_headers: bool,
+    /// The one header the proxy sets; the others are ignored.
+    #[serde(default)]
+    pub web_app_proxy_header: ProxyHeader,
     #[serde(default = "default_forwarded_allow_ips")]
     pub web_app_forwarded_allow_ips: String,
     #[serde(default, deserialize_with = "deserialize_optional_lang")]
     pub force_user_lang: Option<LanguageCode>,
     pub teamtalk_client_template_dir: Option<String>,
+    /// Templates up to this many MiB are zipped on the fly; larger ones
+    /// (or 0) are zipped into `temp_dir` first.
+    #[serde(default = "default_client_zip_stream_max_mb")]
+    pub web_client_zip_stream_max_mb: u64,
+    /// Compression of client ZIP entries.
+    #[serde(default)]
+    pub zip_compression: ZipCompression,
+    /// Level for `zip_compression`; the method's default when unset.
+    #[serde(default)]
+    pub zip_compression_level: Option<i64>,
+    /// Password protection of the `.tt` file inside client ZIPs.
+    #[serde(default)]
+    pub zip_password: ZipPassword,
+    /// Registrations allowed from one IP within
+    /// `web_registrations_per_ip_window_seconds`; 0 removes the limit.
+    #[serde(default = "default_registrations_per_ip")]
+    pub web_registrations_per_ip: u32,
+    #[serde(default = "default_registered_ip_ttl")]
+    pub web_registrations_per_ip_window_seconds: u64,
+    #[serde(default = "default_rate_limit_per_minute")]
+    pub web_rate_limit_per_minute: u32,
+    #[serde(default = "default_rate_limit_subnet_per_minute")]
+    pub web_rate_limit_subnet_per_minute: u32,
+    #[serde(default = "default_rate_limit_block_seconds")]
+    pub web_rate_limit_block_seconds: u64,
+    /// Downloads allowed per link; 0 keeps links valid until they expire.
+    #[serde(default = "default_download_max_uses")]
+    pub web_download_max_uses: u32,
+    /// How download links are issued.
+    #[serde(default)]
+    pub web_download_links: DownloadLinkMode,
+    /// HMAC key for signed download links; random per start when unset.
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
+    pub web_download_signing_key: Option<String>,
+    /// Invalid download links one IP may try before it is blocked from
+    /// downloads; 0 turns the block off.
+    #[serde(default = "default_download_max_failures")]
+    pub web_download_max_failures: u32,
+    /// How long an IP stays blocked from downloads, and how long its
+    /// invalid links are counted.
+    #[serde(default = "default_download_block_seconds")]
+    pub web_download_block_seconds: u64,
+    /// Require a CSRF token on the HTML form posts; API-only deployments
+    /// may turn it off.
+    #[serde(default = "default_true")]
+    pub web_csrf_protection: bool,
+    /// Add a hidden field to the registration form and refuse posts that
+    /// fill it in.
+    #[serde(default = "default_true")]
+    pub web_form_honeypot: bool,
+    /// Refuse registration posts sent sooner than this after the form was
+    /// shown; 0 turns the check off.
+    #[serde(default = "default_form_min_fill")]
+    pub web_form_min_fill_seconds: u64,
+    /// Nested `[web.*]` tables.
+    #[serde(default, rename = "web")]
+    pub sections: WebSections,
+}
+
+/// How web download links are backed.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadLinkMode {
+    /// A token row in the database per link.
+    #[default]
+    Database,
+    /// HMAC-signed links verified without touching the database.
+    Signed,
+}
+
+/// Header a reverse proxy reports the client address in.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProxyHeader {
+    /// RFC 7239 `Forwarded`, from its `for=` parameters.
+    Forwarded,
+    /// `X-Forwarded-For`, walked from the nearest hop.
+    #[default]
+    XForwardedFor,
+    /// `X-Real-IP`, a single address.
+    XRealIp,
+}
+
+/// Compression method of client ZIP entries.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ZipCompression {
+    /// No compression, for templates of already-compressed binaries.
+    Store,
+    #[default]
+    Deflate,
+    /// Zstandard; needs a build with the `zstd` feature.
+    Zstd,
+}
+
+/// Password the `.tt` file inside client ZIPs is encrypted with.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ZipPassword {
+    /// ZIPs are not encrypted.
+    #[default]
+    Off,
+    /// The user's `TeamTalk` password.
+    Account,
+    /// A generated passphrase shown next to the download.
+    Random,
+}
+
+impl TeamTalkConfig {
+    fn validate_reconnect(&self) -> Result<()> {
+        if !(1.0..).contains(&self.tt_reconnect_multiplier) {
+            anyhow::bail!("tt_reconnect_multiplier must be at least 1.0");
+        }
+        if !(0.0..=1.0).contains(&self.tt_reconnect_jitter) {
+            anyhow::bail!("tt_reconnect_jitter must be between 0.0 and 1.0");
+        }
+        Ok(())
+    }
+
+    /// Path of `username`'s personal channel, when personal channels are enabled.
+    pub fn personal_channel_path(&self, username: &str) -> Option<String> {
+        self.personal_channels_enabled
+            .then(|| personal_channel_path(&self.personal_channel_parent, username))
+    }
+}
+
+/// Path of `username`'s channel under `parent`; `/` in the username would
+/// nest channels, so it is replaced.
+pub fn personal_channel_path(parent: &str, username: &str) -> String {
+    format!(
+        "{}/{}/",
+        parent.trim_end_matches('/'),
+        username.replace('/', "_")
+    )
+}
+
+impl WebConfig {
+    fn validate_root_path(&self) -> Result<()> {
+        if !self.root_path.is_empty() && !self.root_path.starts_with('/') {
+            anyhow::bail!("root_path must start with '/', e.g. \"/reg\"");
+        }
+        Ok(())
+    }
+
+    /// `root_path` as a prefix for links and redirects: without the trailing
+    /// slash, empty when the app is served at the root.
+    pub fn base_path(&self) -> &str {
+        self.root_path.trim_end_matches('/')
+    }
+
+    /// `Path` attribute of the app's cookies.
+    pub fn cookie_path(&self) -> &str {
+        match self.base_path() {
+            "" => "/",
+            base => base,
+        }
+    }
+
+    fn resolve_theme_dirs(&mut self, config_dir: &Path) {
+        for dir in [&mut self.web_static_dir, &mut self.web_templates_dir] {
+            *dir = dir
+                .take()
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .map(|dir| config_dir.join(dir));
+        }
+    }
+
+    fn validate_uds(&self) -> Result<()> {
+        if self.web_app_uds_path.is_none() {
+            return Ok(());
+        }
+        if !cfg!(unix) {
+            anyhow::bail!("web_app_uds_path is only supported on Unix");
+        }
+        if self.web_app_ssl_enabled {
+            anyhow::bail!(
+                "web_app_uds_path serves plain HTTP; terminate TLS at the proxy and set web_app_ssl_enabled = false"
+            );
+        }
+        if self.uds_mode().is_none() {
+            anyhow::bail!("web_app_uds_mode must be octal permissions such as \"660\"");
+        }
+        Ok(())
+    }
+
+    /// Parsed `web_app_uds_mode`.
+    pub fn uds_mode(&self) -> Option<u32> {
+        u32::from_str_radix(self.web_app_uds_mode.trim(), 8)
+            .ok()
+            .filter(|mode| *mode <= 0o777)
+    }
+
+    fn validate_zip_compression(&self) -> Result<()> {
+        let range = match self.zip_compression {
+            ZipCompression::Store => {
+                if self.zip_compression_level.is_some() {
+                    anyhow::bail!(
+                        "zip_compression_level does not apply to zip_compression = \"store\""
+                    );
+                }
+                return Ok(());
+            }
+            ZipCompression::Deflate => 1..=9,
+            ZipCompression::Zstd => {
+                if !cfg!(feature = "zstd") {
+                    anyhow::bail!("zip_compression = \"zstd\" needs a build with the zstd feature");
+                }
+                -7..=22
+            }
+        };
+        if let Some(level) = self.zip_compression_level
+            && !range.contains(&level)
+        {
+            anyhow::bail!(
+                "zip_compression_level {level} is out of range {}..={} for {:?}",
+                range.start(),
+                range.end(),
+                self.zip_compression
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Optional `[web.*]` tables.
+#[derive(Clone, Deserialize, Debug, Default)]
+pub struct WebSections {
+    #[serde(default)]
+    pub captcha: Option<CaptchaConfig>,
+    #[serde(default)]
+    pub admin: Option<WebAdminConfig>,
+    #[serde(default)]
+    pub hardening: WebHardeningConfig,
+    #[serde(default)]
+    pub acme: Option<WebAcmeConfig>,
+    #[serde(default)]
+    pub cors: Option<WebCorsConfig>,
+    #[serde(default)]
+    pub geoip: Option<WebGeoIpConfig>,
+}
+
+/// `[web.geoip]` country and network lookups of registrant IPs from `MaxMind`
+/// databases, shown to admins reviewing web registrations.
+#[derive(Clone, Deserialize, Debug)]
+pub struct WebGeoIpConfig {
+    /// `GeoIP2`/`GeoLite2` Country or City database; relative paths are
+    /// inside `data_dir`.
+    #[serde(default)]
+    pub country_database: Option<PathBuf>,
+    /// `GeoLite2` ASN database, same rules as `country_database`.
+    #[serde(default)]
+    pub asn_database: Option<PathBuf>,
+    /// ISO country codes whose registrations always wait for admin approval,
+    /// even without `web_verify_registration`.
+    #[serde(default)]
+    pub require_approval_countries: Vec<String>,
+}
+
+impl WebGeoIpConfig {
+    /// Whether registrations from `country` must be approved by an admin.
+    pub fn requires_approval(&self, country: &str) -> bool {
+        self.require_approval_countries
+            .iter()
+            .any(|code| code.eq_ignore_ascii_case(country))
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.country_database.is_none() && self.asn_database.is_none() {
+            anyhow::bail!("[web.geoip] needs country_database or asn_database");
+        }
+        if !self.require_approval_countries.is_empty() && self.country_database.is_none() {
+            anyhow::bail!("[web.geoip] require_approval_countries needs country_database");
+        }
+        if let Some(code) = self
+            .require_approval_countries
+            .iter()
+            .find(|code| code.len() != 2 || !code.chars().all(|c| c.is_ascii_alphabetic()))
+        {
+            anyhow::bail!(
+                "[web.geoip] require_approval_countries entry {code:?} must be a two-letter country code"
+            );
+        }
+        Ok(())
+    }
+}
+
+/// `[web.cors]` cross-origin access to the JSON API for frontends hosted
+/// elsewhere.
+#[derive(Clone, Deserialize, Debug)]
+pub struct WebCorsConfig {
+    /// Origins such as `https://example.com`; `"*"` allows any.
+    pub allowed_origins: Vec<String>,
+    #[serde(default = "default_cors_methods")]
+    pub allowed_methods: Vec<String>,
+    /// Request headers frontends may send besides the CORS-safelisted ones.
+    #[serde(default = "default_cors_headers")]
+    pub allowed_headers: Vec<String>,
+    /// Let browsers send cookies; needs explicit origins.
+    #[serde(default)]
+    pub allow_credentials: bool,
+    /// How long browsers may cache a preflight answer.
+    #[serde(default = "default_cors_max_age")]
+    pub max_age_seconds: u64,
+}
+
+impl WebCorsConfig {
+    /// Whether `allowed_origins` is `"*"`.
+    pub fn allows_any_origin(&self) -> bool {
+        self.allowed_origins.iter().any(|origin| origin == "*")
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.allowed_origins.is_empty() {
+            anyhow::bail!("[web.cors] allowed_origins must list at least one origin");
+        }
+        if self.allows_any_origin() {
+            if self.allowed_origins.len() > 1 {
+                anyhow::bail!("[web.cors] \"*\" cannot be combined with other origins");
+            }
+            if self.allow_credentials {
+                anyhow::bail!("[web.cors] allow_credentials needs explicit allowed_origins");
+            }
+        }
+        for origin in self.allowed_origins.iter().filter(|origin| *origin != "*") {
+            let valid = (origin.starts_with("http://") || origin.starts_with("https://"))
+                && !origin.ends_with('/')
+                && axum::http::HeaderValue::from_str(origin).is_ok();
+            if !valid {
+                anyhow::bail!(
+                    "[web.cors] allowed_origins entry {origin:?} must look like https://example.com"
+                );
+            }
+        }
+        if let Some(method) = self
+            .allowed_methods
+            .iter()
+            .find(|method| method.parse::<axum::http::Method>().is_err())
+        {
+            anyhow::bail!("[web.cors] allowed_methods entry {method:?} is not an HTTP method");
+        }
+        if let Some(name) = self
+            .allowed_headers
+            .iter()
+            .find(|name| name.parse::<axum::http::HeaderName>().is_err())
+        {
+            anyhow::bail!("[web.cors] allowed_headers entry {name:?} is not a header name");
+        }
+        Ok(())
+    }
+}
+
+/// `[web.acme]` automatic certificates, used instead of
+/// `web_app_ssl_cert_path`/`web_app_ssl_key_path`.
+#[derive(Clone, Deserialize, Debug)]
+pub struct WebAcmeConfig {
+    /// Domains the certificate is issued for; each must resolve to this host
+    /// with port 443 reaching `web_app_port` for the TLS-ALPN-01 challenge.
+    pub domains: Vec<String>,
+    /// Address the CA sends expiry notices to.
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
+    pub contact_email: Option<String>,
+    /// Account key and certificate cache; relative paths are inside
+    /// `data_dir`.
+    #[serde(default = "default_acme_cache_dir")]
+    pub cache_dir: PathBuf,
+    /// Use the Let's Encrypt staging directory, for trying the setup out
+    /// without hitting production rate limits.
+    #[serde(default)]
+    pub staging: bool,
+    /// ACME directory of another CA; overrides `staging`.
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
+    pub directory_url: Option<String>,
+}
+
+impl WebAcmeConfig {
+    fn validate(&self, ssl_enabled: bool) -> Result<()> {
+        if !ssl_enabled {
+            anyhow::bail!("[web.acme] needs web_app_ssl_enabled = true");
+        }
+        if self.domains.iter().all(|domain| domain.trim().is_empty()) {
+            anyhow::bail!("[web.acme] domains must list at least one domain");
+        }
+        Ok(())
+    }
+}
+
+/// Default `Content-Security-Policy`: the pages' inline styles and scripts
+/// plus the CAPTCHA providers.
+const DEFAULT_CONTENT_SECURITY_POLICY: &str = "default-src 'self'; \
+    script-src 'self' 'unsafe-inline' https://challenges.cloudflare.com https://js.hcaptcha.com https://*.hcaptcha.com; \
+    style-src 'self' 'unsafe-inline' https://*.hcaptcha.com; \
+    frame-src https://challenges.cloudflare.com https://*.hcaptcha.com; \
+    connect-src 'self' https://*.hcaptcha.com; \
+    img-src 'self' data:; frame-ancestors 'none'; base-uri 'self'; form-action 'self'";
+
+/// `[web.hardening]` response headers and request logging.
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub struct WebHardeningConfig {
+    /// Send `Content-Security-Policy`, `X-Content-Type-Options`,
+    /// `Referrer-Policy` and, over TLS, `Strict-Transport-Security`.
+    pub security_headers: bool,
+    pub content_security_policy: String,
+    pub referrer_policy: String,
+    /// `max-age` of `Strict-Transport-Security`; 0 leaves the header out.
+    pub hsts_max_age_seconds: u64,
+    /// Log every request with its method, route, status and latency under an
+    /// `X-Request-Id`, which is also returned to the client.
+    pub request_tracing: bool,
+}
+
+impl WebHardeningConfig {
+    fn validate(&self) -> Result<()> {
+        for (key, value) in [
+            ("content_security_policy", &self.content_security_policy),
+            ("referrer_policy", &self.referrer_policy),
+        ] {
+            if axum::http::HeaderValue::from_str(value).is_err() {
+                anyhow::bail!("[web.hardening] {key} is not a valid HTTP header value");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for WebHardeningConfig {
+    fn default() -> Self {
+        Self {
+            security_headers: true,
+            content_security_policy: DEFAULT_CONTENT_SECURITY_POLICY.to_string(),
+            referrer_policy: "no-referrer".to_string(),
+            hsts_max_age_seconds: 31_536_000,
+            request_tracing: true,
+        }
+    }
+}
+
+/// CAPTCHA provider used on the web registration form.
+#[derive(Clone, Copy, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptchaProvider {
+    Turnstile,
+    Hcaptcha,
+}
+
+/// `[web.admin]` settings for the admin dashboard.
+#[derive(Clone, Deserialize, Debug)]
+pub struct WebAdminConfig {
+    pub username: String,
+    /// Plain-text password, for setups without `password_hash`.
+    #[serde(default)]
+    pub password: String,
+    /// Argon2 hash in PHC format, as printed by `--hash-password`.
+    #[serde(default)]
+    pub password_hash: String,
+    /// Base32 TOTP secret; when set, signing in also takes the current code.
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
+    pub totp_secret: Option<String>,
+    #[serde(default = "default_admin_session_ttl")]
+    pub session_ttl_seconds: u64,
+    /// Key signing session cookies; random per start when unset, which signs
+    /// everyone out on restart.
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
+    pub session_secret: Option<String>,
+    /// Failed sign-ins from one IP before it is locked out; 0 never locks.
+    #[serde(default = "default_admin_max_failed_logins")]
+    pub max_failed_logins: u32,
+    #[serde(default = "default_admin_lockout")]
+    pub lockout_seconds: u64,
+}
+
+impl WebAdminConfig {
+    fn validate(&self) -> Result<()> {
+        if self.password.is_empty() && self.password_hash.is_empty() {
+            anyhow::bail!("[web.admin] needs password_hash (or password)");
+        }
+        if !self.password_hash.is_empty()
+            && let Err(e) = argon2::PasswordHash::new(&self.password_hash)
+        {
+            anyhow::bail!("[web.admin] password_hash is not a valid PHC string: {e}");
+        }
+        if self.totp_secret.is_some() && self.totp_key().is_none() {
+            anyhow::bail!("[web.admin] totp_secret must be base32 of at least 10 bytes");
+        }
+        Ok(())
+    }
+
+    /// Decoded `totp_secret`.
+    pub fn totp_key(&self) -> Option<Vec<u8>> {
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+        let secret = self.totp_secret.as_ref()?;
+        let mut key = Vec::new();
+        let (mut buffer, mut bits) = (0u32, 0u32);
+        for c in secret.bytes().filter(|c| !matches!(c, b' ' | b'-' | b'=')) {
+            let value = ALPHABET.iter().position(|&a| a == c.to_ascii_uppercase())?;
+            buffer = (buffer << 5) | u32::try_from(value).ok()?;
+            bits += 5;
+            if bits >= 8 {
+                bits -= 8;
+                key.push(u8::try_from((buffer >> bits) & 0xff).ok()?);
+            }
+        }
+        (key.len() >= 10).then_some(key)
+    }
+}
+
+/// `[web.captcha]` settings.
+#[derive(Clone, Deserialize, Debug)]
+pub struct CaptchaConfig {
+    pub provider: CaptchaProvider,
+    pub site_key: String,
+    pub secret_key: String,
 }
 
 /// Database and file generation settings.
@@ -100,14 +848,43 @@ pub struct WebConfig {
 pub struct DatabaseConfig {
     #[serde(default = "default_ttl")]
     pub generated_file_ttl_seconds: u64,
+    /// Directory for the database, key file and backups; relative paths
+    /// (and the empty default) resolve against the config file's directory.
+    #[serde(default)]
+    pub data_dir: PathBuf,
+    /// Directory for generated files; empty means `temp_files` in `data_dir`.
+    #[serde(default)]
+    pub temp_dir: PathBuf,
     #[serde(default = "default_db_name")]
     pub db_name: String,
+    /// `postgres://` or `sqlite://` URL; when set, `db_name` is ignored.
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
+    pub database_url: Option<String>,
     #[serde(default = "default_cleanup")]
     pub db_cleanup_interval_seconds: u64,
     #[serde(default = "default_pending_ttl")]
     pub pending_reg_ttl_seconds: u64,
+    /// Age after which admins are reminded of an undecided request; 0 disables
+    /// reminders.
+    #[serde(default)]
+    pub pending_reminder_after_seconds: u64,
     #[serde(default = "default_registered_ip_ttl")]
     pub registered_ip_ttl_seconds: u64,
+    /// Seconds between scheduled backups; 0 disables them.
+    #[serde(default)]
+    pub db_backup_interval_seconds: u64,
+    /// Directory for scheduled backups, relative to `data_dir`.
+    #[serde(default = "default_backup_dir")]
+    pub db_backup_dir: String,
+    /// Scheduled backups kept; older ones are deleted.
+    #[serde(default = "default_backup_retention")]
+    pub db_backup_retention: usize,
+    /// Base64 32-byte key sealing pending registration passwords; overrides the key file.
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
+    pub pending_password_key: Option<String>,
+    /// Key file in `data_dir` used when `pending_password_key` is unset, created on first start.
+    #[serde(default = "default_pending_password_key_file")]
+    pub pending_password_key_file: String,
 }
 
 /// Logging settings.
@@ -146,9 +923,15 @@ fn default_nickname() -> String {
 fn default_client_name() -> String {
     "PyTalkRegisterBot".to_string()
 }
+fn default_server_id() -> String {
+    DEFAULT_SERVER_ID.to_string()
+}
 fn default_server_name() -> String {
     "TeamTalk Server".to_string()
 }
+fn default_personal_channel_parent() -> String {
+    "/".to_string()
+}
 const fn default_status() -> String {
     String::new()
 }
@@ -173,18 +956,115 @@ const fn default_ttl() -> u64 {
 fn default_db_name() -> String {
     "users.db".to_string()
 }
+fn default_backup_dir() -> String {
+    "backups".to_string()
+}
+fn default_pending_password_key_file() -> String {
+    "pending_password.key".to_string()
+}
+const fn default_backup_retention() -> usize {
+    7
+}
 const fn default_cleanup() -> u64 {
     3600
 }
 const fn default_true() -> bool {
     true
 }
+const fn default_ssl_reload() -> u64 {
+    60
+}
+fn default_uds_mode() -> String {
+    "660".to_string()
+}
+const fn default_admin_session_ttl() -> u64 {
+    12 * 60 * 60
+}
+const fn default_admin_max_failed_logins() -> u32 {
+    5
+}
+const fn default_admin_lockout() -> u64 {
+    15 * 60
+}
+const fn default_form_min_fill() -> u64 {
+    3
+}
+fn default_cors_methods() -> Vec<String> {
+    vec!["GET".to_string(), "POST".to_string()]
+}
+fn default_cors_headers() -> Vec<String> {
+    vec!["content-type".to_string(), "accept-language".to_string()]
+}
+const fn default_cors_max_age() -> u64 {
+    600
+}
+fn default_acme_cache_dir() -> PathBuf {
+    PathBuf::from("acme")
+}
 const fn default_pending_ttl() -> u64 {
     604_800
 }
 const fn default_registered_ip_ttl() -> u64 {
     2_592_000
 }
+const fn default_admin_page_size() -> usize {
+    20
+}
+const fn default_tt_command_queue_size() -> usize {
+    64
+}
+const fn default_tt_command_timeout() -> u64 {
+    30
+}
+const fn default_auto_ban_debounce() -> u64 {
+    2
+}
+const fn default_tt_reconnect_initial_delay() -> u64 {
+    200
+}
+const fn default_tt_reconnect_max_delay() -> u64 {
+    60
+}
+const fn default_tt_reconnect_multiplier() -> f64 {
+    1.6
+}
+const fn default_tt_connection_notify_interval() -> u64 {
+    60
+}
+const fn default_tt_account_cache_refresh() -> u64 {
+    600
+}
+const fn default_rate_limit_per_minute() -> u32 {
+    30
+}
+const fn default_rate_limit_subnet_per_minute() -> u32 {
+    120
+}
+const fn default_rate_limit_block_seconds() -> u64 {
+    600
+}
+
+const fn default_download_max_uses() -> u32 {
+    1
+}
+
+const fn default_registrations_per_ip() -> u32 {
+    1
+}
+
+const fn default_download_max_failures() -> u32 {
+    10
+}
+
+const fn default_download_block_seconds() -> u64 {
+    900
+}
+
+const fn default_client_zip_stream_max_mb() -> u64 {
+    50
+}
+
+const MAX_ADMIN_PAGE_SIZE: usize = 30;
 
 impl AppConfig {
     /// Load configuration from a TOML file.
@@ -192,15 +1072,274 @@ impl AppConfig {
         let content = fs::read_to_string(path)?;
         let mut config: Self = toml::from_str(&content)?;
 
+        let config_dir = path.parent().unwrap_or_else(|| Path::new("."));
+        config.web.resolve_theme_dirs(config_dir);
+        let database = &mut config.database;
+        database.data_dir = config_dir.join(&database.data_dir);
+        database.temp_dir = if database.temp_dir.as_os_str().is_empty() {
+            database.data_dir.join("temp_files")
+        } else {
+            config_dir.join(&database.temp_dir)
+        };
+
+        if config.client_templates.is_empty()
+            && let Some(dir) = config
+                .web
+                .teamtalk_client_template_dir
+                .clone()
+                .filter(|dir| !dir.trim().is_empty())
+        {
+            config.client_templates.push(ClientTemplate {
+                name: "default".to_string(),
+                dir,
+                label: None,
+            });
+        }
+        for (i, template) in config.client_templates.iter().enumerate() {
+            let valid_name = !template.name.is_empty()
+                && template
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid_name {
+                anyhow::bail!(
+                    "client_templates name {:?} must be non-empty and use only letters, digits, '-' and '_'",
+                    template.name
+                );
+            }
+            if config.client_templates[..i]
+                .iter()
+                .any(|other| other.name == template.name)
+            {
+                anyhow::bail!("Duplicate client_templates name {:?}", template.name);
+            }
+        }
+
+        if let Some(template) = config
+            .teamtalk
+            .tt_file_template
+            .as_deref()
+            .filter(|path| !path.trim().is_empty())
+        {
+            let template_path = config_dir.join(template);
+            let content = fs::read_to_string(&template_path).with_context(|| {
+                format!(
+                    "Failed to read tt_file_template {}",
+                    template_path.display()
+                )
+            })?;
+            let unknown = unknown_tt_placeholders(&content);
+            if !unknown.is_empty() {
+                anyhow::bail!(
+                    "tt_file_template uses unknown placeholders: {}",
+                    unknown.join(", ")
+                );
+            }
+            config.teamtalk.tt_file_template_content = Some(content);
+        }
+
+        config.web.validate_zip_compression()?;
+        config.web.validate_uds()?;
+        config.web.validate_root_path()?;
+        config.validate_web_sections()?;
+        config.teamtalk.validate_reconnect()?;
+        config.resolve_rights_profiles()?;
+        config.validate_servers()?;
+
         if config.teamtalk.udp_port.is_none() {
             config.teamtalk.udp_port = Some(config.teamtalk.tcp_port);
         }
+        // Registrations the cleanup deletes no longer count towards the per-IP
+        // limit, so keep them at least as long as its window.
+        config.database.registered_ip_ttl_seconds = config
+            .database
+            .registered_ip_ttl_seconds
+            .max(config.web.web_registrations_per_ip_window_seconds);
+        // Telegram keyboards take at most 100 buttons; list rows carry up to three.
+        config.telegram.admin_page_size = config
+            .telegram
+            .admin_page_size
+            .clamp(1, MAX_ADMIN_PAGE_SIZE);
+        let telegram = &mut config.telegram;
+        for id in std::mem::take(&mut telegram.admin_ids) {
+            if !telegram.owner_ids.contains(&id) {
+                telegram.owner_ids.push(id);
+            }
+        }
+        telegram.admin_ids.clone_from(&telegram.owner_ids);
+        for &id in &telegram.moderator_ids {
+            if !telegram.admin_ids.contains(&id) {
+                telegram.admin_ids.push(id);
+            }
+        }
         Ok(config)
     }
 
-    /// Resolve the database path relative to the config file.
-    pub fn get_db_path(&self, config_path: &Path) -> PathBuf {
-        let parent = config_path.parent().unwrap_or_else(|| Path::new("."));
-        parent.join(&self.database.db_name)
+    fn validate_web_sections(&mut self) -> Result<()> {
+        let sections = &mut self.web.sections;
+        sections.hardening.validate()?;
+        if let Some(acme) = &mut sections.acme {
+            acme.validate(self.web.web_app_ssl_enabled)?;
+            acme.cache_dir = self.database.data_dir.join(&acme.cache_dir);
+        }
+        if let Some(admin) = &sections.admin {
+            admin.validate()?;
+        }
+        if let Some(cors) = &sections.cors {
+            cors.validate()?;
+        }
+        if let Some(geoip) = &mut sections.geoip {
+            geoip.validate()?;
+            for path in [&mut geoip.country_database, &mut geoip.asn_database] {
+                *path = path.take().map(|path| self.database.data_dir.join(path));
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_rights_profiles(&mut self) -> Result<()> {
+        if !self.rights_profiles.contains_key(DEFAULT_RIGHTS_PROFILE) {
+            self.rights_profiles.insert(
+                DEFAULT_RIGHTS_PROFILE.to_string(),
+                self.teamtalk.teamtalk_default_user_rights.clone(),
+            );
+        }
+        for name in self.rights_profiles.keys() {
+            let valid_name = !name.is_empty()
+                && name.len() <= MAX_RIGHTS_PROFILE_LEN
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid_name {
+                anyhow::bail!(
+                    "rights_profiles name {name:?} must be 1-{MAX_RIGHTS_PROFILE_LEN} letters, digits, '-' or '_'"
+                );
+            }
+        }
+        for name in &self.teamtalk.requestable_rights_profiles {
+            if !self.rights_profiles.contains_key(name) {
+                anyhow::bail!("requestable_rights_profiles: unknown rights profile {name:?}");
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_servers(&self) -> Result<()> {
+        for (i, server) in self.teamtalk_servers.iter().enumerate() {
+            let valid_id = !server.id.is_empty()
+                && server.id.len() <= MAX_SERVER_ID_LEN
+                && server
+                    .id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid_id {
+                anyhow::bail!(
+                    "teamtalk_servers id {:?} must be 1-{MAX_SERVER_ID_LEN} letters, digits, '-' or '_'",
+                    server.id
+                );
+            }
+            if server.id == DEFAULT_SERVER_ID
+                || self.teamtalk_servers[..i]
+                    .iter()
+                    .any(|other| other.id == server.id)
+            {
+                anyhow::bail!("Duplicate teamtalk_servers id {:?}", server.id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `id` names the top-level server or a `[[teamtalk_servers]]` entry.
+    pub fn is_server(&self, id: &str) -> bool {
+        id == DEFAULT_SERVER_ID || self.teamtalk_servers.iter().any(|server| server.id == id)
+    }
+
+    /// This configuration with the connection settings of `server`; every
+    /// other setting is shared.
+    pub fn for_server(&self, server: &TeamTalkServer) -> Self {
+        let mut config = self.clone();
+        let tt = &mut config.teamtalk;
+        tt.server_id.clone_from(&server.id);
+        tt.host_name.clone_from(&server.host_name);
+        if let Some(port) = server.tcp_port {
+            tt.tcp_port = port;
+            tt.udp_port = Some(port);
+        }
+        if server.udp_port.is_some() {
+            tt.udp_port = server.udp_port;
+        }
+        let overrides = [
+            (&mut tt.user_name, &server.user_name),
+            (&mut tt.password, &server.password),
+            (&mut tt.nick_name, &server.nick_name),
+            (&mut tt.server_name, &server.server_name),
+        ];
+        for (value, server_value) in overrides {
+            if let Some(server_value) = server_value {
+                value.clone_from(server_value);
+            }
+        }
+        if let Some(encrypted) = server.encrypted {
+            tt.encrypted = encrypted;
+        }
+        if server.tt_public_hostname.is_some() {
+            tt.tt_public_hostname.clone_from(&server.tt_public_hostname);
+        }
+        if server.tt_join_channel.is_some() {
+            tt.tt_join_channel.clone_from(&server.tt_join_channel);
+            tt.tt_join_channel_password
+                .clone_from(&server.tt_join_channel_password);
+        }
+        config
+    }
+
+    /// Name of this configuration's server when there are several, so admins
+    /// can tell which one a request is for.
+    pub fn server_label(&self) -> Option<&str> {
+        (!self.teamtalk_servers.is_empty()).then_some(self.teamtalk.server_name.as_str())
+    }
+
+    /// Rights profiles admins can pick besides `default`.
+    pub fn extra_rights_profiles(&self) -> Vec<&str> {
+        self.rights_profiles
+            .keys()
+            .map(String::as_str)
+            .filter(|name| *name != DEFAULT_RIGHTS_PROFILE)
+            .collect()
+    }
+
+    /// Whether users may ask for `name` while registering.
+    pub fn is_requestable_profile(&self, name: &str) -> bool {
+        self.teamtalk
+            .requestable_rights_profiles
+            .iter()
+            .any(|profile| profile == name)
+    }
+
+    /// Whether the Telegram user is an owner rather than a moderator.
+    pub fn is_owner(&self, id: TelegramId) -> bool {
+        self.telegram.owner_ids.contains(&id)
+    }
+
+    /// Resolve the database path inside the data directory.
+    pub fn get_db_path(&self) -> PathBuf {
+        self.database.data_dir.join(&self.database.db_name)
+    }
+
+    /// Resolve the pending password key file inside the data directory.
+    pub fn get_pending_password_key_file(&self) -> PathBuf {
+        self.database
+            .data_dir
+            .join(&self.database.pending_password_key_file)
+    }
+
+    /// Resolve the scheduled backup directory inside the data directory.
+    pub fn get_backup_dir(&self) -> PathBuf {
+        self.database.data_dir.join(&self.database.db_backup_dir)
+    }
+
+    /// Directory for generated connection files, ZIPs and exports.
+    pub fn get_temp_dir(&self) -> &Path {
+        &self.database.temp_dir
     }
 }
@@ -1,158 +1,261 @@
-use crate::types::TelegramId;
+use crate::types::{
+    DownloadTokenType, RegistrationOrigin, RegistrationSource, SourceInfo, SourceKind, TelegramId,
+    WebRequestStatus,
+};
 use anyhow::Result;
-use chrono::Utc;
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
-use sqlx::{Pool, Row, Sqlite};
-use std::collections::HashSet;
+use chrono::NaiveDateTime;
 use std::path::Path;
-use std::str::FromStr;
-use std::time::Duration;
-use tracing::{error, info, instrument, trace};
+use tracing::{info, warn};
 
+mod postgres;
 /// Database schema row types.
 pub mod schema;
+mod sealing;
+mod sqlite;
+
+pub use sealing::PasswordSealer;
+
+use postgres::PostgresDb;
 use schema::{
-    BannedUser, DeeplinkToken, FastapiDownloadToken, PendingTelegramRegistration,
-    TelegramRegistration,
+    AdminSettings, AuditEntry, Ban, BannedUser, DeeplinkToken, FastapiDownloadToken,
+    PendingTelegramRegistration, PendingWebRegistration, QueuedNotification, QueuedRegistration,
+    RateLimitOffender, RegistrationStats, TelegramRegistration,
 };
+use sqlite::SqliteDb;
+
+/// How long an admin's claim on a web registration blocks other admins, so a
+/// decision interrupted by a crash can be retried.
+pub const WEB_CLAIM_TTL_SECONDS: i64 = 600;
+
+/// Fields for a new web registration awaiting admin approval.
+pub struct NewPendingWebRegistration<'a> {
+    pub key: &'a str,
+    pub username: &'a str,
+    pub password: &'a str,
+    pub nickname: &'a str,
+    pub ip_address: &'a str,
+    pub user_agent: Option<&'a str>,
+    pub source_info: &'a SourceInfo,
+}
+
+/// Fields for a registration queued while `TeamTalk` is unreachable.
+pub struct NewQueuedRegistration<'a> {
+    pub username: &'a str,
+    pub password: &'a str,
+    pub nickname: &'a str,
+    pub account_type: &'a str,
+    pub rights_profile: &'a str,
+    pub source: &'a str,
+    pub source_info: Option<&'a SourceInfo>,
+    pub telegram_id: Option<TelegramId>,
+    pub language: Option<&'a str>,
+    pub server_id: &'a str,
+}
+
+/// Fields linking a Telegram user to the account created for them.
+pub struct NewRegistration<'a> {
+    pub telegram_id: TelegramId,
+    pub username: &'a str,
+    pub nickname: &'a str,
+    pub language: Option<&'a str>,
+    pub server_id: &'a str,
+    pub source: RegistrationOrigin,
+}
+
+/// Fields for a new download token.
+pub struct NewDownloadToken<'a> {
+    pub token: &'a str,
+    pub filepath: &'a str,
+    pub original_name: &'a str,
+    pub token_type: DownloadTokenType,
+    pub expires_at: NaiveDateTime,
+    /// Downloads allowed; `None` keeps the link valid until it expires.
+    pub max_uses: Option<u32>,
+    /// `.tt` content to build a client ZIP from on download instead of a file.
+    pub zip_source: Option<&'a str>,
+    /// Readme bundled with a ZIP generated on download.
+    pub zip_readme: Option<&'a str>,
+    /// Password a ZIP generated on download is encrypted with.
+    pub zip_password: Option<&'a str>,
+    /// Client template a ZIP token was issued for.
+    pub client_template: Option<&'a str>,
+}
 
 /// Database access layer.
+///
+/// Passwords of pending registrations are sealed with [`PasswordSealer`] on
+/// the way in and opened on the way out.
 #[derive(Clone)]
 pub struct Database {
-    pub pool: Pool<Sqlite>,
+    backend: Backend,
+    sealer: PasswordSealer,
 }
 
-static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations");
+#[derive(Clone)]
+enum Backend {
+    Sqlite(SqliteDb),
+    Postgres(PostgresDb),
+}
 
 impl Database {
-    /// `new` database operation.
-    pub async fn new(db_filename: &str) -> Result<Self> {
-        let db_url = format!("sqlite://{db_filename}");
+    /// Open (creating if needed) and migrate an `SQLite` database file.
+    pub async fn new(db_filename: &str, sealer: PasswordSealer) -> Result<Self> {
+        let backend = Backend::Sqlite(SqliteDb::new(db_filename).await?);
+        Self::with_backend(backend, sealer).await
+    }
 
-        if !Path::new(db_filename).exists() {
-            if let Some(parent) = Path::new(db_filename).parent() {
-                std::fs::create_dir_all(parent)?;
-            }
-            std::fs::File::create(db_filename)?;
+    /// Connect to a `database_url`: `postgres://` or `postgresql://` for
+    /// `PostgreSQL`, `sqlite://<path>` for a local file.
+    pub async fn connect(url: &str, sealer: PasswordSealer) -> Result<Self> {
+        if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            let backend = Backend::Postgres(PostgresDb::new(url).await?);
+            return Self::with_backend(backend, sealer).await;
         }
+        let Some(path) = url.strip_prefix("sqlite://") else {
+            anyhow::bail!("Unsupported database_url scheme; expected postgres:// or sqlite://");
+        };
+        Self::new(path, sealer).await
+    }
 
-        let connect_options = SqliteConnectOptions::from_str(&db_url)?
-            .create_if_missing(true)
-            .busy_timeout(Duration::from_secs(5));
-
-        let pool = SqlitePoolOptions::new()
-            .max_connections(5)
-            .after_connect(|conn, _meta| {
-                Box::pin(async move {
-                    sqlx::query("PRAGMA journal_mode = WAL;")
-                        .execute(&mut *conn)
-                        .await?;
-                    sqlx::query("PRAGMA synchronous = NORMAL;")
-                        .execute(&mut *conn)
-                        .await?;
-                    sqlx::query("PRAGMA foreign_keys = ON;")
-                        .execute(&mut *conn)
-                        .await?;
-                    sqlx::query("PRAGMA temp_store = MEMORY;")
-                        .execute(&mut *conn)
-                        .await?;
-                    Ok(())
-                })
-            })
-            .connect_with(connect_options)
-            .await?;
-
-        MIGRATOR.run(&pool).await?;
-        integrity_check(&pool).await?;
-        validate_schema(&pool).await?;
-        let db = Self { pool };
-        Ok(db)
+    async fn with_backend(backend: Backend, sealer: PasswordSealer) -> Result<Self> {
+        let migrated = match &backend {
+            Backend::Sqlite(db) => db.seal_legacy_passwords(&sealer).await?,
+            Backend::Postgres(db) => db.seal_legacy_passwords(&sealer).await?,
+        };
+        if migrated > 0 {
+            info!(
+                count = migrated,
+                "Encrypted cleartext passwords of pending registrations"
+            );
+        }
+        let converted = match &backend {
+            Backend::Sqlite(db) => db.convert_legacy_source_info().await?,
+            Backend::Postgres(db) => db.convert_legacy_source_info().await?,
+        };
+        if converted > 0 {
+            info!(
+                count = converted,
+                "Converted registration source info to JSON"
+            );
+        }
+        Ok(Self { backend, sealer })
     }
 
-    /// `is_telegram_registered` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn is_telegram_registered(&self, tg_id: TelegramId) -> Result<bool> {
-        let count: i64 = sqlx::query_scalar!(
-            "SELECT count(*) FROM telegram_registrations WHERE telegram_id = ?",
-            tg_id
-        )
-        .fetch_one(&self.pool)
-        .await?;
-        Ok(count > 0)
+    /// Whether [`Database::backup`] can snapshot this backend.
+    pub const fn supports_backup(&self) -> bool {
+        matches!(self.backend, Backend::Sqlite(_))
     }
 
-    /// `add_registration` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn add_registration(&self, tg_id: TelegramId, tt_username: &str) -> Result<()> {
-        trace!(tg_id = %tg_id, tt_username, "Adding registration");
-        sqlx::query!(
-            "INSERT OR REPLACE INTO telegram_registrations (telegram_id, teamtalk_username) VALUES (?, ?)",
-            tg_id,
-            tt_username
-        )
-        .execute(&self.pool)
-        .await?;
-        Ok(())
+    /// Write a consistent copy of the database to `path`.
+    ///
+    /// Only `SQLite` supports this; use `pg_dump` for `PostgreSQL`. The target
+    /// file must not exist yet.
+    pub async fn backup(&self, path: &Path) -> Result<()> {
+        match &self.backend {
+            Backend::Sqlite(db) => db.backup(path).await,
+            Backend::Postgres(_) => {
+                anyhow::bail!("Built-in backups only support SQLite; use pg_dump for PostgreSQL")
+            }
+        }
     }
 
-    /// `delete_registration` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn delete_registration(&self, tg_id: TelegramId) -> Result<bool> {
-        let res = sqlx::query!(
-            "DELETE FROM telegram_registrations WHERE telegram_id = ?",
-            tg_id
-        )
-        .execute(&self.pool)
-        .await?;
-        Ok(res.rows_affected() > 0)
+    /// `close` database operation.
+    pub async fn close(&self) {
+        match &self.backend {
+            Backend::Sqlite(db) => db.close().await,
+            Backend::Postgres(db) => db.close().await,
+        }
     }
+}
 
-    /// `get_all_registrations` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn get_all_registrations(&self) -> Result<Vec<TelegramRegistration>> {
-        let users = sqlx::query_as!(
-            TelegramRegistration,
-            "SELECT telegram_id as \"telegram_id!: TelegramId\", teamtalk_username as \"teamtalk_username!: String\" FROM telegram_registrations"
-        )
-        .fetch_all(&self.pool)
-        .await?;
-        Ok(users)
+#[cfg(test)]
+impl Database {
+    /// A migrated `SQLite` database in a new directory under the system temp
+    /// directory, which is returned for the test to put files in and remove.
+    pub async fn open_temp() -> Result<(Self, std::path::PathBuf)> {
+        use base64::Engine;
+        let dir = std::env::temp_dir().join(format!("ttreg-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir)?;
+        let key = base64::engine::general_purpose::STANDARD.encode([7u8; 32]);
+        let path = dir.join("test.db");
+        let db = Self::new(&path.to_string_lossy(), PasswordSealer::from_base64(&key)?).await?;
+        Ok((db, dir))
     }
 
-    /// `get_registration_by_id` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn get_registration_by_id(
+    /// Store a `.tt` download token for `file` allowing `max_uses` until `expires_at`.
+    pub async fn add_test_download_token(
         &self,
-        tg_id: TelegramId,
-    ) -> Result<Option<TelegramRegistration>> {
-        let user = sqlx::query_as!(
-            TelegramRegistration,
-            "SELECT telegram_id as \"telegram_id!: TelegramId\", teamtalk_username as \"teamtalk_username!: String\" FROM telegram_registrations WHERE telegram_id = ?",
-            tg_id
-        )
-        .fetch_optional(&self.pool)
-        .await?;
-        Ok(user)
+        token: &str,
+        file: &str,
+        max_uses: Option<u32>,
+        expires_at: NaiveDateTime,
+    ) -> Result<()> {
+        self.add_download_token(&NewDownloadToken {
+            token,
+            filepath: file,
+            original_name: file,
+            token_type: DownloadTokenType::TtConfig,
+            expires_at,
+            max_uses,
+            zip_source: None,
+            zip_readme: None,
+            zip_password: None,
+            client_template: None,
+        })
+        .await
     }
+}
 
-    /// `get_registration_by_tt_username` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn get_registration_by_tt_username(
-        &self,
-        tt_username: &str,
-    ) -> Result<Option<TelegramRegistration>> {
-        let user = sqlx::query_as!(
-            TelegramRegistration,
-            "SELECT telegram_id as \"telegram_id!: TelegramId\", teamtalk_username as \"teamtalk_username!: String\" FROM telegram_registrations WHERE teamtalk_username = ?",
-            tt_username
-        )
-        .fetch_optional(&self.pool)
-        .await?;
-        Ok(user)
+/// JSON for a pending Telegram request's old-format `source_info`.
+fn legacy_telegram_source(stored: &str, registrant: TelegramId) -> String {
+    let mut info = SourceInfo::from_legacy(SourceKind::Telegram, stored);
+    info.telegram_id = Some(registrant);
+    info.to_json()
+}
+
+/// JSON for a pending web request's old-format `source_info`.
+fn legacy_web_source(stored: &str, ip_address: &str, user_agent: Option<&str>) -> String {
+    let mut info = SourceInfo::from_legacy(SourceKind::Web, stored);
+    info.ip = ip_address.parse().ok();
+    info.user_agent = user_agent.map(str::to_string);
+    info.to_json()
+}
+
+/// JSON for a queued registration's old-format `source_info`, given its
+/// `source` column.
+fn legacy_queued_source(stored: &str, source: &str) -> String {
+    let (kind, telegram_id, ip) = match RegistrationSource::try_from(source) {
+        Ok(RegistrationSource::Telegram(id)) => (SourceKind::Telegram, Some(id), None),
+        Ok(RegistrationSource::TeamTalk(ip)) => (SourceKind::TeamTalk, None, Some(ip)),
+        Ok(RegistrationSource::Web(ip)) => (SourceKind::Web, None, Some(ip)),
+        Err(()) => (SourceKind::Web, None, None),
+    };
+    let mut info = SourceInfo::from_legacy(kind, stored);
+    info.telegram_id = info.telegram_id.or(telegram_id);
+    info.ip = info.ip.or(ip);
+    info.to_json()
+}
+
+/// Pending registrations keep their password sealed at rest.
+impl Database {
+    fn open_password(&self, stored: &str) -> String {
+        self.sealer.open(stored).unwrap_or_else(|e| {
+            warn!(error = %e, "Failed to decrypt a pending registration password");
+            String::new()
+        })
+    }
+
+    fn open_telegram(&self, mut reg: PendingTelegramRegistration) -> PendingTelegramRegistration {
+        reg.password_cleartext = self.open_password(&reg.password_cleartext);
+        reg
+    }
+
+    fn open_web(&self, mut reg: PendingWebRegistration) -> PendingWebRegistration {
+        reg.password_cleartext = self.open_password(&reg.password_cleartext);
+        reg
     }
 
     /// `add_pending_registration` database operation.
-    #[instrument(skip(self), err)]
     pub async fn add_pending_registration(
         &self,
         key: &str,
@@ -160,372 +263,472 @@ impl Database {
         username: &str,
         password: &str,
         nickname: &str,
-        source_info: &str,
+        source_info: &SourceInfo,
     ) -> Result<()> {
-        sqlx::query!(
-            "INSERT INTO pending_telegram_registrations (request_key, registrant_telegram_id, username, password_cleartext, nickname, source_info) VALUES (?, ?, ?, ?, ?, ?)",
-            key,
-            tg_id,
-            username,
-            password,
-            nickname,
-            source_info
-        )
-        .execute(&self.pool)
-        .await?;
-        Ok(())
+        let sealed = self.sealer.seal(password)?;
+        match &self.backend {
+            Backend::Sqlite(db) => {
+                db.add_pending_registration(key, tg_id, username, &sealed, nickname, source_info)
+                    .await
+            }
+            Backend::Postgres(db) => {
+                db.add_pending_registration(key, tg_id, username, &sealed, nickname, source_info)
+                    .await
+            }
+        }
     }
 
     /// `get_pending_registration` database operation.
-    #[instrument(skip(self), err)]
     pub async fn get_pending_registration(
         &self,
         key: &str,
     ) -> Result<Option<PendingTelegramRegistration>> {
-        let reg = sqlx::query_as!(
-            PendingTelegramRegistration,
-            "SELECT id as \"id?: i64\", request_key as \"request_key!: String\", registrant_telegram_id as \"registrant_telegram_id!: TelegramId\", username as \"username!: String\", password_cleartext as \"password_cleartext!: String\", nickname as \"nickname!: String\", source_info as \"source_info!: String\", created_at as \"created_at!: chrono::NaiveDateTime\" FROM pending_telegram_registrations WHERE request_key = ?",
-            key
-        )
-        .fetch_optional(&self.pool)
-        .await?;
-        Ok(reg)
-    }
-
-    /// `delete_pending_registration` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn delete_pending_registration(&self, key: &str) -> Result<()> {
-        sqlx::query!(
-            "DELETE FROM pending_telegram_registrations WHERE request_key = ?",
-            key
-        )
-        .execute(&self.pool)
-        .await?;
-        Ok(())
-    }
-
-    /// `get_banned_user` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn get_banned_user(&self, tg_id: TelegramId) -> Result<Option<BannedUser>> {
-        let user = sqlx::query_as!(
-            BannedUser,
-            "SELECT telegram_id as \"telegram_id!: TelegramId\", teamtalk_username as \"teamtalk_username?: String\", banned_at as \"banned_at!: chrono::NaiveDateTime\", banned_by_admin_id as \"banned_by_admin_id?: TelegramId\", reason as \"reason?: String\" FROM banned_users WHERE telegram_id = ?",
-            tg_id
-        )
-        .fetch_optional(&self.pool)
-        .await?;
-        Ok(user)
+        let reg = match &self.backend {
+            Backend::Sqlite(db) => db.get_pending_registration(key).await?,
+            Backend::Postgres(db) => db.get_pending_registration(key).await?,
+        };
+        Ok(reg.map(|reg| self.open_telegram(reg)))
     }
 
-    /// `get_all_banned_users` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn get_all_banned_users(&self) -> Result<Vec<BannedUser>> {
-        let users = sqlx::query_as!(
-            BannedUser,
-            "SELECT telegram_id as \"telegram_id!: TelegramId\", teamtalk_username as \"teamtalk_username?: String\", banned_at as \"banned_at!: chrono::NaiveDateTime\", banned_by_admin_id as \"banned_by_admin_id?: TelegramId\", reason as \"reason?: String\" FROM banned_users ORDER BY banned_at DESC"
-        )
-        .fetch_all(&self.pool)
-        .await?;
-        Ok(users)
+    /// `get_all_pending_registrations` database operation.
+    pub async fn get_all_pending_registrations(&self) -> Result<Vec<PendingTelegramRegistration>> {
+        let regs = match &self.backend {
+            Backend::Sqlite(db) => db.get_all_pending_registrations().await?,
+            Backend::Postgres(db) => db.get_all_pending_registrations().await?,
+        };
+        Ok(regs
+            .into_iter()
+            .map(|reg| self.open_telegram(reg))
+            .collect())
     }
 
-    /// `ban_user` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn ban_user(
+    /// Atomically take a pending registration so only one admin decides it.
+    ///
+    /// The row is removed and returned; `None` means another admin already
+    /// handled the request.
+    pub async fn claim_pending_registration(
         &self,
-        tg_id: TelegramId,
-        tt_username: Option<&str>,
-        admin_id: Option<TelegramId>,
-        reason: Option<&str>,
-    ) -> Result<()> {
-        trace!(
-            tg_id = %tg_id,
-            admin_id = ?admin_id.map(TelegramId::as_i64),
-            "Banning user"
-        );
-        let now = Utc::now().naive_utc();
-        sqlx::query!(
-            "INSERT OR REPLACE INTO banned_users (telegram_id, teamtalk_username, banned_at, banned_by_admin_id, reason) VALUES (?, ?, ?, ?, ?)",
-            tg_id,
-            tt_username,
-            now,
-            admin_id,
-            reason
-        )
-        .execute(&self.pool)
-        .await?;
-        Ok(())
-    }
-
-    /// `unban_user` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn unban_user(&self, tg_id: TelegramId) -> Result<bool> {
-        let res = sqlx::query!("DELETE FROM banned_users WHERE telegram_id = ?", tg_id)
-            .execute(&self.pool)
-            .await?;
-        Ok(res.rows_affected() > 0)
-    }
-
-    /// `is_ip_registered` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn is_ip_registered(&self, ip: &str) -> Result<bool> {
-        let count: i64 = sqlx::query_scalar!(
-            "SELECT count(*) FROM fastapi_registered_ips WHERE ip_address = ?",
-            ip
-        )
-        .fetch_one(&self.pool)
-        .await?;
-        Ok(count > 0)
+        key: &str,
+    ) -> Result<Option<PendingTelegramRegistration>> {
+        let reg = match &self.backend {
+            Backend::Sqlite(db) => db.claim_pending_registration(key).await?,
+            Backend::Postgres(db) => db.claim_pending_registration(key).await?,
+        };
+        Ok(reg.map(|reg| self.open_telegram(reg)))
     }
 
-    /// `add_registered_ip` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn add_registered_ip(&self, ip: &str, username: Option<&str>) -> Result<()> {
-        let now = Utc::now().naive_utc();
-        sqlx::query!(
-            "INSERT INTO fastapi_registered_ips (ip_address, username, registration_timestamp) VALUES (?, ?, ?)",
-            ip,
-            username,
-            now
-        )
-        .execute(&self.pool)
-        .await?;
-        Ok(())
+    /// Remove Telegram requests older than `ttl_seconds` that no admin
+    /// decided, returning them so their requesters can be told.
+    pub async fn expire_pending_registrations(
+        &self,
+        ttl_seconds: u64,
+    ) -> Result<Vec<PendingTelegramRegistration>> {
+        let regs = match &self.backend {
+            Backend::Sqlite(db) => db.expire_pending_registrations(ttl_seconds).await?,
+            Backend::Postgres(db) => db.expire_pending_registrations(ttl_seconds).await?,
+        };
+        Ok(regs
+            .into_iter()
+            .map(|reg| self.open_telegram(reg))
+            .collect())
     }
 
-    /// `add_download_token` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn add_download_token(
+    /// `add_pending_web_registration` database operation.
+    pub async fn add_pending_web_registration(
         &self,
-        token: &str,
-        filepath: &str,
-        original_name: &str,
-        token_type: crate::types::DownloadTokenType,
-        expires_at: chrono::NaiveDateTime,
+        reg: &NewPendingWebRegistration<'_>,
     ) -> Result<()> {
-        let now = Utc::now().naive_utc();
-        let token_type_str = token_type.as_str();
-        sqlx::query!(
-            "INSERT INTO fastapi_download_tokens (token, filepath_on_server, original_filename, token_type, created_at, expires_at, is_used) VALUES (?, ?, ?, ?, ?, ?, 0)",
-            token,
-            filepath,
-            original_name,
-            token_type_str,
-            now,
-            expires_at
-        )
-        .execute(&self.pool)
-        .await?;
-        Ok(())
+        let sealed = self.sealer.seal(reg.password)?;
+        let reg = NewPendingWebRegistration {
+            password: &sealed,
+            ..*reg
+        };
+        match &self.backend {
+            Backend::Sqlite(db) => db.add_pending_web_registration(&reg).await,
+            Backend::Postgres(db) => db.add_pending_web_registration(&reg).await,
+        }
     }
 
-    /// `get_download_token` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn get_download_token(&self, token: &str) -> Result<Option<FastapiDownloadToken>> {
-        let now = Utc::now().naive_utc();
-        let tok = sqlx::query_as!(
-            FastapiDownloadToken,
-            "SELECT token as \"token!: String\", filepath_on_server as \"filepath_on_server!: String\", original_filename as \"original_filename!: String\", token_type as \"token_type!: String\", created_at as \"created_at!: chrono::NaiveDateTime\", expires_at as \"expires_at!: chrono::NaiveDateTime\", is_used as \"is_used!: bool\" FROM fastapi_download_tokens WHERE token = ? AND is_used = 0 AND expires_at > ?",
-            token,
-            now
-        )
-        .fetch_optional(&self.pool)
-        .await?;
-        Ok(tok)
+    /// `get_pending_web_registration` database operation.
+    pub async fn get_pending_web_registration(
+        &self,
+        key: &str,
+    ) -> Result<Option<PendingWebRegistration>> {
+        let reg = match &self.backend {
+            Backend::Sqlite(db) => db.get_pending_web_registration(key).await?,
+            Backend::Postgres(db) => db.get_pending_web_registration(key).await?,
+        };
+        Ok(reg.map(|reg| self.open_web(reg)))
     }
 
-    /// `mark_token_used` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn mark_token_used(&self, token: &str) -> Result<()> {
-        sqlx::query!(
-            "UPDATE fastapi_download_tokens SET is_used = 1 WHERE token = ?",
-            token
-        )
-        .execute(&self.pool)
-        .await?;
-        Ok(())
+    /// `get_undecided_web_registrations` database operation.
+    pub async fn get_undecided_web_registrations(&self) -> Result<Vec<PendingWebRegistration>> {
+        let regs = match &self.backend {
+            Backend::Sqlite(db) => db.get_undecided_web_registrations().await?,
+            Backend::Postgres(db) => db.get_undecided_web_registrations().await?,
+        };
+        Ok(regs.into_iter().map(|reg| self.open_web(reg)).collect())
     }
 
-    /// `create_deeplink` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn create_deeplink(
+    /// Atomically claim a pending web registration for `admin`.
+    ///
+    /// Returns `None` if the request was already decided or another admin holds
+    /// a claim younger than [`WEB_CLAIM_TTL_SECONDS`].
+    pub async fn claim_web_registration(
         &self,
-        token: &str,
-        expires_at: chrono::NaiveDateTime,
-        admin_id: TelegramId,
-    ) -> Result<()> {
-        sqlx::query!(
-            "INSERT INTO deeplink_tokens (token, expires_at, generated_by_admin_id, created_at) VALUES (?, ?, ?, datetime('now'))",
-            token,
-            expires_at,
-            admin_id
-        )
-        .execute(&self.pool)
-        .await?;
-        Ok(())
+        key: &str,
+        admin: TelegramId,
+    ) -> Result<Option<PendingWebRegistration>> {
+        let reg = match &self.backend {
+            Backend::Sqlite(db) => db.claim_web_registration(key, admin).await?,
+            Backend::Postgres(db) => db.claim_web_registration(key, admin).await?,
+        };
+        Ok(reg.map(|reg| self.open_web(reg)))
     }
 
-    /// `get_valid_deeplink` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn get_valid_deeplink(&self, token: &str) -> Result<Option<DeeplinkToken>> {
-        let now = Utc::now().naive_utc();
-        let token_obj = sqlx::query_as!(
-            DeeplinkToken,
-            "SELECT id as \"id?: i64\", token as \"token!: String\", created_at as \"created_at!: chrono::NaiveDateTime\", expires_at as \"expires_at!: chrono::NaiveDateTime\", is_used as \"is_used!: bool\", generated_by_admin_id as \"generated_by_admin_id?: i64\" FROM deeplink_tokens WHERE token = ? AND is_used = 0 AND expires_at > ?",
-            token,
-            now
-        )
-        .fetch_optional(&self.pool)
-        .await?;
-        Ok(token_obj)
+    /// Take the password kept for an approved web request, wiping it, so its
+    /// downloads are issued once.
+    pub async fn take_web_registration_password(&self, key: &str) -> Result<Option<String>> {
+        let password = match &self.backend {
+            Backend::Sqlite(db) => db.take_web_registration_password(key).await?,
+            Backend::Postgres(db) => db.take_web_registration_password(key).await?,
+        };
+        Ok(password.map(|password| self.open_password(&password)))
     }
+}
 
-    /// `mark_deeplink_used` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn mark_deeplink_used(&self, token: &str) -> Result<()> {
-        sqlx::query!(
-            "UPDATE deeplink_tokens SET is_used = 1 WHERE token = ?",
-            token
-        )
-        .execute(&self.pool)
-        .await?;
-        Ok(())
+/// Queued registrations keep their password sealed at rest.
+impl Database {
+    /// Queue a registration until `TeamTalk` is reachable again.
+    pub async fn add_queued_registration(&self, reg: &NewQueuedRegistration<'_>) -> Result<()> {
+        let sealed = self.sealer.seal(reg.password)?;
+        let reg = NewQueuedRegistration {
+            password: &sealed,
+            ..*reg
+        };
+        match &self.backend {
+            Backend::Sqlite(db) => db.add_queued_registration(&reg).await,
+            Backend::Postgres(db) => db.add_queued_registration(&reg).await,
+        }
     }
 
-    /// `cleanup` database operation.
-    #[instrument(skip(self), err)]
-    pub async fn cleanup(
+    /// Approve a Telegram request by moving it into the queue, marked as
+    /// attempted, in one transaction; `None` when another admin already
+    /// decided it.
+    ///
+    /// The queue entry stays until `finish_registration` records the account,
+    /// so a crash in between is picked up by the offline queue replay.
+    pub async fn approve_pending_registration(
         &self,
-        pending_reg_ttl_seconds: u64,
-        registered_ip_ttl_seconds: u64,
-    ) -> Result<()> {
-        trace!(
-            pending_reg_ttl_seconds,
-            registered_ip_ttl_seconds, "Running db cleanup"
-        );
-        let now = Utc::now().naive_utc();
-        sqlx::query!(
-            "DELETE FROM fastapi_download_tokens WHERE expires_at < ? OR is_used = 1",
-            now
-        )
-        .execute(&self.pool)
-        .await?;
-        sqlx::query!(
-            "DELETE FROM deeplink_tokens WHERE expires_at < ? OR is_used = 1",
-            now
-        )
-        .execute(&self.pool)
-        .await?;
-        let pending_ttl = format!("-{pending_reg_ttl_seconds} seconds");
-        let ip_ttl = format!("-{registered_ip_ttl_seconds} seconds");
-        sqlx::query!(
-            "DELETE FROM pending_telegram_registrations WHERE created_at < datetime('now', ?)",
-            pending_ttl
-        )
-        .execute(&self.pool)
-        .await?;
-        sqlx::query!(
-            "DELETE FROM fastapi_registered_ips WHERE registration_timestamp < datetime('now', ?)",
-            ip_ttl
-        )
-        .execute(&self.pool)
-        .await?;
-
-        sqlx::query("PRAGMA optimize;").execute(&self.pool).await?;
-
-        Ok(())
-    }
-
-    /// `close` database operation.
-    pub async fn close(&self) {
-        self.pool.close().await;
+        key: &str,
+        reg: &NewQueuedRegistration<'_>,
+    ) -> Result<Option<i64>> {
+        let sealed = self.sealer.seal(reg.password)?;
+        let reg = NewQueuedRegistration {
+            password: &sealed,
+            ..*reg
+        };
+        match &self.backend {
+            Backend::Sqlite(db) => db.approve_pending_registration(key, &reg).await,
+            Backend::Postgres(db) => db.approve_pending_registration(key, &reg).await,
+        }
     }
-}
 
-async fn integrity_check(pool: &Pool<Sqlite>) -> Result<()> {
-    let result: String = sqlx::query_scalar("PRAGMA integrity_check;")
-        .fetch_one(pool)
-        .await?;
-    if result.trim() == "ok" {
-        info!("Database integrity check: ok");
-        Ok(())
-    } else {
-        error!(result = %result, "Database integrity check failed");
-        anyhow::bail!("Database integrity check failed: {result}");
+    /// Queued registrations, oldest first.
+    pub async fn get_queued_registrations(&self) -> Result<Vec<QueuedRegistration>> {
+        let regs = match &self.backend {
+            Backend::Sqlite(db) => db.get_queued_registrations().await?,
+            Backend::Postgres(db) => db.get_queued_registrations().await?,
+        };
+        Ok(regs
+            .into_iter()
+            .map(|mut reg| {
+                reg.password = self.open_password(&reg.password);
+                reg
+            })
+            .collect())
     }
 }
 
-async fn validate_schema(pool: &Pool<Sqlite>) -> Result<()> {
-    let tables: Vec<String> = sqlx::query("SELECT name FROM sqlite_master WHERE type = 'table'")
-        .map(|row: sqlx::sqlite::SqliteRow| row.get::<String, _>("name"))
-        .fetch_all(pool)
-        .await?;
-    let present: HashSet<String> = tables.into_iter().collect();
-    let required_tables = [
-        "telegram_registrations",
-        "pending_telegram_registrations",
-        "pending_web_registrations",
-        "banned_users",
-        "fastapi_download_tokens",
-        "fastapi_registered_ips",
-        "deeplink_tokens",
-        "_sqlx_migrations",
-    ];
-    for table in &required_tables {
-        if !present.contains(*table) {
-            anyhow::bail!("Database schema missing table: {table}");
+/// Client ZIPs generated on the fly keep their `.tt` source and password
+/// sealed at rest.
+impl Database {
+    /// `add_download_token` database operation.
+    pub async fn add_download_token(&self, new: &NewDownloadToken<'_>) -> Result<()> {
+        let sealed = new
+            .zip_source
+            .map(|source| self.sealer.seal(source))
+            .transpose()?;
+        let sealed_password = new
+            .zip_password
+            .map(|password| self.sealer.seal(password))
+            .transpose()?;
+        let new = NewDownloadToken {
+            zip_source: sealed.as_deref(),
+            zip_password: sealed_password.as_deref(),
+            ..*new
+        };
+        match &self.backend {
+            Backend::Sqlite(db) => db.add_download_token(&new).await,
+            Backend::Postgres(db) => db.add_download_token(&new).await,
         }
     }
 
-    ensure_columns(
-        pool,
-        "pending_telegram_registrations",
-        &[
-            "id",
-            "request_key",
-            "registrant_telegram_id",
-            "username",
-            "password_cleartext",
-            "nickname",
-            "source_info",
-            "created_at",
-        ],
-    )
-    .await?;
-
-    ensure_columns(
-        pool,
-        "pending_web_registrations",
-        &[
-            "id",
-            "request_key",
-            "username",
-            "password_cleartext",
-            "nickname",
-            "ip_address",
-            "user_agent",
-            "source_info",
-            "created_at",
-        ],
-    )
-    .await?;
-
-    Ok(())
+    /// `get_download_token` database operation.
+    pub async fn get_download_token(&self, token: &str) -> Result<Option<FastapiDownloadToken>> {
+        let tok = match &self.backend {
+            Backend::Sqlite(db) => db.get_download_token(token).await?,
+            Backend::Postgres(db) => db.get_download_token(token).await?,
+        };
+        Ok(tok.map(|mut tok| {
+            tok.zip_source = tok.zip_source.map(|source| {
+                self.sealer.open(&source).unwrap_or_else(|e| {
+                    warn!(error = %e, "Failed to decrypt a client ZIP source");
+                    String::new()
+                })
+            });
+            // Never fall back to an unencrypted ZIP: drop the source instead.
+            if let Some(password) = tok.zip_password.take() {
+                match self.sealer.open(&password) {
+                    Ok(password) => tok.zip_password = Some(password),
+                    Err(e) => {
+                        warn!(error = %e, "Failed to decrypt a client ZIP password");
+                        tok.zip_source = Some(String::new());
+                    }
+                }
+            }
+            tok
+        }))
+    }
 }
 
-async fn ensure_columns(pool: &Pool<Sqlite>, table: &str, expected: &[&str]) -> Result<()> {
-    let rows = sqlx::query(&format!("PRAGMA table_info({table})"))
-        .fetch_all(pool)
-        .await?;
-    let mut present = HashSet::new();
-    for row in rows {
-        let name: String = row.get("name");
-        present.insert(name);
-    }
-    for col in expected {
-        if !present.contains(*col) {
-            anyhow::bail!("Table {table} missing column: {col}");
+/// Forward each listed operation to the active backend.
+macro_rules! delegate {
+    ($($(#[doc = $doc:literal])* pub async fn $name:ident(&self $(, $arg:ident: $ty:ty)* $(,)?) -> $ret:ty;)*) => {
+        impl Database {
+            $(
+                $(#[doc = $doc])*
+                pub async fn $name(&self $(, $arg: $ty)*) -> $ret {
+                    match &self.backend {
+                        Backend::Sqlite(db) => db.$name($($arg),*).await,
+                        Backend::Postgres(db) => db.$name($($arg),*).await,
+                    }
+                }
+            )*
         }
-    }
-    Ok(())
+    };
+}
+
+delegate! {
+    /// `ping` database operation.
+    pub async fn ping(&self) -> Result<()>;
+    /// `is_telegram_registered` database operation.
+    pub async fn is_telegram_registered(&self, tg_id: TelegramId) -> Result<bool>;
+    /// Language stored for a registered Telegram user, if any.
+    pub async fn get_registration_language(&self, tg_id: TelegramId) -> Result<Option<String>>;
+    /// Remember the language a registered Telegram user picked.
+    pub async fn set_registration_language(&self, tg_id: TelegramId, language: &str) -> Result<()>;
+    /// `delete_registration` database operation.
+    pub async fn delete_registration(&self, tg_id: TelegramId) -> Result<bool>;
+    /// `get_all_registrations` database operation.
+    pub async fn get_all_registrations(&self) -> Result<Vec<TelegramRegistration>>;
+    /// `get_registration_by_id` database operation.
+    pub async fn get_registration_by_id(
+        &self,
+        tg_id: TelegramId,
+    ) -> Result<Option<TelegramRegistration>>;
+    /// `get_registration_by_tt_username` database operation.
+    pub async fn get_registration_by_tt_username(
+        &self,
+        tt_username: &str,
+        server_id: &str,
+    ) -> Result<Option<TelegramRegistration>>;
+    /// `set_web_registration_status` database operation.
+    ///
+    /// Only pending requests claimed by `admin` are updated; the stored password
+    /// is wiped once a decision is recorded, except on approval, where
+    /// [`Self::take_web_registration_password`] collects it later. Returns `false` if the request was
+    /// already decided or claimed by someone else.
+    pub async fn set_web_registration_status(
+        &self,
+        key: &str,
+        admin: TelegramId,
+        status: WebRequestStatus,
+        reason: Option<&str>,
+    ) -> Result<bool>;
+    /// Change the username of a Telegram request still awaiting approval; a
+    /// nickname that was the old username follows it. `false` if the request
+    /// was already decided.
+    pub async fn rename_pending_registration(&self, key: &str, username: &str) -> Result<bool>;
+    /// Change the username of an undecided web request, like
+    /// [`Self::rename_pending_registration`].
+    pub async fn rename_pending_web_registration(
+        &self,
+        key: &str,
+        username: &str,
+    ) -> Result<bool>;
+    /// `get_banned_user` database operation.
+    pub async fn get_banned_user(&self, tg_id: TelegramId) -> Result<Option<BannedUser>>;
+    /// `get_all_banned_users` database operation.
+    pub async fn get_all_banned_users(&self) -> Result<Vec<BannedUser>>;
+    /// `ban_user` database operation.
+    pub async fn ban_user(
+        &self,
+        tg_id: TelegramId,
+        tt_username: Option<&str>,
+        server_id: Option<&str>,
+        admin_id: Option<TelegramId>,
+        reason: Option<&str>,
+    ) -> Result<()>;
+    /// `unban_user` database operation.
+    pub async fn unban_user(&self, tg_id: TelegramId) -> Result<bool>;
+    /// Ban a `TeamTalk` username or an IP network; banning it again
+    /// replaces the reason. Returns the ban's id.
+    pub async fn add_ban(
+        &self,
+        kind: &str,
+        value: &str,
+        reason: Option<&str>,
+    ) -> Result<i64>;
+    /// Username and IP bans, newest first.
+    pub async fn get_bans(&self) -> Result<Vec<Ban>>;
+    /// Lift a username or IP ban.
+    pub async fn remove_ban(&self, id: i64) -> Result<bool>;
+    /// Addresses `username` was registered from.
+    pub async fn get_registration_ips(&self, username: &str) -> Result<Vec<String>>;
+    /// Preferences of every admin who changed any.
+    pub async fn get_admin_settings(&self) -> Result<Vec<AdminSettings>>;
+    /// Store the notification kinds `admin_id` turned off.
+    pub async fn set_muted_notifications(&self, admin_id: TelegramId, muted: &str) -> Result<()>;
+    /// Store `admin_id`'s quiet hours; `None` follows the configured default.
+    pub async fn set_quiet_hours(
+        &self,
+        admin_id: TelegramId,
+        quiet_hours: Option<&str>,
+    ) -> Result<()>;
+    /// Hold a notification back for `admin_id`'s next digest.
+    pub async fn queue_notification(
+        &self,
+        admin_id: TelegramId,
+        kind: &str,
+        text: &str,
+    ) -> Result<()>;
+    /// Held-back notifications of every admin, oldest first.
+    pub async fn get_queued_notifications(&self) -> Result<Vec<QueuedNotification>>;
+    /// Drop `admin_id`'s held-back notifications up to and including `up_to_id`.
+    pub async fn delete_queued_notifications(
+        &self,
+        admin_id: TelegramId,
+        up_to_id: i64,
+    ) -> Result<()>;
+    /// Registrations recorded for `ip` since `since`.
+    pub async fn count_ip_registrations(&self, ip: &str, since: NaiveDateTime) -> Result<i64>;
+    /// `add_registered_ip` database operation.
+    pub async fn add_registered_ip(&self, ip: &str, username: Option<&str>) -> Result<()>;
+    /// `get_active_download_tokens` database operation.
+    pub async fn get_active_download_tokens(&self) -> Result<Vec<FastapiDownloadToken>>;
+    /// Take one use of a limited download token; `None` when none is left,
+    /// otherwise whether it was the last.
+    pub async fn consume_download_token(&self, token: &str) -> Result<Option<bool>>;
+    /// Give back a use of a download token whose transfer was aborted.
+    pub async fn restore_download_token(&self, token: &str) -> Result<()>;
+    /// Delete a download token so its link stops working; `false` when it
+    /// was already gone.
+    pub async fn revoke_download_token(&self, token: &str) -> Result<bool>;
+    /// Whether the signed link `token_id` is neither revoked nor used up.
+    pub async fn signed_link_usable(&self, token_id: &str) -> Result<bool>;
+    /// Take one use of the signed link `token_id`, which allows `max_uses`
+    /// until `expires_at`; `None` when none is left, otherwise whether it was
+    /// the last.
+    pub async fn consume_signed_link(
+        &self,
+        token_id: &str,
+        max_uses: i64,
+        expires_at: NaiveDateTime,
+    ) -> Result<Option<bool>>;
+    /// Give back a use of the signed link `token_id` whose transfer was aborted.
+    pub async fn restore_signed_link(&self, token_id: &str) -> Result<()>;
+    /// Revoke the signed link `token_id`, kept on the list until `expires_at`;
+    /// `false` when it was already revoked.
+    pub async fn revoke_signed_link(&self, token_id: &str, expires_at: NaiveDateTime) -> Result<bool>;
+    /// Store a new invite token; returns its id.
+    pub async fn create_deeplink(
+        &self,
+        token: &str,
+        expires_at: NaiveDateTime,
+        max_uses: i64,
+        admin_id: TelegramId,
+        server_id: Option<&str>,
+    ) -> Result<i64>;
+    /// `get_valid_deeplink` database operation.
+    pub async fn get_valid_deeplink(&self, token: &str) -> Result<Option<DeeplinkToken>>;
+    /// Invite tokens that have not expired yet, soonest expiry first.
+    pub async fn get_active_deeplinks(&self) -> Result<Vec<DeeplinkToken>>;
+    /// Delete an invite token so its link stops working.
+    pub async fn revoke_deeplink(&self, id: i64) -> Result<bool>;
+    /// Delete every invite token with uses left; returns how many were revoked.
+    pub async fn revoke_all_deeplinks(&self) -> Result<u64>;
+    /// Take one use of a valid invite token; `false` if it expired or ran out meanwhile.
+    pub async fn consume_deeplink(&self, token: &str) -> Result<bool>;
+    /// Give back a use of an invite token taken by a registration that failed.
+    pub async fn restore_deeplink(&self, token: &str) -> Result<()>;
+    /// `record_rate_limit_offender` database operation.
+    pub async fn record_rate_limit_offender(
+        &self,
+        subject: &str,
+        blocked_until: NaiveDateTime,
+    ) -> Result<()>;
+    /// `get_active_rate_limit_blocks` database operation.
+    pub async fn get_active_rate_limit_blocks(&self) -> Result<Vec<RateLimitOffender>>;
+    /// Drop a queued registration once it was replayed or refused.
+    pub async fn delete_queued_registration(&self, id: i64) -> Result<bool>;
+    /// Set or clear when the account of a queued registration was last sent
+    /// to `TeamTalk`.
+    pub async fn set_queued_registration_attempt(
+        &self,
+        id: i64,
+        attempted_at: Option<NaiveDateTime>,
+    ) -> Result<()>;
+    /// Record a created account in one transaction: link its Telegram user
+    /// and drop the queue entry it was created from.
+    pub async fn finish_registration(
+        &self,
+        reg: Option<&NewRegistration<'_>>,
+        queued_id: Option<i64>,
+    ) -> Result<()>;
+    /// Remember an account the bot created so it is welcomed on first login.
+    pub async fn add_account_welcome(
+        &self,
+        server_id: &str,
+        username: &str,
+        language: &str,
+    ) -> Result<()>;
+    /// Mark `username` as welcomed; returns its language only the first time.
+    pub async fn claim_account_welcome(
+        &self,
+        server_id: &str,
+        username: &str,
+    ) -> Result<Option<String>>;
+    /// Append an entry to the audit log.
+    pub async fn add_audit_entry(
+        &self,
+        actor: &str,
+        action: &str,
+        target: &str,
+        details: Option<&str>,
+    ) -> Result<()>;
+    /// Audit log entries, newest first.
+    pub async fn get_audit_entries(&self, limit: i64, offset: i64) -> Result<Vec<AuditEntry>>;
+    /// Registration, decision, ban and invite counts for `/stats`.
+    pub async fn get_registration_stats(&self) -> Result<RegistrationStats>;
+    /// `cleanup` database operation.
+    ///
+    /// Returns the files (relative to the temp directory) of the removed
+    /// download tokens so the caller can delete them. Telegram requests are
+    /// left to [`Self::expire_pending_registrations`].
+    pub async fn cleanup(
+        &self,
+        pending_reg_ttl_seconds: u64,
+        registered_ip_ttl_seconds: u64,
+    ) -> Result<Vec<String>>;
 }
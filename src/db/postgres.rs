@@ -0,0 +1,1209 @@
+use super::WEB_CLAIM_TTL_SECONDS;
+use super::schema::{
+    AdminSettings, AuditEntry, Ban, BannedUser, DeeplinkToken, FastapiDownloadToken,
+    PendingTelegramRegistration, PendingWebRegistration, QueuedNotification, QueuedRegistration,
+    RateLimitOffender, RegistrationStats, TelegramRegistration,
+};
+use super::sealing::{PasswordSealer, SEALED_PREFIX};
+use super::{
+    NewDownloadToken, NewPendingWebRegistration, NewQueuedRegistration, NewRegistration,
+    legacy_queued_source, legacy_telegram_source, legacy_web_source,
+};
+use crate::types::{SourceInfo, TelegramId, WebRequestStatus};
+use anyhow::Result;
+use chrono::{NaiveDateTime, Utc};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{Pool, Postgres};
+use std::time::Duration;
+use tracing::{instrument, trace};
+
+/// `PostgreSQL` backend.
+///
+/// Queries are checked at runtime: the compile-time `query!` cache only
+/// covers the `SQLite` schema.
+#[derive(Clone)]
+pub struct PostgresDb {
+    pool: Pool<Postgres>,
+}
+
+static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations_postgres");
+
+const TELEGRAM_REGISTRATION_COLUMNS: &str =
+    "telegram_id, teamtalk_username, nickname, created_at, server_id, source";
+const PENDING_TELEGRAM_COLUMNS: &str = "id, request_key, registrant_telegram_id, username, password_cleartext, nickname, source_info, created_at";
+const PENDING_WEB_COLUMNS: &str = "id, request_key, username, password_cleartext, nickname, ip_address, user_agent, source_info, created_at, status, decided_at, decision_reason";
+const BANNED_USER_COLUMNS: &str =
+    "telegram_id, teamtalk_username, banned_at, banned_by_admin_id, reason, server_id";
+const DOWNLOAD_TOKEN_COLUMNS: &str = "token, filepath_on_server, original_filename, token_type, created_at, expires_at, is_used, uses_remaining, zip_source, zip_readme, zip_password, client_template";
+const DEEPLINK_COLUMNS: &str =
+    "id, token, created_at, expires_at, is_used, uses_remaining, generated_by_admin_id, server_id";
+
+impl PostgresDb {
+    /// Connect to `url` and apply the `PostgreSQL` migrations.
+    pub async fn new(url: &str) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .acquire_timeout(Duration::from_secs(5))
+            .connect(url)
+            .await?;
+        MIGRATOR.run(&pool).await?;
+        Ok(Self { pool })
+    }
+
+    /// `ping` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// `is_telegram_registered` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn is_telegram_registered(&self, tg_id: TelegramId) -> Result<bool> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT count(*) FROM telegram_registrations WHERE telegram_id = $1",
+        )
+        .bind(tg_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count > 0)
+    }
+
+    /// Language stored for a registered Telegram user, if any.
+    #[instrument(skip(self), err)]
+    pub async fn get_registration_language(&self, tg_id: TelegramId) -> Result<Option<String>> {
+        let language: Option<Option<String>> = sqlx::query_scalar(
+            "SELECT language FROM telegram_registrations WHERE telegram_id = $1",
+        )
+        .bind(tg_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(language.flatten())
+    }
+
+    /// Remember the language a registered Telegram user picked.
+    #[instrument(skip(self), err)]
+    pub async fn set_registration_language(&self, tg_id: TelegramId, language: &str) -> Result<()> {
+        sqlx::query("UPDATE telegram_registrations SET language = $1 WHERE telegram_id = $2")
+            .bind(language)
+            .bind(tg_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// `delete_registration` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn delete_registration(&self, tg_id: TelegramId) -> Result<bool> {
+        let res = sqlx::query("DELETE FROM telegram_registrations WHERE telegram_id = $1")
+            .bind(tg_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// `get_all_registrations` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_all_registrations(&self) -> Result<Vec<TelegramRegistration>> {
+        let users = sqlx::query_as(&format!(
+            "SELECT {TELEGRAM_REGISTRATION_COLUMNS} FROM telegram_registrations ORDER BY lower(teamtalk_username), telegram_id"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(users)
+    }
+
+    /// `get_registration_by_id` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_registration_by_id(
+        &self,
+        tg_id: TelegramId,
+    ) -> Result<Option<TelegramRegistration>> {
+        let user = sqlx::query_as(&format!(
+            "SELECT {TELEGRAM_REGISTRATION_COLUMNS} FROM telegram_registrations WHERE telegram_id = $1"
+        ))
+        .bind(tg_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(user)
+    }
+
+    /// `get_registration_by_tt_username` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_registration_by_tt_username(
+        &self,
+        tt_username: &str,
+        server_id: &str,
+    ) -> Result<Option<TelegramRegistration>> {
+        let user = sqlx::query_as(&format!(
+            "SELECT {TELEGRAM_REGISTRATION_COLUMNS} FROM telegram_registrations WHERE teamtalk_username = $1 AND server_id = $2"
+        ))
+        .bind(tt_username)
+        .bind(server_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(user)
+    }
+
+    /// `add_pending_registration` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn add_pending_registration(
+        &self,
+        key: &str,
+        tg_id: TelegramId,
+        username: &str,
+        password: &str,
+        nickname: &str,
+        source_info: &SourceInfo,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO pending_telegram_registrations (request_key, registrant_telegram_id, username, password_cleartext, nickname, source_info) VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(key)
+        .bind(tg_id)
+        .bind(username)
+        .bind(password)
+        .bind(nickname)
+        .bind(source_info.to_json())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `get_pending_registration` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_pending_registration(
+        &self,
+        key: &str,
+    ) -> Result<Option<PendingTelegramRegistration>> {
+        let reg = sqlx::query_as(&format!(
+            "SELECT {PENDING_TELEGRAM_COLUMNS} FROM pending_telegram_registrations WHERE request_key = $1"
+        ))
+        .bind(key)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(reg)
+    }
+
+    /// `get_all_pending_registrations` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_all_pending_registrations(&self) -> Result<Vec<PendingTelegramRegistration>> {
+        let regs = sqlx::query_as(&format!(
+            "SELECT {PENDING_TELEGRAM_COLUMNS} FROM pending_telegram_registrations ORDER BY created_at DESC"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(regs)
+    }
+
+    /// Atomically take a pending registration so only one admin decides it.
+    #[instrument(skip(self), err)]
+    pub async fn claim_pending_registration(
+        &self,
+        key: &str,
+    ) -> Result<Option<PendingTelegramRegistration>> {
+        let reg = sqlx::query_as(&format!(
+            "DELETE FROM pending_telegram_registrations WHERE request_key = $1 RETURNING {PENDING_TELEGRAM_COLUMNS}"
+        ))
+        .bind(key)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(reg)
+    }
+
+    /// Remove undecided Telegram requests older than `ttl_seconds`.
+    #[instrument(skip(self), err)]
+    pub async fn expire_pending_registrations(
+        &self,
+        ttl_seconds: u64,
+    ) -> Result<Vec<PendingTelegramRegistration>> {
+        let cutoff = Utc::now().naive_utc() - chrono::Duration::seconds(ttl_seconds.try_into()?);
+        let regs = sqlx::query_as(&format!(
+            "DELETE FROM pending_telegram_registrations WHERE created_at < $1 RETURNING {PENDING_TELEGRAM_COLUMNS}"
+        ))
+        .bind(cutoff)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(regs)
+    }
+
+    /// `add_pending_web_registration` database operation.
+    #[instrument(skip(self, reg), fields(username = reg.username), err)]
+    pub async fn add_pending_web_registration(
+        &self,
+        reg: &NewPendingWebRegistration<'_>,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO pending_web_registrations (request_key, username, password_cleartext, nickname, ip_address, user_agent, source_info) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(reg.key)
+        .bind(reg.username)
+        .bind(reg.password)
+        .bind(reg.nickname)
+        .bind(reg.ip_address)
+        .bind(reg.user_agent)
+        .bind(reg.source_info.to_json())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `get_pending_web_registration` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_pending_web_registration(
+        &self,
+        key: &str,
+    ) -> Result<Option<PendingWebRegistration>> {
+        let reg = sqlx::query_as(&format!(
+            "SELECT {PENDING_WEB_COLUMNS} FROM pending_web_registrations WHERE request_key = $1"
+        ))
+        .bind(key)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(reg)
+    }
+
+    /// `get_undecided_web_registrations` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_undecided_web_registrations(&self) -> Result<Vec<PendingWebRegistration>> {
+        let regs = sqlx::query_as(&format!(
+            "SELECT {PENDING_WEB_COLUMNS} FROM pending_web_registrations WHERE status = 'pending' ORDER BY created_at DESC"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(regs)
+    }
+
+    /// Atomically claim a pending web registration for `admin`.
+    #[instrument(skip(self), err)]
+    pub async fn claim_web_registration(
+        &self,
+        key: &str,
+        admin: TelegramId,
+    ) -> Result<Option<PendingWebRegistration>> {
+        let now = Utc::now().naive_utc();
+        let stale = now - chrono::Duration::seconds(WEB_CLAIM_TTL_SECONDS);
+        let reg = sqlx::query_as(&format!(
+            "UPDATE pending_web_registrations SET claimed_by = $1, claimed_at = $2 WHERE request_key = $3 AND status = 'pending' AND (claimed_by IS NULL OR claimed_at < $4) RETURNING {PENDING_WEB_COLUMNS}"
+        ))
+        .bind(admin)
+        .bind(now)
+        .bind(key)
+        .bind(stale)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(reg)
+    }
+
+    /// `set_web_registration_status` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn set_web_registration_status(
+        &self,
+        key: &str,
+        admin: TelegramId,
+        status: WebRequestStatus,
+        reason: Option<&str>,
+    ) -> Result<bool> {
+        let now = Utc::now().naive_utc();
+        let res = sqlx::query(
+            "UPDATE pending_web_registrations SET status = $1, decided_at = $2, decision_reason = $3, password_cleartext = CASE WHEN $1 = 'approved' THEN password_cleartext ELSE '' END WHERE request_key = $4 AND status = 'pending' AND claimed_by = $5",
+        )
+        .bind(status.as_str())
+        .bind(now)
+        .bind(reason)
+        .bind(key)
+        .bind(admin)
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Take the password kept for an approved web request, wiping it.
+    #[instrument(skip(self), err)]
+    pub async fn take_web_registration_password(&self, key: &str) -> Result<Option<String>> {
+        let password = sqlx::query_scalar(
+            "UPDATE pending_web_registrations SET password_cleartext = '' WHERE request_key = $1 AND status = 'approved' AND password_cleartext <> '' RETURNING password_cleartext",
+        )
+        .bind(key)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(password)
+    }
+
+    /// Change the username of a Telegram request still awaiting approval.
+    #[instrument(skip(self), err)]
+    pub async fn rename_pending_registration(&self, key: &str, username: &str) -> Result<bool> {
+        let res = sqlx::query(
+            "UPDATE pending_telegram_registrations SET nickname = CASE WHEN nickname = username THEN $1 ELSE nickname END, username = $1 WHERE request_key = $2",
+        )
+        .bind(username)
+        .bind(key)
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Change the username of an undecided web request.
+    #[instrument(skip(self), err)]
+    pub async fn rename_pending_web_registration(&self, key: &str, username: &str) -> Result<bool> {
+        let res = sqlx::query(
+            "UPDATE pending_web_registrations SET nickname = CASE WHEN nickname = username THEN $1 ELSE nickname END, username = $1 WHERE request_key = $2 AND status = 'pending'",
+        )
+        .bind(username)
+        .bind(key)
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// `get_banned_user` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_banned_user(&self, tg_id: TelegramId) -> Result<Option<BannedUser>> {
+        let user = sqlx::query_as(&format!(
+            "SELECT {BANNED_USER_COLUMNS} FROM banned_users WHERE telegram_id = $1"
+        ))
+        .bind(tg_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(user)
+    }
+
+    /// `get_all_banned_users` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_all_banned_users(&self) -> Result<Vec<BannedUser>> {
+        let users = sqlx::query_as(&format!(
+            "SELECT {BANNED_USER_COLUMNS} FROM banned_users ORDER BY banned_at DESC"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(users)
+    }
+
+    /// `ban_user` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn ban_user(
+        &self,
+        tg_id: TelegramId,
+        tt_username: Option<&str>,
+        server_id: Option<&str>,
+        admin_id: Option<TelegramId>,
+        reason: Option<&str>,
+    ) -> Result<()> {
+        trace!(
+            tg_id = %tg_id,
+            admin_id = ?admin_id.map(TelegramId::as_i64),
+            "Banning user"
+        );
+        let now = Utc::now().naive_utc();
+        sqlx::query(
+            "INSERT INTO banned_users (telegram_id, teamtalk_username, banned_at, banned_by_admin_id, reason, server_id) VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (telegram_id) DO UPDATE SET teamtalk_username = excluded.teamtalk_username, banned_at = excluded.banned_at, banned_by_admin_id = excluded.banned_by_admin_id, reason = excluded.reason, server_id = excluded.server_id",
+        )
+        .bind(tg_id)
+        .bind(tt_username)
+        .bind(now)
+        .bind(admin_id)
+        .bind(reason)
+        .bind(server_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `unban_user` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn unban_user(&self, tg_id: TelegramId) -> Result<bool> {
+        let res = sqlx::query("DELETE FROM banned_users WHERE telegram_id = $1")
+            .bind(tg_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Ban a `TeamTalk` username or an IP network; banning it again
+    /// replaces the reason. Returns the ban's id.
+    #[instrument(skip(self), err)]
+    pub async fn add_ban(&self, kind: &str, value: &str, reason: Option<&str>) -> Result<i64> {
+        let id = sqlx::query_scalar(
+            "INSERT INTO bans (kind, value, banned_at, reason) VALUES ($1, $2, $3, $4) ON CONFLICT (kind, value) DO UPDATE SET banned_at = excluded.banned_at, reason = excluded.reason RETURNING id",
+        )
+        .bind(kind)
+        .bind(value)
+        .bind(Utc::now().naive_utc())
+        .bind(reason)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(id)
+    }
+
+    /// Username and IP bans, newest first.
+    #[instrument(skip(self), err)]
+    pub async fn get_bans(&self) -> Result<Vec<Ban>> {
+        let bans = sqlx::query_as(
+            "SELECT id, kind, value, banned_at, reason FROM bans ORDER BY banned_at DESC, id DESC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(bans)
+    }
+
+    /// Lift a username or IP ban.
+    #[instrument(skip(self), err)]
+    pub async fn remove_ban(&self, id: i64) -> Result<bool> {
+        let res = sqlx::query("DELETE FROM bans WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Addresses `username` was registered from.
+    #[instrument(skip(self), err)]
+    pub async fn get_registration_ips(&self, username: &str) -> Result<Vec<String>> {
+        let ips = sqlx::query_scalar(
+            "SELECT DISTINCT ip_address FROM fastapi_registered_ips WHERE username = $1",
+        )
+        .bind(username)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(ips)
+    }
+
+    /// Preferences of every admin who changed any.
+    #[instrument(skip(self), err)]
+    pub async fn get_admin_settings(&self) -> Result<Vec<AdminSettings>> {
+        let settings =
+            sqlx::query_as("SELECT admin_id, muted_notifications, quiet_hours FROM admin_settings")
+                .fetch_all(&self.pool)
+                .await?;
+        Ok(settings)
+    }
+
+    /// Store the notification kinds `admin_id` turned off.
+    #[instrument(skip(self), err)]
+    pub async fn set_muted_notifications(&self, admin_id: TelegramId, muted: &str) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO admin_settings (admin_id, muted_notifications) VALUES ($1, $2) ON CONFLICT (admin_id) DO UPDATE SET muted_notifications = excluded.muted_notifications",
+        )
+        .bind(admin_id)
+        .bind(muted)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Store `admin_id`'s quiet hours; `None` follows the configured default.
+    #[instrument(skip(self), err)]
+    pub async fn set_quiet_hours(
+        &self,
+        admin_id: TelegramId,
+        quiet_hours: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO admin_settings (admin_id, quiet_hours) VALUES ($1, $2) ON CONFLICT (admin_id) DO UPDATE SET quiet_hours = excluded.quiet_hours",
+        )
+        .bind(admin_id)
+        .bind(quiet_hours)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Hold a notification back for `admin_id`'s next digest.
+    #[instrument(skip(self, text), err)]
+    pub async fn queue_notification(
+        &self,
+        admin_id: TelegramId,
+        kind: &str,
+        text: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO queued_notifications (admin_id, kind, text, created_at) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(admin_id)
+        .bind(kind)
+        .bind(text)
+        .bind(Utc::now().naive_utc())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Held-back notifications of every admin, oldest first.
+    #[instrument(skip(self), err)]
+    pub async fn get_queued_notifications(&self) -> Result<Vec<QueuedNotification>> {
+        let rows = sqlx::query_as(
+            "SELECT id, admin_id, kind, text, created_at FROM queued_notifications ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Drop `admin_id`'s held-back notifications up to and including `up_to_id`.
+    #[instrument(skip(self), err)]
+    pub async fn delete_queued_notifications(
+        &self,
+        admin_id: TelegramId,
+        up_to_id: i64,
+    ) -> Result<()> {
+        sqlx::query("DELETE FROM queued_notifications WHERE admin_id = $1 AND id <= $2")
+            .bind(admin_id)
+            .bind(up_to_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Registrations recorded for `ip` since `since`.
+    #[instrument(skip(self), err)]
+    pub async fn count_ip_registrations(&self, ip: &str, since: NaiveDateTime) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT count(*) FROM fastapi_registered_ips WHERE ip_address = $1 AND registration_timestamp >= $2",
+        )
+        .bind(ip)
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count)
+    }
+
+    /// `add_registered_ip` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn add_registered_ip(&self, ip: &str, username: Option<&str>) -> Result<()> {
+        let now = Utc::now().naive_utc();
+        sqlx::query(
+            "INSERT INTO fastapi_registered_ips (ip_address, username, registration_timestamp) VALUES ($1, $2, $3)",
+        )
+        .bind(ip)
+        .bind(username)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `add_download_token` database operation.
+    #[instrument(skip(self, new), fields(file = new.filepath), err)]
+    pub async fn add_download_token(&self, new: &NewDownloadToken<'_>) -> Result<()> {
+        let now = Utc::now().naive_utc();
+        sqlx::query(
+            "INSERT INTO fastapi_download_tokens (token, filepath_on_server, original_filename, token_type, created_at, expires_at, is_used, uses_remaining, zip_source, zip_readme, zip_password, client_template) VALUES ($1, $2, $3, $4, $5, $6, FALSE, $7, $8, $9, $10, $11)",
+        )
+        .bind(new.token)
+        .bind(new.filepath)
+        .bind(new.original_name)
+        .bind(new.token_type.as_str())
+        .bind(now)
+        .bind(new.expires_at)
+        .bind(new.max_uses.map(i64::from))
+        .bind(new.zip_source)
+        .bind(new.zip_readme)
+        .bind(new.zip_password)
+        .bind(new.client_template)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `get_download_token` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_download_token(&self, token: &str) -> Result<Option<FastapiDownloadToken>> {
+        let now = Utc::now().naive_utc();
+        let tok = sqlx::query_as(&format!(
+            "SELECT {DOWNLOAD_TOKEN_COLUMNS} FROM fastapi_download_tokens WHERE token = $1 AND NOT is_used AND (uses_remaining IS NULL OR uses_remaining > 0) AND expires_at > $2"
+        ))
+        .bind(token)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(tok)
+    }
+
+    /// `get_active_download_tokens` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_active_download_tokens(&self) -> Result<Vec<FastapiDownloadToken>> {
+        let now = Utc::now().naive_utc();
+        let tokens = sqlx::query_as(&format!(
+            "SELECT {DOWNLOAD_TOKEN_COLUMNS} FROM fastapi_download_tokens WHERE NOT is_used AND (uses_remaining IS NULL OR uses_remaining > 0) AND expires_at > $1 ORDER BY created_at DESC"
+        ))
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(tokens)
+    }
+
+    /// Take one use of a limited download token; `None` when none is left,
+    /// otherwise whether it was the last.
+    #[instrument(skip(self), err)]
+    pub async fn consume_download_token(&self, token: &str) -> Result<Option<bool>> {
+        let exhausted: Option<bool> = sqlx::query_scalar(
+            "UPDATE fastapi_download_tokens SET uses_remaining = uses_remaining - 1, is_used = (uses_remaining <= 1) WHERE token = $1 AND uses_remaining > 0 RETURNING is_used",
+        )
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(exhausted)
+    }
+
+    /// Give back a use of a download token whose transfer was aborted.
+    #[instrument(skip(self), err)]
+    pub async fn restore_download_token(&self, token: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE fastapi_download_tokens SET uses_remaining = uses_remaining + 1, is_used = FALSE WHERE token = $1 AND uses_remaining IS NOT NULL",
+        )
+        .bind(token)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Delete a download token so its link stops working; `false` when it
+    /// was already gone.
+    #[instrument(skip(self), err)]
+    pub async fn revoke_download_token(&self, token: &str) -> Result<bool> {
+        let res = sqlx::query("DELETE FROM fastapi_download_tokens WHERE token = $1")
+            .bind(token)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Whether the signed link `token_id` is neither revoked nor used up.
+    #[instrument(skip(self), err)]
+    pub async fn signed_link_usable(&self, token_id: &str) -> Result<bool> {
+        let spent: Option<String> = sqlx::query_scalar(
+            "SELECT token_id FROM signed_download_links WHERE token_id = $1 AND (revoked_at IS NOT NULL OR uses_remaining <= 0)",
+        )
+        .bind(token_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(spent.is_none())
+    }
+
+    /// Take one use of the signed link `token_id`, which allows `max_uses`
+    /// until `expires_at`; `None` when none is left, otherwise whether it was
+    /// the last.
+    #[instrument(skip(self), err)]
+    pub async fn consume_signed_link(
+        &self,
+        token_id: &str,
+        max_uses: i64,
+        expires_at: NaiveDateTime,
+    ) -> Result<Option<bool>> {
+        let remaining: Option<i64> = sqlx::query_scalar(
+            "INSERT INTO signed_download_links (token_id, uses_remaining, expires_at) VALUES ($1, $2, $3) ON CONFLICT (token_id) DO UPDATE SET uses_remaining = signed_download_links.uses_remaining - 1 WHERE signed_download_links.uses_remaining > 0 RETURNING uses_remaining",
+        )
+        .bind(token_id)
+        .bind(max_uses - 1)
+        .bind(expires_at)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(remaining.map(|remaining| remaining <= 0))
+    }
+
+    /// Give back a use of the signed link `token_id` whose transfer was aborted.
+    #[instrument(skip(self), err)]
+    pub async fn restore_signed_link(&self, token_id: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE signed_download_links SET uses_remaining = uses_remaining + 1 WHERE token_id = $1",
+        )
+        .bind(token_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Revoke the signed link `token_id`, kept on the list until `expires_at`;
+    /// `false` when it was already revoked.
+    #[instrument(skip(self), err)]
+    pub async fn revoke_signed_link(
+        &self,
+        token_id: &str,
+        expires_at: NaiveDateTime,
+    ) -> Result<bool> {
+        let res = sqlx::query(
+            "INSERT INTO signed_download_links (token_id, expires_at, revoked_at) VALUES ($1, $2, $3) ON CONFLICT (token_id) DO UPDATE SET revoked_at = EXCLUDED.revoked_at WHERE signed_download_links.revoked_at IS NULL",
+        )
+        .bind(token_id)
+        .bind(expires_at)
+        .bind(Utc::now().naive_utc())
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Store a new invite token; returns its id.
+    #[instrument(skip(self), err)]
+    pub async fn create_deeplink(
+        &self,
+        token: &str,
+        expires_at: NaiveDateTime,
+        max_uses: i64,
+        admin_id: TelegramId,
+        server_id: Option<&str>,
+    ) -> Result<i64> {
+        let now = Utc::now().naive_utc();
+        let id = sqlx::query_scalar(
+            "INSERT INTO deeplink_tokens (token, expires_at, uses_remaining, generated_by_admin_id, created_at, server_id) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id",
+        )
+        .bind(token)
+        .bind(expires_at)
+        .bind(max_uses)
+        .bind(admin_id)
+        .bind(now)
+        .bind(server_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(id)
+    }
+
+    /// `get_valid_deeplink` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_valid_deeplink(&self, token: &str) -> Result<Option<DeeplinkToken>> {
+        let now = Utc::now().naive_utc();
+        let token_obj = sqlx::query_as(&format!(
+            "SELECT {DEEPLINK_COLUMNS} FROM deeplink_tokens WHERE token = $1 AND uses_remaining > 0 AND expires_at > $2"
+        ))
+        .bind(token)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(token_obj)
+    }
+
+    /// Invite tokens that have not expired yet, soonest expiry first.
+    #[instrument(skip(self), err)]
+    pub async fn get_active_deeplinks(&self) -> Result<Vec<DeeplinkToken>> {
+        let now = Utc::now().naive_utc();
+        let tokens = sqlx::query_as(&format!(
+            "SELECT {DEEPLINK_COLUMNS} FROM deeplink_tokens WHERE expires_at > $1 ORDER BY expires_at, id"
+        ))
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(tokens)
+    }
+
+    /// Delete an invite token so its link stops working.
+    #[instrument(skip(self), err)]
+    pub async fn revoke_deeplink(&self, id: i64) -> Result<bool> {
+        let res = sqlx::query("DELETE FROM deeplink_tokens WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Delete every invite token with uses left; returns how many were revoked.
+    #[instrument(skip(self), err)]
+    pub async fn revoke_all_deeplinks(&self) -> Result<u64> {
+        let res = sqlx::query("DELETE FROM deeplink_tokens WHERE uses_remaining > 0")
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    /// Take one use of a valid invite token; `false` if it expired or ran out meanwhile.
+    #[instrument(skip(self), err)]
+    pub async fn consume_deeplink(&self, token: &str) -> Result<bool> {
+        let now = Utc::now().naive_utc();
+        let res = sqlx::query(
+            "UPDATE deeplink_tokens SET uses_remaining = uses_remaining - 1, is_used = (uses_remaining <= 1) WHERE token = $1 AND uses_remaining > 0 AND expires_at > $2",
+        )
+        .bind(token)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Give back a use of an invite token taken by a registration that failed.
+    #[instrument(skip(self), err)]
+    pub async fn restore_deeplink(&self, token: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE deeplink_tokens SET uses_remaining = uses_remaining + 1, is_used = FALSE WHERE token = $1",
+        )
+        .bind(token)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `record_rate_limit_offender` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn record_rate_limit_offender(
+        &self,
+        subject: &str,
+        blocked_until: NaiveDateTime,
+    ) -> Result<()> {
+        let now = Utc::now().naive_utc();
+        sqlx::query(
+            "INSERT INTO web_rate_limit_offenders (subject, blocked_until, hits, last_seen) VALUES ($1, $2, 1, $3) ON CONFLICT (subject) DO UPDATE SET blocked_until = excluded.blocked_until, hits = web_rate_limit_offenders.hits + 1, last_seen = excluded.last_seen",
+        )
+        .bind(subject)
+        .bind(blocked_until)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `get_active_rate_limit_blocks` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_active_rate_limit_blocks(&self) -> Result<Vec<RateLimitOffender>> {
+        let now = Utc::now().naive_utc();
+        let rows = sqlx::query_as(
+            "SELECT subject, blocked_until, hits, last_seen FROM web_rate_limit_offenders WHERE blocked_until > $1",
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Append an entry to the audit log.
+    #[instrument(skip(self), err)]
+    pub async fn add_audit_entry(
+        &self,
+        actor: &str,
+        action: &str,
+        target: &str,
+        details: Option<&str>,
+    ) -> Result<()> {
+        let now = Utc::now().naive_utc();
+        sqlx::query(
+            "INSERT INTO audit_log (created_at, actor, action, target, details) VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(now)
+        .bind(actor)
+        .bind(action)
+        .bind(target)
+        .bind(details)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Audit log entries, newest first.
+    #[instrument(skip(self), err)]
+    pub async fn get_audit_entries(&self, limit: i64, offset: i64) -> Result<Vec<AuditEntry>> {
+        let rows = sqlx::query_as(
+            "SELECT created_at, actor, action, target, details FROM audit_log ORDER BY id DESC LIMIT $1 OFFSET $2",
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Queue a registration until `TeamTalk` is reachable again.
+    #[instrument(skip(self, reg), fields(username = reg.username), err)]
+    pub async fn add_queued_registration(&self, reg: &NewQueuedRegistration<'_>) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO queued_registrations (username, password, nickname, account_type, rights_profile, source, source_info, telegram_id, language, server_id) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        )
+        .bind(reg.username)
+        .bind(reg.password)
+        .bind(reg.nickname)
+        .bind(reg.account_type)
+        .bind(reg.rights_profile)
+        .bind(reg.source)
+        .bind(reg.source_info.map(SourceInfo::to_json))
+        .bind(reg.telegram_id)
+        .bind(reg.language)
+        .bind(reg.server_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Queued registrations, oldest first.
+    #[instrument(skip(self), err)]
+    pub async fn get_queued_registrations(&self) -> Result<Vec<QueuedRegistration>> {
+        let rows = sqlx::query_as(
+            "SELECT id, created_at, username, password, nickname, account_type, rights_profile, source, source_info, telegram_id, language, server_id, attempted_at FROM queued_registrations ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Drop a queued registration once it was replayed or refused.
+    #[instrument(skip(self), err)]
+    pub async fn delete_queued_registration(&self, id: i64) -> Result<bool> {
+        let res = sqlx::query("DELETE FROM queued_registrations WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Move an approved Telegram request into the queue in one transaction,
+    /// marked as attempted; `None` when another admin already decided it.
+    #[instrument(skip(self, reg), fields(username = reg.username), err)]
+    pub async fn approve_pending_registration(
+        &self,
+        key: &str,
+        reg: &NewQueuedRegistration<'_>,
+    ) -> Result<Option<i64>> {
+        let mut tx = self.pool.begin().await?;
+        let claimed =
+            sqlx::query("DELETE FROM pending_telegram_registrations WHERE request_key = $1")
+                .bind(key)
+                .execute(&mut *tx)
+                .await?;
+        if claimed.rows_affected() == 0 {
+            return Ok(None);
+        }
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO queued_registrations (username, password, nickname, account_type, rights_profile, source, source_info, telegram_id, language, server_id, attempted_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) RETURNING id",
+        )
+        .bind(reg.username)
+        .bind(reg.password)
+        .bind(reg.nickname)
+        .bind(reg.account_type)
+        .bind(reg.rights_profile)
+        .bind(reg.source)
+        .bind(reg.source_info.map(SourceInfo::to_json))
+        .bind(reg.telegram_id)
+        .bind(reg.language)
+        .bind(reg.server_id)
+        .bind(Utc::now().naive_utc())
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(Some(id))
+    }
+
+    /// Set or clear when the account of a queued registration was last sent
+    /// to `TeamTalk`.
+    #[instrument(skip(self), err)]
+    pub async fn set_queued_registration_attempt(
+        &self,
+        id: i64,
+        attempted_at: Option<NaiveDateTime>,
+    ) -> Result<()> {
+        sqlx::query("UPDATE queued_registrations SET attempted_at = $1 WHERE id = $2")
+            .bind(attempted_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Record a created account in one transaction: link its Telegram user
+    /// and drop the queue entry it was created from.
+    #[instrument(skip(self, reg), err)]
+    pub async fn finish_registration(
+        &self,
+        reg: Option<&NewRegistration<'_>>,
+        queued_id: Option<i64>,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        if let Some(reg) = reg {
+            trace!(tg_id = %reg.telegram_id, tt_username = reg.username, server_id = reg.server_id, "Adding registration");
+            sqlx::query(
+                "INSERT INTO telegram_registrations (telegram_id, teamtalk_username, nickname, created_at, language, server_id, source) VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (telegram_id) DO UPDATE SET teamtalk_username = excluded.teamtalk_username, nickname = excluded.nickname, created_at = excluded.created_at, language = excluded.language, server_id = excluded.server_id, source = excluded.source",
+            )
+            .bind(reg.telegram_id)
+            .bind(reg.username)
+            .bind(reg.nickname)
+            .bind(Utc::now().naive_utc())
+            .bind(reg.language)
+            .bind(reg.server_id)
+            .bind(reg.source.as_str())
+            .execute(&mut *tx)
+            .await?;
+        }
+        if let Some(id) = queued_id {
+            sqlx::query("DELETE FROM queued_registrations WHERE id = $1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Remember an account the bot created so it is welcomed on first login.
+    #[instrument(skip(self), err)]
+    pub async fn add_account_welcome(
+        &self,
+        server_id: &str,
+        username: &str,
+        language: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO account_welcomes (server_id, username, language) VALUES ($1, $2, $3) ON CONFLICT (server_id, username) DO UPDATE SET language = EXCLUDED.language, created_at = (NOW() AT TIME ZONE 'UTC'), welcomed_at = NULL",
+        )
+        .bind(server_id)
+        .bind(username)
+        .bind(language)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Mark `username` as welcomed; returns its language only the first time.
+    #[instrument(skip(self), err)]
+    pub async fn claim_account_welcome(
+        &self,
+        server_id: &str,
+        username: &str,
+    ) -> Result<Option<String>> {
+        let now = Utc::now().naive_utc();
+        let language = sqlx::query_scalar(
+            "UPDATE account_welcomes SET welcomed_at = $1 WHERE server_id = $2 AND username = $3 AND welcomed_at IS NULL RETURNING language",
+        )
+        .bind(now)
+        .bind(server_id)
+        .bind(username)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(language)
+    }
+
+    /// Registration, decision, ban and invite counts for `/stats`.
+    #[instrument(skip(self), err)]
+    pub async fn get_registration_stats(&self) -> Result<RegistrationStats> {
+        let today = Utc::now().date_naive().and_time(chrono::NaiveTime::MIN);
+        let stats = sqlx::query_as(
+            "SELECT (SELECT count(*) FROM telegram_registrations) AS telegram_total, (SELECT count(*) FROM telegram_registrations WHERE created_at >= $1) AS telegram_today, (SELECT count(*) FROM telegram_registrations WHERE source = 'approved') AS telegram_approved, (SELECT count(*) FROM fastapi_registered_ips) AS web_total, (SELECT count(*) FROM fastapi_registered_ips WHERE registration_timestamp >= $1) AS web_today, (SELECT count(*) FROM pending_web_registrations WHERE status = 'approved') AS web_approved, (SELECT count(*) FROM pending_web_registrations WHERE status = 'approved' AND decided_at >= $1) AS web_approved_today, (SELECT count(*) FROM pending_web_registrations WHERE status = 'rejected') AS web_rejected, (SELECT count(*) FROM pending_web_registrations WHERE status = 'rejected' AND decided_at >= $1) AS web_rejected_today, (SELECT count(*) FROM pending_web_registrations WHERE status = 'pending') AS web_pending, (SELECT count(*) FROM banned_users) AS banned, (SELECT count(*) FROM deeplink_tokens) AS deeplinks_issued, (SELECT count(*) FROM deeplink_tokens WHERE is_used) AS deeplinks_used",
+        )
+        .bind(today)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(stats)
+    }
+
+    /// `cleanup` database operation; returns the files of removed download tokens.
+    #[instrument(skip(self), err)]
+    pub async fn cleanup(
+        &self,
+        pending_reg_ttl_seconds: u64,
+        registered_ip_ttl_seconds: u64,
+    ) -> Result<Vec<String>> {
+        trace!(
+            pending_reg_ttl_seconds,
+            registered_ip_ttl_seconds, "Running db cleanup"
+        );
+        let now = Utc::now().naive_utc();
+        let pending_cutoff = now - chrono::Duration::seconds(pending_reg_ttl_seconds.try_into()?);
+        let ip_cutoff = now - chrono::Duration::seconds(registered_ip_ttl_seconds.try_into()?);
+        let files = sqlx::query_scalar(
+            "DELETE FROM fastapi_download_tokens WHERE expires_at < $1 OR is_used RETURNING filepath_on_server",
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await?;
+        sqlx::query("DELETE FROM deeplink_tokens WHERE expires_at < $1 OR is_used")
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("DELETE FROM signed_download_links WHERE expires_at < $1")
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("DELETE FROM web_rate_limit_offenders WHERE blocked_until < $1")
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("DELETE FROM pending_web_registrations WHERE created_at < $1")
+            .bind(pending_cutoff)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("DELETE FROM fastapi_registered_ips WHERE registration_timestamp < $1")
+            .bind(ip_cutoff)
+            .execute(&self.pool)
+            .await?;
+        Ok(files)
+    }
+
+    /// Encrypt pending passwords stored before sealing was introduced.
+    #[instrument(skip(self, sealer), err)]
+    pub async fn seal_legacy_passwords(&self, sealer: &PasswordSealer) -> Result<u64> {
+        let pattern = format!("{SEALED_PREFIX}%");
+        let mut tx = self.pool.begin().await?;
+        let mut sealed_count = 0;
+        for table in [
+            "pending_telegram_registrations",
+            "pending_web_registrations",
+        ] {
+            let rows: Vec<(i64, String)> = sqlx::query_as(&format!(
+                "SELECT id, password_cleartext FROM {table} WHERE password_cleartext <> '' AND password_cleartext NOT LIKE $1"
+            ))
+            .bind(&pattern)
+            .fetch_all(&mut *tx)
+            .await?;
+            for (id, password) in rows {
+                sqlx::query(&format!(
+                    "UPDATE {table} SET password_cleartext = $1 WHERE id = $2"
+                ))
+                .bind(sealer.seal(&password)?)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+                sealed_count += 1;
+            }
+        }
+        tx.commit().await?;
+        Ok(sealed_count)
+    }
+
+    /// Rewrite `source_info` stored in the old `key=value` format as JSON.
+    #[instrument(skip(self), err)]
+    pub async fn convert_legacy_source_info(&self) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let telegram: Vec<(i64, TelegramId, String)> = sqlx::query_as(
+            "SELECT id, registrant_telegram_id, source_info FROM pending_telegram_registrations WHERE source_info NOT LIKE '{%'",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let web: Vec<(i64, String, Option<String>, String)> = sqlx::query_as(
+            "SELECT id, ip_address, user_agent, source_info FROM pending_web_registrations WHERE source_info NOT LIKE '{%'",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let queued: Vec<(i64, String, String)> = sqlx::query_as(
+            "SELECT id, source, source_info FROM queued_registrations WHERE source_info IS NOT NULL AND source_info NOT LIKE '{%'",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let converted = telegram
+            .iter()
+            .map(|(id, registrant, stored)| {
+                let json = legacy_telegram_source(stored, *registrant);
+                ("pending_telegram_registrations", *id, json)
+            })
+            .chain(web.iter().map(|(id, ip, user_agent, stored)| {
+                let json = legacy_web_source(stored, ip, user_agent.as_deref());
+                ("pending_web_registrations", *id, json)
+            }))
+            .chain(queued.iter().map(|(id, source, stored)| {
+                (
+                    "queued_registrations",
+                    *id,
+                    legacy_queued_source(stored, source),
+                )
+            }));
+        let mut count = 0;
+        for (table, id, json) in converted {
+            sqlx::query(&format!(
+                "UPDATE {table} SET source_info = $1 WHERE id = $2"
+            ))
+            .bind(json)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+            count += 1;
+        }
+        tx.commit().await?;
+        Ok(count)
+    }
+
+    /// `close` database operation.
+    pub async fn close(&self) {
+        self.pool.close().await;
+    }
+}
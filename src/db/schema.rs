@@ -1,12 +1,31 @@
-use crate::types::TelegramId;
+use crate::config::DEFAULT_SERVER_ID;
+use crate::domain::QuietHours;
+use crate::types::{NotificationKind, RegistrationOrigin, SourceInfo, TelegramId};
 use chrono::NaiveDateTime;
+use serde::Serialize;
 use sqlx::FromRow;
 
 /// Row for Telegram registrations table.
-#[derive(Debug, FromRow)]
+#[derive(Debug, FromRow, Serialize)]
 pub struct TelegramRegistration {
     pub telegram_id: TelegramId,
     pub teamtalk_username: String,
+    pub nickname: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+    /// `TeamTalk` server the account was created on.
+    pub server_id: String,
+    /// [`RegistrationOrigin`] as stored; `None` for rows from before it was
+    /// recorded.
+    pub source: Option<String>,
+}
+
+impl TelegramRegistration {
+    /// How the account came about, if known.
+    pub fn origin(&self) -> Option<RegistrationOrigin> {
+        self.source
+            .as_deref()
+            .and_then(|source| RegistrationOrigin::try_from(source).ok())
+    }
 }
 
 /// Row for pending registration table.
@@ -19,19 +38,108 @@ pub struct PendingTelegramRegistration {
     pub username: String,
     pub password_cleartext: String,
     pub nickname: String,
-    pub source_info: String,
+    pub source_info: SourceInfo,
     pub created_at: NaiveDateTime,
 }
 
-/// Row for banned users table.
+/// Row for pending web registration table.
 #[derive(Debug, FromRow)]
 #[allow(dead_code)]
+pub struct PendingWebRegistration {
+    pub id: Option<i64>,
+    pub request_key: String,
+    pub username: String,
+    pub password_cleartext: String,
+    pub nickname: String,
+    pub ip_address: String,
+    pub user_agent: Option<String>,
+    pub source_info: SourceInfo,
+    pub created_at: NaiveDateTime,
+    pub status: String,
+    pub decided_at: Option<NaiveDateTime>,
+    pub decision_reason: Option<String>,
+}
+
+/// Row for banned users table.
+#[derive(Debug, FromRow, Serialize)]
+#[allow(dead_code)]
 pub struct BannedUser {
     pub telegram_id: TelegramId,
     pub teamtalk_username: Option<String>,
     pub banned_at: NaiveDateTime,
     pub banned_by_admin_id: Option<TelegramId>,
     pub reason: Option<String>,
+    /// `TeamTalk` server of the linked account; `None` for rows from before
+    /// it was recorded.
+    pub server_id: Option<String>,
+}
+
+impl BannedUser {
+    /// Server the linked account is on.
+    pub fn account_server(&self) -> &str {
+        self.server_id.as_deref().unwrap_or(DEFAULT_SERVER_ID)
+    }
+
+    /// Whether the linked account is on the top-level server, the only one
+    /// admins manage accounts on.
+    pub fn on_primary(&self) -> bool {
+        self.account_server() == DEFAULT_SERVER_ID
+    }
+}
+
+/// Row for the table of username and IP bans.
+#[derive(Debug, FromRow)]
+pub struct Ban {
+    pub id: i64,
+    /// [`BanKind`](crate::types::BanKind) as stored.
+    pub kind: String,
+    /// `TeamTalk` username, or an IP address or CIDR network.
+    pub value: String,
+    pub banned_at: NaiveDateTime,
+    pub reason: Option<String>,
+}
+
+/// Row for per-admin preferences.
+#[derive(Debug, FromRow)]
+pub struct AdminSettings {
+    pub admin_id: TelegramId,
+    /// Comma-separated [`NotificationKind`]s the admin turned off.
+    pub muted_notifications: String,
+    /// `HH:MM-HH:MM`, or [`QUIET_HOURS_OFF`]; `None` follows the configured
+    /// default.
+    pub quiet_hours: Option<String>,
+}
+
+/// Stored `quiet_hours` of an admin who turned the default off.
+pub const QUIET_HOURS_OFF: &str = "off";
+
+impl AdminSettings {
+    /// The admin's quiet hours, given the configured `default`.
+    pub fn quiet_hours(&self, default: Option<QuietHours>) -> Option<QuietHours> {
+        match self.quiet_hours.as_deref() {
+            None => default,
+            Some(QUIET_HOURS_OFF) => None,
+            Some(window) => QuietHours::parse(window),
+        }
+    }
+
+    /// Whether the admin turned `kind` off.
+    pub fn mutes(&self, kind: NotificationKind) -> bool {
+        self.muted_notifications
+            .split(',')
+            .any(|muted| muted == kind.as_str())
+    }
+}
+
+/// Notification held back during an admin's quiet hours.
+#[derive(Debug, FromRow)]
+pub struct QueuedNotification {
+    pub id: i64,
+    pub admin_id: TelegramId,
+    /// [`NotificationKind`] as stored.
+    pub kind: String,
+    pub text: String,
+    pub created_at: NaiveDateTime,
 }
 
 /// Row for download tokens table.
@@ -45,6 +153,16 @@ pub struct FastapiDownloadToken {
     pub created_at: NaiveDateTime,
     pub expires_at: NaiveDateTime,
     pub is_used: bool,
+    /// Downloads left; `None` when the link only expires by time.
+    pub uses_remaining: Option<i64>,
+    /// `.tt` content of a client ZIP generated on the fly; sealed at rest.
+    pub zip_source: Option<String>,
+    /// Readme bundled with a ZIP generated on the fly.
+    pub zip_readme: Option<String>,
+    /// Password of a ZIP generated on the fly; sealed at rest.
+    pub zip_password: Option<String>,
+    /// Client template of a ZIP token.
+    pub client_template: Option<String>,
 }
 
 /// Row for registered IP table.
@@ -57,7 +175,7 @@ pub struct FastapiRegisteredIp {
 }
 
 /// Row for deeplink tokens table.
-#[derive(Debug, FromRow)]
+#[derive(Clone, Debug, FromRow)]
 #[allow(dead_code)]
 pub struct DeeplinkToken {
     pub id: Option<i64>,
@@ -65,5 +183,67 @@ pub struct DeeplinkToken {
     pub created_at: NaiveDateTime,
     pub expires_at: NaiveDateTime,
     pub is_used: bool,
+    pub uses_remaining: i64,
     pub generated_by_admin_id: Option<i64>,
+    /// Server the invite registers on; users pick one when unset.
+    pub server_id: Option<String>,
+}
+
+/// Row for web rate limit offenders table.
+#[derive(Debug, FromRow)]
+#[allow(dead_code)]
+pub struct RateLimitOffender {
+    pub subject: String,
+    pub blocked_until: NaiveDateTime,
+    pub hits: i64,
+    pub last_seen: NaiveDateTime,
+}
+
+/// Aggregate counts for the admin `/stats` command; `today` counts start at UTC midnight.
+#[derive(Debug, FromRow)]
+pub struct RegistrationStats {
+    pub telegram_total: i64,
+    pub telegram_today: i64,
+    /// Telegram registrations an admin approved.
+    pub telegram_approved: i64,
+    pub web_total: i64,
+    pub web_today: i64,
+    pub web_approved: i64,
+    pub web_approved_today: i64,
+    pub web_rejected: i64,
+    pub web_rejected_today: i64,
+    pub web_pending: i64,
+    pub banned: i64,
+    pub deeplinks_issued: i64,
+    pub deeplinks_used: i64,
+}
+
+/// Row for audit log table.
+#[derive(Debug, FromRow)]
+pub struct AuditEntry {
+    pub created_at: NaiveDateTime,
+    pub actor: String,
+    pub action: String,
+    pub target: String,
+    pub details: Option<String>,
+}
+
+/// Row for the queue of registrations waiting for `TeamTalk` to come back.
+#[derive(Debug, FromRow)]
+pub struct QueuedRegistration {
+    pub id: i64,
+    pub created_at: NaiveDateTime,
+    pub username: String,
+    pub password: String,
+    pub nickname: String,
+    pub account_type: String,
+    pub rights_profile: String,
+    pub source: String,
+    pub source_info: Option<SourceInfo>,
+    pub telegram_id: Option<TelegramId>,
+    pub language: Option<String>,
+    pub server_id: String,
+    /// Set while the account may have reached `TeamTalk` without being
+    /// recorded, e.g. across a crash.
+    pub attempted_at: Option<NaiveDateTime>,
 }
@@ -0,0 +1,94 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Marks a stored password as encrypted; anything else is a legacy cleartext value.
+pub const SEALED_PREFIX: &str = "sealed:v1:";
+
+const KEY_LEN: usize = 32;
+
+/// Encrypts passwords of pending registrations before they reach the database.
+#[derive(Clone)]
+pub struct PasswordSealer {
+    key: Arc<LessSafeKey>,
+}
+
+impl PasswordSealer {
+    /// Build a sealer from a base64-encoded 32-byte key.
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        let raw = BASE64
+            .decode(encoded.trim())
+            .context("Pending password key is not valid base64")?;
+        if raw.len() != KEY_LEN {
+            anyhow::bail!(
+                "Pending password key must be {KEY_LEN} bytes, got {}",
+                raw.len()
+            );
+        }
+        let key = UnboundKey::new(&AES_256_GCM, &raw)
+            .map_err(|_| anyhow::anyhow!("Invalid pending password key"))?;
+        Ok(Self {
+            key: Arc::new(LessSafeKey::new(key)),
+        })
+    }
+
+    /// Read the key from `path`, generating it (owner-only permissions) on first start.
+    pub fn load_or_create(path: &Path) -> Result<Self> {
+        if path.exists() {
+            let encoded = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            return Self::from_base64(&encoded);
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let encoded = BASE64.encode(rand::random::<[u8; KEY_LEN]>());
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options
+            .open(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        writeln!(file, "{encoded}")?;
+        tracing::info!(path = %path.display(), "Generated pending password key");
+        Self::from_base64(&encoded)
+    }
+
+    /// Encrypt `password` for storage.
+    pub fn seal(&self, password: &str) -> Result<String> {
+        let nonce = rand::random::<[u8; NONCE_LEN]>();
+        let mut buf = password.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut buf)
+            .map_err(|_| anyhow::anyhow!("Failed to encrypt pending password"))?;
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&buf);
+        Ok(format!("{SEALED_PREFIX}{}", BASE64.encode(payload)))
+    }
+
+    /// Decrypt a stored password; legacy cleartext values are returned unchanged.
+    pub fn open(&self, stored: &str) -> Result<String> {
+        let Some(encoded) = stored.strip_prefix(SEALED_PREFIX) else {
+            return Ok(stored.to_string());
+        };
+        let mut payload = BASE64
+            .decode(encoded)
+            .context("Sealed password is not valid base64")?;
+        if payload.len() < NONCE_LEN {
+            anyhow::bail!("Sealed password is truncated");
+        }
+        let mut ciphertext = payload.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&payload)
+            .map_err(|_| anyhow::anyhow!("Sealed password has a bad nonce"))?;
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut ciphertext)
+            .map_err(|_| anyhow::anyhow!("Failed to decrypt pending password; wrong key?"))?;
+        Ok(String::from_utf8(plaintext.to_vec())?)
+    }
+}
@@ -0,0 +1,1521 @@
+use super::WEB_CLAIM_TTL_SECONDS;
+use super::schema::{
+    AdminSettings, AuditEntry, Ban, BannedUser, DeeplinkToken, FastapiDownloadToken,
+    PendingTelegramRegistration, PendingWebRegistration, QueuedNotification, QueuedRegistration,
+    RateLimitOffender, RegistrationStats, TelegramRegistration,
+};
+use super::sealing::{PasswordSealer, SEALED_PREFIX};
+use super::{
+    NewDownloadToken, NewPendingWebRegistration, NewQueuedRegistration, NewRegistration,
+    legacy_queued_source, legacy_telegram_source, legacy_web_source,
+};
+use crate::types::{SourceInfo, TelegramId, WebRequestStatus};
+use anyhow::Result;
+use chrono::{NaiveDateTime, Utc};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Pool, Row, Sqlite};
+use std::collections::HashSet;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::{error, info, instrument, trace};
+
+/// `SQLite` backend; queries are checked at compile time against `.sqlx`.
+#[derive(Clone)]
+pub struct SqliteDb {
+    pool: Pool<Sqlite>,
+}
+
+static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations");
+
+impl SqliteDb {
+    /// Open (creating if needed) and migrate the database file.
+    pub async fn new(db_filename: &str) -> Result<Self> {
+        let db_url = format!("sqlite://{db_filename}");
+
+        if !Path::new(db_filename).exists() {
+            if let Some(parent) = Path::new(db_filename).parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::File::create(db_filename)?;
+        }
+
+        let connect_options = SqliteConnectOptions::from_str(&db_url)?
+            .create_if_missing(true)
+            .busy_timeout(Duration::from_secs(5));
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(5)
+            .after_connect(|conn, _meta| {
+                Box::pin(async move {
+                    sqlx::query("PRAGMA journal_mode = WAL;")
+                        .execute(&mut *conn)
+                        .await?;
+                    sqlx::query("PRAGMA synchronous = NORMAL;")
+                        .execute(&mut *conn)
+                        .await?;
+                    sqlx::query("PRAGMA foreign_keys = ON;")
+                        .execute(&mut *conn)
+                        .await?;
+                    sqlx::query("PRAGMA temp_store = MEMORY;")
+                        .execute(&mut *conn)
+                        .await?;
+                    Ok(())
+                })
+            })
+            .connect_with(connect_options)
+            .await?;
+
+        MIGRATOR.run(&pool).await?;
+        integrity_check(&pool).await?;
+        validate_schema(&pool).await?;
+        Ok(Self { pool })
+    }
+
+    /// `ping` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn ping(&self) -> Result<()> {
+        sqlx::query!("SELECT 1 as \"one!: i64\"")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// `is_telegram_registered` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn is_telegram_registered(&self, tg_id: TelegramId) -> Result<bool> {
+        let count: i64 = sqlx::query_scalar!(
+            "SELECT count(*) FROM telegram_registrations WHERE telegram_id = ?",
+            tg_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count > 0)
+    }
+
+    /// Language stored for a registered Telegram user, if any.
+    #[instrument(skip(self), err)]
+    pub async fn get_registration_language(&self, tg_id: TelegramId) -> Result<Option<String>> {
+        let language = sqlx::query_scalar!(
+            "SELECT language as \"language?: String\" FROM telegram_registrations WHERE telegram_id = ?",
+            tg_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(language.flatten())
+    }
+
+    /// Remember the language a registered Telegram user picked.
+    #[instrument(skip(self), err)]
+    pub async fn set_registration_language(&self, tg_id: TelegramId, language: &str) -> Result<()> {
+        sqlx::query!(
+            "UPDATE telegram_registrations SET language = ? WHERE telegram_id = ?",
+            language,
+            tg_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `delete_registration` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn delete_registration(&self, tg_id: TelegramId) -> Result<bool> {
+        let res = sqlx::query!(
+            "DELETE FROM telegram_registrations WHERE telegram_id = ?",
+            tg_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// `get_all_registrations` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_all_registrations(&self) -> Result<Vec<TelegramRegistration>> {
+        let users = sqlx::query_as!(
+            TelegramRegistration,
+            "SELECT telegram_id as \"telegram_id!: TelegramId\", teamtalk_username as \"teamtalk_username!: String\", nickname as \"nickname?: String\", created_at as \"created_at?: chrono::NaiveDateTime\", server_id as \"server_id!: String\", source as \"source?: String\" FROM telegram_registrations ORDER BY teamtalk_username COLLATE NOCASE, telegram_id"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(users)
+    }
+
+    /// `get_registration_by_id` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_registration_by_id(
+        &self,
+        tg_id: TelegramId,
+    ) -> Result<Option<TelegramRegistration>> {
+        let user = sqlx::query_as!(
+            TelegramRegistration,
+            "SELECT telegram_id as \"telegram_id!: TelegramId\", teamtalk_username as \"teamtalk_username!: String\", nickname as \"nickname?: String\", created_at as \"created_at?: chrono::NaiveDateTime\", server_id as \"server_id!: String\", source as \"source?: String\" FROM telegram_registrations WHERE telegram_id = ?",
+            tg_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(user)
+    }
+
+    /// `get_registration_by_tt_username` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_registration_by_tt_username(
+        &self,
+        tt_username: &str,
+        server_id: &str,
+    ) -> Result<Option<TelegramRegistration>> {
+        let user = sqlx::query_as!(
+            TelegramRegistration,
+            "SELECT telegram_id as \"telegram_id!: TelegramId\", teamtalk_username as \"teamtalk_username!: String\", nickname as \"nickname?: String\", created_at as \"created_at?: chrono::NaiveDateTime\", server_id as \"server_id!: String\", source as \"source?: String\" FROM telegram_registrations WHERE teamtalk_username = ? AND server_id = ?",
+            tt_username,
+            server_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(user)
+    }
+
+    /// `add_pending_registration` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn add_pending_registration(
+        &self,
+        key: &str,
+        tg_id: TelegramId,
+        username: &str,
+        password: &str,
+        nickname: &str,
+        source_info: &SourceInfo,
+    ) -> Result<()> {
+        let source_info = source_info.to_json();
+        sqlx::query!(
+            "INSERT INTO pending_telegram_registrations (request_key, registrant_telegram_id, username, password_cleartext, nickname, source_info) VALUES (?, ?, ?, ?, ?, ?)",
+            key,
+            tg_id,
+            username,
+            password,
+            nickname,
+            source_info
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `get_pending_registration` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_pending_registration(
+        &self,
+        key: &str,
+    ) -> Result<Option<PendingTelegramRegistration>> {
+        let reg = sqlx::query_as!(
+            PendingTelegramRegistration,
+            "SELECT id as \"id?: i64\", request_key as \"request_key!: String\", registrant_telegram_id as \"registrant_telegram_id!: TelegramId\", username as \"username!: String\", password_cleartext as \"password_cleartext!: String\", nickname as \"nickname!: String\", source_info as \"source_info!: SourceInfo\", created_at as \"created_at!: chrono::NaiveDateTime\" FROM pending_telegram_registrations WHERE request_key = ?",
+            key
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(reg)
+    }
+
+    /// `get_all_pending_registrations` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_all_pending_registrations(&self) -> Result<Vec<PendingTelegramRegistration>> {
+        let regs = sqlx::query_as!(
+            PendingTelegramRegistration,
+            "SELECT id as \"id?: i64\", request_key as \"request_key!: String\", registrant_telegram_id as \"registrant_telegram_id!: TelegramId\", username as \"username!: String\", password_cleartext as \"password_cleartext!: String\", nickname as \"nickname!: String\", source_info as \"source_info!: SourceInfo\", created_at as \"created_at!: chrono::NaiveDateTime\" FROM pending_telegram_registrations ORDER BY created_at DESC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(regs)
+    }
+
+    /// Atomically take a pending registration so only one admin decides it.
+    ///
+    /// The row is removed and returned; `None` means another admin already
+    /// handled the request.
+    #[instrument(skip(self), err)]
+    pub async fn claim_pending_registration(
+        &self,
+        key: &str,
+    ) -> Result<Option<PendingTelegramRegistration>> {
+        let reg = sqlx::query_as!(
+            PendingTelegramRegistration,
+            "DELETE FROM pending_telegram_registrations WHERE request_key = ? RETURNING id as \"id?: i64\", request_key as \"request_key!: String\", registrant_telegram_id as \"registrant_telegram_id!: TelegramId\", username as \"username!: String\", password_cleartext as \"password_cleartext!: String\", nickname as \"nickname!: String\", source_info as \"source_info!: SourceInfo\", created_at as \"created_at!: chrono::NaiveDateTime\"",
+            key
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(reg)
+    }
+
+    /// Remove undecided Telegram requests older than `ttl_seconds`.
+    #[instrument(skip(self), err)]
+    pub async fn expire_pending_registrations(
+        &self,
+        ttl_seconds: u64,
+    ) -> Result<Vec<PendingTelegramRegistration>> {
+        let ttl = format!("-{ttl_seconds} seconds");
+        let regs = sqlx::query_as!(
+            PendingTelegramRegistration,
+            "DELETE FROM pending_telegram_registrations WHERE created_at < datetime('now', ?) RETURNING id as \"id?: i64\", request_key as \"request_key!: String\", registrant_telegram_id as \"registrant_telegram_id!: TelegramId\", username as \"username!: String\", password_cleartext as \"password_cleartext!: String\", nickname as \"nickname!: String\", source_info as \"source_info!: SourceInfo\", created_at as \"created_at!: chrono::NaiveDateTime\"",
+            ttl
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(regs)
+    }
+
+    /// `add_pending_web_registration` database operation.
+    #[instrument(skip(self, reg), fields(username = reg.username), err)]
+    pub async fn add_pending_web_registration(
+        &self,
+        reg: &NewPendingWebRegistration<'_>,
+    ) -> Result<()> {
+        let NewPendingWebRegistration {
+            key,
+            username,
+            password,
+            nickname,
+            ip_address,
+            user_agent,
+            source_info,
+        } = reg;
+        let source_info = source_info.to_json();
+        sqlx::query!(
+            "INSERT INTO pending_web_registrations (request_key, username, password_cleartext, nickname, ip_address, user_agent, source_info) VALUES (?, ?, ?, ?, ?, ?, ?)",
+            key,
+            username,
+            password,
+            nickname,
+            ip_address,
+            user_agent,
+            source_info
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `get_pending_web_registration` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_pending_web_registration(
+        &self,
+        key: &str,
+    ) -> Result<Option<PendingWebRegistration>> {
+        let reg = sqlx::query_as!(
+            PendingWebRegistration,
+            "SELECT id as \"id?: i64\", request_key as \"request_key!: String\", username as \"username!: String\", password_cleartext as \"password_cleartext!: String\", nickname as \"nickname!: String\", ip_address as \"ip_address!: String\", user_agent as \"user_agent?: String\", source_info as \"source_info!: SourceInfo\", created_at as \"created_at!: chrono::NaiveDateTime\", status as \"status!: String\", decided_at as \"decided_at?: chrono::NaiveDateTime\", decision_reason as \"decision_reason?: String\" FROM pending_web_registrations WHERE request_key = ?",
+            key
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(reg)
+    }
+
+    /// `get_undecided_web_registrations` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_undecided_web_registrations(&self) -> Result<Vec<PendingWebRegistration>> {
+        let regs = sqlx::query_as!(
+            PendingWebRegistration,
+            "SELECT id as \"id?: i64\", request_key as \"request_key!: String\", username as \"username!: String\", password_cleartext as \"password_cleartext!: String\", nickname as \"nickname!: String\", ip_address as \"ip_address!: String\", user_agent as \"user_agent?: String\", source_info as \"source_info!: SourceInfo\", created_at as \"created_at!: chrono::NaiveDateTime\", status as \"status!: String\", decided_at as \"decided_at?: chrono::NaiveDateTime\", decision_reason as \"decision_reason?: String\" FROM pending_web_registrations WHERE status = 'pending' ORDER BY created_at DESC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(regs)
+    }
+
+    /// Atomically claim a pending web registration for `admin`.
+    ///
+    /// Returns `None` if the request was already decided or another admin holds
+    /// a claim younger than [`WEB_CLAIM_TTL_SECONDS`].
+    #[instrument(skip(self), err)]
+    pub async fn claim_web_registration(
+        &self,
+        key: &str,
+        admin: TelegramId,
+    ) -> Result<Option<PendingWebRegistration>> {
+        let now = Utc::now().naive_utc();
+        let stale = now - chrono::Duration::seconds(WEB_CLAIM_TTL_SECONDS);
+        let reg = sqlx::query_as!(
+            PendingWebRegistration,
+            "UPDATE pending_web_registrations SET claimed_by = ?, claimed_at = ? WHERE request_key = ? AND status = 'pending' AND (claimed_by IS NULL OR claimed_at < ?) RETURNING id as \"id?: i64\", request_key as \"request_key!: String\", username as \"username!: String\", password_cleartext as \"password_cleartext!: String\", nickname as \"nickname!: String\", ip_address as \"ip_address!: String\", user_agent as \"user_agent?: String\", source_info as \"source_info!: SourceInfo\", created_at as \"created_at!: chrono::NaiveDateTime\", status as \"status!: String\", decided_at as \"decided_at?: chrono::NaiveDateTime\", decision_reason as \"decision_reason?: String\"",
+            admin,
+            now,
+            key,
+            stale
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(reg)
+    }
+
+    /// `set_web_registration_status` database operation.
+    ///
+    /// Only pending requests claimed by `admin` are updated; the stored password
+    /// is wiped once a decision is recorded, except on approval, where it is
+    /// kept until the user collects their downloads. Returns `false` if the request was
+    /// already decided or claimed by someone else.
+    #[instrument(skip(self), err)]
+    pub async fn set_web_registration_status(
+        &self,
+        key: &str,
+        admin: TelegramId,
+        status: WebRequestStatus,
+        reason: Option<&str>,
+    ) -> Result<bool> {
+        let now = Utc::now().naive_utc();
+        let status_str = status.as_str();
+        let res = sqlx::query!(
+            "UPDATE pending_web_registrations SET status = ?, decided_at = ?, decision_reason = ?, password_cleartext = CASE WHEN ? = 'approved' THEN password_cleartext ELSE '' END WHERE request_key = ? AND status = 'pending' AND claimed_by = ?",
+            status_str,
+            now,
+            reason,
+            status_str,
+            key,
+            admin
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Take the password kept for an approved web request, wiping it.
+    #[instrument(skip(self), err)]
+    pub async fn take_web_registration_password(&self, key: &str) -> Result<Option<String>> {
+        let password = sqlx::query_scalar!(
+            "UPDATE pending_web_registrations SET password_cleartext = '' WHERE request_key = ? AND status = 'approved' AND password_cleartext <> '' RETURNING password_cleartext as \"password_cleartext!: String\"",
+            key
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(password)
+    }
+
+    /// Change the username of a Telegram request still awaiting approval.
+    #[instrument(skip(self), err)]
+    pub async fn rename_pending_registration(&self, key: &str, username: &str) -> Result<bool> {
+        let res = sqlx::query!(
+            "UPDATE pending_telegram_registrations SET nickname = CASE WHEN nickname = username THEN ? ELSE nickname END, username = ? WHERE request_key = ?",
+            username,
+            username,
+            key
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Change the username of an undecided web request.
+    #[instrument(skip(self), err)]
+    pub async fn rename_pending_web_registration(&self, key: &str, username: &str) -> Result<bool> {
+        let res = sqlx::query!(
+            "UPDATE pending_web_registrations SET nickname = CASE WHEN nickname = username THEN ? ELSE nickname END, username = ? WHERE request_key = ? AND status = 'pending'",
+            username,
+            username,
+            key
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// `get_banned_user` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_banned_user(&self, tg_id: TelegramId) -> Result<Option<BannedUser>> {
+        let user = sqlx::query_as!(
+            BannedUser,
+            "SELECT telegram_id as \"telegram_id!: TelegramId\", teamtalk_username as \"teamtalk_username?: String\", banned_at as \"banned_at!: chrono::NaiveDateTime\", banned_by_admin_id as \"banned_by_admin_id?: TelegramId\", reason as \"reason?: String\", server_id as \"server_id?: String\" FROM banned_users WHERE telegram_id = ?",
+            tg_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(user)
+    }
+
+    /// `get_all_banned_users` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_all_banned_users(&self) -> Result<Vec<BannedUser>> {
+        let users = sqlx::query_as!(
+            BannedUser,
+            "SELECT telegram_id as \"telegram_id!: TelegramId\", teamtalk_username as \"teamtalk_username?: String\", banned_at as \"banned_at!: chrono::NaiveDateTime\", banned_by_admin_id as \"banned_by_admin_id?: TelegramId\", reason as \"reason?: String\", server_id as \"server_id?: String\" FROM banned_users ORDER BY banned_at DESC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(users)
+    }
+
+    /// `ban_user` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn ban_user(
+        &self,
+        tg_id: TelegramId,
+        tt_username: Option<&str>,
+        server_id: Option<&str>,
+        admin_id: Option<TelegramId>,
+        reason: Option<&str>,
+    ) -> Result<()> {
+        trace!(
+            tg_id = %tg_id,
+            admin_id = ?admin_id.map(TelegramId::as_i64),
+            "Banning user"
+        );
+        let now = Utc::now().naive_utc();
+        sqlx::query!(
+            "INSERT OR REPLACE INTO banned_users (telegram_id, teamtalk_username, banned_at, banned_by_admin_id, reason, server_id) VALUES (?, ?, ?, ?, ?, ?)",
+            tg_id,
+            tt_username,
+            now,
+            admin_id,
+            reason,
+            server_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `unban_user` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn unban_user(&self, tg_id: TelegramId) -> Result<bool> {
+        let res = sqlx::query!("DELETE FROM banned_users WHERE telegram_id = ?", tg_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Ban a `TeamTalk` username or an IP network; banning it again
+    /// replaces the reason. Returns the ban's id.
+    #[instrument(skip(self), err)]
+    pub async fn add_ban(&self, kind: &str, value: &str, reason: Option<&str>) -> Result<i64> {
+        let now = Utc::now().naive_utc();
+        let id = sqlx::query_scalar!(
+            "INSERT INTO bans (kind, value, banned_at, reason) VALUES (?, ?, ?, ?) ON CONFLICT (kind, value) DO UPDATE SET banned_at = excluded.banned_at, reason = excluded.reason RETURNING id as \"id!: i64\"",
+            kind,
+            value,
+            now,
+            reason
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(id)
+    }
+
+    /// Username and IP bans, newest first.
+    #[instrument(skip(self), err)]
+    pub async fn get_bans(&self) -> Result<Vec<Ban>> {
+        let bans = sqlx::query_as!(
+            Ban,
+            "SELECT id as \"id!: i64\", kind as \"kind!: String\", value as \"value!: String\", banned_at as \"banned_at!: chrono::NaiveDateTime\", reason as \"reason?: String\" FROM bans ORDER BY banned_at DESC, id DESC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(bans)
+    }
+
+    /// Lift a username or IP ban.
+    #[instrument(skip(self), err)]
+    pub async fn remove_ban(&self, id: i64) -> Result<bool> {
+        let res = sqlx::query!("DELETE FROM bans WHERE id = ?", id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Addresses `username` was registered from.
+    #[instrument(skip(self), err)]
+    pub async fn get_registration_ips(&self, username: &str) -> Result<Vec<String>> {
+        let ips = sqlx::query_scalar!(
+            "SELECT DISTINCT ip_address as \"ip_address!: String\" FROM fastapi_registered_ips WHERE username = ?",
+            username
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(ips)
+    }
+
+    /// Preferences of every admin who changed any.
+    #[instrument(skip(self), err)]
+    pub async fn get_admin_settings(&self) -> Result<Vec<AdminSettings>> {
+        let settings = sqlx::query_as!(
+            AdminSettings,
+            "SELECT admin_id as \"admin_id!: TelegramId\", muted_notifications as \"muted_notifications!: String\", quiet_hours as \"quiet_hours?: String\" FROM admin_settings"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(settings)
+    }
+
+    /// Store the notification kinds `admin_id` turned off.
+    #[instrument(skip(self), err)]
+    pub async fn set_muted_notifications(&self, admin_id: TelegramId, muted: &str) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO admin_settings (admin_id, muted_notifications) VALUES (?, ?) ON CONFLICT (admin_id) DO UPDATE SET muted_notifications = excluded.muted_notifications",
+            admin_id,
+            muted
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Store `admin_id`'s quiet hours; `None` follows the configured default.
+    #[instrument(skip(self), err)]
+    pub async fn set_quiet_hours(
+        &self,
+        admin_id: TelegramId,
+        quiet_hours: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO admin_settings (admin_id, quiet_hours) VALUES (?, ?) ON CONFLICT (admin_id) DO UPDATE SET quiet_hours = excluded.quiet_hours",
+            admin_id,
+            quiet_hours
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Hold a notification back for `admin_id`'s next digest.
+    #[instrument(skip(self, text), err)]
+    pub async fn queue_notification(
+        &self,
+        admin_id: TelegramId,
+        kind: &str,
+        text: &str,
+    ) -> Result<()> {
+        let now = Utc::now().naive_utc();
+        sqlx::query!(
+            "INSERT INTO queued_notifications (admin_id, kind, text, created_at) VALUES (?, ?, ?, ?)",
+            admin_id,
+            kind,
+            text,
+            now
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Held-back notifications of every admin, oldest first.
+    #[instrument(skip(self), err)]
+    pub async fn get_queued_notifications(&self) -> Result<Vec<QueuedNotification>> {
+        let rows = sqlx::query_as!(
+            QueuedNotification,
+            "SELECT id as \"id!: i64\", admin_id as \"admin_id!: TelegramId\", kind as \"kind!: String\", text as \"text!: String\", created_at as \"created_at!: chrono::NaiveDateTime\" FROM queued_notifications ORDER BY id"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Drop `admin_id`'s held-back notifications up to and including `up_to_id`.
+    #[instrument(skip(self), err)]
+    pub async fn delete_queued_notifications(
+        &self,
+        admin_id: TelegramId,
+        up_to_id: i64,
+    ) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM queued_notifications WHERE admin_id = ? AND id <= ?",
+            admin_id,
+            up_to_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Registrations recorded for `ip` since `since`.
+    #[instrument(skip(self), err)]
+    pub async fn count_ip_registrations(&self, ip: &str, since: NaiveDateTime) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar!(
+            "SELECT count(*) FROM fastapi_registered_ips WHERE ip_address = ? AND registration_timestamp >= ?",
+            ip,
+            since
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count)
+    }
+
+    /// `add_registered_ip` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn add_registered_ip(&self, ip: &str, username: Option<&str>) -> Result<()> {
+        let now = Utc::now().naive_utc();
+        sqlx::query!(
+            "INSERT INTO fastapi_registered_ips (ip_address, username, registration_timestamp) VALUES (?, ?, ?)",
+            ip,
+            username,
+            now
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `add_download_token` database operation.
+    #[instrument(skip(self, new), fields(file = new.filepath), err)]
+    pub async fn add_download_token(&self, new: &NewDownloadToken<'_>) -> Result<()> {
+        let now = Utc::now().naive_utc();
+        let token_type_str = new.token_type.as_str();
+        let uses_remaining = new.max_uses.map(i64::from);
+        sqlx::query!(
+            "INSERT INTO fastapi_download_tokens (token, filepath_on_server, original_filename, token_type, created_at, expires_at, is_used, uses_remaining, zip_source, zip_readme, zip_password, client_template) VALUES (?, ?, ?, ?, ?, ?, 0, ?, ?, ?, ?, ?)",
+            new.token,
+            new.filepath,
+            new.original_name,
+            token_type_str,
+            now,
+            new.expires_at,
+            uses_remaining,
+            new.zip_source,
+            new.zip_readme,
+            new.zip_password,
+            new.client_template
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `get_download_token` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_download_token(&self, token: &str) -> Result<Option<FastapiDownloadToken>> {
+        let now = Utc::now().naive_utc();
+        let tok = sqlx::query_as!(
+            FastapiDownloadToken,
+            "SELECT token as \"token!: String\", filepath_on_server as \"filepath_on_server!: String\", original_filename as \"original_filename!: String\", token_type as \"token_type!: String\", created_at as \"created_at!: chrono::NaiveDateTime\", expires_at as \"expires_at!: chrono::NaiveDateTime\", is_used as \"is_used!: bool\", uses_remaining as \"uses_remaining?: i64\", zip_source as \"zip_source?: String\", zip_readme as \"zip_readme?: String\", zip_password as \"zip_password?: String\", client_template as \"client_template?: String\" FROM fastapi_download_tokens WHERE token = ? AND is_used = 0 AND (uses_remaining IS NULL OR uses_remaining > 0) AND expires_at > ?",
+            token,
+            now
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(tok)
+    }
+
+    /// `get_active_download_tokens` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_active_download_tokens(&self) -> Result<Vec<FastapiDownloadToken>> {
+        let now = Utc::now().naive_utc();
+        let tokens = sqlx::query_as!(
+            FastapiDownloadToken,
+            "SELECT token as \"token!: String\", filepath_on_server as \"filepath_on_server!: String\", original_filename as \"original_filename!: String\", token_type as \"token_type!: String\", created_at as \"created_at!: chrono::NaiveDateTime\", expires_at as \"expires_at!: chrono::NaiveDateTime\", is_used as \"is_used!: bool\", uses_remaining as \"uses_remaining?: i64\", zip_source as \"zip_source?: String\", zip_readme as \"zip_readme?: String\", zip_password as \"zip_password?: String\", client_template as \"client_template?: String\" FROM fastapi_download_tokens WHERE is_used = 0 AND (uses_remaining IS NULL OR uses_remaining > 0) AND expires_at > ? ORDER BY created_at DESC",
+            now
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(tokens)
+    }
+
+    /// Take one use of a limited download token; `None` when none is left,
+    /// otherwise whether it was the last.
+    #[instrument(skip(self), err)]
+    pub async fn consume_download_token(&self, token: &str) -> Result<Option<bool>> {
+        let exhausted = sqlx::query_scalar!(
+            "UPDATE fastapi_download_tokens SET uses_remaining = uses_remaining - 1, is_used = (uses_remaining <= 1) WHERE token = ? AND uses_remaining > 0 RETURNING is_used as \"is_used!: bool\"",
+            token
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(exhausted)
+    }
+
+    /// Give back a use of a download token whose transfer was aborted.
+    #[instrument(skip(self), err)]
+    pub async fn restore_download_token(&self, token: &str) -> Result<()> {
+        sqlx::query!(
+            "UPDATE fastapi_download_tokens SET uses_remaining = uses_remaining + 1, is_used = 0 WHERE token = ? AND uses_remaining IS NOT NULL",
+            token
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Delete a download token so its link stops working; `false` when it
+    /// was already gone.
+    #[instrument(skip(self), err)]
+    pub async fn revoke_download_token(&self, token: &str) -> Result<bool> {
+        let res = sqlx::query!("DELETE FROM fastapi_download_tokens WHERE token = ?", token)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Whether the signed link `token_id` is neither revoked nor used up.
+    #[instrument(skip(self), err)]
+    pub async fn signed_link_usable(&self, token_id: &str) -> Result<bool> {
+        let spent = sqlx::query_scalar!(
+            "SELECT token_id as \"token_id!: String\" FROM signed_download_links WHERE token_id = ? AND (revoked_at IS NOT NULL OR uses_remaining <= 0)",
+            token_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(spent.is_none())
+    }
+
+    /// Take one use of the signed link `token_id`, which allows `max_uses`
+    /// until `expires_at`; `None` when none is left, otherwise whether it was
+    /// the last.
+    #[instrument(skip(self), err)]
+    pub async fn consume_signed_link(
+        &self,
+        token_id: &str,
+        max_uses: i64,
+        expires_at: NaiveDateTime,
+    ) -> Result<Option<bool>> {
+        let first_remaining = max_uses - 1;
+        let remaining = sqlx::query_scalar!(
+            "INSERT INTO signed_download_links (token_id, uses_remaining, expires_at) VALUES (?, ?, ?) ON CONFLICT (token_id) DO UPDATE SET uses_remaining = uses_remaining - 1 WHERE uses_remaining > 0 RETURNING uses_remaining as \"uses_remaining!: i64\"",
+            token_id,
+            first_remaining,
+            expires_at
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(remaining.map(|remaining| remaining <= 0))
+    }
+
+    /// Give back a use of the signed link `token_id` whose transfer was aborted.
+    #[instrument(skip(self), err)]
+    pub async fn restore_signed_link(&self, token_id: &str) -> Result<()> {
+        sqlx::query!(
+            "UPDATE signed_download_links SET uses_remaining = uses_remaining + 1 WHERE token_id = ?",
+            token_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Revoke the signed link `token_id`, kept on the list until `expires_at`;
+    /// `false` when it was already revoked.
+    #[instrument(skip(self), err)]
+    pub async fn revoke_signed_link(
+        &self,
+        token_id: &str,
+        expires_at: NaiveDateTime,
+    ) -> Result<bool> {
+        let now = Utc::now().naive_utc();
+        let res = sqlx::query!(
+            "INSERT INTO signed_download_links (token_id, expires_at, revoked_at) VALUES (?, ?, ?) ON CONFLICT (token_id) DO UPDATE SET revoked_at = excluded.revoked_at WHERE revoked_at IS NULL",
+            token_id,
+            expires_at,
+            now
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Store a new invite token; returns its id.
+    #[instrument(skip(self), err)]
+    pub async fn create_deeplink(
+        &self,
+        token: &str,
+        expires_at: chrono::NaiveDateTime,
+        max_uses: i64,
+        admin_id: TelegramId,
+        server_id: Option<&str>,
+    ) -> Result<i64> {
+        let res = sqlx::query!(
+            "INSERT INTO deeplink_tokens (token, expires_at, uses_remaining, generated_by_admin_id, created_at, server_id) VALUES (?, ?, ?, ?, datetime('now'), ?)",
+            token,
+            expires_at,
+            max_uses,
+            admin_id,
+            server_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(res.last_insert_rowid())
+    }
+
+    /// `get_valid_deeplink` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_valid_deeplink(&self, token: &str) -> Result<Option<DeeplinkToken>> {
+        let now = Utc::now().naive_utc();
+        let token_obj = sqlx::query_as!(
+            DeeplinkToken,
+            "SELECT id as \"id?: i64\", token as \"token!: String\", created_at as \"created_at!: chrono::NaiveDateTime\", expires_at as \"expires_at!: chrono::NaiveDateTime\", is_used as \"is_used!: bool\", uses_remaining as \"uses_remaining!: i64\", generated_by_admin_id as \"generated_by_admin_id?: i64\", server_id as \"server_id?: String\" FROM deeplink_tokens WHERE token = ? AND uses_remaining > 0 AND expires_at > ?",
+            token,
+            now
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(token_obj)
+    }
+
+    /// Invite tokens that have not expired yet, soonest expiry first.
+    #[instrument(skip(self), err)]
+    pub async fn get_active_deeplinks(&self) -> Result<Vec<DeeplinkToken>> {
+        let now = Utc::now().naive_utc();
+        let tokens = sqlx::query_as!(
+            DeeplinkToken,
+            "SELECT id as \"id?: i64\", token as \"token!: String\", created_at as \"created_at!: chrono::NaiveDateTime\", expires_at as \"expires_at!: chrono::NaiveDateTime\", is_used as \"is_used!: bool\", uses_remaining as \"uses_remaining!: i64\", generated_by_admin_id as \"generated_by_admin_id?: i64\", server_id as \"server_id?: String\" FROM deeplink_tokens WHERE expires_at > ? ORDER BY expires_at, id",
+            now
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(tokens)
+    }
+
+    /// Delete an invite token so its link stops working.
+    #[instrument(skip(self), err)]
+    pub async fn revoke_deeplink(&self, id: i64) -> Result<bool> {
+        let res = sqlx::query!("DELETE FROM deeplink_tokens WHERE id = ?", id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Delete every invite token with uses left; returns how many were revoked.
+    #[instrument(skip(self), err)]
+    pub async fn revoke_all_deeplinks(&self) -> Result<u64> {
+        let res = sqlx::query!("DELETE FROM deeplink_tokens WHERE uses_remaining > 0")
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    /// Take one use of a valid invite token; `false` if it expired or ran out meanwhile.
+    #[instrument(skip(self), err)]
+    pub async fn consume_deeplink(&self, token: &str) -> Result<bool> {
+        let now = Utc::now().naive_utc();
+        let res = sqlx::query!(
+            "UPDATE deeplink_tokens SET uses_remaining = uses_remaining - 1, is_used = (uses_remaining <= 1) WHERE token = ? AND uses_remaining > 0 AND expires_at > ?",
+            token,
+            now
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Give back a use of an invite token taken by a registration that failed.
+    #[instrument(skip(self), err)]
+    pub async fn restore_deeplink(&self, token: &str) -> Result<()> {
+        sqlx::query!(
+            "UPDATE deeplink_tokens SET uses_remaining = uses_remaining + 1, is_used = 0 WHERE token = ?",
+            token
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `record_rate_limit_offender` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn record_rate_limit_offender(
+        &self,
+        subject: &str,
+        blocked_until: chrono::NaiveDateTime,
+    ) -> Result<()> {
+        let now = Utc::now().naive_utc();
+        sqlx::query!(
+            "INSERT INTO web_rate_limit_offenders (subject, blocked_until, hits, last_seen) VALUES (?, ?, 1, ?) ON CONFLICT(subject) DO UPDATE SET blocked_until = excluded.blocked_until, hits = hits + 1, last_seen = excluded.last_seen",
+            subject,
+            blocked_until,
+            now
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `get_active_rate_limit_blocks` database operation.
+    #[instrument(skip(self), err)]
+    pub async fn get_active_rate_limit_blocks(&self) -> Result<Vec<RateLimitOffender>> {
+        let now = Utc::now().naive_utc();
+        let rows = sqlx::query_as!(
+            RateLimitOffender,
+            "SELECT subject as \"subject!: String\", blocked_until as \"blocked_until!: chrono::NaiveDateTime\", hits as \"hits!: i64\", last_seen as \"last_seen!: chrono::NaiveDateTime\" FROM web_rate_limit_offenders WHERE blocked_until > ?",
+            now
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Append an entry to the audit log.
+    #[instrument(skip(self), err)]
+    pub async fn add_audit_entry(
+        &self,
+        actor: &str,
+        action: &str,
+        target: &str,
+        details: Option<&str>,
+    ) -> Result<()> {
+        let now = Utc::now().naive_utc();
+        sqlx::query!(
+            "INSERT INTO audit_log (created_at, actor, action, target, details) VALUES (?, ?, ?, ?, ?)",
+            now,
+            actor,
+            action,
+            target,
+            details
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Audit log entries, newest first.
+    #[instrument(skip(self), err)]
+    pub async fn get_audit_entries(&self, limit: i64, offset: i64) -> Result<Vec<AuditEntry>> {
+        let rows = sqlx::query_as!(
+            AuditEntry,
+            "SELECT created_at as \"created_at!: chrono::NaiveDateTime\", actor as \"actor!: String\", action as \"action!: String\", target as \"target!: String\", details as \"details?: String\" FROM audit_log ORDER BY id DESC LIMIT ? OFFSET ?",
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Queue a registration until `TeamTalk` is reachable again.
+    #[instrument(skip(self, reg), fields(username = reg.username), err)]
+    pub async fn add_queued_registration(&self, reg: &NewQueuedRegistration<'_>) -> Result<()> {
+        let NewQueuedRegistration {
+            username,
+            password,
+            nickname,
+            account_type,
+            rights_profile,
+            source,
+            source_info,
+            telegram_id,
+            language,
+            server_id,
+        } = reg;
+        let source_info = source_info.map(SourceInfo::to_json);
+        sqlx::query!(
+            "INSERT INTO queued_registrations (username, password, nickname, account_type, rights_profile, source, source_info, telegram_id, language, server_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            username,
+            password,
+            nickname,
+            account_type,
+            rights_profile,
+            source,
+            source_info,
+            telegram_id,
+            language,
+            server_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Queued registrations, oldest first.
+    #[instrument(skip(self), err)]
+    pub async fn get_queued_registrations(&self) -> Result<Vec<QueuedRegistration>> {
+        let rows = sqlx::query_as!(
+            QueuedRegistration,
+            "SELECT id as \"id!: i64\", created_at as \"created_at!: chrono::NaiveDateTime\", username as \"username!: String\", password as \"password!: String\", nickname as \"nickname!: String\", account_type as \"account_type!: String\", rights_profile as \"rights_profile!: String\", source as \"source!: String\", source_info as \"source_info?: SourceInfo\", telegram_id as \"telegram_id?: TelegramId\", language as \"language?: String\", server_id as \"server_id!: String\", attempted_at as \"attempted_at?: chrono::NaiveDateTime\" FROM queued_registrations ORDER BY id"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Drop a queued registration once it was replayed or refused.
+    #[instrument(skip(self), err)]
+    pub async fn delete_queued_registration(&self, id: i64) -> Result<bool> {
+        let res = sqlx::query!("DELETE FROM queued_registrations WHERE id = ?", id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Move an approved Telegram request into the queue in one transaction,
+    /// marked as attempted; `None` when another admin already decided it.
+    #[instrument(skip(self, reg), fields(username = reg.username), err)]
+    pub async fn approve_pending_registration(
+        &self,
+        key: &str,
+        reg: &NewQueuedRegistration<'_>,
+    ) -> Result<Option<i64>> {
+        let NewQueuedRegistration {
+            username,
+            password,
+            nickname,
+            account_type,
+            rights_profile,
+            source,
+            source_info,
+            telegram_id,
+            language,
+            server_id,
+        } = reg;
+        let source_info = source_info.map(SourceInfo::to_json);
+        let now = Utc::now().naive_utc();
+        let mut tx = self.pool.begin().await?;
+        let claimed = sqlx::query!(
+            "DELETE FROM pending_telegram_registrations WHERE request_key = ?",
+            key
+        )
+        .execute(&mut *tx)
+        .await?;
+        if claimed.rows_affected() == 0 {
+            return Ok(None);
+        }
+        let id = sqlx::query_scalar!(
+            "INSERT INTO queued_registrations (username, password, nickname, account_type, rights_profile, source, source_info, telegram_id, language, server_id, attempted_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) RETURNING id as \"id!: i64\"",
+            username,
+            password,
+            nickname,
+            account_type,
+            rights_profile,
+            source,
+            source_info,
+            telegram_id,
+            language,
+            server_id,
+            now
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(Some(id))
+    }
+
+    /// Set or clear when the account of a queued registration was last sent
+    /// to `TeamTalk`.
+    #[instrument(skip(self), err)]
+    pub async fn set_queued_registration_attempt(
+        &self,
+        id: i64,
+        attempted_at: Option<NaiveDateTime>,
+    ) -> Result<()> {
+        sqlx::query!(
+            "UPDATE queued_registrations SET attempted_at = ? WHERE id = ?",
+            attempted_at,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record a created account in one transaction: link its Telegram user
+    /// and drop the queue entry it was created from.
+    #[instrument(skip(self, reg), err)]
+    pub async fn finish_registration(
+        &self,
+        reg: Option<&NewRegistration<'_>>,
+        queued_id: Option<i64>,
+    ) -> Result<()> {
+        let now = Utc::now().naive_utc();
+        let mut tx = self.pool.begin().await?;
+        if let Some(reg) = reg {
+            let source = reg.source.as_str();
+            trace!(tg_id = %reg.telegram_id, tt_username = reg.username, server_id = reg.server_id, "Adding registration");
+            sqlx::query!(
+                "INSERT OR REPLACE INTO telegram_registrations (telegram_id, teamtalk_username, nickname, created_at, language, server_id, source) VALUES (?, ?, ?, ?, ?, ?, ?)",
+                reg.telegram_id,
+                reg.username,
+                reg.nickname,
+                now,
+                reg.language,
+                reg.server_id,
+                source
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        if let Some(id) = queued_id {
+            sqlx::query!("DELETE FROM queued_registrations WHERE id = ?", id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Remember an account the bot created so it is welcomed on first login.
+    #[instrument(skip(self), err)]
+    pub async fn add_account_welcome(
+        &self,
+        server_id: &str,
+        username: &str,
+        language: &str,
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO account_welcomes (server_id, username, language) VALUES (?, ?, ?) ON CONFLICT(server_id, username) DO UPDATE SET language = excluded.language, created_at = CURRENT_TIMESTAMP, welcomed_at = NULL",
+            server_id,
+            username,
+            language
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Mark `username` as welcomed; returns its language only the first time.
+    #[instrument(skip(self), err)]
+    pub async fn claim_account_welcome(
+        &self,
+        server_id: &str,
+        username: &str,
+    ) -> Result<Option<String>> {
+        let now = Utc::now().naive_utc();
+        let row = sqlx::query!(
+            "UPDATE account_welcomes SET welcomed_at = ? WHERE server_id = ? AND username = ? AND welcomed_at IS NULL RETURNING language as \"language!: String\"",
+            now,
+            server_id,
+            username
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|row| row.language))
+    }
+
+    /// Registration, decision, ban and invite counts for `/stats`.
+    #[instrument(skip(self), err)]
+    pub async fn get_registration_stats(&self) -> Result<RegistrationStats> {
+        let stats = sqlx::query_as!(
+            RegistrationStats,
+            "SELECT (SELECT count(*) FROM telegram_registrations) as \"telegram_total!: i64\", (SELECT count(*) FROM telegram_registrations WHERE created_at >= date('now')) as \"telegram_today!: i64\", (SELECT count(*) FROM telegram_registrations WHERE source = 'approved') as \"telegram_approved!: i64\", (SELECT count(*) FROM fastapi_registered_ips) as \"web_total!: i64\", (SELECT count(*) FROM fastapi_registered_ips WHERE registration_timestamp >= date('now')) as \"web_today!: i64\", (SELECT count(*) FROM pending_web_registrations WHERE status = 'approved') as \"web_approved!: i64\", (SELECT count(*) FROM pending_web_registrations WHERE status = 'approved' AND decided_at >= date('now')) as \"web_approved_today!: i64\", (SELECT count(*) FROM pending_web_registrations WHERE status = 'rejected') as \"web_rejected!: i64\", (SELECT count(*) FROM pending_web_registrations WHERE status = 'rejected' AND decided_at >= date('now')) as \"web_rejected_today!: i64\", (SELECT count(*) FROM pending_web_registrations WHERE status = 'pending') as \"web_pending!: i64\", (SELECT count(*) FROM banned_users) as \"banned!: i64\", (SELECT count(*) FROM deeplink_tokens) as \"deeplinks_issued!: i64\", (SELECT count(*) FROM deeplink_tokens WHERE is_used = 1) as \"deeplinks_used!: i64\""
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(stats)
+    }
+
+    /// `cleanup` database operation.
+    ///
+    /// Returns the files of the removed download tokens so the caller can
+    /// delete them.
+    #[instrument(skip(self), err)]
+    pub async fn cleanup(
+        &self,
+        pending_reg_ttl_seconds: u64,
+        registered_ip_ttl_seconds: u64,
+    ) -> Result<Vec<String>> {
+        trace!(
+            pending_reg_ttl_seconds,
+            registered_ip_ttl_seconds, "Running db cleanup"
+        );
+        let now = Utc::now().naive_utc();
+        let files = sqlx::query_scalar!(
+            "DELETE FROM fastapi_download_tokens WHERE expires_at < ? OR is_used = 1 RETURNING filepath_on_server",
+            now
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM deeplink_tokens WHERE expires_at < ? OR is_used = 1",
+            now
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM signed_download_links WHERE expires_at < ?",
+            now
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM web_rate_limit_offenders WHERE blocked_until < ?",
+            now
+        )
+        .execute(&self.pool)
+        .await?;
+        let pending_ttl = format!("-{pending_reg_ttl_seconds} seconds");
+        let ip_ttl = format!("-{registered_ip_ttl_seconds} seconds");
+        sqlx::query!(
+            "DELETE FROM pending_web_registrations WHERE created_at < datetime('now', ?)",
+            pending_ttl
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM fastapi_registered_ips WHERE registration_timestamp < datetime('now', ?)",
+            ip_ttl
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query("PRAGMA optimize;").execute(&self.pool).await?;
+
+        Ok(files)
+    }
+
+    /// Encrypt pending passwords stored before sealing was introduced.
+    #[instrument(skip(self, sealer), err)]
+    pub async fn seal_legacy_passwords(&self, sealer: &PasswordSealer) -> Result<u64> {
+        let pattern = format!("{SEALED_PREFIX}%");
+        let mut tx = self.pool.begin().await?;
+        let telegram = sqlx::query!(
+            "SELECT id as \"id!: i64\", password_cleartext FROM pending_telegram_registrations WHERE password_cleartext <> '' AND password_cleartext NOT LIKE ?",
+            pattern
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        for row in &telegram {
+            let encrypted = sealer.seal(&row.password_cleartext)?;
+            sqlx::query!(
+                "UPDATE pending_telegram_registrations SET password_cleartext = ? WHERE id = ?",
+                encrypted,
+                row.id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        let web = sqlx::query!(
+            "SELECT id as \"id!: i64\", password_cleartext FROM pending_web_registrations WHERE password_cleartext <> '' AND password_cleartext NOT LIKE ?",
+            pattern
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        for row in &web {
+            let encrypted = sealer.seal(&row.password_cleartext)?;
+            sqlx::query!(
+                "UPDATE pending_web_registrations SET password_cleartext = ? WHERE id = ?",
+                encrypted,
+                row.id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok((telegram.len() + web.len()) as u64)
+    }
+
+    /// Rewrite `source_info` stored in the old `key=value` format as JSON.
+    #[instrument(skip(self), err)]
+    pub async fn convert_legacy_source_info(&self) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let telegram = sqlx::query!(
+            "SELECT id as \"id!: i64\", registrant_telegram_id as \"registrant_telegram_id!: TelegramId\", source_info FROM pending_telegram_registrations WHERE source_info NOT LIKE '{%'"
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        for row in &telegram {
+            let converted = legacy_telegram_source(&row.source_info, row.registrant_telegram_id);
+            sqlx::query!(
+                "UPDATE pending_telegram_registrations SET source_info = ? WHERE id = ?",
+                converted,
+                row.id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        let web = sqlx::query!(
+            "SELECT id as \"id!: i64\", ip_address, user_agent, source_info FROM pending_web_registrations WHERE source_info NOT LIKE '{%'"
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        for row in &web {
+            let converted =
+                legacy_web_source(&row.source_info, &row.ip_address, row.user_agent.as_deref());
+            sqlx::query!(
+                "UPDATE pending_web_registrations SET source_info = ? WHERE id = ?",
+                converted,
+                row.id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        let queued = sqlx::query!(
+            "SELECT id as \"id!: i64\", source, source_info as \"source_info!: String\" FROM queued_registrations WHERE source_info IS NOT NULL AND source_info NOT LIKE '{%'"
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        for row in &queued {
+            let converted = legacy_queued_source(&row.source_info, &row.source);
+            sqlx::query!(
+                "UPDATE queued_registrations SET source_info = ? WHERE id = ?",
+                converted,
+                row.id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok((telegram.len() + web.len() + queued.len()) as u64)
+    }
+
+    /// Write a consistent copy of the database to `path` with `VACUUM INTO`.
+    ///
+    /// The target file must not exist yet.
+    #[instrument(skip(self), err)]
+    pub async fn backup(&self, path: &Path) -> Result<()> {
+        sqlx::query("VACUUM INTO ?")
+            .bind(path.to_string_lossy().to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// `close` database operation.
+    pub async fn close(&self) {
+        self.pool.close().await;
+    }
+}
+
+async fn integrity_check(pool: &Pool<Sqlite>) -> Result<()> {
+    let result: String = sqlx::query_scalar("PRAGMA integrity_check;")
+        .fetch_one(pool)
+        .await?;
+    if result.trim() == "ok" {
+        info!("Database integrity check: ok");
+        Ok(())
+    } else {
+        error!(result = %result, "Database integrity check failed");
+        anyhow::bail!("Database integrity check failed: {result}");
+    }
+}
+
+async fn validate_schema(pool: &Pool<Sqlite>) -> Result<()> {
+    let tables: Vec<String> = sqlx::query("SELECT name FROM sqlite_master WHERE type = 'table'")
+        .map(|row: sqlx::sqlite::SqliteRow| row.get::<String, _>("name"))
+        .fetch_all(pool)
+        .await?;
+    let present: HashSet<String> = tables.into_iter().collect();
+    let required_tables = [
+        "telegram_registrations",
+        "pending_telegram_registrations",
+        "pending_web_registrations",
+        "banned_users",
+        "bans",
+        "admin_settings",
+        "queued_notifications",
+        "fastapi_download_tokens",
+        "signed_download_links",
+        "fastapi_registered_ips",
+        "deeplink_tokens",
+        "web_rate_limit_offenders",
+        "_sqlx_migrations",
+    ];
+    for table in &required_tables {
+        if !present.contains(*table) {
+            anyhow::bail!("Database schema missing table: {table}");
+        }
+    }
+    validate_columns(pool).await
+}
+
+/// Columns each table must have once the migrations ran.
+const EXPECTED_COLUMNS: &[(&str, &[&str])] = &[
+    (
+        "admin_settings",
+        &["admin_id", "muted_notifications", "quiet_hours"],
+    ),
+    (
+        "telegram_registrations",
+        &[
+            "telegram_id",
+            "teamtalk_username",
+            "nickname",
+            "created_at",
+            "language",
+            "server_id",
+            "source",
+        ],
+    ),
+    (
+        "pending_telegram_registrations",
+        &[
+            "id",
+            "request_key",
+            "registrant_telegram_id",
+            "username",
+            "password_cleartext",
+            "nickname",
+            "source_info",
+            "created_at",
+        ],
+    ),
+    (
+        "pending_web_registrations",
+        &[
+            "id",
+            "request_key",
+            "username",
+            "password_cleartext",
+            "nickname",
+            "ip_address",
+            "user_agent",
+            "source_info",
+            "created_at",
+            "status",
+            "decided_at",
+            "decision_reason",
+            "claimed_by",
+            "claimed_at",
+        ],
+    ),
+    (
+        "deeplink_tokens",
+        &[
+            "id",
+            "token",
+            "created_at",
+            "expires_at",
+            "is_used",
+            "uses_remaining",
+            "generated_by_admin_id",
+            "server_id",
+        ],
+    ),
+    (
+        "audit_log",
+        &["id", "created_at", "actor", "action", "target", "details"],
+    ),
+    (
+        "account_welcomes",
+        &[
+            "server_id",
+            "username",
+            "created_at",
+            "language",
+            "welcomed_at",
+        ],
+    ),
+    (
+        "queued_registrations",
+        &[
+            "id",
+            "created_at",
+            "username",
+            "password",
+            "nickname",
+            "account_type",
+            "rights_profile",
+            "source",
+            "source_info",
+            "telegram_id",
+            "language",
+            "server_id",
+            "attempted_at",
+        ],
+    ),
+];
+
+async fn validate_columns(pool: &Pool<Sqlite>) -> Result<()> {
+    for (table, columns) in EXPECTED_COLUMNS {
+        ensure_columns(pool, table, columns).await?;
+    }
+    Ok(())
+}
+
+async fn ensure_columns(pool: &Pool<Sqlite>, table: &str, expected: &[&str]) -> Result<()> {
+    let rows = sqlx::query(&format!("PRAGMA table_info({table})"))
+        .fetch_all(pool)
+        .await?;
+    let mut present = HashSet::new();
+    for row in rows {
+        let name: String = row.get("name");
+        present.insert(name);
+    }
+    for col in expected {
+        if !present.contains(*col) {
+            anyhow::bail!("Table {table} missing column: {col}");
+        }
+    }
+    Ok(())
+}
@@ -1,3 +1,13 @@
+mod network;
+mod policy;
+mod quiet_hours;
+
+pub use network::IpNetwork;
+pub use policy::{
+    PasswordPolicy, PasswordViolation, UsernameMatch, UsernamePolicy, UsernameViolation,
+};
+pub use quiet_hours::QuietHours;
+
 /// `TeamTalk` username wrapper.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Username(String);
@@ -0,0 +1,62 @@
+use std::fmt;
+use std::net::IpAddr;
+
+/// An address with a prefix length, e.g. `10.0.0.0/8`.
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNetwork {
+    /// Parse an address or `address/prefix`; a bare address is a network of
+    /// one.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (addr, prefix) = value
+            .split_once('/')
+            .map_or((value, None), |(addr, prefix)| (addr, Some(prefix)));
+        let addr = addr.parse::<IpAddr>().ok()?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = prefix.map_or(Some(max), |p| p.parse::<u8>().ok())?;
+        (prefix <= max).then_some(Self {
+            addr: addr.to_canonical(),
+            prefix,
+        })
+    }
+
+    /// Whether `ip` lies inside the network.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                prefix_matches(&net.octets(), &ip.octets(), self.prefix)
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                prefix_matches(&net.octets(), &ip.octets(), self.prefix)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let max = if self.addr.is_ipv4() { 32 } else { 128 };
+        if self.prefix == max {
+            write!(f, "{}", self.addr)
+        } else {
+            write!(f, "{}/{}", self.addr, self.prefix)
+        }
+    }
+}
+
+fn prefix_matches(net: &[u8], ip: &[u8], prefix: u8) -> bool {
+    let full_bytes = usize::from(prefix / 8);
+    let rest_bits = prefix % 8;
+    if net[..full_bytes] != ip[..full_bytes] {
+        return false;
+    }
+    if rest_bits == 0 {
+        return true;
+    }
+    let mask = 0xFF_u8 << (8 - rest_bits);
+    (net[full_bytes] & mask) == (ip[full_bytes] & mask)
+}
@@ -0,0 +1,364 @@
+use super::{Password, Username};
+use rand::seq::{IndexedRandom, SliceRandom};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use unicode_normalization::UnicodeNormalization;
+
+const DEFAULT_MIN_LENGTH: usize = 2;
+const DEFAULT_MAX_LENGTH: usize = 32;
+const DEFAULT_PATTERN: &str = r"^[\p{L}\p{N}._-]+$";
+const DEFAULT_RESERVED: &[&str] = &["admin", "administrator", "root", "moderator", "owner"];
+
+/// `[username_policy]` table as written in the config file.
+#[derive(Deserialize)]
+#[serde(default)]
+struct RawUsernamePolicy {
+    min_length: usize,
+    max_length: usize,
+    allowed_pattern: String,
+    reserved_names: Vec<String>,
+    /// Older switch; `false` means `collision_check = "exact"`.
+    case_insensitive: Option<bool>,
+    collision_check: Option<UsernameMatch>,
+}
+
+impl Default for RawUsernamePolicy {
+    fn default() -> Self {
+        Self {
+            min_length: DEFAULT_MIN_LENGTH,
+            max_length: DEFAULT_MAX_LENGTH,
+            allowed_pattern: DEFAULT_PATTERN.to_string(),
+            reserved_names: DEFAULT_RESERVED.iter().map(ToString::to_string).collect(),
+            case_insensitive: None,
+            collision_check: None,
+        }
+    }
+}
+
+/// When a new username counts as the same as an existing one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsernameMatch {
+    /// Only identical names.
+    Exact,
+    /// Names differing only in letter case.
+    Case,
+    /// Names equal after NFKC normalization and case folding, which also
+    /// catches full-width and other compatibility lookalikes.
+    #[default]
+    Normalized,
+}
+
+impl UsernameMatch {
+    /// Form of `name` that equals the form of every name it matches.
+    pub fn fold(self, name: &str) -> String {
+        match self {
+            Self::Exact => name.to_string(),
+            Self::Case => name.to_lowercase(),
+            Self::Normalized => name.nfkc().flat_map(char::to_lowercase).nfkc().collect(),
+        }
+    }
+}
+
+/// Rules a new `TeamTalk` username must satisfy.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "RawUsernamePolicy")]
+pub struct UsernamePolicy {
+    min_length: usize,
+    max_length: usize,
+    pattern: Regex,
+    reserved_names: Vec<String>,
+    matching: UsernameMatch,
+}
+
+/// Why a username was refused by [`UsernamePolicy::check`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UsernameViolation {
+    TooShort { min: usize },
+    TooLong { max: usize },
+    InvalidCharacters,
+    Reserved,
+}
+
+impl UsernameViolation {
+    /// Translation key of the user-facing message; `min`/`max` are its arguments.
+    pub const fn message_key(self) -> &'static str {
+        match self {
+            Self::TooShort { .. } => "username-too-short",
+            Self::TooLong { .. } => "username-too-long",
+            Self::InvalidCharacters => "username-invalid-chars",
+            Self::Reserved => "username-reserved",
+        }
+    }
+
+    /// Arguments for the message named by [`Self::message_key`].
+    pub fn message_args(self) -> HashMap<String, String> {
+        match self {
+            Self::TooShort { min } => HashMap::from([("min".to_string(), min.to_string())]),
+            Self::TooLong { max } => HashMap::from([("max".to_string(), max.to_string())]),
+            Self::InvalidCharacters | Self::Reserved => HashMap::new(),
+        }
+    }
+}
+
+impl TryFrom<RawUsernamePolicy> for UsernamePolicy {
+    type Error = String;
+
+    fn try_from(raw: RawUsernamePolicy) -> Result<Self, Self::Error> {
+        if raw.min_length == 0 || raw.min_length > raw.max_length {
+            return Err(format!(
+                "username_policy: invalid length range {}..={}",
+                raw.min_length, raw.max_length
+            ));
+        }
+        let pattern = Regex::new(&raw.allowed_pattern)
+            .map_err(|e| format!("username_policy.allowed_pattern: {e}"))?;
+        let matching = match (raw.collision_check, raw.case_insensitive) {
+            (Some(matching), _) => matching,
+            (None, Some(false)) => UsernameMatch::Exact,
+            (None, _) => UsernameMatch::default(),
+        };
+        Ok(Self {
+            min_length: raw.min_length,
+            max_length: raw.max_length,
+            pattern,
+            reserved_names: raw
+                .reserved_names
+                .iter()
+                .map(|name| reserved_fold(matching, name.trim()))
+                .collect(),
+            matching,
+        })
+    }
+}
+
+impl Default for UsernamePolicy {
+    fn default() -> Self {
+        Self::try_from(RawUsernamePolicy::default())
+            .unwrap_or_else(|e| unreachable!("default username policy is valid: {e}"))
+    }
+}
+
+impl UsernamePolicy {
+    /// Check a new username against length, charset and reserved-name rules.
+    pub fn check(&self, username: &Username) -> Result<(), UsernameViolation> {
+        let name = username.as_str();
+        let length = name.chars().count();
+        if length < self.min_length {
+            return Err(UsernameViolation::TooShort {
+                min: self.min_length,
+            });
+        }
+        if length > self.max_length {
+            return Err(UsernameViolation::TooLong {
+                max: self.max_length,
+            });
+        }
+        if !self.pattern.is_match(name) {
+            return Err(UsernameViolation::InvalidCharacters);
+        }
+        if self
+            .reserved_names
+            .contains(&reserved_fold(self.matching, name))
+        {
+            return Err(UsernameViolation::Reserved);
+        }
+        Ok(())
+    }
+
+    /// When a new name collides with an existing account.
+    pub const fn matching(&self) -> UsernameMatch {
+        self.matching
+    }
+}
+
+/// Reserved names are compared ignoring case even with exact matching.
+fn reserved_fold(matching: UsernameMatch, name: &str) -> String {
+    match matching {
+        UsernameMatch::Exact | UsernameMatch::Case => UsernameMatch::Case.fold(name),
+        UsernameMatch::Normalized => UsernameMatch::Normalized.fold(name),
+    }
+}
+
+const DEFAULT_PASSWORD_MIN_LENGTH: usize = 8;
+const DEFAULT_PASSWORD_MAX_LENGTH: usize = 64;
+/// Length of generated passwords, unless the policy demands more.
+const GENERATED_PASSWORD_LENGTH: usize = 16;
+const LOWERCASE: &[u8] = b"abcdefghijkmnopqrstuvwxyz";
+const UPPERCASE: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+const DIGITS: &[u8] = b"23456789";
+const SYMBOLS: &[u8] = b"!#%+-=?@_";
+
+/// Character class a password can be required to contain.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CharClass {
+    Lowercase,
+    Uppercase,
+    Digit,
+    Symbol,
+}
+
+impl CharClass {
+    fn matches(self, c: char) -> bool {
+        match self {
+            Self::Lowercase => c.is_lowercase(),
+            Self::Uppercase => c.is_uppercase(),
+            Self::Digit => c.is_numeric(),
+            Self::Symbol => !c.is_alphanumeric() && !c.is_whitespace(),
+        }
+    }
+
+    const fn alphabet(self) -> &'static [u8] {
+        match self {
+            Self::Lowercase => LOWERCASE,
+            Self::Uppercase => UPPERCASE,
+            Self::Digit => DIGITS,
+            Self::Symbol => SYMBOLS,
+        }
+    }
+}
+
+/// `[password_policy]` table as written in the config file.
+#[derive(Deserialize)]
+#[serde(default)]
+struct RawPasswordPolicy {
+    min_length: usize,
+    max_length: usize,
+    require: Vec<CharClass>,
+}
+
+impl Default for RawPasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: DEFAULT_PASSWORD_MIN_LENGTH,
+            max_length: DEFAULT_PASSWORD_MAX_LENGTH,
+            require: Vec::new(),
+        }
+    }
+}
+
+/// Length and complexity rules for new passwords.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "RawPasswordPolicy")]
+pub struct PasswordPolicy {
+    min_length: usize,
+    max_length: usize,
+    require: Vec<CharClass>,
+}
+
+/// Why a password was refused by [`PasswordPolicy::check`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PasswordViolation {
+    TooShort { min: usize },
+    TooLong { max: usize },
+    Missing(CharClass),
+}
+
+impl PasswordViolation {
+    /// Translation key of the user-facing message; `min`/`max` are its arguments.
+    pub const fn message_key(self) -> &'static str {
+        match self {
+            Self::TooShort { .. } => "password-too-short",
+            Self::TooLong { .. } => "password-too-long",
+            Self::Missing(CharClass::Lowercase) => "password-missing-lowercase",
+            Self::Missing(CharClass::Uppercase) => "password-missing-uppercase",
+            Self::Missing(CharClass::Digit) => "password-missing-digit",
+            Self::Missing(CharClass::Symbol) => "password-missing-symbol",
+        }
+    }
+
+    /// Arguments for the message named by [`Self::message_key`].
+    pub fn message_args(self) -> HashMap<String, String> {
+        match self {
+            Self::TooShort { min } => HashMap::from([("min".to_string(), min.to_string())]),
+            Self::TooLong { max } => HashMap::from([("max".to_string(), max.to_string())]),
+            Self::Missing(_) => HashMap::new(),
+        }
+    }
+}
+
+impl TryFrom<RawPasswordPolicy> for PasswordPolicy {
+    type Error = String;
+
+    fn try_from(raw: RawPasswordPolicy) -> Result<Self, Self::Error> {
+        if raw.min_length == 0
+            || raw.min_length > raw.max_length
+            || raw.require.len() > raw.max_length
+        {
+            return Err(format!(
+                "password_policy: invalid length range {}..={}",
+                raw.min_length, raw.max_length
+            ));
+        }
+        let mut require = Vec::new();
+        for class in raw.require {
+            if !require.contains(&class) {
+                require.push(class);
+            }
+        }
+        Ok(Self {
+            min_length: raw.min_length,
+            max_length: raw.max_length,
+            require,
+        })
+    }
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self::try_from(RawPasswordPolicy::default())
+            .unwrap_or_else(|e| unreachable!("default password policy is valid: {e}"))
+    }
+}
+
+impl PasswordPolicy {
+    /// Check a new password against the length and character class rules.
+    pub fn check(&self, password: &Password) -> Result<(), PasswordViolation> {
+        let value = password.as_str();
+        let length = value.chars().count();
+        if length < self.min_length {
+            return Err(PasswordViolation::TooShort {
+                min: self.min_length,
+            });
+        }
+        if length > self.max_length {
+            return Err(PasswordViolation::TooLong {
+                max: self.max_length,
+            });
+        }
+        if let Some(&class) = self
+            .require
+            .iter()
+            .find(|class| !value.chars().any(|c| class.matches(c)))
+        {
+            return Err(PasswordViolation::Missing(class));
+        }
+        Ok(())
+    }
+
+    /// Generate a random password that satisfies this policy, avoiding look-alike characters.
+    pub fn generate(&self) -> Password {
+        let mut rng = rand::rng();
+        let length = GENERATED_PASSWORD_LENGTH.clamp(self.min_length, self.max_length);
+        let classes = [CharClass::Lowercase, CharClass::Uppercase, CharClass::Digit];
+        let alphabet: Vec<u8> = classes
+            .iter()
+            .chain(&self.require)
+            .flat_map(|class| class.alphabet().iter().copied())
+            .collect();
+        let mut chars: Vec<u8> = self
+            .require
+            .iter()
+            .filter_map(|class| class.alphabet().choose(&mut rng).copied())
+            .collect();
+        while chars.len() < length {
+            if let Some(&c) = alphabet.choose(&mut rng) {
+                chars.push(c);
+            }
+        }
+        chars.shuffle(&mut rng);
+        Password(chars.into_iter().map(char::from).collect())
+    }
+}
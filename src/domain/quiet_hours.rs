@@ -0,0 +1,52 @@
+use chrono::NaiveTime;
+use serde::Deserialize;
+use std::fmt;
+
+/// Daily window of server local time, e.g. `22:00-07:00`; it may wrap past
+/// midnight.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuietHours {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl QuietHours {
+    /// Parse `HH:MM-HH:MM`; a window that starts where it ends is refused.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (start, end) = value.trim().split_once('-')?;
+        let start = NaiveTime::parse_from_str(start.trim(), "%H:%M").ok()?;
+        let end = NaiveTime::parse_from_str(end.trim(), "%H:%M").ok()?;
+        (start != end).then_some(Self { start, end })
+    }
+
+    /// Whether `time` falls inside the window.
+    pub fn contains(self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl fmt::Display for QuietHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+impl<'de> Deserialize<'de> for QuietHours {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        Self::parse(&raw)
+            .ok_or_else(|| serde::de::Error::custom("quiet hours must look like \"22:00-07:00\""))
+    }
+}
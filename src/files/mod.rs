@@ -1,5 +1,9 @@
 /// Link generators for client shortcuts.
 pub mod links;
+/// QR codes for quick-join links.
+pub mod qr;
+/// Instructions bundled with client ZIPs.
+pub mod readme;
 /// Rights mask helpers.
 pub mod rights;
 /// `.tt` file generators.
@@ -8,6 +12,11 @@ pub mod tt;
 pub mod zip;
 
 pub use links::generate_tt_link;
+pub use qr::{qr_png, qr_svg};
+pub use readme::generate_client_readme;
 pub use rights::get_user_rights_mask;
-pub use tt::generate_tt_file_content;
-pub use zip::create_client_zip;
+pub use tt::{generate_tt_file_content, unknown_tt_placeholders};
+pub use zip::{
+    ClientZipContent, Compression, client_zip_bytes, create_client_zip, stream_client_zip,
+    template_size, zip_single_file,
+};
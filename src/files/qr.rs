@@ -0,0 +1,49 @@
+use anyhow::Result;
+use qrcode::render::svg;
+use qrcode::{Color, QrCode};
+
+/// Empty modules around the code, as the QR specification asks for.
+const QUIET_ZONE: usize = 4;
+/// Pixels per module in PNG output.
+const PNG_MODULE_PX: usize = 8;
+
+/// Render `data` (usually a `tt://` link) as an SVG QR code.
+pub fn qr_svg(data: &str) -> Result<String> {
+    let code = QrCode::new(data.as_bytes())?;
+    Ok(code
+        .render::<svg::Color<'_>>()
+        .min_dimensions(200, 200)
+        .dark_color(svg::Color("#000000"))
+        .light_color(svg::Color("#ffffff"))
+        .build())
+}
+
+/// Render `data` as a grayscale PNG QR code.
+pub fn qr_png(data: &str) -> Result<Vec<u8>> {
+    let code = QrCode::new(data.as_bytes())?;
+    let modules = code.width();
+    let colors = code.to_colors();
+    let side = (modules + 2 * QUIET_ZONE) * PNG_MODULE_PX;
+
+    let mut pixels = vec![u8::MAX; side * side];
+    for (i, color) in colors.iter().enumerate() {
+        if *color != Color::Dark {
+            continue;
+        }
+        let x0 = (i % modules + QUIET_ZONE) * PNG_MODULE_PX;
+        let y0 = (i / modules + QUIET_ZONE) * PNG_MODULE_PX;
+        for y in y0..y0 + PNG_MODULE_PX {
+            pixels[y * side + x0..y * side + x0 + PNG_MODULE_PX].fill(0);
+        }
+    }
+
+    let side = u32::try_from(side)?;
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, side, side);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels)?;
+    writer.finish()?;
+    Ok(out)
+}
@@ -0,0 +1,35 @@
+use crate::config::AppConfig;
+use crate::i18n::t_args;
+use std::collections::HashMap;
+
+/// Generate the `README.txt` bundled with a client ZIP, in `lang`.
+pub fn generate_client_readme(
+    config: &AppConfig,
+    lang: &str,
+    username: &str,
+    tt_filename: &str,
+) -> String {
+    let host = config
+        .teamtalk
+        .tt_public_hostname
+        .as_deref()
+        .filter(|s| !s.is_empty())
+        .unwrap_or(&config.teamtalk.host_name);
+    let args = HashMap::from([
+        (
+            "server_name".to_string(),
+            config.teamtalk.server_name.clone(),
+        ),
+        ("host".to_string(), host.to_string()),
+        ("port".to_string(), config.teamtalk.tcp_port.to_string()),
+        ("username".to_string(), username.to_string()),
+        ("tt_file".to_string(), tt_filename.to_string()),
+    ]);
+    // Fluent wraps arguments in bidi isolation marks, which plain-text
+    // editors show as stray characters.
+    let text: String = t_args(lang, "zip-readme", &args)
+        .chars()
+        .filter(|c| !matches!(c, '\u{2068}' | '\u{2069}'))
+        .collect();
+    format!("{}\r\n", text.replace('\n', "\r\n"))
+}
@@ -1,29 +1,15 @@
 use crate::config::AppConfig;
 
-/// Generate `TeamTalk` `.tt` file XML content.
-pub fn generate_tt_file_content(
-    config: &AppConfig,
-    username: &str,
-    password: &str,
-    nickname: &str,
-) -> String {
-    let host = config
-        .teamtalk
-        .tt_public_hostname
-        .as_deref()
-        .filter(|s| !s.is_empty())
-        .unwrap_or(&config.teamtalk.host_name);
-
-    format!(
-        r#"<?xml version="1.0" encoding="UTF-8" ?>
+/// Built-in `.tt` layout; `tt_file_template` replaces it.
+const DEFAULT_TT_TEMPLATE: &str = r#"<?xml version="1.0" encoding="UTF-8" ?>
 <!DOCTYPE teamtalk>
 <teamtalk version="5.0">
     <host>
-        <name>{server_name}</name>
-        <address>{host}</address>
-        <tcpport>{tcp}</tcpport>
-        <udpport>{udp}</udpport>
-        <encrypted>{enc}</encrypted>
+        <name>{{server_name}}</name>
+        <address>{{host}}</address>
+        <tcpport>{{tcp_port}}</tcpport>
+        <udpport>{{udp_port}}</udpport>
+        <encrypted>{{encrypted}}</encrypted>
         <trusted-certificate>
             <certificate-authority-pem></certificate-authority-pem>
             <client-certificate-pem></client-certificate-pem>
@@ -31,37 +17,120 @@ pub fn generate_tt_file_content(
             <verify-peer>false</verify-peer>
         </trusted-certificate>
         <auth>
-            <username>{user}</username>
-            <password>{pass}</password>
-            <nickname>{nick}</nickname>
+            <username>{{username}}</username>
+            <password>{{password}}</password>
+            <nickname>{{nickname}}</nickname>
         </auth>
         <join>
-            <channel>{join_chan}</channel>
-            <password>{join_pass}</password>
+            <channel>{{join_channel}}</channel>
+            <password>{{join_password}}</password>
         </join>
     </host>
-</teamtalk>"#,
-        server_name = escape_xml(&config.teamtalk.server_name),
-        host = escape_xml(host),
-        tcp = config.teamtalk.tcp_port,
-        udp = config.teamtalk.udp_port.unwrap_or(config.teamtalk.tcp_port),
-        enc = if config.teamtalk.encrypted {
-            "true"
-        } else {
-            "false"
-        },
-        user = escape_xml(username),
-        pass = escape_xml(password),
-        nick = escape_xml(nickname),
-        join_chan = escape_xml(config.teamtalk.tt_join_channel.as_deref().unwrap_or("")),
-        join_pass = escape_xml(
-            config
+</teamtalk>"#;
+
+/// Placeholders understood in `.tt` templates.
+const TT_TEMPLATE_PLACEHOLDERS: &[&str] = &[
+    "server_name",
+    "host",
+    "tcp_port",
+    "udp_port",
+    "encrypted",
+    "username",
+    "password",
+    "nickname",
+    "join_channel",
+    "join_password",
+];
+
+/// Generate `TeamTalk` `.tt` file XML content from the configured template,
+/// or the built-in one.
+pub fn generate_tt_file_content(
+    config: &AppConfig,
+    username: &str,
+    password: &str,
+    nickname: &str,
+) -> String {
+    let host = config
+        .teamtalk
+        .tt_public_hostname
+        .as_deref()
+        .filter(|s| !s.is_empty())
+        .unwrap_or(&config.teamtalk.host_name);
+    // A personal channel replaces the configured one; it has no password.
+    let (join_channel, join_password) =
+        config.teamtalk.personal_channel_path(username).map_or_else(
+            || {
+                (
+                    config.teamtalk.tt_join_channel.clone().unwrap_or_default(),
+                    config
+                        .teamtalk
+                        .tt_join_channel_password
+                        .as_deref()
+                        .unwrap_or(""),
+                )
+            },
+            |path| (path, ""),
+        );
+    let template = config
+        .teamtalk
+        .tt_file_template_content
+        .as_deref()
+        .unwrap_or(DEFAULT_TT_TEMPLATE);
+
+    render(template, |name| {
+        Some(match name {
+            "server_name" => escape_xml(&config.teamtalk.server_name),
+            "host" => escape_xml(host),
+            "tcp_port" => config.teamtalk.tcp_port.to_string(),
+            "udp_port" => config
                 .teamtalk
-                .tt_join_channel_password
-                .as_deref()
-                .unwrap_or("")
-        ),
-    )
+                .udp_port
+                .unwrap_or(config.teamtalk.tcp_port)
+                .to_string(),
+            "encrypted" => config.teamtalk.encrypted.to_string(),
+            "username" => escape_xml(username),
+            "password" => escape_xml(password),
+            "nickname" => escape_xml(nickname),
+            "join_channel" => escape_xml(&join_channel),
+            "join_password" => escape_xml(join_password),
+            _ => return None,
+        })
+    })
+}
+
+/// Names of `{{placeholder}}`s in `template` that are not understood.
+pub fn unknown_tt_placeholders(template: &str) -> Vec<String> {
+    let mut unknown = Vec::new();
+    render(template, |name| {
+        if !TT_TEMPLATE_PLACEHOLDERS.contains(&name) {
+            unknown.push(name.to_string());
+        }
+        Some(String::new())
+    });
+    unknown
+}
+
+/// Substitute every `{{name}}` in one pass, so values are never expanded
+/// again; unknown placeholders are kept as written.
+pub fn render(template: &str, mut value: impl FnMut(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        let name = after[..end].trim();
+        match value(name) {
+            Some(text) => out.push_str(&text),
+            None => out.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
 }
 
 fn escape_xml(s: &str) -> String {
@@ -1,31 +1,191 @@
+use crate::config::ZipCompression;
 use anyhow::Result;
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::Write;
-use std::path::Path;
+use std::io::{Cursor, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::SystemTime;
 use walkdir::WalkDir;
 use zip::write::FileOptions;
 
+/// Archive path of the generated instructions; replaces a template file of
+/// the same name.
+const README_ENTRY: &str = "README.txt";
+
+/// How client ZIP entries are compressed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Compression {
+    pub method: ZipCompression,
+    /// Method-specific level; `None` for the method's default.
+    pub level: Option<i64>,
+}
+
+/// Generated entries added to a client ZIP next to the template files.
+pub struct ClientZipContent<'a> {
+    pub tt_filename: &'a str,
+    pub tt_content: &'a str,
+    pub readme: Option<&'a str>,
+    /// Encrypts the `.tt` entry (AES-256) when set.
+    pub password: Option<&'a str>,
+}
+
 /// Create a `TeamTalk` client ZIP from template directory.
 pub fn create_client_zip(
     template_path: &str,
     output_path: &Path,
-    tt_filename: &str,
-    tt_content: &str,
+    content: &ClientZipContent<'_>,
+    compression: Compression,
 ) -> Result<()> {
-    let tpl_path = Path::new(template_path);
+    let tpl_path = existing_template(template_path)?;
+    let file = File::create(output_path)?;
+    write_client_zip(zip::ZipWriter::new(file), tpl_path, content, compression)?;
+    Ok(())
+}
+
+/// Build a `TeamTalk` client ZIP in memory.
+pub fn client_zip_bytes(
+    template_path: &str,
+    content: &ClientZipContent<'_>,
+    compression: Compression,
+) -> Result<Vec<u8>> {
+    let tpl_path = existing_template(template_path)?;
+    let zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    Ok(write_client_zip(zip, tpl_path, content, compression)?.into_inner())
+}
 
+/// Write a `TeamTalk` client ZIP to a writer that cannot seek, such as a pipe
+/// feeding an HTTP response.
+///
+/// The archive is assembled in memory first: raw entry copies into a
+/// non-seekable `ZipWriter` are flagged as having data descriptors that are
+/// never written, which strict unzip tools reject.
+pub fn stream_client_zip<W: Write>(
+    template_path: &str,
+    mut writer: W,
+    content: &ClientZipContent<'_>,
+    compression: Compression,
+) -> Result<()> {
+    writer.write_all(&client_zip_bytes(template_path, content, compression)?)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Total size in bytes of the files in a template directory.
+pub fn template_size(template_path: &str) -> u64 {
+    WalkDir::new(template_path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(std::fs::Metadata::is_file)
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+fn existing_template(template_path: &str) -> Result<&Path> {
+    let tpl_path = Path::new(template_path);
     if !tpl_path.exists() {
         return Err(anyhow::anyhow!(
             "Template directory does not exist: {template_path}"
         ));
     }
+    Ok(tpl_path)
+}
 
-    let file = File::create(output_path)?;
-    let mut zip = zip::ZipWriter::new(file);
+/// Template entries compressed once and reused until the template changes.
+struct BaseZip {
+    stamp: TemplateStamp,
+    compression: Compression,
+    bytes: Arc<[u8]>,
+}
 
-    let options = FileOptions::<()>::default()
-        .compression_method(zip::CompressionMethod::Deflated)
-        .unix_permissions(0o755);
+/// File count, total size and newest mtime of a template directory.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct TemplateStamp {
+    files: u64,
+    bytes: u64,
+    modified: Option<SystemTime>,
+}
+
+static BASE_ZIPS: Mutex<BTreeMap<PathBuf, BaseZip>> = Mutex::new(BTreeMap::new());
+
+fn write_client_zip<W: Write + Seek>(
+    mut zip: zip::ZipWriter<W>,
+    tpl_path: &Path,
+    content: &ClientZipContent<'_>,
+    compression: Compression,
+) -> Result<W> {
+    let base = base_zip(tpl_path, compression)?;
+    let mut archive = zip::ZipArchive::new(Cursor::new(&*base))?;
+    for index in 0..archive.len() {
+        let entry = archive.by_index_raw(index)?;
+        if content.readme.is_some() && entry.name().eq_ignore_ascii_case(README_ENTRY) {
+            continue;
+        }
+        zip.raw_copy_file(entry)?;
+    }
+
+    if let Some(readme) = content.readme {
+        zip.start_file(README_ENTRY, entry_options(compression))?;
+        zip.write_all(readme.as_bytes())?;
+    }
+
+    let tt_entry_name = format!("Client/{}", content.tt_filename);
+    let tt_options = entry_options(compression);
+    let tt_options = content.password.map_or(tt_options, |password| {
+        tt_options.with_aes_encryption(zip::AesMode::Aes256, password)
+    });
+    zip.start_file(tt_entry_name, tt_options)?;
+    zip.write_all(content.tt_content.as_bytes())?;
+
+    Ok(zip.finish()?)
+}
+
+/// Compressed template entries, rebuilt when any file or directory in the
+/// template or the compression changed since the cached copy was made.
+fn base_zip(tpl_path: &Path, compression: Compression) -> Result<Arc<[u8]>> {
+    let stamp = template_stamp(tpl_path)?;
+    let mut cache = BASE_ZIPS.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(base) = cache.get(tpl_path)
+        && base.stamp == stamp
+        && base.compression == compression
+    {
+        return Ok(Arc::clone(&base.bytes));
+    }
+    let bytes: Arc<[u8]> = build_base_zip(tpl_path, compression)?.into();
+    cache.insert(
+        tpl_path.to_path_buf(),
+        BaseZip {
+            stamp,
+            compression,
+            bytes: Arc::clone(&bytes),
+        },
+    );
+    drop(cache);
+    tracing::debug!(template = %tpl_path.display(), "Rebuilt client template ZIP cache");
+    Ok(bytes)
+}
+
+fn template_stamp(tpl_path: &Path) -> Result<TemplateStamp> {
+    let mut stamp = TemplateStamp {
+        files: 0,
+        bytes: 0,
+        modified: None,
+    };
+    for entry in WalkDir::new(tpl_path) {
+        let metadata = entry?.metadata()?;
+        if metadata.is_file() {
+            stamp.files += 1;
+            stamp.bytes += metadata.len();
+        }
+        stamp.modified = stamp.modified.max(metadata.modified().ok());
+    }
+    Ok(stamp)
+}
+
+fn build_base_zip(tpl_path: &Path, compression: Compression) -> Result<Vec<u8>> {
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = entry_options(compression);
 
     let walk = WalkDir::new(tpl_path);
     for entry in walk {
@@ -47,10 +207,33 @@ pub fn create_client_zip(
         std::io::copy(&mut f, &mut zip)?;
     }
 
-    let tt_entry_name = format!("Client/{tt_filename}");
-    zip.start_file(tt_entry_name, options)?;
-    zip.write_all(tt_content.as_bytes())?;
+    Ok(zip.finish()?.into_inner())
+}
+
+fn entry_options(compression: Compression) -> FileOptions<'static, ()> {
+    let method = match compression.method {
+        ZipCompression::Store => zip::CompressionMethod::Stored,
+        ZipCompression::Deflate => zip::CompressionMethod::Deflated,
+        #[cfg(feature = "zstd")]
+        ZipCompression::Zstd => zip::CompressionMethod::Zstd,
+        // Rejected when the config is loaded.
+        #[cfg(not(feature = "zstd"))]
+        ZipCompression::Zstd => zip::CompressionMethod::Deflated,
+    };
+    FileOptions::<()>::default()
+        .compression_method(method)
+        .compression_level(compression.level)
+        .unix_permissions(0o755)
+}
 
+/// Compress a single file into a new ZIP under `entry_name`.
+pub fn zip_single_file(source: &Path, entry_name: &str, output_path: &Path) -> Result<()> {
+    let file = File::create(output_path)?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = FileOptions::<()>::default().compression_method(zip::CompressionMethod::Deflated);
+    zip.start_file(entry_name, options)?;
+    let mut f = File::open(source)?;
+    std::io::copy(&mut f, &mut zip)?;
     zip.finish()?;
     Ok(())
 }
@@ -1,6 +1,7 @@
+use crate::types::LanguageCode;
 use fluent_templates::{Loader, fluent_bundle::FluentValue};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::{Arc, OnceLock};
@@ -12,22 +13,54 @@ fluent_templates::static_loader! {
     };
 }
 
+/// Languages tried, in order, when a key is missing in the one asked for;
+/// English always comes last.
+static FALLBACK_CHAINS: OnceLock<HashMap<String, Vec<String>>> = OnceLock::new();
+
+/// Install the configured `language_fallbacks`; only the first call counts.
+pub fn set_fallback_chains(chains: &HashMap<LanguageCode, Vec<LanguageCode>>) {
+    let chains = chains
+        .iter()
+        .map(|(lang, chain)| {
+            let chain = chain.iter().map(|code| code.as_str().to_string()).collect();
+            (lang.as_str().to_string(), chain)
+        })
+        .collect();
+    let _ = FALLBACK_CHAINS.set(chains);
+}
+
 /// Translate a message key for the given language.
 pub fn t(lang: &str, key: &str) -> String {
-    let lang_id = lang.parse().unwrap_or(unic_langid::langid!("en"));
-    LOCALES.lookup(&lang_id, key)
+    lookup(lang, key, None)
 }
 
 /// Translate a message key with arguments for the given language.
 pub fn t_args(lang: &str, key: &str, args: &HashMap<String, String>) -> String {
-    let lang_id = lang.parse().unwrap_or(unic_langid::langid!("en"));
-
-    let mut fluent_args: HashMap<Cow<str>, FluentValue> = HashMap::new();
+    let mut fluent_args: HashMap<Cow<'static, str>, FluentValue> = HashMap::new();
     for (k, v) in args {
         fluent_args.insert(Cow::from(k.clone()), FluentValue::from(v.clone()));
     }
 
-    LOCALES.lookup_with_args(&lang_id, key, &fluent_args)
+    lookup(lang, key, Some(&fluent_args))
+}
+
+/// Look `key` up in `lang`, then along its fallback chain, then in English.
+fn lookup(lang: &str, key: &str, args: Option<&HashMap<Cow<'static, str>, FluentValue>>) -> String {
+    let lang_id = lang.parse().unwrap_or(unic_langid::langid!("en"));
+    let chain = FALLBACK_CHAINS
+        .get()
+        .and_then(|chains| chains.get(lang))
+        .into_iter()
+        .flatten();
+    for code in std::iter::once(lang).chain(chain.map(String::as_str)) {
+        let Ok(id) = code.parse() else {
+            continue;
+        };
+        if let Some(text) = LOCALES.lookup_no_default_fallback(&id, key, args) {
+            return text;
+        }
+    }
+    LOCALES.lookup_complete(&lang_id, key, args)
 }
 
 static LANG_CACHE: OnceLock<Arc<Vec<(String, String)>>> = OnceLock::new();
@@ -69,3 +102,29 @@ pub fn available_languages() -> Arc<Vec<(String, String)>> {
     let _ = LANG_CACHE.set(Arc::clone(&cached));
     cached
 }
+
+/// Message keys defined in `lang`'s `.ftl` files.
+pub fn locale_keys(lang: &str) -> BTreeSet<String> {
+    let mut keys = BTreeSet::new();
+    let Ok(entries) = fs::read_dir(PathBuf::from("./locales").join(lang)) else {
+        return keys;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "ftl") {
+            continue;
+        }
+        let Ok(source) = fs::read_to_string(&path) else {
+            continue;
+        };
+        for line in source.lines() {
+            if !line.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                continue;
+            }
+            if let Some((key, _)) = line.split_once('=') {
+                keys.insert(key.trim().to_string());
+            }
+        }
+    }
+    keys
+}
@@ -13,32 +13,57 @@ mod web;
 use anyhow::{Context, Result};
 use clap::Parser;
 use config::AppConfig;
-use db::Database;
-use std::path::PathBuf;
+use db::{Database, PasswordSealer};
+use std::convert::Infallible;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::sync::Arc;
-use std::sync::mpsc;
 use teloxide::dispatching::UpdateHandler;
 use teloxide::dispatching::dialogue::InMemStorage;
 use teloxide::prelude::*;
-use tg_bot::handlers::{Command, MyDialogue, State};
+use teloxide::update_listeners::UpdateListener;
+use tg_bot::handlers::{Command, HandlerResult, MyDialogue, State};
+use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 use tokio::time::{Duration, MissedTickBehavior};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, info, warn};
 use tracing_subscriber::EnvFilter;
+use types::{RESTART_EXIT_CODE, ShutdownHandle};
 
 type HandlerError = Box<dyn std::error::Error + Send + Sync>;
 
+/// How often registrations queued while `TeamTalk` was down, or approvals
+/// interrupted by a crash, are retried.
+const OFFLINE_QUEUE_INTERVAL: Duration = Duration::from_secs(15);
+/// How often held-back admin notifications are checked for a digest.
+const DIGEST_INTERVAL: Duration = Duration::from_mins(1);
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
     #[arg(short, long, default_value = "config.toml")]
     config: String,
+    /// Read a password from stdin, print its hash for `[web.admin]
+    /// password_hash` and exit.
+    #[arg(long)]
+    hash_password: bool,
+    /// Compare every language's messages with English, list unused keys and
+    /// exit; fails when a language lacks keys.
+    #[arg(long)]
+    check_translations: bool,
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
     let args = Args::parse();
+    if args.hash_password {
+        return print_password_hash();
+    }
+    if args.check_translations {
+        return print_translation_report();
+    }
     let config_path = PathBuf::from(&args.config);
     let config = AppConfig::load(&config_path)
         .with_context(|| format!("Failed to load config at {}", config_path.display()))?;
@@ -51,8 +76,48 @@ async fn main() -> Result<()> {
 
     info!(config_path = ?config_path, "Loading config");
     info!("Starting TeamTalk Reg Bot");
+    i18n::set_fallback_chains(&config.language_fallbacks);
+    for coverage in services::translations::coverage() {
+        if coverage.missing.is_empty() {
+            continue;
+        }
+        warn!(
+            lang = coverage.lang,
+            count = coverage.missing.len(),
+            keys = coverage.missing.join(", "),
+            "Translation is missing keys; they fall back along language_fallbacks, then English"
+        );
+    }
+
+    run_app(config).await
+}
+
+fn print_password_hash() -> Result<ExitCode> {
+    let mut password = String::new();
+    std::io::stdin().read_line(&mut password)?;
+    let hash = web::hash_password(password.trim_end_matches(['\r', '\n']))?;
+    writeln!(std::io::stdout(), "{hash}")?;
+    Ok(ExitCode::SUCCESS)
+}
 
-    run_app(config, config_path).await
+fn print_translation_report() -> Result<ExitCode> {
+    let report = services::translations::report();
+    if !report.found_locales() {
+        anyhow::bail!("No English messages in ./locales; run from the directory holding locales/");
+    }
+    // Fluent wraps arguments in bidi isolation marks, which terminals show
+    // as stray characters.
+    let text: String = report
+        .render("en")
+        .chars()
+        .filter(|c| !matches!(c, '\u{2068}' | '\u{2069}'))
+        .collect();
+    writeln!(std::io::stdout(), "{text}")?;
+    Ok(if report.is_complete() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
 }
 
 fn init_tracing(env_filter: EnvFilter) {
@@ -80,13 +145,14 @@ fn build_env_filter(config: &AppConfig) -> (EnvFilter, Option<String>) {
     )
 }
 
-async fn run_app(config: AppConfig, config_path: PathBuf) -> Result<()> {
+async fn run_app(config: AppConfig) -> Result<ExitCode> {
     let shutdown = CancellationToken::new();
-    let db = init_db(&config, &config_path).await?;
-    let (tx_tt, rx_tt) = mpsc::channel();
+    let shutdown_handle = ShutdownHandle::new(shutdown.clone());
+    let db = init_db(&config).await?;
+    let (servers, receivers) = server_channels(&config);
     let bot = Bot::new(&config.telegram.tg_bot_token);
 
-    ensure_temp_dir()?;
+    std::fs::create_dir_all(config.get_temp_dir())?;
 
     let cleanup_handle = spawn_cleanup_task(
         db.clone(),
@@ -95,26 +161,49 @@ async fn run_app(config: AppConfig, config_path: PathBuf) -> Result<()> {
         config.database.pending_reg_ttl_seconds,
         config.database.registered_ip_ttl_seconds,
         config.database.generated_file_ttl_seconds,
+        config.get_temp_dir().to_path_buf(),
     );
+    let periodic_handles = spawn_periodic_tasks(&bot, &db, &servers, &shutdown, &config);
+
+    let tt_handles = servers
+        .all()
+        .iter()
+        .zip(receivers)
+        .map(|(server, rx_tt)| {
+            spawn_tt_worker(
+                server.config.clone(),
+                rx_tt,
+                server.tx_tt.clone(),
+                bot.clone(),
+                db.clone(),
+                tokio::runtime::Handle::current(),
+                shutdown.clone(),
+            )
+        })
+        .collect();
+
+    tg_bot::commands::register_commands(&bot, &config).await;
+    let webhook = tg_bot::webhook::start(&bot, &config).await?;
+    let (listener, webhook_router) = webhook.map_or((None, None), |w| (Some(w.listener), w.router));
 
-    let tt_handle = spawn_tt_worker(
-        Arc::new(config.clone()),
-        rx_tt,
+    let web_handle = spawn_web_server(
+        &config,
         bot.clone(),
         db.clone(),
-        tokio::runtime::Handle::current(),
+        servers.clone(),
         shutdown.clone(),
+        webhook_router,
     );
 
-    let web_handle = spawn_web_server(&config, db.clone(), tx_tt.clone(), shutdown.clone());
-
-    let (dispatch_handle, shutdown_task) = spawn_dispatcher(bot, &db, tx_tt, config, shutdown);
+    let (dispatch_handle, shutdown_task) =
+        spawn_dispatcher(bot, &db, servers, config, shutdown_handle.clone(), listener);
 
     wait_for_tasks(
         dispatch_handle,
         shutdown_task,
         cleanup_handle,
-        tt_handle,
+        periodic_handles,
+        tt_handles,
         web_handle,
     )
     .await;
@@ -123,22 +212,105 @@ async fn run_app(config: AppConfig, config_path: PathBuf) -> Result<()> {
     db.close().await;
     info!("Database pool closed.");
 
-    Ok(())
+    if shutdown_handle.restart_requested() {
+        info!(code = RESTART_EXIT_CODE, "Exiting for restart");
+        return Ok(ExitCode::from(RESTART_EXIT_CODE));
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// A command channel per server, the top-level one first, with the receivers
+/// for their workers.
+fn server_channels(
+    config: &AppConfig,
+) -> (tt::TTServers, Vec<mpsc::Receiver<types::TTWorkerCommand>>) {
+    let configs = std::iter::once(config.clone()).chain(
+        config
+            .teamtalk_servers
+            .iter()
+            .map(|server| config.for_server(server)),
+    );
+    let (servers, receivers) = configs
+        .map(|config| {
+            let (tx_tt, rx_tt) = tt::TTWorkerHandle::channel(
+                config.teamtalk.tt_command_queue_size,
+                Duration::from_secs(config.teamtalk.tt_command_timeout_seconds),
+            );
+            let server = tt::TTServer {
+                config: Arc::new(config),
+                tx_tt,
+            };
+            (server, rx_tt)
+        })
+        .unzip();
+    (tt::TTServers::new(servers), receivers)
 }
 
-async fn init_db(config: &AppConfig, config_path: &std::path::Path) -> Result<Database> {
-    let db_path = config.get_db_path(config_path);
+async fn init_db(config: &AppConfig) -> Result<Database> {
+    let sealer = match &config.database.pending_password_key {
+        Some(key) => PasswordSealer::from_base64(key)?,
+        None => PasswordSealer::load_or_create(&config.get_pending_password_key_file())?,
+    };
+    if let Some(url) = &config.database.database_url {
+        return Database::connect(url, sealer).await;
+    }
+    let db_path = config.get_db_path();
     let db_path_str = db_path.to_string_lossy().to_string();
     debug!(db_path = db_path_str, "Database path");
-    Database::new(&db_path_str).await
+    Database::new(&db_path_str, sealer).await
 }
 
-fn ensure_temp_dir() -> Result<()> {
-    let temp_files_dir = std::env::current_dir()?.join("temp_files");
-    if !temp_files_dir.exists() {
-        std::fs::create_dir(&temp_files_dir)?;
-    }
-    Ok(())
+/// Start the background tasks that run on a schedule; disabled ones are `None`.
+fn spawn_periodic_tasks(
+    bot: &Bot,
+    db: &Database,
+    servers: &tt::TTServers,
+    shutdown: &CancellationToken,
+    config: &AppConfig,
+) -> [(&'static str, Option<JoinHandle<()>>); 5] {
+    [
+        (
+            "Backup",
+            spawn_backup_task(db.clone(), shutdown.clone(), config),
+        ),
+        (
+            "Offline queue",
+            Some(spawn_offline_queue_task(
+                bot.clone(),
+                db.clone(),
+                servers.clone(),
+                shutdown.clone(),
+            )),
+        ),
+        (
+            "Pending requests",
+            Some(spawn_pending_task(
+                bot.clone(),
+                db.clone(),
+                shutdown.clone(),
+                config,
+            )),
+        ),
+        (
+            "Notification digest",
+            Some(spawn_digest_task(
+                bot.clone(),
+                db.clone(),
+                shutdown.clone(),
+                config,
+            )),
+        ),
+        (
+            "Reconciliation",
+            spawn_reconcile_task(
+                bot.clone(),
+                db.clone(),
+                servers.clone(),
+                shutdown.clone(),
+                config,
+            ),
+        ),
+    ]
 }
 
 fn spawn_cleanup_task(
@@ -148,6 +320,7 @@ fn spawn_cleanup_task(
     pending_ttl_seconds: u64,
     registered_ip_ttl_seconds: u64,
     generated_file_ttl_seconds: u64,
+    temp_dir: PathBuf,
 ) -> JoinHandle<()> {
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(Duration::from_secs(cleanup_interval_seconds));
@@ -159,22 +332,168 @@ fn spawn_cleanup_task(
                 _ = interval.tick() => {}
             }
             debug!("Running periodic cleanup");
-            if let Err(e) = db
-                .cleanup(pending_ttl_seconds, registered_ip_ttl_seconds)
-                .await
-            {
-                tracing::error!(error = %e, "DB cleanup failed");
+            run_cleanup(
+                &db,
+                &temp_dir,
+                pending_ttl_seconds,
+                registered_ip_ttl_seconds,
+                generated_file_ttl_seconds,
+            )
+            .await;
+        }
+    })
+}
+
+/// Delete expired and used rows together with their generated files, then
+/// sweep files in `temp_dir` older than `generated_file_ttl_seconds`.
+async fn run_cleanup(
+    db: &Database,
+    temp_dir: &Path,
+    pending_ttl_seconds: u64,
+    registered_ip_ttl_seconds: u64,
+    generated_file_ttl_seconds: u64,
+) {
+    match db
+        .cleanup(pending_ttl_seconds, registered_ip_ttl_seconds)
+        .await
+    {
+        Ok(files) => {
+            for file in &files {
+                services::registration::remove_generated_file(temp_dir, file).await;
+            }
+        }
+        Err(e) => tracing::error!(error = %e, "DB cleanup failed"),
+    }
+    cleanup_temp_files(temp_dir.to_path_buf(), generated_file_ttl_seconds).await;
+}
+
+fn spawn_backup_task(
+    db: Database,
+    shutdown: CancellationToken,
+    config: &AppConfig,
+) -> Option<JoinHandle<()>> {
+    let interval_seconds = config.database.db_backup_interval_seconds;
+    if interval_seconds == 0 {
+        return None;
+    }
+    if !db.supports_backup() {
+        warn!("Scheduled backups only support SQLite; back up PostgreSQL with pg_dump");
+        return None;
+    }
+    let dir = config.get_backup_dir();
+    let retention = config.database.db_backup_retention.max(1);
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_seconds));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        interval.tick().await;
+        loop {
+            tokio::select! {
+                () = shutdown.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+            match services::backup::create_backup(&db, &dir).await {
+                Ok(path) => info!(path = %path.display(), "Database backup written"),
+                Err(e) => tracing::error!(error = %e, "Database backup failed"),
+            }
+            if let Err(e) = services::backup::prune_backups(&dir, retention).await {
+                tracing::error!(error = %e, "Pruning old backups failed");
             }
-            cleanup_temp_files(generated_file_ttl_seconds).await;
+        }
+    }))
+}
+
+fn spawn_offline_queue_task(
+    bot: Bot,
+    db: Database,
+    servers: tt::TTServers,
+    shutdown: CancellationToken,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(OFFLINE_QUEUE_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                () = shutdown.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+            tg_bot::handlers::replay_queued_registrations(&bot, &db, &servers).await;
         }
     })
 }
 
-async fn cleanup_temp_files(file_ttl_seconds: u64) {
-    let temp_dir = match std::env::current_dir() {
-        Ok(dir) => dir.join("temp_files"),
-        Err(_) => return,
-    };
+fn spawn_reconcile_task(
+    bot: Bot,
+    db: Database,
+    servers: tt::TTServers,
+    shutdown: CancellationToken,
+    config: &AppConfig,
+) -> Option<JoinHandle<()>> {
+    let interval_seconds = config.teamtalk.tt_reconcile_interval_seconds;
+    if interval_seconds == 0 {
+        return None;
+    }
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_seconds));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        interval.tick().await;
+        loop {
+            tokio::select! {
+                () = shutdown.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+            for server in servers.all() {
+                tg_bot::handlers::run_reconciliation(&bot, &db, &server.config, &server.tx_tt)
+                    .await;
+            }
+        }
+    }))
+}
+
+fn spawn_pending_task(
+    bot: Bot,
+    db: Database,
+    shutdown: CancellationToken,
+    config: &AppConfig,
+) -> JoinHandle<()> {
+    let config = config.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(
+            config.database.db_cleanup_interval_seconds,
+        ));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        let mut reminders = tg_bot::handlers::PendingReminders::default();
+        loop {
+            tokio::select! {
+                () = shutdown.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+            tg_bot::handlers::expire_pending_requests(&bot, &db, &config).await;
+            reminders.run(&bot, &db, &config).await;
+        }
+    })
+}
+
+fn spawn_digest_task(
+    bot: Bot,
+    db: Database,
+    shutdown: CancellationToken,
+    config: &AppConfig,
+) -> JoinHandle<()> {
+    let tg = config.telegram.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(DIGEST_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                () = shutdown.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+            tg_bot::notify::send_digests(&bot, &db, &tg).await;
+        }
+    })
+}
+
+async fn cleanup_temp_files(temp_dir: PathBuf, file_ttl_seconds: u64) {
     let _ = tokio::task::spawn_blocking(move || {
         if let Ok(entries) = std::fs::read_dir(&temp_dir) {
             for entry in entries.flatten() {
@@ -196,54 +515,99 @@ async fn cleanup_temp_files(file_ttl_seconds: u64) {
 fn spawn_tt_worker(
     config: Arc<AppConfig>,
     rx_tt: mpsc::Receiver<types::TTWorkerCommand>,
+    tx_tt: tt::TTWorkerHandle,
     bot: Bot,
     db: Database,
     rt_handle: tokio::runtime::Handle,
     shutdown: CancellationToken,
 ) -> JoinHandle<()> {
     tokio::spawn(async move {
-        tt::run_tt_worker(config, rx_tt, bot, db, rt_handle, shutdown).await;
+        tt::run_tt_worker(config, rx_tt, tx_tt, bot, db, rt_handle, shutdown).await;
     })
 }
 
 fn spawn_web_server(
     config: &AppConfig,
+    bot: Bot,
     db: Database,
-    tx_tt: mpsc::Sender<types::TTWorkerCommand>,
+    servers: tt::TTServers,
     shutdown: CancellationToken,
+    webhook: Option<axum::Router>,
 ) -> Option<JoinHandle<()>> {
-    if !config.web.web_registration_enabled {
+    if !config.web.web_registration_enabled
+        && config.web.sections.admin.is_none()
+        && webhook.is_none()
+    {
         return None;
     }
     let web_config = config.clone();
     Some(tokio::spawn(async move {
-        web::run_server(web_config, db, tx_tt, shutdown).await;
+        web::run_server(web_config, bot, db, servers, shutdown, webhook).await;
     }))
 }
 
+async fn dispatch_command(
+    bot: Bot,
+    msg: Message,
+    cmd: Command,
+    db: Database,
+    config: Arc<AppConfig>,
+    dialogue: MyDialogue,
+    servers: tt::TTServers,
+) -> HandlerResult {
+    // Admin commands act on the top-level server only; accounts on the other
+    // servers are managed there directly.
+    let tx_tt = servers.primary().tx_tt.clone();
+    match cmd {
+        Command::Start => tg_bot::handlers::start(bot, msg, dialogue, db, config, servers).await,
+        Command::Cancel => tg_bot::handlers::cancel(bot, msg, dialogue, config).await,
+        Command::MyAccount => tg_bot::handlers::my_account(bot, msg, db, config).await,
+        Command::ChangePassword => {
+            tg_bot::handlers::change_password(bot, msg, dialogue, db, config).await
+        }
+        Command::AdminPanel => tg_bot::handlers::admin_panel(bot, msg, config, dialogue).await,
+        Command::Online => tg_bot::handlers::online_users(bot, msg, config, tx_tt).await,
+        Command::Stats => tg_bot::handlers::stats(bot, msg, db, config, tx_tt).await,
+        Command::TtStatus => tg_bot::handlers::tt_status(bot, msg, config, tx_tt).await,
+        Command::Audit(args) => tg_bot::handlers::audit_log(bot, msg, db, config, args).await,
+        Command::Broadcast(text) => {
+            tg_bot::handlers::tt_broadcast_command(bot, msg, config, tx_tt, text).await
+        }
+        Command::Register(args) => {
+            tg_bot::handlers::admin_register(bot, msg, db, config, tx_tt, args).await
+        }
+        Command::Generate(args) => {
+            tg_bot::handlers::generate_invite(bot, msg, db, config, args).await
+        }
+        Command::QuietHours(args) => {
+            tg_bot::handlers::quiet_hours_command(bot, msg, db, config, args).await
+        }
+        Command::Translations => tg_bot::handlers::translations_command(bot, msg, config).await,
+        Command::Backup => tg_bot::handlers::backup_command(bot, msg, db, config).await,
+        // Routed to `exit_bot` before reaching here; see `build_message_handler`.
+        Command::Exit | Command::Restart => Ok(()),
+        Command::Help => tg_bot::handlers::help(bot, msg, db, config).await,
+    }
+}
+
 fn build_message_handler() -> UpdateHandler<HandlerError> {
     Update::filter_message()
         .enter_dialogue::<Message, InMemStorage<State>, State>()
-        .branch(dptree::entry().filter_command::<Command>().endpoint(
-            |bot: Bot,
-             msg: Message,
-             cmd: Command,
-             db: Database,
-             config: Arc<AppConfig>,
-             dialogue: MyDialogue| async move {
-                match cmd {
-                    Command::Start => tg_bot::handlers::start(bot, msg, dialogue, db, config).await,
-                    Command::AdminPanel => {
-                        tg_bot::handlers::admin_panel(bot, msg, config, dialogue).await
-                    }
-                    Command::Generate => {
-                        tg_bot::handlers::generate_invite(bot, msg, db, config).await
-                    }
-                    Command::Exit => tg_bot::handlers::exit_bot(bot, msg, config).await,
-                    Command::Help => Ok(()),
-                }
-            },
-        ))
+        // Group chats (e.g. the shared admin chat) only take admin replies such as reasons.
+        .branch(
+            dptree::filter(|msg: Message| !msg.chat.is_private())
+                .branch(build_admin_input_handler())
+                .endpoint(|| async { Ok(()) }),
+        )
+        .branch(
+            dptree::entry()
+                .filter_command::<Command>()
+                .branch(
+                    dptree::filter(|cmd: Command| matches!(cmd, Command::Exit | Command::Restart))
+                        .endpoint(tg_bot::handlers::exit_bot),
+                )
+                .endpoint(dispatch_command),
+        )
         .branch(
             dptree::filter_async(|d: MyDialogue| async move {
                 match d.get().await {
@@ -271,6 +635,21 @@ fn build_message_handler() -> UpdateHandler<HandlerError> {
             })
             .endpoint(tg_bot::handlers::receive_username),
         )
+        .branch(
+            dptree::filter_async(|d: MyDialogue| async move {
+                match d.get().await {
+                    Ok(state) => matches!(state, Some(State::AwaitingNewPassword { .. })),
+                    Err(e) => {
+                        tracing::warn!(
+                            error = %e,
+                            "Failed to read dialogue state (AwaitingNewPassword)"
+                        );
+                        false
+                    }
+                }
+            })
+            .endpoint(tg_bot::handlers::receive_new_password),
+        )
         .branch(
             dptree::filter_async(|d: MyDialogue| async move {
                 match d.get().await {
@@ -301,6 +680,12 @@ fn build_message_handler() -> UpdateHandler<HandlerError> {
             })
             .endpoint(tg_bot::handlers::receive_nickname),
         )
+        .branch(build_admin_input_handler())
+}
+
+/// Free-text input expected from admins after a panel action.
+fn build_admin_input_handler() -> UpdateHandler<HandlerError> {
+    dptree::entry()
         .branch(
             dptree::filter_async(|d: MyDialogue| async move {
                 match d.get().await {
@@ -316,6 +701,117 @@ fn build_message_handler() -> UpdateHandler<HandlerError> {
             })
             .endpoint(tg_bot::handlers::admin_manual_ban_input),
         )
+        .branch(
+            dptree::filter_async(|d: MyDialogue| async move {
+                match d.get().await {
+                    Ok(state) => matches!(state, Some(State::AwaitingTeamTalkMessage { .. })),
+                    Err(e) => {
+                        tracing::warn!(
+                            error = %e,
+                            "Failed to read dialogue state (AwaitingTeamTalkMessage)"
+                        );
+                        false
+                    }
+                }
+            })
+            .endpoint(tg_bot::handlers::admin_tt_message_input),
+        )
+        .branch(
+            dptree::filter_async(|d: MyDialogue| async move {
+                match d.get().await {
+                    Ok(state) => matches!(state, Some(State::AwaitingTeamTalkBroadcast)),
+                    Err(e) => {
+                        tracing::warn!(
+                            error = %e,
+                            "Failed to read dialogue state (AwaitingTeamTalkBroadcast)"
+                        );
+                        false
+                    }
+                }
+            })
+            .endpoint(tg_bot::handlers::admin_tt_broadcast_input),
+        )
+        .branch(
+            dptree::filter_async(|d: MyDialogue| async move {
+                match d.get().await {
+                    Ok(state) => matches!(state, Some(State::AwaitingTelegramBroadcast)),
+                    Err(e) => {
+                        tracing::warn!(
+                            error = %e,
+                            "Failed to read dialogue state (AwaitingTelegramBroadcast)"
+                        );
+                        false
+                    }
+                }
+            })
+            .endpoint(tg_bot::handlers::admin_tg_broadcast_input),
+        )
+        .branch(build_admin_search_handler())
+        .branch(
+            dptree::filter_async(|d: MyDialogue| async move {
+                match d.get().await {
+                    Ok(state) => matches!(state, Some(State::AwaitingDecisionReason { .. })),
+                    Err(e) => {
+                        tracing::warn!(
+                            error = %e,
+                            "Failed to read dialogue state (AwaitingDecisionReason)"
+                        );
+                        false
+                    }
+                }
+            })
+            .endpoint(tg_bot::handlers::admin_decision_reason_input),
+        )
+        .branch(
+            dptree::filter_async(|d: MyDialogue| async move {
+                match d.get().await {
+                    Ok(state) => matches!(state, Some(State::AwaitingApprovalUsername { .. })),
+                    Err(e) => {
+                        tracing::warn!(
+                            error = %e,
+                            "Failed to read dialogue state (AwaitingApprovalUsername)"
+                        );
+                        false
+                    }
+                }
+            })
+            .endpoint(tg_bot::handlers::admin_rename_input),
+        )
+}
+
+/// Search text for the admin panel search and list filters.
+fn build_admin_search_handler() -> UpdateHandler<HandlerError> {
+    dptree::entry()
+        .branch(
+            dptree::filter_async(|d: MyDialogue| async move {
+                match d.get().await {
+                    Ok(state) => matches!(state, Some(State::AwaitingSearch)),
+                    Err(e) => {
+                        tracing::warn!(
+                            error = %e,
+                            "Failed to read dialogue state (AwaitingSearch)"
+                        );
+                        false
+                    }
+                }
+            })
+            .endpoint(tg_bot::handlers::admin_search_input),
+        )
+        .branch(
+            dptree::filter_async(|d: MyDialogue| async move {
+                match d.get().await {
+                    Ok(state) => matches!(state, Some(State::AwaitingListFilter { .. })),
+                    Err(e) => {
+                        tracing::warn!(
+                            error = %e,
+                            "Failed to read dialogue state (AwaitingListFilter)"
+                        );
+                        false
+                    }
+                }
+            })
+            .endpoint(tg_bot::handlers::admin_list_filter_input),
+        )
 }
 
 fn build_callback_handler() -> UpdateHandler<HandlerError> {
@@ -324,7 +820,7 @@ fn build_callback_handler() -> UpdateHandler<HandlerError> {
         .branch(
             dptree::filter_async(|d: MyDialogue| async move {
                 match d.get().await {
-                    Ok(state) => matches!(state, Some(State::ChoosingLanguage)),
+                    Ok(state) => matches!(state, Some(State::ChoosingLanguage { .. })),
                     Err(e) => {
                         tracing::warn!(
                             error = %e,
@@ -336,6 +832,21 @@ fn build_callback_handler() -> UpdateHandler<HandlerError> {
             })
             .endpoint(tg_bot::handlers::receive_language),
         )
+        .branch(
+            dptree::filter_async(|d: MyDialogue| async move {
+                match d.get().await {
+                    Ok(state) => matches!(state, Some(State::ChoosingServer { .. })),
+                    Err(e) => {
+                        tracing::warn!(
+                            error = %e,
+                            "Failed to read dialogue state (ChoosingServer)"
+                        );
+                        false
+                    }
+                }
+            })
+            .endpoint(tg_bot::handlers::receive_server),
+        )
         .branch(
             dptree::filter_async(|d: MyDialogue| async move {
                 match d.get().await {
@@ -351,10 +862,31 @@ fn build_callback_handler() -> UpdateHandler<HandlerError> {
             })
             .endpoint(tg_bot::handlers::receive_nickname_choice),
         )
+        .branch(
+            dptree::filter(|q: CallbackQuery| q.data.as_deref() == Some("password_generate"))
+                .filter_async(|d: MyDialogue| async move {
+                    match d.get().await {
+                        Ok(state) => matches!(state, Some(State::AwaitingPassword { .. })),
+                        Err(e) => {
+                            tracing::warn!(
+                                error = %e,
+                                "Failed to read dialogue state (AwaitingPassword)"
+                            );
+                            false
+                        }
+                    }
+                })
+                .endpoint(tg_bot::handlers::generate_password_callback),
+        )
         .branch(
             dptree::filter_async(|d: MyDialogue| async move {
                 match d.get().await {
-                    Ok(state) => matches!(state, Some(State::AwaitingAccountType { .. })),
+                    Ok(state) => matches!(
+                        state,
+                        Some(
+                            State::AwaitingAccountType { .. } | State::AwaitingRightsProfile { .. }
+                        )
+                    ),
                     Err(e) => {
                         tracing::warn!(
                             error = %e,
@@ -366,16 +898,28 @@ fn build_callback_handler() -> UpdateHandler<HandlerError> {
             })
             .endpoint(tg_bot::handlers::receive_account_type),
         )
+        .branch(
+            dptree::filter(|q: CallbackQuery| {
+                q.data
+                    .as_deref()
+                    .is_some_and(|d| d.starts_with(tg_bot::handlers::MY_ACCOUNT_CALLBACK_PREFIX))
+            })
+            .endpoint(tg_bot::handlers::my_account_callback),
+        )
         .branch(dptree::entry().endpoint(tg_bot::handlers::admin_callback))
 }
 
-fn spawn_dispatcher(
+fn spawn_dispatcher<L>(
     bot: Bot,
     db: &Database,
-    tx_tt: mpsc::Sender<types::TTWorkerCommand>,
+    servers: tt::TTServers,
     config: AppConfig,
-    shutdown: CancellationToken,
-) -> (JoinHandle<()>, JoinHandle<()>) {
+    shutdown: ShutdownHandle,
+    webhook: Option<L>,
+) -> (JoinHandle<()>, JoinHandle<()>)
+where
+    L: UpdateListener<Err = Infallible> + Send + 'static,
+{
     let config_arc = Arc::new(config);
     let schema = dptree::entry()
         .branch(build_message_handler())
@@ -385,19 +929,34 @@ fn spawn_dispatcher(
         .dependencies(dptree::deps![
             db.clone(),
             config_arc,
-            tx_tt,
+            servers.primary().tx_tt.clone(),
+            servers,
+            shutdown.clone(),
             InMemStorage::<State>::new()
         ])
         .build();
 
     let shutdown_token = dispatcher.shutdown_token();
     let dispatch_handle = tokio::spawn(async move {
-        dispatcher.dispatch().await;
+        match webhook {
+            Some(listener) => {
+                dispatcher
+                    .dispatch_with_listener(
+                        listener,
+                        LoggingErrorHandler::with_custom_text("An error from the webhook listener"),
+                    )
+                    .await;
+            }
+            None => dispatcher.dispatch().await,
+        }
     });
 
     let shutdown_task = tokio::spawn(async move {
-        wait_for_shutdown_signal().await;
-        shutdown.cancel();
+        tokio::select! {
+            () = wait_for_shutdown_signal() => {}
+            () = shutdown.cancelled() => info!("Shutdown requested from Telegram"),
+        }
+        shutdown.request(false);
         if let Ok(fut) = shutdown_token.shutdown() {
             fut.await;
         }
@@ -410,7 +969,8 @@ async fn wait_for_tasks(
     dispatch_handle: JoinHandle<()>,
     shutdown_task: JoinHandle<()>,
     cleanup_handle: JoinHandle<()>,
-    tt_handle: JoinHandle<()>,
+    periodic_handles: [(&str, Option<JoinHandle<()>>); 5],
+    tt_handles: Vec<JoinHandle<()>>,
     web_handle: Option<JoinHandle<()>>,
 ) {
     if let Err(e) = dispatch_handle.await {
@@ -422,8 +982,17 @@ async fn wait_for_tasks(
     if let Err(e) = cleanup_handle.await {
         tracing::error!(error = ?e, "Cleanup task failed");
     }
-    if let Err(e) = tt_handle.await {
-        tracing::error!(error = ?e, "TT worker task failed");
+    for (task, handle) in periodic_handles {
+        if let Some(handle) = handle
+            && let Err(e) = handle.await
+        {
+            tracing::error!(error = ?e, task, "Periodic task failed");
+        }
+    }
+    for handle in tt_handles {
+        if let Err(e) = handle.await {
+            tracing::error!(error = ?e, "TT worker task failed");
+        }
     }
     if let Some(handle) = web_handle
         && let Err(e) = handle.await
@@ -456,3 +1025,64 @@ async fn wait_for_shutdown_signal() {
         tracing::error!(error = %e, "Failed to listen for Ctrl+C");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeDelta, Utc};
+    use std::time::SystemTime;
+
+    const HOUR: u64 = 3600;
+
+    #[tokio::test]
+    async fn cleanup_deletes_files_of_used_and_expired_tokens() -> Result<()> {
+        let (db, dir) = Database::open_temp().await?;
+        let temp_dir = dir.join("temp");
+        std::fs::create_dir(&temp_dir)?;
+        for file in ["expired.tt", "used.tt", "live.tt"] {
+            std::fs::write(temp_dir.join(file), "tt")?;
+        }
+        let now = Utc::now();
+        let later = (now + TimeDelta::hours(1)).naive_utc();
+        db.add_test_download_token(
+            "expired",
+            "expired.tt",
+            Some(1),
+            (now - TimeDelta::hours(1)).naive_utc(),
+        )
+        .await?;
+        db.add_test_download_token("used", "used.tt", Some(1), later)
+            .await?;
+        db.add_test_download_token("live", "live.tt", Some(1), later)
+            .await?;
+        assert_eq!(db.consume_download_token("used").await?, Some(true));
+
+        run_cleanup(&db, &temp_dir, HOUR, HOUR, HOUR).await;
+
+        assert!(!temp_dir.join("expired.tt").exists());
+        assert!(!temp_dir.join("used.tt").exists());
+        assert!(temp_dir.join("live.tt").exists());
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn cleanup_sweeps_old_orphaned_files() -> Result<()> {
+        let (db, dir) = Database::open_temp().await?;
+        let temp_dir = dir.join("temp");
+        std::fs::create_dir(&temp_dir)?;
+        std::fs::write(temp_dir.join("orphan.tt"), "tt")?;
+        std::fs::write(temp_dir.join("fresh.tt"), "tt")?;
+        std::fs::File::options()
+            .write(true)
+            .open(temp_dir.join("orphan.tt"))?
+            .set_modified(SystemTime::now() - Duration::from_secs(2 * HOUR))?;
+
+        run_cleanup(&db, &temp_dir, HOUR, HOUR, HOUR).await;
+
+        assert!(!temp_dir.join("orphan.tt").exists());
+        assert!(temp_dir.join("fresh.tt").exists());
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
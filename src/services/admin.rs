@@ -1,32 +1,48 @@
-/// Parsed metadata about the registration source.
-pub struct SourceInfo {
-    pub lang: crate::types::LanguageCode,
-    pub tg_username: String,
-    pub fullname: String,
-}
-
-/// Parse stored source info into structured fields.
-pub fn parse_source_info(source_info: &str) -> SourceInfo {
-    let mut lang = crate::types::LanguageCode::default();
-    let mut tg_username = String::new();
-    let mut fullname = String::new();
-
-    for part in source_info.split(';') {
-        let mut iter = part.splitn(2, '=');
-        let Some(key) = iter.next() else { continue };
-        let Some(val) = iter.next() else { continue };
+use crate::db::Database;
+use crate::domain::Username;
+use crate::tt::TTWorkerHandle;
+use crate::types::{TTCommandError, TTWorkerCommand, TelegramId};
+use tracing::warn;
 
-        match key {
-            "lang" => lang = crate::types::LanguageCode::parse_or_default(val),
-            "tg_username" => tg_username = val.to_string(),
-            "fullname" => fullname = val.to_string(),
-            _ => {}
-        }
+/// Remove a Telegram registration and ban the user from registering again.
+///
+/// Returns `false` if there was no registration for `tg_id`.
+pub async fn delete_registration_and_ban(
+    db: &Database,
+    tg_id: TelegramId,
+    admin_id: Option<TelegramId>,
+    reason: &str,
+) -> anyhow::Result<bool> {
+    let reg = db.get_registration_by_id(tg_id).await?;
+    if !db.delete_registration(tg_id).await? {
+        return Ok(false);
     }
+    let username = reg.as_ref().map(|r| r.teamtalk_username.as_str());
+    let server_id = reg.as_ref().map(|r| r.server_id.as_str());
+    db.ban_user(tg_id, username, server_id, admin_id, Some(reason))
+        .await?;
+    Ok(true)
+}
 
-    SourceInfo {
-        lang,
-        tg_username,
-        fullname,
+/// Delete a `TeamTalk` account through the worker.
+///
+/// On failure returns why the server refused or the worker failed.
+pub async fn delete_teamtalk_account(
+    tx_tt: &TTWorkerHandle,
+    username: Username,
+) -> Result<(), TTCommandError> {
+    match tx_tt
+        .request(|resp| TTWorkerCommand::DeleteUser { username, resp })
+        .await
+    {
+        Ok(Ok(true)) => Ok(()),
+        Ok(Ok(false)) => Err(TTCommandError::Other(
+            "Command indicated failure without a specific error.".to_string(),
+        )),
+        Ok(Err(err)) => Err(err),
+        Err(e) => {
+            warn!(error = %e, "TeamTalk delete user request failed");
+            Err(TTCommandError::Other(e.to_string()))
+        }
     }
 }
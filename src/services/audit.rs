@@ -0,0 +1,98 @@
+use crate::db::Database;
+use crate::types::TelegramId;
+use std::fmt;
+use std::net::IpAddr;
+use tracing::{info, warn};
+
+/// Who performed an audited action.
+#[derive(Clone, Copy, Debug)]
+pub enum Actor {
+    /// A bot admin, by Telegram ID.
+    Admin(TelegramId),
+    /// Someone signed in to the web dashboard.
+    Dashboard,
+    /// The service itself, e.g. reacting to `TeamTalk` server events.
+    System,
+    /// An anonymous web visitor.
+    Visitor(IpAddr),
+}
+
+impl fmt::Display for Actor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Admin(id) => write!(f, "admin:{id}"),
+            Self::Dashboard => f.write_str("dashboard"),
+            Self::System => f.write_str("system"),
+            Self::Visitor(ip) => write!(f, "ip:{ip}"),
+        }
+    }
+}
+
+/// Kind of audited action, stored as its lowercase name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuditAction {
+    Approve,
+    Reject,
+    Ban,
+    Unban,
+    AutoBan,
+    DeleteTeamTalkAccount,
+    CreateAccount,
+    GenerateInvite,
+    RevokeInvite,
+    WebRegistration,
+    Backup,
+    RemoveRegistration,
+    TeamTalkRegistration,
+    RenameRequest,
+    ExpireRequest,
+    DashboardLogin,
+    DownloadBlocked,
+    RevokeDownload,
+}
+
+impl AuditAction {
+    /// Convert action to its storage string.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Approve => "approve",
+            Self::Reject => "reject",
+            Self::Ban => "ban",
+            Self::Unban => "unban",
+            Self::AutoBan => "auto_ban",
+            Self::DeleteTeamTalkAccount => "delete_tt_account",
+            Self::CreateAccount => "create_account",
+            Self::GenerateInvite => "generate_invite",
+            Self::RevokeInvite => "revoke_invite",
+            Self::WebRegistration => "web_registration",
+            Self::Backup => "backup",
+            Self::RemoveRegistration => "remove_registration",
+            Self::TeamTalkRegistration => "tt_registration",
+            Self::RenameRequest => "rename_request",
+            Self::ExpireRequest => "expire_request",
+            Self::DashboardLogin => "dashboard_login",
+            Self::DownloadBlocked => "download_blocked",
+            Self::RevokeDownload => "revoke_download",
+        }
+    }
+}
+
+/// Record an action in the audit log.
+///
+/// A failed write is logged but never fails the action itself.
+pub async fn record(
+    db: &Database,
+    actor: Actor,
+    action: AuditAction,
+    target: &str,
+    details: Option<&str>,
+) {
+    let actor = actor.to_string();
+    info!(%actor, action = action.as_str(), target, details, "Audit");
+    if let Err(e) = db
+        .add_audit_entry(&actor, action.as_str(), target, details)
+        .await
+    {
+        warn!(error = %e, action = action.as_str(), target, "Failed to write audit log entry");
+    }
+}
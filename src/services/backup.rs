@@ -0,0 +1,50 @@
+use crate::db::Database;
+use crate::files::zip_single_file;
+use anyhow::Result;
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+const BACKUP_PREFIX: &str = "users_backup_";
+
+/// Snapshot the database into `dir` as a ZIP and return its path.
+pub async fn create_backup(db: &Database, dir: &Path) -> Result<PathBuf> {
+    tokio::fs::create_dir_all(dir).await?;
+    let stamp = Utc::now().format("%Y%m%d_%H%M%S");
+    let db_name = format!("{BACKUP_PREFIX}{stamp}.db");
+    let snapshot = dir.join(&db_name);
+    let archive = dir.join(format!("{BACKUP_PREFIX}{stamp}.zip"));
+    db.backup(&snapshot).await?;
+
+    let (source, output) = (snapshot.clone(), archive.clone());
+    let zipped =
+        tokio::task::spawn_blocking(move || zip_single_file(&source, &db_name, &output)).await;
+    if let Err(e) = tokio::fs::remove_file(&snapshot).await {
+        warn!(error = %e, path = %snapshot.display(), "Failed to remove raw backup snapshot");
+    }
+    zipped??;
+    Ok(archive)
+}
+
+/// Delete all but the newest `keep` backups in `dir`.
+pub async fn prune_backups(dir: &Path, keep: usize) -> Result<()> {
+    let mut backups = Vec::new();
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with(BACKUP_PREFIX)
+            && Path::new(&name)
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+        {
+            backups.push(entry.path());
+        }
+    }
+    // Timestamps in the names sort chronologically.
+    backups.sort();
+    let excess = backups.len().saturating_sub(keep);
+    for path in backups.into_iter().take(excess) {
+        tokio::fs::remove_file(&path).await?;
+    }
+    Ok(())
+}
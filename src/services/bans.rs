@@ -0,0 +1,108 @@
+use crate::config::AppConfig;
+use crate::db::Database;
+use crate::db::schema::Ban;
+use crate::domain::{IpNetwork, Username};
+use crate::services::audit::{self, Actor, AuditAction};
+use crate::types::BanKind;
+use std::net::IpAddr;
+use tracing::warn;
+
+/// Canonical form of a `kind` ban on `value`; `None` when `value` is not a
+/// username or IP network.
+pub fn normalize(kind: BanKind, value: &str) -> Option<String> {
+    match kind {
+        BanKind::Username => Username::parse(value).map(|name| name.as_str().to_string()),
+        BanKind::Ip => IpNetwork::parse(value.trim()).map(|net| net.to_string()),
+    }
+}
+
+/// Audit log target of a ban, e.g. `ip:10.0.0.0/8`.
+pub fn target(kind: BanKind, value: &str) -> String {
+    format!("{}:{value}", kind.as_str())
+}
+
+/// The ban covering `username` or `ip`, if any.
+///
+/// A failed lookup is logged and lets the registration through, like the
+/// other registration limits.
+pub async fn find_ban(
+    db: &Database,
+    config: &AppConfig,
+    username: Option<&str>,
+    ip: Option<IpAddr>,
+) -> Option<Ban> {
+    let bans = match db.get_bans().await {
+        Ok(bans) => bans,
+        Err(e) => {
+            warn!(error = %e, "Failed to load bans");
+            return None;
+        }
+    };
+    let matching = config.username_policy.matching();
+    let username = username.map(|name| matching.fold(name));
+    bans.into_iter()
+        .find(|ban| match BanKind::try_from(ban.kind.as_str()) {
+            Ok(BanKind::Username) => username
+                .as_deref()
+                .is_some_and(|name| matching.fold(&ban.value) == name),
+            Ok(BanKind::Ip) => ip
+                .is_some_and(|ip| IpNetwork::parse(&ban.value).is_some_and(|net| net.contains(ip))),
+            Err(()) => false,
+        })
+}
+
+/// Ban `username` and the addresses it was registered from, for an account
+/// deleted from `TeamTalk` that no Telegram user can be banned for.
+///
+/// Returns the id and audit target of each ban that was stored.
+pub async fn ban_unlinked_account(
+    db: &Database,
+    username: &str,
+    reason: &str,
+) -> Vec<(i64, String)> {
+    let ips = db.get_registration_ips(username).await.unwrap_or_else(|e| {
+        warn!(error = %e, username, "Failed to look up registration addresses");
+        Vec::new()
+    });
+    let entries = std::iter::once((BanKind::Username, username.to_string())).chain(
+        ips.iter()
+            .filter_map(|ip| normalize(BanKind::Ip, ip))
+            .map(|ip| (BanKind::Ip, ip)),
+    );
+    let mut banned = Vec::new();
+    for (kind, value) in entries {
+        let id = match db.add_ban(kind.as_str(), &value, Some(reason)).await {
+            Ok(id) => id,
+            Err(e) => {
+                warn!(error = %e, kind = kind.as_str(), value, "Failed to store ban");
+                continue;
+            }
+        };
+        let target = target(kind, &value);
+        audit::record(
+            db,
+            Actor::System,
+            AuditAction::AutoBan,
+            &target,
+            Some(reason),
+        )
+        .await;
+        banned.push((id, target));
+    }
+    banned
+}
+
+/// Lift the username or IP ban `id` on behalf of `actor`.
+///
+/// Returns the ban's audit target, or `None` when it no longer exists.
+pub async fn lift(db: &Database, id: i64, actor: Actor) -> anyhow::Result<Option<String>> {
+    let Some(ban) = db.get_bans().await?.into_iter().find(|ban| ban.id == id) else {
+        return Ok(None);
+    };
+    if !db.remove_ban(id).await? {
+        return Ok(None);
+    }
+    let target = format!("{}:{}", ban.kind, ban.value);
+    audit::record(db, actor, AuditAction::Unban, &target, None).await;
+    Ok(Some(target))
+}
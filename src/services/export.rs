@@ -0,0 +1,115 @@
+use crate::db::Database;
+use crate::db::schema::{BannedUser, TelegramRegistration};
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Snapshot of registrations and bans.
+#[derive(Serialize)]
+struct ExportData<'a> {
+    exported_at: NaiveDateTime,
+    registrations: &'a [TelegramRegistration],
+    banned_users: &'a [BannedUser],
+}
+
+/// Write CSV files for registrations and bans plus one JSON file with both
+/// into `dir` (the temp directory, where the periodic cleanup removes them later).
+///
+/// Returns the paths of the written files.
+pub async fn write_exports(db: &Database, dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let registrations = db.get_all_registrations().await?;
+    let banned_users = db.get_all_banned_users().await?;
+    let now = Utc::now().naive_utc();
+    let stamp = now.format("%Y%m%d_%H%M%S");
+    let json = serde_json::to_vec_pretty(&ExportData {
+        exported_at: now,
+        registrations: &registrations,
+        banned_users: &banned_users,
+    })?;
+
+    let files = [
+        (
+            dir.join(format!("export_{stamp}_registrations.csv")),
+            registrations_csv(&registrations).into_bytes(),
+        ),
+        (
+            dir.join(format!("export_{stamp}_banned_users.csv")),
+            banned_users_csv(&banned_users).into_bytes(),
+        ),
+        (dir.join(format!("export_{stamp}.json")), json),
+    ];
+    let mut paths = Vec::with_capacity(files.len());
+    for (path, content) in files {
+        tokio::fs::write(&path, content).await?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+fn registrations_csv(rows: &[TelegramRegistration]) -> String {
+    let mut out = String::from("telegram_id,teamtalk_username,nickname,created_at,source\n");
+    for row in rows {
+        push_row(
+            &mut out,
+            &[
+                &row.telegram_id.to_string(),
+                &row.teamtalk_username,
+                row.nickname.as_deref().unwrap_or_default(),
+                &format_date(row.created_at),
+                row.source.as_deref().unwrap_or_default(),
+            ],
+        );
+    }
+    out
+}
+
+fn banned_users_csv(rows: &[BannedUser]) -> String {
+    let mut out =
+        String::from("telegram_id,teamtalk_username,banned_at,banned_by_admin_id,reason\n");
+    for row in rows {
+        push_row(
+            &mut out,
+            &[
+                &row.telegram_id.to_string(),
+                row.teamtalk_username.as_deref().unwrap_or_default(),
+                &format_date(Some(row.banned_at)),
+                &row.banned_by_admin_id
+                    .map(|id| id.to_string())
+                    .unwrap_or_default(),
+                row.reason.as_deref().unwrap_or_default(),
+            ],
+        );
+    }
+    out
+}
+
+fn format_date(value: Option<NaiveDateTime>) -> String {
+    value
+        .map(|v| v.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+fn push_row(out: &mut String, fields: &[&str]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(&csv_field(field));
+    }
+    out.push('\n');
+}
+
+/// Quote a CSV field when needed. Values starting with a formula character
+/// get a leading `'` so spreadsheets don't evaluate user-chosen names.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
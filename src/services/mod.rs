@@ -1,4 +1,16 @@
 /// Admin-facing helpers.
 pub mod admin;
+/// Audit log of admin and system actions.
+pub mod audit;
+/// Compressed database snapshots.
+pub mod backup;
+/// Bans on `TeamTalk` usernames and IP networks.
+pub mod bans;
+/// CSV and JSON exports of registrations and bans.
+pub mod export;
+/// Comparison of `TeamTalk` accounts with the registrations table.
+pub mod reconcile;
 /// Registration workflow helpers.
 pub mod registration;
+/// Translation coverage against English.
+pub mod translations;
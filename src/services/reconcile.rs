@@ -0,0 +1,150 @@
+use crate::config::AppConfig;
+use crate::db::Database;
+use crate::domain::Username;
+use crate::services::admin;
+use crate::services::audit::{self, Actor, AuditAction};
+use crate::tt::TTWorkerHandle;
+use crate::types::{TTWorkerCommand, TelegramId};
+use anyhow::{Result, bail};
+use std::collections::HashSet;
+use tracing::warn;
+
+/// Differences between the `TeamTalk` accounts and the local database.
+#[derive(Debug, Default)]
+pub struct Drift {
+    /// Server accounts no Telegram registration or ban refers to.
+    pub unlinked: Vec<String>,
+    /// Telegram registrations whose account no longer exists on the server.
+    pub vanished: Vec<(TelegramId, String)>,
+    /// Banned Telegram users whose account still exists on the server.
+    pub banned: Vec<(TelegramId, String)>,
+}
+
+impl Drift {
+    pub const fn is_empty(&self) -> bool {
+        self.unlinked.is_empty() && self.vanished.is_empty() && self.banned.is_empty()
+    }
+}
+
+/// What [`repair_drift`] changed.
+#[derive(Debug, Default)]
+pub struct Repairs {
+    /// Registrations removed because their account was gone.
+    pub removed: usize,
+    /// Accounts of banned users deleted from the server.
+    pub deleted: usize,
+    pub failed: usize,
+}
+
+/// Compare every account of `config`'s server with the registrations and
+/// bans on it.
+///
+/// Fails while the bot is logged out: the worker then answers with an empty
+/// account list, which would make every registration look vanished.
+pub async fn find_drift(
+    db: &Database,
+    config: &AppConfig,
+    tx_tt: &TTWorkerHandle,
+) -> Result<Drift> {
+    let status = tx_tt
+        .request(|resp| TTWorkerCommand::GetStatus { resp })
+        .await?;
+    if !status.logged_in {
+        bail!("Bot not logged in to TeamTalk");
+    }
+    let accounts = tx_tt
+        .request(|resp| TTWorkerCommand::GetAllUsers { resp })
+        .await?;
+    // The bot's own account is always listed, so an empty list is a failure.
+    if accounts.is_empty() {
+        bail!("TeamTalk returned no accounts");
+    }
+    // Registrations on the other servers are not in this account list.
+    let registrations: Vec<_> = db
+        .get_all_registrations()
+        .await?
+        .into_iter()
+        .filter(|reg| reg.server_id == config.teamtalk.server_id)
+        .collect();
+    let bans: Vec<_> = db
+        .get_all_banned_users()
+        .await?
+        .into_iter()
+        .filter(|ban| ban.account_server() == config.teamtalk.server_id)
+        .collect();
+
+    let matching = config.username_policy.matching();
+    let key = |name: &str| matching.fold(name);
+    let on_server: HashSet<String> = accounts.iter().map(|name| key(name)).collect();
+    let known: HashSet<String> = registrations
+        .iter()
+        .map(|reg| key(&reg.teamtalk_username))
+        .chain(
+            bans.iter()
+                .filter_map(|ban| ban.teamtalk_username.as_deref().map(key)),
+        )
+        .chain([key(&config.teamtalk.user_name)])
+        .collect();
+
+    let mut drift = Drift {
+        unlinked: accounts
+            .into_iter()
+            .filter(|name| !known.contains(&key(name)))
+            .collect(),
+        vanished: registrations
+            .into_iter()
+            .filter(|reg| !on_server.contains(&key(&reg.teamtalk_username)))
+            .map(|reg| (reg.telegram_id, reg.teamtalk_username))
+            .collect(),
+        banned: bans
+            .into_iter()
+            .filter_map(|ban| Some((ban.telegram_id, ban.teamtalk_username?)))
+            .filter(|(_, name)| on_server.contains(&key(name)))
+            .collect(),
+    };
+    drift.unlinked.sort();
+    drift.vanished.sort_by(|a, b| a.1.cmp(&b.1));
+    drift.banned.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(drift)
+}
+
+/// Remove registrations whose account is gone and delete the accounts of
+/// banned users. Unlinked accounts are left alone: admins and the web form
+/// create those legitimately.
+pub async fn repair_drift(db: &Database, tx_tt: &TTWorkerHandle, drift: &Drift) -> Repairs {
+    let mut repairs = Repairs::default();
+    for (tg_id, username) in &drift.vanished {
+        match db.delete_registration(*tg_id).await {
+            Ok(_) => {
+                repairs.removed += 1;
+                let details = format!("Account {username} no longer exists on TeamTalk");
+                let target = tg_id.to_string();
+                let action = AuditAction::RemoveRegistration;
+                audit::record(db, Actor::System, action, &target, Some(&details)).await;
+            }
+            Err(e) => {
+                warn!(error = %e, tg_id = %tg_id, "Failed to remove stale registration");
+                repairs.failed += 1;
+            }
+        }
+    }
+    for (tg_id, name) in &drift.banned {
+        let Some(username) = Username::parse(name) else {
+            repairs.failed += 1;
+            continue;
+        };
+        match admin::delete_teamtalk_account(tx_tt, username).await {
+            Ok(()) => {
+                repairs.deleted += 1;
+                let details = format!("Telegram user {tg_id} is banned");
+                let action = AuditAction::DeleteTeamTalkAccount;
+                audit::record(db, Actor::System, action, name, Some(&details)).await;
+            }
+            Err(e) => {
+                warn!(error = %e, username = %name, "Failed to delete banned user's account");
+                repairs.failed += 1;
+            }
+        }
+    }
+    repairs
+}
@@ -1,55 +1,129 @@
-use crate::config::AppConfig;
-use crate::db::Database;
+use crate::config::{AppConfig, ClientTemplate, DEFAULT_RIGHTS_PROFILE, ZipPassword};
+use crate::db::{Database, NewQueuedRegistration, NewRegistration};
 use crate::domain::{Nickname, Password, Username};
-use crate::files::{create_client_zip, generate_tt_file_content, generate_tt_link};
-use crate::types::{RegistrationSource, TTAccountType, TTWorkerCommand, TelegramId};
+use crate::files::{
+    ClientZipContent, Compression, client_zip_bytes, create_client_zip, generate_client_readme,
+    generate_tt_file_content, generate_tt_link, qr_png, qr_svg, stream_client_zip, template_size,
+};
+use crate::i18n::{t, t_args};
+use crate::tt::TTWorkerHandle;
+use crate::types::{
+    LanguageCode, RegistrationOrigin, RegistrationSource, SourceInfo, TTAccountType,
+    TTCommandError, TTWorkerCommand, TelegramId,
+};
+use chrono::{NaiveDateTime, Utc};
+use std::collections::HashMap;
 use std::error::Error;
-use std::path::{Path, PathBuf};
-use std::sync::mpsc::Sender;
-use tracing::{error, instrument};
+use std::path::Path;
+use tokio::io::DuplexStream;
+use tokio::task::JoinHandle;
+use tokio_util::io::SyncIoBridge;
+use tracing::{error, info, instrument, warn};
 
-/// Assets generated for a registration (tt file, link, filename).
+/// Assets generated for a registration (tt file, link, filename, ZIP readme
+/// and password).
 pub struct RegistrationAssets {
     pub content: String,
     pub link: String,
     pub filename: String,
+    pub readme: String,
+    /// Password client ZIPs are encrypted with, per `zip_password`.
+    pub zip_password: Option<String>,
 }
 
 /// Result of `TeamTalk` account creation flow.
 pub struct RegistrationResult {
     pub created: bool,
+    /// Stored to be created once `TeamTalk` is reachable again.
+    pub queued: bool,
     pub db_sync_error: Option<String>,
     pub assets: Option<RegistrationAssets>,
+    /// Why the account was neither created nor queued.
+    pub error: Option<TTCommandError>,
 }
 
-/// Build registration assets from config and account fields.
+impl RegistrationResult {
+    const fn failed(error: TTCommandError) -> Self {
+        Self {
+            created: false,
+            queued: false,
+            db_sync_error: None,
+            assets: None,
+            error: Some(error),
+        }
+    }
+
+    /// Audit log suffix for an account that was not simply created.
+    pub fn outcome_note(&self) -> String {
+        if self.queued {
+            "; queued until TeamTalk is reachable".to_string()
+        } else if let Some(err) = &self.error {
+            format!("; account creation failed: {err}")
+        } else {
+            String::new()
+        }
+    }
+}
+
+/// Build registration assets from config and account fields; the ZIP readme
+/// is written in `lang`.
 pub fn build_assets(
     config: &AppConfig,
+    lang: &str,
     username: &str,
     password: &str,
     nickname: &str,
 ) -> RegistrationAssets {
     let tt_content = generate_tt_file_content(config, username, password, nickname);
     let tt_link = generate_tt_link(config, username, password, nickname);
-    let tt_filename = format!("{}.tt", config.teamtalk.server_name);
+    let tt_filename = tt_filename(config);
+    let readme = generate_client_readme(config, lang, username, &tt_filename);
+    let zip_password = match config.web.zip_password {
+        ZipPassword::Off => None,
+        ZipPassword::Account => Some(password.to_string()).filter(|p| !p.is_empty()),
+        ZipPassword::Random => Some(config.password_policy.generate().as_str().to_string()),
+    };
 
     RegistrationAssets {
         content: tt_content,
         link: tt_link,
         filename: tt_filename,
+        readme,
+        zip_password,
     }
 }
 
+/// QR code of a `tt://` link as PNG, for chat clients.
+pub fn link_qr_png(link: &str) -> Option<Vec<u8>> {
+    qr_png(link)
+        .map_err(|e| warn!(error = %e, "Failed to render TT link QR code"))
+        .ok()
+}
+
+/// QR code of a `tt://` link as inline SVG, for web pages.
+pub fn link_qr_svg(link: &str) -> Option<String> {
+    qr_svg(link)
+        .map_err(|e| warn!(error = %e, "Failed to render TT link QR code"))
+        .ok()
+}
+
 /// Parameters for `TeamTalk` account creation.
 pub struct CreateAccountParams<'a> {
     pub username: &'a Username,
     pub password: &'a Password,
     pub nickname: &'a Nickname,
     pub account_type: TTAccountType,
+    /// `rights_profiles` entry picked by an admin; `None` for `default`.
+    pub rights_profile: Option<&'a str>,
     pub source: RegistrationSource,
-    pub source_info: Option<String>,
+    pub source_info: Option<SourceInfo>,
     pub telegram_id: Option<TelegramId>,
-    pub tx_tt: Sender<TTWorkerCommand>,
+    /// Language remembered for the linked Telegram user.
+    pub language: Option<&'a LanguageCode>,
+    /// `queued_registrations` entry journaling this registration; it is
+    /// dropped in the same transaction that records the account.
+    pub queued_id: Option<i64>,
+    pub tx_tt: TTWorkerHandle,
     pub db: &'a Database,
     pub config: &'a AppConfig,
 }
@@ -59,114 +133,387 @@ pub struct CreateAccountParams<'a> {
     fields(username = %params.username.as_str(), account_type = ?params.account_type)
 )]
 /// Create a `TeamTalk` account and sync DB metadata.
+///
+/// With `tt_offline_queue_enabled`, a registration made while the bot is
+/// logged out is queued instead and reported with `queued` set.
 pub async fn create_teamtalk_account(
     params: CreateAccountParams<'_>,
 ) -> Result<RegistrationResult, Box<dyn Error + Send + Sync>> {
+    let queue_offline = params.config.teamtalk.tt_offline_queue_enabled;
+    Ok(create_account(params, queue_offline).await)
+}
+
+/// Create an account taken from the offline queue; it is never queued again.
+pub async fn create_queued_account(params: CreateAccountParams<'_>) -> RegistrationResult {
+    create_account(params, false).await
+}
+
+async fn create_account(
+    params: CreateAccountParams<'_>,
+    queue_offline: bool,
+) -> RegistrationResult {
+    let result = params
+        .tx_tt
+        .request(|resp| TTWorkerCommand::CreateAccount {
+            username: params.username.clone(),
+            password: params.password.clone(),
+            nickname: params.nickname.clone(),
+            account_type: params.account_type,
+            rights_profile: params
+                .rights_profile
+                .unwrap_or(DEFAULT_RIGHTS_PROFILE)
+                .to_string(),
+            source: params.source.clone(),
+            source_info: params.source_info.as_ref().map(ToString::to_string),
+            resp,
+        })
+        .await;
+    match result {
+        Ok(Ok(true)) => finish_created(&params).await,
+        Ok(Ok(false)) => {
+            error!("TeamTalk create account returned false");
+            RegistrationResult::failed(TTCommandError::Other(
+                "Command indicated failure without a specific error.".to_string(),
+            ))
+        }
+        Ok(Err(TTCommandError::NotConnected)) if queue_offline => match params.queued_id {
+            Some(id) => keep_queued(params.db, id).await,
+            None => queue_account(&params).await,
+        },
+        Ok(Err(e)) => {
+            error!(error = %e, "TeamTalk create account failed");
+            RegistrationResult::failed(e)
+        }
+        Err(e) => {
+            error!(error = %e, "TeamTalk create account request failed");
+            RegistrationResult::failed(TTCommandError::Other(e.to_string()))
+        }
+    }
+}
+
+async fn finish_created(params: &CreateAccountParams<'_>) -> RegistrationResult {
     let CreateAccountParams {
         username,
         password,
         nickname,
-        account_type,
-        source,
-        source_info,
         telegram_id,
-        tx_tt,
+        language,
+        source_info,
+        queued_id,
         db,
         config,
+        ..
     } = params;
-    let (tx, rx) = tokio::sync::oneshot::channel();
-    let cmd = TTWorkerCommand::CreateAccount {
-        username: username.clone(),
-        password: password.clone(),
-        nickname: nickname.clone(),
-        account_type,
-        source,
-        source_info,
-        resp: tx,
+    let registration = telegram_id.map(|telegram_id| NewRegistration {
+        telegram_id,
+        username: username.as_str(),
+        nickname: nickname.as_str(),
+        language: language.map(LanguageCode::as_str),
+        server_id: &config.teamtalk.server_id,
+        source: RegistrationOrigin::of(source_info.as_ref()),
+    });
+    let db_sync_error = if (registration.is_some() || queued_id.is_some())
+        && let Err(e) = db
+            .finish_registration(registration.as_ref(), *queued_id)
+            .await
+    {
+        Some(e.to_string())
+    } else {
+        None
     };
-    if let Err(e) = tx_tt.send(cmd) {
-        error!(error = %e, "Failed to send TeamTalk create command");
-        return Err(Box::new(e));
+
+    let lang = language.cloned().unwrap_or_default();
+    schedule_welcome(db, config, username.as_str(), lang.as_str()).await;
+    let assets = build_assets(
+        config,
+        lang.as_str(),
+        username.as_str(),
+        password.as_str(),
+        nickname.as_str(),
+    );
+    RegistrationResult {
+        created: true,
+        queued: false,
+        db_sync_error,
+        assets: Some(assets),
+        error: None,
     }
+}
 
-    let result = rx.await;
-    match result {
-        Ok(Ok(true)) => {
-            let db_sync_error = if let Some(tg_id) = telegram_id
-                && let Err(e) = db.add_registration(tg_id, username.as_str()).await
-            {
-                Some(e.to_string())
-            } else {
-                None
-            };
-
-            let assets = build_assets(
-                config,
-                username.as_str(),
-                password.as_str(),
-                nickname.as_str(),
-            );
-            Ok(RegistrationResult {
-                created: true,
-                db_sync_error,
-                assets: Some(assets),
-            })
-        }
-        Ok(Ok(false)) => {
-            error!("TeamTalk create account returned false");
-            Ok(RegistrationResult {
-                created: false,
-                db_sync_error: None,
-                assets: None,
-            })
+/// With `tt_welcome_enabled`, have the account greeted in `lang` the first
+/// time it logs in to `TeamTalk`.
+pub async fn schedule_welcome(db: &Database, config: &AppConfig, username: &str, lang: &str) {
+    if !config.teamtalk.tt_welcome_enabled {
+        return;
+    }
+    let server_id = &config.teamtalk.server_id;
+    if let Err(e) = db.add_account_welcome(server_id, username, lang).await {
+        warn!(error = %e, username, "Failed to schedule welcome message");
+    }
+}
+
+/// Whether `ip` already registered `web_registrations_per_ip` accounts within
+/// `web_registrations_per_ip_window_seconds`.
+pub async fn ip_limit_reached(db: &Database, config: &AppConfig, ip: &str) -> bool {
+    let limit = config.web.web_registrations_per_ip;
+    if limit == 0 {
+        return false;
+    }
+    let since = i64::try_from(config.web.web_registrations_per_ip_window_seconds)
+        .ok()
+        .and_then(chrono::Duration::try_seconds)
+        .and_then(|window| Utc::now().naive_utc().checked_sub_signed(window))
+        .unwrap_or(NaiveDateTime::MIN);
+    match db.count_ip_registrations(ip, since).await {
+        Ok(count) => count >= i64::from(limit),
+        Err(e) => {
+            warn!(error = %e, ip, "Failed to count registrations from IP");
+            false
         }
-        Ok(Err(e)) => {
-            error!(error = %e, "TeamTalk create account failed");
-            Ok(RegistrationResult {
+    }
+}
+
+/// Record an account from the offline queue that already exists because its
+/// create command reached `TeamTalk` before a crash.
+pub async fn adopt_queued_account(params: CreateAccountParams<'_>) -> RegistrationResult {
+    finish_created(&params).await
+}
+
+/// Leave journaled registration `id` queued; its create command was never
+/// sent, so it no longer counts as attempted.
+async fn keep_queued(db: &Database, id: i64) -> RegistrationResult {
+    if let Err(e) = db.set_queued_registration_attempt(id, None).await {
+        warn!(error = %e, id, "Failed to clear queued registration attempt");
+    }
+    info!("TeamTalk is unreachable; registration queued");
+    RegistrationResult {
+        created: false,
+        queued: true,
+        db_sync_error: None,
+        assets: None,
+        error: None,
+    }
+}
+
+/// Keep the registration until the bot is logged in again; `queued` tells
+/// whether it was stored.
+pub async fn queue_account(params: &CreateAccountParams<'_>) -> RegistrationResult {
+    let source = params.source.to_string();
+    let queued = params
+        .db
+        .add_queued_registration(&NewQueuedRegistration {
+            username: params.username.as_str(),
+            password: params.password.as_str(),
+            nickname: params.nickname.as_str(),
+            account_type: params.account_type.as_str(),
+            rights_profile: params.rights_profile.unwrap_or(DEFAULT_RIGHTS_PROFILE),
+            source: &source,
+            source_info: params.source_info.as_ref(),
+            telegram_id: params.telegram_id,
+            language: params.language.map(LanguageCode::as_str),
+            server_id: &params.config.teamtalk.server_id,
+        })
+        .await;
+    match queued {
+        Ok(()) => {
+            info!("TeamTalk is unreachable; registration queued");
+            RegistrationResult {
                 created: false,
+                queued: true,
                 db_sync_error: None,
                 assets: None,
-            })
+                error: None,
+            }
         }
         Err(e) => {
-            error!(error = %e, "TeamTalk create account response channel failed");
-            Ok(RegistrationResult {
-                created: false,
-                db_sync_error: None,
-                assets: None,
-            })
+            error!(error = %e, "Failed to queue registration");
+            RegistrationResult::failed(TTCommandError::NotConnected)
         }
     }
 }
 
-/// Resolve temp directory used for generated files.
-pub fn temp_dir() -> PathBuf {
-    std::env::current_dir()
-        .unwrap_or_else(|_| PathBuf::from("."))
-        .join("temp_files")
+/// Delete a generated download file; `name` is relative to `temp_dir`.
+///
+/// A file that is already gone is not an error.
+pub async fn remove_generated_file(temp_dir: &Path, name: &str) {
+    let Some(file_name) = Path::new(name).file_name() else {
+        return;
+    };
+    let path = temp_dir.join(file_name);
+    match tokio::fs::remove_file(&path).await {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => warn!(error = %e, path = %path.display(), "Failed to delete generated file"),
+    }
+}
+
+/// Client templates whose directory exists, in configured order.
+pub fn client_templates(config: &AppConfig) -> Vec<&ClientTemplate> {
+    config
+        .client_templates
+        .iter()
+        .filter(|template| Path::new(&template.dir).exists())
+        .collect()
 }
 
-/// Try to create a client ZIP if template is present.
+/// Look up an available client template by name.
+pub fn find_client_template<'a>(config: &'a AppConfig, name: &str) -> Option<&'a ClientTemplate> {
+    client_templates(config)
+        .into_iter()
+        .find(|template| template.name == name)
+}
+
+/// ZIP file name offered to `username`; the template name is added once
+/// there is more than one variant.
+pub fn client_zip_filename(
+    config: &AppConfig,
+    template: &ClientTemplate,
+    username: &str,
+) -> String {
+    if config.client_templates.len() > 1 {
+        format!("{username}_TeamTalk_{}.zip", template.name)
+    } else {
+        format!("{username}_TeamTalk.zip")
+    }
+}
+
+/// Compression configured for client ZIPs.
+const fn zip_compression(config: &AppConfig) -> Compression {
+    Compression {
+        method: config.web.zip_compression,
+        level: config.web.zip_compression_level,
+    }
+}
+
+/// Generated entries of a client ZIP, owned so the archive can be built on
+/// a blocking thread.
+pub struct ClientZipSource {
+    pub tt_content: String,
+    pub readme: Option<String>,
+    /// Password the `.tt` entry is encrypted with.
+    pub password: Option<String>,
+}
+
+impl ClientZipSource {
+    fn from_assets(assets: &RegistrationAssets) -> Self {
+        Self {
+            tt_content: assets.content.clone(),
+            readme: Some(assets.readme.clone()),
+            password: assets.zip_password.clone(),
+        }
+    }
+
+    fn content<'a>(&'a self, tt_filename: &'a str) -> ClientZipContent<'a> {
+        ClientZipContent {
+            tt_filename,
+            tt_content: &self.tt_content,
+            readme: self.readme.as_deref(),
+            password: self.password.as_deref(),
+        }
+    }
+}
+
+/// Try to create a client ZIP from `template`.
 pub async fn try_create_client_zip_async(
     config: &AppConfig,
+    template: &ClientTemplate,
     output_path: &Path,
     assets: &RegistrationAssets,
 ) -> bool {
-    let Some(tpl_dir) = &config.web.teamtalk_client_template_dir else {
-        return false;
-    };
-    if !Path::new(tpl_dir).exists() {
-        return false;
-    }
-
-    let tpl_dir = tpl_dir.clone();
+    let compression = zip_compression(config);
+    let tpl_dir = template.dir.clone();
     let output_path = output_path.to_path_buf();
     let tt_filename = assets.filename.clone();
-    let tt_content = assets.content.clone();
+    let source = ClientZipSource::from_assets(assets);
 
     tokio::task::spawn_blocking(move || {
-        create_client_zip(&tpl_dir, &output_path, &tt_filename, &tt_content).is_ok()
+        create_client_zip(
+            &tpl_dir,
+            &output_path,
+            &source.content(&tt_filename),
+            compression,
+        )
+        .is_ok()
     })
     .await
     .unwrap_or(false)
 }
+
+/// Whether ZIPs of `template` can be generated on the fly: it is no larger
+/// than `web_client_zip_stream_max_mb`.
+pub async fn client_zip_streamable(config: &AppConfig, template: &ClientTemplate) -> bool {
+    let max_bytes = config
+        .web
+        .web_client_zip_stream_max_mb
+        .saturating_mul(1_048_576);
+    if max_bytes == 0 {
+        return false;
+    }
+    let tpl_dir = template.dir.clone();
+    tokio::task::spawn_blocking(move || template_size(&tpl_dir) <= max_bytes)
+        .await
+        .unwrap_or(false)
+}
+
+/// Build a client ZIP from `template` in memory; `None` on failure.
+pub async fn client_zip_bytes_async(
+    config: &AppConfig,
+    template: &ClientTemplate,
+    assets: &RegistrationAssets,
+) -> Option<Vec<u8>> {
+    let compression = zip_compression(config);
+    let tpl_dir = template.dir.clone();
+    let tt_filename = assets.filename.clone();
+    let source = ClientZipSource::from_assets(assets);
+    tokio::task::spawn_blocking(move || {
+        client_zip_bytes(&tpl_dir, &source.content(&tt_filename), compression)
+            .inspect_err(|e| error!(error = %e, "Failed to build client ZIP"))
+            .ok()
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+/// Start generating a client ZIP from `template` for `source` into a pipe.
+///
+/// Returns the read end and a task that resolves to `true` once the archive
+/// was written completely.
+pub fn stream_client_zip_async(
+    config: &AppConfig,
+    template: &ClientTemplate,
+    source: ClientZipSource,
+) -> (DuplexStream, JoinHandle<bool>) {
+    let compression = zip_compression(config);
+    let tpl_dir = template.dir.clone();
+    let tt_filename = tt_filename(config);
+    let (reader, writer) = tokio::io::duplex(64 * 1024);
+    let writer = SyncIoBridge::new(writer);
+    let task = tokio::task::spawn_blocking(move || {
+        stream_client_zip(&tpl_dir, writer, &source.content(&tt_filename), compression)
+            .inspect_err(|e| error!(error = %e, "Failed to stream client ZIP"))
+            .is_ok()
+    });
+    (reader, task)
+}
+
+/// Text telling the user how the client ZIPs are protected, if they are.
+pub fn zip_password_note(
+    config: &AppConfig,
+    lang: &str,
+    assets: &RegistrationAssets,
+) -> Option<String> {
+    let password = assets.zip_password.as_deref()?;
+    Some(match config.web.zip_password {
+        ZipPassword::Account => t(lang, "zip-password-account"),
+        ZipPassword::Random | ZipPassword::Off => t_args(
+            lang,
+            "zip-password-random",
+            &HashMap::from([("password".to_string(), password.to_string())]),
+        ),
+    })
+}
+
+fn tt_filename(config: &AppConfig) -> String {
+    format!("{}.tt", config.teamtalk.server_name)
+}
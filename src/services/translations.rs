@@ -0,0 +1,177 @@
+use crate::i18n::{available_languages, locale_keys, t, t_args};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Source directories searched for message keys, relative to the working
+/// directory like `./locales`.
+const SOURCE_DIRS: [&str; 2] = ["./src", "./templates"];
+
+/// How one language's keys compare with English.
+pub struct LanguageCoverage {
+    pub lang: String,
+    /// English keys the language lacks; they are shown through the fallbacks.
+    pub missing: Vec<String>,
+    /// Keys English does not have, so nothing asks for them.
+    pub extra: Vec<String>,
+}
+
+/// Coverage of every language plus English keys the code never uses.
+pub struct TranslationReport {
+    english_keys: usize,
+    pub languages: Vec<LanguageCoverage>,
+    /// `None` when the source tree is not there to search.
+    pub unused: Option<Vec<String>>,
+}
+
+/// Compare every available language's keys with English.
+pub fn coverage() -> Vec<LanguageCoverage> {
+    let english = locale_keys("en");
+    available_languages()
+        .iter()
+        .filter(|(code, _)| code != "en")
+        .map(|(code, _)| {
+            let keys = locale_keys(code);
+            LanguageCoverage {
+                lang: code.clone(),
+                missing: english.difference(&keys).cloned().collect(),
+                extra: keys.difference(&english).cloned().collect(),
+            }
+        })
+        .collect()
+}
+
+/// Build the full report, searching the source tree for unused keys.
+pub fn report() -> TranslationReport {
+    let english = locale_keys("en");
+    TranslationReport {
+        english_keys: english.len(),
+        languages: coverage(),
+        unused: unused_keys(&english),
+    }
+}
+
+impl TranslationReport {
+    /// Whether English messages were found under `./locales` at all.
+    pub const fn found_locales(&self) -> bool {
+        self.english_keys > 0
+    }
+
+    /// Whether every language has every English key.
+    pub fn is_complete(&self) -> bool {
+        self.languages.iter().all(|lang| lang.missing.is_empty())
+    }
+
+    /// The report as text in `lang`, one paragraph per finding.
+    pub fn render(&self, lang: &str) -> String {
+        let mut paragraphs = vec![t_args(
+            lang,
+            "translations-report-title",
+            &HashMap::from([("count".to_string(), self.english_keys.to_string())]),
+        )];
+        for coverage in &self.languages {
+            if coverage.missing.is_empty() && coverage.extra.is_empty() {
+                paragraphs.push(t_args(
+                    lang,
+                    "translations-lang-complete",
+                    &HashMap::from([("lang".to_string(), coverage.lang.clone())]),
+                ));
+            }
+            for (key, keys) in [
+                ("translations-lang-missing", &coverage.missing),
+                ("translations-lang-extra", &coverage.extra),
+            ] {
+                if !keys.is_empty() {
+                    paragraphs.push(key_list(lang, key, Some(&coverage.lang), keys));
+                }
+            }
+        }
+        paragraphs.push(match &self.unused {
+            None => t(lang, "translations-unused-skipped"),
+            Some(keys) if keys.is_empty() => t(lang, "translations-unused-none"),
+            Some(keys) => key_list(lang, "translations-unused", None, keys),
+        });
+        paragraphs.join("\n\n")
+    }
+}
+
+fn key_list(lang: &str, key: &str, language: Option<&str>, keys: &[String]) -> String {
+    let mut args = HashMap::from([
+        ("count".to_string(), keys.len().to_string()),
+        ("keys".to_string(), keys.join(", ")),
+    ]);
+    if let Some(language) = language {
+        args.insert("lang".to_string(), language.to_string());
+    }
+    t_args(lang, key, &args)
+}
+
+/// English keys found nowhere in the source tree, or `None` without one.
+///
+/// A key counts as used when it appears as a string literal, or when a
+/// literal prefix (`"cmd-"`, `"admin-online-{name}-done"`) joined with another
+/// literal spells it, as keys built with `format!` do.
+fn unused_keys(english: &BTreeSet<String>) -> Option<Vec<String>> {
+    if !SOURCE_DIRS.iter().any(|dir| Path::new(dir).is_dir()) {
+        return None;
+    }
+    let mut literals = BTreeSet::new();
+    let mut patterns = BTreeSet::new();
+    for dir in SOURCE_DIRS {
+        for entry in WalkDir::new(dir).into_iter().flatten() {
+            let path = entry.path();
+            if !path
+                .extension()
+                .is_some_and(|ext| ext == "rs" || ext == "html")
+            {
+                continue;
+            }
+            let Ok(source) = fs::read_to_string(path) else {
+                continue;
+            };
+            collect_literals(&source, &mut literals, &mut patterns);
+        }
+    }
+    let used = |key: &str| {
+        literals.contains(key)
+            || patterns.iter().any(|(prefix, suffix): &(String, String)| {
+                key.len() > prefix.len() + suffix.len()
+                    && key.starts_with(prefix.as_str())
+                    && key.ends_with(suffix.as_str())
+                    && literals.contains(&key[prefix.len()..key.len() - suffix.len()])
+            })
+    };
+    Some(english.iter().filter(|key| !used(key)).cloned().collect())
+}
+
+/// Gather key-like string literals, and the prefix and suffix around the
+/// placeholder of literals such as `"cmd-{command}"` or `"admin-web-notice-"`.
+fn collect_literals(
+    source: &str,
+    literals: &mut BTreeSet<String>,
+    patterns: &mut BTreeSet<(String, String)>,
+) {
+    let is_key_part = |s: &str| {
+        s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    };
+    for literal in source.split('"') {
+        if literal.is_empty() {
+            continue;
+        }
+        if is_key_part(literal) {
+            literals.insert(literal.to_string());
+            if literal.ends_with('-') {
+                patterns.insert((literal.to_string(), String::new()));
+            }
+        } else if let Some((prefix, rest)) = literal.split_once('{')
+            && let Some((_, suffix)) = rest.split_once('}')
+            && is_key_part(prefix)
+            && is_key_part(suffix)
+            && !(prefix.is_empty() && suffix.is_empty())
+        {
+            patterns.insert((prefix.to_string(), suffix.to_string()));
+        }
+    }
+}
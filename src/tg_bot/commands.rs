@@ -0,0 +1,83 @@
+use crate::config::AppConfig;
+use crate::i18n::{available_languages, t};
+use teloxide::prelude::*;
+use teloxide::types::{BotCommand, BotCommandScope, Recipient};
+use tracing::{info, warn};
+
+const USER_COMMANDS: &[&str] = &["start", "myaccount", "changepassword", "cancel", "help"];
+const ADMIN_COMMANDS: &[&str] = &[
+    "adminpanel",
+    "online",
+    "stats",
+    "ttstatus",
+    "audit",
+    "broadcast",
+    "register",
+    "quiethours",
+    "translations",
+];
+const OWNER_COMMANDS: &[&str] = &["generate", "backup", "exit", "restart"];
+
+/// Publish the command menu: user commands for everyone in every available
+/// language, plus admin commands in each admin's private chat.
+pub async fn register_commands(bot: &Bot, config: &AppConfig) {
+    let languages = available_languages();
+    let admin_lang = config.telegram.bot_admin_lang.as_str();
+
+    let mut failed = 0usize;
+    for lang in std::iter::once(None).chain(languages.iter().map(|(code, _)| Some(code.as_str()))) {
+        let user_commands = localized(lang.unwrap_or("en"), USER_COMMANDS, config);
+        if !set_commands(bot, user_commands.clone(), BotCommandScope::Default, lang).await {
+            failed += 1;
+        }
+
+        for &admin in &config.telegram.admin_ids {
+            let mut commands = user_commands.clone();
+            commands.extend(localized(admin_lang, ADMIN_COMMANDS, config));
+            if config.is_owner(admin) {
+                commands.extend(localized(admin_lang, OWNER_COMMANDS, config));
+            }
+            let scope = BotCommandScope::Chat {
+                chat_id: Recipient::Id(ChatId(admin.as_i64())),
+            };
+            if !set_commands(bot, commands, scope, lang).await {
+                failed += 1;
+            }
+        }
+    }
+
+    if failed == 0 {
+        info!(languages = languages.len(), "Registered bot commands");
+    } else {
+        warn!(failed, "Some bot command scopes could not be registered");
+    }
+}
+
+fn localized(lang: &str, commands: &[&str], config: &AppConfig) -> Vec<BotCommand> {
+    commands
+        .iter()
+        .filter(|&&command| {
+            command != "generate" || config.telegram.telegram_deeplink_registration_enabled
+        })
+        .map(|command| BotCommand::new(*command, t(lang, &format!("cmd-{command}"))))
+        .collect()
+}
+
+async fn set_commands(
+    bot: &Bot,
+    commands: Vec<BotCommand>,
+    scope: BotCommandScope,
+    lang: Option<&str>,
+) -> bool {
+    let mut request = bot.set_my_commands(commands).scope(scope);
+    if let Some(lang) = lang {
+        request = request.language_code(lang);
+    }
+    match request.await {
+        Ok(_) => true,
+        Err(e) => {
+            warn!(error = %e, lang = lang.unwrap_or("default"), "Failed to set bot commands");
+            false
+        }
+    }
+}
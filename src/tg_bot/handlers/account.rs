@@ -0,0 +1,344 @@
+use super::registration::send_registration_assets;
+use super::{HandlerResult, MyDialogue, State, known_user_lang};
+use crate::config::AppConfig;
+use crate::db::Database;
+use crate::db::schema::TelegramRegistration;
+use crate::domain::{Password, Username};
+use crate::i18n::{t, t_args};
+use crate::services::registration;
+use crate::tt::{TTServer, TTServers};
+use crate::types::{TTWorkerCommand, TelegramId};
+use std::collections::HashMap;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use teloxide::types::InputFile;
+use tracing::{error, info, warn};
+
+/// Callback data prefix for `/myaccount` buttons.
+pub const MY_ACCOUNT_CALLBACK_PREFIX: &str = "myaccount_";
+
+/// Show the caller's linked `TeamTalk` account.
+pub async fn my_account(
+    bot: Bot,
+    msg: Message,
+    db: Database,
+    config: Arc<AppConfig>,
+) -> HandlerResult {
+    let chat_id = TelegramId::new(msg.chat.id.0);
+    let lang = known_user_lang(&db, chat_id, msg.from.as_ref(), &config).await;
+    if db.get_banned_user(chat_id).await?.is_some() {
+        return Ok(());
+    }
+    let Some(reg) = db.get_registration_by_id(chat_id).await? else {
+        bot.send_message(msg.chat.id, t(lang.as_str(), "myaccount-not-registered"))
+            .await?;
+        return Ok(());
+    };
+
+    let nickname = reg
+        .nickname
+        .clone()
+        .unwrap_or_else(|| reg.teamtalk_username.clone());
+    let registered_at = reg.created_at.map_or_else(
+        || t(lang.as_str(), "myaccount-registered-unknown"),
+        |at| {
+            t_args(
+                lang.as_str(),
+                "myaccount-registered-at",
+                &HashMap::from([(
+                    "date".to_string(),
+                    at.format("%Y-%m-%d %H:%M UTC").to_string(),
+                )]),
+            )
+        },
+    );
+    let text = [
+        t(lang.as_str(), "myaccount-title"),
+        t_args(
+            lang.as_str(),
+            "myaccount-username",
+            &HashMap::from([("username".to_string(), reg.teamtalk_username.clone())]),
+        ),
+        t_args(
+            lang.as_str(),
+            "myaccount-nickname",
+            &HashMap::from([("nickname".to_string(), nickname)]),
+        ),
+        registered_at,
+        String::new(),
+        t(lang.as_str(), "myaccount-password-note"),
+    ]
+    .join("\n");
+    bot.send_message(msg.chat.id, text)
+        .reply_markup(crate::tg_bot::keyboards::my_account_keyboard(
+            &t(lang.as_str(), "btn-myaccount-file"),
+            &t(lang.as_str(), "btn-myaccount-link"),
+        ))
+        .await?;
+    Ok(())
+}
+
+/// Resend the `.tt` file or `tt://` link for the caller's account.
+pub async fn my_account_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    db: Database,
+    config: Arc<AppConfig>,
+    servers: TTServers,
+) -> HandlerResult {
+    let tg_id = TelegramId::new(i64::try_from(q.from.id.0)?);
+    let lang = known_user_lang(&db, tg_id, Some(&q.from), &config).await;
+    let action = q
+        .data
+        .as_deref()
+        .and_then(|d| d.strip_prefix(MY_ACCOUNT_CALLBACK_PREFIX))
+        .unwrap_or_default()
+        .to_string();
+    let chat_id = q.from.id;
+    bot.answer_callback_query(q.id).await?;
+
+    let Some(reg) = db.get_registration_by_id(tg_id).await? else {
+        bot.send_message(chat_id, t(lang.as_str(), "myaccount-not-registered"))
+            .await?;
+        return Ok(());
+    };
+    let Some(server) = account_server(&servers, &reg) else {
+        bot.send_message(chat_id, t(lang.as_str(), "myaccount-server-unknown"))
+            .await?;
+        return Ok(());
+    };
+    let assets = account_assets(&server.config, lang.as_str(), &reg);
+    match action.as_str() {
+        "file" => {
+            let file = InputFile::memory(assets.content.into_bytes()).file_name(assets.filename);
+            if let Err(e) = bot
+                .send_document(chat_id, file)
+                .caption(t(lang.as_str(), "file-caption"))
+                .await
+            {
+                warn!(error = %e, "Failed to resend TT config file");
+                bot.send_message(chat_id, t(lang.as_str(), "file-send-error"))
+                    .await?;
+            }
+        }
+        "link" => {
+            bot.send_message(
+                chat_id,
+                format!("{}\n{}", t(lang.as_str(), "link-text"), assets.link),
+            )
+            .await?;
+        }
+        other => warn!(action = other, "Unknown /myaccount callback"),
+    }
+    Ok(())
+}
+
+/// The server a registration's account lives on. An unknown id means the
+/// server was removed from the configuration, so the account cannot be served.
+fn account_server<'a>(servers: &'a TTServers, reg: &TelegramRegistration) -> Option<&'a TTServer> {
+    let server = servers.get(&reg.server_id);
+    if server.is_none() {
+        error!(tg_id = %reg.telegram_id, server = %reg.server_id, "Registration names an unknown server");
+    }
+    server
+}
+
+/// Client files for a stored registration; the password is never stored, so it is left blank.
+fn account_assets(
+    config: &AppConfig,
+    lang: &str,
+    reg: &TelegramRegistration,
+) -> registration::RegistrationAssets {
+    let nickname = reg.nickname.as_deref().unwrap_or(&reg.teamtalk_username);
+    registration::build_assets(config, lang, &reg.teamtalk_username, "", nickname)
+}
+
+/// Start the `/changepassword` flow for a registered user.
+pub async fn change_password(
+    bot: Bot,
+    msg: Message,
+    dialogue: MyDialogue,
+    db: Database,
+    config: Arc<AppConfig>,
+) -> HandlerResult {
+    let chat_id = TelegramId::new(msg.chat.id.0);
+    let lang = known_user_lang(&db, chat_id, msg.from.as_ref(), &config).await;
+    if db.get_banned_user(chat_id).await?.is_some() {
+        return Ok(());
+    }
+    if db.get_registration_by_id(chat_id).await?.is_none() {
+        bot.send_message(msg.chat.id, t(lang.as_str(), "myaccount-not-registered"))
+            .await?;
+        return Ok(());
+    }
+    bot.send_message(msg.chat.id, t(lang.as_str(), "changepassword-prompt"))
+        .await?;
+    dialogue.update(State::AwaitingNewPassword { lang }).await?;
+    Ok(())
+}
+
+/// Apply the new password and resend the connection files.
+pub async fn receive_new_password(
+    bot: Bot,
+    msg: Message,
+    dialogue: MyDialogue,
+    db: Database,
+    servers: TTServers,
+    config: Arc<AppConfig>,
+) -> HandlerResult {
+    let Some(State::AwaitingNewPassword { lang }) = (match dialogue.get().await {
+        Ok(state) => state,
+        Err(e) => {
+            warn!(error = %e, "Failed to read dialogue state (AwaitingNewPassword)");
+            return Ok(());
+        }
+    }) else {
+        return Ok(());
+    };
+    let Some(password) = Password::parse(msg.text().unwrap_or("")) else {
+        bot.send_message(msg.chat.id, t(lang.as_str(), "password-empty-error"))
+            .await?;
+        return Ok(());
+    };
+    if let Err(e) = bot.delete_message(msg.chat.id, msg.id).await {
+        warn!(error = %e, "Failed to delete password message");
+    }
+    if let Err(violation) = config.password_policy.check(&password) {
+        bot.send_message(
+            msg.chat.id,
+            t_args(
+                lang.as_str(),
+                violation.message_key(),
+                &violation.message_args(),
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+    dialogue.exit().await?;
+
+    let chat_id = TelegramId::new(msg.chat.id.0);
+    let Some(reg) = db.get_registration_by_id(chat_id).await? else {
+        bot.send_message(msg.chat.id, t(lang.as_str(), "myaccount-not-registered"))
+            .await?;
+        return Ok(());
+    };
+    let Some(username) = Username::parse(&reg.teamtalk_username) else {
+        error!(tg_id = %chat_id, "Stored TeamTalk username is empty");
+        bot.send_message(msg.chat.id, t(lang.as_str(), "changepassword-failed"))
+            .await?;
+        return Ok(());
+    };
+    let Some(server) = account_server(&servers, &reg) else {
+        bot.send_message(msg.chat.id, t(lang.as_str(), "myaccount-server-unknown"))
+            .await?;
+        return Ok(());
+    };
+
+    let result = server
+        .tx_tt
+        .request(|resp| TTWorkerCommand::UpdateAccount {
+            username: username.clone(),
+            new_password: Some(password.clone()),
+            new_rights: None,
+            new_note: None,
+            resp,
+        })
+        .await;
+    match result {
+        Ok(Ok(true)) => {
+            info!(tg_id = %chat_id, username = %username, "Password changed via bot");
+            bot.send_message(msg.chat.id, t(lang.as_str(), "changepassword-done"))
+                .await?;
+            let nickname = reg.nickname.as_deref().unwrap_or(username.as_str());
+            let assets = registration::build_assets(
+                &server.config,
+                lang.as_str(),
+                username.as_str(),
+                password.as_str(),
+                nickname,
+            );
+            send_registration_assets(
+                &bot,
+                msg.chat.id,
+                lang.as_str(),
+                &server.config,
+                username.as_str(),
+                password.as_str(),
+                &assets,
+            )
+            .await?;
+        }
+        Ok(Ok(false)) => {
+            warn!(tg_id = %chat_id, username = %username, "Password change returned false");
+            bot.send_message(msg.chat.id, t(lang.as_str(), "changepassword-failed"))
+                .await?;
+        }
+        Ok(Err(e)) => {
+            warn!(tg_id = %chat_id, username = %username, error = %e, "Password change failed");
+            bot.send_message(msg.chat.id, t(lang.as_str(), "changepassword-failed"))
+                .await?;
+        }
+        Err(e) => {
+            error!(tg_id = %chat_id, error = %e, "TeamTalk update account request failed");
+            bot.send_message(msg.chat.id, t(lang.as_str(), "changepassword-failed"))
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DEFAULT_SERVER_ID, TeamTalkServer};
+    use crate::tt::TTWorkerHandle;
+    use anyhow::Result;
+    use std::path::Path;
+    use std::time::Duration;
+
+    fn server(config: AppConfig) -> TTServer {
+        let (tx_tt, _rx_tt) = TTWorkerHandle::channel(1, Duration::from_secs(1));
+        TTServer {
+            config: Arc::new(config),
+            tx_tt,
+        }
+    }
+
+    #[test]
+    fn account_on_second_server_uses_its_config() -> Result<()> {
+        let mut config = AppConfig::load(Path::new("config.toml.example"))?;
+        let music: TeamTalkServer = toml::from_str(
+            "id = \"music\"\nhost_name = \"music.example.com\"\nserver_name = \"Music\"",
+        )?;
+        config.teamtalk_servers.push(music.clone());
+        let servers = TTServers::new(vec![
+            server(config.clone()),
+            server(config.for_server(&music)),
+        ]);
+        let mut reg = TelegramRegistration {
+            telegram_id: TelegramId::new(1),
+            teamtalk_username: "alice".to_string(),
+            nickname: None,
+            created_at: None,
+            server_id: "music".to_string(),
+            source: None,
+        };
+
+        let server = account_server(&servers, &reg)
+            .ok_or_else(|| anyhow::anyhow!("second server not found"))?;
+        assert_eq!(server.id(), "music");
+        let assets = account_assets(&server.config, "en", &reg);
+        assert!(assets.link.contains("music.example.com"));
+        assert_eq!(assets.filename, "Music.tt");
+
+        reg.server_id = DEFAULT_SERVER_ID.to_string();
+        assert_eq!(
+            account_server(&servers, &reg).map(TTServer::id),
+            Some(DEFAULT_SERVER_ID)
+        );
+        reg.server_id = "gone".to_string();
+        assert!(account_server(&servers, &reg).is_none());
+        Ok(())
+    }
+}
@@ -1,23 +1,55 @@
+use super::broadcast::{prompt_tg_broadcast, prompt_tt_broadcast, start_tg_broadcast};
+use super::invites::{InviteAction, InviteOptions, handle_invite_action};
+use super::notify_settings::{NotifySettingsAction, handle_notify_settings};
+use super::offline_queue::notify_admin_queued;
+use super::online::{
+    OnlineAction, apply_online_action, prompt_online_action, prompt_online_message,
+    show_online_users,
+};
+use super::pending::{PendingAction, handle_pending_action};
+use super::reason::{DecisionOrigin, cancel_reason, prompt_reason, skip_reason, with_reason};
 use super::registration::{notify_db_sync_error, send_registration_assets};
-use super::{HandlerResult, MyDialogue, State};
-use crate::config::AppConfig;
-use crate::db::Database;
+use super::rename::prompt_rename;
+use super::web_approval::{WebApproveInput, answer_already_handled, handle_web_approve};
+use super::{
+    AdminList, Command, HandlerResult, MyDialogue, ReasonTarget, RenameTarget, State, tt_error_text,
+};
+use crate::config::{AppConfig, BanPropagation, DEFAULT_RIGHTS_PROFILE, DEFAULT_SERVER_ID};
+use crate::db::schema::TelegramRegistration;
+use crate::db::{Database, NewQueuedRegistration};
 use crate::domain::{Nickname, Password, Username};
 use crate::i18n::{t, t_args};
-use crate::services::admin::parse_source_info;
-use crate::services::registration;
-use crate::types::{LanguageCode, RegistrationSource, TTAccountType, TTWorkerCommand, TelegramId};
+use crate::services::admin as admin_service;
+use crate::services::audit::{self, Actor, AuditAction};
+use crate::services::{bans, registration};
+use crate::tg_bot::notify::AdminChats;
+use crate::tg_bot::throttle;
+use crate::tt::{TTServer, TTServers, TTWorkerHandle};
+use crate::types::{
+    LanguageCode, NotificationKind, RegistrationSource, ShutdownHandle, SourceInfo, TTAccountType,
+    TTCommandError, TTWorkerCommand, TelegramId, WebRequestStatus,
+};
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::sync::mpsc::Sender;
 use teloxide::prelude::*;
-use teloxide::types::ChatId;
-use tracing::warn;
+use teloxide::types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup, User};
+use tracing::{info, warn};
 use uuid::Uuid;
 
+/// Longest search prefix carried in pagination callbacks (Telegram allows 64 bytes of data).
+const MAX_FILTER_BYTES: usize = 32;
+/// Ban reasons are shortened in the list so a page stays within Telegram's message limit.
+const MAX_REASON_CHARS: usize = 100;
+
 enum AdminCallback {
-    Approve(String),
+    Approve(String, ApproveAs),
     Reject(String),
+    ApproveWeb(String, ApproveAs),
+    RejectWeb(String),
+    Rename(RenameTarget),
+    ReasonSkip,
+    ReasonCancel,
+    NotifySettings(NotifySettingsAction),
     Panel(AdminPanelAction),
 }
 
@@ -26,23 +58,110 @@ enum AdminPanelAction {
     DeleteUsersPage(usize),
     DeleteConfirm(i64),
     BanlistView,
-    BanlistPage(usize),
+    BanlistPage(usize, String),
+    BanlistSearch,
     Unban(i64),
+    UndoBans(Vec<i64>),
     BanManual,
     ListTeamTalkUsers,
-    ListTeamTalkUsersPage(usize),
+    ListTeamTalkUsersPage(usize, String),
+    TeamTalkSearch,
     TeamTalkDeletePrompt(String),
     TeamTalkDeleteConfirm(String),
+    OnlineUsers,
+    OnlineUsersPage(usize),
+    OnlineKickPrompt(i32),
+    OnlineKick(i32),
+    OnlineBanPrompt(i32),
+    OnlineBan(i32),
+    OnlineMessage(i32),
+    TeamTalkBroadcast,
+    TelegramBroadcast,
+    TelegramBroadcastConfirm,
+    Invites(InviteAction),
+    Pending(PendingAction),
+    Search,
+    Export,
     Cancel,
 }
 
+impl AdminCallback {
+    /// Callbacks moderators may not use.
+    const fn requires_owner(&self) -> bool {
+        match self {
+            Self::Approve(_, approve_as) | Self::ApproveWeb(_, approve_as) => {
+                matches!(approve_as, ApproveAs::Admin)
+            }
+            Self::Panel(action) => action.requires_owner(),
+            _ => false,
+        }
+    }
+}
+
+impl AdminPanelAction {
+    /// Actions moderators may not take: bans, account deletion and invites.
+    const fn requires_owner(&self) -> bool {
+        matches!(
+            self,
+            Self::DeleteUsers
+                | Self::DeleteUsersPage(_)
+                | Self::DeleteConfirm(_)
+                | Self::Unban(_)
+                | Self::UndoBans(_)
+                | Self::BanManual
+                | Self::TeamTalkDeletePrompt(_)
+                | Self::TeamTalkDeleteConfirm(_)
+                | Self::OnlineBanPrompt(_)
+                | Self::OnlineBan(_)
+                | Self::Invites(_)
+        )
+    }
+}
+
+/// What an approve button grants.
+pub(super) enum ApproveAs {
+    /// The request as submitted, with the rights profile the user asked for.
+    Requested,
+    Profile(String),
+    Admin,
+}
+
+impl ApproveAs {
+    /// Account type and rights profile to create the account with, given the
+    /// profile the user asked for.
+    pub(super) fn grants<'a>(
+        &'a self,
+        requested: Option<&'a str>,
+    ) -> (TTAccountType, Option<&'a str>) {
+        match self {
+            Self::Requested => (TTAccountType::Default, requested),
+            Self::Profile(name) => (TTAccountType::Default, Some(name)),
+            Self::Admin => (TTAccountType::Admin, None),
+        }
+    }
+}
+
+/// Audit note on what an approval granted beyond a default account.
+pub(super) fn grant_note(account_type: TTAccountType, rights_profile: Option<&str>) -> String {
+    match (account_type, rights_profile) {
+        (TTAccountType::Admin, _) => "; as admin".to_string(),
+        (TTAccountType::Default, Some(profile)) => format!("; rights profile: {profile}"),
+        (TTAccountType::Default, None) => String::new(),
+    }
+}
+
 struct PendingApproval {
     username: Username,
     password: Password,
     nickname: Nickname,
     req_lang: LanguageCode,
     registrant_id: TelegramId,
-    source_info: String,
+    source_info: SourceInfo,
+    server: TTServer,
+    /// Profile the user asked for.
+    rights_profile: Option<String>,
+    /// `queued_registrations` entry journaling the approval.
+    queued_id: i64,
 }
 
 /// Show admin panel entrypoint.
@@ -61,16 +180,29 @@ pub async fn admin_panel(
     }
     let lang = config.telegram.bot_admin_lang.clone();
     bot.send_message(msg.chat.id, t(lang.as_str(), "admin-panel-title"))
-        .reply_markup(crate::tg_bot::keyboards::admin_panel_keyboard(
-            &t(lang.as_str(), "btn-delete-user"),
-            &t(lang.as_str(), "btn-manage-banlist"),
-            &t(lang.as_str(), "btn-list-tt-accounts"),
-        ))
+        .reply_markup(panel_keyboard(&lang))
         .await?;
     dialogue.update(State::AdminPanel).await?;
     Ok(())
 }
 
+pub(super) fn panel_keyboard(lang: &LanguageCode) -> InlineKeyboardMarkup {
+    let lang = lang.as_str();
+    crate::tg_bot::keyboards::admin_panel_keyboard(crate::tg_bot::keyboards::AdminPanelLabels {
+        delete: t(lang, "btn-delete-user"),
+        banlist: t(lang, "btn-manage-banlist"),
+        tt_list: t(lang, "btn-list-tt-accounts"),
+        online: t(lang, "btn-online-users"),
+        server_broadcast: t(lang, "btn-tt-broadcast"),
+        users_broadcast: t(lang, "btn-tg-broadcast"),
+        invites: t(lang, "btn-invites"),
+        pending: t(lang, "btn-pending-requests"),
+        search: t(lang, "btn-search"),
+        export: t(lang, "btn-export"),
+        notifications: t(lang, "btn-notifications"),
+    })
+}
+
 /// Handle admin panel callbacks.
 pub async fn admin_callback(
     bot: Bot,
@@ -78,7 +210,7 @@ pub async fn admin_callback(
     db: Database,
     config: Arc<AppConfig>,
     dialogue: MyDialogue,
-    tx_tt: Sender<TTWorkerCommand>,
+    servers: TTServers,
 ) -> HandlerResult {
     let data = q.data.clone().unwrap_or_default();
     if data.is_empty() {
@@ -90,31 +222,66 @@ pub async fn admin_callback(
         return Ok(());
     };
     let lang = config.telegram.bot_admin_lang.clone();
-    if !config
-        .telegram
-        .admin_ids
-        .contains(&TelegramId::new(chat_id))
-    {
+    let admin_id = TelegramId::new(chat_id);
+    if !config.telegram.admin_ids.contains(&admin_id) {
+        return Ok(());
+    }
+    let Some(callback) = parse_admin_callback(&data) else {
+        warn!(data = %data, "Unknown admin callback action");
+        return Ok(());
+    };
+    if callback.requires_owner() && !config.is_owner(admin_id) {
+        bot.answer_callback_query(q.id)
+            .text(t(lang.as_str(), "admin-owner-only"))
+            .show_alert(true)
+            .await?;
         return Ok(());
     }
-    match parse_admin_callback(&data) {
-        Some(AdminCallback::Approve(req_id)) => {
+    match callback {
+        AdminCallback::Approve(req_id, approve_as) => {
             handle_admin_approve(AdminApproveInput {
+                bot: &bot,
+                q: &q,
+                db: &db,
+                lang: &lang,
+                req_id: &req_id,
+                approve_as: &approve_as,
+                servers: &servers,
+                chat_id,
+            })
+            .await?;
+        }
+        AdminCallback::Reject(req_id) => {
+            let target = ReasonTarget::Reject(req_id);
+            prompt_reject(&bot, &q, &db, &lang, &dialogue, target).await?;
+        }
+        AdminCallback::ApproveWeb(req_id, approve_as) => {
+            handle_web_approve(WebApproveInput {
                 bot: &bot,
                 q: &q,
                 db: &db,
                 config: &config,
                 lang: &lang,
                 req_id: &req_id,
-                tx_tt,
+                approve_as: &approve_as,
+                servers: &servers,
                 chat_id,
             })
             .await?;
         }
-        Some(AdminCallback::Reject(req_id)) => {
-            handle_admin_reject(&bot, &q, &db, &config, &lang, &req_id).await?;
+        AdminCallback::RejectWeb(req_id) => {
+            let target = ReasonTarget::RejectWeb(req_id);
+            prompt_reject(&bot, &q, &db, &lang, &dialogue, target).await?;
+        }
+        AdminCallback::Rename(target) => {
+            prompt_rename(&bot, &q, &db, &lang, &dialogue, target).await?;
+        }
+        AdminCallback::ReasonSkip => skip_reason(&bot, &q, &db, &config, &dialogue).await?,
+        AdminCallback::ReasonCancel => cancel_reason(&bot, &q, &config, &dialogue).await?,
+        AdminCallback::NotifySettings(action) => {
+            handle_notify_settings(&bot, &q, &db, &config, admin_id, action).await?;
         }
-        Some(AdminCallback::Panel(action)) => {
+        AdminCallback::Panel(action) => {
             bot.answer_callback_query(q.id).await?;
             let Some(msg) = q.message.as_ref().and_then(|m| m.regular_message()) else {
                 warn!("Admin callback query missing or inaccessible message");
@@ -127,21 +294,45 @@ pub async fn admin_callback(
                     db: &db,
                     lang: &lang,
                     dialogue: &dialogue,
-                    tx_tt: &tx_tt,
-                    chat_id,
+                    tx_tt: &servers.primary().tx_tt,
+                    config: &config,
                 },
                 action,
             )
             .await?;
         }
-        None => {
-            warn!(data = %data, "Unknown admin callback action");
-        }
     }
 
     Ok(())
 }
 
+/// Ask for a rejection reason if the request is still undecided.
+async fn prompt_reject(
+    bot: &Bot,
+    q: &CallbackQuery,
+    db: &Database,
+    lang: &LanguageCode,
+    dialogue: &MyDialogue,
+    target: ReasonTarget,
+) -> HandlerResult {
+    let undecided = match &target {
+        ReasonTarget::Reject(req_id) => db.get_pending_registration(req_id).await?.is_some(),
+        ReasonTarget::RejectWeb(req_id) => db
+            .get_pending_web_registration(req_id)
+            .await?
+            .is_some_and(|req| req.status == WebRequestStatus::Pending.as_str()),
+        ReasonTarget::Ban(_) => true,
+    };
+    if !undecided {
+        return answer_already_handled(bot, q, lang).await;
+    }
+    if let Some(m) = &q.message {
+        bot.answer_callback_query(q.id.clone()).await?;
+        prompt_reason(bot, m.chat().id, m.id(), lang, dialogue, target).await?;
+    }
+    Ok(())
+}
+
 /// Handle manual ban input from admin.
 pub async fn admin_manual_ban_input(
     bot: Bot,
@@ -150,6 +341,10 @@ pub async fn admin_manual_ban_input(
     config: Arc<AppConfig>,
     dialogue: MyDialogue,
 ) -> HandlerResult {
+    if !config.is_owner(TelegramId::new(msg.chat.id.0)) {
+        dialogue.update(State::AdminPanel).await?;
+        return Ok(());
+    }
     let text = msg.text().unwrap_or("");
     let parts: Vec<&str> = text.lines().collect();
     let lang = config.telegram.bot_admin_lang.clone();
@@ -166,10 +361,13 @@ pub async fn admin_manual_ban_input(
             None
         };
         let args = HashMap::from([("tg_id".to_string(), tg_id.to_string())]);
+        let target_lang = registered_lang(&db, tg_id_typed, &lang).await;
+        let reg = db.get_registration_by_id(tg_id_typed).await.ok().flatten();
         if db
             .ban_user(
                 tg_id_typed,
-                None,
+                reg.as_ref().map(|reg| reg.teamtalk_username.as_str()),
+                reg.as_ref().map(|reg| reg.server_id.as_str()),
                 Some(TelegramId::new(msg.chat.id.0)),
                 reason,
             )
@@ -179,11 +377,17 @@ pub async fn admin_manual_ban_input(
             bot.send_message(msg.chat.id, t_args(lang.as_str(), "admin-ban-fail", &args))
                 .await?;
         } else {
+            let actor = Actor::Admin(TelegramId::new(msg.chat.id.0));
+            audit::record(&db, actor, AuditAction::Ban, &tg_id.to_string(), reason).await;
+            notify_banned_user(&bot, &target_lang, tg_id_typed, reason).await;
             bot.send_message(
                 msg.chat.id,
                 t_args(lang.as_str(), "admin-ban-success", &args),
             )
             .await?;
+            if let Some(reg) = &reg {
+                offer_teamtalk_delete(&bot, msg.chat.id, &config, &lang, reg).await?;
+            }
         }
     } else {
         bot.send_message(msg.chat.id, t(lang.as_str(), "admin-ban-invalid"))
@@ -194,103 +398,135 @@ pub async fn admin_manual_ban_input(
     Ok(())
 }
 
-/// Generate a Telegram deeplink invite token.
+/// Generate a Telegram deeplink invite token (`/generate [ttl] [uses] [--server <id>]`).
 pub async fn generate_invite(
     bot: Bot,
     msg: Message,
     db: Database,
     config: Arc<AppConfig>,
+    args: String,
 ) -> HandlerResult {
-    if !config
-        .telegram
-        .admin_ids
-        .contains(&TelegramId::new(msg.chat.id.0))
-    {
+    if !config.is_owner(TelegramId::new(msg.chat.id.0)) {
         return Ok(());
     }
+    let lang = config.telegram.bot_admin_lang.as_str();
     if !config.telegram.telegram_deeplink_registration_enabled {
-        bot.send_message(
-            msg.chat.id,
-            t(config.telegram.bot_admin_lang.as_str(), "deeplink-disabled"),
-        )
-        .await?;
+        bot.send_message(msg.chat.id, t(lang, "deeplink-disabled"))
+            .await?;
+        return Ok(());
+    }
+
+    let Some(options) = InviteOptions::parse(&args) else {
+        bot.send_message(msg.chat.id, t(lang, "deeplink-generate-usage"))
+            .await?;
+        return Ok(());
+    };
+
+    if let Some(server) = &options.server
+        && !config.is_server(server)
+    {
+        let args = HashMap::from([("server".to_string(), server.clone())]);
+        bot.send_message(msg.chat.id, t_args(lang, "deeplink-unknown-server", &args))
+            .await?;
         return Ok(());
     }
 
     let token = Uuid::new_v4().to_string().replace('-', "");
-    let expires = chrono::Utc::now().naive_utc() + chrono::Duration::minutes(5);
-    if db
-        .create_deeplink(&token, expires, TelegramId::new(msg.chat.id.0))
+    let expires = chrono::Utc::now().naive_utc() + options.ttl;
+    let admin_id = TelegramId::new(msg.chat.id.0);
+    let server = options.server.as_deref();
+    let Ok(invite_id) = db
+        .create_deeplink(&token, expires, options.max_uses, admin_id, server)
         .await
-        .is_err()
-    {
-        bot.send_message(
-            msg.chat.id,
-            t(
-                config.telegram.bot_admin_lang.as_str(),
-                "deeplink-generate-error",
-            ),
-        )
-        .await?;
+    else {
+        bot.send_message(msg.chat.id, t(lang, "deeplink-generate-error"))
+            .await?;
         return Ok(());
+    };
+
+    let mut details = format!(
+        "{} use(s), expires {}",
+        options.max_uses,
+        expires.format("%Y-%m-%d %H:%M UTC")
+    );
+    if let Some(server) = server {
+        details.push_str(", server ");
+        details.push_str(server);
     }
+    let target = invite_id.to_string();
+    audit::record(
+        &db,
+        Actor::Admin(admin_id),
+        AuditAction::GenerateInvite,
+        &target,
+        Some(&details),
+    )
+    .await;
 
     let bot_info = match bot.get_me().await {
         Ok(info) => info,
         Err(e) => {
             warn!(error = %e, "Failed to fetch bot info");
-            bot.send_message(
-                msg.chat.id,
-                t(
-                    config.telegram.bot_admin_lang.as_str(),
-                    "deeplink-generate-error",
-                ),
-            )
-            .await?;
+            bot.send_message(msg.chat.id, t(lang, "deeplink-generate-error"))
+                .await?;
             return Ok(());
         }
     };
     let Some(bot_username) = bot_info.username.clone() else {
-        bot.send_message(
-            msg.chat.id,
-            t(
-                config.telegram.bot_admin_lang.as_str(),
-                "deeplink-bot-username-missing",
-            ),
-        )
-        .await?;
+        bot.send_message(msg.chat.id, t(lang, "deeplink-bot-username-missing"))
+            .await?;
         return Ok(());
     };
     let link = format!("https://t.me/{bot_username}?start={token}");
-    bot.send_message(msg.chat.id, link).await?;
+    let args = HashMap::from([
+        ("link".to_string(), link),
+        (
+            "expires".to_string(),
+            expires.format("%Y-%m-%d %H:%M UTC").to_string(),
+        ),
+        ("uses".to_string(), options.max_uses.to_string()),
+    ]);
+    let mut text = t_args(lang, "deeplink-generated", &args);
+    if config.web.web_registration_enabled {
+        let args = HashMap::from([("token".to_string(), token)]);
+        text = format!("{text}\n{}", t_args(lang, "deeplink-generated-web", &args));
+    }
+    bot.send_message(msg.chat.id, text).await?;
     Ok(())
 }
 
+/// Outcome reported to the other admins.
+enum AdminDecision<'a> {
+    Approved,
+    Rejected { reason: Option<&'a str> },
+}
+
 async fn notify_admin_decision(
     bot: &Bot,
-    config: &AppConfig,
-    q: &CallbackQuery,
-    decision: &str,
+    admins: &AdminChats,
+    admin: &User,
+    decision: AdminDecision<'_>,
     username: &str,
     registrant_telegram_id: TelegramId,
-    source_info: &str,
+    source: &SourceInfo,
 ) {
-    let admin_lang = config.telegram.bot_admin_lang.clone();
-    let source = parse_source_info(source_info);
-    let user_lang = source.lang;
-    let tg_username = source.tg_username;
-    let fullname = source.fullname;
-    let admin_name = q.from.full_name();
-
-    let decision_text = if decision == "approved" {
-        t(admin_lang.as_str(), "admin-decision-approved")
-    } else {
-        t(admin_lang.as_str(), "admin-decision-rejected")
+    let admin_lang = admins.lang();
+    let user_lang = &source.lang;
+    let tg_username = source.tg_username.clone().unwrap_or_default();
+    let fullname = source
+        .fullname
+        .clone()
+        .unwrap_or_else(|| t(admin_lang, "value-unknown"));
+    let admin_name = admin.full_name();
+
+    let (decision_text, reason) = match decision {
+        AdminDecision::Approved => (t(admin_lang, "admin-decision-approved"), None),
+        AdminDecision::Rejected { reason } => (t(admin_lang, "admin-decision-rejected"), reason),
     };
 
     let mut args = HashMap::new();
     args.insert("admin_name".to_string(), admin_name);
-    args.insert("admin_id".to_string(), q.from.id.0.to_string());
+    args.insert("admin_id".to_string(), admin.id.0.to_string());
     args.insert("decision".to_string(), decision_text);
     args.insert("teamtalk_username".to_string(), username.to_string());
     args.insert(
@@ -304,58 +540,112 @@ async fn notify_admin_decision(
         user_lang.as_str().to_string(),
     );
 
-    let mut text = t_args(admin_lang.as_str(), "admin-decision-notify", &args);
+    let mut text = t_args(admin_lang, "admin-decision-notify", &args);
     if !args
         .get("registrant_tg_username")
         .unwrap_or(&String::new())
         .is_empty()
     {
-        let suffix = t_args(
-            admin_lang.as_str(),
-            "admin-decision-telegram-username",
-            &args,
-        );
+        let suffix = t_args(admin_lang, "admin-decision-telegram-username", &args);
         text.push_str(&suffix);
     }
-    for &admin_id in &config.telegram.admin_ids {
-        if let Ok(sender_id) = i64::try_from(q.from.id.0)
-            && admin_id.as_i64() != sender_id
-        {
-            let _ = bot.send_message(ChatId(admin_id.as_i64()), &text).await;
-        }
-    }
+    let text = with_reason(admin_lang, text, reason);
+    let sender = i64::try_from(admin.id.0).ok().map(ChatId);
+    admins
+        .send(bot, NotificationKind::Approvals, &text, None, sender)
+        .await;
 }
 
-/// Exit command handler.
-pub async fn exit_bot(bot: Bot, msg: Message, config: Arc<AppConfig>) -> HandlerResult {
-    if !config
-        .telegram
-        .admin_ids
-        .contains(&TelegramId::new(msg.chat.id.0))
-    {
+/// `/exit` and `/restart`: stop the bot gracefully.
+pub async fn exit_bot(
+    bot: Bot,
+    msg: Message,
+    cmd: Command,
+    config: Arc<AppConfig>,
+    shutdown: ShutdownHandle,
+) -> HandlerResult {
+    if !config.is_owner(TelegramId::new(msg.chat.id.0)) {
         return Ok(());
     }
-    bot.send_message(
-        msg.chat.id,
-        t(config.telegram.bot_admin_lang.as_str(), "bot-shutdown"),
+    let restart = matches!(cmd, Command::Restart);
+    let key = if restart {
+        "bot-restart"
+    } else {
+        "bot-shutdown"
+    };
+    bot.send_message(msg.chat.id, t(config.telegram.bot_admin_lang.as_str(), key))
+        .await?;
+    info!(
+        admin_id = msg.chat.id.0,
+        restart, "Shutdown requested via bot"
+    );
+    shutdown.request(restart);
+    Ok(())
+}
+
+/// Split `"{id}:{profile}"` and `"{id}@admin"` approve callback data into
+/// its parts.
+fn split_approve_as(data: &str) -> (String, ApproveAs) {
+    if let Some(id) = data.strip_suffix("@admin") {
+        return (id.to_string(), ApproveAs::Admin);
+    }
+    data.split_once(':').map_or_else(
+        || (data.to_string(), ApproveAs::Requested),
+        |(id, profile)| (id.to_string(), ApproveAs::Profile(profile.to_string())),
     )
-    .await?;
-    std::process::exit(0);
 }
 
 fn parse_admin_callback(data: &str) -> Option<AdminCallback> {
+    if let Some(id) = data.strip_prefix("approve_web_") {
+        let (id, approve_as) = split_approve_as(id);
+        return Some(AdminCallback::ApproveWeb(id, approve_as));
+    }
+    if let Some(id) = data.strip_prefix("reject_web_") {
+        return Some(AdminCallback::RejectWeb(id.to_string()));
+    }
+    if let Some(id) = data.strip_prefix("rename_web_") {
+        return Some(AdminCallback::Rename(RenameTarget::Web(id.to_string())));
+    }
     if let Some(id) = data.strip_prefix("approve_") {
-        return Some(AdminCallback::Approve(id.to_string()));
+        let (id, approve_as) = split_approve_as(id);
+        return Some(AdminCallback::Approve(id, approve_as));
     }
     if let Some(id) = data.strip_prefix("reject_") {
         return Some(AdminCallback::Reject(id.to_string()));
     }
+    if let Some(id) = data.strip_prefix("rename_") {
+        return Some(AdminCallback::Rename(RenameTarget::Telegram(
+            id.to_string(),
+        )));
+    }
+    match data {
+        "reason_skip" => return Some(AdminCallback::ReasonSkip),
+        "reason_cancel" => return Some(AdminCallback::ReasonCancel),
+        _ => {}
+    }
 
+    if let Some(action) = PendingAction::parse(data) {
+        return Some(AdminCallback::Panel(AdminPanelAction::Pending(action)));
+    }
+    if let Some(action) = NotifySettingsAction::parse(data) {
+        return Some(AdminCallback::NotifySettings(action));
+    }
     let panel = match data {
         "admin_del" => AdminPanelAction::DeleteUsers,
         "admin_banlist_view" => AdminPanelAction::BanlistView,
+        "admin_banlist_search" => AdminPanelAction::BanlistSearch,
+        "admin_tt_search" => AdminPanelAction::TeamTalkSearch,
         "admin_ban_manual" => AdminPanelAction::BanManual,
         "admin_tt_list" => AdminPanelAction::ListTeamTalkUsers,
+        "admin_online" => AdminPanelAction::OnlineUsers,
+        "admin_tt_broadcast" => AdminPanelAction::TeamTalkBroadcast,
+        "admin_tg_broadcast" => AdminPanelAction::TelegramBroadcast,
+        "confirm_tg_broadcast" => AdminPanelAction::TelegramBroadcastConfirm,
+        "admin_invites" => AdminPanelAction::Invites(InviteAction::List(0)),
+        "admin_invite_revoke_all" => AdminPanelAction::Invites(InviteAction::RevokeAllPrompt),
+        "confirm_invite_revoke_all" => AdminPanelAction::Invites(InviteAction::RevokeAll),
+        "admin_search" => AdminPanelAction::Search,
+        "admin_export" => AdminPanelAction::Export,
         "cancel_action" => AdminPanelAction::Cancel,
         _ => {
             if let Some(id) = data.strip_prefix("admin_del_confirm_") {
@@ -364,19 +654,39 @@ fn parse_admin_callback(data: &str) -> Option<AdminCallback> {
             } else if let Some(page) = data.strip_prefix("admin_del_page_") {
                 let page = page.parse::<usize>().ok()?;
                 AdminPanelAction::DeleteUsersPage(page)
-            } else if let Some(id) = data.strip_prefix("admin_unban_") {
-                let id = id.parse::<i64>().ok()?;
-                AdminPanelAction::Unban(id)
-            } else if let Some(page) = data.strip_prefix("admin_banlist_page_") {
-                let page = page.parse::<usize>().ok()?;
-                AdminPanelAction::BanlistPage(page)
+            } else if let Some(action) = parse_ban_callback(data) {
+                action
             } else if let Some(user) = data.strip_prefix("admin_tt_del_prompt_") {
                 AdminPanelAction::TeamTalkDeletePrompt(user.to_string())
             } else if let Some(user) = data.strip_prefix("confirm_tt_del_") {
                 AdminPanelAction::TeamTalkDeleteConfirm(user.to_string())
             } else if let Some(page) = data.strip_prefix("admin_tt_list_page_") {
+                let (page, filter) = parse_page_callback(page)?;
+                AdminPanelAction::ListTeamTalkUsersPage(page, filter)
+            } else if let Some(page) = data.strip_prefix("admin_invites_page_") {
+                let page = page.parse::<usize>().ok()?;
+                AdminPanelAction::Invites(InviteAction::List(page))
+            } else if let Some(id) = data.strip_prefix("admin_invite_revoke_") {
+                let id = id.parse::<i64>().ok()?;
+                AdminPanelAction::Invites(InviteAction::Revoke(id))
+            } else if let Some(page) = data.strip_prefix("admin_online_page_") {
                 let page = page.parse::<usize>().ok()?;
-                AdminPanelAction::ListTeamTalkUsersPage(page)
+                AdminPanelAction::OnlineUsersPage(page)
+            } else if let Some(id) = data.strip_prefix("admin_online_kick_") {
+                let id = id.parse::<i32>().ok()?;
+                AdminPanelAction::OnlineKickPrompt(id)
+            } else if let Some(id) = data.strip_prefix("confirm_online_kick_") {
+                let id = id.parse::<i32>().ok()?;
+                AdminPanelAction::OnlineKick(id)
+            } else if let Some(id) = data.strip_prefix("admin_online_ban_") {
+                let id = id.parse::<i32>().ok()?;
+                AdminPanelAction::OnlineBanPrompt(id)
+            } else if let Some(id) = data.strip_prefix("confirm_online_ban_") {
+                let id = id.parse::<i32>().ok()?;
+                AdminPanelAction::OnlineBan(id)
+            } else if let Some(id) = data.strip_prefix("admin_online_msg_") {
+                let id = id.parse::<i32>().ok()?;
+                AdminPanelAction::OnlineMessage(id)
             } else {
                 return None;
             }
@@ -386,14 +696,29 @@ fn parse_admin_callback(data: &str) -> Option<AdminCallback> {
     Some(AdminCallback::Panel(panel))
 }
 
+/// Banlist callbacks carrying ids or a page.
+fn parse_ban_callback(data: &str) -> Option<AdminPanelAction> {
+    if let Some(ids) = data.strip_prefix("admin_ban_undo_") {
+        let ids = ids.split('.').map(str::parse).collect::<Result<_, _>>();
+        Some(AdminPanelAction::UndoBans(ids.ok()?))
+    } else if let Some(id) = data.strip_prefix("admin_unban_") {
+        Some(AdminPanelAction::Unban(id.parse().ok()?))
+    } else if let Some(page) = data.strip_prefix("admin_banlist_page_") {
+        let (page, filter) = parse_page_callback(page)?;
+        Some(AdminPanelAction::BanlistPage(page, filter))
+    } else {
+        None
+    }
+}
+
 struct AdminApproveInput<'a> {
     bot: &'a Bot,
     q: &'a CallbackQuery,
     db: &'a Database,
-    config: &'a AppConfig,
     lang: &'a LanguageCode,
     req_id: &'a str,
-    tx_tt: Sender<TTWorkerCommand>,
+    approve_as: &'a ApproveAs,
+    servers: &'a TTServers,
     chat_id: i64,
 }
 
@@ -402,159 +727,216 @@ async fn handle_admin_approve(input: AdminApproveInput<'_>) -> HandlerResult {
         bot,
         q,
         db,
-        config,
         lang,
         req_id,
-        tx_tt,
+        approve_as,
+        servers,
         chat_id,
     } = input;
-    let Some(pending) = load_pending_approval(bot, q, db, lang, req_id).await? else {
+    let Some(pending) =
+        claim_pending_approval(bot, q, db, lang, req_id, approve_as, servers).await?
+    else {
         return Ok(());
     };
-
+    let config = &*pending.server.config;
+    let (account_type, rights_profile) = approve_as.grants(pending.rights_profile.as_deref());
     let result = registration::create_teamtalk_account(registration::CreateAccountParams {
         username: &pending.username,
         password: &pending.password,
         nickname: &pending.nickname,
-        account_type: TTAccountType::Default,
+        account_type,
         source: RegistrationSource::Telegram(pending.registrant_id),
         source_info: Some(pending.source_info.clone()),
         telegram_id: Some(pending.registrant_id),
-        tx_tt: tx_tt.clone(),
+        language: Some(&pending.req_lang),
+        queued_id: Some(pending.queued_id),
+        rights_profile,
+        tx_tt: pending.server.tx_tt.clone(),
         db,
         config,
     })
     .await?;
 
+    let details = format!(
+        "Telegram user {}{}{}",
+        pending.registrant_id,
+        grant_note(account_type, rights_profile),
+        result.outcome_note()
+    );
+    let actor = Actor::Admin(TelegramId::new(i64::try_from(q.from.id.0)?));
+    audit::record(
+        db,
+        actor,
+        AuditAction::Approve,
+        pending.username.as_str(),
+        Some(&details),
+    )
+    .await;
     notify_user_approved(bot, pending.registrant_id, &pending.req_lang).await;
     notify_admin_approve_alert(bot, q, lang, pending.username.as_str()).await?;
 
-    if !result.created {
-        notify_admin_approve_failed(bot, chat_id, lang, pending.username.as_str()).await;
-        notify_admin_decision(
+    if result.created {
+        handle_approval_success(
             bot,
+            db,
             config,
-            q,
-            "approved",
+            &pending,
+            result.db_sync_error.as_deref(),
+            result.assets.as_ref(),
+        )
+        .await;
+    } else if result.queued {
+        notify_admin_queued(
+            bot,
+            ChatId(chat_id),
+            lang.as_str(),
             pending.username.as_str(),
-            pending.registrant_id,
-            &pending.source_info,
         )
         .await;
-        db.delete_pending_registration(req_id).await?;
-        return Ok(());
+    } else {
+        if let Err(e) = db.delete_queued_registration(pending.queued_id).await {
+            warn!(error = %e, "Failed to drop the journal of a failed approval");
+        }
+        if let Some(err) = &result.error {
+            notify_admin_approve_failed(bot, chat_id, lang, pending.username.as_str(), err).await;
+        }
     }
 
-    handle_approval_success(
-        bot,
-        config,
-        &pending,
-        result.db_sync_error.as_deref(),
-        result.assets.as_ref(),
-    )
-    .await;
-
     notify_admin_decision(
         bot,
-        config,
-        q,
-        "approved",
+        &AdminChats::new(&config.telegram, db),
+        &q.from,
+        AdminDecision::Approved,
         pending.username.as_str(),
         pending.registrant_id,
         &pending.source_info,
     )
     .await;
-    db.delete_pending_registration(req_id).await?;
     Ok(())
 }
 
-async fn handle_admin_reject(
+pub(super) async fn handle_admin_reject(
     bot: &Bot,
-    q: &CallbackQuery,
+    origin: &DecisionOrigin<'_>,
     db: &Database,
     config: &AppConfig,
     lang: &LanguageCode,
     req_id: &str,
+    reason: Option<&str>,
 ) -> HandlerResult {
-    if let Ok(Some(req)) = db.get_pending_registration(req_id).await {
-        let username = req.username.clone();
-        let req_lang = parse_source_info(&req.source_info).lang;
-        bot.send_message(
-            ChatId(req.registrant_telegram_id.as_i64()),
-            t(req_lang.as_str(), "admin-rejected"),
+    let Ok(Some(req)) = db.claim_pending_registration(req_id).await else {
+        bot.edit_message_text(
+            origin.chat_id,
+            origin.message_id,
+            t(lang.as_str(), "admin-req-handled"),
         )
         .await?;
-        let alert_args = HashMap::from([("username".to_string(), username.clone())]);
-        bot.answer_callback_query(q.id.clone())
-            .text(t_args(
-                lang.as_str(),
-                "admin-req-rejected-alert",
-                &alert_args,
-            ))
-            .await?;
-        if let Some(m) = &q.message
-            && let Err(e) = bot.delete_message(m.chat().id, m.id()).await
-        {
-            warn!(error = %e, "Failed to delete admin request message");
-        }
-        notify_admin_decision(
-            bot,
-            config,
-            q,
-            "rejected",
-            &username,
-            req.registrant_telegram_id,
-            &req.source_info,
-        )
-        .await;
-        db.delete_pending_registration(req_id).await?;
-    } else {
-        bot.answer_callback_query(q.id.clone())
-            .text(t(lang.as_str(), "admin-req-not-found"))
-            .await?;
-        if let Some(m) = &q.message {
-            bot.edit_message_text(m.chat().id, m.id(), t(lang.as_str(), "admin-req-handled"))
-                .await?;
-        }
+        return Ok(());
+    };
+    let username = req.username.clone();
+    let req_lang = req.source_info.lang.clone();
+    let actor = Actor::Admin(TelegramId::new(i64::try_from(origin.admin.id.0)?));
+    audit::record(db, actor, AuditAction::Reject, &username, reason).await;
+    bot.send_message(
+        ChatId(req.registrant_telegram_id.as_i64()),
+        with_reason(
+            req_lang.as_str(),
+            t(req_lang.as_str(), "admin-rejected"),
+            reason,
+        ),
+    )
+    .await?;
+    let alert_args = HashMap::from([("username".to_string(), username.clone())]);
+    bot.send_message(
+        origin.chat_id,
+        t_args(lang.as_str(), "admin-req-rejected-alert", &alert_args),
+    )
+    .await?;
+    if let Err(e) = bot.delete_message(origin.chat_id, origin.message_id).await {
+        warn!(error = %e, "Failed to delete admin request message");
     }
+    notify_admin_decision(
+        bot,
+        &AdminChats::new(&config.telegram, db),
+        origin.admin,
+        AdminDecision::Rejected { reason },
+        &username,
+        req.registrant_telegram_id,
+        &req.source_info,
+    )
+    .await;
+    Ok(())
+}
 
+/// Answer an approval callback whose request another admin already decided.
+async fn answer_request_handled(
+    bot: &Bot,
+    q: &CallbackQuery,
+    lang: &LanguageCode,
+) -> HandlerResult {
+    bot.answer_callback_query(q.id.clone())
+        .text(t(lang.as_str(), "admin-req-not-found"))
+        .await?;
+    if let Some(m) = &q.message {
+        bot.edit_message_text(m.chat().id, m.id(), t(lang.as_str(), "admin-req-handled"))
+            .await?;
+    }
     Ok(())
 }
 
-async fn load_pending_approval(
+/// Take a pending request for approval, moving it into the offline queue as
+/// the journal that lets a crash before the account is recorded be retried.
+async fn claim_pending_approval(
     bot: &Bot,
     q: &CallbackQuery,
     db: &Database,
     lang: &LanguageCode,
     req_id: &str,
+    approve_as: &ApproveAs,
+    servers: &TTServers,
 ) -> Result<Option<PendingApproval>, Box<dyn std::error::Error + Send + Sync>> {
     let Some(req) = db.get_pending_registration(req_id).await? else {
-        bot.answer_callback_query(q.id.clone())
-            .text(t(lang.as_str(), "admin-req-not-found"))
-            .await?;
-        if let Some(m) = &q.message {
-            bot.edit_message_text(m.chat().id, m.id(), t(lang.as_str(), "admin-req-handled"))
-                .await?;
-        }
+        answer_request_handled(bot, q, lang).await?;
         return Ok(None);
     };
 
-    let Some(username) = Username::parse(&req.username) else {
+    let (Some(username), Some(password), Some(nickname)) = (
+        Username::parse(&req.username),
+        Password::parse(&req.password_cleartext),
+        Nickname::parse(&req.nickname),
+    ) else {
         bot.answer_callback_query(q.id.clone())
             .text(t(lang.as_str(), "admin-req-not-found"))
             .await?;
         return Ok(None);
     };
-    let Some(password) = Password::parse(&req.password_cleartext) else {
+    let source = SourceInfo {
+        approved_by: Some(TelegramId::new(i64::try_from(q.from.id.0)?)),
+        ..req.source_info
+    };
+    let Some(server) = servers.resolve(source.server.as_deref()) else {
+        warn!(req_id, server = ?source.server, "Pending registration names an unknown server");
         bot.answer_callback_query(q.id.clone())
             .text(t(lang.as_str(), "admin-req-not-found"))
             .await?;
         return Ok(None);
     };
-    let Some(nickname) = Nickname::parse(&req.nickname) else {
-        bot.answer_callback_query(q.id.clone())
-            .text(t(lang.as_str(), "admin-req-not-found"))
-            .await?;
+
+    let (account_type, rights_profile) = approve_as.grants(source.rights_profile.as_deref());
+    let journal = NewQueuedRegistration {
+        username: username.as_str(),
+        password: password.as_str(),
+        nickname: nickname.as_str(),
+        account_type: account_type.as_str(),
+        rights_profile: rights_profile.unwrap_or(DEFAULT_RIGHTS_PROFILE),
+        source: &RegistrationSource::Telegram(req.registrant_telegram_id).to_string(),
+        source_info: Some(&source),
+        telegram_id: Some(req.registrant_telegram_id),
+        language: Some(source.lang.as_str()),
+        server_id: server.id(),
+    };
+    let Some(queued_id) = db.approve_pending_registration(req_id, &journal).await? else {
+        answer_request_handled(bot, q, lang).await?;
         return Ok(None);
     };
 
@@ -562,20 +944,19 @@ async fn load_pending_approval(
         username,
         password,
         nickname,
-        req_lang: parse_source_info(&req.source_info).lang,
+        req_lang: source.lang.clone(),
         registrant_id: req.registrant_telegram_id,
-        source_info: req.source_info.clone(),
+        server: server.clone(),
+        rights_profile: source.rights_profile.clone(),
+        source_info: source,
+        queued_id,
     }))
 }
 
 async fn notify_user_approved(bot: &Bot, registrant_id: TelegramId, req_lang: &LanguageCode) {
-    if let Err(e) = bot
-        .send_message(
-            ChatId(registrant_id.as_i64()),
-            t(req_lang.as_str(), "admin-approved"),
-        )
-        .await
-    {
+    let chat_id = ChatId(registrant_id.as_i64());
+    let request = bot.send_message(chat_id, t(req_lang.as_str(), "admin-approved"));
+    if let Err(e) = throttle::send(chat_id, request).await {
         warn!(error = %e, "Failed to notify user about approval");
     }
 }
@@ -602,15 +983,22 @@ async fn notify_admin_approve_alert(
     Ok(())
 }
 
-async fn notify_admin_approve_failed(bot: &Bot, chat_id: i64, lang: &LanguageCode, username: &str) {
+/// Tell the approving admin that the account could not be created.
+pub(super) async fn notify_admin_approve_failed(
+    bot: &Bot,
+    chat_id: i64,
+    lang: &LanguageCode,
+    username: &str,
+    err: &TTCommandError,
+) {
+    let args = HashMap::from([
+        ("username".to_string(), username.to_string()),
+        ("error".to_string(), tt_error_text(lang.as_str(), err)),
+    ]);
     if let Err(e) = bot
         .send_message(
             ChatId(chat_id),
-            t_args(
-                lang.as_str(),
-                "admin-approve-failed-critical",
-                &HashMap::from([("username".to_string(), username.to_string())]),
-            ),
+            t_args(lang.as_str(), "admin-approve-failed-critical", &args),
         )
         .await
     {
@@ -620,6 +1008,7 @@ async fn notify_admin_approve_failed(bot: &Bot, chat_id: i64, lang: &LanguageCod
 
 async fn handle_approval_success(
     bot: &Bot,
+    db: &Database,
     config: &AppConfig,
     pending: &PendingApproval,
     db_sync_error: Option<&str>,
@@ -628,6 +1017,7 @@ async fn handle_approval_success(
     if let Some(err) = db_sync_error {
         notify_db_sync_error(
             bot,
+            db,
             config,
             ChatId(pending.registrant_id.as_i64()),
             pending.username.as_str(),
@@ -666,8 +1056,10 @@ struct AdminPanelContext<'a> {
     db: &'a Database,
     lang: &'a LanguageCode,
     dialogue: &'a MyDialogue,
-    tx_tt: &'a Sender<TTWorkerCommand>,
-    chat_id: i64,
+    /// Worker of the top-level server: account lists, online users and
+    /// account deletion in the panel only act on that one.
+    tx_tt: &'a TTWorkerHandle,
+    config: &'a AppConfig,
 }
 
 async fn handle_admin_panel_action(
@@ -681,59 +1073,131 @@ async fn handle_admin_panel_action(
         lang,
         dialogue,
         tx_tt,
-        chat_id,
+        config,
     } = ctx;
+    let page_size = config.telegram.admin_page_size;
     match action {
-        AdminPanelAction::DeleteUsers => show_admin_delete_users(bot, msg, db, lang, 0).await?,
+        AdminPanelAction::DeleteUsers => {
+            show_admin_delete_users(bot, msg, db, lang, 0, page_size).await?;
+        }
         AdminPanelAction::DeleteUsersPage(page) => {
-            show_admin_delete_users(bot, msg, db, lang, page).await?;
+            show_admin_delete_users(bot, msg, db, lang, page, page_size).await?;
         }
         AdminPanelAction::DeleteConfirm(target_id) => {
-            handle_admin_delete_confirm(bot, msg, db, lang, chat_id, target_id).await?;
+            let target = ReasonTarget::Ban(target_id);
+            prompt_reason(bot, msg.chat.id, msg.id, lang, dialogue, target).await?;
         }
-        AdminPanelAction::BanlistView => show_admin_banlist(bot, msg, db, lang, 0).await?,
-        AdminPanelAction::BanlistPage(page) => {
+        AdminPanelAction::BanlistView => {
+            let page = ListPage::new(0, page_size, "");
             show_admin_banlist(bot, msg, db, lang, page).await?;
         }
+        AdminPanelAction::BanlistPage(index, filter) => {
+            let page = ListPage::new(index, page_size, &filter);
+            show_admin_banlist(bot, msg, db, lang, page).await?;
+        }
+        AdminPanelAction::BanlistSearch => {
+            prompt_list_filter(bot, msg, lang, dialogue, AdminList::Banlist).await?;
+        }
         AdminPanelAction::Unban(target_id) => {
             handle_admin_unban(bot, msg, db, lang, target_id).await?;
         }
-        AdminPanelAction::BanManual => {
-            bot.send_message(msg.chat.id, t(lang.as_str(), "admin-ban-prompt"))
-                .await?;
-            dialogue.update(State::AwaitingManualBanInput).await?;
-        }
+        AdminPanelAction::UndoBans(ids) => handle_admin_undo_bans(bot, msg, db, lang, &ids).await?,
+        AdminPanelAction::BanManual => prompt_manual_ban(bot, msg, lang, dialogue).await?,
         AdminPanelAction::ListTeamTalkUsers => {
-            handle_admin_tt_list(bot, msg, lang, tx_tt, 0).await?;
+            let page = ListPage::new(0, page_size, "");
+            handle_admin_tt_list(bot, msg, lang, tx_tt, page).await?;
         }
-        AdminPanelAction::ListTeamTalkUsersPage(page) => {
+        AdminPanelAction::ListTeamTalkUsersPage(index, filter) => {
+            let page = ListPage::new(index, page_size, &filter);
             handle_admin_tt_list(bot, msg, lang, tx_tt, page).await?;
         }
+        AdminPanelAction::TeamTalkSearch => {
+            prompt_list_filter(bot, msg, lang, dialogue, AdminList::TeamTalkAccounts).await?;
+        }
         AdminPanelAction::TeamTalkDeletePrompt(username) => {
             handle_admin_tt_delete_prompt(bot, msg, lang, &username).await?;
         }
         AdminPanelAction::TeamTalkDeleteConfirm(username) => {
-            handle_admin_tt_delete_confirm(bot, msg, lang, tx_tt, &username).await?;
+            handle_admin_tt_delete_confirm(bot, msg, db, lang, tx_tt, &username).await?;
         }
-        AdminPanelAction::Cancel => {
-            bot.edit_message_text(msg.chat.id, msg.id, t(lang.as_str(), "admin-panel-title"))
-                .reply_markup(crate::tg_bot::keyboards::admin_panel_keyboard(
-                    &t(lang.as_str(), "btn-delete-user"),
-                    &t(lang.as_str(), "btn-manage-banlist"),
-                    &t(lang.as_str(), "btn-list-tt-accounts"),
-                ))
-                .await?;
+        AdminPanelAction::OnlineUsers => {
+            show_online_users(bot, msg, lang, tx_tt, 0, page_size).await?;
+        }
+        AdminPanelAction::OnlineUsersPage(page) => {
+            show_online_users(bot, msg, lang, tx_tt, page, page_size).await?;
+        }
+        AdminPanelAction::OnlineKickPrompt(user_id) => {
+            prompt_online_action(bot, msg, lang, OnlineAction::Kick, user_id).await?;
+        }
+        AdminPanelAction::OnlineKick(user_id) => {
+            apply_online_action(bot, msg, lang, tx_tt, OnlineAction::Kick, user_id).await?;
+        }
+        AdminPanelAction::OnlineBanPrompt(user_id) => {
+            prompt_online_action(bot, msg, lang, OnlineAction::Ban, user_id).await?;
+        }
+        AdminPanelAction::OnlineBan(user_id) => {
+            apply_online_action(bot, msg, lang, tx_tt, OnlineAction::Ban, user_id).await?;
+        }
+        AdminPanelAction::OnlineMessage(user_id) => {
+            prompt_online_message(bot, msg, lang, dialogue, user_id).await?;
+        }
+        AdminPanelAction::TeamTalkBroadcast => {
+            prompt_tt_broadcast(bot, msg, lang, dialogue).await?;
         }
+        AdminPanelAction::TelegramBroadcast => {
+            prompt_tg_broadcast(bot, msg, lang, dialogue).await?;
+        }
+        AdminPanelAction::TelegramBroadcastConfirm => {
+            start_tg_broadcast(bot, msg, db, lang, dialogue).await?;
+        }
+        AdminPanelAction::Invites(action) => {
+            handle_invite_action(bot, msg, db, lang, page_size, action).await?;
+        }
+        AdminPanelAction::Pending(action) => {
+            handle_pending_action(bot, msg, db, config, action).await?;
+        }
+        AdminPanelAction::Export => {
+            super::export::send_export(bot, msg, db, lang, config.get_temp_dir()).await?;
+        }
+        AdminPanelAction::Search => super::search::prompt_search(bot, msg, lang, dialogue).await?,
+        AdminPanelAction::Cancel => back_to_panel(bot, msg, lang, dialogue).await?,
     }
     Ok(())
 }
 
+/// Turn the message back into the admin panel.
+async fn back_to_panel(
+    bot: &Bot,
+    msg: &Message,
+    lang: &LanguageCode,
+    dialogue: &MyDialogue,
+) -> HandlerResult {
+    bot.edit_message_text(msg.chat.id, msg.id, t(lang.as_str(), "admin-panel-title"))
+        .reply_markup(panel_keyboard(lang))
+        .await?;
+    dialogue.update(State::AdminPanel).await?;
+    Ok(())
+}
+
+async fn prompt_manual_ban(
+    bot: &Bot,
+    msg: &Message,
+    lang: &LanguageCode,
+    dialogue: &MyDialogue,
+) -> HandlerResult {
+    bot.send_message(msg.chat.id, t(lang.as_str(), "admin-ban-prompt"))
+        .await?;
+    dialogue.update(State::AwaitingManualBanInput).await?;
+    Ok(())
+}
+
 async fn show_admin_delete_users(
     bot: &Bot,
     msg: &Message,
     db: &Database,
     lang: &LanguageCode,
     page: usize,
+    page_size: usize,
 ) -> HandlerResult {
     let users = db.get_all_registrations().await?;
     if users.is_empty() {
@@ -741,10 +1205,10 @@ async fn show_admin_delete_users(
             .await?;
     } else {
         let user_list: Vec<(TelegramId, String)> = users
-            .into_iter()
-            .map(|u| (u.telegram_id, u.teamtalk_username))
+            .iter()
+            .map(|u| (u.telegram_id, registration_label(lang, u)))
             .collect();
-        let (page_items, total_pages, page_index) = paginate(&user_list, page, ADMIN_PAGE_SIZE);
+        let (page_items, total_pages, page_index) = paginate(&user_list, page, page_size);
         let prev_label = t(lang.as_str(), "btn-prev-page");
         let next_label = t(lang.as_str(), "btn-next-page");
         let nav_row = crate::tg_bot::keyboards::pagination_row(
@@ -783,111 +1247,319 @@ async fn show_admin_delete_users(
     Ok(())
 }
 
-async fn handle_admin_delete_confirm(
+/// Username followed by whatever is known of the nickname, registration date
+/// and origin, e.g. `alice (Alice, 2026-03-01, self-service)`.
+fn registration_label(lang: &LanguageCode, reg: &TelegramRegistration) -> String {
+    let details: Vec<String> = [
+        reg.nickname
+            .clone()
+            .filter(|nickname| *nickname != reg.teamtalk_username),
+        reg.created_at
+            .map(|created_at| created_at.format("%Y-%m-%d").to_string()),
+        reg.origin()
+            .map(|origin| t(lang.as_str(), origin.label_key())),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if details.is_empty() {
+        reg.teamtalk_username.clone()
+    } else {
+        format!("{} ({})", reg.teamtalk_username, details.join(", "))
+    }
+}
+
+pub(super) async fn handle_admin_delete_confirm(
     bot: &Bot,
-    msg: &Message,
+    origin: &DecisionOrigin<'_>,
     db: &Database,
+    config: &AppConfig,
     lang: &LanguageCode,
-    chat_id: i64,
     target_id: i64,
+    reason: Option<&str>,
 ) -> HandlerResult {
-    let tg_id = TelegramId::new(target_id);
-    let reg = db.get_registration_by_id(tg_id).await?;
-    if db.delete_registration(tg_id).await? {
-        let tt_user = reg.map_or_else(|| "Unknown".to_string(), |r| r.teamtalk_username);
-        db.ban_user(
-            tg_id,
-            Some(&tt_user),
-            Some(TelegramId::new(chat_id)),
-            Some("Deleted via admin panel"),
-        )
+    let admin_id = i64::try_from(origin.admin.id.0)?;
+    let target_lang = registered_lang(db, TelegramId::new(target_id), lang).await;
+    let reg = db
+        .get_registration_by_id(TelegramId::new(target_id))
         .await?;
-        let args = HashMap::from([("tg_id".to_string(), target_id.to_string())]);
+    let deleted = admin_service::delete_registration_and_ban(
+        db,
+        TelegramId::new(target_id),
+        Some(TelegramId::new(admin_id)),
+        reason.unwrap_or("Deleted via admin panel"),
+    )
+    .await?;
+    let args = HashMap::from([("tg_id".to_string(), target_id.to_string())]);
+    if deleted {
+        let actor = Actor::Admin(TelegramId::new(admin_id));
+        audit::record(db, actor, AuditAction::Ban, &target_id.to_string(), reason).await;
+        notify_banned_user(bot, &target_lang, TelegramId::new(target_id), reason).await;
         bot.edit_message_text(
-            msg.chat.id,
-            msg.id,
+            origin.chat_id,
+            origin.message_id,
             t_args(lang.as_str(), "admin-user-deleted", &args),
         )
         .await?;
+        if let Some(reg) = &reg {
+            offer_teamtalk_delete(bot, origin.chat_id, config, lang, reg).await?;
+        }
+    } else {
+        bot.edit_message_text(
+            origin.chat_id,
+            origin.message_id,
+            t_args(lang.as_str(), "admin-user-delete-missing", &args),
+        )
+        .await?;
     }
     Ok(())
 }
 
+/// Ask whether a banned user's linked `TeamTalk` account should be deleted
+/// too, when `ban_propagation = "ask"`. The buttons reuse the
+/// `TeamTalk` account delete confirmation, which acts on the top-level
+/// server, so accounts on other servers are only pointed out.
+async fn offer_teamtalk_delete(
+    bot: &Bot,
+    chat_id: ChatId,
+    config: &AppConfig,
+    lang: &LanguageCode,
+    reg: &TelegramRegistration,
+) -> HandlerResult {
+    if config.telegram.ban_propagation != BanPropagation::Ask {
+        return Ok(());
+    }
+    let tt_username = &reg.teamtalk_username;
+    let mut args = HashMap::from([("tt_username".to_string(), tt_username.clone())]);
+    if reg.server_id != DEFAULT_SERVER_ID {
+        args.insert("server".to_string(), reg.server_id.clone());
+        bot.send_message(
+            chat_id,
+            t_args(lang.as_str(), "admin-ban-propagate-other-server", &args),
+        )
+        .await?;
+        return Ok(());
+    }
+    bot.send_message(
+        chat_id,
+        t_args(lang.as_str(), "admin-ban-propagate-prompt", &args),
+    )
+    .reply_markup(crate::tg_bot::keyboards::confirm_keyboard(
+        &t(lang.as_str(), "btn-confirm-delete"),
+        &t(lang.as_str(), "btn-cancel"),
+        &format!("tt_del_{tt_username}"),
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Language a registered user chose, or `fallback` when none is stored.
+async fn registered_lang(
+    db: &Database,
+    tg_id: TelegramId,
+    fallback: &LanguageCode,
+) -> LanguageCode {
+    match db.get_registration_language(tg_id).await {
+        Ok(Some(stored)) => LanguageCode::parse_or_default(&stored),
+        _ => fallback.clone(),
+    }
+}
+
+/// Tell a user they were banned; failures (e.g. the bot is blocked) are only logged.
+async fn notify_banned_user(
+    bot: &Bot,
+    lang: &LanguageCode,
+    tg_id: TelegramId,
+    reason: Option<&str>,
+) {
+    let text = with_reason(
+        lang.as_str(),
+        t(lang.as_str(), "user-banned-notice"),
+        reason,
+    );
+    let chat_id = ChatId(tg_id.as_i64());
+    if let Err(e) = throttle::send(chat_id, bot.send_message(chat_id, text)).await {
+        warn!(tg_id = %tg_id, error = %e, "Failed to notify banned user");
+    }
+}
+
 async fn show_admin_banlist(
     bot: &Bot,
     msg: &Message,
     db: &Database,
     lang: &LanguageCode,
-    page: usize,
+    page: ListPage<'_>,
 ) -> HandlerResult {
-    let banned = db.get_all_banned_users().await?;
-    if banned.is_empty() {
-        bot.edit_message_text(msg.chat.id, msg.id, t(lang.as_str(), "admin-banlist-empty"))
+    let (text, keyboard) = banlist_view(db, lang, page).await?;
+    let edit = bot.edit_message_text(msg.chat.id, msg.id, text);
+    let result = match keyboard {
+        Some(keyboard) => edit.reply_markup(keyboard).await,
+        None => edit.await,
+    };
+    if result.is_err() {
+        bot.send_message(msg.chat.id, t(lang.as_str(), "admin-action-refresh-fail"))
             .await?;
-        return Ok(());
     }
+    Ok(())
+}
 
-    let mut lines = Vec::new();
-    lines.push(t(lang.as_str(), "admin-banlist-title"));
-    let list: Vec<(TelegramId, String, String)> = banned
-        .into_iter()
-        .map(|b| {
-            let tt_user = b.teamtalk_username.unwrap_or_else(|| "N/A".to_string());
-            let reason = b.reason.unwrap_or_else(|| "N/A".to_string());
-            (b.telegram_id, tt_user, reason)
-        })
-        .collect();
-    let (page_items, total_pages, page_index) = paginate(&list, page, ADMIN_PAGE_SIZE);
-    for (tg_id, tt_user, reason) in &page_items {
-        lines.push(format!(
-            "TG ID: {tg_id} - TT User: {tt_user} (Reason: {reason})"
-        ));
+/// Page of an admin list, optionally narrowed to entries starting with `filter`.
+#[derive(Clone, Copy)]
+struct ListPage<'a> {
+    index: usize,
+    size: usize,
+    filter: &'a str,
+}
+
+impl<'a> ListPage<'a> {
+    const fn new(index: usize, size: usize, filter: &'a str) -> Self {
+        Self {
+            index,
+            size,
+            filter,
+        }
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        value
+            .to_lowercase()
+            .starts_with(&self.filter.to_lowercase())
+    }
+
+    /// Callback data for another page, keeping the filter.
+    fn callback(&self, prefix: &str, index: usize) -> String {
+        if self.filter.is_empty() {
+            format!("{prefix}{index}")
+        } else {
+            format!("{prefix}{index}_{}", self.filter)
+        }
+    }
+}
+
+/// Split `"{page}"` or `"{page}_{filter}"` from a pagination callback.
+fn parse_page_callback(data: &str) -> Option<(usize, String)> {
+    let (page, filter) = data.split_once('_').unwrap_or((data, ""));
+    Some((page.parse().ok()?, filter.to_string()))
+}
+
+/// Trim a search prefix so it still fits into callback data.
+fn clamp_filter(filter: &str) -> String {
+    let mut end = filter.len().min(MAX_FILTER_BYTES);
+    while !filter.is_char_boundary(end) {
+        end -= 1;
     }
-    if total_pages > 1 {
+    filter[..end].to_string()
+}
+
+fn list_header(lang: &LanguageCode, title_key: &str, filter: &str) -> Vec<String> {
+    let mut lines = vec![t(lang.as_str(), title_key)];
+    if !filter.is_empty() {
         lines.push(t_args(
+            lang.as_str(),
+            "admin-list-filter",
+            &HashMap::from([("filter".to_string(), filter.to_string())]),
+        ));
+    }
+    lines
+}
+
+fn page_footer(lang: &LanguageCode, page_index: usize, total_pages: usize) -> Option<String> {
+    (total_pages > 1).then(|| {
+        t_args(
             lang.as_str(),
             "admin-list-page",
             &HashMap::from([
                 ("page".to_string(), (page_index + 1).to_string()),
                 ("pages".to_string(), total_pages.to_string()),
             ]),
+        )
+    })
+}
+
+fn search_row(
+    lang: &LanguageCode,
+    search_cb: &str,
+    reset_cb: &str,
+    filter: &str,
+) -> Vec<InlineKeyboardButton> {
+    let mut row = vec![InlineKeyboardButton::callback(
+        t(lang.as_str(), "btn-list-search"),
+        search_cb,
+    )];
+    if !filter.is_empty() {
+        row.push(InlineKeyboardButton::callback(
+            t(lang.as_str(), "btn-list-show-all"),
+            reset_cb,
         ));
     }
-    let text = lines.join("\n");
-    let prev_label = t(lang.as_str(), "btn-prev-page");
-    let next_label = t(lang.as_str(), "btn-next-page");
-    let nav_row = crate::tg_bot::keyboards::pagination_row(
-        &prev_label,
-        &next_label,
-        if page_index > 0 {
-            Some(format!("admin_banlist_page_{}", page_index - 1))
-        } else {
-            None
-        },
-        if page_index + 1 < total_pages {
-            Some(format!("admin_banlist_page_{}", page_index + 1))
+    row
+}
+
+async fn banlist_view(
+    db: &Database,
+    lang: &LanguageCode,
+    page: ListPage<'_>,
+) -> anyhow::Result<(String, Option<InlineKeyboardMarkup>)> {
+    let banned = db.get_all_banned_users().await?;
+    if banned.is_empty() {
+        return Ok((t(lang.as_str(), "admin-banlist-empty"), None));
+    }
+
+    let list: Vec<(TelegramId, String, String)> = banned
+        .into_iter()
+        .map(|b| {
+            let tt_user = b
+                .teamtalk_username
+                .unwrap_or_else(|| t(lang.as_str(), "value-not-available"));
+            let reason = b
+                .reason
+                .unwrap_or_else(|| t(lang.as_str(), "value-not-available"));
+            (b.telegram_id, tt_user, reason)
+        })
+        .filter(|(tg_id, tt_user, _)| page.matches(&tg_id.to_string()) || page.matches(tt_user))
+        .collect();
+    let mut lines = list_header(lang, "admin-banlist-title", page.filter);
+    if list.is_empty() {
+        lines.push(t(lang.as_str(), "admin-list-no-matches"));
+    }
+    let (page_items, total_pages, page_index) = paginate(&list, page.index, page.size);
+    for (tg_id, tt_user, reason) in &page_items {
+        let reason: String = if reason.chars().count() > MAX_REASON_CHARS {
+            reason.chars().take(MAX_REASON_CHARS).chain(['…']).collect()
         } else {
-            None
-        },
-    );
-    if bot
-        .edit_message_text(msg.chat.id, msg.id, text)
-        .reply_markup(crate::tg_bot::keyboards::admin_banlist_keyboard(
-            page_items
-                .iter()
-                .map(|(tg_id, _, reason)| (*tg_id, reason.clone()))
-                .collect(),
-            &t(lang.as_str(), "btn-unban"),
-            &t(lang.as_str(), "btn-add-ban-manual"),
-            nav_row,
-        ))
-        .await
-        .is_err()
-    {
-        bot.send_message(msg.chat.id, t(lang.as_str(), "admin-action-refresh-fail"))
-            .await?;
+            reason.clone()
+        };
+        let args = HashMap::from([
+            ("tg_id".to_string(), tg_id.to_string()),
+            ("tt_user".to_string(), tt_user.clone()),
+            ("reason".to_string(), reason),
+        ]);
+        lines.push(t_args(lang.as_str(), "admin-banlist-entry", &args));
     }
-    Ok(())
+    lines.extend(page_footer(lang, page_index, total_pages));
+    let nav_row = crate::tg_bot::keyboards::pagination_row(
+        &t(lang.as_str(), "btn-prev-page"),
+        &t(lang.as_str(), "btn-next-page"),
+        (page_index > 0).then(|| page.callback("admin_banlist_page_", page_index - 1)),
+        (page_index + 1 < total_pages)
+            .then(|| page.callback("admin_banlist_page_", page_index + 1)),
+    );
+    let keyboard = crate::tg_bot::keyboards::admin_banlist_keyboard(
+        page_items
+            .iter()
+            .map(|(tg_id, _, reason)| (*tg_id, reason.clone()))
+            .collect(),
+        &t(lang.as_str(), "btn-unban"),
+        &t(lang.as_str(), "btn-add-ban-manual"),
+        search_row(
+            lang,
+            "admin_banlist_search",
+            "admin_banlist_view",
+            page.filter,
+        ),
+        nav_row,
+    );
+    Ok((lines.join("\n"), Some(keyboard)))
 }
 
 async fn handle_admin_unban(
@@ -908,6 +1580,8 @@ async fn handle_admin_unban(
     }
     let args = HashMap::from([("tg_id".to_string(), target_id.to_string())]);
     let edit_result = if db.unban_user(TelegramId::new(target_id)).await? {
+        let actor = Actor::Admin(TelegramId::new(msg.chat.id.0));
+        audit::record(db, actor, AuditAction::Unban, &target_id.to_string(), None).await;
         bot.edit_message_text(
             msg.chat.id,
             msg.id,
@@ -929,84 +1603,152 @@ async fn handle_admin_unban(
     Ok(())
 }
 
-async fn handle_admin_tt_list(
+/// Lift the username and IP bans an automatic ban stored.
+async fn handle_admin_undo_bans(
     bot: &Bot,
     msg: &Message,
+    db: &Database,
     lang: &LanguageCode,
-    tx_tt: &Sender<TTWorkerCommand>,
-    page: usize,
+    ids: &[i64],
 ) -> HandlerResult {
-    let (tx, rx) = tokio::sync::oneshot::channel();
-    if let Err(e) = tx_tt.send(TTWorkerCommand::GetAllUsers { resp: tx }) {
-        warn!(error = %e, "Failed to enqueue TeamTalk users list request");
-        bot.edit_message_text(msg.chat.id, msg.id, t(lang.as_str(), "admin-tt-list-error"))
+    let actor = Actor::Admin(TelegramId::new(msg.chat.id.0));
+    let mut lifted = Vec::new();
+    for &id in ids {
+        if let Some(target) = bans::lift(db, id, actor).await? {
+            lifted.push(target);
+        }
+    }
+    let text = if lifted.is_empty() {
+        t(lang.as_str(), "admin-bans-undo-none")
+    } else {
+        let args = HashMap::from([("bans".to_string(), lifted.join(", "))]);
+        t_args(lang.as_str(), "admin-bans-undone", &args)
+    };
+    if bot
+        .edit_message_text(msg.chat.id, msg.id, text)
+        .await
+        .is_err()
+    {
+        bot.send_message(msg.chat.id, t(lang.as_str(), "admin-action-refresh-fail"))
             .await?;
-        return Ok(());
     }
-    match rx.await {
-        Ok(users) => {
-            if users.is_empty() {
-                bot.edit_message_text(
-                    msg.chat.id,
-                    msg.id,
-                    t(lang.as_str(), "admin-tt-no-accounts"),
-                )
-                .await?;
-            } else {
-                let mut lines = Vec::new();
-                lines.push(t(lang.as_str(), "admin-tt-list-title"));
-                let (page_items, total_pages, page_index) = paginate(&users, page, ADMIN_PAGE_SIZE);
-                for u in &page_items {
-                    lines.push(format!("- {u}"));
-                }
-                if total_pages > 1 {
-                    lines.push(t_args(
-                        lang.as_str(),
-                        "admin-list-page",
-                        &HashMap::from([
-                            ("page".to_string(), (page_index + 1).to_string()),
-                            ("pages".to_string(), total_pages.to_string()),
-                        ]),
-                    ));
-                }
-                let text = lines.join("\n");
-                let prev_label = t(lang.as_str(), "btn-prev-page");
-                let next_label = t(lang.as_str(), "btn-next-page");
-                let nav_row = crate::tg_bot::keyboards::pagination_row(
-                    &prev_label,
-                    &next_label,
-                    if page_index > 0 {
-                        Some(format!("admin_tt_list_page_{}", page_index - 1))
-                    } else {
-                        None
-                    },
-                    if page_index + 1 < total_pages {
-                        Some(format!("admin_tt_list_page_{}", page_index + 1))
-                    } else {
-                        None
-                    },
-                );
-                bot.edit_message_text(msg.chat.id, msg.id, text)
-                    .reply_markup(crate::tg_bot::keyboards::admin_tt_accounts_keyboard(
-                        page_items,
-                        &t(lang.as_str(), "btn-delete-from-tt"),
-                        nav_row,
-                    ))
-                    .await?;
-            }
-        }
+    Ok(())
+}
+
+async fn handle_admin_tt_list(
+    bot: &Bot,
+    msg: &Message,
+    lang: &LanguageCode,
+    tx_tt: &TTWorkerHandle,
+    page: ListPage<'_>,
+) -> HandlerResult {
+    let (text, keyboard) = tt_accounts_view(lang, tx_tt, page).await;
+    let edit = bot.edit_message_text(msg.chat.id, msg.id, text);
+    match keyboard {
+        Some(keyboard) => edit.reply_markup(keyboard).await?,
+        None => edit.await?,
+    };
+    Ok(())
+}
+
+async fn tt_accounts_view(
+    lang: &LanguageCode,
+    tx_tt: &TTWorkerHandle,
+    page: ListPage<'_>,
+) -> (String, Option<InlineKeyboardMarkup>) {
+    let users = match tx_tt
+        .request(|resp| TTWorkerCommand::GetAllUsers { resp })
+        .await
+    {
+        Ok(users) => users,
         Err(e) => {
-            warn!(error = %e, "Failed to receive TeamTalk users list");
-            bot.edit_message_text(msg.chat.id, msg.id, t(lang.as_str(), "admin-tt-list-error"))
-                .await?;
+            warn!(error = %e, "TeamTalk users list request failed");
+            return (t(lang.as_str(), "admin-tt-list-error"), None);
         }
+    };
+    if users.is_empty() {
+        return (t(lang.as_str(), "admin-tt-no-accounts"), None);
+    }
+
+    let users: Vec<String> = users.into_iter().filter(|u| page.matches(u)).collect();
+    let mut lines = list_header(lang, "admin-tt-list-title", page.filter);
+    if users.is_empty() {
+        lines.push(t(lang.as_str(), "admin-list-no-matches"));
     }
+    let (page_items, total_pages, page_index) = paginate(&users, page.index, page.size);
+    for u in &page_items {
+        lines.push(format!("- {u}"));
+    }
+    lines.extend(page_footer(lang, page_index, total_pages));
+    let nav_row = crate::tg_bot::keyboards::pagination_row(
+        &t(lang.as_str(), "btn-prev-page"),
+        &t(lang.as_str(), "btn-next-page"),
+        (page_index > 0).then(|| page.callback("admin_tt_list_page_", page_index - 1)),
+        (page_index + 1 < total_pages)
+            .then(|| page.callback("admin_tt_list_page_", page_index + 1)),
+    );
+    let keyboard = crate::tg_bot::keyboards::admin_tt_accounts_keyboard(
+        page_items,
+        &t(lang.as_str(), "btn-delete-from-tt"),
+        search_row(lang, "admin_tt_search", "admin_tt_list", page.filter),
+        nav_row,
+    );
+    (lines.join("\n"), Some(keyboard))
+}
+
+/// Ask the admin for a search prefix for a list.
+async fn prompt_list_filter(
+    bot: &Bot,
+    msg: &Message,
+    lang: &LanguageCode,
+    dialogue: &MyDialogue,
+    list: AdminList,
+) -> HandlerResult {
+    bot.send_message(msg.chat.id, t(lang.as_str(), "admin-list-search-prompt"))
+        .await?;
+    dialogue.update(State::AwaitingListFilter { list }).await?;
     Ok(())
 }
 
-const ADMIN_PAGE_SIZE: usize = 20;
+/// Show the banlist or `TeamTalk` accounts narrowed to the entered prefix.
+pub async fn admin_list_filter_input(
+    bot: Bot,
+    msg: Message,
+    db: Database,
+    config: Arc<AppConfig>,
+    dialogue: MyDialogue,
+    tx_tt: TTWorkerHandle,
+) -> HandlerResult {
+    let Some(State::AwaitingListFilter { list }) = (match dialogue.get().await {
+        Ok(state) => state,
+        Err(e) => {
+            warn!(error = %e, "Failed to read dialogue state (AwaitingListFilter)");
+            return Ok(());
+        }
+    }) else {
+        return Ok(());
+    };
+    let lang = &config.telegram.bot_admin_lang;
+    let filter = clamp_filter(msg.text().unwrap_or("").trim());
+    let page = ListPage::new(0, config.telegram.admin_page_size, &filter);
+    let (text, keyboard) = match list {
+        AdminList::Banlist => banlist_view(&db, lang, page).await?,
+        AdminList::TeamTalkAccounts => tt_accounts_view(lang, &tx_tt, page).await,
+    };
+    let send = bot.send_message(msg.chat.id, text);
+    match keyboard {
+        Some(keyboard) => send.reply_markup(keyboard).await?,
+        None => send.await?,
+    };
+    dialogue.update(State::AdminPanel).await?;
+    Ok(())
+}
 
-fn paginate<T: Clone>(items: &[T], page: usize, page_size: usize) -> (Vec<T>, usize, usize) {
+pub(super) fn paginate<T: Clone>(
+    items: &[T],
+    page: usize,
+    page_size: usize,
+) -> (Vec<T>, usize, usize) {
     if items.is_empty() {
         return (Vec::new(), 0, 0);
     }
@@ -1041,8 +1783,9 @@ async fn handle_admin_tt_delete_prompt(
 async fn handle_admin_tt_delete_confirm(
     bot: &Bot,
     msg: &Message,
+    db: &Database,
     lang: &LanguageCode,
-    tx_tt: &Sender<TTWorkerCommand>,
+    tx_tt: &TTWorkerHandle,
     username: &str,
 ) -> HandlerResult {
     let Some(tt_username) = Username::parse(username) else {
@@ -1050,59 +1793,27 @@ async fn handle_admin_tt_delete_confirm(
             .await?;
         return Ok(());
     };
-    let (tx, rx) = tokio::sync::oneshot::channel();
-    if let Err(e) = tx_tt.send(TTWorkerCommand::DeleteUser {
-        username: tt_username,
-        resp: tx,
-    }) {
-        warn!(error = %e, "Failed to enqueue TeamTalk delete user command");
-        let mut args = HashMap::from([("tt_username".to_string(), username.to_string())]);
-        args.insert("error".to_string(), "Dispatcher error".to_string());
-        bot.edit_message_text(
-            msg.chat.id,
-            msg.id,
-            t_args(lang.as_str(), "admin-tt-delete-fail", &args),
-        )
-        .await?;
-        return Ok(());
-    }
-    let args = HashMap::from([("tt_username".to_string(), username.to_string())]);
-    match rx.await {
-        Ok(Ok(true)) => {
-            bot.edit_message_text(
-                msg.chat.id,
-                msg.id,
-                t_args(lang.as_str(), "admin-tt-deleted", &args),
-            )
-            .await?;
-        }
-        Ok(Ok(false)) => {
-            let mut args = args.clone();
-            args.insert(
-                "error".to_string(),
-                "Command indicated failure without a specific error.".to_string(),
-            );
-            bot.edit_message_text(
-                msg.chat.id,
-                msg.id,
-                t_args(lang.as_str(), "admin-tt-delete-fail", &args),
+    let mut args = HashMap::from([("tt_username".to_string(), username.to_string())]);
+    match admin_service::delete_teamtalk_account(tx_tt, tt_username).await {
+        Ok(()) => {
+            let actor = Actor::Admin(TelegramId::new(msg.chat.id.0));
+            audit::record(
+                db,
+                actor,
+                AuditAction::DeleteTeamTalkAccount,
+                username,
+                None,
             )
-            .await?;
-        }
-        Ok(Err(err)) => {
-            let mut args = args.clone();
-            args.insert("error".to_string(), err);
+            .await;
             bot.edit_message_text(
                 msg.chat.id,
                 msg.id,
-                t_args(lang.as_str(), "admin-tt-delete-fail", &args),
+                t_args(lang.as_str(), "admin-tt-deleted", &args),
             )
             .await?;
         }
-        Err(e) => {
-            warn!(error = %e, "Failed to receive TT delete response");
-            let mut args = args.clone();
-            args.insert("error".to_string(), "Unknown error".to_string());
+        Err(err) => {
+            args.insert("error".to_string(), tt_error_text(lang.as_str(), &err));
             bot.edit_message_text(
                 msg.chat.id,
                 msg.id,
@@ -0,0 +1,280 @@
+use super::offline_queue::notify_admin_queued;
+use super::registration::{notify_db_sync_error, send_registration_assets};
+use super::{HandlerResult, tt_error_text};
+use crate::config::AppConfig;
+use crate::db::Database;
+use crate::domain::{Nickname, Password, Username};
+use crate::i18n::{t, t_args};
+use crate::services::audit::{self, Actor, AuditAction};
+use crate::services::registration;
+use crate::tg_bot::notify::AdminChats;
+use crate::tt::TTWorkerHandle;
+use crate::types::{
+    NotificationKind, RegistrationSource, SourceInfo, TTAccountType, TTWorkerCommand, TelegramId,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use tracing::{error, warn};
+
+/// Arguments of `/register <username> <password> [nickname] [--to <chat_id>] [--profile <name>]`.
+struct RegisterArgs {
+    username: Username,
+    password: Password,
+    nickname: Nickname,
+    forward_to: Option<ChatId>,
+    rights_profile: Option<String>,
+}
+
+impl RegisterArgs {
+    fn parse(input: &str) -> Option<Self> {
+        let mut words: Vec<&str> = input.split_whitespace().collect();
+        let mut forward_to = None;
+        if let Some(pos) = words.iter().position(|w| *w == "--to") {
+            let chat = words.get(pos + 1)?.parse::<i64>().ok()?;
+            forward_to = Some(ChatId(chat));
+            words.drain(pos..=pos + 1);
+        }
+        let mut rights_profile = None;
+        if let Some(pos) = words.iter().position(|w| *w == "--profile") {
+            rights_profile = Some((*words.get(pos + 1)?).to_string());
+            words.drain(pos..=pos + 1);
+        }
+        let [username, password, nickname @ ..] = words.as_slice() else {
+            return None;
+        };
+        let nickname = if nickname.is_empty() {
+            Nickname::parse(username)?
+        } else {
+            Nickname::parse(&nickname.join(" "))?
+        };
+        Some(Self {
+            username: Username::parse(username)?,
+            password: Password::parse(password)?,
+            nickname,
+            forward_to,
+            rights_profile,
+        })
+    }
+
+    /// Audit log details: where the files went and the rights profile, if any.
+    fn audit_details(&self) -> Option<String> {
+        let details = [
+            self.forward_to
+                .map(|chat| format!("Files sent to chat {chat}")),
+            self.rights_profile
+                .as_ref()
+                .map(|profile| format!("rights profile: {profile}")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("; ");
+        (!details.is_empty()).then_some(details)
+    }
+}
+
+/// Whether `username` is free on the server; otherwise the admin is told why
+/// not.
+async fn username_available(
+    bot: &Bot,
+    chat_id: ChatId,
+    lang: &str,
+    tx_tt: &TTWorkerHandle,
+    config: &AppConfig,
+    username: &Username,
+) -> Result<bool, teloxide::RequestError> {
+    let exists = tx_tt
+        .request(|resp| TTWorkerCommand::CheckUserExists {
+            username: username.clone(),
+            matching: config.username_policy.matching(),
+            resp,
+        })
+        .await;
+    let key = match exists {
+        Ok(false) => return Ok(true),
+        Ok(true) => "username-taken",
+        Err(e) => {
+            error!(error = %e, "Username check request failed");
+            "username-check-error"
+        }
+    };
+    bot.send_message(chat_id, t(lang, key)).await?;
+    Ok(false)
+}
+
+/// Create a `TeamTalk` account in one step (`/register`), bypassing the dialogue.
+pub async fn admin_register(
+    bot: Bot,
+    msg: Message,
+    db: Database,
+    config: Arc<AppConfig>,
+    tx_tt: TTWorkerHandle,
+    args: String,
+) -> HandlerResult {
+    let admin_id = TelegramId::new(msg.chat.id.0);
+    if !config.telegram.admin_ids.contains(&admin_id) {
+        return Ok(());
+    }
+    let lang = config.telegram.bot_admin_lang.as_str();
+    let Some(args) = RegisterArgs::parse(&args) else {
+        bot.send_message(msg.chat.id, t(lang, "admin-register-usage"))
+            .await?;
+        return Ok(());
+    };
+    if let Some(profile) = &args.rights_profile
+        && !config.rights_profiles.contains_key(profile)
+    {
+        let text_args = HashMap::from([("profile".to_string(), profile.clone())]);
+        bot.send_message(
+            msg.chat.id,
+            t_args(lang, "admin-register-unknown-profile", &text_args),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    if !username_available(&bot, msg.chat.id, lang, &tx_tt, &config, &args.username).await? {
+        return Ok(());
+    }
+
+    let result = registration::create_teamtalk_account(registration::CreateAccountParams {
+        username: &args.username,
+        password: &args.password,
+        nickname: &args.nickname,
+        account_type: TTAccountType::Default,
+        source: RegistrationSource::Telegram(admin_id),
+        source_info: Some(SourceInfo::admin(admin_id)),
+        telegram_id: None,
+        language: None,
+        queued_id: None,
+        rights_profile: args.rights_profile.as_deref(),
+        tx_tt,
+        db: &db,
+        config: &config,
+    })
+    .await?;
+    if result.queued {
+        return deliver_queued(&bot, msg.chat.id, lang, &config, &args).await;
+    }
+    if let Some(err) = &result.error {
+        let reason = HashMap::from([("error".to_string(), tt_error_text(lang, err))]);
+        bot.send_message(msg.chat.id, t_args(lang, "admin-register-failed", &reason))
+            .await?;
+        return Ok(());
+    }
+    if let Some(err) = result.db_sync_error {
+        notify_db_sync_error(
+            &bot,
+            &db,
+            &config,
+            msg.chat.id,
+            args.username.as_str(),
+            &err,
+        )
+        .await;
+    }
+    audit::record(
+        &db,
+        Actor::Admin(admin_id),
+        AuditAction::CreateAccount,
+        args.username.as_str(),
+        args.audit_details().as_deref(),
+    )
+    .await;
+
+    let text_args = HashMap::from([
+        ("username".to_string(), args.username.as_str().to_string()),
+        ("admin_id".to_string(), admin_id.to_string()),
+    ]);
+    bot.send_message(
+        msg.chat.id,
+        t_args(lang, "admin-register-success", &text_args),
+    )
+    .await?;
+    AdminChats::new(&config.telegram, &db)
+        .send(
+            &bot,
+            NotificationKind::AccountCreated,
+            &t_args(lang, "admin-register-notify", &text_args),
+            None,
+            Some(msg.chat.id),
+        )
+        .await;
+
+    let Some(assets) = result.assets else {
+        return Ok(());
+    };
+    deliver_assets(&bot, msg.chat.id, lang, &config, &args, &assets).await
+}
+
+/// Hand out the files of a queued account right away; they work once the
+/// account is created.
+async fn deliver_queued(
+    bot: &Bot,
+    admin_chat: ChatId,
+    lang: &str,
+    config: &AppConfig,
+    args: &RegisterArgs,
+) -> HandlerResult {
+    notify_admin_queued(bot, admin_chat, lang, args.username.as_str()).await;
+    let assets = registration::build_assets(
+        config,
+        lang,
+        args.username.as_str(),
+        args.password.as_str(),
+        args.nickname.as_str(),
+    );
+    deliver_assets(bot, admin_chat, lang, config, args, &assets).await
+}
+
+/// Send the connection files to the `--to` chat, falling back to the admin's chat.
+async fn deliver_assets(
+    bot: &Bot,
+    admin_chat: ChatId,
+    lang: &str,
+    config: &AppConfig,
+    args: &RegisterArgs,
+    assets: &registration::RegistrationAssets,
+) -> HandlerResult {
+    let target = args.forward_to.unwrap_or(admin_chat);
+    let sent = send_registration_assets(
+        bot,
+        target,
+        lang,
+        config,
+        args.username.as_str(),
+        args.password.as_str(),
+        assets,
+    )
+    .await;
+    if let Err(e) = sent {
+        warn!(error = %e, chat_id = target.0, "Failed to deliver registration assets");
+        if target != admin_chat {
+            let forward_args = HashMap::from([("chat_id".to_string(), target.0.to_string())]);
+            bot.send_message(
+                admin_chat,
+                t_args(lang, "admin-register-forward-failed", &forward_args),
+            )
+            .await?;
+            send_registration_assets(
+                bot,
+                admin_chat,
+                lang,
+                config,
+                args.username.as_str(),
+                args.password.as_str(),
+                assets,
+            )
+            .await?;
+        }
+    } else if target != admin_chat {
+        let forward_args = HashMap::from([("chat_id".to_string(), target.0.to_string())]);
+        bot.send_message(
+            admin_chat,
+            t_args(lang, "admin-register-forwarded", &forward_args),
+        )
+        .await?;
+    }
+    Ok(())
+}
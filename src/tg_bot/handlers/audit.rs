@@ -0,0 +1,70 @@
+use super::HandlerResult;
+use crate::config::AppConfig;
+use crate::db::Database;
+use crate::i18n::{t, t_args};
+use crate::types::TelegramId;
+use std::collections::HashMap;
+use std::sync::Arc;
+use teloxide::prelude::*;
+
+/// Entries shown per `/audit` page.
+const AUDIT_PAGE_SIZE: i64 = 20;
+
+/// Show the most recent audit log entries (`/audit [page]`).
+pub async fn audit_log(
+    bot: Bot,
+    msg: Message,
+    db: Database,
+    config: Arc<AppConfig>,
+    args: String,
+) -> HandlerResult {
+    if !config
+        .telegram
+        .admin_ids
+        .contains(&TelegramId::new(msg.chat.id.0))
+    {
+        return Ok(());
+    }
+    let lang = config.telegram.bot_admin_lang.as_str();
+    let args = args.trim();
+    let page = if args.is_empty() {
+        Some(1)
+    } else {
+        args.parse::<i64>().ok().filter(|page| *page >= 1)
+    };
+    let Some(page) = page else {
+        bot.send_message(msg.chat.id, t(lang, "admin-audit-usage"))
+            .await?;
+        return Ok(());
+    };
+
+    let entries = db
+        .get_audit_entries(AUDIT_PAGE_SIZE, (page - 1).saturating_mul(AUDIT_PAGE_SIZE))
+        .await?;
+    if entries.is_empty() {
+        bot.send_message(msg.chat.id, t(lang, "admin-audit-empty"))
+            .await?;
+        return Ok(());
+    }
+    let mut lines = vec![t_args(
+        lang,
+        "admin-audit-title",
+        &HashMap::from([("page".to_string(), page.to_string())]),
+    )];
+    for entry in entries {
+        let mut line = format!(
+            "{} {} {} {}",
+            entry.created_at.format("%Y-%m-%d %H:%M"),
+            entry.actor,
+            entry.action,
+            entry.target
+        );
+        if let Some(details) = entry.details {
+            line.push_str(" - ");
+            line.push_str(&details);
+        }
+        lines.push(line);
+    }
+    bot.send_message(msg.chat.id, lines.join("\n")).await?;
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use super::HandlerResult;
+use crate::config::AppConfig;
+use crate::db::Database;
+use crate::i18n::t;
+use crate::services::audit::{self, Actor, AuditAction};
+use crate::services::backup;
+use crate::types::TelegramId;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use teloxide::types::InputFile;
+use tracing::error;
+
+/// Send a fresh compressed database snapshot to the requesting owner (`/backup`).
+pub async fn backup_command(
+    bot: Bot,
+    msg: Message,
+    db: Database,
+    config: Arc<AppConfig>,
+) -> HandlerResult {
+    let caller = TelegramId::new(msg.chat.id.0);
+    if !config.is_owner(caller) {
+        return Ok(());
+    }
+    let lang = config.telegram.bot_admin_lang.as_str();
+    if !db.supports_backup() {
+        bot.send_message(msg.chat.id, t(lang, "admin-backup-unsupported"))
+            .await?;
+        return Ok(());
+    }
+    // The snapshot goes to the temp directory so the periodic cleanup removes it.
+    match backup::create_backup(&db, config.get_temp_dir()).await {
+        Ok(path) => {
+            audit::record(
+                &db,
+                Actor::Admin(caller),
+                AuditAction::Backup,
+                "database",
+                None,
+            )
+            .await;
+            bot.send_document(msg.chat.id, InputFile::file(path))
+                .caption(t(lang, "admin-backup-ready"))
+                .await?;
+        }
+        Err(e) => {
+            error!(error = %e, "Database backup failed");
+            bot.send_message(msg.chat.id, t(lang, "admin-backup-error"))
+                .await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,341 @@
+use super::{HandlerResult, MyDialogue, State, tt_error_text};
+use crate::config::AppConfig;
+use crate::db::Database;
+use crate::i18n::{t, t_args};
+use crate::tg_bot::throttle;
+use crate::tt::TTWorkerHandle;
+use crate::types::{LanguageCode, TTWorkerCommand, TelegramId};
+use std::collections::HashMap;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use teloxide::sugar::request::RequestReplyExt;
+use teloxide::types::MessageId;
+use teloxide::{ApiError, RequestError};
+use tracing::{info, warn};
+
+/// How often the progress message is refreshed.
+const PROGRESS_EVERY: usize = 25;
+/// Failed IDs listed in the summary; the rest are only counted.
+const MAX_LISTED_FAILURES: usize = 30;
+
+/// Send a text message to everyone on the `TeamTalk` server (`/broadcast <text>`).
+pub async fn tt_broadcast_command(
+    bot: Bot,
+    msg: Message,
+    config: Arc<AppConfig>,
+    tx_tt: TTWorkerHandle,
+    text: String,
+) -> HandlerResult {
+    let admin_id = TelegramId::new(msg.chat.id.0);
+    if !config.telegram.admin_ids.contains(&admin_id) {
+        return Ok(());
+    }
+    let lang = &config.telegram.bot_admin_lang;
+    let text = text.trim();
+    if text.is_empty() {
+        bot.send_message(msg.chat.id, t(lang.as_str(), "broadcast-tt-usage"))
+            .await?;
+        return Ok(());
+    }
+    send_tt_broadcast(&bot, msg.chat.id, lang, &tx_tt, admin_id, text).await
+}
+
+/// Ask the admin for the `TeamTalk` broadcast text from the panel.
+pub(super) async fn prompt_tt_broadcast(
+    bot: &Bot,
+    msg: &Message,
+    lang: &LanguageCode,
+    dialogue: &MyDialogue,
+) -> HandlerResult {
+    bot.send_message(msg.chat.id, t(lang.as_str(), "broadcast-tt-prompt"))
+        .await?;
+    dialogue.update(State::AwaitingTeamTalkBroadcast).await?;
+    Ok(())
+}
+
+/// Broadcast the text entered after the panel prompt.
+pub async fn admin_tt_broadcast_input(
+    bot: Bot,
+    msg: Message,
+    config: Arc<AppConfig>,
+    dialogue: MyDialogue,
+    tx_tt: TTWorkerHandle,
+) -> HandlerResult {
+    let text = msg.text().unwrap_or("").trim();
+    if text.is_empty() {
+        return Ok(());
+    }
+    let lang = &config.telegram.bot_admin_lang;
+    let admin_id = TelegramId::new(msg.chat.id.0);
+    send_tt_broadcast(&bot, msg.chat.id, lang, &tx_tt, admin_id, text).await?;
+    dialogue.update(State::AdminPanel).await?;
+    Ok(())
+}
+
+async fn send_tt_broadcast(
+    bot: &Bot,
+    chat_id: ChatId,
+    lang: &LanguageCode,
+    tx_tt: &TTWorkerHandle,
+    admin_id: TelegramId,
+    text: &str,
+) -> HandlerResult {
+    let result = tx_tt
+        .request(|resp| TTWorkerCommand::Broadcast {
+            text: text.to_string(),
+            resp,
+        })
+        .await;
+    let mut args = HashMap::new();
+    let reply = match result {
+        Ok(Ok(true)) => {
+            info!(
+                target: "audit",
+                admin_id = %admin_id,
+                action = "tt_broadcast",
+                text,
+                "Admin broadcast sent to TeamTalk"
+            );
+            t(lang.as_str(), "broadcast-tt-sent")
+        }
+        Ok(Ok(false)) => {
+            args.insert("error".to_string(), "-".to_string());
+            t_args(lang.as_str(), "broadcast-tt-failed", &args)
+        }
+        Ok(Err(err)) => {
+            args.insert("error".to_string(), tt_error_text(lang.as_str(), &err));
+            t_args(lang.as_str(), "broadcast-tt-failed", &args)
+        }
+        Err(e) => {
+            warn!(error = %e, "TeamTalk broadcast request failed");
+            args.insert("error".to_string(), e.to_string());
+            t_args(lang.as_str(), "broadcast-tt-failed", &args)
+        }
+    };
+    bot.send_message(chat_id, reply).await?;
+    Ok(())
+}
+
+/// Ask the admin to compose a message for all registered Telegram users.
+pub(super) async fn prompt_tg_broadcast(
+    bot: &Bot,
+    msg: &Message,
+    lang: &LanguageCode,
+    dialogue: &MyDialogue,
+) -> HandlerResult {
+    bot.send_message(msg.chat.id, t(lang.as_str(), "broadcast-tg-prompt"))
+        .await?;
+    dialogue.update(State::AwaitingTelegramBroadcast).await?;
+    Ok(())
+}
+
+/// Take the composed message and ask for confirmation before sending it.
+pub async fn admin_tg_broadcast_input(
+    bot: Bot,
+    msg: Message,
+    db: Database,
+    config: Arc<AppConfig>,
+    dialogue: MyDialogue,
+) -> HandlerResult {
+    let lang = &config.telegram.bot_admin_lang;
+    let recipients = db.get_all_registrations().await?.len();
+    let args = HashMap::from([("count".to_string(), recipients.to_string())]);
+    bot.send_message(
+        msg.chat.id,
+        t_args(lang.as_str(), "broadcast-tg-confirm", &args),
+    )
+    .reply_to(msg.id)
+    .reply_markup(crate::tg_bot::keyboards::confirm_keyboard(
+        &t(lang.as_str(), "btn-broadcast-send"),
+        &t(lang.as_str(), "btn-cancel"),
+        "tg_broadcast",
+    ))
+    .await?;
+    dialogue
+        .update(State::ConfirmingTelegramBroadcast { message_id: msg.id })
+        .await?;
+    Ok(())
+}
+
+/// Start sending the confirmed message in the background.
+pub(super) async fn start_tg_broadcast(
+    bot: &Bot,
+    msg: &Message,
+    db: &Database,
+    lang: &LanguageCode,
+    dialogue: &MyDialogue,
+) -> HandlerResult {
+    let state = dialogue.get().await?;
+    dialogue.update(State::AdminPanel).await?;
+    let Some(State::ConfirmingTelegramBroadcast { message_id }) = state else {
+        bot.edit_message_text(
+            msg.chat.id,
+            msg.id,
+            t(lang.as_str(), "broadcast-tg-expired"),
+        )
+        .await?;
+        return Ok(());
+    };
+    let recipients: Vec<TelegramId> = db
+        .get_all_registrations()
+        .await?
+        .into_iter()
+        .map(|reg| reg.telegram_id)
+        .collect();
+    info!(
+        target: "audit",
+        admin_id = msg.chat.id.0,
+        action = "tg_broadcast",
+        recipients = recipients.len(),
+        "Admin broadcast to Telegram users started"
+    );
+    bot.edit_message_text(
+        msg.chat.id,
+        msg.id,
+        progress_text(lang, 0, recipients.len()),
+    )
+    .await?;
+    let job = BroadcastJob {
+        bot: bot.clone(),
+        lang: lang.clone(),
+        admin_chat: msg.chat.id,
+        progress_message: msg.id,
+        source_message: message_id,
+        recipients,
+    };
+    tokio::spawn(job.run());
+    Ok(())
+}
+
+#[derive(Default)]
+struct BroadcastReport {
+    sent: usize,
+    blocked: Vec<TelegramId>,
+    deactivated: Vec<TelegramId>,
+    not_found: Vec<TelegramId>,
+    other: Vec<TelegramId>,
+}
+
+struct BroadcastJob {
+    bot: Bot,
+    lang: LanguageCode,
+    admin_chat: ChatId,
+    progress_message: MessageId,
+    source_message: MessageId,
+    recipients: Vec<TelegramId>,
+}
+
+impl BroadcastJob {
+    async fn run(self) {
+        let total = self.recipients.len();
+        let mut report = BroadcastReport::default();
+        for (index, tg_id) in self.recipients.iter().enumerate() {
+            match self.deliver(*tg_id).await {
+                Ok(()) => report.sent += 1,
+                Err(RequestError::Api(ApiError::BotBlocked | ApiError::BotKicked)) => {
+                    report.blocked.push(*tg_id);
+                }
+                Err(RequestError::Api(ApiError::UserDeactivated)) => {
+                    report.deactivated.push(*tg_id);
+                }
+                Err(RequestError::Api(ApiError::ChatNotFound | ApiError::UserNotFound)) => {
+                    report.not_found.push(*tg_id);
+                }
+                Err(e) => {
+                    warn!(tg_id = %tg_id, error = %e, "Broadcast delivery failed");
+                    report.other.push(*tg_id);
+                }
+            }
+            let done = index + 1;
+            if done % PROGRESS_EVERY == 0 && done < total {
+                let text = progress_text(&self.lang, done, total);
+                if let Err(e) = self
+                    .bot
+                    .edit_message_text(self.admin_chat, self.progress_message, text)
+                    .await
+                {
+                    warn!(error = %e, "Failed to update broadcast progress");
+                }
+            }
+        }
+        info!(
+            sent = report.sent,
+            blocked = report.blocked.len(),
+            deactivated = report.deactivated.len(),
+            not_found = report.not_found.len(),
+            other = report.other.len(),
+            "Telegram broadcast finished"
+        );
+        let summary = summary_text(&self.lang, total, &report);
+        if let Err(e) = self
+            .bot
+            .edit_message_text(self.admin_chat, self.progress_message, summary)
+            .await
+        {
+            warn!(error = %e, "Failed to send broadcast summary");
+        }
+    }
+
+    /// Copy the admin's message to one user.
+    async fn deliver(&self, tg_id: TelegramId) -> Result<(), RequestError> {
+        let chat = ChatId(tg_id.as_i64());
+        let request = self
+            .bot
+            .copy_message(chat, self.admin_chat, self.source_message);
+        throttle::send(chat, request).await.map(|_| ())
+    }
+}
+
+fn progress_text(lang: &LanguageCode, done: usize, total: usize) -> String {
+    t_args(
+        lang.as_str(),
+        "broadcast-tg-progress",
+        &HashMap::from([
+            ("done".to_string(), done.to_string()),
+            ("total".to_string(), total.to_string()),
+        ]),
+    )
+}
+
+fn summary_text(lang: &LanguageCode, total: usize, report: &BroadcastReport) -> String {
+    let failed = report.blocked.len()
+        + report.deactivated.len()
+        + report.not_found.len()
+        + report.other.len();
+    let mut lines = vec![t_args(
+        lang.as_str(),
+        "broadcast-tg-done",
+        &HashMap::from([
+            ("sent".to_string(), report.sent.to_string()),
+            ("total".to_string(), total.to_string()),
+            ("failed".to_string(), failed.to_string()),
+        ]),
+    )];
+    for (key, ids) in [
+        ("broadcast-tg-failed-blocked", &report.blocked),
+        ("broadcast-tg-failed-deactivated", &report.deactivated),
+        ("broadcast-tg-failed-not-found", &report.not_found),
+        ("broadcast-tg-failed-other", &report.other),
+    ] {
+        if ids.is_empty() {
+            continue;
+        }
+        let mut listed: Vec<String> = ids
+            .iter()
+            .take(MAX_LISTED_FAILURES)
+            .map(ToString::to_string)
+            .collect();
+        if ids.len() > MAX_LISTED_FAILURES {
+            listed.push("…".to_string());
+        }
+        lines.push(t_args(
+            lang.as_str(),
+            key,
+            &HashMap::from([
+                ("count".to_string(), ids.len().to_string()),
+                ("ids".to_string(), listed.join(", ")),
+            ]),
+        ));
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,35 @@
+use super::HandlerResult;
+use crate::db::Database;
+use crate::i18n::t;
+use crate::services::export;
+use crate::types::LanguageCode;
+use std::path::Path;
+use teloxide::prelude::*;
+use teloxide::types::InputFile;
+use tracing::error;
+
+/// Send CSV and JSON exports of registrations and bans as documents.
+pub(super) async fn send_export(
+    bot: &Bot,
+    msg: &Message,
+    db: &Database,
+    lang: &LanguageCode,
+    temp_dir: &Path,
+) -> HandlerResult {
+    let paths = match export::write_exports(db, temp_dir).await {
+        Ok(paths) => paths,
+        Err(e) => {
+            error!(error = %e, "Failed to write export files");
+            bot.send_message(msg.chat.id, t(lang.as_str(), "admin-export-error"))
+                .await?;
+            return Ok(());
+        }
+    };
+    bot.send_message(msg.chat.id, t(lang.as_str(), "admin-export-ready"))
+        .await?;
+    for path in paths {
+        bot.send_document(msg.chat.id, InputFile::file(path))
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use super::{HandlerResult, known_user_lang};
+use crate::config::AppConfig;
+use crate::db::Database;
+use crate::i18n::t;
+use crate::types::TelegramId;
+use std::sync::Arc;
+use teloxide::prelude::*;
+
+/// List the commands available to the caller, reflecting enabled features.
+pub async fn help(bot: Bot, msg: Message, db: Database, config: Arc<AppConfig>) -> HandlerResult {
+    let caller = TelegramId::new(msg.chat.id.0);
+    let is_admin = config.telegram.admin_ids.contains(&caller);
+    let is_owner = config.is_owner(caller);
+    let lang = known_user_lang(&db, caller, msg.from.as_ref(), &config).await;
+    let lang = lang.as_str();
+    let tg = &config.telegram;
+
+    let mut lines = vec![t(lang, "help-title"), String::new()];
+    if tg.telegram_public_registration_enabled || is_admin {
+        lines.push(t(lang, "help-start"));
+    } else if tg.telegram_deeplink_registration_enabled {
+        lines.push(t(lang, "help-start-invite-only"));
+    }
+    lines.push(t(lang, "help-myaccount"));
+    lines.push(t(lang, "help-changepassword"));
+    lines.push(t(lang, "help-cancel"));
+    lines.push(t(lang, "help-help"));
+    if config.web.web_registration_enabled {
+        lines.push(String::new());
+        lines.push(t(lang, "help-web-enabled"));
+    }
+
+    if is_admin {
+        let admin_lang = tg.bot_admin_lang.as_str();
+        lines.push(String::new());
+        lines.push(t(admin_lang, "help-admin-title"));
+        lines.push(t(admin_lang, "help-adminpanel"));
+        lines.push(t(admin_lang, "help-online"));
+        lines.push(t(admin_lang, "help-stats"));
+        lines.push(t(admin_lang, "help-ttstatus"));
+        lines.push(t(admin_lang, "help-audit"));
+        lines.push(t(admin_lang, "help-broadcast"));
+        lines.push(t(admin_lang, "help-register"));
+        lines.push(t(admin_lang, "help-quiethours"));
+        lines.push(t(admin_lang, "help-translations"));
+        if is_owner {
+            if tg.telegram_deeplink_registration_enabled {
+                lines.push(t(admin_lang, "help-generate"));
+            }
+            lines.push(t(admin_lang, "help-backup"));
+            lines.push(t(admin_lang, "help-exit"));
+            lines.push(t(admin_lang, "help-restart"));
+        }
+    }
+
+    bot.send_message(msg.chat.id, lines.join("\n")).await?;
+    Ok(())
+}
@@ -0,0 +1,232 @@
+use super::HandlerResult;
+use super::admin::paginate;
+use crate::db::Database;
+use crate::db::schema::DeeplinkToken;
+use crate::i18n::{t, t_args};
+use crate::services::audit::{self, Actor, AuditAction};
+use crate::types::{LanguageCode, TelegramId};
+use std::collections::HashMap;
+use teloxide::prelude::*;
+
+/// Characters of the token shown so admins can tell links apart.
+const TOKEN_PREVIEW_CHARS: usize = 8;
+/// Lifetime of a link created by a bare `/generate`.
+const DEFAULT_INVITE_TTL_MINUTES: i64 = 5;
+/// Longest lifetime an admin can request.
+const MAX_INVITE_TTL_DAYS: i64 = 30;
+/// Most registrations a single link can admit.
+const MAX_INVITE_USES: i64 = 1000;
+
+/// Expiry, use limit and server for a new invite link, from
+/// `/generate [ttl] [uses] [--server <id>]`.
+#[derive(Clone, Debug)]
+pub(super) struct InviteOptions {
+    pub ttl: chrono::Duration,
+    pub max_uses: i64,
+    /// Server the invite registers on; users pick one when unset.
+    pub server: Option<String>,
+}
+
+impl Default for InviteOptions {
+    fn default() -> Self {
+        Self {
+            ttl: chrono::Duration::minutes(DEFAULT_INVITE_TTL_MINUTES),
+            max_uses: 1,
+            server: None,
+        }
+    }
+}
+
+impl InviteOptions {
+    /// Parse `"24h 10"`-style arguments; either part may be omitted, in that
+    /// order, and `--server <id>` may appear anywhere.
+    /// Durations take an `s`, `m`, `h` or `d` suffix.
+    pub(super) fn parse(args: &str) -> Option<Self> {
+        let mut options = Self::default();
+        let mut words: Vec<&str> = args.split_whitespace().collect();
+        if let Some(pos) = words.iter().position(|w| *w == "--server") {
+            options.server = Some((*words.get(pos + 1)?).to_string());
+            words.drain(pos..=pos + 1);
+        }
+        let mut parts = words.into_iter().peekable();
+        if let Some(ttl) = parts.peek().and_then(|part| parse_ttl(part)) {
+            options.ttl = ttl;
+            parts.next();
+        }
+        if let Some(uses) = parts.next() {
+            options.max_uses = uses
+                .parse()
+                .ok()
+                .filter(|n| (1..=MAX_INVITE_USES).contains(n))?;
+        }
+        parts.next().is_none().then_some(options)
+    }
+}
+
+fn parse_ttl(value: &str) -> Option<chrono::Duration> {
+    let split = value.len().checked_sub(1)?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().ok().filter(|n| *n > 0)?;
+    let ttl = match unit {
+        "s" => chrono::Duration::try_seconds(amount)?,
+        "m" => chrono::Duration::try_minutes(amount)?,
+        "h" => chrono::Duration::try_hours(amount)?,
+        "d" => chrono::Duration::try_days(amount)?,
+        _ => return None,
+    };
+    (ttl <= chrono::Duration::days(MAX_INVITE_TTL_DAYS)).then_some(ttl)
+}
+
+/// Invite link actions from the admin panel.
+pub(super) enum InviteAction {
+    List(usize),
+    Revoke(i64),
+    RevokeAllPrompt,
+    RevokeAll,
+}
+
+/// Run an invite link action on the panel message.
+pub(super) async fn handle_invite_action(
+    bot: &Bot,
+    msg: &Message,
+    db: &Database,
+    lang: &LanguageCode,
+    page_size: usize,
+    action: InviteAction,
+) -> HandlerResult {
+    match action {
+        InviteAction::List(page) => show_invites(bot, msg, db, lang, page, page_size).await,
+        InviteAction::Revoke(id) => revoke_invite(bot, msg, db, lang, id).await,
+        InviteAction::RevokeAllPrompt => prompt_revoke_all_invites(bot, msg, lang).await,
+        InviteAction::RevokeAll => revoke_all_invites(bot, msg, db, lang).await,
+    }
+}
+
+/// List invite links that have not expired yet.
+async fn show_invites(
+    bot: &Bot,
+    msg: &Message,
+    db: &Database,
+    lang: &LanguageCode,
+    page: usize,
+    page_size: usize,
+) -> HandlerResult {
+    let invites = db.get_active_deeplinks().await?;
+    if invites.is_empty() {
+        bot.edit_message_text(msg.chat.id, msg.id, t(lang.as_str(), "admin-invites-empty"))
+            .await?;
+        return Ok(());
+    }
+
+    let (page_items, total_pages, page_index) = paginate(&invites, page, page_size);
+    let mut lines = vec![t(lang.as_str(), "admin-invites-title")];
+    for invite in &page_items {
+        lines.push(format_invite(lang, invite));
+    }
+    if total_pages > 1 {
+        lines.push(t_args(
+            lang.as_str(),
+            "admin-list-page",
+            &HashMap::from([
+                ("page".to_string(), (page_index + 1).to_string()),
+                ("pages".to_string(), total_pages.to_string()),
+            ]),
+        ));
+    }
+    let nav_row = crate::tg_bot::keyboards::pagination_row(
+        &t(lang.as_str(), "btn-prev-page"),
+        &t(lang.as_str(), "btn-next-page"),
+        (page_index > 0).then(|| format!("admin_invites_page_{}", page_index - 1)),
+        (page_index + 1 < total_pages).then(|| format!("admin_invites_page_{}", page_index + 1)),
+    );
+    bot.edit_message_text(msg.chat.id, msg.id, lines.join("\n"))
+        .reply_markup(crate::tg_bot::keyboards::admin_invites_keyboard(
+            &page_items,
+            &t(lang.as_str(), "btn-invite-revoke"),
+            &t(lang.as_str(), "btn-invite-revoke-all"),
+            nav_row,
+        ))
+        .await?;
+    Ok(())
+}
+
+/// Revoke a single invite link.
+async fn revoke_invite(
+    bot: &Bot,
+    msg: &Message,
+    db: &Database,
+    lang: &LanguageCode,
+    id: i64,
+) -> HandlerResult {
+    let args = HashMap::from([("id".to_string(), id.to_string())]);
+    let key = if db.revoke_deeplink(id).await? {
+        let actor = Actor::Admin(TelegramId::new(msg.chat.id.0));
+        audit::record(db, actor, AuditAction::RevokeInvite, &id.to_string(), None).await;
+        "admin-invite-revoked"
+    } else {
+        "admin-invite-not-found"
+    };
+    bot.edit_message_text(msg.chat.id, msg.id, t_args(lang.as_str(), key, &args))
+        .await?;
+    Ok(())
+}
+
+/// Ask for confirmation before revoking every unused invite link.
+async fn prompt_revoke_all_invites(bot: &Bot, msg: &Message, lang: &LanguageCode) -> HandlerResult {
+    bot.edit_message_text(
+        msg.chat.id,
+        msg.id,
+        t(lang.as_str(), "admin-invites-revoke-all-prompt"),
+    )
+    .reply_markup(crate::tg_bot::keyboards::confirm_keyboard(
+        &t(lang.as_str(), "btn-invite-revoke-all"),
+        &t(lang.as_str(), "btn-cancel"),
+        "invite_revoke_all",
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Revoke every unused invite link.
+async fn revoke_all_invites(
+    bot: &Bot,
+    msg: &Message,
+    db: &Database,
+    lang: &LanguageCode,
+) -> HandlerResult {
+    let count = db.revoke_all_deeplinks().await?;
+    let details = format!("{count} invite link(s)");
+    let actor = Actor::Admin(TelegramId::new(msg.chat.id.0));
+    audit::record(db, actor, AuditAction::RevokeInvite, "all", Some(&details)).await;
+    let args = HashMap::from([("count".to_string(), count.to_string())]);
+    bot.edit_message_text(
+        msg.chat.id,
+        msg.id,
+        t_args(lang.as_str(), "admin-invites-revoked-all", &args),
+    )
+    .await?;
+    Ok(())
+}
+
+fn format_invite(lang: &LanguageCode, invite: &DeeplinkToken) -> String {
+    let creator = invite
+        .generated_by_admin_id
+        .map_or_else(|| "-".to_string(), |id| id.to_string());
+    t_args(
+        lang.as_str(),
+        "admin-invite-entry",
+        &HashMap::from([
+            ("id".to_string(), invite.id.unwrap_or_default().to_string()),
+            (
+                "token".to_string(),
+                invite.token.chars().take(TOKEN_PREVIEW_CHARS).collect(),
+            ),
+            ("creator".to_string(), creator),
+            (
+                "expires".to_string(),
+                invite.expires_at.format("%Y-%m-%d %H:%M UTC").to_string(),
+            ),
+            ("uses".to_string(), invite.uses_remaining.to_string()),
+        ]),
+    )
+}
@@ -1,17 +1,62 @@
+use crate::config::AppConfig;
 use crate::domain::{Nickname, Password, Username};
 use crate::types::LanguageCode;
 use teloxide::dispatching::dialogue::InMemStorage;
 use teloxide::prelude::*;
 use teloxide::utils::command::BotCommands;
 
+mod account;
 mod admin;
+mod admin_register;
+mod audit;
+mod backup;
+mod broadcast;
+mod export;
+mod help;
+mod invites;
+mod notify_settings;
+mod offline_queue;
+mod online;
+mod pending;
+mod reason;
+mod reconcile;
 mod registration;
+mod rename;
+mod search;
+mod stats;
+mod translations;
+mod tt_status;
+mod web_approval;
 
-pub use admin::{admin_callback, admin_manual_ban_input, admin_panel, exit_bot, generate_invite};
+pub use account::{
+    MY_ACCOUNT_CALLBACK_PREFIX, change_password, my_account, my_account_callback,
+    receive_new_password,
+};
+pub use admin::{
+    admin_callback, admin_list_filter_input, admin_manual_ban_input, admin_panel, exit_bot,
+    generate_invite,
+};
+pub use admin_register::admin_register;
+pub use audit::audit_log;
+pub use backup::backup_command;
+pub use broadcast::{admin_tg_broadcast_input, admin_tt_broadcast_input, tt_broadcast_command};
+pub use help::help;
+pub use notify_settings::quiet_hours_command;
+pub use offline_queue::replay_queued_registrations;
+pub use online::{admin_tt_message_input, online_users};
+pub use pending::{PendingReminders, expire_pending_requests};
+pub use reason::admin_decision_reason_input;
+pub use reconcile::run_reconciliation;
 pub use registration::{
-    receive_account_type, receive_language, receive_nickname, receive_nickname_choice,
-    receive_password, receive_username, start,
+    cancel, generate_password_callback, receive_account_type, receive_language, receive_nickname,
+    receive_nickname_choice, receive_password, receive_server, receive_username, start,
 };
+pub use rename::admin_rename_input;
+pub use search::admin_search_input;
+pub use stats::stats;
+pub use translations::translations_command;
+pub use tt_status::tt_status;
+pub use web_approval::{WebApprovalRequest, notify_admins_web_request};
 
 /// Supported bot commands.
 #[derive(BotCommands, Clone, Debug)]
@@ -19,12 +64,38 @@ pub use registration::{
 pub enum Command {
     /// Start the registration flow.
     Start,
+    /// Abort the current registration.
+    Cancel,
+    /// Show your registered account.
+    MyAccount,
+    /// Change your `TeamTalk` password.
+    ChangePassword,
+    /// List online `TeamTalk` users.
+    Online,
+    /// Show registration statistics.
+    Stats,
+    /// Show the `TeamTalk` connection state.
+    TtStatus,
+    /// Show the audit log of admin and system actions (`/audit [page]`).
+    Audit(String),
+    /// Send a message to everyone on the `TeamTalk` server.
+    Broadcast(String),
     /// Open admin panel.
     AdminPanel,
-    /// Generate a one-time invite link.
-    Generate,
+    /// Create a `TeamTalk` account directly (`/register <username> <password> [nickname] [--to <chat_id>] [--profile <name>]`).
+    Register(String),
+    /// Generate an invite link, optionally with a lifetime, use limit and server (`/generate 24h 10 --server <id>`).
+    Generate(String),
+    /// Hold notifications for a digest during set hours (`/quiethours [HH:MM-HH:MM|off|default]`).
+    QuietHours(String),
+    /// Show which messages each language lacks and which keys are unused.
+    Translations,
+    /// Send a compressed database snapshot.
+    Backup,
     /// Gracefully stop the bot.
     Exit,
+    /// Gracefully stop the bot so a supervisor restarts it.
+    Restart,
     /// Show help.
     Help,
 }
@@ -34,32 +105,183 @@ pub enum Command {
 pub enum State {
     #[default]
     Start,
-    ChoosingLanguage,
+    /// `server` is set when an invite link picked the server.
+    ChoosingLanguage {
+        server: Option<String>,
+    },
+    ChoosingServer {
+        lang: LanguageCode,
+    },
     AwaitingUsername {
         lang: LanguageCode,
+        server: String,
     },
     AwaitingPassword {
         lang: LanguageCode,
+        server: String,
         username: Username,
     },
     AwaitingNicknameChoice {
         lang: LanguageCode,
+        server: String,
         username: Username,
         password: Password,
     },
     AwaitingNickname {
         lang: LanguageCode,
+        server: String,
         username: Username,
         password: Password,
     },
     AwaitingAccountType {
         lang: LanguageCode,
+        server: String,
+        username: Username,
+        password: Password,
+        nickname: Nickname,
+    },
+    /// Users pick from `requestable_rights_profiles`.
+    AwaitingRightsProfile {
+        lang: LanguageCode,
+        server: String,
         username: Username,
         password: Password,
         nickname: Nickname,
     },
     AdminPanel,
     AwaitingManualBanInput,
+    AwaitingNewPassword {
+        lang: LanguageCode,
+    },
+    AwaitingTeamTalkMessage {
+        user_id: i32,
+    },
+    AwaitingTeamTalkBroadcast,
+    AwaitingListFilter {
+        list: AdminList,
+    },
+    AwaitingSearch,
+    AwaitingDecisionReason {
+        target: ReasonTarget,
+        message_id: teloxide::types::MessageId,
+    },
+    AwaitingTelegramBroadcast,
+    ConfirmingTelegramBroadcast {
+        message_id: teloxide::types::MessageId,
+    },
+    /// `message_id` is the request the admin pressed "Edit username" on.
+    AwaitingApprovalUsername {
+        target: RenameTarget,
+        message_id: teloxide::types::MessageId,
+    },
+}
+
+/// Admin panel list that can be searched by prefix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdminList {
+    Banlist,
+    TeamTalkAccounts,
+}
+
+/// Decision waiting for an optional reason from the admin.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReasonTarget {
+    Reject(String),
+    RejectWeb(String),
+    Ban(i64),
+}
+
+/// Pending registration whose username an admin is editing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RenameTarget {
+    Telegram(String),
+    Web(String),
+}
+
+impl State {
+    /// Language chosen so far in the registration flow, if any.
+    pub const fn lang(&self) -> Option<&LanguageCode> {
+        match self {
+            Self::ChoosingServer { lang }
+            | Self::AwaitingUsername { lang, .. }
+            | Self::AwaitingPassword { lang, .. }
+            | Self::AwaitingNicknameChoice { lang, .. }
+            | Self::AwaitingNickname { lang, .. }
+            | Self::AwaitingAccountType { lang, .. }
+            | Self::AwaitingRightsProfile { lang, .. }
+            | Self::AwaitingNewPassword { lang } => Some(lang),
+            Self::Start
+            | Self::ChoosingLanguage { .. }
+            | Self::AdminPanel
+            | Self::AwaitingManualBanInput
+            | Self::AwaitingTeamTalkMessage { .. }
+            | Self::AwaitingTeamTalkBroadcast
+            | Self::AwaitingListFilter { .. }
+            | Self::AwaitingSearch
+            | Self::AwaitingDecisionReason { .. }
+            | Self::AwaitingTelegramBroadcast
+            | Self::ConfirmingTelegramBroadcast { .. }
+            | Self::AwaitingApprovalUsername { .. } => None,
+        }
+    }
+}
+
+/// Language for a user outside the registration flow.
+fn user_lang(user: Option<&teloxide::types::User>, config: &AppConfig) -> LanguageCode {
+    if let Some(lang) = &config.web.force_user_lang {
+        return lang.clone();
+    }
+    user.and_then(|u| u.language_code.as_deref()).map_or_else(
+        || config.telegram.bot_admin_lang.clone(),
+        LanguageCode::parse_or_default,
+    )
+}
+
+/// Language for a registered user: the one stored at registration, falling
+/// back to [`user_lang`] for unknown users or older rows.
+async fn known_user_lang(
+    db: &crate::db::Database,
+    tg_id: crate::types::TelegramId,
+    user: Option<&teloxide::types::User>,
+    config: &AppConfig,
+) -> LanguageCode {
+    if config.web.force_user_lang.is_none()
+        && let Ok(Some(stored)) = db.get_registration_language(tg_id).await
+    {
+        return LanguageCode::parse_or_default(&stored);
+    }
+    user_lang(user, config)
+}
+
+/// Localized buttons of registration requests sent to admins.
+fn approval_labels(lang: &str) -> crate::tg_bot::keyboards::ApprovalLabels {
+    crate::tg_bot::keyboards::ApprovalLabels {
+        approve: crate::i18n::t(lang, "btn-admin-verify"),
+        reject: crate::i18n::t(lang, "btn-admin-reject"),
+        approve_admin: crate::i18n::t(lang, "btn-admin-verify-admin"),
+        rename: crate::i18n::t(lang, "btn-admin-rename"),
+    }
+}
+
+/// `(label, name)` pairs for the approve buttons of non-default rights profiles.
+fn rights_profile_buttons(config: &AppConfig) -> Vec<(String, String)> {
+    let lang = config.telegram.bot_admin_lang.as_str();
+    config
+        .extra_rights_profiles()
+        .into_iter()
+        .map(|name| {
+            let args = std::collections::HashMap::from([("profile".to_string(), name.to_string())]);
+            (
+                crate::i18n::t_args(lang, "btn-admin-verify-profile", &args),
+                name.to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Localized reason a `TeamTalk` command failed.
+fn tt_error_text(lang: &str, err: &crate::types::TTCommandError) -> String {
+    crate::i18n::t_args(lang, err.message_key(), &err.message_args())
 }
 
 /// Dialogue type used by handlers.
@@ -0,0 +1,149 @@
+use super::HandlerResult;
+use crate::config::AppConfig;
+use crate::db::Database;
+use crate::db::schema::QUIET_HOURS_OFF;
+use crate::domain::QuietHours;
+use crate::i18n::{t, t_args};
+use crate::tg_bot::keyboards::admin_notify_settings_keyboard;
+use crate::types::{NotificationKind, TelegramId};
+use std::collections::HashMap;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use tracing::warn;
+
+/// Notification settings view of the admin panel.
+pub(super) enum NotifySettingsAction {
+    Show,
+    Toggle(NotificationKind),
+}
+
+impl NotifySettingsAction {
+    /// Parse `admin_notify` and `admin_notify_{kind}` callback data.
+    pub(super) fn parse(data: &str) -> Option<Self> {
+        let rest = data.strip_prefix("admin_notify")?;
+        if rest.is_empty() {
+            return Some(Self::Show);
+        }
+        let kind = NotificationKind::try_from(rest.strip_prefix('_')?).ok()?;
+        Some(Self::Toggle(kind))
+    }
+}
+
+/// Show `admin_id`'s notification settings, toggling one first if asked.
+pub(super) async fn handle_notify_settings(
+    bot: &Bot,
+    q: &CallbackQuery,
+    db: &Database,
+    config: &AppConfig,
+    admin_id: TelegramId,
+    action: NotifySettingsAction,
+) -> HandlerResult {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(msg) = q.message.as_ref().and_then(|m| m.regular_message()) else {
+        warn!("Admin callback query missing or inaccessible message");
+        return Ok(());
+    };
+    let settings = db
+        .get_admin_settings()
+        .await?
+        .into_iter()
+        .find(|settings| settings.admin_id == admin_id);
+    let mut muted: Vec<NotificationKind> = NotificationKind::MUTABLE
+        .into_iter()
+        .filter(|&kind| settings.as_ref().is_some_and(|s| s.mutes(kind)))
+        .collect();
+    if let NotifySettingsAction::Toggle(kind) = action {
+        if let Some(index) = muted.iter().position(|&muted| muted == kind) {
+            muted.remove(index);
+        } else {
+            muted.push(kind);
+        }
+        let stored: Vec<&str> = muted.iter().map(|kind| kind.as_str()).collect();
+        db.set_muted_notifications(admin_id, &stored.join(","))
+            .await?;
+    }
+
+    let lang = config.telegram.bot_admin_lang.as_str();
+    let mut text = t(lang, "admin-notify-title");
+    if config.telegram.admin_chat_id.is_some() {
+        text.push_str("\n\n");
+        text.push_str(&t(lang, "admin-notify-shared-chat"));
+    }
+    let toggles: Vec<(String, NotificationKind)> = NotificationKind::MUTABLE
+        .into_iter()
+        .map(|kind| {
+            let mark = if muted.contains(&kind) {
+                "🔕"
+            } else {
+                "🔔"
+            };
+            (format!("{mark} {}", t(lang, kind.label_key())), kind)
+        })
+        .collect();
+    let keyboard = admin_notify_settings_keyboard(toggles, &t(lang, "btn-back-to-panel"));
+    bot.edit_message_text(msg.chat.id, msg.id, text)
+        .reply_markup(keyboard)
+        .await?;
+    Ok(())
+}
+
+/// Show or change the admin's quiet hours
+/// (`/quiethours [HH:MM-HH:MM|off|default]`).
+pub async fn quiet_hours_command(
+    bot: Bot,
+    msg: Message,
+    db: Database,
+    config: Arc<AppConfig>,
+    args: String,
+) -> HandlerResult {
+    let admin_id = TelegramId::new(msg.chat.id.0);
+    if !config.telegram.admin_ids.contains(&admin_id) {
+        return Ok(());
+    }
+    let lang = config.telegram.bot_admin_lang.as_str();
+    let default = config.telegram.admin_quiet_hours;
+    let args = args.trim();
+    let stored = match args {
+        "" => {
+            let hours = db
+                .get_admin_settings()
+                .await?
+                .into_iter()
+                .find(|settings| settings.admin_id == admin_id)
+                .map_or(default, |settings| settings.quiet_hours(default));
+            let mut text = quiet_hours_reply(lang, "quiet-hours-current", hours);
+            text.push_str("\n\n");
+            text.push_str(&t(lang, "quiet-hours-usage"));
+            bot.send_message(msg.chat.id, text).await?;
+            return Ok(());
+        }
+        "default" => None,
+        QUIET_HOURS_OFF => Some(QUIET_HOURS_OFF.to_string()),
+        window => {
+            let Some(hours) = QuietHours::parse(window) else {
+                bot.send_message(msg.chat.id, t(lang, "quiet-hours-usage"))
+                    .await?;
+                return Ok(());
+            };
+            Some(hours.to_string())
+        }
+    };
+    db.set_quiet_hours(admin_id, stored.as_deref()).await?;
+    let hours = match stored.as_deref() {
+        None => default,
+        Some(QUIET_HOURS_OFF) => None,
+        Some(window) => QuietHours::parse(window),
+    };
+    let mut text = quiet_hours_reply(lang, "quiet-hours-set", hours);
+    if config.telegram.admin_chat_id.is_some() {
+        text.push_str("\n\n");
+        text.push_str(&t(lang, "admin-notify-shared-chat"));
+    }
+    bot.send_message(msg.chat.id, text).await?;
+    Ok(())
+}
+
+fn quiet_hours_reply(lang: &str, key: &str, hours: Option<QuietHours>) -> String {
+    let hours = hours.map_or_else(|| t(lang, "quiet-hours-off"), |hours| hours.to_string());
+    t_args(lang, key, &HashMap::from([("hours".to_string(), hours)]))
+}
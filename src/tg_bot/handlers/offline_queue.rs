@@ -0,0 +1,274 @@
+use super::registration::{notify_db_sync_error, send_registration_assets};
+use crate::config::AppConfig;
+use crate::db::Database;
+use crate::db::schema::QueuedRegistration;
+use crate::domain::{Nickname, Password, Username};
+use crate::i18n::t_args;
+use crate::services::audit::{self, Actor, AuditAction};
+use crate::services::registration::{self, RegistrationResult};
+use crate::tg_bot::notify::AdminChats;
+use crate::tg_bot::throttle;
+use crate::tt::{TTServers, TTWorkerHandle};
+use crate::types::{
+    LanguageCode, NotificationKind, RegistrationSource, TTAccountType, TTWorkerCommand,
+};
+use chrono::Utc;
+use std::collections::HashMap;
+use teloxide::prelude::*;
+use tracing::{error, info, warn};
+
+/// Create the accounts queued while `TeamTalk` was unreachable, once the bot
+/// is logged in again, and finish approvals a crash interrupted.
+///
+/// Names taken on the server in the meantime are dropped, and so are accounts
+/// the server refuses while the bot stays logged in; the admins and the linked
+/// Telegram user are told either way. An existing account whose create
+/// command was already sent counts as created, so replays are idempotent.
+pub async fn replay_queued_registrations(bot: &Bot, db: &Database, servers: &TTServers) {
+    let mut queued = match db.get_queued_registrations().await {
+        Ok(queued) if !queued.is_empty() => queued,
+        Ok(_) => return,
+        Err(e) => {
+            error!(error = %e, "Failed to load queued registrations");
+            return;
+        }
+    };
+    for server in servers.all() {
+        let (mine, rest): (Vec<_>, Vec<_>) = queued
+            .into_iter()
+            .partition(|reg| reg.server_id == server.id());
+        queued = rest;
+        if mine.is_empty() || !is_logged_in(&server.tx_tt).await {
+            continue;
+        }
+        info!(
+            count = mine.len(),
+            server = server.id(),
+            "Replaying queued registrations"
+        );
+        for reg in mine {
+            if !replay(bot, db, &server.config, &server.tx_tt, reg).await {
+                break;
+            }
+        }
+    }
+    for reg in queued {
+        warn!(id = reg.id, server = %reg.server_id, "Queued registration names an unknown server");
+    }
+}
+
+/// Tell the admin who approved or registered `username` that it was queued.
+pub(super) async fn notify_admin_queued(bot: &Bot, chat_id: ChatId, lang: &str, username: &str) {
+    let args = HashMap::from([("username".to_string(), username.to_string())]);
+    let request = bot.send_message(chat_id, t_args(lang, "admin-account-queued", &args));
+    if let Err(e) = throttle::send(chat_id, request).await {
+        warn!(error = %e, "Failed to notify admin about a queued account");
+    }
+}
+
+async fn is_logged_in(tx_tt: &TTWorkerHandle) -> bool {
+    tx_tt
+        .request(|resp| TTWorkerCommand::GetStatus { resp })
+        .await
+        .is_ok_and(|status| status.logged_in)
+}
+
+/// Replay one registration; `false` once the link is down again and the
+/// rest has to wait for the next attempt.
+async fn replay(
+    bot: &Bot,
+    db: &Database,
+    config: &AppConfig,
+    tx_tt: &TTWorkerHandle,
+    reg: QueuedRegistration,
+) -> bool {
+    let (Some(username), Some(password), Some(nickname), Ok(account_type), Ok(source)) = (
+        Username::parse(&reg.username),
+        Password::parse(&reg.password),
+        Nickname::parse(&reg.nickname),
+        TTAccountType::try_from(reg.account_type.as_str()),
+        RegistrationSource::try_from(reg.source.as_str()),
+    ) else {
+        warn!(id = reg.id, "Dropping unreadable queued registration");
+        remove(db, reg.id).await;
+        return true;
+    };
+    let exists = tx_tt
+        .request(|resp| TTWorkerCommand::CheckUserExists {
+            username: username.clone(),
+            matching: config.username_policy.matching(),
+            resp,
+        })
+        .await;
+    let exists = match exists {
+        Ok(exists) => exists,
+        Err(e) => {
+            warn!(error = %e, "Queued registration check failed");
+            return false;
+        }
+    };
+    if exists && reg.attempted_at.is_none() {
+        drop_registration(bot, db, config, &reg, "queued-account-taken").await;
+        return true;
+    }
+
+    let language = reg.language.as_deref().and_then(LanguageCode::parse);
+    let params = registration::CreateAccountParams {
+        username: &username,
+        password: &password,
+        nickname: &nickname,
+        account_type,
+        rights_profile: Some(&reg.rights_profile),
+        source,
+        source_info: reg.source_info.clone(),
+        telegram_id: reg.telegram_id,
+        language: language.as_ref(),
+        queued_id: Some(reg.id),
+        tx_tt: tx_tt.clone(),
+        db,
+        config,
+    };
+    let result = if exists {
+        // Created before a crash cut the registration short; just record it.
+        info!(username = %reg.username, "Queued account already exists, recording it");
+        registration::adopt_queued_account(params).await
+    } else {
+        let now = Utc::now().naive_utc();
+        if let Err(e) = db.set_queued_registration_attempt(reg.id, Some(now)).await {
+            warn!(error = %e, id = reg.id, "Failed to mark queued registration attempted");
+            return false;
+        }
+        registration::create_queued_account(params).await
+    };
+    if !result.created {
+        if !is_logged_in(tx_tt).await {
+            return false;
+        }
+        drop_registration(bot, db, config, &reg, "queued-account-failed").await;
+        return true;
+    }
+
+    info!(username = %reg.username, queued_at = %reg.created_at, "Created queued account");
+    audit::record(
+        db,
+        Actor::System,
+        AuditAction::CreateAccount,
+        username.as_str(),
+        Some("Created from the offline queue"),
+    )
+    .await;
+    notify_admins(
+        bot,
+        db,
+        config,
+        NotificationKind::AccountCreated,
+        "admin-queued-account-created",
+        &reg.username,
+    )
+    .await;
+    if let Some(tg_id) = reg.telegram_id {
+        let chat_id = ChatId(tg_id.as_i64());
+        if let Some(err) = &result.db_sync_error {
+            notify_db_sync_error(bot, db, config, chat_id, username.as_str(), err).await;
+        }
+        deliver_to_user(
+            bot,
+            config,
+            chat_id,
+            language.unwrap_or_default().as_str(),
+            &username,
+            &password,
+            &result,
+        )
+        .await;
+    }
+    true
+}
+
+/// Give up on a queued registration; `user_key` explains why to its owner.
+async fn drop_registration(
+    bot: &Bot,
+    db: &Database,
+    config: &AppConfig,
+    reg: &QueuedRegistration,
+    user_key: &str,
+) {
+    warn!(username = %reg.username, reason = user_key, "Dropping queued registration");
+    remove(db, reg.id).await;
+    let key = format!("admin-{user_key}");
+    notify_admins(
+        bot,
+        db,
+        config,
+        NotificationKind::Alert,
+        &key,
+        &reg.username,
+    )
+    .await;
+    let Some(tg_id) = reg.telegram_id else {
+        return;
+    };
+    let lang = reg
+        .language
+        .as_deref()
+        .and_then(LanguageCode::parse)
+        .unwrap_or_default();
+    let args = HashMap::from([("username".to_string(), reg.username.clone())]);
+    let chat_id = ChatId(tg_id.as_i64());
+    let request = bot.send_message(chat_id, t_args(lang.as_str(), user_key, &args));
+    if let Err(e) = throttle::send(chat_id, request).await {
+        warn!(error = %e, "Failed to notify user about a dropped queued registration");
+    }
+}
+
+async fn deliver_to_user(
+    bot: &Bot,
+    config: &AppConfig,
+    chat_id: ChatId,
+    lang: &str,
+    username: &Username,
+    password: &Password,
+    result: &RegistrationResult,
+) {
+    let args = HashMap::from([("username".to_string(), username.as_str().to_string())]);
+    let request = bot.send_message(chat_id, t_args(lang, "register-success", &args));
+    if let Err(e) = throttle::send(chat_id, request).await {
+        warn!(error = %e, "Failed to notify user about a queued account");
+        return;
+    }
+    if let Some(assets) = &result.assets
+        && let Err(e) = send_registration_assets(
+            bot,
+            chat_id,
+            lang,
+            config,
+            username.as_str(),
+            password.as_str(),
+            assets,
+        )
+        .await
+    {
+        warn!(error = %e, "Failed to send registration assets for a queued account");
+    }
+}
+
+async fn notify_admins(
+    bot: &Bot,
+    db: &Database,
+    config: &AppConfig,
+    kind: NotificationKind,
+    key: &str,
+    username: &str,
+) {
+    let args = HashMap::from([("username".to_string(), username.to_string())]);
+    let text = t_args(config.telegram.bot_admin_lang.as_str(), key, &args);
+    AdminChats::new(&config.telegram, db)
+        .send(bot, kind, &text, None, None)
+        .await;
+}
+
+async fn remove(db: &Database, id: i64) {
+    if let Err(e) = db.delete_queued_registration(id).await {
+        error!(error = %e, id, "Failed to remove queued registration");
+    }
+}
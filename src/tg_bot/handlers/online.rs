@@ -0,0 +1,296 @@
+use super::admin::paginate;
+use super::{HandlerResult, MyDialogue, State, tt_error_text};
+use crate::config::AppConfig;
+use crate::i18n::{t, t_args};
+use crate::tt::TTWorkerHandle;
+use crate::types::{LanguageCode, OnlineUser, TTWorkerCommand, TelegramId};
+use std::collections::HashMap;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use teloxide::types::InlineKeyboardMarkup;
+use tracing::{info, warn};
+
+const USERTYPE_ADMIN: u8 = 2;
+
+/// Show online `TeamTalk` users (`/online`).
+pub async fn online_users(
+    bot: Bot,
+    msg: Message,
+    config: Arc<AppConfig>,
+    tx_tt: TTWorkerHandle,
+) -> HandlerResult {
+    if !config
+        .telegram
+        .admin_ids
+        .contains(&TelegramId::new(msg.chat.id.0))
+    {
+        return Ok(());
+    }
+    let lang = &config.telegram.bot_admin_lang;
+    match fetch_online_view(lang, &tx_tt, 0, config.telegram.admin_page_size).await {
+        Ok((text, Some(keyboard))) => {
+            bot.send_message(msg.chat.id, text)
+                .reply_markup(keyboard)
+                .await?;
+        }
+        Ok((text, None)) | Err(text) => {
+            bot.send_message(msg.chat.id, text).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Render a page of the online users list into the panel message.
+pub(super) async fn show_online_users(
+    bot: &Bot,
+    msg: &Message,
+    lang: &LanguageCode,
+    tx_tt: &TTWorkerHandle,
+    page: usize,
+    page_size: usize,
+) -> HandlerResult {
+    match fetch_online_view(lang, tx_tt, page, page_size).await {
+        Ok((text, Some(keyboard))) => {
+            bot.edit_message_text(msg.chat.id, msg.id, text)
+                .reply_markup(keyboard)
+                .await?;
+        }
+        Ok((text, None)) | Err(text) => {
+            bot.edit_message_text(msg.chat.id, msg.id, text).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Moderation action on an online user that needs confirmation.
+#[derive(Clone, Copy, Debug)]
+pub(super) enum OnlineAction {
+    Kick,
+    Ban,
+}
+
+impl OnlineAction {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Kick => "kick",
+            Self::Ban => "ban",
+        }
+    }
+}
+
+/// Ask the admin to confirm a kick or ban.
+pub(super) async fn prompt_online_action(
+    bot: &Bot,
+    msg: &Message,
+    lang: &LanguageCode,
+    action: OnlineAction,
+    user_id: i32,
+) -> HandlerResult {
+    let args = HashMap::from([("user_id".to_string(), user_id.to_string())]);
+    let name = action.name();
+    bot.edit_message_text(
+        msg.chat.id,
+        msg.id,
+        t_args(lang.as_str(), &format!("admin-online-{name}-prompt"), &args),
+    )
+    .reply_markup(crate::tg_bot::keyboards::confirm_keyboard(
+        &t(lang.as_str(), &format!("btn-online-confirm-{name}")),
+        &t(lang.as_str(), "btn-cancel"),
+        &format!("online_{name}_{user_id}"),
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Kick or ban an online user after confirmation.
+pub(super) async fn apply_online_action(
+    bot: &Bot,
+    msg: &Message,
+    lang: &LanguageCode,
+    tx_tt: &TTWorkerHandle,
+    action: OnlineAction,
+    user_id: i32,
+) -> HandlerResult {
+    let mut args = HashMap::from([("user_id".to_string(), user_id.to_string())]);
+    let result = match action {
+        OnlineAction::Kick => {
+            tx_tt
+                .request(|resp| TTWorkerCommand::KickUser { user_id, resp })
+                .await
+        }
+        OnlineAction::Ban => {
+            tx_tt
+                .request(|resp| TTWorkerCommand::BanUser { user_id, resp })
+                .await
+        }
+    };
+    let name = action.name();
+    let done_key = format!("admin-online-{name}-done");
+    let fail_key = format!("admin-online-{name}-fail");
+    let text = match result {
+        Ok(Ok(true)) => {
+            info!(user_id, action = name, "Online user moderated via bot");
+            t_args(lang.as_str(), &done_key, &args)
+        }
+        Ok(Ok(false)) => {
+            args.insert("error".to_string(), "-".to_string());
+            t_args(lang.as_str(), &fail_key, &args)
+        }
+        Ok(Err(err)) => {
+            args.insert("error".to_string(), tt_error_text(lang.as_str(), &err));
+            t_args(lang.as_str(), &fail_key, &args)
+        }
+        Err(e) => {
+            warn!(error = %e, user_id, action = name, "TeamTalk moderation request failed");
+            args.insert("error".to_string(), e.to_string());
+            t_args(lang.as_str(), &fail_key, &args)
+        }
+    };
+    bot.edit_message_text(msg.chat.id, msg.id, text).await?;
+    Ok(())
+}
+
+/// Ask the admin for the text to send to an online user.
+pub(super) async fn prompt_online_message(
+    bot: &Bot,
+    msg: &Message,
+    lang: &LanguageCode,
+    dialogue: &MyDialogue,
+    user_id: i32,
+) -> HandlerResult {
+    let args = HashMap::from([("user_id".to_string(), user_id.to_string())]);
+    bot.send_message(
+        msg.chat.id,
+        t_args(lang.as_str(), "admin-online-message-prompt", &args),
+    )
+    .await?;
+    dialogue
+        .update(State::AwaitingTeamTalkMessage { user_id })
+        .await?;
+    Ok(())
+}
+
+/// Send the admin's text to the selected online user.
+pub async fn admin_tt_message_input(
+    bot: Bot,
+    msg: Message,
+    config: Arc<AppConfig>,
+    dialogue: MyDialogue,
+    tx_tt: TTWorkerHandle,
+) -> HandlerResult {
+    let Some(State::AwaitingTeamTalkMessage { user_id }) = (match dialogue.get().await {
+        Ok(state) => state,
+        Err(e) => {
+            warn!(error = %e, "Failed to read dialogue state (AwaitingTeamTalkMessage)");
+            return Ok(());
+        }
+    }) else {
+        return Ok(());
+    };
+    let lang = &config.telegram.bot_admin_lang;
+    let text = msg.text().unwrap_or("").trim().to_string();
+    if text.is_empty() {
+        return Ok(());
+    }
+    let mut args = HashMap::from([("user_id".to_string(), user_id.to_string())]);
+    let result = tx_tt
+        .request(|resp| TTWorkerCommand::SendUserMessage {
+            user_id,
+            text,
+            resp,
+        })
+        .await;
+    let reply = match result {
+        Ok(Ok(true)) => t_args(lang.as_str(), "admin-online-message-sent", &args),
+        Ok(Ok(false)) => {
+            args.insert("error".to_string(), "-".to_string());
+            t_args(lang.as_str(), "admin-online-message-fail", &args)
+        }
+        Ok(Err(err)) => {
+            args.insert("error".to_string(), tt_error_text(lang.as_str(), &err));
+            t_args(lang.as_str(), "admin-online-message-fail", &args)
+        }
+        Err(e) => {
+            warn!(error = %e, user_id, "TeamTalk text message request failed");
+            args.insert("error".to_string(), e.to_string());
+            t_args(lang.as_str(), "admin-online-message-fail", &args)
+        }
+    };
+    bot.send_message(msg.chat.id, reply).await?;
+    dialogue.update(State::AdminPanel).await?;
+    Ok(())
+}
+
+/// Build the list text and keyboard, or an error text if the worker failed.
+async fn fetch_online_view(
+    lang: &LanguageCode,
+    tx_tt: &TTWorkerHandle,
+    page: usize,
+    page_size: usize,
+) -> Result<(String, Option<InlineKeyboardMarkup>), String> {
+    let users = match tx_tt
+        .request(|resp| TTWorkerCommand::GetOnlineUsers { resp })
+        .await
+    {
+        Ok(users) => users,
+        Err(e) => {
+            warn!(error = %e, "TeamTalk online users request failed");
+            return Err(t(lang.as_str(), "admin-online-error"));
+        }
+    };
+    if users.is_empty() {
+        return Ok((t(lang.as_str(), "admin-online-empty"), None));
+    }
+
+    let (page_items, total_pages, page_index) = paginate(&users, page, page_size);
+    let mut lines = vec![t_args(
+        lang.as_str(),
+        "admin-online-title",
+        &HashMap::from([("count".to_string(), users.len().to_string())]),
+    )];
+    for user in &page_items {
+        lines.push(format_user(lang, user));
+    }
+    if total_pages > 1 {
+        lines.push(t_args(
+            lang.as_str(),
+            "admin-list-page",
+            &HashMap::from([
+                ("page".to_string(), (page_index + 1).to_string()),
+                ("pages".to_string(), total_pages.to_string()),
+            ]),
+        ));
+    }
+    let nav_row = crate::tg_bot::keyboards::pagination_row(
+        &t(lang.as_str(), "btn-prev-page"),
+        &t(lang.as_str(), "btn-next-page"),
+        (page_index > 0).then(|| format!("admin_online_page_{}", page_index - 1)),
+        (page_index + 1 < total_pages).then(|| format!("admin_online_page_{}", page_index + 1)),
+    );
+    let keyboard = crate::tg_bot::keyboards::admin_online_users_keyboard(
+        &page_items,
+        &t(lang.as_str(), "btn-online-kick"),
+        &t(lang.as_str(), "btn-online-ban"),
+        &t(lang.as_str(), "btn-online-message"),
+        nav_row,
+    );
+    Ok((lines.join("\n"), Some(keyboard)))
+}
+
+fn format_user(lang: &LanguageCode, user: &OnlineUser) -> String {
+    let type_key = if user.user_type & USERTYPE_ADMIN != 0 {
+        "admin-online-type-admin"
+    } else {
+        "admin-online-type-default"
+    };
+    t_args(
+        lang.as_str(),
+        "admin-online-entry",
+        &HashMap::from([
+            ("nickname".to_string(), user.nickname.clone()),
+            ("username".to_string(), user.username.clone()),
+            ("channel_id".to_string(), user.channel_id.to_string()),
+            ("user_type".to_string(), t(lang.as_str(), type_key)),
+        ]),
+    )
+}
@@ -0,0 +1,300 @@
+use super::HandlerResult;
+use super::admin::paginate;
+use super::tt_status::format_duration;
+use crate::config::AppConfig;
+use crate::db::Database;
+use crate::i18n::{t, t_args};
+use crate::services::audit::{self, Actor, AuditAction};
+use crate::tg_bot::keyboards::{admin_approval_keyboard, admin_web_approval_keyboard};
+use crate::tg_bot::notify::AdminChats;
+use crate::tg_bot::throttle;
+use crate::types::{NotificationKind, SourceKind};
+use chrono::{NaiveDateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use teloxide::prelude::*;
+use tracing::{info, warn};
+
+/// Requests listed in one reminder; the rest are only counted.
+const REMINDER_ENTRIES: usize = 20;
+
+/// A registration request no admin has decided yet.
+#[derive(Clone)]
+struct QueuedRequest {
+    key: String,
+    web: bool,
+    username: String,
+    /// `admin-request-source-*` message key.
+    source_key: &'static str,
+    created_at: NaiveDateTime,
+}
+
+impl QueuedRequest {
+    fn waiting_seconds(&self, now: NaiveDateTime) -> u64 {
+        u64::try_from((now - self.created_at).num_seconds()).unwrap_or(0)
+    }
+
+    fn line(&self, lang: &str, now: NaiveDateTime) -> String {
+        let args = HashMap::from([
+            ("username".to_string(), self.username.clone()),
+            ("source".to_string(), t(lang, self.source_key)),
+            (
+                "age".to_string(),
+                format_duration(self.waiting_seconds(now)),
+            ),
+        ]);
+        t_args(lang, "admin-pending-entry", &args)
+    }
+}
+
+/// Undecided Telegram and web requests, oldest first.
+async fn queued_requests(
+    db: &Database,
+) -> Result<Vec<QueuedRequest>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut requests: Vec<QueuedRequest> = db
+        .get_all_pending_registrations()
+        .await?
+        .into_iter()
+        .map(|req| QueuedRequest {
+            key: req.request_key,
+            web: false,
+            username: req.username,
+            source_key: "admin-request-source-telegram",
+            created_at: req.created_at,
+        })
+        .collect();
+    let web = db.get_undecided_web_registrations().await?;
+    requests.extend(web.into_iter().map(|req| {
+        let via_teamtalk = req.source_info.source == SourceKind::TeamTalk;
+        QueuedRequest {
+            key: req.request_key,
+            web: true,
+            username: req.username,
+            source_key: if via_teamtalk {
+                "admin-request-source-teamtalk"
+            } else {
+                "admin-request-source-web"
+            },
+            created_at: req.created_at,
+        }
+    }));
+    requests.sort_by_key(|req| req.created_at);
+    Ok(requests)
+}
+
+/// Drop Telegram requests nobody decided within `pending_reg_ttl_seconds` and
+/// tell the users who sent them.
+pub async fn expire_pending_requests(bot: &Bot, db: &Database, config: &AppConfig) {
+    let expired = match db
+        .expire_pending_registrations(config.database.pending_reg_ttl_seconds)
+        .await
+    {
+        Ok(expired) => expired,
+        Err(e) => {
+            warn!(error = %e, "Failed to expire pending registrations");
+            return;
+        }
+    };
+    for req in expired {
+        info!(username = %req.username, "Registration request expired without a decision");
+        audit::record(
+            db,
+            Actor::System,
+            AuditAction::ExpireRequest,
+            &req.username,
+            None,
+        )
+        .await;
+        let lang = req.source_info.lang;
+        let chat_id = ChatId(req.registrant_telegram_id.as_i64());
+        let request = bot.send_message(chat_id, t(lang.as_str(), "pending-expired"));
+        if let Err(e) = throttle::send(chat_id, request).await {
+            warn!(error = %e, "Failed to tell user their request expired");
+        }
+    }
+}
+
+/// Requests admins were already reminded of, so each is mentioned once.
+#[derive(Default)]
+pub struct PendingReminders {
+    reminded: HashSet<String>,
+}
+
+impl PendingReminders {
+    /// Remind admins of requests waiting longer than
+    /// `pending_reminder_after_seconds`.
+    pub async fn run(&mut self, bot: &Bot, db: &Database, config: &AppConfig) {
+        let after = config.database.pending_reminder_after_seconds;
+        if after == 0 {
+            return;
+        }
+        let requests = match queued_requests(db).await {
+            Ok(requests) => requests,
+            Err(e) => {
+                warn!(error = %e, "Failed to load pending registrations for reminders");
+                return;
+            }
+        };
+        self.reminded
+            .retain(|key| requests.iter().any(|req| &req.key == key));
+        let now = Utc::now().naive_utc();
+        let due: Vec<&QueuedRequest> = requests
+            .iter()
+            .filter(|req| req.waiting_seconds(now) >= after && !self.reminded.contains(&req.key))
+            .collect();
+        if due.is_empty() {
+            return;
+        }
+        let lang = config.telegram.bot_admin_lang.as_str();
+        let title_args = HashMap::from([
+            ("count".to_string(), due.len().to_string()),
+            ("age".to_string(), format_duration(after)),
+        ]);
+        let mut lines = vec![t_args(lang, "pending-reminder-title", &title_args)];
+        lines.extend(
+            due.iter()
+                .take(REMINDER_ENTRIES)
+                .map(|req| req.line(lang, now)),
+        );
+        lines.push(t(lang, "pending-reminder-hint"));
+        AdminChats::new(&config.telegram, db)
+            .send(
+                bot,
+                NotificationKind::Approvals,
+                &lines.join("\n"),
+                None,
+                None,
+            )
+            .await;
+        self.reminded
+            .extend(due.into_iter().map(|req| req.key.clone()));
+    }
+}
+
+/// Pending request actions from the admin panel.
+pub(super) enum PendingAction {
+    List(usize),
+    /// Send a request again with its approval buttons; `true` for web requests.
+    Show(bool, String),
+}
+
+impl PendingAction {
+    /// Parse `admin_pending`, `admin_pending_page_{n}` and
+    /// `admin_pending_{tg|web}_{key}` callback data.
+    pub(super) fn parse(data: &str) -> Option<Self> {
+        let rest = data.strip_prefix("admin_pending")?;
+        if rest.is_empty() {
+            return Some(Self::List(0));
+        }
+        if let Some(page) = rest.strip_prefix("_page_") {
+            return page.parse().ok().map(Self::List);
+        }
+        if let Some(key) = rest.strip_prefix("_tg_") {
+            return Some(Self::Show(false, key.to_string()));
+        }
+        rest.strip_prefix("_web_")
+            .map(|key| Self::Show(true, key.to_string()))
+    }
+}
+
+/// Run a pending request action on the panel message.
+pub(super) async fn handle_pending_action(
+    bot: &Bot,
+    msg: &Message,
+    db: &Database,
+    config: &AppConfig,
+    action: PendingAction,
+) -> HandlerResult {
+    match action {
+        PendingAction::List(page) => show_pending_queue(bot, msg, db, config, page).await,
+        PendingAction::Show(web, key) => {
+            show_pending_request(bot, msg, db, config, web, &key).await
+        }
+    }
+}
+
+/// List undecided requests, oldest first.
+async fn show_pending_queue(
+    bot: &Bot,
+    msg: &Message,
+    db: &Database,
+    config: &AppConfig,
+    page: usize,
+) -> HandlerResult {
+    let lang = config.telegram.bot_admin_lang.as_str();
+    let requests = queued_requests(db).await?;
+    if requests.is_empty() {
+        bot.edit_message_text(msg.chat.id, msg.id, t(lang, "admin-pending-empty"))
+            .await?;
+        return Ok(());
+    }
+
+    let (page_items, total_pages, page_index) =
+        paginate(&requests, page, config.telegram.admin_page_size);
+    let now = Utc::now().naive_utc();
+    let title_args = HashMap::from([("count".to_string(), requests.len().to_string())]);
+    let mut lines = vec![t_args(lang, "admin-pending-title", &title_args)];
+    lines.extend(page_items.iter().map(|req| req.line(lang, now)));
+    if total_pages > 1 {
+        lines.push(t_args(
+            lang,
+            "admin-list-page",
+            &HashMap::from([
+                ("page".to_string(), (page_index + 1).to_string()),
+                ("pages".to_string(), total_pages.to_string()),
+            ]),
+        ));
+    }
+    let nav_row = crate::tg_bot::keyboards::pagination_row(
+        &t(lang, "btn-prev-page"),
+        &t(lang, "btn-next-page"),
+        (page_index > 0).then(|| format!("admin_pending_page_{}", page_index - 1)),
+        (page_index + 1 < total_pages).then(|| format!("admin_pending_page_{}", page_index + 1)),
+    );
+    let entries: Vec<(bool, String, String)> = page_items
+        .into_iter()
+        .map(|req| (req.web, req.key, req.username))
+        .collect();
+    bot.edit_message_text(msg.chat.id, msg.id, lines.join("\n"))
+        .reply_markup(crate::tg_bot::keyboards::admin_pending_keyboard(
+            &entries, nav_row,
+        ))
+        .await?;
+    Ok(())
+}
+
+/// Send one request with its approval buttons.
+async fn show_pending_request(
+    bot: &Bot,
+    msg: &Message,
+    db: &Database,
+    config: &AppConfig,
+    web: bool,
+    key: &str,
+) -> HandlerResult {
+    let lang = config.telegram.bot_admin_lang.as_str();
+    let request = queued_requests(db)
+        .await?
+        .into_iter()
+        .find(|req| req.web == web && req.key == key);
+    let Some(request) = request else {
+        bot.send_message(msg.chat.id, t(lang, "admin-req-handled"))
+            .await?;
+        return Ok(());
+    };
+    let labels = super::approval_labels(lang);
+    let profiles = super::rights_profile_buttons(config);
+    let keyboard = if web {
+        admin_web_approval_keyboard(&labels, key, &profiles)
+    } else {
+        admin_approval_keyboard(&labels, key, &profiles)
+    };
+    let text = format!(
+        "{}\n{}",
+        t(lang, "admin-request-title"),
+        request.line(lang, Utc::now().naive_utc())
+    );
+    bot.send_message(msg.chat.id, text)
+        .reply_markup(keyboard)
+        .await?;
+    Ok(())
+}
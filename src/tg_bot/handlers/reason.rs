@@ -0,0 +1,217 @@
+use super::admin::{handle_admin_delete_confirm, handle_admin_reject, panel_keyboard};
+use super::web_approval::handle_web_reject;
+use super::{HandlerResult, MyDialogue, ReasonTarget, State};
+use crate::config::AppConfig;
+use crate::db::Database;
+use crate::i18n::{t, t_args};
+use crate::types::{LanguageCode, TelegramId};
+use std::collections::HashMap;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use teloxide::types::{MessageId, User};
+use tracing::warn;
+
+/// Longest reason kept; anything beyond is cut off.
+const MAX_REASON_CHARS: usize = 500;
+
+/// Admin who made a decision and the message it was made on.
+pub(super) struct DecisionOrigin<'a> {
+    pub admin: &'a User,
+    pub chat_id: ChatId,
+    pub message_id: MessageId,
+}
+
+/// Append the admin's reason to a message, if one was given.
+pub(super) fn with_reason(lang: &str, text: String, reason: Option<&str>) -> String {
+    match reason {
+        Some(reason) => {
+            let line = t_args(
+                lang,
+                "decision-reason",
+                &HashMap::from([("reason".to_string(), reason.to_string())]),
+            );
+            format!("{text}\n{line}")
+        }
+        None => text,
+    }
+}
+
+/// Ask the admin for an optional reason before rejecting or banning.
+pub(super) async fn prompt_reason(
+    bot: &Bot,
+    chat_id: ChatId,
+    message_id: MessageId,
+    lang: &LanguageCode,
+    dialogue: &MyDialogue,
+    target: ReasonTarget,
+) -> HandlerResult {
+    let keyboard = crate::tg_bot::keyboards::reason_keyboard(
+        &t(lang.as_str(), "btn-reason-skip"),
+        &t(lang.as_str(), "btn-cancel"),
+    );
+    if let ReasonTarget::Ban(tg_id) = target {
+        let args = HashMap::from([("tg_id".to_string(), tg_id.to_string())]);
+        bot.edit_message_text(
+            chat_id,
+            message_id,
+            t_args(lang.as_str(), "reason-prompt-ban", &args),
+        )
+        .reply_markup(keyboard)
+        .await?;
+    } else {
+        bot.edit_message_reply_markup(chat_id, message_id)
+            .reply_markup(keyboard)
+            .await?;
+        bot.send_message(chat_id, t(lang.as_str(), "reason-prompt-reject"))
+            .await?;
+    }
+    dialogue
+        .update(State::AwaitingDecisionReason { target, message_id })
+        .await?;
+    Ok(())
+}
+
+/// Apply the pending decision with the reason the admin typed.
+pub async fn admin_decision_reason_input(
+    bot: Bot,
+    msg: Message,
+    db: Database,
+    config: Arc<AppConfig>,
+    dialogue: MyDialogue,
+) -> HandlerResult {
+    let Some(State::AwaitingDecisionReason { target, message_id }) = (match dialogue.get().await {
+        Ok(state) => state,
+        Err(e) => {
+            warn!(error = %e, "Failed to read dialogue state (AwaitingDecisionReason)");
+            return Ok(());
+        }
+    }) else {
+        return Ok(());
+    };
+    // In a shared admin chat anyone can post; only an admin's reply counts.
+    let Some(admin) = msg.from.as_ref().filter(|user| {
+        i64::try_from(user.id.0)
+            .is_ok_and(|id| config.telegram.admin_ids.contains(&TelegramId::new(id)))
+    }) else {
+        return Ok(());
+    };
+    let reason: String = msg
+        .text()
+        .unwrap_or("")
+        .trim()
+        .chars()
+        .take(MAX_REASON_CHARS)
+        .collect();
+    if reason.is_empty() {
+        return Ok(());
+    }
+    dialogue.update(State::AdminPanel).await?;
+    let origin = DecisionOrigin {
+        admin,
+        chat_id: msg.chat.id,
+        message_id,
+    };
+    apply_decision(&bot, &origin, &db, &config, target, Some(&reason)).await
+}
+
+/// Apply the pending decision without a reason.
+pub(super) async fn skip_reason(
+    bot: &Bot,
+    q: &CallbackQuery,
+    db: &Database,
+    config: &AppConfig,
+    dialogue: &MyDialogue,
+) -> HandlerResult {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.message.as_ref() else {
+        return Ok(());
+    };
+    let lang = &config.telegram.bot_admin_lang;
+    let Some(State::AwaitingDecisionReason { target, .. }) = dialogue.get().await? else {
+        bot.edit_message_text(
+            message.chat().id,
+            message.id(),
+            t(lang.as_str(), "admin-req-handled"),
+        )
+        .await?;
+        return Ok(());
+    };
+    dialogue.update(State::AdminPanel).await?;
+    let origin = DecisionOrigin {
+        admin: &q.from,
+        chat_id: message.chat().id,
+        message_id: message.id(),
+    };
+    apply_decision(bot, &origin, db, config, target, None).await
+}
+
+/// Drop the pending decision and restore the previous buttons.
+pub(super) async fn cancel_reason(
+    bot: &Bot,
+    q: &CallbackQuery,
+    config: &AppConfig,
+    dialogue: &MyDialogue,
+) -> HandlerResult {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(message) = q.message.as_ref() else {
+        return Ok(());
+    };
+    let lang = config.telegram.bot_admin_lang.as_str();
+    let (chat_id, message_id) = (message.chat().id, message.id());
+    let state = dialogue.get().await?;
+    dialogue.update(State::AdminPanel).await?;
+    match state {
+        Some(State::AwaitingDecisionReason {
+            target: ReasonTarget::Reject(req_id),
+            ..
+        }) => {
+            bot.edit_message_reply_markup(chat_id, message_id)
+                .reply_markup(crate::tg_bot::keyboards::admin_approval_keyboard(
+                    &super::approval_labels(lang),
+                    &req_id,
+                    &super::rights_profile_buttons(config),
+                ))
+                .await?;
+        }
+        Some(State::AwaitingDecisionReason {
+            target: ReasonTarget::RejectWeb(req_id),
+            ..
+        }) => {
+            bot.edit_message_reply_markup(chat_id, message_id)
+                .reply_markup(crate::tg_bot::keyboards::admin_web_approval_keyboard(
+                    &super::approval_labels(lang),
+                    &req_id,
+                    &super::rights_profile_buttons(config),
+                ))
+                .await?;
+        }
+        _ => {
+            bot.edit_message_text(chat_id, message_id, t(lang, "admin-panel-title"))
+                .reply_markup(panel_keyboard(&config.telegram.bot_admin_lang))
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn apply_decision(
+    bot: &Bot,
+    origin: &DecisionOrigin<'_>,
+    db: &Database,
+    config: &AppConfig,
+    target: ReasonTarget,
+    reason: Option<&str>,
+) -> HandlerResult {
+    let lang = &config.telegram.bot_admin_lang;
+    match target {
+        ReasonTarget::Reject(req_id) => {
+            handle_admin_reject(bot, origin, db, config, lang, &req_id, reason).await
+        }
+        ReasonTarget::RejectWeb(req_id) => {
+            handle_web_reject(bot, origin, db, config, lang, &req_id, reason).await
+        }
+        ReasonTarget::Ban(tg_id) => {
+            handle_admin_delete_confirm(bot, origin, db, config, lang, tg_id, reason).await
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::config::AppConfig;
+use crate::db::Database;
+use crate::i18n::{t, t_args};
+use crate::services::reconcile::{self, Drift, Repairs};
+use crate::tg_bot::notify::AdminChats;
+use crate::tt::TTWorkerHandle;
+use crate::types::NotificationKind;
+use std::collections::HashMap;
+use teloxide::prelude::*;
+use tracing::{info, warn};
+
+/// Names listed per section of the digest; the rest are only counted.
+const DIGEST_NAMES: usize = 20;
+
+/// Compare the `TeamTalk` accounts with the database and send admins a digest
+/// of the differences, repairing them first when `tt_reconcile_repair` is on.
+pub async fn run_reconciliation(
+    bot: &Bot,
+    db: &Database,
+    config: &AppConfig,
+    tx_tt: &TTWorkerHandle,
+) {
+    let drift = match reconcile::find_drift(db, config, tx_tt).await {
+        Ok(drift) => drift,
+        Err(e) => {
+            warn!(server = %config.teamtalk.server_id, error = %e, "Skipping TeamTalk account reconciliation");
+            return;
+        }
+    };
+    let server = &config.teamtalk.server_id;
+    if drift.is_empty() {
+        info!(server, "TeamTalk accounts match the database");
+        return;
+    }
+    info!(
+        server,
+        unlinked = drift.unlinked.len(),
+        vanished = drift.vanished.len(),
+        banned = drift.banned.len(),
+        "TeamTalk accounts differ from the database"
+    );
+    let repairs = if config.teamtalk.tt_reconcile_repair {
+        Some(reconcile::repair_drift(db, tx_tt, &drift).await)
+    } else {
+        None
+    };
+    let text = digest(
+        config.telegram.bot_admin_lang.as_str(),
+        config.server_label(),
+        &drift,
+        repairs.as_ref(),
+    );
+    AdminChats::new(&config.telegram, db)
+        .send(bot, NotificationKind::Alert, &text, None, None)
+        .await;
+}
+
+fn digest(lang: &str, server: Option<&str>, drift: &Drift, repairs: Option<&Repairs>) -> String {
+    let mut lines = vec![t(lang, "reconcile-title")];
+    if let Some(server) = server {
+        let args = HashMap::from([("server".to_string(), server.to_string())]);
+        lines.push(t_args(lang, "reconcile-server", &args));
+    }
+    let vanished: Vec<&str> = drift
+        .vanished
+        .iter()
+        .map(|(_, name)| name.as_str())
+        .collect();
+    let banned: Vec<&str> = drift.banned.iter().map(|(_, name)| name.as_str()).collect();
+    let unlinked: Vec<&str> = drift.unlinked.iter().map(String::as_str).collect();
+    for (key, names) in [
+        ("reconcile-vanished", vanished),
+        ("reconcile-banned", banned),
+        ("reconcile-unlinked", unlinked),
+    ] {
+        if !names.is_empty() {
+            lines.push(section(lang, key, &names));
+        }
+    }
+    lines.push(repairs.map_or_else(
+        || t(lang, "reconcile-report-only"),
+        |repairs| {
+            let args = HashMap::from([
+                ("removed".to_string(), repairs.removed.to_string()),
+                ("deleted".to_string(), repairs.deleted.to_string()),
+                ("failed".to_string(), repairs.failed.to_string()),
+            ]);
+            t_args(lang, "reconcile-repaired", &args)
+        },
+    ));
+    lines.join("\n\n")
+}
+
+fn section(lang: &str, key: &str, names: &[&str]) -> String {
+    let mut listed = names
+        .iter()
+        .take(DIGEST_NAMES)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if names.len() > DIGEST_NAMES {
+        listed.push_str(", …");
+    }
+    let args = HashMap::from([
+        ("count".to_string(), names.len().to_string()),
+        ("names".to_string(), listed),
+    ]);
+    t_args(lang, key, &args)
+}
@@ -1,14 +1,17 @@
-use super::{HandlerResult, MyDialogue, State};
-use crate::config::AppConfig;
+use super::{HandlerResult, MyDialogue, State, tt_error_text, user_lang};
+use crate::config::{AppConfig, ClientTemplate};
 use crate::db::Database;
 use crate::domain::{Nickname, Password, Username};
 use crate::i18n::{t, t_args};
 use crate::services::registration;
-use crate::types::{LanguageCode, RegistrationSource, TTAccountType, TTWorkerCommand, TelegramId};
+use crate::tg_bot::notify::AdminChats;
+use crate::tt::{TTServer, TTServers};
+use crate::types::{
+    LanguageCode, NotificationKind, RegistrationSource, SourceInfo, SourceKind, TTAccountType,
+    TTCommandError, TTWorkerCommand, TelegramId,
+};
 use std::collections::HashMap;
-use std::fmt::Write as _;
 use std::sync::Arc;
-use std::sync::mpsc::Sender;
 use teloxide::prelude::*;
 use teloxide::types::{ChatId, InputFile};
 use tracing::{debug, error, trace, warn};
@@ -25,6 +28,7 @@ pub async fn start(
     dialogue: MyDialogue,
     db: Database,
     config: Arc<AppConfig>,
+    servers: TTServers,
 ) -> HandlerResult {
     let chat_id = TelegramId::new(msg.chat.id.0);
 
@@ -45,6 +49,7 @@ pub async fn start(
     let args: Vec<&str> = text.split_whitespace().collect();
 
     let mut is_deeplink = false;
+    let mut server = None;
     if args.len() > 1 {
         let token = args[1];
         if !config.telegram.telegram_deeplink_registration_enabled {
@@ -53,7 +58,7 @@ pub async fn start(
             return Ok(());
         }
 
-        if let Ok(Some(_token_obj)) = db.get_valid_deeplink(token).await {
+        if let Ok(Some(token_obj)) = db.get_valid_deeplink(token).await {
             if db.is_telegram_registered(chat_id).await.unwrap_or(false) && !is_admin {
                 bot.send_message(
                     msg.chat.id,
@@ -62,9 +67,15 @@ pub async fn start(
                 .await?;
                 return Ok(());
             }
-            db.mark_deeplink_used(token).await?;
+            if !db.consume_deeplink(token).await? {
+                bot.send_message(msg.chat.id, t(initial_lang.as_str(), "deeplink-invalid"))
+                    .await?;
+                return Ok(());
+            }
             debug!(chat_id = %chat_id, "Deeplink used by user");
             is_deeplink = true;
+            // A server removed from the config since is chosen again.
+            server = token_obj.server_id.filter(|id| servers.get(id).is_some());
         } else {
             bot.send_message(msg.chat.id, t(initial_lang.as_str(), "deeplink-invalid"))
                 .await?;
@@ -81,12 +92,7 @@ pub async fn start(
     }
 
     if let Some(lang) = &config.web.force_user_lang {
-        bot.send_message(msg.chat.id, t(lang.as_str(), "username-prompt"))
-            .await?;
-        dialogue
-            .update(State::AwaitingUsername { lang: lang.clone() })
-            .await?;
-        return Ok(());
+        return ask_server(&bot, msg.chat.id, lang.clone(), server, &servers, &dialogue).await;
     }
 
     let start_key = if is_deeplink {
@@ -98,27 +104,126 @@ pub async fn start(
         .reply_markup(crate::tg_bot::keyboards::language_keyboard())
         .await?;
 
-    dialogue.update(State::ChoosingLanguage).await?;
+    dialogue.update(State::ChoosingLanguage { server }).await?;
+    Ok(())
+}
+
+/// Abort the current dialogue from any state and drop the collected data.
+pub async fn cancel(
+    bot: Bot,
+    msg: Message,
+    dialogue: MyDialogue,
+    config: Arc<AppConfig>,
+) -> HandlerResult {
+    let state = dialogue.get().await?;
+    let lang = state
+        .as_ref()
+        .and_then(State::lang)
+        .cloned()
+        .unwrap_or_else(|| user_lang(msg.from.as_ref(), &config));
+    dialogue.exit().await?;
+
+    let key = match state {
+        None | Some(State::Start) => "cancel-nothing",
+        Some(_) => "cancel-done",
+    };
+    debug!(chat_id = msg.chat.id.0, "Dialogue cancelled");
+    bot.send_message(msg.chat.id, t(lang.as_str(), key)).await?;
     Ok(())
 }
 
 /// Handle language selection callback.
-pub async fn receive_language(bot: Bot, q: CallbackQuery, dialogue: MyDialogue) -> HandlerResult {
+pub async fn receive_language(
+    bot: Bot,
+    q: CallbackQuery,
+    dialogue: MyDialogue,
+    db: Database,
+    servers: TTServers,
+) -> HandlerResult {
+    let server = match dialogue.get().await {
+        Ok(Some(State::ChoosingLanguage { server })) => server,
+        Ok(_) => None,
+        Err(e) => {
+            warn!(error = %e, "Failed to read dialogue state (ChoosingLanguage)");
+            None
+        }
+    };
     if let Some(data) = q.data {
         let lang = LanguageCode::parse_or_default(&data.replace("lang_", ""));
+        if let Ok(tg_id) = i64::try_from(q.from.id.0)
+            && let Err(e) = db
+                .set_registration_language(TelegramId::new(tg_id), lang.as_str())
+                .await
+        {
+            warn!(error = %e, "Failed to store user language");
+        }
         bot.answer_callback_query(q.id)
             .text(t(lang.as_str(), "language-set"))
             .await?;
 
-        if let Some(msg) = q.message {
-            bot.send_message(msg.chat().id, t(lang.as_str(), "username-prompt"))
-                .await?;
-        } else {
+        let Some(msg) = q.message else {
             warn!("Language callback missing message");
+            return Ok(());
+        };
+        ask_server(&bot, msg.chat().id, lang, server, &servers, &dialogue).await?;
+    }
+    Ok(())
+}
+
+/// Handle server selection callback.
+pub async fn receive_server(
+    bot: Bot,
+    q: CallbackQuery,
+    dialogue: MyDialogue,
+    servers: TTServers,
+) -> HandlerResult {
+    let Some(State::ChoosingServer { lang }) = (match dialogue.get().await {
+        Ok(state) => state,
+        Err(e) => {
+            warn!(error = %e, "Failed to read dialogue state (ChoosingServer)");
+            return Ok(());
         }
+    }) else {
+        return Ok(());
+    };
+    let server = q
+        .data
+        .as_deref()
+        .and_then(|data| data.strip_prefix("server_"))
+        .and_then(|id| servers.get(id))
+        .map(|server| server.id().to_string());
+    bot.answer_callback_query(q.id).await?;
+    let Some(msg) = q.message else {
+        warn!("Server callback missing message");
+        return Ok(());
+    };
+    ask_server(&bot, msg.chat().id, lang, server, &servers, &dialogue).await
+}
 
-        dialogue.update(State::AwaitingUsername { lang }).await?;
-    }
+/// Ask which server to register on unless it is already known or there is
+/// only one, then move on to the username.
+async fn ask_server(
+    bot: &Bot,
+    chat_id: ChatId,
+    lang: LanguageCode,
+    server: Option<String>,
+    servers: &TTServers,
+    dialogue: &MyDialogue,
+) -> HandlerResult {
+    let server =
+        server.or_else(|| (!servers.has_choice()).then(|| servers.primary().id().to_string()));
+    let Some(server) = server else {
+        bot.send_message(chat_id, t(lang.as_str(), "server-prompt"))
+            .reply_markup(crate::tg_bot::keyboards::server_keyboard(servers))
+            .await?;
+        dialogue.update(State::ChoosingServer { lang }).await?;
+        return Ok(());
+    };
+    bot.send_message(chat_id, t(lang.as_str(), "username-prompt"))
+        .await?;
+    dialogue
+        .update(State::AwaitingUsername { lang, server })
+        .await?;
     Ok(())
 }
 
@@ -127,15 +232,17 @@ pub async fn receive_username(
     bot: Bot,
     msg: Message,
     dialogue: MyDialogue,
-    tx_tt: Sender<TTWorkerCommand>,
+    servers: TTServers,
+    config: Arc<AppConfig>,
 ) -> HandlerResult {
-    let lang = match dialogue.get().await {
-        Ok(Some(State::AwaitingUsername { lang })) => lang,
-        Ok(_) => LanguageCode::default(),
+    let Some(State::AwaitingUsername { lang, server }) = (match dialogue.get().await {
+        Ok(state) => state,
         Err(e) => {
             warn!(error = %e, "Failed to read dialogue state (AwaitingUsername)");
-            LanguageCode::default()
+            return Ok(());
         }
+    }) else {
+        return Ok(());
     };
 
     let Some(username) = Username::parse(msg.text().unwrap_or("")) else {
@@ -143,18 +250,31 @@ pub async fn receive_username(
             .await?;
         return Ok(());
     };
-    let (tx, rx) = tokio::sync::oneshot::channel();
-    if let Err(e) = tx_tt.send(TTWorkerCommand::CheckUserExists {
-        username: username.clone(),
-        resp: tx,
-    }) {
-        error!(error = %e, "Failed to enqueue username check");
-        bot.send_message(msg.chat.id, t(lang.as_str(), "username-check-error"))
-            .await?;
+    let policy = &config.username_policy;
+    if let Err(violation) = policy.check(&username) {
+        bot.send_message(
+            msg.chat.id,
+            t_args(
+                lang.as_str(),
+                violation.message_key(),
+                &violation.message_args(),
+            ),
+        )
+        .await?;
         return Ok(());
     }
+    let exists = servers
+        .get(&server)
+        .unwrap_or_else(|| servers.primary())
+        .tx_tt
+        .request(|resp| TTWorkerCommand::CheckUserExists {
+            username: username.clone(),
+            matching: policy.matching(),
+            resp,
+        })
+        .await;
 
-    match rx.await {
+    match exists {
         Ok(true) => {
             bot.send_message(msg.chat.id, t(lang.as_str(), "username-taken"))
                 .await?;
@@ -162,7 +282,7 @@ pub async fn receive_username(
         }
         Ok(false) => {}
         Err(e) => {
-            warn!(error = %e, "Failed to receive username check response");
+            error!(error = %e, "Username check request failed");
             bot.send_message(msg.chat.id, t(lang.as_str(), "username-check-error"))
                 .await?;
             return Ok(());
@@ -170,22 +290,40 @@ pub async fn receive_username(
     }
 
     bot.send_message(msg.chat.id, t(lang.as_str(), "password-prompt"))
+        .reply_markup(crate::tg_bot::keyboards::generate_password_keyboard(&t(
+            lang.as_str(),
+            "btn-generate-password",
+        )))
         .await?;
     dialogue
-        .update(State::AwaitingPassword { lang, username })
+        .update(State::AwaitingPassword {
+            lang,
+            server,
+            username,
+        })
         .await?;
     Ok(())
 }
 
 /// Handle password input.
-pub async fn receive_password(bot: Bot, msg: Message, dialogue: MyDialogue) -> HandlerResult {
-    let Some(State::AwaitingPassword { lang, username }) = (match dialogue.get().await {
+pub async fn receive_password(
+    bot: Bot,
+    msg: Message,
+    dialogue: MyDialogue,
+    config: Arc<AppConfig>,
+) -> HandlerResult {
+    let Some(State::AwaitingPassword {
+        lang,
+        server,
+        username,
+    }) = (match dialogue.get().await {
         Ok(state) => state,
         Err(e) => {
             warn!(error = %e, "Failed to read dialogue state (AwaitingPassword)");
             return Ok(());
         }
-    }) else {
+    })
+    else {
         return Ok(());
     };
 
@@ -194,10 +332,79 @@ pub async fn receive_password(bot: Bot, msg: Message, dialogue: MyDialogue) -> H
             .await?;
         return Ok(());
     };
+    if let Err(violation) = config.password_policy.check(&password) {
+        bot.send_message(
+            msg.chat.id,
+            t_args(
+                lang.as_str(),
+                violation.message_key(),
+                &violation.message_args(),
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+    ask_nickname_choice(
+        &bot,
+        msg.chat.id,
+        lang,
+        server,
+        username,
+        password,
+        &dialogue,
+    )
+    .await
+}
+
+/// Generate a strong password for the user instead of asking for one.
+pub async fn generate_password_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    dialogue: MyDialogue,
+    config: Arc<AppConfig>,
+) -> HandlerResult {
+    let Some(State::AwaitingPassword {
+        lang,
+        server,
+        username,
+    }) = (match dialogue.get().await {
+        Ok(state) => state,
+        Err(e) => {
+            warn!(error = %e, "Failed to read dialogue state (AwaitingPassword)");
+            return Ok(());
+        }
+    })
+    else {
+        return Ok(());
+    };
+    bot.answer_callback_query(q.id).await?;
+    let Some(message) = q.message else {
+        return Ok(());
+    };
+    let chat_id = message.chat().id;
+    if let Err(e) = bot.edit_message_reply_markup(chat_id, message.id()).await {
+        warn!(error = %e, "Failed to remove password keyboard");
+    }
+    let password = config.password_policy.generate();
+    let args = HashMap::from([("password".to_string(), password.as_str().to_string())]);
+    bot.send_message(chat_id, t_args(lang.as_str(), "password-generated", &args))
+        .await?;
+    ask_nickname_choice(&bot, chat_id, lang, server, username, password, &dialogue).await
+}
+
+async fn ask_nickname_choice(
+    bot: &Bot,
+    chat_id: ChatId,
+    lang: LanguageCode,
+    server: String,
+    username: Username,
+    password: Password,
+    dialogue: &MyDialogue,
+) -> HandlerResult {
     let args = HashMap::from([("username".to_string(), username.as_str().to_string())]);
 
     bot.send_message(
-        msg.chat.id,
+        chat_id,
         t_args(lang.as_str(), "nickname-prompt-choice", &args),
     )
     .reply_markup(crate::tg_bot::keyboards::nickname_choice_keyboard(
@@ -209,6 +416,7 @@ pub async fn receive_password(bot: Bot, msg: Message, dialogue: MyDialogue) -> H
     dialogue
         .update(State::AwaitingNicknameChoice {
             lang,
+            server,
             username,
             password,
         })
@@ -221,12 +429,13 @@ pub async fn receive_nickname_choice(
     bot: Bot,
     q: CallbackQuery,
     dialogue: MyDialogue,
-    tx_tt: Sender<TTWorkerCommand>,
+    servers: TTServers,
     db: Database,
     config: Arc<AppConfig>,
 ) -> HandlerResult {
     let Some(State::AwaitingNicknameChoice {
         lang,
+        server,
         username,
         password,
     }) = (match dialogue.get().await {
@@ -255,6 +464,7 @@ pub async fn receive_nickname_choice(
         dialogue
             .update(State::AwaitingNickname {
                 lang,
+                server,
                 username,
                 password,
             })
@@ -267,13 +477,9 @@ pub async fn receive_nickname_choice(
                 dialogue.exit().await?;
                 return Ok(());
             };
-            if config
-                .telegram
-                .admin_ids
-                .contains(&TelegramId::new(msg.chat().id.0))
-            {
-                ask_account_type(bot, msg.chat().id, lang, username, password, nick, dialogue)
-                    .await?;
+            if let Some(ask) = final_question(&config, msg.chat().id) {
+                let next = ask(lang, server, username, password, nick);
+                ask_account_type(&bot, msg.chat().id, next, &dialogue, &config).await?;
                 return Ok(());
             }
             handle_registration_end_with_type(RegistrationEndInput {
@@ -284,7 +490,8 @@ pub async fn receive_nickname_choice(
                 password,
                 nickname: nick,
                 account_type: TTAccountType::Default,
-                tx_tt,
+                rights_profile: None,
+                server: registration_server(&servers, &server),
                 db,
                 config,
             })
@@ -308,12 +515,13 @@ pub async fn receive_nickname(
     bot: Bot,
     msg: Message,
     dialogue: MyDialogue,
-    tx_tt: Sender<TTWorkerCommand>,
+    servers: TTServers,
     db: Database,
     config: Arc<AppConfig>,
 ) -> HandlerResult {
     let Some(State::AwaitingNickname {
         lang,
+        server,
         username,
         password,
     }) = (match dialogue.get().await {
@@ -332,21 +540,9 @@ pub async fn receive_nickname(
             .await?;
         return Ok(());
     };
-    if config
-        .telegram
-        .admin_ids
-        .contains(&TelegramId::new(msg.chat.id.0))
-    {
-        ask_account_type(
-            bot,
-            msg.chat.id,
-            lang,
-            username,
-            password,
-            nickname,
-            dialogue,
-        )
-        .await?;
+    if let Some(ask) = final_question(&config, msg.chat.id) {
+        let next = ask(lang, server, username, password, nickname);
+        ask_account_type(&bot, msg.chat.id, next, &dialogue, &config).await?;
         return Ok(());
     }
     handle_registration_end_with_type(RegistrationEndInput {
@@ -357,7 +553,8 @@ pub async fn receive_nickname(
         password,
         nickname,
         account_type: TTAccountType::Default,
-        tx_tt,
+        rights_profile: None,
+        server: registration_server(&servers, &server),
         db,
         config,
     })
@@ -366,29 +563,36 @@ pub async fn receive_nickname(
     Ok(())
 }
 
-/// Handle account type selection callback for admin registrations.
+/// Handle the account type choice of admins and the rights profile choice
+/// of users.
 pub async fn receive_account_type(
     bot: Bot,
     q: CallbackQuery,
     dialogue: MyDialogue,
-    tx_tt: Sender<TTWorkerCommand>,
+    servers: TTServers,
     db: Database,
     config: Arc<AppConfig>,
 ) -> HandlerResult {
-    let Some(State::AwaitingAccountType {
-        lang,
-        username,
-        password,
-        nickname,
-    }) = (match dialogue.get().await {
-        Ok(state) => state,
+    let (lang, server, username, password, nickname, picks_profile) = match dialogue.get().await {
+        Ok(Some(State::AwaitingAccountType {
+            lang,
+            server,
+            username,
+            password,
+            nickname,
+        })) => (lang, server, username, password, nickname, false),
+        Ok(Some(State::AwaitingRightsProfile {
+            lang,
+            server,
+            username,
+            password,
+            nickname,
+        })) => (lang, server, username, password, nickname, true),
+        Ok(_) => return Ok(()),
         Err(e) => {
             warn!(error = %e, "Failed to read dialogue state (AwaitingAccountType)");
             return Ok(());
         }
-    })
-    else {
-        return Ok(());
     };
 
     let data = q.data.clone().unwrap_or_default();
@@ -398,11 +602,15 @@ pub async fn receive_account_type(
     }
     bot.answer_callback_query(q.id).await?;
 
-    let account_type = if data == "acct_admin" {
+    let account_type = if !picks_profile && data == "acct_admin" {
         TTAccountType::Admin
     } else {
         TTAccountType::Default
     };
+    let rights_profile = data
+        .strip_prefix("profile_")
+        .filter(|name| picks_profile && config.is_requestable_profile(name))
+        .map(str::to_string);
 
     if let Some(msg) = q.message {
         handle_registration_end_with_type(RegistrationEndInput {
@@ -413,7 +621,8 @@ pub async fn receive_account_type(
             password,
             nickname,
             account_type,
-            tx_tt,
+            rights_profile,
+            server: registration_server(&servers, &server),
             db,
             config,
         })
@@ -425,6 +634,47 @@ pub async fn receive_account_type(
     Ok(())
 }
 
+/// Builds the state of the last question before registering.
+type FinalQuestion = fn(LanguageCode, String, Username, Password, Nickname) -> State;
+
+/// Admins pick the account type; users pick a rights profile when they may
+/// request one. `None` when there is nothing left to ask.
+fn final_question(config: &AppConfig, chat_id: ChatId) -> Option<FinalQuestion> {
+    if config
+        .telegram
+        .admin_ids
+        .contains(&TelegramId::new(chat_id.0))
+    {
+        return Some(
+            |lang, server, username, password, nickname| State::AwaitingAccountType {
+                lang,
+                server,
+                username,
+                password,
+                nickname,
+            },
+        );
+    }
+    if config.teamtalk.requestable_rights_profiles.is_empty() {
+        return None;
+    }
+    Some(
+        |lang, server, username, password, nickname| State::AwaitingRightsProfile {
+            lang,
+            server,
+            username,
+            password,
+            nickname,
+        },
+    )
+}
+
+/// The server a dialogue picked; it is looked up again because the dialogue
+/// only keeps its id.
+fn registration_server(servers: &TTServers, id: &str) -> TTServer {
+    servers.get(id).unwrap_or_else(|| servers.primary()).clone()
+}
+
 struct RegistrationEndInput {
     bot: Bot,
     chat_id: ChatId,
@@ -433,7 +683,9 @@ struct RegistrationEndInput {
     password: Password,
     nickname: Nickname,
     account_type: TTAccountType,
-    tx_tt: Sender<TTWorkerCommand>,
+    /// Profile the user asked for.
+    rights_profile: Option<String>,
+    server: TTServer,
     db: Database,
     config: Arc<AppConfig>,
 }
@@ -447,7 +699,8 @@ async fn handle_registration_end_with_type(input: RegistrationEndInput) -> Handl
         password,
         nickname,
         account_type,
-        tx_tt,
+        rights_profile,
+        server,
         db,
         config,
     } = input;
@@ -464,6 +717,8 @@ async fn handle_registration_end_with_type(input: RegistrationEndInput) -> Handl
             username: &username,
             password: &password,
             nickname: &nickname,
+            rights_profile: rights_profile.as_deref(),
+            server: &server,
             db: &db,
             config: &config,
         })
@@ -479,9 +734,9 @@ async fn handle_registration_end_with_type(input: RegistrationEndInput) -> Handl
         password: &password,
         nickname: &nickname,
         account_type,
-        tx_tt,
+        rights_profile: rights_profile.as_deref(),
+        server: &server,
         db: &db,
-        config: &config,
     })
     .await?;
     Ok(())
@@ -494,6 +749,8 @@ struct AdminVerificationInput<'a> {
     username: &'a Username,
     password: &'a Password,
     nickname: &'a Nickname,
+    rights_profile: Option<&'a str>,
+    server: &'a TTServer,
     db: &'a Database,
     config: &'a AppConfig,
 }
@@ -506,9 +763,9 @@ struct DirectRegistrationInput<'a> {
     password: &'a Password,
     nickname: &'a Nickname,
     account_type: TTAccountType,
-    tx_tt: Sender<TTWorkerCommand>,
+    rights_profile: Option<&'a str>,
+    server: &'a TTServer,
     db: &'a Database,
-    config: &'a AppConfig,
 }
 
 async fn handle_admin_verification(input: AdminVerificationInput<'_>) -> HandlerResult {
@@ -519,17 +776,21 @@ async fn handle_admin_verification(input: AdminVerificationInput<'_>) -> Handler
         username,
         password,
         nickname,
+        rights_profile,
+        server,
         db,
         config,
     } = input;
     let request_id = Uuid::new_v4().to_string();
     let (fullname, tg_username) = fetch_user_info(bot, chat_id).await;
-    let source_info = format!(
-        "lang={};tg_username={};fullname={}",
-        lang.as_str(),
-        tg_username,
-        fullname
-    );
+    let source_info = SourceInfo {
+        telegram_id: Some(TelegramId::new(chat_id.0)),
+        tg_username: Some(tg_username.clone()).filter(|name| !name.is_empty()),
+        fullname: Some(fullname.clone()).filter(|name| !name.is_empty()),
+        server: Some(server.id().to_string()),
+        rights_profile: rights_profile.map(str::to_string),
+        ..SourceInfo::new(SourceKind::Telegram, lang.clone())
+    };
     if db
         .add_pending_registration(
             &request_id,
@@ -553,28 +814,32 @@ async fn handle_admin_verification(input: AdminVerificationInput<'_>) -> Handler
     let admin_lang = config.telegram.bot_admin_lang.clone();
     let text = build_admin_request_text(
         admin_lang.as_str(),
-        chat_id,
-        username,
-        nickname,
-        &fullname,
-        &tg_username,
+        &TelegramApprovalRequest {
+            chat_id,
+            username,
+            nickname,
+            fullname: &fullname,
+            tg_username: &tg_username,
+            server: server.config.server_label(),
+            rights_profile,
+        },
     );
 
     let keyboard = crate::tg_bot::keyboards::admin_approval_keyboard(
-        &t(admin_lang.as_str(), "btn-admin-verify"),
-        &t(admin_lang.as_str(), "btn-admin-reject"),
+        &super::approval_labels(admin_lang.as_str()),
         &request_id,
+        &super::rights_profile_buttons(config),
     );
 
-    for &admin_id in &config.telegram.admin_ids {
-        if let Err(e) = bot
-            .send_message(ChatId(admin_id.as_i64()), &text)
-            .reply_markup(keyboard.clone())
-            .await
-        {
-            warn!(error = %e, admin_id = %admin_id, "Failed to send admin approval message");
-        }
-    }
+    AdminChats::new(&config.telegram, db)
+        .send(
+            bot,
+            NotificationKind::Approvals,
+            &text,
+            Some(&keyboard),
+            None,
+        )
+        .await;
 
     Ok(())
 }
@@ -588,18 +853,19 @@ async fn handle_direct_registration(input: DirectRegistrationInput<'_>) -> Handl
         password,
         nickname,
         account_type,
-        tx_tt,
+        rights_profile,
+        server,
         db,
-        config,
     } = input;
+    let config = &*server.config;
     let (tg_fullname, tg_username) = fetch_user_info(bot, chat_id).await;
-    let mut source_info = format!("Telegram ID: {}", chat_id.0);
-    if !tg_username.is_empty() {
-        let _ = write!(&mut source_info, ", username: @{tg_username}");
-    }
-    if !tg_fullname.is_empty() {
-        let _ = write!(&mut source_info, ", name: {tg_fullname}");
-    }
+    let source_info = SourceInfo {
+        telegram_id: Some(TelegramId::new(chat_id.0)),
+        tg_username: Some(tg_username).filter(|name| !name.is_empty()),
+        fullname: Some(tg_fullname).filter(|name| !name.is_empty()),
+        server: Some(server.id().to_string()),
+        ..SourceInfo::new(SourceKind::Telegram, lang.clone())
+    };
 
     let result = registration::create_teamtalk_account(registration::CreateAccountParams {
         username,
@@ -609,20 +875,34 @@ async fn handle_direct_registration(input: DirectRegistrationInput<'_>) -> Handl
         source: RegistrationSource::Telegram(TelegramId::new(chat_id.0)),
         source_info: Some(source_info),
         telegram_id: Some(TelegramId::new(chat_id.0)),
-        tx_tt,
+        language: Some(&lang),
+        queued_id: None,
+        rights_profile,
+        tx_tt: server.tx_tt.clone(),
         db,
         config,
     })
     .await?;
 
-    if !result.created {
-        bot.send_message(chat_id, t(lang.as_str(), "register-error"))
+    if result.queued {
+        let args = HashMap::from([("username".to_string(), username.as_str().to_string())]);
+        bot.send_message(chat_id, t_args(lang.as_str(), "register-queued", &args))
             .await?;
         return Ok(());
     }
+    if !result.created {
+        let text = match &result.error {
+            Some(err @ (TTCommandError::AccountExists | TTCommandError::InvalidUsername)) => {
+                tt_error_text(lang.as_str(), err)
+            }
+            _ => t(lang.as_str(), "register-error"),
+        };
+        bot.send_message(chat_id, text).await?;
+        return Ok(());
+    }
 
     if let Some(err) = result.db_sync_error {
-        notify_db_sync_error(bot, config, chat_id, username.as_str(), &err).await;
+        notify_db_sync_error(bot, db, config, chat_id, username.as_str(), &err).await;
         if let Err(e) = bot
             .send_message(chat_id, t(lang.as_str(), "register-success-db-sync-issue"))
             .await
@@ -654,7 +934,7 @@ async fn handle_direct_registration(input: DirectRegistrationInput<'_>) -> Handl
 async fn fetch_user_info(bot: &Bot, chat_id: ChatId) -> (String, String) {
     match bot.get_chat(chat_id).await {
         Ok(u) => {
-            let first = u.first_name().unwrap_or("Unknown");
+            let first = u.first_name().unwrap_or_default();
             let last = u.last_name().unwrap_or("");
             let fullname = if last.is_empty() {
                 first.to_string()
@@ -666,42 +946,66 @@ async fn fetch_user_info(bot: &Bot, chat_id: ChatId) -> (String, String) {
         }
         Err(e) => {
             warn!(error = %e, "Failed to fetch Telegram user info");
-            ("Unknown".to_string(), String::new())
+            (String::new(), String::new())
         }
     }
 }
 
-fn build_admin_request_text(
-    lang: &str,
+/// A Telegram registration as shown to admins for review.
+struct TelegramApprovalRequest<'a> {
     chat_id: ChatId,
-    username: &Username,
-    nickname: &Nickname,
-    fullname: &str,
-    tg_username: &str,
-) -> String {
+    username: &'a Username,
+    nickname: &'a Nickname,
+    fullname: &'a str,
+    tg_username: &'a str,
+    /// Set when there are several servers to choose from.
+    server: Option<&'a str>,
+    rights_profile: Option<&'a str>,
+}
+
+fn build_admin_request_text(lang: &str, request: &TelegramApprovalRequest<'_>) -> String {
     let mut text = String::new();
     text.push_str(&t(lang, "admin-request-title"));
     text.push('\n');
     text.push_str(&t(lang, "admin-request-username"));
     text.push(' ');
-    text.push_str(username.as_str());
+    text.push_str(request.username.as_str());
     text.push('\n');
-    if nickname.as_str() != username.as_str() {
+    if request.nickname.as_str() != request.username.as_str() {
         text.push_str(&t(lang, "admin-request-nickname"));
         text.push(' ');
-        text.push_str(nickname.as_str());
+        text.push_str(request.nickname.as_str());
+        text.push('\n');
+    }
+    if let Some(server) = request.server {
+        text.push_str(&t(lang, "admin-request-server"));
+        text.push(' ');
+        text.push_str(server);
         text.push('\n');
     }
+    if let Some(profile) = request.rights_profile {
+        text.push_str(&t(lang, "admin-request-rights-profile"));
+        text.push(' ');
+        text.push_str(profile);
+        text.push('\n');
+    }
+    text.push_str(&t(lang, "admin-request-source"));
+    text.push(' ');
+    text.push_str(&t(lang, "admin-request-source-telegram"));
+    text.push('\n');
 
-    let mut tg_line = String::new();
-    tg_line.push_str(fullname);
-    if !tg_username.is_empty() {
+    let mut tg_line = if request.fullname.is_empty() {
+        t(lang, "value-unknown")
+    } else {
+        request.fullname.to_string()
+    };
+    if !request.tg_username.is_empty() {
         tg_line.push_str(" (@");
-        tg_line.push_str(tg_username);
+        tg_line.push_str(request.tg_username);
         tg_line.push(')');
     }
     tg_line.push_str(" (ID: ");
-    tg_line.push_str(&chat_id.0.to_string());
+    tg_line.push_str(&request.chat_id.0.to_string());
     tg_line.push(')');
     text.push_str(&t(lang, "admin-request-telegram-user"));
     text.push(' ');
@@ -743,6 +1047,16 @@ pub(super) async fn send_registration_assets(
         bot.send_message(chat_id, t(lang, "file-send-error"))
             .await?;
     }
+    if let Some(png) = registration::link_qr_png(&assets.link) {
+        let photo = InputFile::memory(png).file_name("tt_link.png");
+        if let Err(e) = bot
+            .send_photo(chat_id, photo)
+            .caption(t(lang, "qr-caption"))
+            .await
+        {
+            warn!(error = %e, "Failed to send TT link QR code");
+        }
+    }
 
     let public_host = config
         .teamtalk
@@ -764,74 +1078,116 @@ pub(super) async fn send_registration_assets(
     bot.send_message(chat_id, host_msg).await?;
     bot.send_message(chat_id, port_msg).await?;
 
-    let zip_filename = format!("{username}_TeamTalk.zip");
-    let zip_path = registration::temp_dir().join(&zip_filename);
-    if registration::try_create_client_zip_async(config, &zip_path, assets).await
-        && let Ok(metadata) = tokio::fs::metadata(&zip_path).await
-    {
-        let size_mb = metadata.len() / 1_048_576;
-        if size_mb < 49 {
-            let file_zip = InputFile::file(zip_path.clone()).file_name(zip_filename);
-            if let Err(e) = bot.send_document(chat_id, file_zip).await {
-                error!(error = %e, "Failed to send ZIP");
+    send_client_zips(bot, chat_id, lang, config, username, assets).await;
+    Ok(())
+}
+
+/// Send one client ZIP per template, noting how the archives are protected.
+async fn send_client_zips(
+    bot: &Bot,
+    chat_id: ChatId,
+    lang: &str,
+    config: &AppConfig,
+    username: &str,
+    assets: &registration::RegistrationAssets,
+) {
+    let password_note = registration::zip_password_note(config, lang, assets);
+    let templates = registration::client_templates(config);
+    for template in &templates {
+        let zip_filename = registration::client_zip_filename(config, template, username);
+        let zip_path = config.get_temp_dir().join(&zip_filename);
+        if let Some((size, file_zip)) = build_client_zip(config, template, &zip_path, assets).await
+        {
+            let size_mb = size / 1_048_576;
+            if size_mb < 49 {
+                let label = (templates.len() > 1).then(|| template.label());
+                let caption: Vec<&str> =
+                    label.into_iter().chain(password_note.as_deref()).collect();
+                let mut request =
+                    bot.send_document(chat_id, file_zip.file_name(zip_filename.clone()));
+                if !caption.is_empty() {
+                    request = request.caption(caption.join("\n"));
+                }
+                if let Err(e) = request.await {
+                    error!(error = %e, template = %template.name, "Failed to send ZIP");
+                }
+            } else {
+                warn!(size_mb, template = %template.name, "ZIP too big, skipping upload");
             }
-        } else {
-            warn!(size_mb, "ZIP too big, skipping upload");
         }
+        registration::remove_generated_file(config.get_temp_dir(), &zip_filename).await;
     }
+}
 
-    Ok(())
+/// Build a client ZIP from `template` in memory, or at `zip_path` for
+/// templates too large to zip on the fly; returns its size and the file to
+/// upload.
+async fn build_client_zip(
+    config: &AppConfig,
+    template: &ClientTemplate,
+    zip_path: &std::path::Path,
+    assets: &registration::RegistrationAssets,
+) -> Option<(u64, InputFile)> {
+    if registration::client_zip_streamable(config, template).await {
+        let bytes = registration::client_zip_bytes_async(config, template, assets).await?;
+        return Some((bytes.len() as u64, InputFile::memory(bytes)));
+    }
+    if !registration::try_create_client_zip_async(config, template, zip_path, assets).await {
+        return None;
+    }
+    let metadata = tokio::fs::metadata(zip_path).await.ok()?;
+    Some((metadata.len(), InputFile::file(zip_path.to_path_buf())))
 }
 
 pub(super) async fn notify_db_sync_error(
     bot: &Bot,
+    db: &Database,
     config: &AppConfig,
     chat_id: ChatId,
     username: &str,
     err: &str,
 ) {
-    for &admin_id in &config.telegram.admin_ids {
-        if admin_id.as_i64() != chat_id.0
-            && let Err(e) = bot
-                .send_message(
-                    ChatId(admin_id.as_i64()),
-                    format!(
-                        "DB SYNC ERROR (Exception): User {username} (TG ID: {}) created in TeamTalk but FAILED local DB save. Exception: {err}",
-                        chat_id.0
-                    ),
-                )
-                .await
-        {
-            warn!(error = %e, admin_id = %admin_id, "Failed to send DB sync error to admin");
-        }
-    }
+    let admins = AdminChats::new(&config.telegram, db);
+    let args = HashMap::from([
+        ("username".to_string(), username.to_string()),
+        ("tg_id".to_string(), chat_id.0.to_string()),
+        ("error".to_string(), err.to_string()),
+    ]);
+    let text = t_args(admins.lang(), "admin-db-sync-error", &args);
+    admins
+        .send(bot, NotificationKind::Alert, &text, None, Some(chat_id))
+        .await;
 }
 async fn ask_account_type(
-    bot: Bot,
+    bot: &Bot,
     chat_id: ChatId,
-    lang: LanguageCode,
-    username: Username,
-    password: Password,
-    nickname: Nickname,
-    dialogue: MyDialogue,
+    next: State,
+    dialogue: &MyDialogue,
+    config: &AppConfig,
 ) -> HandlerResult {
-    let args = HashMap::from([("username".to_string(), username.as_str().to_string())]);
-    bot.send_message(
-        chat_id,
-        t_args(lang.as_str(), "tt-account-type-prompt", &args),
-    )
-    .reply_markup(crate::tg_bot::keyboards::admin_account_type_keyboard(
-        &t(lang.as_str(), "tt-account-admin"),
-        &t(lang.as_str(), "tt-account-user"),
-    ))
-    .await?;
-    dialogue
-        .update(State::AwaitingAccountType {
-            lang,
-            username,
-            password,
-            nickname,
-        })
+    let (prompt, keyboard) = match &next {
+        State::AwaitingAccountType { lang, username, .. } => {
+            let args = HashMap::from([("username".to_string(), username.as_str().to_string())]);
+            (
+                t_args(lang.as_str(), "tt-account-type-prompt", &args),
+                crate::tg_bot::keyboards::admin_account_type_keyboard(
+                    &t(lang.as_str(), "tt-account-admin"),
+                    &t(lang.as_str(), "tt-account-user"),
+                ),
+            )
+        }
+        State::AwaitingRightsProfile { lang, .. } => (
+            t(lang.as_str(), "rights-profile-prompt"),
+            crate::tg_bot::keyboards::rights_profile_keyboard(
+                &t(lang.as_str(), "rights-profile-default"),
+                &config.teamtalk.requestable_rights_profiles,
+            ),
+        ),
+        _ => return Ok(()),
+    };
+    bot.send_message(chat_id, prompt)
+        .reply_markup(keyboard)
         .await?;
+    dialogue.update(next).await?;
     Ok(())
 }
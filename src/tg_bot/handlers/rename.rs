@@ -0,0 +1,192 @@
+use super::web_approval::answer_already_handled;
+use super::{HandlerResult, MyDialogue, RenameTarget, State};
+use crate::config::AppConfig;
+use crate::db::Database;
+use crate::domain::Username;
+use crate::i18n::{t, t_args};
+use crate::services::audit::{self, Actor, AuditAction};
+use crate::tg_bot::keyboards::{admin_approval_keyboard, admin_web_approval_keyboard};
+use crate::tt::TTServers;
+use crate::types::{LanguageCode, SourceInfo, TTWorkerCommand, TelegramId, WebRequestStatus};
+use std::collections::HashMap;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use tracing::{error, warn};
+
+/// Ask the admin for the username a pending request should get instead.
+pub(super) async fn prompt_rename(
+    bot: &Bot,
+    q: &CallbackQuery,
+    db: &Database,
+    lang: &LanguageCode,
+    dialogue: &MyDialogue,
+    target: RenameTarget,
+) -> HandlerResult {
+    let request = load_request(db, &target).await?;
+    let (Some((username, _)), Some(message)) = (request, &q.message) else {
+        return answer_already_handled(bot, q, lang).await;
+    };
+    bot.answer_callback_query(q.id.clone()).await?;
+    let args = HashMap::from([("username".to_string(), username)]);
+    bot.send_message(
+        message.chat().id,
+        t_args(lang.as_str(), "admin-rename-prompt", &args),
+    )
+    .await?;
+    dialogue
+        .update(State::AwaitingApprovalUsername {
+            target,
+            message_id: message.id(),
+        })
+        .await?;
+    Ok(())
+}
+
+/// Give the pending request the username the admin typed, then offer the
+/// approval buttons again.
+pub async fn admin_rename_input(
+    bot: Bot,
+    msg: Message,
+    db: Database,
+    config: Arc<AppConfig>,
+    dialogue: MyDialogue,
+    servers: TTServers,
+) -> HandlerResult {
+    let Some(State::AwaitingApprovalUsername { target, message_id }) = (match dialogue.get().await {
+        Ok(state) => state,
+        Err(e) => {
+            warn!(error = %e, "Failed to read dialogue state (AwaitingApprovalUsername)");
+            return Ok(());
+        }
+    }) else {
+        return Ok(());
+    };
+    // In a shared admin chat anyone can post; only an admin's reply counts.
+    let Some(admin_id) = msg
+        .from
+        .as_ref()
+        .and_then(|user| i64::try_from(user.id.0).ok())
+        .map(TelegramId::new)
+        .filter(|id| config.telegram.admin_ids.contains(id))
+    else {
+        return Ok(());
+    };
+    let lang = config.telegram.bot_admin_lang.as_str();
+    let Some(username) = Username::parse(msg.text().unwrap_or("")) else {
+        bot.send_message(msg.chat.id, t(lang, "username-empty-error"))
+            .await?;
+        return Ok(());
+    };
+    if let Err(violation) = config.username_policy.check(&username) {
+        bot.send_message(
+            msg.chat.id,
+            t_args(lang, violation.message_key(), &violation.message_args()),
+        )
+        .await?;
+        return Ok(());
+    }
+    let Some((old_username, source_info)) = load_request(&db, &target).await? else {
+        dialogue.update(State::AdminPanel).await?;
+        bot.send_message(msg.chat.id, t(lang, "admin-req-handled"))
+            .await?;
+        return Ok(());
+    };
+    if let Some(key) = taken_error(&config, &servers, &source_info, &username).await {
+        bot.send_message(msg.chat.id, t(lang, key)).await?;
+        return Ok(());
+    }
+
+    let renamed = match &target {
+        RenameTarget::Telegram(id) => {
+            db.rename_pending_registration(id, username.as_str())
+                .await?
+        }
+        RenameTarget::Web(id) => {
+            db.rename_pending_web_registration(id, username.as_str())
+                .await?
+        }
+    };
+    dialogue.update(State::AdminPanel).await?;
+    if !renamed {
+        bot.send_message(msg.chat.id, t(lang, "admin-req-handled"))
+            .await?;
+        return Ok(());
+    }
+    let details = format!("Renamed to {}", username.as_str());
+    audit::record(
+        &db,
+        Actor::Admin(admin_id),
+        AuditAction::RenameRequest,
+        &old_username,
+        Some(&details),
+    )
+    .await;
+
+    // The buttons move to the confirmation so they are next to the new name.
+    if let Err(e) = bot.edit_message_reply_markup(msg.chat.id, message_id).await {
+        warn!(error = %e, "Failed to remove buttons from admin request message");
+    }
+    let labels = super::approval_labels(lang);
+    let profiles = super::rights_profile_buttons(&config);
+    let keyboard = match &target {
+        RenameTarget::Telegram(id) => admin_approval_keyboard(&labels, id, &profiles),
+        RenameTarget::Web(id) => admin_web_approval_keyboard(&labels, id, &profiles),
+    };
+    let args = HashMap::from([
+        ("old_username".to_string(), old_username),
+        ("username".to_string(), username.as_str().to_string()),
+    ]);
+    bot.send_message(msg.chat.id, t_args(lang, "admin-rename-done", &args))
+        .reply_markup(keyboard)
+        .await?;
+    Ok(())
+}
+
+/// Username and `source_info` of a request that is still undecided.
+async fn load_request(
+    db: &Database,
+    target: &RenameTarget,
+) -> Result<Option<(String, SourceInfo)>, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(match target {
+        RenameTarget::Telegram(id) => db
+            .get_pending_registration(id)
+            .await?
+            .map(|req| (req.username, req.source_info)),
+        RenameTarget::Web(id) => db
+            .get_pending_web_registration(id)
+            .await?
+            .filter(|req| req.status == WebRequestStatus::Pending.as_str())
+            .map(|req| (req.username, req.source_info)),
+    })
+}
+
+/// Message key explaining why `username` cannot be used on the request's
+/// server, if it cannot.
+async fn taken_error(
+    config: &AppConfig,
+    servers: &TTServers,
+    source_info: &SourceInfo,
+    username: &Username,
+) -> Option<&'static str> {
+    let server = &source_info.server;
+    let Some(server) = servers.resolve(server.as_deref()) else {
+        warn!(server = ?server, "Pending registration names an unknown server");
+        return Some("username-check-error");
+    };
+    match server
+        .tx_tt
+        .request(|resp| TTWorkerCommand::CheckUserExists {
+            username: username.clone(),
+            matching: config.username_policy.matching(),
+            resp,
+        })
+        .await
+    {
+        Ok(false) => None,
+        Ok(true) => Some("username-taken"),
+        Err(e) => {
+            error!(error = %e, "TeamTalk user existence check failed");
+            Some("username-check-error")
+        }
+    }
+}
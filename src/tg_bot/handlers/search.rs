@@ -0,0 +1,177 @@
+use super::{HandlerResult, MyDialogue, State};
+use crate::config::AppConfig;
+use crate::db::Database;
+use crate::i18n::{t, t_args};
+use crate::tt::TTWorkerHandle;
+use crate::types::{LanguageCode, TTWorkerCommand};
+use std::collections::HashMap;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+use tracing::warn;
+
+/// Results shown per section; the rest are only counted.
+const MAX_RESULTS: usize = 10;
+/// Longest search fragment accepted, in characters.
+const MAX_QUERY_CHARS: usize = 32;
+
+/// Ask the admin for a search fragment.
+pub(super) async fn prompt_search(
+    bot: &Bot,
+    msg: &Message,
+    lang: &LanguageCode,
+    dialogue: &MyDialogue,
+) -> HandlerResult {
+    bot.send_message(msg.chat.id, t(lang.as_str(), "admin-search-prompt"))
+        .await?;
+    dialogue.update(State::AwaitingSearch).await?;
+    Ok(())
+}
+
+/// Search registrations, bans and `TeamTalk` accounts for the entered fragment.
+pub async fn admin_search_input(
+    bot: Bot,
+    msg: Message,
+    db: Database,
+    config: Arc<AppConfig>,
+    dialogue: MyDialogue,
+    tx_tt: TTWorkerHandle,
+) -> HandlerResult {
+    let lang = &config.telegram.bot_admin_lang;
+    let query: String = msg
+        .text()
+        .unwrap_or("")
+        .trim()
+        .chars()
+        .take(MAX_QUERY_CHARS)
+        .collect::<String>()
+        .to_lowercase();
+    if query.is_empty() {
+        bot.send_message(msg.chat.id, t(lang.as_str(), "admin-search-prompt"))
+            .await?;
+        return Ok(());
+    }
+    let matches = |value: &str| value.to_lowercase().contains(&query);
+
+    let registrations: Vec<(i64, String)> = db
+        .get_all_registrations()
+        .await?
+        .into_iter()
+        .filter(|r| {
+            matches(&r.telegram_id.to_string())
+                || matches(&r.teamtalk_username)
+                || r.nickname.as_deref().is_some_and(matches)
+        })
+        .map(|r| (r.telegram_id.as_i64(), r.teamtalk_username))
+        .collect();
+    let banned: Vec<(i64, String)> = db
+        .get_all_banned_users()
+        .await?
+        .into_iter()
+        .filter(|b| {
+            matches(&b.telegram_id.to_string())
+                || b.teamtalk_username.as_deref().is_some_and(matches)
+        })
+        .map(|b| {
+            let name = b
+                .teamtalk_username
+                .unwrap_or_else(|| t(lang.as_str(), "value-not-available"));
+            (b.telegram_id.as_i64(), name)
+        })
+        .collect();
+    let accounts = match tx_tt
+        .request(|resp| TTWorkerCommand::GetAllUsers { resp })
+        .await
+    {
+        Ok(users) => Some(users.into_iter().filter(|u| matches(u)).collect::<Vec<_>>()),
+        Err(e) => {
+            warn!(error = %e, "TeamTalk users list request failed");
+            None
+        }
+    };
+
+    let (text, keyboard) = results_view(lang, &query, &registrations, &banned, accounts.as_deref());
+    bot.send_message(msg.chat.id, text)
+        .reply_markup(keyboard)
+        .await?;
+    dialogue.update(State::AdminPanel).await?;
+    Ok(())
+}
+
+fn results_view(
+    lang: &LanguageCode,
+    query: &str,
+    registrations: &[(i64, String)],
+    banned: &[(i64, String)],
+    accounts: Option<&[String]>,
+) -> (String, InlineKeyboardMarkup) {
+    let lang = lang.as_str();
+    let mut lines = vec![t_args(
+        lang,
+        "admin-search-title",
+        &HashMap::from([("query".to_string(), query.to_string())]),
+    )];
+    let mut buttons = Vec::new();
+
+    section(
+        lang,
+        &mut lines,
+        "admin-search-registrations",
+        registrations.len(),
+    );
+    for (tg_id, username) in registrations.iter().take(MAX_RESULTS) {
+        lines.push(format!("TG ID: {tg_id} - TT User: {username}"));
+        buttons.push(vec![InlineKeyboardButton::callback(
+            format!("{} ({username})", t(lang, "btn-delete-user")),
+            format!("admin_del_confirm_{tg_id}"),
+        )]);
+    }
+
+    section(lang, &mut lines, "admin-search-banned", banned.len());
+    for (tg_id, username) in banned.iter().take(MAX_RESULTS) {
+        lines.push(format!("TG ID: {tg_id} - TT User: {username}"));
+        buttons.push(vec![InlineKeyboardButton::callback(
+            format!("{} ({tg_id})", t(lang, "btn-unban")),
+            format!("admin_unban_{tg_id}"),
+        )]);
+    }
+
+    if let Some(accounts) = accounts {
+        section(lang, &mut lines, "admin-search-accounts", accounts.len());
+        for account in accounts.iter().take(MAX_RESULTS) {
+            lines.push(format!("- {account}"));
+            buttons.push(vec![InlineKeyboardButton::callback(
+                format!("{} ({account})", t(lang, "btn-delete-from-tt")),
+                format!("admin_tt_del_prompt_{account}"),
+            )]);
+        }
+    } else {
+        lines.push(String::new());
+        lines.push(t(lang, "admin-tt-list-error"));
+    }
+
+    buttons.push(vec![
+        InlineKeyboardButton::callback(t(lang, "btn-search-again"), "admin_search"),
+        InlineKeyboardButton::callback(t(lang, "btn-back-to-panel"), "cancel_action"),
+    ]);
+    (lines.join("\n"), InlineKeyboardMarkup::new(buttons))
+}
+
+/// Append a section heading with its match count, noting results beyond the limit.
+fn section(lang: &str, lines: &mut Vec<String>, title_key: &str, total: usize) {
+    lines.push(String::new());
+    lines.push(t_args(
+        lang,
+        title_key,
+        &HashMap::from([("count".to_string(), total.to_string())]),
+    ));
+    if total == 0 {
+        lines.push(t(lang, "admin-list-no-matches"));
+    } else if total > MAX_RESULTS {
+        lines.push(t_args(
+            lang,
+            "admin-search-more",
+            &HashMap::from([("count".to_string(), (total - MAX_RESULTS).to_string())]),
+        ));
+    }
+}
@@ -0,0 +1,80 @@
+use super::HandlerResult;
+use crate::config::AppConfig;
+use crate::db::Database;
+use crate::i18n::{t, t_args};
+use crate::tt::TTWorkerHandle;
+use crate::types::{TTWorkerCommand, TelegramId};
+use std::collections::HashMap;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use tracing::warn;
+
+/// Show registration statistics (`/stats`).
+pub async fn stats(
+    bot: Bot,
+    msg: Message,
+    db: Database,
+    config: Arc<AppConfig>,
+    tx_tt: TTWorkerHandle,
+) -> HandlerResult {
+    if !config
+        .telegram
+        .admin_ids
+        .contains(&TelegramId::new(msg.chat.id.0))
+    {
+        return Ok(());
+    }
+    let lang = config.telegram.bot_admin_lang.as_str();
+    let stats = db.get_registration_stats().await?;
+    let online = match tx_tt
+        .request(|resp| TTWorkerCommand::GetOnlineUsers { resp })
+        .await
+    {
+        Ok(users) => users.len().to_string(),
+        Err(e) => {
+            warn!(error = %e, "Online users request failed");
+            t(lang, "admin-stats-unavailable")
+        }
+    };
+
+    let args = HashMap::from([
+        (
+            "telegram_total".to_string(),
+            stats.telegram_total.to_string(),
+        ),
+        (
+            "telegram_today".to_string(),
+            stats.telegram_today.to_string(),
+        ),
+        (
+            "telegram_approved".to_string(),
+            stats.telegram_approved.to_string(),
+        ),
+        ("web_total".to_string(), stats.web_total.to_string()),
+        ("web_today".to_string(), stats.web_today.to_string()),
+        ("approved".to_string(), stats.web_approved.to_string()),
+        (
+            "approved_today".to_string(),
+            stats.web_approved_today.to_string(),
+        ),
+        ("rejected".to_string(), stats.web_rejected.to_string()),
+        (
+            "rejected_today".to_string(),
+            stats.web_rejected_today.to_string(),
+        ),
+        ("pending".to_string(), stats.web_pending.to_string()),
+        ("banned".to_string(), stats.banned.to_string()),
+        (
+            "deeplinks_issued".to_string(),
+            stats.deeplinks_issued.to_string(),
+        ),
+        (
+            "deeplinks_used".to_string(),
+            stats.deeplinks_used.to_string(),
+        ),
+        ("online".to_string(), online),
+    ]);
+    bot.send_message(msg.chat.id, t_args(lang, "admin-stats", &args))
+        .await?;
+    Ok(())
+}
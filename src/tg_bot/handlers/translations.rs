@@ -0,0 +1,46 @@
+use super::HandlerResult;
+use crate::config::AppConfig;
+use crate::services::translations;
+use crate::types::TelegramId;
+use std::sync::Arc;
+use teloxide::prelude::*;
+
+/// Telegram's limit on the length of one message.
+const MAX_MESSAGE_CHARS: usize = 4096;
+
+/// Show translation coverage against English (`/translations`).
+pub async fn translations_command(bot: Bot, msg: Message, config: Arc<AppConfig>) -> HandlerResult {
+    if !config
+        .telegram
+        .admin_ids
+        .contains(&TelegramId::new(msg.chat.id.0))
+    {
+        return Ok(());
+    }
+    let report = tokio::task::spawn_blocking(translations::report).await?;
+    let text = report.render(config.telegram.bot_admin_lang.as_str());
+    for chunk in split_message(&text) {
+        bot.send_message(msg.chat.id, chunk).await?;
+    }
+    Ok(())
+}
+
+/// Split `text` into messages Telegram accepts, breaking at whitespace.
+fn split_message(text: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0;
+    for word in text.split_inclusive(char::is_whitespace) {
+        let word_chars = word.chars().count();
+        if current_chars + word_chars > MAX_MESSAGE_CHARS && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current).trim_end().to_string());
+            current_chars = 0;
+        }
+        current.push_str(word);
+        current_chars += word_chars;
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current.trim_end().to_string());
+    }
+    chunks
+}
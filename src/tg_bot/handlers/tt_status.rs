@@ -0,0 +1,89 @@
+use super::HandlerResult;
+use crate::config::AppConfig;
+use crate::i18n::{t, t_args};
+use crate::tt::TTWorkerHandle;
+use crate::types::{TTWorkerCommand, TelegramId};
+use std::collections::HashMap;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use tracing::warn;
+
+/// Show the `TeamTalk` connection state (`/ttstatus`).
+pub async fn tt_status(
+    bot: Bot,
+    msg: Message,
+    config: Arc<AppConfig>,
+    tx_tt: TTWorkerHandle,
+) -> HandlerResult {
+    if !config
+        .telegram
+        .admin_ids
+        .contains(&TelegramId::new(msg.chat.id.0))
+    {
+        return Ok(());
+    }
+    let lang = config.telegram.bot_admin_lang.as_str();
+    let status = match tx_tt
+        .request(|resp| TTWorkerCommand::GetStatus { resp })
+        .await
+    {
+        Ok(status) => status,
+        Err(e) => {
+            warn!(error = %e, "TeamTalk status request failed");
+            bot.send_message(msg.chat.id, t(lang, "admin-tt-status-unavailable"))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let state_key = if status.logged_in {
+        "admin-tt-status-online"
+    } else if status.connected {
+        "admin-tt-status-connecting"
+    } else {
+        "admin-tt-status-offline"
+    };
+    let none = || t(lang, "admin-tt-status-none");
+    let args = HashMap::from([
+        ("state".to_string(), t(lang, state_key)),
+        (
+            "server".to_string(),
+            status.server_name.unwrap_or_else(none),
+        ),
+        (
+            "uptime".to_string(),
+            status.uptime_seconds.map_or_else(none, format_duration),
+        ),
+        (
+            "attempts".to_string(),
+            status.reconnect_attempts.to_string(),
+        ),
+        (
+            "pending".to_string(),
+            (status.pending_commands + status.pending_lists).to_string(),
+        ),
+        (
+            "last_error".to_string(),
+            status.last_error.unwrap_or_else(none),
+        ),
+    ]);
+    bot.send_message(msg.chat.id, t_args(lang, "admin-tt-status", &args))
+        .await?;
+    Ok(())
+}
+
+/// `1d 2h 3m`, leaving out leading zero units.
+pub(super) fn format_duration(seconds: u64) -> String {
+    let (days, hours, minutes) = (
+        seconds / 86_400,
+        seconds % 86_400 / 3600,
+        seconds % 3600 / 60,
+    );
+    if days > 0 {
+        format!("{days}d {hours}h {minutes}m")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
@@ -0,0 +1,398 @@
+use super::HandlerResult;
+use super::admin::{ApproveAs, grant_note, notify_admin_approve_failed};
+use super::offline_queue::notify_admin_queued;
+use super::reason::{DecisionOrigin, with_reason};
+use crate::config::AppConfig;
+use crate::db::Database;
+use crate::domain::{Nickname, Password, Username};
+use crate::i18n::{t, t_args};
+use crate::services::audit::{self, Actor, AuditAction};
+use crate::services::registration;
+use crate::tg_bot::notify::AdminChats;
+use crate::tt::{TTServer, TTServers};
+use crate::types::{
+    LanguageCode, NotificationKind, RegistrationSource, SourceKind, TelegramId, WebRequestStatus,
+};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use teloxide::prelude::*;
+use teloxide::types::{ChatId, User};
+use tracing::warn;
+
+/// Details of a web registration shown to admins for review.
+pub struct WebApprovalRequest<'a> {
+    pub request_key: &'a str,
+    pub username: &'a Username,
+    pub nickname: &'a Nickname,
+    pub ip: IpAddr,
+    pub user_agent: Option<&'a str>,
+    /// Set when there are several servers to choose from.
+    pub server: Option<&'a str>,
+    /// Sent as a `TeamTalk` private message rather than from the web form.
+    pub via_teamtalk: bool,
+    pub rights_profile: Option<&'a str>,
+    /// Country and network of `ip` from `[web.geoip]`.
+    pub location: Option<&'a str>,
+}
+
+/// Send a web registration request with an approve/reject keyboard to all admins.
+pub async fn notify_admins_web_request(
+    bot: &Bot,
+    db: &Database,
+    config: &AppConfig,
+    request: &WebApprovalRequest<'_>,
+) {
+    let admin_lang = config.telegram.bot_admin_lang.clone();
+    let text = build_web_request_text(admin_lang.as_str(), request);
+    let keyboard = crate::tg_bot::keyboards::admin_web_approval_keyboard(
+        &super::approval_labels(admin_lang.as_str()),
+        request.request_key,
+        &super::rights_profile_buttons(config),
+    );
+
+    AdminChats::new(&config.telegram, db)
+        .send(
+            bot,
+            NotificationKind::WebRegistrations,
+            &text,
+            Some(&keyboard),
+            None,
+        )
+        .await;
+}
+
+fn build_web_request_text(lang: &str, request: &WebApprovalRequest<'_>) -> String {
+    let mut text = String::new();
+    text.push_str(&t(lang, "admin-web-request-title"));
+    text.push('\n');
+    text.push_str(&t(lang, "admin-request-username"));
+    text.push(' ');
+    text.push_str(request.username.as_str());
+    text.push('\n');
+    if request.nickname.as_str() != request.username.as_str() {
+        text.push_str(&t(lang, "admin-request-nickname"));
+        text.push(' ');
+        text.push_str(request.nickname.as_str());
+        text.push('\n');
+    }
+    if let Some(server) = request.server {
+        text.push_str(&t(lang, "admin-request-server"));
+        text.push(' ');
+        text.push_str(server);
+        text.push('\n');
+    }
+    if let Some(profile) = request.rights_profile {
+        text.push_str(&t(lang, "admin-request-rights-profile"));
+        text.push(' ');
+        text.push_str(profile);
+        text.push('\n');
+    }
+    text.push_str(&t(lang, "admin-request-source"));
+    text.push(' ');
+    text.push_str(&t(
+        lang,
+        if request.via_teamtalk {
+            "admin-request-source-teamtalk"
+        } else {
+            "admin-request-source-web"
+        },
+    ));
+    text.push('\n');
+    text.push_str(&t(lang, "admin-request-ip"));
+    text.push(' ');
+    text.push_str(&request.ip.to_string());
+    text.push('\n');
+    if let Some(location) = request.location {
+        text.push_str(&t(lang, "admin-request-location"));
+        text.push(' ');
+        text.push_str(location);
+        text.push('\n');
+    }
+    if let Some(user_agent) = request.user_agent {
+        text.push_str(&t(lang, "admin-request-user-agent"));
+        text.push(' ');
+        text.push_str(user_agent);
+        text.push('\n');
+    }
+    text.push_str(&t(lang, "admin-request-approve"));
+    text
+}
+
+/// Inputs for approving a queued web registration.
+pub(super) struct WebApproveInput<'a> {
+    pub bot: &'a Bot,
+    pub q: &'a CallbackQuery,
+    pub db: &'a Database,
+    pub config: &'a AppConfig,
+    pub lang: &'a LanguageCode,
+    pub req_id: &'a str,
+    pub approve_as: &'a ApproveAs,
+    pub servers: &'a TTServers,
+    pub chat_id: i64,
+}
+
+struct PendingWebApproval {
+    username: Username,
+    password: Password,
+    nickname: Nickname,
+    ip: IpAddr,
+    source: RegistrationSource,
+    server: TTServer,
+    /// Profile the user asked for.
+    rights_profile: Option<String>,
+}
+
+/// Approve a queued web registration and create the `TeamTalk` account.
+pub(super) async fn handle_web_approve(input: WebApproveInput<'_>) -> HandlerResult {
+    let WebApproveInput {
+        bot,
+        q,
+        db,
+        config,
+        lang,
+        req_id,
+        approve_as,
+        servers,
+        chat_id,
+    } = input;
+    let admin_id = TelegramId::new(i64::try_from(q.from.id.0)?);
+    let Some(pending) =
+        load_pending_web_approval(bot, q, db, lang, req_id, admin_id, servers).await?
+    else {
+        return Ok(());
+    };
+    let (account_type, rights_profile) = approve_as.grants(pending.rights_profile.as_deref());
+
+    let result = registration::create_teamtalk_account(registration::CreateAccountParams {
+        username: &pending.username,
+        password: &pending.password,
+        nickname: &pending.nickname,
+        account_type,
+        source: pending.source.clone(),
+        source_info: None,
+        telegram_id: None,
+        language: None,
+        queued_id: None,
+        rights_profile,
+        tx_tt: pending.server.tx_tt.clone(),
+        db,
+        config: &pending.server.config,
+    })
+    .await?;
+
+    let status = if result.created || result.queued {
+        WebRequestStatus::Approved
+    } else {
+        WebRequestStatus::Failed
+    };
+    if !db
+        .set_web_registration_status(req_id, admin_id, status, None)
+        .await?
+    {
+        warn!(req_id, "Web registration was decided concurrently");
+    }
+    let details = format!(
+        "Web request from {}{}{}",
+        pending.ip,
+        grant_note(account_type, rights_profile),
+        result.outcome_note()
+    );
+    let username = pending.username.as_str();
+    audit::record(
+        db,
+        Actor::Admin(admin_id),
+        AuditAction::Approve,
+        username,
+        Some(&details),
+    )
+    .await;
+
+    let alert_args = HashMap::from([(
+        "username".to_string(),
+        pending.username.as_str().to_string(),
+    )]);
+    bot.answer_callback_query(q.id.clone())
+        .text(t_args(
+            lang.as_str(),
+            "admin-req-approved-alert",
+            &alert_args,
+        ))
+        .await?;
+    delete_request_message(bot, q).await;
+
+    if result.queued {
+        notify_admin_queued(bot, ChatId(chat_id), lang.as_str(), username).await;
+    } else if let Some(err) = &result.error {
+        notify_admin_approve_failed(bot, chat_id, lang, username, err).await;
+    }
+
+    notify_web_decision(
+        bot,
+        &AdminChats::new(&config.telegram, db),
+        &q.from,
+        "admin-decision-approved",
+        pending.username.as_str(),
+        &pending.ip.to_string(),
+        None,
+    )
+    .await;
+    Ok(())
+}
+
+/// Reject a queued web registration.
+pub(super) async fn handle_web_reject(
+    bot: &Bot,
+    origin: &DecisionOrigin<'_>,
+    db: &Database,
+    config: &AppConfig,
+    lang: &LanguageCode,
+    req_id: &str,
+    reason: Option<&str>,
+) -> HandlerResult {
+    let admin_id = TelegramId::new(i64::try_from(origin.admin.id.0)?);
+    let claimed = db.claim_web_registration(req_id, admin_id).await?;
+    let Some(req) = claimed else {
+        bot.edit_message_text(
+            origin.chat_id,
+            origin.message_id,
+            t(lang.as_str(), "admin-req-handled"),
+        )
+        .await?;
+        return Ok(());
+    };
+    if !db
+        .set_web_registration_status(req_id, admin_id, WebRequestStatus::Rejected, reason)
+        .await?
+    {
+        bot.edit_message_text(
+            origin.chat_id,
+            origin.message_id,
+            t(lang.as_str(), "admin-req-handled"),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    audit::record(
+        db,
+        Actor::Admin(admin_id),
+        AuditAction::Reject,
+        &req.username,
+        reason,
+    )
+    .await;
+    let alert_args = HashMap::from([("username".to_string(), req.username.clone())]);
+    bot.send_message(
+        origin.chat_id,
+        t_args(lang.as_str(), "admin-req-rejected-alert", &alert_args),
+    )
+    .await?;
+    if let Err(e) = bot.delete_message(origin.chat_id, origin.message_id).await {
+        warn!(error = %e, "Failed to delete admin request message");
+    }
+    notify_web_decision(
+        bot,
+        &AdminChats::new(&config.telegram, db),
+        origin.admin,
+        "admin-decision-rejected",
+        &req.username,
+        &req.ip_address,
+        reason,
+    )
+    .await;
+    Ok(())
+}
+
+async fn load_pending_web_approval(
+    bot: &Bot,
+    q: &CallbackQuery,
+    db: &Database,
+    lang: &LanguageCode,
+    req_id: &str,
+    admin_id: TelegramId,
+    servers: &TTServers,
+) -> Result<Option<PendingWebApproval>, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(req) = db.claim_web_registration(req_id, admin_id).await? else {
+        answer_already_handled(bot, q, lang).await?;
+        return Ok(None);
+    };
+    let parsed = (
+        Username::parse(&req.username),
+        Password::parse(&req.password_cleartext),
+        Nickname::parse(&req.nickname),
+        req.ip_address.parse::<IpAddr>().ok(),
+        servers.resolve(req.source_info.server.as_deref()),
+    );
+    let (Some(username), Some(password), Some(nickname), Some(ip), Some(server)) = parsed else {
+        warn!(req_id, "Stored web registration is invalid");
+        bot.answer_callback_query(q.id.clone())
+            .text(t(lang.as_str(), "admin-req-not-found"))
+            .await?;
+        return Ok(None);
+    };
+    let source = if req.source_info.source == SourceKind::TeamTalk {
+        RegistrationSource::TeamTalk(ip)
+    } else {
+        RegistrationSource::Web(ip)
+    };
+    Ok(Some(PendingWebApproval {
+        username,
+        password,
+        nickname,
+        ip,
+        source,
+        server: server.clone(),
+        rights_profile: req.source_info.rights_profile,
+    }))
+}
+
+pub(super) async fn answer_already_handled(
+    bot: &Bot,
+    q: &CallbackQuery,
+    lang: &LanguageCode,
+) -> HandlerResult {
+    bot.answer_callback_query(q.id.clone())
+        .text(t(lang.as_str(), "admin-req-not-found"))
+        .await?;
+    if let Some(m) = &q.message {
+        bot.edit_message_text(m.chat().id, m.id(), t(lang.as_str(), "admin-req-handled"))
+            .await?;
+    }
+    Ok(())
+}
+
+async fn delete_request_message(bot: &Bot, q: &CallbackQuery) {
+    if let Some(m) = &q.message
+        && let Err(e) = bot.delete_message(m.chat().id, m.id()).await
+    {
+        warn!(error = %e, "Failed to delete admin request message");
+    }
+}
+
+async fn notify_web_decision(
+    bot: &Bot,
+    admins: &AdminChats,
+    admin: &User,
+    decision_key: &str,
+    username: &str,
+    ip_address: &str,
+    reason: Option<&str>,
+) {
+    let admin_lang = admins.lang();
+    let args = HashMap::from([
+        ("admin_name".to_string(), admin.full_name()),
+        ("admin_id".to_string(), admin.id.0.to_string()),
+        ("decision".to_string(), t(admin_lang, decision_key)),
+        ("teamtalk_username".to_string(), username.to_string()),
+        ("ip_address".to_string(), ip_address.to_string()),
+    ]);
+    let text = with_reason(
+        admin_lang,
+        t_args(admin_lang, "admin-web-decision-notify", &args),
+        reason,
+    );
+    let sender = i64::try_from(admin.id.0).ok().map(ChatId);
+    admins
+        .send(bot, NotificationKind::WebRegistrations, &text, None, sender)
+        .await;
+}
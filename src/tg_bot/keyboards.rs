@@ -1,5 +1,7 @@
+use crate::db::schema::DeeplinkToken;
 use crate::i18n::available_languages;
-use crate::types::TelegramId;
+use crate::tt::TTServers;
+use crate::types::{NotificationKind, OnlineUser, TelegramId};
 use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
 
 /// Keyboard for language selection.
@@ -25,6 +27,16 @@ pub fn language_keyboard() -> InlineKeyboardMarkup {
     InlineKeyboardMarkup::new(rows)
 }
 
+/// Keyboard for choosing the server to register on.
+pub fn server_keyboard(servers: &TTServers) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(servers.all().iter().map(|server| {
+        vec![InlineKeyboardButton::callback(
+            server.label(),
+            format!("server_{}", server.id()),
+        )]
+    }))
+}
+
 /// Keyboard for choosing default or custom nickname.
 pub fn nickname_choice_keyboard(yes_text: &str, no_text: &str) -> InlineKeyboardMarkup {
     InlineKeyboardMarkup::new(vec![vec![
@@ -33,34 +45,207 @@ pub fn nickname_choice_keyboard(yes_text: &str, no_text: &str) -> InlineKeyboard
     ]])
 }
 
-/// Keyboard for admin approval of a pending registration.
+/// Keyboard offering a generated password at the password prompt.
+pub fn generate_password_keyboard(text: &str) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+        text,
+        "password_generate",
+    )]])
+}
+
+/// Keyboard for re-downloading account files from `/myaccount`.
+pub fn my_account_keyboard(file_text: &str, link_text: &str) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback(file_text, "myaccount_file"),
+        InlineKeyboardButton::callback(link_text, "myaccount_link"),
+    ]])
+}
+
+/// Localized button labels of registration requests sent to admins.
+pub struct ApprovalLabels {
+    pub approve: String,
+    pub reject: String,
+    pub approve_admin: String,
+    pub rename: String,
+}
+
+/// Keyboard for admin approval of a pending registration. `profiles` holds
+/// `(label, name)` pairs for extra approve buttons, one per rights profile.
 pub fn admin_approval_keyboard(
-    yes_text: &str,
-    no_text: &str,
+    labels: &ApprovalLabels,
     request_id: &str,
+    profiles: &[(String, String)],
 ) -> InlineKeyboardMarkup {
-    InlineKeyboardMarkup::new(vec![vec![
-        InlineKeyboardButton::callback(yes_text, format!("approve_{request_id}")),
-        InlineKeyboardButton::callback(no_text, format!("reject_{request_id}")),
-    ]])
+    approval_keyboard(labels, "", request_id, profiles)
 }
 
-/// Keyboard for admin panel actions.
-pub fn admin_panel_keyboard(
-    btn_delete: &str,
-    btn_banlist: &str,
-    btn_tt_list: &str,
+/// Keyboard for admin approval of web registrations.
+pub fn admin_web_approval_keyboard(
+    labels: &ApprovalLabels,
+    request_id: &str,
+    profiles: &[(String, String)],
+) -> InlineKeyboardMarkup {
+    approval_keyboard(labels, "web_", request_id, profiles)
+}
+
+fn approval_keyboard(
+    labels: &ApprovalLabels,
+    kind: &str,
+    request_id: &str,
+    profiles: &[(String, String)],
 ) -> InlineKeyboardMarkup {
+    let mut rows = vec![vec![
+        InlineKeyboardButton::callback(&labels.approve, format!("approve_{kind}{request_id}")),
+        InlineKeyboardButton::callback(&labels.reject, format!("reject_{kind}{request_id}")),
+    ]];
+    rows.extend(profiles.iter().map(|(label, name)| {
+        vec![InlineKeyboardButton::callback(
+            label,
+            format!("approve_{kind}{request_id}:{name}"),
+        )]
+    }));
+    rows.push(vec![
+        InlineKeyboardButton::callback(
+            &labels.approve_admin,
+            format!("approve_{kind}{request_id}@admin"),
+        ),
+        InlineKeyboardButton::callback(&labels.rename, format!("rename_{kind}{request_id}")),
+    ]);
+    InlineKeyboardMarkup::new(rows)
+}
+
+/// Localized button labels for the admin panel.
+pub struct AdminPanelLabels {
+    pub delete: String,
+    pub banlist: String,
+    pub tt_list: String,
+    pub online: String,
+    pub server_broadcast: String,
+    pub users_broadcast: String,
+    pub invites: String,
+    pub pending: String,
+    pub search: String,
+    pub export: String,
+    pub notifications: String,
+}
+
+/// Keyboard for admin panel actions.
+pub fn admin_panel_keyboard(labels: AdminPanelLabels) -> InlineKeyboardMarkup {
     InlineKeyboardMarkup::new(vec![
-        vec![InlineKeyboardButton::callback(btn_delete, "admin_del")],
         vec![InlineKeyboardButton::callback(
-            btn_banlist,
+            labels.search,
+            "admin_search",
+        )],
+        vec![InlineKeyboardButton::callback(
+            labels.pending,
+            "admin_pending",
+        )],
+        vec![InlineKeyboardButton::callback(labels.delete, "admin_del")],
+        vec![InlineKeyboardButton::callback(
+            labels.banlist,
             "admin_banlist_view",
         )],
-        vec![InlineKeyboardButton::callback(btn_tt_list, "admin_tt_list")],
+        vec![InlineKeyboardButton::callback(
+            labels.tt_list,
+            "admin_tt_list",
+        )],
+        vec![InlineKeyboardButton::callback(
+            labels.online,
+            "admin_online",
+        )],
+        vec![InlineKeyboardButton::callback(
+            labels.server_broadcast,
+            "admin_tt_broadcast",
+        )],
+        vec![InlineKeyboardButton::callback(
+            labels.users_broadcast,
+            "admin_tg_broadcast",
+        )],
+        vec![InlineKeyboardButton::callback(
+            labels.invites,
+            "admin_invites",
+        )],
+        vec![InlineKeyboardButton::callback(
+            labels.export,
+            "admin_export",
+        )],
+        vec![InlineKeyboardButton::callback(
+            labels.notifications,
+            "admin_notify",
+        )],
     ])
 }
 
+/// Keyboard toggling each notification kind, labelled with its state.
+pub fn admin_notify_settings_keyboard(
+    toggles: Vec<(String, NotificationKind)>,
+    back_text: &str,
+) -> InlineKeyboardMarkup {
+    let mut rows: Vec<Vec<InlineKeyboardButton>> = toggles
+        .into_iter()
+        .map(|(label, kind)| {
+            vec![InlineKeyboardButton::callback(
+                label,
+                format!("admin_notify_{}", kind.as_str()),
+            )]
+        })
+        .collect();
+    rows.push(vec![InlineKeyboardButton::callback(
+        back_text,
+        "cancel_action",
+    )]);
+    InlineKeyboardMarkup::new(rows)
+}
+
+/// Keyboard for active invite links with revoke buttons.
+pub fn admin_invites_keyboard(
+    invites: &[DeeplinkToken],
+    revoke_text: &str,
+    revoke_all_text: &str,
+    nav_row: Option<Vec<InlineKeyboardButton>>,
+) -> InlineKeyboardMarkup {
+    let mut buttons = vec![];
+    for invite in invites {
+        let Some(id) = invite.id else {
+            continue;
+        };
+        buttons.push(vec![InlineKeyboardButton::callback(
+            format!("{revoke_text} (#{id})"),
+            format!("admin_invite_revoke_{id}"),
+        )]);
+    }
+    buttons.push(vec![InlineKeyboardButton::callback(
+        revoke_all_text,
+        "admin_invite_revoke_all",
+    )]);
+    if let Some(row) = nav_row {
+        buttons.push(row);
+    }
+    InlineKeyboardMarkup::new(buttons)
+}
+
+/// Keyboard opening each listed pending request; `requests` holds
+/// `(web, request_key, username)`.
+pub fn admin_pending_keyboard(
+    requests: &[(bool, String, String)],
+    nav_row: Option<Vec<InlineKeyboardButton>>,
+) -> InlineKeyboardMarkup {
+    let mut buttons: Vec<Vec<InlineKeyboardButton>> = requests
+        .iter()
+        .map(|(web, key, username)| {
+            let kind = if *web { "web" } else { "tg" };
+            vec![InlineKeyboardButton::callback(
+                username,
+                format!("admin_pending_{kind}_{key}"),
+            )]
+        })
+        .collect();
+    if let Some(row) = nav_row {
+        buttons.push(row);
+    }
+    InlineKeyboardMarkup::new(buttons)
+}
+
 /// Keyboard for selecting a registered user.
 pub fn admin_user_list_keyboard(
     users: Vec<(TelegramId, String)>,
@@ -84,6 +269,7 @@ pub fn admin_banlist_keyboard(
     banned_users: Vec<(TelegramId, String)>,
     unban_text: &str,
     manual_text: &str,
+    search_row: Vec<InlineKeyboardButton>,
     nav_row: Option<Vec<InlineKeyboardButton>>,
 ) -> InlineKeyboardMarkup {
     let mut buttons = vec![];
@@ -97,16 +283,37 @@ pub fn admin_banlist_keyboard(
         manual_text,
         "admin_ban_manual",
     )]);
+    buttons.push(search_row);
     if let Some(row) = nav_row {
         buttons.push(row);
     }
     InlineKeyboardMarkup::new(buttons)
 }
 
+/// Telegram's limit on callback data, in bytes.
+const MAX_CALLBACK_DATA_BYTES: usize = 64;
+
+/// Callback lifting the username and IP bans `ids`; `None` when there are
+/// none or too many to fit.
+pub fn undo_bans_callback(ids: &[i64]) -> Option<String> {
+    if ids.is_empty() {
+        return None;
+    }
+    let ids: Vec<String> = ids.iter().map(ToString::to_string).collect();
+    let data = format!("admin_ban_undo_{}", ids.join("."));
+    (data.len() <= MAX_CALLBACK_DATA_BYTES).then_some(data)
+}
+
+/// Keyboard with a single button undoing an automatic ban.
+pub fn undo_ban_keyboard(text: &str, callback: String) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(text, callback)]])
+}
+
 /// Keyboard for `TeamTalk` accounts list.
 pub fn admin_tt_accounts_keyboard(
     accounts: Vec<String>,
     delete_text: &str,
+    search_row: Vec<InlineKeyboardButton>,
     nav_row: Option<Vec<InlineKeyboardButton>>,
 ) -> InlineKeyboardMarkup {
     let mut buttons = vec![];
@@ -116,6 +323,32 @@ pub fn admin_tt_accounts_keyboard(
             format!("admin_tt_del_prompt_{acc}"),
         )]);
     }
+    buttons.push(search_row);
+    if let Some(row) = nav_row {
+        buttons.push(row);
+    }
+    InlineKeyboardMarkup::new(buttons)
+}
+
+/// Keyboard for online `TeamTalk` users with per-user actions.
+pub fn admin_online_users_keyboard(
+    users: &[OnlineUser],
+    kick_text: &str,
+    ban_text: &str,
+    message_text: &str,
+    nav_row: Option<Vec<InlineKeyboardButton>>,
+) -> InlineKeyboardMarkup {
+    let mut buttons = vec![];
+    for user in users {
+        buttons.push(vec![
+            InlineKeyboardButton::callback(
+                format!("{kick_text} ({})", user.nickname),
+                format!("admin_online_kick_{}", user.id),
+            ),
+            InlineKeyboardButton::callback(ban_text, format!("admin_online_ban_{}", user.id)),
+            InlineKeyboardButton::callback(message_text, format!("admin_online_msg_{}", user.id)),
+        ]);
+    }
     if let Some(row) = nav_row {
         buttons.push(row);
     }
@@ -150,6 +383,14 @@ pub fn confirm_keyboard(
     ]])
 }
 
+/// Keyboard shown while waiting for a reject or ban reason.
+pub fn reason_keyboard(skip_text: &str, cancel_text: &str) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback(skip_text, "reason_skip"),
+        InlineKeyboardButton::callback(cancel_text, "reason_cancel"),
+    ]])
+}
+
 /// Keyboard for account type selection.
 pub fn admin_account_type_keyboard(admin_text: &str, user_text: &str) -> InlineKeyboardMarkup {
     InlineKeyboardMarkup::new(vec![
@@ -157,3 +398,18 @@ pub fn admin_account_type_keyboard(admin_text: &str, user_text: &str) -> InlineK
         vec![InlineKeyboardButton::callback(user_text, "acct_user")],
     ])
 }
+
+/// Rights profiles users may ask for, after the default one.
+pub fn rights_profile_keyboard(default_text: &str, profiles: &[String]) -> InlineKeyboardMarkup {
+    let mut rows = vec![vec![InlineKeyboardButton::callback(
+        default_text,
+        "profile_default",
+    )]];
+    rows.extend(profiles.iter().map(|name| {
+        vec![InlineKeyboardButton::callback(
+            name,
+            format!("profile_{name}"),
+        )]
+    }));
+    InlineKeyboardMarkup::new(rows)
+}
@@ -1,4 +1,12 @@
+/// Telegram command menu registration.
+pub mod commands;
 /// Telegram bot handlers.
 pub mod handlers;
 /// Telegram bot keyboards.
 pub mod keyboards;
+/// Admin notification delivery.
+pub mod notify;
+/// Flood-limit aware sending of notifications.
+pub mod throttle;
+/// Telegram webhook listener.
+pub mod webhook;
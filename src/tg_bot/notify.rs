@@ -0,0 +1,229 @@
+use crate::config::TelegramConfig;
+use crate::db::Database;
+use crate::db::schema::QueuedNotification;
+use crate::domain::QuietHours;
+use crate::i18n::t;
+use crate::tg_bot::throttle;
+use crate::types::{LanguageCode, NotificationKind, TelegramId};
+use chrono::Local;
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardMarkup, MessageId, ThreadId};
+use tracing::warn;
+
+/// Chats that receive admin notifications: a shared group (optionally a forum
+/// topic) when `admin_chat_id` is set, otherwise every admin's private chat.
+#[derive(Clone)]
+pub struct AdminChats {
+    chats: Vec<ChatId>,
+    thread_id: Option<ThreadId>,
+    shared: bool,
+    /// Language notifications are written in.
+    lang: LanguageCode,
+    /// Where each admin's muted notification kinds are stored.
+    db: Database,
+    /// Quiet hours of admins who did not pick their own.
+    quiet_hours: Option<QuietHours>,
+}
+
+/// Private chats a notification must not be sent to right away.
+#[derive(Default)]
+struct Held {
+    /// Admins who turned the kind off.
+    muted: Vec<ChatId>,
+    /// Admins inside their quiet hours; they get it in the next digest.
+    quiet: Vec<ChatId>,
+}
+
+impl AdminChats {
+    /// Resolve notification targets from the Telegram settings.
+    pub fn new(tg: &TelegramConfig, db: &Database) -> Self {
+        tg.admin_chat_id.map_or_else(
+            || Self {
+                chats: tg.admin_ids.iter().map(|id| ChatId(id.as_i64())).collect(),
+                thread_id: None,
+                shared: false,
+                lang: tg.bot_admin_lang.clone(),
+                db: db.clone(),
+                quiet_hours: tg.admin_quiet_hours,
+            },
+            |chat_id| Self {
+                chats: vec![ChatId(chat_id)],
+                thread_id: tg.admin_topic_id.map(|id| ThreadId(MessageId(id))),
+                shared: true,
+                lang: tg.bot_admin_lang.clone(),
+                db: db.clone(),
+                quiet_hours: tg.admin_quiet_hours,
+            },
+        )
+    }
+
+    /// Language notifications are written in.
+    pub fn lang(&self) -> &str {
+        self.lang.as_str()
+    }
+
+    /// Send a `kind` notification to every admin chat. `except` skips that
+    /// admin's private chat, and so do the admin's muted kinds; admins inside
+    /// their quiet hours get it in a digest later, without the keyboard.
+    /// None of that applies when notifications go to a shared chat.
+    pub async fn send(
+        &self,
+        bot: &Bot,
+        kind: NotificationKind,
+        text: &str,
+        keyboard: Option<&InlineKeyboardMarkup>,
+        except: Option<ChatId>,
+    ) {
+        let held = self.held_chats(kind).await;
+        for &chat_id in &self.chats {
+            if !self.shared && (except == Some(chat_id) || held.muted.contains(&chat_id)) {
+                continue;
+            }
+            if held.quiet.contains(&chat_id) {
+                let admin_id = TelegramId::new(chat_id.0);
+                if let Err(e) = self
+                    .db
+                    .queue_notification(admin_id, kind.as_str(), text)
+                    .await
+                {
+                    warn!(error = %e, chat_id = %chat_id, "Failed to queue admin notification");
+                }
+                continue;
+            }
+            let mut request = bot.send_message(chat_id, text);
+            if let Some(thread_id) = self.thread_id {
+                request = request.message_thread_id(thread_id);
+            }
+            if let Some(keyboard) = keyboard {
+                request = request.reply_markup(keyboard.clone());
+            }
+            if let Err(e) = throttle::send(chat_id, request).await {
+                warn!(error = %e, chat_id = %chat_id, "Failed to send admin notification");
+            }
+        }
+    }
+
+    /// Private chats of admins who turned `kind` off or are inside their
+    /// quiet hours.
+    async fn held_chats(&self, kind: NotificationKind) -> Held {
+        if self.shared || kind == NotificationKind::Alert {
+            return Held::default();
+        }
+        let settings = match self.db.get_admin_settings().await {
+            Ok(settings) => settings,
+            Err(e) => {
+                warn!(error = %e, "Failed to load admin notification settings");
+                return Held::default();
+            }
+        };
+        let now = Local::now().time();
+        let mut held = Held::default();
+        for &chat_id in &self.chats {
+            let admin = settings.iter().find(|s| s.admin_id.as_i64() == chat_id.0);
+            if admin.is_some_and(|admin| admin.mutes(kind)) {
+                held.muted.push(chat_id);
+                continue;
+            }
+            let quiet_hours = admin.map_or(self.quiet_hours, |admin| {
+                admin.quiet_hours(self.quiet_hours)
+            });
+            if quiet_hours.is_some_and(|hours| hours.contains(now)) {
+                held.quiet.push(chat_id);
+            }
+        }
+        held
+    }
+}
+
+/// Telegram's limit on the length of one message.
+const MAX_MESSAGE_CHARS: usize = 4096;
+
+/// Send every admin outside their quiet hours the notifications held back
+/// for them. Rows of chats that no longer belong to an admin are dropped.
+pub async fn send_digests(bot: &Bot, db: &Database, tg: &TelegramConfig) {
+    let queued = match db.get_queued_notifications().await {
+        Ok(queued) => queued,
+        Err(e) => {
+            warn!(error = %e, "Failed to load queued admin notifications");
+            return;
+        }
+    };
+    if queued.is_empty() {
+        return;
+    }
+    let settings = match db.get_admin_settings().await {
+        Ok(settings) => settings,
+        Err(e) => {
+            warn!(error = %e, "Failed to load admin notification settings");
+            return;
+        }
+    };
+    let now = Local::now().time();
+    let mut admin_ids: Vec<TelegramId> = queued.iter().map(|n| n.admin_id).collect();
+    admin_ids.sort_unstable_by_key(|id| id.as_i64());
+    admin_ids.dedup();
+    for admin_id in admin_ids {
+        let entries: Vec<&QueuedNotification> =
+            queued.iter().filter(|n| n.admin_id == admin_id).collect();
+        let Some(last_id) = entries.iter().map(|n| n.id).max() else {
+            continue;
+        };
+        if tg.admin_ids.contains(&admin_id) {
+            let quiet_hours = settings
+                .iter()
+                .find(|s| s.admin_id == admin_id)
+                .map_or(tg.admin_quiet_hours, |s| {
+                    s.quiet_hours(tg.admin_quiet_hours)
+                });
+            if quiet_hours.is_some_and(|hours| hours.contains(now)) {
+                continue;
+            }
+            let chat_id = ChatId(admin_id.as_i64());
+            if let Err(e) = send_digest(bot, chat_id, tg.bot_admin_lang.as_str(), &entries).await {
+                warn!(error = %e, chat_id = %chat_id, "Failed to send notification digest");
+                continue;
+            }
+        }
+        if let Err(e) = db.delete_queued_notifications(admin_id, last_id).await {
+            warn!(error = %e, "Failed to clear queued admin notifications");
+        }
+    }
+}
+
+/// Send `entries` grouped by kind, split across messages when too long.
+async fn send_digest(
+    bot: &Bot,
+    chat_id: ChatId,
+    lang: &str,
+    entries: &[&QueuedNotification],
+) -> Result<(), teloxide::RequestError> {
+    let mut blocks = Vec::new();
+    for kind in NotificationKind::MUTABLE {
+        let mut of_kind = entries
+            .iter()
+            .filter(|n| n.kind == kind.as_str())
+            .peekable();
+        if of_kind.peek().is_some() {
+            blocks.push(format!("— {} —", t(lang, kind.label_key())));
+        }
+        for n in of_kind {
+            let at = n.created_at.and_utc().with_timezone(&Local).format("%H:%M");
+            blocks.push(format!("[{at}] {}", n.text));
+        }
+    }
+    let mut message = t(lang, "admin-notify-digest-title");
+    for block in blocks {
+        if message.chars().count() + block.chars().count() + 2 > MAX_MESSAGE_CHARS {
+            throttle::send(
+                chat_id,
+                bot.send_message(chat_id, std::mem::take(&mut message)),
+            )
+            .await?;
+        } else {
+            message.push_str("\n\n");
+        }
+        message.push_str(&block);
+    }
+    throttle::send(chat_id, bot.send_message(chat_id, message)).await?;
+    Ok(())
+}
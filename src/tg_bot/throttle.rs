@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex, PoisonError};
+use std::time::Duration;
+use teloxide::RequestError;
+use teloxide::requests::{Output, Request};
+use teloxide::types::ChatId;
+use tokio::time::{Instant, sleep_until};
+use tracing::warn;
+
+/// Gap between any two sends; Telegram allows about 30 messages per second.
+const GLOBAL_INTERVAL: Duration = Duration::from_millis(35);
+/// Gap between sends to one private chat (about one message per second).
+const PRIVATE_CHAT_INTERVAL: Duration = Duration::from_secs(1);
+/// Gap between sends to one group (about 20 messages per minute).
+const GROUP_CHAT_INTERVAL: Duration = Duration::from_secs(3);
+/// How often a request is retried after Telegram answers with `RetryAfter`.
+const MAX_RETRIES: u32 = 3;
+
+/// When the next message may go out, overall and per chat.
+struct Slots {
+    next_global: Instant,
+    next_per_chat: HashMap<ChatId, Instant>,
+}
+
+static SLOTS: LazyLock<Mutex<Slots>> = LazyLock::new(|| {
+    Mutex::new(Slots {
+        next_global: Instant::now(),
+        next_per_chat: HashMap::new(),
+    })
+});
+
+/// Send `request` to `chat` within Telegram's flood limits: wait for a free
+/// slot in the process-wide and per-chat budgets, and when Telegram still
+/// answers `RetryAfter`, hold every send for that long and retry.
+pub async fn send<R>(chat: ChatId, request: R) -> Result<Output<R>, RequestError>
+where
+    R: Request<Err = RequestError>,
+{
+    let mut retries = 0;
+    loop {
+        sleep_until(reserve(chat)).await;
+        match request.send_ref().await {
+            Err(RequestError::RetryAfter(wait)) if retries < MAX_RETRIES => {
+                retries += 1;
+                warn!(chat_id = %chat, seconds = wait.seconds(), "Telegram asked to slow down");
+                pause(chat, wait.duration());
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Take the next free slot for `chat` and return when it starts.
+fn reserve(chat: ChatId) -> Instant {
+    let now = Instant::now();
+    let mut slots = SLOTS.lock().unwrap_or_else(PoisonError::into_inner);
+    slots.next_per_chat.retain(|_, next| *next > now);
+    let at = slots
+        .next_per_chat
+        .get(&chat)
+        .map_or(slots.next_global, |&next| next.max(slots.next_global))
+        .max(now);
+    let interval = if chat.is_user() {
+        PRIVATE_CHAT_INTERVAL
+    } else {
+        GROUP_CHAT_INTERVAL
+    };
+    slots.next_global = at + GLOBAL_INTERVAL;
+    slots.next_per_chat.insert(chat, at + interval);
+    at
+}
+
+/// Hold every send, and `chat`'s in particular, for `wait`.
+fn pause(chat: ChatId, wait: Duration) {
+    let until = Instant::now() + wait;
+    let mut slots = SLOTS.lock().unwrap_or_else(PoisonError::into_inner);
+    slots.next_global = slots.next_global.max(until);
+    slots
+        .next_per_chat
+        .entry(chat)
+        .and_modify(|next| *next = (*next).max(until))
+        .or_insert(until);
+    drop(slots);
+}
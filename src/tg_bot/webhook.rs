@@ -0,0 +1,84 @@
+use crate::config::AppConfig;
+use anyhow::{Context, Result};
+use axum::Router;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use teloxide::prelude::*;
+use teloxide::update_listeners::UpdateListener;
+use teloxide::update_listeners::webhooks::{self, Options};
+use tokio::net::TcpListener;
+use tracing::{error, info, warn};
+
+/// Update listener for webhook mode plus the router Telegram posts to when it
+/// is served by the web server rather than a dedicated port.
+pub struct Webhook<L> {
+    pub listener: L,
+    pub router: Option<Router>,
+}
+
+/// Register the webhook with Telegram and start receiving updates.
+///
+/// Returns `None` when `webhook_url` is not configured and long polling should
+/// be used. The webhook is removed again once the listener stops.
+pub async fn start(
+    bot: &Bot,
+    config: &AppConfig,
+) -> Result<Option<Webhook<impl UpdateListener<Err = Infallible> + Send + 'static>>> {
+    let Some(url) = config.telegram.webhook_url.as_deref() else {
+        return Ok(None);
+    };
+    let url = reqwest::Url::parse(url).with_context(|| format!("Invalid webhook_url '{url}'"))?;
+    let port = config
+        .telegram
+        .webhook_port
+        .unwrap_or(config.web.web_app_port);
+    let addr_str = format!("{}:{port}", config.web.web_app_host);
+    let addr: SocketAddr = addr_str
+        .parse()
+        .with_context(|| format!("Invalid webhook listen address '{addr_str}'"))?;
+
+    let mut options = Options::new(addr, url.clone());
+    if url.path() != "/" {
+        options = options.path(url.path().to_owned());
+    }
+    let secret = options.get_or_gen_secret_token().to_owned();
+    bot.set_webhook(url)
+        .secret_token(secret)
+        .await
+        .context("Failed to register the Telegram webhook")?;
+
+    let (listener, stop_flag, router) = webhooks::axum_no_setup(options);
+    let cleanup_bot = bot.clone();
+    let stopped = async move {
+        stop_flag.await;
+        if let Err(e) = cleanup_bot.delete_webhook().await {
+            warn!(error = %e, "Failed to delete Telegram webhook");
+        }
+    };
+
+    if config.telegram.webhook_port.is_none() {
+        info!("Telegram webhook served by the web server");
+        tokio::spawn(stopped);
+        return Ok(Some(Webhook {
+            listener,
+            router: Some(router),
+        }));
+    }
+
+    let tcp = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind webhook listener on {addr}"))?;
+    info!(addr = %addr, "Telegram webhook listening");
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(tcp, router)
+            .with_graceful_shutdown(stopped)
+            .await
+        {
+            error!(error = %e, "Webhook server failed");
+        }
+    });
+    Ok(Some(Webhook {
+        listener,
+        router: None,
+    }))
+}
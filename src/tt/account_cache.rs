@@ -0,0 +1,160 @@
+use crate::domain::UsernameMatch;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Wait before listing again after a listing failed.
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Usernames on the server, so existence checks do not have to list every
+/// account.
+///
+/// Filled by a full listing after login and kept current from account
+/// events. Events arriving while a listing is in flight are replayed on top
+/// of its result, since the listing may predate them.
+pub(super) struct AccountCache {
+    names: HashSet<String>,
+    /// Lowercased names with how many accounts fold to each.
+    folded: HashMap<String, usize>,
+    /// Same for NFKC-normalized, case-folded names.
+    normalized: HashMap<String, usize>,
+    refreshed_at: Option<Instant>,
+    /// Changes seen during a refresh; `true` for a created account.
+    journal: Option<Vec<(String, bool)>>,
+    failed_at: Option<Instant>,
+    refresh_interval: Option<Duration>,
+}
+
+impl AccountCache {
+    /// `refresh_seconds == 0` only refreshes after a login.
+    pub fn new(refresh_seconds: u64) -> Self {
+        Self {
+            names: HashSet::new(),
+            folded: HashMap::new(),
+            normalized: HashMap::new(),
+            refreshed_at: None,
+            journal: None,
+            failed_at: None,
+            refresh_interval: (refresh_seconds > 0).then(|| Duration::from_secs(refresh_seconds)),
+        }
+    }
+
+    /// Whether a full listing has been loaded since the last login.
+    pub const fn is_loaded(&self) -> bool {
+        self.refreshed_at.is_some()
+    }
+
+    /// Whether a full listing should be requested now.
+    pub fn refresh_due(&self) -> bool {
+        if self.journal.is_some() || self.failed_at.is_some_and(|at| at.elapsed() < RETRY_DELAY) {
+            return false;
+        }
+        match (self.refreshed_at, self.refresh_interval) {
+            (None, _) => true,
+            (Some(at), Some(interval)) => at.elapsed() >= interval,
+            (Some(_), None) => false,
+        }
+    }
+
+    pub fn contains(&self, username: &str, matching: UsernameMatch) -> bool {
+        if self.names.contains(username) {
+            return true;
+        }
+        match matching {
+            UsernameMatch::Exact => false,
+            UsernameMatch::Case => self.folded.contains_key(&matching.fold(username)),
+            UsernameMatch::Normalized => self.normalized.contains_key(&matching.fold(username)),
+        }
+    }
+
+    pub fn insert(&mut self, username: &str) {
+        if let Some(journal) = &mut self.journal {
+            journal.push((username.to_string(), true));
+        }
+        self.add(username);
+    }
+
+    pub fn remove(&mut self, username: &str) {
+        if let Some(journal) = &mut self.journal {
+            journal.push((username.to_string(), false));
+        }
+        self.drop_name(username);
+    }
+
+    pub fn begin_refresh(&mut self) {
+        self.journal = Some(Vec::new());
+    }
+
+    /// Replace the cache with a full listing and replay changes seen meanwhile.
+    pub fn finish_refresh(&mut self, usernames: Vec<String>) {
+        let journal = self.journal.take().unwrap_or_default();
+        self.names.clear();
+        self.folded.clear();
+        self.normalized.clear();
+        for username in usernames {
+            self.add(&username);
+        }
+        for (username, created) in journal {
+            if created {
+                self.add(&username);
+            } else {
+                self.drop_name(&username);
+            }
+        }
+        self.refreshed_at = Some(Instant::now());
+        self.failed_at = None;
+    }
+
+    /// Keep the current contents after a failed listing and retry later.
+    pub fn abort_refresh(&mut self) {
+        self.journal = None;
+        self.failed_at = Some(Instant::now());
+    }
+
+    /// Forget everything; the next login loads a fresh listing.
+    pub fn clear(&mut self) {
+        self.names.clear();
+        self.folded.clear();
+        self.normalized.clear();
+        self.refreshed_at = None;
+        self.journal = None;
+        self.failed_at = None;
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    fn add(&mut self, username: &str) {
+        if self.names.insert(username.to_string()) {
+            *self
+                .folded
+                .entry(UsernameMatch::Case.fold(username))
+                .or_default() += 1;
+            *self
+                .normalized
+                .entry(UsernameMatch::Normalized.fold(username))
+                .or_default() += 1;
+        }
+    }
+
+    fn drop_name(&mut self, username: &str) {
+        if !self.names.remove(username) {
+            return;
+        }
+        forget(&mut self.folded, &UsernameMatch::Case.fold(username));
+        forget(
+            &mut self.normalized,
+            &UsernameMatch::Normalized.fold(username),
+        );
+    }
+}
+
+/// Count one account less under `key`.
+fn forget(counts: &mut HashMap<String, usize>, key: &str) {
+    if let Some(count) = counts.get_mut(key) {
+        *count -= 1;
+        if *count == 0 {
+            counts.remove(key);
+        }
+    }
+}
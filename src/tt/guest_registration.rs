@@ -0,0 +1,268 @@
+use super::TTWorkerHandle;
+use crate::config::AppConfig;
+use crate::db::{Database, NewPendingWebRegistration};
+use crate::domain::{Nickname, Password, Username};
+use crate::i18n::{t, t_args};
+use crate::services::audit::{self, Actor, AuditAction};
+use crate::services::{bans, registration};
+use crate::tg_bot::handlers::{WebApprovalRequest, notify_admins_web_request};
+use crate::types::{
+    RegistrationSource, SourceInfo, SourceKind, TTAccountType, TTCommandError, TTWorkerCommand,
+};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
+use teloxide::prelude::*;
+use tokio::runtime::Handle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Registers accounts for users who send the bot `register <username> <password>`
+/// on the server.
+#[derive(Clone)]
+pub(super) struct GuestRegistrar {
+    config: Arc<AppConfig>,
+    bot: Bot,
+    db: Database,
+    tx_tt: TTWorkerHandle,
+    rt_handle: Handle,
+}
+
+/// A `register` private message.
+pub(super) struct GuestRequest {
+    pub user_id: i32,
+    pub nickname: String,
+    /// Known only when the bot may see other users' addresses.
+    pub ip: Option<IpAddr>,
+    pub username: String,
+    pub password: String,
+}
+
+impl GuestRegistrar {
+    /// `None` unless `tt_pm_registration_enabled` is set.
+    pub fn new(
+        config: &Arc<AppConfig>,
+        bot: &Bot,
+        db: &Database,
+        tx_tt: &TTWorkerHandle,
+        rt_handle: &Handle,
+    ) -> Option<Self> {
+        config.teamtalk.tt_pm_registration_enabled.then(|| Self {
+            config: config.clone(),
+            bot: bot.clone(),
+            db: db.clone(),
+            tx_tt: tx_tt.clone(),
+            rt_handle: rt_handle.clone(),
+        })
+    }
+
+    /// Register in the background and answer the sender when done.
+    pub fn spawn(&self, request: GuestRequest) {
+        let this = self.clone();
+        self.rt_handle.spawn(async move {
+            let text = this.register(&request).await;
+            let sent = this
+                .tx_tt
+                .request(|resp| TTWorkerCommand::SendUserMessage {
+                    user_id: request.user_id,
+                    text,
+                    resp,
+                })
+                .await;
+            if !matches!(sent, Ok(Ok(true))) {
+                warn!(
+                    user_id = request.user_id,
+                    "Failed to answer TeamTalk registration"
+                );
+            }
+        });
+    }
+
+    /// Run the registration; returns the reply for the sender.
+    async fn register(&self, request: &GuestRequest) -> String {
+        let config = &*self.config;
+        let lang = config.telegram.bot_admin_lang.as_str();
+        if let Some(ip) = request.ip
+            && registration::ip_limit_reached(&self.db, config, &ip.to_string()).await
+        {
+            return t(lang, "web-err-ip-limit");
+        }
+        let Some(username) = Username::parse(&request.username) else {
+            return t(lang, "web-err-username-invalid");
+        };
+        if let Err(violation) = config.username_policy.check(&username) {
+            return t_args(lang, violation.message_key(), &violation.message_args());
+        }
+        if let Some(ban) =
+            bans::find_ban(&self.db, config, Some(username.as_str()), request.ip).await
+        {
+            info!(username = %username, ban = ban.id, "Refused banned guest registration");
+            return t(lang, "web-err-banned");
+        }
+        let Some(password) = Password::parse(&request.password) else {
+            return t(lang, "web-err-password-invalid");
+        };
+        if let Err(violation) = config.password_policy.check(&password) {
+            return t_args(lang, violation.message_key(), &violation.message_args());
+        }
+        let Some(nickname) =
+            Nickname::parse(&request.nickname).or_else(|| Nickname::parse(username.as_str()))
+        else {
+            return t(lang, "web-err-nickname-invalid");
+        };
+        match self
+            .tx_tt
+            .request(|resp| TTWorkerCommand::CheckUserExists {
+                username: username.clone(),
+                matching: config.username_policy.matching(),
+                resp,
+            })
+            .await
+        {
+            Ok(false) => {}
+            Ok(true) => return t(lang, "web-err-username-taken"),
+            Err(e) => {
+                error!(error = %e, "TeamTalk user existence check failed");
+                return t(lang, "web-err-timeout");
+            }
+        }
+
+        let ip = request.ip.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        let outcome = if config.teamtalk.tt_pm_verify_registration {
+            self.submit_for_approval(ip, &username, &password, &nickname)
+                .await
+        } else {
+            self.create(ip, &username, &password, &nickname).await
+        };
+        let key = match outcome {
+            Ok(key) => {
+                self.remember_ip(request.ip, &username).await;
+                key
+            }
+            Err(key) => key,
+        };
+        let args = HashMap::from([("username".to_string(), username.as_str().to_string())]);
+        t_args(lang, key, &args)
+    }
+
+    async fn create(
+        &self,
+        ip: IpAddr,
+        username: &Username,
+        password: &Password,
+        nickname: &Nickname,
+    ) -> Result<&'static str, &'static str> {
+        let result = registration::create_teamtalk_account(registration::CreateAccountParams {
+            username,
+            password,
+            nickname,
+            account_type: TTAccountType::Default,
+            rights_profile: None,
+            source: RegistrationSource::TeamTalk(ip),
+            source_info: None,
+            telegram_id: None,
+            language: Some(&self.config.telegram.bot_admin_lang),
+            queued_id: None,
+            tx_tt: self.tx_tt.clone(),
+            db: &self.db,
+            config: &self.config,
+        })
+        .await;
+        let (key, details) = match result {
+            Ok(result) if result.created => ("tt-pm-register-created", None),
+            Ok(result) if result.queued => (
+                "web-queued-title",
+                Some("Queued until TeamTalk is reachable"),
+            ),
+            Ok(result) => {
+                return Err(match result.error {
+                    Some(TTCommandError::AccountExists) => "web-err-username-taken",
+                    Some(TTCommandError::InvalidUsername) => "web-err-username-invalid",
+                    _ => "tt-pm-register-failed",
+                });
+            }
+            Err(_) => return Err("tt-pm-register-failed"),
+        };
+        info!(username = %username.as_str(), "Registered account from a TeamTalk message");
+        audit::record(
+            &self.db,
+            Actor::Visitor(ip),
+            AuditAction::TeamTalkRegistration,
+            username.as_str(),
+            details,
+        )
+        .await;
+        Ok(key)
+    }
+
+    async fn submit_for_approval(
+        &self,
+        ip: IpAddr,
+        username: &Username,
+        password: &Password,
+        nickname: &Nickname,
+    ) -> Result<&'static str, &'static str> {
+        let request_key = Uuid::new_v4().to_string();
+        let lang = &self.config.telegram.bot_admin_lang;
+        let user_agent = format!("TeamTalk: {}", nickname.as_str());
+        let source_info = SourceInfo {
+            server: Some(self.config.teamtalk.server_id.clone()),
+            ip: Some(ip),
+            user_agent: Some(user_agent.clone()),
+            ..SourceInfo::new(SourceKind::TeamTalk, lang.clone())
+        };
+        if let Err(e) = self
+            .db
+            .add_pending_web_registration(&NewPendingWebRegistration {
+                key: &request_key,
+                username: username.as_str(),
+                password: password.as_str(),
+                nickname: nickname.as_str(),
+                ip_address: &ip.to_string(),
+                user_agent: Some(&user_agent),
+                source_info: &source_info,
+            })
+            .await
+        {
+            error!(error = %e, "Failed to store pending TeamTalk registration");
+            return Err("web-err-submit");
+        }
+        audit::record(
+            &self.db,
+            Actor::Visitor(ip),
+            AuditAction::TeamTalkRegistration,
+            username.as_str(),
+            Some("Awaiting approval"),
+        )
+        .await;
+        notify_admins_web_request(
+            &self.bot,
+            &self.db,
+            &self.config,
+            &WebApprovalRequest {
+                request_key: &request_key,
+                username,
+                nickname,
+                ip,
+                user_agent: Some(&user_agent),
+                server: self.config.server_label(),
+                via_teamtalk: true,
+                rights_profile: None,
+                location: None,
+            },
+        )
+        .await;
+        Ok("web-pending-title")
+    }
+
+    async fn remember_ip(&self, ip: Option<IpAddr>, username: &Username) {
+        if let Some(ip) = ip
+            && let Err(e) = self
+                .db
+                .add_registered_ip(&ip.to_string(), Some(username.as_str()))
+                .await
+        {
+            warn!(error = %e, ip = %ip, "Failed to store registered IP");
+        }
+    }
+}
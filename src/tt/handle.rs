@@ -0,0 +1,79 @@
+use crate::types::TTWorkerCommand;
+use std::time::Duration;
+use tokio::sync::mpsc::error::SendTimeoutError;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+
+/// Errors returned when the `TeamTalk` worker cannot serve a request.
+#[derive(Debug, thiserror::Error)]
+pub enum TTWorkerError {
+    /// The command queue stayed full for the whole timeout.
+    #[error("TeamTalk worker is overloaded (command queue full)")]
+    Overloaded,
+    /// The worker loop has stopped and dropped its receiver.
+    #[error("TeamTalk worker is not running")]
+    Unavailable,
+    /// The worker accepted the command but did not answer in time.
+    #[error("TeamTalk worker did not respond within {0:?}")]
+    TimedOut(Duration),
+}
+
+/// Cloneable sender side of the `TeamTalk` worker command queue.
+#[derive(Clone, Debug)]
+pub struct TTWorkerHandle {
+    tx: mpsc::Sender<TTWorkerCommand>,
+    command_timeout: Duration,
+}
+
+impl TTWorkerHandle {
+    /// Create a bounded command queue and the handle that feeds it.
+    pub fn channel(
+        queue_size: usize,
+        command_timeout: Duration,
+    ) -> (Self, mpsc::Receiver<TTWorkerCommand>) {
+        let (tx, rx) = mpsc::channel(queue_size.max(1));
+        (
+            Self {
+                tx,
+                command_timeout,
+            },
+            rx,
+        )
+    }
+
+    /// Number of commands waiting in the queue.
+    pub fn queue_len(&self) -> usize {
+        self.tx.max_capacity() - self.tx.capacity()
+    }
+
+    /// Maximum number of queued commands.
+    pub fn queue_capacity(&self) -> usize {
+        self.tx.max_capacity()
+    }
+
+    /// Send a command built around a response channel and wait for the answer.
+    ///
+    /// Waiting for queue capacity and waiting for the response share one
+    /// deadline of `command_timeout`.
+    pub async fn request<T>(
+        &self,
+        build: impl FnOnce(oneshot::Sender<T>) -> TTWorkerCommand,
+    ) -> Result<T, TTWorkerError> {
+        let deadline = Instant::now() + self.command_timeout;
+        let (resp_tx, resp_rx) = oneshot::channel();
+        match self
+            .tx
+            .send_timeout(build(resp_tx), self.command_timeout)
+            .await
+        {
+            Ok(()) => {}
+            Err(SendTimeoutError::Timeout(_)) => return Err(TTWorkerError::Overloaded),
+            Err(SendTimeoutError::Closed(_)) => return Err(TTWorkerError::Unavailable),
+        }
+        match tokio::time::timeout_at(deadline, resp_rx).await {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(_)) => Err(TTWorkerError::Unavailable),
+            Err(_) => Err(TTWorkerError::TimedOut(self.command_timeout)),
+        }
+    }
+}
@@ -1,3 +1,13 @@
+mod account_cache;
+mod guest_registration;
+pub mod handle;
+mod reconnect;
+mod servers;
+mod status;
+mod text_commands;
+mod welcome;
 pub mod worker;
 
+pub use handle::TTWorkerHandle;
+pub use servers::{TTServer, TTServers};
 pub use worker::run_tt_worker;
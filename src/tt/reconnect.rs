@@ -0,0 +1,113 @@
+use crate::config::TeamTalkConfig;
+use std::time::{Duration, Instant};
+
+/// A connection that lasted this long starts the backoff over when it drops.
+const STABILITY_THRESHOLD: Duration = Duration::from_secs(10);
+
+/// Reconnect backoff from the `tt_reconnect_*` settings.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct ReconnectPolicy {
+    initial_delay: Duration,
+    max_delay: Duration,
+    multiplier: f64,
+    /// `0` retries forever.
+    max_attempts: u32,
+    /// Largest fraction a delay is shortened or stretched by at random.
+    jitter: f64,
+}
+
+impl ReconnectPolicy {
+    pub const fn from_config(config: &TeamTalkConfig) -> Self {
+        Self {
+            initial_delay: Duration::from_millis(config.tt_reconnect_initial_delay_ms),
+            max_delay: Duration::from_secs(config.tt_reconnect_max_delay_seconds),
+            multiplier: config.tt_reconnect_multiplier,
+            max_attempts: config.tt_reconnect_max_attempts,
+            jitter: config.tt_reconnect_jitter,
+        }
+    }
+}
+
+/// When to try connecting again after the connection failed or dropped.
+pub(super) struct Reconnect {
+    policy: ReconnectPolicy,
+    attempts: u32,
+    /// Delay after the next attempt, before jitter.
+    delay: Duration,
+    next_at: Option<Instant>,
+    connected_at: Option<Instant>,
+    gave_up: bool,
+}
+
+impl Reconnect {
+    pub const fn new(policy: ReconnectPolicy) -> Self {
+        Self {
+            policy,
+            attempts: 0,
+            delay: policy.initial_delay,
+            next_at: None,
+            connected_at: None,
+            gave_up: false,
+        }
+    }
+
+    pub fn mark_connected(&mut self) {
+        self.connected_at = Some(Instant::now());
+    }
+
+    pub fn mark_disconnected(&mut self) {
+        if self
+            .connected_at
+            .take()
+            .is_some_and(|at| at.elapsed() >= STABILITY_THRESHOLD)
+        {
+            self.attempts = 0;
+            self.delay = self.policy.initial_delay;
+            self.next_at = None;
+            self.gave_up = false;
+        }
+    }
+
+    pub const fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Whether every allowed attempt has been made.
+    pub const fn is_exhausted(&self) -> bool {
+        self.policy.max_attempts > 0 && self.attempts >= self.policy.max_attempts
+    }
+
+    /// Whether the next attempt may start now.
+    pub fn is_due(&self) -> bool {
+        !self.is_exhausted() && self.next_at.is_none_or(|at| Instant::now() >= at)
+    }
+
+    /// Count an attempt and schedule the next one; returns the wait before it.
+    pub fn record_attempt(&mut self) -> Duration {
+        self.attempts += 1;
+        let wait = self.jittered(self.delay);
+        self.next_at = Some(Instant::now() + wait);
+        self.delay = Duration::try_from_secs_f64(self.delay.as_secs_f64() * self.policy.multiplier)
+            .map_or(self.policy.max_delay, |next| {
+                next.min(self.policy.max_delay)
+            });
+        wait
+    }
+
+    /// `true` the first time this is called after the attempts ran out.
+    pub const fn give_up(&mut self) -> bool {
+        if !self.is_exhausted() || self.gave_up {
+            return false;
+        }
+        self.gave_up = true;
+        true
+    }
+
+    fn jittered(&self, delay: Duration) -> Duration {
+        if self.policy.jitter <= 0.0 {
+            return delay;
+        }
+        let factor = rand::random_range(-self.policy.jitter..=self.policy.jitter);
+        delay.mul_f64(1.0 + factor)
+    }
+}
@@ -0,0 +1,61 @@
+use super::TTWorkerHandle;
+use crate::config::{AppConfig, DEFAULT_SERVER_ID};
+use std::sync::Arc;
+
+/// One `TeamTalk` server accounts can be registered on.
+#[derive(Clone, Debug)]
+pub struct TTServer {
+    /// Configuration with this server's connection settings.
+    pub config: Arc<AppConfig>,
+    pub tx_tt: TTWorkerHandle,
+}
+
+impl TTServer {
+    pub fn id(&self) -> &str {
+        &self.config.teamtalk.server_id
+    }
+
+    /// Name users pick the server by.
+    pub fn label(&self) -> &str {
+        &self.config.teamtalk.server_name
+    }
+}
+
+/// Every configured server; the top-level one comes first.
+#[derive(Clone, Debug)]
+pub struct TTServers {
+    servers: Arc<Vec<TTServer>>,
+}
+
+impl TTServers {
+    pub fn new(servers: Vec<TTServer>) -> Self {
+        debug_assert!(servers.first().is_some_and(|s| s.id() == DEFAULT_SERVER_ID));
+        Self {
+            servers: Arc::new(servers),
+        }
+    }
+
+    /// The server of the top-level connection settings.
+    pub fn primary(&self) -> &TTServer {
+        &self.servers[0]
+    }
+
+    pub fn get(&self, id: &str) -> Option<&TTServer> {
+        self.servers.iter().find(|server| server.id() == id)
+    }
+
+    /// The server a stored request names; requests from before servers were
+    /// recorded name none and go to the primary one.
+    pub fn resolve(&self, id: Option<&str>) -> Option<&TTServer> {
+        id.map_or_else(|| Some(self.primary()), |id| self.get(id))
+    }
+
+    pub fn all(&self) -> &[TTServer] {
+        &self.servers
+    }
+
+    /// Whether users have a server to choose.
+    pub fn has_choice(&self) -> bool {
+        self.servers.len() > 1
+    }
+}
@@ -0,0 +1,188 @@
+use crate::i18n::t_args;
+use crate::tg_bot::notify::AdminChats;
+use crate::types::{LanguageCode, NotificationKind};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use teloxide::prelude::*;
+use tokio::runtime::Handle;
+
+/// How long a notice waits for further changes before it is sent, so a
+/// connect immediately followed by a login becomes one message.
+const NOTICE_SETTLE: Duration = Duration::from_secs(2);
+
+/// Connection details the worker reports through `GetStatus`, with the
+/// notices sent to admins when they change.
+pub(super) struct ConnectionTracker {
+    logged_in_at: Option<Instant>,
+    server_name: Option<String>,
+    last_error: Option<String>,
+    reconnect_attempts: u32,
+    notices: ConnectionNotices,
+}
+
+impl ConnectionTracker {
+    pub const fn new(notices: ConnectionNotices) -> Self {
+        Self {
+            logged_in_at: None,
+            server_name: None,
+            last_error: None,
+            reconnect_attempts: 0,
+            notices,
+        }
+    }
+
+    pub const fn is_logged_in(&self) -> bool {
+        self.logged_in_at.is_some()
+    }
+
+    pub fn connected(&mut self) {
+        self.notices.push("tt-conn-connected", &HashMap::new());
+    }
+
+    pub fn logged_in(&mut self, server_name: Option<String>) {
+        self.logged_in_at = Some(Instant::now());
+        self.reconnect_attempts = 0;
+        let args = HashMap::from([(
+            "server".to_string(),
+            server_name.clone().unwrap_or_default(),
+        )]);
+        self.server_name = server_name;
+        self.notices.push("tt-conn-logged-in", &args);
+    }
+
+    /// `was_connected` tells a dropped connection from a failed attempt;
+    /// only the former is announced, attempts are reported by [`Self::reconnecting`].
+    pub fn disconnected(&mut self, was_connected: bool) {
+        self.logged_in_at = None;
+        if was_connected {
+            self.last_error = Some("Connection lost".to_string());
+            self.notices.push("tt-conn-lost", &HashMap::new());
+        } else {
+            self.last_error = Some("Connection failed".to_string());
+        }
+    }
+
+    pub fn reconnecting(&mut self, attempts: u32, next_delay: Duration) {
+        self.reconnect_attempts = attempts;
+        let args = HashMap::from([
+            ("attempt".to_string(), attempts.to_string()),
+            ("delay".to_string(), next_delay.as_secs().max(1).to_string()),
+        ]);
+        self.notices.push("tt-conn-reconnecting", &args);
+    }
+
+    /// Alert admins, regardless of the notice interval, that reconnecting stopped.
+    pub fn gave_up(&mut self, attempts: u32) {
+        self.last_error = Some(format!("Gave up reconnecting after {attempts} attempts"));
+        let args = HashMap::from([("attempts".to_string(), attempts.to_string())]);
+        self.notices.alert("tt-conn-gave-up", &args);
+    }
+
+    pub fn error(&mut self, message: String) {
+        self.last_error = Some(message);
+    }
+
+    /// Send a queued notice if it is due.
+    pub fn flush(&mut self) {
+        self.notices.flush();
+    }
+
+    pub fn uptime(&self) -> Option<Duration> {
+        self.logged_in_at.map(|at| at.elapsed())
+    }
+
+    pub fn server_name(&self) -> Option<&str> {
+        self.server_name.as_deref()
+    }
+
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    pub const fn reconnect_attempts(&self) -> u32 {
+        self.reconnect_attempts
+    }
+}
+
+/// Throttled connection notices to admins: at most one per `interval`, and
+/// changes arriving in between collapse into the latest one.
+pub(super) struct ConnectionNotices {
+    bot: Bot,
+    admin_chats: AdminChats,
+    lang: LanguageCode,
+    rt_handle: Handle,
+    interval: Option<Duration>,
+    last_sent: Option<Instant>,
+    pending: Option<(String, Instant)>,
+}
+
+impl ConnectionNotices {
+    /// `interval_seconds == 0` turns notices off.
+    pub fn new(
+        bot: Bot,
+        admin_chats: AdminChats,
+        lang: LanguageCode,
+        rt_handle: Handle,
+        interval_seconds: u64,
+    ) -> Self {
+        Self {
+            bot,
+            admin_chats,
+            lang,
+            rt_handle,
+            interval: (interval_seconds > 0).then(|| Duration::from_secs(interval_seconds)),
+            last_sent: None,
+            pending: None,
+        }
+    }
+
+    /// Queue the notice `key`, replacing one that has not been sent yet.
+    pub fn push(&mut self, key: &str, args: &HashMap<String, String>) {
+        if self.interval.is_none() {
+            return;
+        }
+        let text = t_args(self.lang.as_str(), key, args);
+        self.pending = Some((text, Instant::now()));
+    }
+
+    /// Send the notice `key` right away, dropping any queued one.
+    pub fn alert(&mut self, key: &str, args: &HashMap<String, String>) {
+        let text = t_args(self.lang.as_str(), key, args);
+        self.pending = None;
+        self.last_sent = Some(Instant::now());
+        self.send(text);
+    }
+
+    /// Send the queued notice once it has settled and the interval allows.
+    pub fn flush(&mut self) {
+        let Some(interval) = self.interval else {
+            return;
+        };
+        let Some((_, queued_at)) = &self.pending else {
+            return;
+        };
+        let now = Instant::now();
+        if now.duration_since(*queued_at) < NOTICE_SETTLE
+            || self
+                .last_sent
+                .is_some_and(|sent| now.duration_since(sent) < interval)
+        {
+            return;
+        }
+        let Some((text, _)) = self.pending.take() else {
+            return;
+        };
+        self.last_sent = Some(now);
+        self.send(text);
+    }
+
+    fn send(&self, text: String) {
+        let bot = self.bot.clone();
+        let admin_chats = self.admin_chats.clone();
+        self.rt_handle.spawn(async move {
+            admin_chats
+                .send(&bot, NotificationKind::Connection, &text, None, None)
+                .await;
+        });
+    }
+}
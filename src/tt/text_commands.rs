@@ -0,0 +1,121 @@
+use super::guest_registration::{GuestRegistrar, GuestRequest};
+use crate::i18n::{t, t_args};
+use crate::types::LanguageCode;
+use std::collections::HashMap;
+use teamtalk::Client;
+use teamtalk::types::TextMessage;
+use tracing::{debug, warn};
+
+/// `TeamTalk` cuts text messages after 511 bytes; longer replies are split.
+const MAX_REPLY_BYTES: usize = 500;
+
+/// Answer a private message sent to the bot by a user on the server;
+/// `guests` is set when `register` is enabled.
+pub(super) fn handle_text_message(
+    client: &Client,
+    msg: &teamtalk::Message,
+    lang: &LanguageCode,
+    guests: Option<&GuestRegistrar>,
+) {
+    let Some(text) = msg.text() else {
+        return;
+    };
+    let my_id = client.my_id();
+    if text.to_id != my_id || text.from_id == my_id || text.more {
+        return;
+    }
+    let mut words = text.text.split_whitespace();
+    let command = words.next().unwrap_or_default().to_lowercase();
+    debug!(from = %text.from_username, command, "TeamTalk private message");
+    let lang = lang.as_str();
+    let reply = match (command.as_str(), guests) {
+        ("register", Some(guests)) => {
+            let args: Vec<&str> = words.collect();
+            let [username, password] = args[..] else {
+                reply_to(client, &text, &t(lang, "tt-pm-register-usage"));
+                return;
+            };
+            let user = client.get_user(text.from_id);
+            guests.spawn(GuestRequest {
+                user_id: text.from_id.0,
+                nickname: user
+                    .as_ref()
+                    .map(|user| user.nickname.clone())
+                    .unwrap_or_default(),
+                ip: user.and_then(|user| user.ip_address.parse().ok()),
+                username: username.to_string(),
+                password: password.to_string(),
+            });
+            return;
+        }
+        ("help", None) => t(lang, "tt-pm-help"),
+        ("help", Some(_)) => format!(
+            "{}\n{}",
+            t(lang, "tt-pm-help"),
+            t(lang, "tt-pm-help-register")
+        ),
+        ("who", _) => who(client, lang),
+        ("version", _) => t_args(
+            lang,
+            "tt-pm-version",
+            &HashMap::from([("version".to_string(), env!("CARGO_PKG_VERSION").to_string())]),
+        ),
+        _ => t(lang, "tt-pm-unknown"),
+    };
+    reply_to(client, &text, &reply);
+}
+
+fn who(client: &Client, lang: &str) -> String {
+    let my_id = client.my_id();
+    let mut names: Vec<String> = client
+        .get_server_users()
+        .into_iter()
+        .filter(|user| user.id != my_id)
+        .map(|user| {
+            if user.nickname.trim().is_empty() {
+                user.username
+            } else {
+                user.nickname
+            }
+        })
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    let args = HashMap::from([
+        ("count".to_string(), names.len().to_string()),
+        ("users".to_string(), names.join(", ")),
+    ]);
+    t_args(lang, "tt-pm-who", &args)
+}
+
+fn reply_to(client: &Client, text: &TextMessage, reply: &str) {
+    for part in split_reply(reply) {
+        if client.send_to_user(text.from_id, part) <= 0 {
+            warn!(to = %text.from_username, "Failed to send TeamTalk private reply");
+            return;
+        }
+    }
+}
+
+/// Split `reply` into parts that fit one message, preferring line and list
+/// boundaries.
+pub(super) fn split_reply(reply: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = reply.trim();
+    while rest.len() > MAX_REPLY_BYTES {
+        let mut end = MAX_REPLY_BYTES;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let cut = rest[..end]
+            .rfind('\n')
+            .or_else(|| rest[..end].rfind(", ").map(|at| at + 1))
+            .filter(|&at| at > 0)
+            .unwrap_or(end);
+        parts.push(rest[..cut].trim_end());
+        rest = rest[cut..].trim_start();
+    }
+    if !rest.is_empty() {
+        parts.push(rest);
+    }
+    parts
+}
@@ -0,0 +1,109 @@
+use super::TTWorkerHandle;
+use super::text_commands::split_reply;
+use crate::config::AppConfig;
+use crate::db::Database;
+use crate::files::tt::render;
+use crate::i18n::t_args;
+use crate::types::TTWorkerCommand;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tracing::{info, warn};
+
+/// Sends accounts the bot created a welcome private message on their first
+/// login.
+#[derive(Clone)]
+pub(super) struct Welcomer {
+    config: Arc<AppConfig>,
+    db: Database,
+    tx_tt: TTWorkerHandle,
+    rt_handle: Handle,
+}
+
+/// A user who just logged in.
+pub(super) struct LoggedInUser {
+    pub user_id: i32,
+    pub username: String,
+    pub nickname: String,
+}
+
+impl Welcomer {
+    /// `None` unless `tt_welcome_enabled` is set.
+    pub fn new(
+        config: &Arc<AppConfig>,
+        db: &Database,
+        tx_tt: &TTWorkerHandle,
+        rt_handle: &Handle,
+    ) -> Option<Self> {
+        config.teamtalk.tt_welcome_enabled.then(|| Self {
+            config: config.clone(),
+            db: db.clone(),
+            tx_tt: tx_tt.clone(),
+            rt_handle: rt_handle.clone(),
+        })
+    }
+
+    /// Welcome `user` in the background if their account is still waiting
+    /// for it.
+    pub fn spawn(&self, user: LoggedInUser) {
+        let this = self.clone();
+        self.rt_handle.spawn(async move {
+            let server_id = &this.config.teamtalk.server_id;
+            let lang = match this.db.claim_account_welcome(server_id, &user.username).await {
+                Ok(Some(lang)) => lang,
+                Ok(None) => return,
+                Err(e) => {
+                    warn!(error = %e, username = %user.username, "Failed to look up welcome message");
+                    return;
+                }
+            };
+            let text = this.message(&lang, &user);
+            for part in split_reply(&text) {
+                let sent = this
+                    .tx_tt
+                    .request(|resp| TTWorkerCommand::SendUserMessage {
+                        user_id: user.user_id,
+                        text: part.to_string(),
+                        resp,
+                    })
+                    .await;
+                if !matches!(sent, Ok(Ok(true))) {
+                    warn!(username = %user.username, "Failed to send welcome message");
+                    return;
+                }
+            }
+            info!(username = %user.username, "Sent welcome message");
+        });
+    }
+
+    fn message(&self, lang: &str, user: &LoggedInUser) -> String {
+        let nickname = if user.nickname.trim().is_empty() {
+            &user.username
+        } else {
+            &user.nickname
+        };
+        let server = &self.config.teamtalk.server_name;
+        self.config
+            .teamtalk
+            .tt_welcome_messages
+            .get(lang)
+            .map_or_else(
+                || {
+                    let args = HashMap::from([
+                        ("username".to_string(), user.username.clone()),
+                        ("nickname".to_string(), nickname.clone()),
+                        ("server".to_string(), server.clone()),
+                    ]);
+                    t_args(lang, "tt-welcome", &args)
+                },
+                |template| {
+                    render(template, |name| match name {
+                        "username" => Some(user.username.clone()),
+                        "nickname" => Some(nickname.clone()),
+                        "server" => Some(server.clone()),
+                        _ => None,
+                    })
+                },
+            )
+    }
+}
@@ -1,28 +1,66 @@
-use crate::config::AppConfig;
+use super::TTWorkerHandle;
+use super::account_cache::AccountCache;
+use super::guest_registration::GuestRegistrar;
+use super::reconnect::{Reconnect, ReconnectPolicy};
+use super::status::{ConnectionNotices, ConnectionTracker};
+use super::text_commands::handle_text_message;
+use super::welcome::{LoggedInUser, Welcomer};
+use crate::config::{AppConfig, DEFAULT_RIGHTS_PROFILE, personal_channel_path};
 use crate::db::Database;
+use crate::domain::UsernameMatch;
 use crate::files::get_user_rights_mask;
-use crate::i18n::t_args;
+use crate::i18n::{t, t_args};
+use crate::services::audit::{self, Actor, AuditAction};
+use crate::services::bans;
+use crate::tg_bot::keyboards::{undo_ban_keyboard, undo_bans_callback};
+use crate::tg_bot::notify::AdminChats;
+use crate::tg_bot::throttle;
 use crate::types::{
-    LanguageCode, OnlineUser, RegistrationSource, TTAccountType, TTWorkerCommand, TelegramId,
+    LanguageCode, NotificationKind, OnlineUser, RegistrationSource, TTAccountType, TTCommandError,
+    TTStatus, TTWorkerCommand, TelegramId,
 };
-use std::collections::HashMap;
-use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use std::time::Instant;
-use teamtalk::client::{ConnectParams, ReconnectConfig, ReconnectHandler};
-use teamtalk::types::{ErrorMessage, UserAccount, UserGender, UserPresence, UserStatus};
+use teamtalk::client::ConnectParams;
+use teamtalk::types::{
+    Channel, ChannelId, ChannelType, ErrorMessage, UserAccount, UserGender, UserId, UserPresence,
+    UserStatus,
+};
 use teamtalk::{Client, Event};
 use teloxide::prelude::*;
-use teloxide::types::ChatId;
 use tokio::runtime::Handle;
+use tokio::sync::mpsc::Receiver;
 use tokio::sync::oneshot;
 use tokio::task::AbortHandle;
 use tracing::instrument;
 use tracing::{debug, error, info, warn};
 
+/// `BANTYPE_IPADDR | BANTYPE_USERNAME`: server-wide ban on the user's IP and account.
+const SERVER_BAN_TYPES: u32 = 0x02 | 0x04;
+
 struct PendingCommand {
-    resp: oneshot::Sender<Result<bool, String>>,
+    resp: oneshot::Sender<Result<bool, TTCommandError>>,
+    dispatched_at: Instant,
+    /// Account to create once this `MakeChannel` command has finished.
+    then_create: Option<PendingChannelAccount>,
+}
+
+/// An account waiting for its personal channel at `path`.
+struct PendingChannelAccount {
+    account: UserAccount,
+    path: String,
+}
+
+impl PendingCommand {
+    fn new(resp: oneshot::Sender<Result<bool, TTCommandError>>) -> Self {
+        Self {
+            resp,
+            dispatched_at: Instant::now(),
+            then_create: None,
+        }
+    }
 }
 
 enum PendingListKind {
@@ -31,28 +69,75 @@ enum PendingListKind {
     },
     Exists {
         username: crate::domain::Username,
+        matching: UsernameMatch,
         resp: oneshot::Sender<bool>,
     },
+    Update {
+        username: crate::domain::Username,
+        changes: AccountChanges,
+        resp: oneshot::Sender<Result<bool, TTCommandError>>,
+    },
+    /// Full listing that reloads the [`AccountCache`].
+    Refresh,
+}
+
+/// Fields of `TTWorkerCommand::UpdateAccount` to apply to the looked-up account.
+struct AccountChanges {
+    password: Option<crate::domain::Password>,
+    rights: Option<Vec<String>>,
+    note: Option<String>,
 }
 
 struct PendingListRequest {
     kind: PendingListKind,
     accumulated: Vec<String>,
+    matched: Option<UserAccount>,
+    dispatched_at: Instant,
     completed_at: Option<Instant>,
     mismatch_logged: bool,
 }
 
+impl PendingListRequest {
+    fn new(kind: PendingListKind) -> Self {
+        Self {
+            kind,
+            accumulated: Vec::new(),
+            matched: None,
+            dispatched_at: Instant::now(),
+            completed_at: None,
+            mismatch_logged: false,
+        }
+    }
+
+    fn record(&mut self, acc: UserAccount) {
+        if let PendingListKind::Update { username, .. } = &self.kind
+            && acc.username == username.as_str()
+        {
+            self.accumulated.push(acc.username.clone());
+            self.matched = Some(acc);
+        } else {
+            self.accumulated.push(acc.username);
+        }
+        if self.completed_at.is_some() {
+            self.completed_at = Some(Instant::now());
+        }
+    }
+}
+
 struct CommandContext<'a> {
     client: &'a Client,
-    rights: &'a [String],
+    rights_profiles: &'a BTreeMap<String, Vec<String>>,
+    personal_channel_parent: Option<&'a str>,
     broadcast_enabled: bool,
     admin_lang: &'a LanguageCode,
     pending_cmds: &'a mut HashMap<i32, PendingCommand>,
     pending_lists: &'a mut HashMap<i32, PendingListRequest>,
-    is_logged_in: bool,
+    connection: &'a ConnectionTracker,
+    accounts: &'a AccountCache,
 }
 
 struct TTWorkerConfig {
+    server_id: String,
     host: String,
     tcp_port: i32,
     udp_port: i32,
@@ -61,14 +146,42 @@ struct TTWorkerConfig {
     username: String,
     password: String,
     client_name: String,
-    rights: Vec<String>,
+    join_channel: Option<String>,
+    join_channel_password: String,
+    rights_profiles: BTreeMap<String, Vec<String>>,
+    personal_channel_parent: Option<String>,
     broadcast_enabled: bool,
-    admin_ids: Vec<TelegramId>,
+    removal: RemovalPolicy,
+    admin_chats: AdminChats,
     admin_lang: LanguageCode,
+    connection_notify_interval: u64,
+    reconnect: ReconnectPolicy,
+    account_cache_refresh: u64,
+    command_timeout: Duration,
     tt_gender_str: String,
     tt_status_text: String,
 }
 
+/// How accounts deleted on the server are handled.
+#[derive(Clone, Copy)]
+struct RemovalPolicy {
+    notify_user: bool,
+    auto_ban: bool,
+    debounce: Duration,
+}
+
+impl TTWorkerConfig {
+    fn connection_tracker(&self, bot: &Bot, rt_handle: &Handle) -> ConnectionTracker {
+        ConnectionTracker::new(ConnectionNotices::new(
+            bot.clone(),
+            self.admin_chats.clone(),
+            self.admin_lang.clone(),
+            rt_handle.clone(),
+            self.connection_notify_interval,
+        ))
+    }
+}
+
 struct TTWorkerRuntime {
     config: TTWorkerConfig,
     rx: Receiver<TTWorkerCommand>,
@@ -77,21 +190,29 @@ struct TTWorkerRuntime {
     rt_handle: Handle,
     shutdown: tokio_util::sync::CancellationToken,
     pending_deletions: Arc<Mutex<HashMap<String, AbortHandle>>>,
+    guests: Option<GuestRegistrar>,
+    welcomer: Option<Welcomer>,
 }
 fn handle_command(cmd: TTWorkerCommand, ctx: &mut CommandContext<'_>) {
-    if !ctx.is_logged_in {
-        handle_command_disconnected(cmd);
+    if !ctx.connection.is_logged_in() {
+        handle_command_disconnected(cmd, ctx);
         return;
     }
 
     handle_command_connected(cmd, ctx);
 }
 
-fn handle_command_disconnected(cmd: TTWorkerCommand) {
+fn handle_command_disconnected(cmd: TTWorkerCommand, ctx: &CommandContext<'_>) {
     match cmd {
-        TTWorkerCommand::CreateAccount { resp, .. } | TTWorkerCommand::DeleteUser { resp, .. } => {
+        TTWorkerCommand::CreateAccount { resp, .. }
+        | TTWorkerCommand::DeleteUser { resp, .. }
+        | TTWorkerCommand::UpdateAccount { resp, .. }
+        | TTWorkerCommand::KickUser { resp, .. }
+        | TTWorkerCommand::BanUser { resp, .. }
+        | TTWorkerCommand::Broadcast { resp, .. }
+        | TTWorkerCommand::SendUserMessage { resp, .. } => {
             warn!("Rejecting TT command: bot not connected");
-            let _ = resp.send(Err("Bot not connected to TeamTalk".to_string()));
+            let _ = resp.send(Err(TTCommandError::NotConnected));
         }
         TTWorkerCommand::CheckUserExists { resp, .. } => {
             warn!("Rejecting user existence check: bot not connected");
@@ -105,6 +226,7 @@ fn handle_command_disconnected(cmd: TTWorkerCommand) {
             warn!("Rejecting online users request: bot not connected");
             let _ = resp.send(vec![]);
         }
+        TTWorkerCommand::GetStatus { resp } => handle_get_status(ctx, resp),
     }
 }
 
@@ -115,6 +237,7 @@ fn handle_command_connected(cmd: TTWorkerCommand, ctx: &mut CommandContext<'_>)
             password,
             nickname,
             account_type,
+            rights_profile,
             source,
             source_info,
             resp,
@@ -124,6 +247,7 @@ fn handle_command_connected(cmd: TTWorkerCommand, ctx: &mut CommandContext<'_>)
                 password,
                 nickname,
                 account_type,
+                rights_profile,
                 source,
                 source_info,
                 resp,
@@ -133,9 +257,54 @@ fn handle_command_connected(cmd: TTWorkerCommand, ctx: &mut CommandContext<'_>)
         TTWorkerCommand::DeleteUser { username, resp } => {
             handle_delete_user(ctx, &username, resp);
         }
+        TTWorkerCommand::UpdateAccount {
+            username,
+            new_password,
+            new_rights,
+            new_note,
+            resp,
+        } => {
+            let changes = AccountChanges {
+                password: new_password,
+                rights: new_rights,
+                note: new_note,
+            };
+            handle_update_account(ctx, username, changes, resp);
+        }
+        TTWorkerCommand::KickUser { user_id, resp } => {
+            debug!(user_id, "Sending KickUser");
+            let cmd_id = ctx.client.kick_user(UserId(user_id), ChannelId(0));
+            track_command(ctx, "KickUser", cmd_id, resp);
+        }
+        TTWorkerCommand::BanUser { user_id, resp } => {
+            debug!(user_id, "Sending BanUser");
+            let cmd_id = ctx.client.ban_user_ex(UserId(user_id), SERVER_BAN_TYPES);
+            track_command(ctx, "BanUser", cmd_id, resp);
+            if cmd_id > 0 && ctx.client.kick_user(UserId(user_id), ChannelId(0)) <= 0 {
+                warn!(user_id, "Failed to dispatch kick after ban");
+            }
+        }
+        TTWorkerCommand::SendUserMessage {
+            user_id,
+            text,
+            resp,
+        } => {
+            debug!(user_id, "Sending text message to user");
+            let cmd_id = ctx.client.send_to_user(UserId(user_id), &text);
+            track_command(ctx, "SendUserMessage", cmd_id, resp);
+        }
+        TTWorkerCommand::Broadcast { text, resp } => {
+            debug!("Sending broadcast message");
+            let cmd_id = ctx.client.send_to_all(&text);
+            track_command(ctx, "Broadcast", cmd_id, resp);
+        }
         TTWorkerCommand::GetAllUsers { resp } => handle_get_all_users(ctx, resp),
-        TTWorkerCommand::CheckUserExists { username, resp } => {
-            handle_check_user_exists(ctx, username, resp);
+        TTWorkerCommand::CheckUserExists {
+            username,
+            matching,
+            resp,
+        } => {
+            handle_check_user_exists(ctx, username, matching, resp);
         }
         TTWorkerCommand::GetOnlineUsers { resp } => {
             let users = ctx.client.get_server_users();
@@ -157,17 +326,32 @@ fn handle_command_connected(cmd: TTWorkerCommand, ctx: &mut CommandContext<'_>)
                 .collect();
             let _ = resp.send(mapped);
         }
+        TTWorkerCommand::GetStatus { resp } => handle_get_status(ctx, resp),
     }
 }
 
+fn handle_get_status(ctx: &CommandContext<'_>, resp: oneshot::Sender<TTStatus>) {
+    let _ = resp.send(TTStatus {
+        connected: ctx.client.is_connected(),
+        logged_in: ctx.connection.is_logged_in(),
+        pending_commands: ctx.pending_cmds.len(),
+        pending_lists: ctx.pending_lists.len(),
+        uptime_seconds: ctx.connection.uptime().map(|uptime| uptime.as_secs()),
+        server_name: ctx.connection.server_name().map(str::to_string),
+        last_error: ctx.connection.last_error().map(str::to_string),
+        reconnect_attempts: ctx.connection.reconnect_attempts(),
+    });
+}
+
 struct CreateAccountInput {
     username: crate::domain::Username,
     password: crate::domain::Password,
     nickname: crate::domain::Nickname,
     account_type: TTAccountType,
+    rights_profile: String,
     source: RegistrationSource,
     source_info: Option<String>,
-    resp: oneshot::Sender<Result<bool, String>>,
+    resp: oneshot::Sender<Result<bool, TTCommandError>>,
 }
 
 fn handle_create_account(input: CreateAccountInput, ctx: &mut CommandContext<'_>) {
@@ -176,13 +360,24 @@ fn handle_create_account(input: CreateAccountInput, ctx: &mut CommandContext<'_>
         password,
         nickname,
         account_type,
+        rights_profile,
         source,
         source_info,
         resp,
     } = input;
+    // Creating an account that exists would overwrite it instead of failing.
+    if ctx
+        .accounts
+        .contains(username.as_str(), UsernameMatch::Exact)
+    {
+        warn!(username = %username.as_str(), "Refusing to create an existing account");
+        let _ = resp.send(Err(TTCommandError::AccountExists));
+        return;
+    }
     let source_info = source_info.unwrap_or_else(|| match &source {
         RegistrationSource::Telegram(id) => format!("Telegram ID: {id}"),
         RegistrationSource::Web(ip) => format!("Web IP: {ip}"),
+        RegistrationSource::TeamTalk(ip) => format!("TeamTalk IP: {ip}"),
     });
     debug!(
         "Sending CreateAccount for '{}'. Source: {}",
@@ -190,7 +385,15 @@ fn handle_create_account(input: CreateAccountInput, ctx: &mut CommandContext<'_>
         source_info
     );
 
-    let rights_mask = get_user_rights_mask(ctx.rights);
+    let rights = ctx
+        .rights_profiles
+        .get(&rights_profile)
+        .or_else(|| {
+            warn!(profile = %rights_profile, "Unknown rights profile; using the default one");
+            ctx.rights_profiles.get(DEFAULT_RIGHTS_PROFILE)
+        })
+        .map_or(&[][..], Vec::as_slice);
+    let rights_mask = get_user_rights_mask(rights);
 
     let user_type = match account_type {
         TTAccountType::Admin => teamtalk::client::ffi::UserType::USERTYPE_ADMIN as u32,
@@ -204,34 +407,158 @@ fn handle_create_account(input: CreateAccountInput, ctx: &mut CommandContext<'_>
         .build();
     acc.note = format!("Reg via Bot ({source_info}), nick={}", nickname.as_str());
 
-    let cmd_id = ctx.client.create_user_account(&acc);
-    if cmd_id > 0 {
-        debug!(cmd_id, "CreateAccount dispatched");
+    let pending = match ctx.personal_channel_parent {
+        Some(parent) => make_personal_channel(ctx.client, parent, acc, resp),
+        None => dispatch_create_account(ctx.client, &acc, resp),
+    };
+    if let Some((cmd_id, pending)) = pending {
         if ctx.broadcast_enabled {
             let args = HashMap::from([("username".to_string(), username.as_str().to_string())]);
             let msg = t_args(ctx.admin_lang.as_str(), "tt-broadcast-registration", &args);
             ctx.client.send_to_all(&msg);
         }
-        ctx.pending_cmds.insert(cmd_id, PendingCommand { resp });
+        ctx.pending_cmds.insert(cmd_id, pending);
+    }
+}
+
+fn dispatch_create_account(
+    client: &Client,
+    acc: &UserAccount,
+    resp: oneshot::Sender<Result<bool, TTCommandError>>,
+) -> Option<(i32, PendingCommand)> {
+    let cmd_id = client.create_user_account(acc);
+    if cmd_id > 0 {
+        debug!(cmd_id, "CreateAccount dispatched");
+        Some((cmd_id, PendingCommand::new(resp)))
     } else {
         warn!("CreateAccount dispatch failed (cmd_id=0)");
-        let _ = resp.send(Err("Client error dispatching command".to_string()));
+        let _ = resp.send(Err(TTCommandError::Other(
+            "Client error dispatching command".to_string(),
+        )));
+        None
+    }
+}
+
+/// Create the account's permanent channel under `parent`, then the account
+/// itself once the server confirms it. An existing channel is reused, and the
+/// account is still created when the parent channel is missing.
+fn make_personal_channel(
+    client: &Client,
+    parent: &str,
+    mut acc: UserAccount,
+    resp: oneshot::Sender<Result<bool, TTCommandError>>,
+) -> Option<(i32, PendingCommand)> {
+    let path = personal_channel_path(parent, &acc.username);
+    let existing = client.get_channel_id_from_path(&path);
+    if existing.0 > 0 {
+        debug!(path = %path, "Personal channel already exists");
+        acc.auto_operator_channels = vec![existing];
+        return dispatch_create_account(client, &acc, resp);
+    }
+    let parent_id = client.get_channel_id_from_path(parent);
+    let Some(parent_channel) = client.get_channel(parent_id).filter(|_| parent_id.0 > 0) else {
+        warn!(parent = %parent, "Personal channel parent not found; creating account without it");
+        return dispatch_create_account(client, &acc, resp);
+    };
+    let channel = Channel::builder(&acc.username.replace('/', "_"))
+        .parent(parent_id)
+        .channel_type(ChannelType::from_raw(ChannelType::PERMANENT))
+        .codec(parent_channel.audio_codec)
+        .build();
+    let cmd_id = client.make_channel(&channel);
+    if cmd_id > 0 {
+        debug!(cmd_id, path = %path, "MakeChannel dispatched");
+        Some((
+            cmd_id,
+            PendingCommand {
+                resp,
+                dispatched_at: Instant::now(),
+                then_create: Some(PendingChannelAccount { account: acc, path }),
+            },
+        ))
+    } else {
+        warn!(path = %path, "MakeChannel dispatch failed (cmd_id=0); creating account without it");
+        dispatch_create_account(client, &acc, resp)
+    }
+}
+
+/// Create the account held back for its personal channel, making the user
+/// operator of the channel when it exists.
+fn create_after_channel(
+    client: &Client,
+    pending_cmds: &mut HashMap<i32, PendingCommand>,
+    pending: PendingChannelAccount,
+    resp: oneshot::Sender<Result<bool, TTCommandError>>,
+) {
+    let PendingChannelAccount { mut account, path } = pending;
+    let channel_id = client.get_channel_id_from_path(&path);
+    if channel_id.0 > 0 {
+        account.auto_operator_channels = vec![channel_id];
+    } else {
+        warn!(path = %path, "Personal channel not found; creating account without it");
+    }
+    if let Some((cmd_id, pending)) = dispatch_create_account(client, &account, resp) {
+        pending_cmds.insert(cmd_id, pending);
     }
 }
 
 fn handle_delete_user(
     ctx: &mut CommandContext<'_>,
     username: &crate::domain::Username,
-    resp: oneshot::Sender<Result<bool, String>>,
+    resp: oneshot::Sender<Result<bool, TTCommandError>>,
 ) {
     debug!(username = %username.as_str(), "Sending DeleteUser");
     let cmd_id = ctx.client.delete_user_account(username.as_str());
     if cmd_id > 0 {
         debug!(cmd_id, "DeleteUser dispatched");
-        ctx.pending_cmds.insert(cmd_id, PendingCommand { resp });
+        ctx.pending_cmds.insert(cmd_id, PendingCommand::new(resp));
     } else {
         warn!(username = %username.as_str(), "DeleteUser dispatch failed (cmd_id=0)");
-        let _ = resp.send(Err("Failed to dispatch command".to_string()));
+        let _ = resp.send(Err(TTCommandError::Other(
+            "Failed to dispatch command".to_string(),
+        )));
+    }
+}
+
+fn track_command(
+    ctx: &mut CommandContext<'_>,
+    name: &str,
+    cmd_id: i32,
+    resp: oneshot::Sender<Result<bool, TTCommandError>>,
+) {
+    if cmd_id > 0 {
+        debug!(cmd_id, command = name, "Command dispatched");
+        ctx.pending_cmds.insert(cmd_id, PendingCommand::new(resp));
+    } else {
+        warn!(command = name, "Command dispatch failed (cmd_id=0)");
+        let _ = resp.send(Err(TTCommandError::Other(
+            "Failed to dispatch command".to_string(),
+        )));
+    }
+}
+
+fn handle_update_account(
+    ctx: &mut CommandContext<'_>,
+    username: crate::domain::Username,
+    changes: AccountChanges,
+    resp: oneshot::Sender<Result<bool, TTCommandError>>,
+) {
+    debug!(username = %username.as_str(), "Looking up account for UpdateAccount");
+    let cmd_id = ctx.client.list_user_accounts(0, 10000);
+    if cmd_id > 0 {
+        ctx.pending_lists.insert(
+            cmd_id,
+            PendingListRequest::new(PendingListKind::Update {
+                username,
+                changes,
+                resp,
+            }),
+        );
+    } else {
+        warn!("User accounts list dispatch failed (cmd_id=0)");
+        let _ = resp.send(Err(TTCommandError::Other(
+            "Failed to dispatch command".to_string(),
+        )));
     }
 }
 
@@ -242,12 +569,7 @@ fn handle_get_all_users(ctx: &mut CommandContext<'_>, resp: oneshot::Sender<Vec<
         debug!(cmd_id, "User accounts list dispatched");
         ctx.pending_lists.insert(
             cmd_id,
-            PendingListRequest {
-                kind: PendingListKind::AllUsers { resp },
-                accumulated: Vec::new(),
-                completed_at: None,
-                mismatch_logged: false,
-            },
+            PendingListRequest::new(PendingListKind::AllUsers { resp }),
         );
     } else {
         warn!("User accounts list dispatch failed (cmd_id=0)");
@@ -258,20 +580,24 @@ fn handle_get_all_users(ctx: &mut CommandContext<'_>, resp: oneshot::Sender<Vec<
 fn handle_check_user_exists(
     ctx: &mut CommandContext<'_>,
     username: crate::domain::Username,
+    matching: UsernameMatch,
     resp: oneshot::Sender<bool>,
 ) {
+    if ctx.accounts.is_loaded() {
+        let _ = resp.send(ctx.accounts.contains(username.as_str(), matching));
+        return;
+    }
     debug!(username = %username.as_str(), "Requesting account existence check");
     let cmd_id = ctx.client.list_user_accounts(0, 10000);
     if cmd_id > 0 {
         debug!(cmd_id, "User accounts list dispatched for existence check");
         ctx.pending_lists.insert(
             cmd_id,
-            PendingListRequest {
-                kind: PendingListKind::Exists { username, resp },
-                accumulated: Vec::new(),
-                completed_at: None,
-                mismatch_logged: false,
-            },
+            PendingListRequest::new(PendingListKind::Exists {
+                username,
+                matching,
+                resp,
+            }),
         );
     } else {
         warn!("User accounts list dispatch failed (cmd_id=0)");
@@ -280,10 +606,11 @@ fn handle_check_user_exists(
 }
 
 /// Run the `TeamTalk` worker loop.
-#[instrument(skip(config, rx, bot, db, rt_handle))]
+#[instrument(skip(config, rx, tx_tt, bot, db, rt_handle))]
 pub async fn run_tt_worker(
     config: Arc<AppConfig>,
     rx: Receiver<TTWorkerCommand>,
+    tx_tt: TTWorkerHandle,
     bot: Bot,
     db: Database,
     rt_handle: Handle,
@@ -297,9 +624,13 @@ pub async fn run_tt_worker(
     let username = config.teamtalk.user_name.clone();
     let password = config.teamtalk.password.clone();
     let client_name = config.teamtalk.client_name.clone();
-    let rights = config.teamtalk.teamtalk_default_user_rights.clone();
+    let rights_profiles = config.rights_profiles.clone();
+    let personal_channel_parent = config
+        .teamtalk
+        .personal_channels_enabled
+        .then(|| config.teamtalk.personal_channel_parent.clone());
     let broadcast_enabled = config.teamtalk.teamtalk_registration_broadcast_enabled;
-    let admin_ids = config.telegram.admin_ids.clone();
+    let admin_chats = AdminChats::new(&config.telegram, &db);
     let admin_lang = config.telegram.bot_admin_lang.clone();
 
     let tt_gender_str = config.teamtalk.tt_gender.clone();
@@ -309,6 +640,7 @@ pub async fn run_tt_worker(
         Arc::new(Mutex::new(HashMap::new()));
 
     let worker_config = TTWorkerConfig {
+        server_id: config.teamtalk.server_id.clone(),
         host,
         tcp_port,
         udp_port,
@@ -317,14 +649,36 @@ pub async fn run_tt_worker(
         username,
         password,
         client_name,
-        rights,
+        join_channel: config
+            .teamtalk
+            .tt_join_channel
+            .clone()
+            .filter(|path| !path.trim().is_empty()),
+        join_channel_password: config
+            .teamtalk
+            .tt_join_channel_password
+            .clone()
+            .unwrap_or_default(),
+        rights_profiles,
+        personal_channel_parent,
         broadcast_enabled,
-        admin_ids,
+        removal: RemovalPolicy {
+            notify_user: config.teamtalk.teamtalk_notify_user_on_removal,
+            auto_ban: config.teamtalk.auto_ban_on_tt_delete,
+            debounce: Duration::from_secs(config.teamtalk.auto_ban_debounce_seconds),
+        },
+        admin_chats,
         admin_lang,
+        connection_notify_interval: config.teamtalk.tt_connection_notify_interval_seconds,
+        reconnect: ReconnectPolicy::from_config(&config.teamtalk),
+        account_cache_refresh: config.teamtalk.tt_account_cache_refresh_seconds,
+        command_timeout: Duration::from_secs(config.teamtalk.tt_command_timeout_seconds),
         tt_gender_str,
         tt_status_text,
     };
 
+    let guests = GuestRegistrar::new(&config, &bot, &db, &tx_tt, &rt_handle);
+    let welcomer = Welcomer::new(&config, &db, &tx_tt, &rt_handle);
     std::thread::spawn(move || {
         run_tt_loop(TTWorkerRuntime {
             config: worker_config,
@@ -334,124 +688,178 @@ pub async fn run_tt_worker(
             rt_handle,
             shutdown,
             pending_deletions,
+            guests,
+            welcomer,
         });
     });
 }
 
-fn run_tt_loop(runtime: TTWorkerRuntime) {
-    let TTWorkerRuntime {
-        config,
-        rx,
-        bot,
-        db,
-        rt_handle,
-        shutdown,
-        pending_deletions,
-    } = runtime;
-    let client = match Client::new() {
-        Ok(c) => c,
-        Err(e) => {
-            error!(error = %e, "Failed to init TeamTalk client");
-            return;
-        }
-    };
-
-    let mut reconnect = ReconnectHandler::new(ReconnectConfig::default());
-    let connect_params = ConnectParams {
-        host: &config.host,
-        tcp: config.tcp_port,
-        udp: config.udp_port,
-        encrypted: config.encrypted,
+fn run_tt_loop(mut runtime: TTWorkerRuntime) {
+    let config = &runtime.config;
+    let Some((client, connect_params)) = start_client(config) else {
+        return;
     };
-
-    info!(host = %config.host, tcp_port = config.tcp_port, "Connecting to TeamTalk server");
-    let _ = client.connect(
-        connect_params.host,
-        connect_params.tcp,
-        connect_params.udp,
-        connect_params.encrypted,
-    );
-
-    let mut is_logged_in = false;
+    let mut reconnect = Reconnect::new(config.reconnect);
+    let mut connection = config.connection_tracker(&runtime.bot, &runtime.rt_handle);
     let mut pending_cmds: HashMap<i32, PendingCommand> = HashMap::new();
     let mut pending_lists: HashMap<i32, PendingListRequest> = HashMap::new();
+    let mut accounts = AccountCache::new(config.account_cache_refresh);
 
     loop {
-        if shutdown.is_cancelled() {
+        if runtime.shutdown.is_cancelled() {
             let _ = client.disconnect();
             break;
         }
 
         let mut ctx = CommandContext {
             client: &client,
-            rights: &config.rights,
+            rights_profiles: &config.rights_profiles,
+            personal_channel_parent: config.personal_channel_parent.as_deref(),
             broadcast_enabled: config.broadcast_enabled,
             admin_lang: &config.admin_lang,
             pending_cmds: &mut pending_cmds,
             pending_lists: &mut pending_lists,
-            is_logged_in,
+            connection: &connection,
+            accounts: &accounts,
         };
-        if !process_commands(&rx, &mut ctx) {
+        if !process_commands(&mut runtime.rx, &runtime.rt_handle, &mut ctx) {
             break;
         }
 
         while let Some((event, msg)) = client.poll(0) {
             match event {
-                Event::ConnectSuccess => handle_connect_success(&client, &mut reconnect, &config),
+                Event::ConnectSuccess => {
+                    connection.connected();
+                    handle_connect_success(&client, &mut reconnect, config);
+                }
                 Event::ConnectFailed | Event::ConnectionLost => {
+                    connection.disconnected(matches!(event, Event::ConnectionLost));
                     handle_connection_lost(
                         &mut reconnect,
-                        &mut is_logged_in,
                         &mut pending_cmds,
                         &mut pending_lists,
+                        &mut accounts,
                     );
                 }
-                Event::MySelfLoggedIn => {
-                    handle_logged_in(&client, &mut is_logged_in, &config);
-                }
+                Event::MySelfLoggedIn => handle_logged_in(&client, &mut connection, config),
                 Event::CmdSuccess => {
-                    handle_cmd_success(&msg, &mut pending_cmds, &mut pending_lists);
+                    handle_cmd_success(&client, &msg, &mut pending_cmds, &mut pending_lists);
+                }
+                Event::CmdError => {
+                    let error = handle_cmd_error(
+                        &client,
+                        &msg,
+                        &mut pending_cmds,
+                        &mut pending_lists,
+                        &mut accounts,
+                    );
+                    connection.error(error);
                 }
-                Event::CmdError => handle_cmd_error(&msg, &mut pending_cmds, &mut pending_lists),
                 Event::UserAccount => handle_user_account(&msg, &mut pending_lists),
+                Event::TextMessage => {
+                    handle_text_message(&client, &msg, &config.admin_lang, runtime.guests.as_ref());
+                }
+                Event::UserLoggedIn => {
+                    handle_user_logged_in(&client, &msg, runtime.welcomer.as_ref());
+                }
                 Event::UserAccountCreated => handle_user_account_created(
                     &msg,
-                    is_logged_in,
-                    &bot,
-                    &config,
-                    &pending_deletions,
-                    &rt_handle,
-                ),
-                Event::UserAccountRemoved => handle_user_account_removed(
-                    &msg,
-                    &bot,
-                    &db,
-                    &config,
-                    &pending_deletions,
-                    &rt_handle,
+                    &mut accounts,
+                    connection.is_logged_in(),
+                    &runtime,
                 ),
+                Event::UserAccountRemoved => {
+                    handle_user_account_removed(&msg, &mut accounts, &runtime);
+                }
                 _ => {}
             }
         }
 
-        flush_completed_lists(&mut pending_lists);
+        flush_completed_lists(
+            &client,
+            &mut pending_cmds,
+            &mut pending_lists,
+            &mut accounts,
+            connection.is_logged_in(),
+        );
+        expire_stale_requests(
+            &mut pending_cmds,
+            &mut pending_lists,
+            &mut accounts,
+            config.command_timeout,
+        );
 
-        if !is_logged_in && !client.is_connected() && !client.is_connecting() {
-            client.handle_reconnect(&connect_params, &mut reconnect);
+        if !connection.is_logged_in() && !client.is_connected() && !client.is_connecting() {
+            reconnect_with_notice(&client, &connect_params, &mut reconnect, &mut connection);
         }
+        connection.flush();
+    }
+}
+
+/// Create the client and start the first connection attempt.
+fn start_client(config: &TTWorkerConfig) -> Option<(Client, ConnectParams<'_>)> {
+    let client = match Client::new() {
+        Ok(c) => c,
+        Err(e) => {
+            error!(error = %e, "Failed to init TeamTalk client");
+            return None;
+        }
+    };
+    let connect_params = ConnectParams {
+        host: &config.host,
+        tcp: config.tcp_port,
+        udp: config.udp_port,
+        encrypted: config.encrypted,
+    };
+
+    info!(host = %config.host, tcp_port = config.tcp_port, "Connecting to TeamTalk server");
+    let _ = client.connect(
+        connect_params.host,
+        connect_params.tcp,
+        connect_params.udp,
+        connect_params.encrypted,
+    );
+    Some((client, connect_params))
+}
+
+fn reconnect_with_notice(
+    client: &Client,
+    params: &ConnectParams<'_>,
+    reconnect: &mut Reconnect,
+    connection: &mut ConnectionTracker,
+) {
+    if reconnect.give_up() {
+        error!(
+            attempts = reconnect.attempts(),
+            "Giving up reconnecting to TeamTalk"
+        );
+        connection.gave_up(reconnect.attempts());
+    }
+    if !reconnect.is_due() {
+        return;
     }
+    let _ = client.disconnect();
+    let wait = reconnect.record_attempt();
+    let _ = client.connect(params.host, params.tcp, params.udp, params.encrypted);
+    connection.reconnecting(reconnect.attempts(), wait);
 }
 
-fn process_commands(rx: &Receiver<TTWorkerCommand>, ctx: &mut CommandContext<'_>) -> bool {
-    match rx.recv_timeout(Duration::from_millis(100)) {
-        Ok(cmd) => {
+fn process_commands(
+    rx: &mut Receiver<TTWorkerCommand>,
+    rt_handle: &Handle,
+    ctx: &mut CommandContext<'_>,
+) -> bool {
+    let first = rt_handle
+        .block_on(async { tokio::time::timeout(Duration::from_millis(100), rx.recv()).await });
+    match first {
+        Ok(Some(cmd)) => {
             handle_command(cmd, ctx);
             while let Ok(cmd) = rx.try_recv() {
                 handle_command(cmd, ctx);
             }
         }
-        Err(RecvTimeoutError::Timeout) => {}
-        Err(RecvTimeoutError::Disconnected) => {
+        Err(_) => {}
+        Ok(None) => {
             warn!("TT worker command channel disconnected");
             return false;
         }
@@ -459,11 +867,7 @@ fn process_commands(rx: &Receiver<TTWorkerCommand>, ctx: &mut CommandContext<'_>
     true
 }
 
-fn handle_connect_success(
-    client: &Client,
-    reconnect: &mut ReconnectHandler,
-    config: &TTWorkerConfig,
-) {
+fn handle_connect_success(client: &Client, reconnect: &mut Reconnect, config: &TTWorkerConfig) {
     info!("Connected. Logging in");
     reconnect.mark_connected();
     client.login(
@@ -475,29 +879,31 @@ fn handle_connect_success(
 }
 
 fn handle_connection_lost(
-    reconnect: &mut ReconnectHandler,
-    is_logged_in: &mut bool,
+    reconnect: &mut Reconnect,
     pending_cmds: &mut HashMap<i32, PendingCommand>,
     pending_lists: &mut HashMap<i32, PendingListRequest>,
+    accounts: &mut AccountCache,
 ) {
     warn!("Connection lost");
-    *is_logged_in = false;
     reconnect.mark_disconnected();
     for (_, cmd) in pending_cmds.drain() {
-        let _ = cmd.resp.send(Err("Connection lost".to_string()));
+        let _ = cmd
+            .resp
+            .send(Err(TTCommandError::Other("Connection lost".to_string())));
     }
     let pending_count = pending_lists.len();
     for (_, req) in pending_lists.drain() {
-        respond_list_request(req, false);
+        respond_list_request(req, false, accounts);
     }
+    accounts.clear();
     if pending_count > 0 {
         warn!(pending_count, "Dropped pending list requests on disconnect");
     }
 }
 
-fn handle_logged_in(client: &Client, is_logged_in: &mut bool, config: &TTWorkerConfig) {
+fn handle_logged_in(client: &Client, connection: &mut ConnectionTracker, config: &TTWorkerConfig) {
     info!("Logged in as bot");
-    *is_logged_in = true;
+    connection.logged_in(client.get_server_properties().map(|props| props.name));
 
     let gender = match config.tt_gender_str.to_lowercase().as_str() {
         "male" => UserGender::Male,
@@ -513,9 +919,24 @@ fn handle_logged_in(client: &Client, is_logged_in: &mut bool, config: &TTWorkerC
 
     client.set_status(status_mode, &config.tt_status_text);
     client.subscribe(client.my_id(), teamtalk::types::Subscriptions::all());
+    if let Some(path) = &config.join_channel {
+        join_channel(client, path, &config.join_channel_password);
+    }
+}
+
+fn join_channel(client: &Client, path: &str, password: &str) {
+    let channel_id = client.get_channel_id_from_path(path);
+    if channel_id.0 <= 0 {
+        warn!(path, "tt_join_channel does not exist on the server");
+    } else if client.join_channel(channel_id, password) > 0 {
+        info!(path, "Joining channel");
+    } else {
+        warn!(path, "Join channel dispatch failed (cmd_id=0)");
+    }
 }
 
 fn handle_cmd_success(
+    client: &Client,
     msg: &teamtalk::Message,
     pending_cmds: &mut HashMap<i32, PendingCommand>,
     pending_lists: &mut HashMap<i32, PendingListRequest>,
@@ -523,7 +944,12 @@ fn handle_cmd_success(
     let cmd_id = msg.source();
     debug!(cmd_id, "Command succeeded");
     if let Some(cmd) = pending_cmds.remove(&cmd_id) {
-        let _ = cmd.resp.send(Ok(true));
+        match cmd.then_create {
+            Some(pending) => create_after_channel(client, pending_cmds, pending, cmd.resp),
+            None => {
+                let _ = cmd.resp.send(Ok(true));
+            }
+        }
     }
     if let Some(req) = pending_lists.get_mut(&cmd_id)
         && req.completed_at.is_none()
@@ -534,18 +960,27 @@ fn handle_cmd_success(
 }
 
 fn handle_cmd_error(
+    client: &Client,
     msg: &teamtalk::Message,
     pending_cmds: &mut HashMap<i32, PendingCommand>,
     pending_lists: &mut HashMap<i32, PendingListRequest>,
-) {
+    accounts: &mut AccountCache,
+) -> String {
     let cmd_id = msg.source();
-    log_cmd_error(cmd_id, msg);
+    let error = log_cmd_error(cmd_id, msg);
     if let Some(cmd) = pending_cmds.remove(&cmd_id) {
-        let _ = cmd.resp.send(Err("Command failed on server".to_string()));
+        match cmd.then_create {
+            // A failed personal channel should not cost the user their account.
+            Some(pending) => create_after_channel(client, pending_cmds, pending, cmd.resp),
+            None => {
+                let _ = cmd.resp.send(Err(error.clone()));
+            }
+        }
     }
     if let Some(req) = pending_lists.remove(&cmd_id) {
-        respond_list_request(req, false);
+        respond_list_request(req, false, accounts);
     }
+    error.to_string()
 }
 
 fn handle_user_account(
@@ -559,10 +994,7 @@ fn handle_user_account(
 
     if let Some(req) = pending_lists.get_mut(&cmd_id) {
         debug!(cmd_id, username = %acc.username, "Received user account");
-        req.accumulated.push(acc.username);
-        if req.completed_at.is_some() {
-            req.completed_at = Some(Instant::now());
-        }
+        req.record(acc);
         return;
     }
 
@@ -571,17 +1003,22 @@ fn handle_user_account(
         if !req.mismatch_logged {
             req.mismatch_logged = true;
         }
-        req.accumulated.push(acc.username);
-        if req.completed_at.is_some() {
-            req.completed_at = Some(Instant::now());
-        }
+        req.record(acc);
         return;
     }
 
     warn!(cmd_id, "Received user account without pending list request");
 }
 
-fn flush_completed_lists(pending_lists: &mut HashMap<i32, PendingListRequest>) {
+/// Finish list requests whose trailing account events had time to arrive,
+/// then start an account cache refresh if one is due.
+fn flush_completed_lists(
+    client: &Client,
+    pending_cmds: &mut HashMap<i32, PendingCommand>,
+    pending_lists: &mut HashMap<i32, PendingListRequest>,
+    accounts: &mut AccountCache,
+    logged_in: bool,
+) {
     const LIST_GRACE: Duration = Duration::from_millis(500);
     let now = Instant::now();
     let mut ready = Vec::new();
@@ -601,24 +1038,145 @@ fn flush_completed_lists(pending_lists: &mut HashMap<i32, PendingListRequest>) {
                 count = req.accumulated.len(),
                 "Finalizing account list"
             );
-            respond_list_request(req, true);
+            if matches!(req.kind, PendingListKind::Update { .. }) {
+                dispatch_account_update(client, pending_cmds, req);
+            } else {
+                respond_list_request(req, true, accounts);
+            }
         }
     }
+
+    if logged_in && accounts.refresh_due() {
+        refresh_account_cache(client, pending_lists, accounts);
+    }
 }
 
-fn respond_list_request(req: PendingListRequest, success: bool) {
+/// Fail commands and listings the server has not answered within `timeout`,
+/// so their callers are not left waiting forever.
+fn expire_stale_requests(
+    pending_cmds: &mut HashMap<i32, PendingCommand>,
+    pending_lists: &mut HashMap<i32, PendingListRequest>,
+    accounts: &mut AccountCache,
+    timeout: Duration,
+) {
+    let stale: Vec<i32> = pending_cmds
+        .iter()
+        .filter(|(_, cmd)| cmd.dispatched_at.elapsed() >= timeout)
+        .map(|(&cmd_id, _)| cmd_id)
+        .collect();
+    for cmd_id in stale {
+        if let Some(cmd) = pending_cmds.remove(&cmd_id) {
+            warn!(cmd_id, "TeamTalk command timed out");
+            let _ = cmd
+                .resp
+                .send(Err(TTCommandError::Other("Command timed out".to_string())));
+        }
+    }
+
+    let stale: Vec<i32> = pending_lists
+        .iter()
+        .filter(|(_, req)| req.completed_at.is_none() && req.dispatched_at.elapsed() >= timeout)
+        .map(|(&cmd_id, _)| cmd_id)
+        .collect();
+    for cmd_id in stale {
+        if let Some(req) = pending_lists.remove(&cmd_id) {
+            warn!(cmd_id, "TeamTalk account listing timed out");
+            respond_list_request(req, false, accounts);
+        }
+    }
+}
+
+fn respond_list_request(req: PendingListRequest, success: bool, accounts: &mut AccountCache) {
     match req.kind {
         PendingListKind::AllUsers { resp } => {
             let _ = resp.send(if success { req.accumulated } else { vec![] });
         }
-        PendingListKind::Exists { username, resp } => {
-            let exists = success && req.accumulated.iter().any(|name| name == username.as_str());
+        PendingListKind::Exists {
+            username,
+            matching,
+            resp,
+        } => {
+            let wanted = matching.fold(username.as_str());
+            let exists = success
+                && req
+                    .accumulated
+                    .iter()
+                    .any(|name| matching.fold(name) == wanted);
             let _ = resp.send(exists);
         }
+        PendingListKind::Update { resp, .. } => {
+            let _ = resp.send(Err(TTCommandError::Other(
+                "Failed to look up account".to_string(),
+            )));
+        }
+        PendingListKind::Refresh if success => {
+            accounts.finish_refresh(req.accumulated);
+            debug!(count = accounts.len(), "Account cache refreshed");
+        }
+        PendingListKind::Refresh => accounts.abort_refresh(),
+    }
+}
+
+/// Request a full account listing to reload `accounts`.
+fn refresh_account_cache(
+    client: &Client,
+    pending_lists: &mut HashMap<i32, PendingListRequest>,
+    accounts: &mut AccountCache,
+) {
+    let cmd_id = client.list_user_accounts(0, 10000);
+    if cmd_id > 0 {
+        debug!(cmd_id, "Refreshing account cache");
+        accounts.begin_refresh();
+        pending_lists.insert(cmd_id, PendingListRequest::new(PendingListKind::Refresh));
+    } else {
+        warn!("Account cache refresh dispatch failed (cmd_id=0)");
+        accounts.abort_refresh();
     }
 }
 
-fn log_cmd_error(cmd_id: i32, msg: &teamtalk::Message) {
+/// Re-submit an existing account with the requested changes, keeping its
+/// type and any field that is not being changed.
+fn dispatch_account_update(
+    client: &Client,
+    pending_cmds: &mut HashMap<i32, PendingCommand>,
+    req: PendingListRequest,
+) {
+    let PendingListKind::Update {
+        username,
+        changes,
+        resp,
+    } = req.kind
+    else {
+        return;
+    };
+    let Some(mut acc) = req.matched else {
+        warn!(username = %username.as_str(), "UpdateAccount target not found");
+        let _ = resp.send(Err(TTCommandError::AccountNotFound));
+        return;
+    };
+    if let Some(password) = changes.password {
+        acc.password = password.as_str().to_string();
+    }
+    if let Some(rights) = changes.rights {
+        acc.user_rights = get_user_rights_mask(&rights);
+    }
+    if let Some(note) = changes.note {
+        acc.note = note;
+    }
+    let cmd_id = client.create_user_account(&acc);
+    if cmd_id > 0 {
+        debug!(cmd_id, username = %username.as_str(), "UpdateAccount dispatched");
+        pending_cmds.insert(cmd_id, PendingCommand::new(resp));
+    } else {
+        warn!(username = %username.as_str(), "UpdateAccount dispatch failed (cmd_id=0)");
+        let _ = resp.send(Err(TTCommandError::Other(
+            "Failed to dispatch command".to_string(),
+        )));
+    }
+}
+
+/// Log a failed command and tell what went wrong.
+fn log_cmd_error(cmd_id: i32, msg: &teamtalk::Message) -> TTCommandError {
     let raw = msg.raw();
     let tt_type = raw.ttType as i32;
     if tt_type == teamtalk::client::ffi::TTType::__CLIENTERRORMSG as i32 {
@@ -629,32 +1187,62 @@ fn log_cmd_error(cmd_id: i32, msg: &teamtalk::Message) {
             message = %err.message,
             "Command failed on TeamTalk server"
         );
+        command_error(&err)
     } else {
         warn!(cmd_id, tt_type, "Command failed on TeamTalk server");
+        TTCommandError::Other("Command failed on TeamTalk server".to_string())
+    }
+}
+
+fn command_error(err: &ErrorMessage) -> TTCommandError {
+    use teamtalk::client::ffi::ClientError;
+    match err.code {
+        code if code == ClientError::CMDERR_INVALID_USERNAME as i32 => {
+            TTCommandError::InvalidUsername
+        }
+        code if code == ClientError::CMDERR_NOT_AUTHORIZED as i32 => TTCommandError::NotAuthorized,
+        code if code == ClientError::CMDERR_SERVER_BANNED as i32 => TTCommandError::Banned,
+        code if code == ClientError::CMDERR_ACCOUNT_NOT_FOUND as i32 => {
+            TTCommandError::AccountNotFound
+        }
+        code => TTCommandError::Other(format!("{} (code {code})", err.message)),
     }
 }
 
+fn handle_user_logged_in(client: &Client, msg: &teamtalk::Message, welcomer: Option<&Welcomer>) {
+    let (Some(welcomer), Some(user)) = (welcomer, msg.user()) else {
+        return;
+    };
+    if user.id == client.my_id() {
+        return;
+    }
+    welcomer.spawn(LoggedInUser {
+        user_id: user.id.0,
+        username: user.username,
+        nickname: user.nickname,
+    });
+}
+
 fn handle_user_account_created(
     msg: &teamtalk::Message,
+    accounts: &mut AccountCache,
     is_logged_in: bool,
-    bot: &Bot,
-    config: &TTWorkerConfig,
-    pending_deletions: &Arc<Mutex<HashMap<String, AbortHandle>>>,
-    rt_handle: &Handle,
+    runtime: &TTWorkerRuntime,
 ) {
-    if !is_logged_in {
-        return;
-    }
     let Some(acc) = msg.account() else {
         return;
     };
+    accounts.insert(&acc.username);
+    if !is_logged_in {
+        return;
+    }
     let u_name = acc.username;
-    let bot_clone = bot.clone();
-    let admins_clone = config.admin_ids.clone();
-    let pending_dels = pending_deletions.clone();
-    let lang_clone = config.admin_lang.clone();
+    let bot_clone = runtime.bot.clone();
+    let admins_clone = runtime.config.admin_chats.clone();
+    let pending_dels = runtime.pending_deletions.clone();
+    let lang_clone = runtime.config.admin_lang.clone();
 
-    rt_handle.spawn(async move {
+    runtime.rt_handle.spawn(async move {
         let mut is_update = false;
         if let Ok(mut lock) = pending_dels.lock() {
             if let Some(abort_handle) = lock.remove(&u_name) {
@@ -669,103 +1257,195 @@ fn handle_user_account_created(
             warn!(username = %u_name, "Failed to lock pending deletions");
         }
 
-        let msg_key = if is_update {
-            "tt-account-changed"
+        let (kind, msg_key) = if is_update {
+            (NotificationKind::AccountChanged, "tt-account-changed")
         } else {
-            "tt-account-created"
+            (NotificationKind::AccountCreated, "tt-account-created")
         };
         let args = HashMap::from([("account_username_str".to_string(), u_name.clone())]);
         let msg_text = t_args(lang_clone.as_str(), msg_key, &args);
 
-        for &aid in &admins_clone {
-            let _ = bot_clone
-                .send_message(ChatId(aid.as_i64()), &msg_text)
-                .await;
-        }
+        admins_clone
+            .send(&bot_clone, kind, &msg_text, None, None)
+            .await;
     });
 }
 
-fn handle_user_account_removed(
-    msg: &teamtalk::Message,
+/// Explain to the registrant, in their stored language, that their account was deleted.
+async fn notify_removed_user(
     bot: &Bot,
     db: &Database,
-    config: &TTWorkerConfig,
-    pending_deletions: &Arc<Mutex<HashMap<String, AbortHandle>>>,
-    rt_handle: &Handle,
+    tg_id: TelegramId,
+    username: &str,
+    fallback_lang: &LanguageCode,
+) {
+    let lang = match db.get_registration_language(tg_id).await {
+        Ok(Some(stored)) => LanguageCode::parse_or_default(&stored),
+        _ => fallback_lang.clone(),
+    };
+    let text = t_args(
+        lang.as_str(),
+        "tt-account-removed-user",
+        &HashMap::from([("username".to_string(), username.to_string())]),
+    );
+    let chat_id = ChatId(tg_id.as_i64());
+    if let Err(e) = throttle::send(chat_id, bot.send_message(chat_id, text)).await {
+        warn!(tg_id = %tg_id, error = %e, "Failed to notify user about account removal");
+    }
+}
+
+fn handle_user_account_removed(
+    msg: &teamtalk::Message,
+    accounts: &mut AccountCache,
+    runtime: &TTWorkerRuntime,
 ) {
     let Some(acc) = msg.account() else {
         return;
     };
+    accounts.remove(&acc.username);
     let u_name = acc.username;
     debug!(
         username = %u_name,
         "User removed from TeamTalk. Starting debounce timer"
     );
 
-    let db_clone = db.clone();
-    let bot_clone = bot.clone();
-    let admins_clone = config.admin_ids.clone();
-    let pending_dels = pending_deletions.clone();
-    let u_name_cl = u_name.clone();
-    let lang_clone = config.admin_lang.clone();
+    let removal = RemovedAccount {
+        db: runtime.db.clone(),
+        bot: runtime.bot.clone(),
+        admin_chats: runtime.config.admin_chats.clone(),
+        admin_lang: runtime.config.admin_lang.clone(),
+        server_id: runtime.config.server_id.clone(),
+        notify_user: runtime.config.removal.notify_user,
+        username: u_name.clone(),
+    };
+    let pending_dels = runtime.pending_deletions.clone();
+    let policy = runtime.config.removal;
 
-    let task = rt_handle.spawn(async move {
-        tokio::time::sleep(Duration::from_secs(2)).await;
+    let task = runtime.rt_handle.spawn(async move {
+        tokio::time::sleep(policy.debounce).await;
 
         if let Ok(mut lock) = pending_dels.lock() {
-            lock.remove(&u_name_cl);
+            lock.remove(&removal.username);
         } else {
-            warn!(username = %u_name_cl, "Failed to lock pending deletions");
+            warn!(username = %removal.username, "Failed to lock pending deletions");
         }
 
-        debug!(
-            username = %u_name_cl,
-            "Timer passed. Auto-banning user associated with account"
-        );
-
         let removed_text = t_args(
-            lang_clone.as_str(),
+            removal.admin_lang.as_str(),
             "tt-account-removed",
-            &HashMap::from([("username".to_string(), u_name_cl.clone())]),
+            &HashMap::from([("username".to_string(), removal.username.clone())]),
         );
-        for &aid in &admins_clone {
-            let _ = bot_clone
-                .send_message(ChatId(aid.as_i64()), &removed_text)
-                .await;
+        removal
+            .admin_chats
+            .send(
+                &removal.bot,
+                NotificationKind::AccountRemoved,
+                &removed_text,
+                None,
+                None,
+            )
+            .await;
+
+        if policy.auto_ban {
+            debug!(
+                username = %removal.username,
+                "Timer passed. Auto-banning user associated with account"
+            );
+            removal.auto_ban().await;
         }
+    });
 
-        if let Ok(Some(reg)) = db_clone.get_registration_by_tt_username(&u_name_cl).await {
-            let _ = db_clone
+    if let Ok(mut lock) = runtime.pending_deletions.lock() {
+        lock.insert(u_name, task.abort_handle());
+    } else {
+        warn!(username = %u_name, "Failed to lock pending deletions");
+    }
+}
+
+/// An account deleted on the server, once its debounce timer has passed.
+struct RemovedAccount {
+    db: Database,
+    bot: Bot,
+    admin_chats: AdminChats,
+    admin_lang: LanguageCode,
+    server_id: String,
+    notify_user: bool,
+    username: String,
+}
+
+impl RemovedAccount {
+    /// Ban whoever the account belonged to and tell admins, with a button
+    /// undoing the ban.
+    async fn auto_ban(self) {
+        let reason = "Account deleted from TeamTalk server";
+        let lang = self.admin_lang.as_str();
+        let (text, undo) = if let Ok(Some(reg)) = self
+            .db
+            .get_registration_by_tt_username(&self.username, &self.server_id)
+            .await
+        {
+            if self.notify_user {
+                notify_removed_user(
+                    &self.bot,
+                    &self.db,
+                    reg.telegram_id,
+                    &self.username,
+                    &self.admin_lang,
+                )
+                .await;
+            }
+            let banned = self
+                .db
                 .ban_user(
                     reg.telegram_id,
-                    Some(&u_name_cl),
+                    Some(&self.username),
+                    Some(&self.server_id),
                     None,
-                    Some("Account deleted from TeamTalk server"),
+                    Some(reason),
+                )
+                .await
+                .is_ok();
+            if banned {
+                let target = reg.telegram_id.to_string();
+                audit::record(
+                    &self.db,
+                    Actor::System,
+                    AuditAction::AutoBan,
+                    &target,
+                    Some(reason),
                 )
                 .await;
-
+            }
             let args = HashMap::from([
-                ("username".to_string(), u_name_cl),
+                ("username".to_string(), self.username.clone()),
                 ("tg_id".to_string(), reg.telegram_id.to_string()),
             ]);
-            let text = t_args(lang_clone.as_str(), "tt-account-removed-banned", &args);
-
-            for &aid in &admins_clone {
-                let _ = bot_clone.send_message(ChatId(aid.as_i64()), &text).await;
-            }
+            let undo = banned.then(|| format!("admin_unban_{}", reg.telegram_id.as_i64()));
+            (t_args(lang, "tt-account-removed-banned", &args), undo)
         } else {
-            let args = HashMap::from([("username".to_string(), u_name_cl)]);
-            let text = t_args(lang_clone.as_str(), "tt-account-removed-no-link", &args);
-
-            for &aid in &admins_clone {
-                let _ = bot_clone.send_message(ChatId(aid.as_i64()), &text).await;
-            }
-        }
-    });
-
-    if let Ok(mut lock) = pending_deletions.lock() {
-        lock.insert(u_name, task.abort_handle());
-    } else {
-        warn!(username = %u_name, "Failed to lock pending deletions");
+            let bans = bans::ban_unlinked_account(&self.db, &self.username, reason).await;
+            let key = if bans.is_empty() {
+                "tt-account-removed-no-link"
+            } else {
+                "tt-account-removed-banned-unlinked"
+            };
+            let targets: Vec<&str> = bans.iter().map(|(_, target)| target.as_str()).collect();
+            let args = HashMap::from([
+                ("username".to_string(), self.username.clone()),
+                ("bans".to_string(), targets.join(", ")),
+            ]);
+            let ids: Vec<i64> = bans.iter().map(|&(id, _)| id).collect();
+            (t_args(lang, key, &args), undo_bans_callback(&ids))
+        };
+        let keyboard = undo.map(|callback| undo_ban_keyboard(&t(lang, "btn-undo-ban"), callback));
+        self.admin_chats
+            .send(
+                &self.bot,
+                NotificationKind::AccountRemoved,
+                &text,
+                keyboard.as_ref(),
+                None,
+            )
+            .await;
     }
 }
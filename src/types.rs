@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
 use std::net::IpAddr;
 use std::str::FromStr;
@@ -24,6 +25,23 @@ pub struct OnlineUser {
     pub user_type: u8,
 }
 
+/// Connection state reported by the `TeamTalk` worker.
+#[derive(Debug, Clone, Serialize)]
+pub struct TTStatus {
+    pub connected: bool,
+    pub logged_in: bool,
+    pub pending_commands: usize,
+    pub pending_lists: usize,
+    /// Seconds since the bot logged in, while it is logged in.
+    pub uptime_seconds: Option<u64>,
+    /// Server name reported at the last login.
+    pub server_name: Option<String>,
+    /// Most recent connection or command failure.
+    pub last_error: Option<String>,
+    /// Reconnect attempts since the connection was last stable.
+    pub reconnect_attempts: u32,
+}
+
 /// Telegram user identifier wrapper.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(transparent)]
@@ -122,6 +140,214 @@ impl<'de> Deserialize<'de> for LanguageCode {
 pub enum RegistrationSource {
     Telegram(TelegramId),
     Web(IpAddr),
+    /// A private message to the bot on the `TeamTalk` server.
+    TeamTalk(IpAddr),
+}
+
+impl fmt::Display for RegistrationSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Telegram(tg_id) => write!(f, "telegram:{tg_id}"),
+            Self::Web(ip) => write!(f, "web:{ip}"),
+            Self::TeamTalk(ip) => write!(f, "teamtalk:{ip}"),
+        }
+    }
+}
+
+impl TryFrom<&str> for RegistrationSource {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.split_once(':') {
+            Some(("telegram", id)) => id
+                .parse()
+                .map(|id| Self::Telegram(TelegramId(id)))
+                .map_err(|_| ()),
+            Some(("web", ip)) => ip.parse().map(Self::Web).map_err(|_| ()),
+            Some(("teamtalk", ip)) => ip.parse().map(Self::TeamTalk).map_err(|_| ()),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Channel a registration request came through, kept in [`SourceInfo`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceKind {
+    Telegram,
+    Web,
+    /// A private message to the bot on the `TeamTalk` server.
+    TeamTalk,
+    /// An admin's `/register` command.
+    Admin,
+}
+
+/// Who asked for a registration, stored as JSON in the `source_info`
+/// columns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceInfo {
+    pub source: SourceKind,
+    /// Language the requester uses.
+    #[serde(default)]
+    pub lang: LanguageCode,
+    /// Requester, or the admin for [`SourceKind::Admin`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub telegram_id: Option<TelegramId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tg_username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fullname: Option<String>,
+    /// Id of the server the account is for; `None` for the top-level one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    /// Rights profile the requester asked for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rights_profile: Option<String>,
+    /// Admin who approved the request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approved_by: Option<TelegramId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip: Option<IpAddr>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// ISO country code of `ip` from `[web.geoip]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    /// Autonomous system of `ip` from `[web.geoip]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asn: Option<u32>,
+    /// Free-form description carried over from rows stored before this
+    /// format, shown as is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl SourceInfo {
+    /// Source info with only the channel and language set.
+    pub const fn new(source: SourceKind, lang: LanguageCode) -> Self {
+        Self {
+            source,
+            lang,
+            telegram_id: None,
+            tg_username: None,
+            fullname: None,
+            server: None,
+            rights_profile: None,
+            approved_by: None,
+            ip: None,
+            user_agent: None,
+            country: None,
+            asn: None,
+            note: None,
+        }
+    }
+
+    /// Source of an account an admin created with `/register`.
+    pub fn admin(admin_id: TelegramId) -> Self {
+        Self {
+            telegram_id: Some(admin_id),
+            ..Self::new(SourceKind::Admin, LanguageCode::default())
+        }
+    }
+
+    /// Serialize for storage.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Read the old `key=value;...` format, keeping anything else as
+    /// [`SourceInfo::note`]. A `via=teamtalk` entry overrides `source`.
+    pub fn from_legacy(source: SourceKind, stored: &str) -> Self {
+        let mut info = Self::new(source, LanguageCode::default());
+        let mut structured = false;
+        for part in stored.split(';') {
+            let Some((key, value)) = part.split_once('=') else {
+                continue;
+            };
+            structured = true;
+            let value = Some(value.to_string()).filter(|value| !value.is_empty());
+            match key {
+                "lang" => {
+                    info.lang =
+                        LanguageCode::parse_or_default(value.as_deref().unwrap_or_default());
+                }
+                "tg_username" => info.tg_username = value,
+                "fullname" => info.fullname = value,
+                "server" => info.server = value,
+                "profile" => info.rights_profile = value,
+                "country" => info.country = value,
+                "asn" => info.asn = value.and_then(|asn| asn.parse().ok()),
+                "via" if value.as_deref() == Some("teamtalk") => info.source = SourceKind::TeamTalk,
+                _ => {}
+            }
+        }
+        if !structured && !stored.is_empty() {
+            info.note = Some(stored.to_string());
+        }
+        info
+    }
+}
+
+/// Summary written to the `TeamTalk` account note.
+impl fmt::Display for SourceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(note) = &self.note {
+            return f.write_str(note);
+        }
+        match self.source {
+            SourceKind::Telegram => {
+                f.write_str("Telegram")?;
+                if let Some(id) = self.telegram_id {
+                    write!(f, " ID: {id}")?;
+                }
+                if let Some(username) = &self.tg_username {
+                    write!(f, ", username: @{username}")?;
+                }
+                if let Some(name) = &self.fullname {
+                    write!(f, ", name: {name}")?;
+                }
+                Ok(())
+            }
+            SourceKind::Web | SourceKind::TeamTalk => {
+                let channel = if self.source == SourceKind::Web {
+                    "Web"
+                } else {
+                    "TeamTalk"
+                };
+                match self.ip {
+                    Some(ip) => write!(f, "{channel} IP: {ip}"),
+                    None => f.write_str(channel),
+                }
+            }
+            SourceKind::Admin => match self.telegram_id {
+                Some(id) => write!(f, "Created by admin {id} via /register"),
+                None => f.write_str("Created by an admin via /register"),
+            },
+        }
+    }
+}
+
+impl<DB: sqlx::Database> sqlx::Type<DB> for SourceInfo
+where
+    String: sqlx::Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <String as sqlx::Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <String as sqlx::Type<DB>>::compatible(ty)
+    }
+}
+
+impl<'r, DB: sqlx::Database> sqlx::Decode<'r, DB> for SourceInfo
+where
+    String: sqlx::Decode<'r, DB>,
+{
+    fn decode(value: DB::ValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        let stored = <String as sqlx::Decode<'r, DB>>::decode(value)?;
+        Ok(serde_json::from_str(&stored)?)
+    }
 }
 
 /// `TeamTalk` account type.
@@ -131,6 +357,173 @@ pub enum TTAccountType {
     Admin,
 }
 
+impl TTAccountType {
+    /// Convert account type to its storage string.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+impl TryFrom<&str> for TTAccountType {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "default" => Ok(Self::Default),
+            "admin" => Ok(Self::Admin),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How a Telegram user's account came about, kept in
+/// `telegram_registrations.source`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistrationOrigin {
+    /// Created right away, without admin review.
+    Direct,
+    /// Created once an admin approved the request.
+    Approved,
+}
+
+impl RegistrationOrigin {
+    /// Origin of an account created for `source_info`.
+    pub fn of(source_info: Option<&SourceInfo>) -> Self {
+        if source_info.is_some_and(|info| info.approved_by.is_some()) {
+            Self::Approved
+        } else {
+            Self::Direct
+        }
+    }
+
+    /// Convert the origin to its storage string.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Direct => "direct",
+            Self::Approved => "approved",
+        }
+    }
+
+    /// Locale key naming the origin in admin views.
+    pub const fn label_key(self) -> &'static str {
+        match self {
+            Self::Direct => "registration-origin-direct",
+            Self::Approved => "registration-origin-approved",
+        }
+    }
+}
+
+impl TryFrom<&str> for RegistrationOrigin {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "direct" => Ok(Self::Direct),
+            "approved" => Ok(Self::Approved),
+            _ => Err(()),
+        }
+    }
+}
+
+/// What a row of the `bans` table blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BanKind {
+    /// A `TeamTalk` username, compared per `username_policy.collision_check`.
+    Username,
+    /// An IP address or CIDR network.
+    Ip,
+}
+
+impl BanKind {
+    /// Convert the kind to its storage string.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Username => "username",
+            Self::Ip => "ip",
+        }
+    }
+}
+
+impl TryFrom<&str> for BanKind {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "username" => Ok(Self::Username),
+            "ip" => Ok(Self::Ip),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Kind of admin notification, for muting per admin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    AccountCreated,
+    AccountChanged,
+    AccountRemoved,
+    /// Telegram registration requests and decisions on them.
+    Approvals,
+    /// Web and `TeamTalk` guest registration requests and decisions on them.
+    WebRegistrations,
+    /// `TeamTalk` connection lost and restored.
+    Connection,
+    /// Errors and reports that cannot be muted.
+    Alert,
+}
+
+impl NotificationKind {
+    /// Kinds an admin can turn off, in settings order.
+    pub const MUTABLE: [Self; 6] = [
+        Self::AccountCreated,
+        Self::AccountChanged,
+        Self::AccountRemoved,
+        Self::Approvals,
+        Self::WebRegistrations,
+        Self::Connection,
+    ];
+
+    /// Convert the kind to its storage string.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::AccountCreated => "account_created",
+            Self::AccountChanged => "account_changed",
+            Self::AccountRemoved => "account_removed",
+            Self::Approvals => "approvals",
+            Self::WebRegistrations => "web_registrations",
+            Self::Connection => "connection",
+            Self::Alert => "alert",
+        }
+    }
+
+    /// Message key of the kind's name in the settings view.
+    pub const fn label_key(self) -> &'static str {
+        match self {
+            Self::AccountCreated => "notify-kind-account-created",
+            Self::AccountChanged => "notify-kind-account-changed",
+            Self::AccountRemoved => "notify-kind-account-removed",
+            Self::Approvals => "notify-kind-approvals",
+            Self::WebRegistrations => "notify-kind-web-registrations",
+            Self::Connection => "notify-kind-connection",
+            Self::Alert => "notify-kind-alert",
+        }
+    }
+}
+
+impl TryFrom<&str> for NotificationKind {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::MUTABLE
+            .into_iter()
+            .find(|kind| kind.as_str() == value)
+            .ok_or(())
+    }
+}
+
 /// Type of downloadable asset.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DownloadTokenType {
@@ -160,6 +553,90 @@ impl TryFrom<&str> for DownloadTokenType {
     }
 }
 
+/// Review state of a web registration waiting for admin approval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebRequestStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Failed,
+}
+
+impl WebRequestStatus {
+    /// Convert status to its storage string.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+impl TryFrom<&str> for WebRequestStatus {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "pending" => Ok(Self::Pending),
+            "approved" => Ok(Self::Approved),
+            "rejected" => Ok(Self::Rejected),
+            "failed" => Ok(Self::Failed),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Why the `TeamTalk` worker could not carry out a command.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TTCommandError {
+    /// The bot is not logged in to the server.
+    #[error("Bot not connected to TeamTalk")]
+    NotConnected,
+    /// An account with the requested username already exists.
+    #[error("Account already exists")]
+    AccountExists,
+    /// The server does not accept the username.
+    #[error("Invalid username")]
+    InvalidUsername,
+    /// The bot's own account lacks the rights for the command.
+    #[error("Bot account lacks the rights for this command")]
+    NotAuthorized,
+    /// The bot is banned from the server.
+    #[error("Bot is banned from the server")]
+    Banned,
+    /// The account to change does not exist.
+    #[error("Account not found")]
+    AccountNotFound,
+    /// Any other failure, described for logs.
+    #[error("{0}")]
+    Other(String),
+}
+
+impl TTCommandError {
+    /// Locale key of the message shown to users and admins.
+    pub const fn message_key(&self) -> &'static str {
+        match self {
+            Self::NotConnected => "tt-err-not-connected",
+            Self::AccountExists => "tt-err-account-exists",
+            Self::InvalidUsername => "tt-err-invalid-username",
+            Self::NotAuthorized => "tt-err-not-authorized",
+            Self::Banned => "tt-err-banned",
+            Self::AccountNotFound => "tt-err-account-not-found",
+            Self::Other(_) => "tt-err-failed",
+        }
+    }
+
+    /// Arguments for the message named by [`Self::message_key`].
+    pub fn message_args(&self) -> HashMap<String, String> {
+        match self {
+            Self::Other(reason) => HashMap::from([("reason".to_string(), reason.clone())]),
+            _ => HashMap::new(),
+        }
+    }
+}
+
 /// Commands for the `TeamTalk` worker thread.
 #[derive(Debug)]
 pub enum TTWorkerCommand {
@@ -168,15 +645,18 @@ pub enum TTWorkerCommand {
         password: crate::domain::Password,
         nickname: crate::domain::Nickname,
         account_type: TTAccountType,
+        /// Name of the `rights_profiles` entry the account gets.
+        rights_profile: String,
         source: RegistrationSource,
+        /// Summary for the account note, from [`SourceInfo`]'s `Display`.
         source_info: Option<String>,
-        resp: tokio::sync::oneshot::Sender<Result<bool, String>>,
+        resp: tokio::sync::oneshot::Sender<Result<bool, TTCommandError>>,
     },
     CheckUserExists {
         username: crate::domain::Username,
+        matching: crate::domain::UsernameMatch,
         resp: tokio::sync::oneshot::Sender<bool>,
     },
-    #[allow(dead_code)]
     GetOnlineUsers {
         resp: tokio::sync::oneshot::Sender<Vec<OnlineUser>>,
     },
@@ -185,6 +665,74 @@ pub enum TTWorkerCommand {
     },
     DeleteUser {
         username: crate::domain::Username,
-        resp: tokio::sync::oneshot::Sender<Result<bool, String>>,
+        resp: tokio::sync::oneshot::Sender<Result<bool, TTCommandError>>,
+    },
+    GetStatus {
+        resp: tokio::sync::oneshot::Sender<TTStatus>,
+    },
+    /// Change an existing account; fields left `None` keep their current value.
+    UpdateAccount {
+        username: crate::domain::Username,
+        new_password: Option<crate::domain::Password>,
+        /// `TeamTalk` right names, as in `teamtalk_default_user_rights`.
+        new_rights: Option<Vec<String>>,
+        new_note: Option<String>,
+        resp: tokio::sync::oneshot::Sender<Result<bool, TTCommandError>>,
+    },
+    KickUser {
+        user_id: i32,
+        resp: tokio::sync::oneshot::Sender<Result<bool, TTCommandError>>,
+    },
+    BanUser {
+        user_id: i32,
+        resp: tokio::sync::oneshot::Sender<Result<bool, TTCommandError>>,
     },
+    Broadcast {
+        text: String,
+        resp: tokio::sync::oneshot::Sender<Result<bool, TTCommandError>>,
+    },
+    SendUserMessage {
+        user_id: i32,
+        text: String,
+        resp: tokio::sync::oneshot::Sender<Result<bool, TTCommandError>>,
+    },
+}
+
+/// Process exit code after `/restart`, so a supervisor can tell it from a stop.
+pub const RESTART_EXIT_CODE: u8 = 75;
+
+/// Lets handlers stop the whole application through the shared cancellation token.
+#[derive(Clone, Debug)]
+pub struct ShutdownHandle {
+    token: tokio_util::sync::CancellationToken,
+    restart: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+impl ShutdownHandle {
+    /// Wrap the application's shutdown token.
+    pub fn new(token: tokio_util::sync::CancellationToken) -> Self {
+        Self {
+            token,
+            restart: std::sync::Arc::default(),
+        }
+    }
+
+    /// Begin a graceful shutdown; with `restart` the process exits with [`RESTART_EXIT_CODE`].
+    pub fn request(&self, restart: bool) {
+        if restart {
+            self.restart
+                .store(true, std::sync::atomic::Ordering::SeqCst);
+        }
+        self.token.cancel();
+    }
+
+    /// Wait until a shutdown has been requested.
+    pub async fn cancelled(&self) {
+        self.token.cancelled().await;
+    }
+
+    /// Whether the shutdown was requested as a restart.
+    pub fn restart_requested(&self) -> bool {
+        self.restart.load(std::sync::atomic::Ordering::SeqCst)
+    }
 }
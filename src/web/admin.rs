@@ -0,0 +1,480 @@
+use super::WebState;
+use super::signed_links::SignedLinks;
+use super::templates::{
+    AdminAuditTemplate, AdminBansTemplate, AdminIndexTemplate, AdminLayout, AdminPendingTemplate,
+    AdminRegistrationsTemplate, AdminTeamTalkTemplate, AdminTokensTemplate,
+};
+use crate::config::BanPropagation;
+use crate::domain::Username;
+use crate::i18n::t;
+use crate::services::admin as admin_service;
+use crate::services::audit::{self, Actor, AuditAction};
+use crate::services::bans as ban_service;
+use crate::services::registration;
+use crate::types::{BanKind, TTWorkerCommand, TelegramId};
+use axum::extract::{Form, Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Redirect, Response};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::{error, warn};
+
+const NOTICE_PREFIX: &str = "admin-web-notice-";
+/// Audit log entries per dashboard page.
+const AUDIT_PAGE_SIZE: usize = 50;
+
+/// Query carrying the result of the previous action.
+#[derive(Deserialize)]
+pub(super) struct NoticeQuery {
+    #[serde(default)]
+    notice: Option<String>,
+}
+
+/// Query for `GET /admin/audit`.
+#[derive(Deserialize)]
+pub(super) struct AuditQuery {
+    #[serde(default)]
+    page: usize,
+}
+
+/// Form for `POST /admin/bans`.
+#[derive(Deserialize)]
+pub(super) struct BanForm {
+    telegram_id: String,
+    #[serde(default)]
+    reason: String,
+}
+
+/// Form for `POST /admin/bans/add`.
+#[derive(Deserialize)]
+pub(super) struct BanEntryForm {
+    kind: String,
+    value: String,
+    #[serde(default)]
+    reason: String,
+}
+
+/// Form for `POST /admin/tokens/revoke`.
+#[derive(Deserialize)]
+pub(super) struct RevokeLinkForm {
+    link: String,
+}
+
+/// Form for `POST /admin/teamtalk/delete`.
+#[derive(Deserialize)]
+pub(super) struct DeleteAccountForm {
+    username: String,
+}
+
+pub(super) fn layout(state: &WebState, title_key: &str, notice: Option<String>) -> AdminLayout {
+    let lang = state.config.telegram.bot_admin_lang.as_str().to_string();
+    let notice = notice
+        .filter(|key| key.starts_with(NOTICE_PREFIX))
+        .map(|key| t(&lang, &key));
+    AdminLayout {
+        title: t(&lang, title_key),
+        lang,
+        server_name: state.config.teamtalk.server_name.clone(),
+        base: admin_base(state),
+        notice,
+    }
+}
+
+pub(super) fn admin_base(state: &WebState) -> String {
+    format!("{}/admin", state.config.web.base_path())
+}
+
+fn redirect(state: &WebState, page: &str, notice: &str) -> Response {
+    Redirect::to(&format!(
+        "{}{page}?notice={NOTICE_PREFIX}{notice}",
+        admin_base(state)
+    ))
+    .into_response()
+}
+
+fn internal_error(state: &WebState, e: &anyhow::Error) -> Response {
+    error!(error = %e, "Admin dashboard query failed");
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        t(
+            state.config.telegram.bot_admin_lang.as_str(),
+            "admin-web-error",
+        ),
+    )
+        .into_response()
+}
+
+/// Overview page with counters.
+pub(super) async fn index(
+    State(state): State<Arc<WebState>>,
+    Query(query): Query<NoticeQuery>,
+) -> Response {
+    let db = &state.db;
+    let counts = async {
+        let registrations = db.get_all_registrations().await?.len();
+        let bans = db.get_all_banned_users().await?.len();
+        let pending = db.get_all_pending_registrations().await?.len()
+            + db.get_undecided_web_registrations().await?.len();
+        let tokens = db.get_active_download_tokens().await?.len();
+        anyhow::Ok((registrations, bans, pending, tokens))
+    };
+    match counts.await {
+        Ok((registrations, bans, pending, tokens)) => AdminIndexTemplate {
+            layout: layout(&state, "admin-web-title-overview", query.notice),
+            registrations,
+            bans,
+            pending,
+            tokens,
+        }
+        .into_response(),
+        Err(e) => internal_error(&state, &e),
+    }
+}
+
+/// List Telegram registrations.
+pub(super) async fn registrations(
+    State(state): State<Arc<WebState>>,
+    Query(query): Query<NoticeQuery>,
+) -> Response {
+    match state.db.get_all_registrations().await {
+        Ok(rows) => AdminRegistrationsTemplate {
+            layout: layout(&state, "admin-web-nav-registrations", query.notice),
+            rows,
+        }
+        .into_response(),
+        Err(e) => internal_error(&state, &e),
+    }
+}
+
+/// Delete a Telegram registration and ban the user, as the bot admin panel does.
+pub(super) async fn delete_registration(
+    State(state): State<Arc<WebState>>,
+    Path(tg_id): Path<i64>,
+) -> Response {
+    match admin_service::delete_registration_and_ban(
+        &state.db,
+        TelegramId::new(tg_id),
+        None,
+        "Deleted via web dashboard",
+    )
+    .await
+    {
+        Ok(true) => {
+            let target = tg_id.to_string();
+            let details = Some("Registration deleted via web dashboard");
+            audit::record(
+                &state.db,
+                Actor::Dashboard,
+                AuditAction::Ban,
+                &target,
+                details,
+            )
+            .await;
+            redirect(&state, "/registrations", "deleted")
+        }
+        Ok(false) => redirect(&state, "/registrations", "not-found"),
+        Err(e) => internal_error(&state, &e),
+    }
+}
+
+/// Show the banlist.
+pub(super) async fn bans(
+    State(state): State<Arc<WebState>>,
+    Query(query): Query<NoticeQuery>,
+) -> Response {
+    let rows = match state.db.get_all_banned_users().await {
+        Ok(rows) => rows,
+        Err(e) => return internal_error(&state, &e),
+    };
+    match state.db.get_bans().await {
+        Ok(entries) => AdminBansTemplate {
+            layout: layout(&state, "admin-web-nav-bans", query.notice),
+            rows,
+            entries,
+            propagate: state.config.telegram.ban_propagation == BanPropagation::Ask,
+        }
+        .into_response(),
+        Err(e) => internal_error(&state, &e),
+    }
+}
+
+/// Ban a Telegram ID manually.
+pub(super) async fn ban(State(state): State<Arc<WebState>>, Form(form): Form<BanForm>) -> Response {
+    let Ok(tg_id) = form.telegram_id.trim().parse::<i64>() else {
+        return redirect(&state, "/bans", "invalid-id");
+    };
+    let reason = form.reason.trim();
+    let reason = if reason.is_empty() {
+        "Banned via web dashboard"
+    } else {
+        reason
+    };
+    let reg = match state
+        .db
+        .get_registration_by_id(TelegramId::new(tg_id))
+        .await
+    {
+        Ok(reg) => reg,
+        Err(e) => return internal_error(&state, &e),
+    };
+    match state
+        .db
+        .ban_user(
+            TelegramId::new(tg_id),
+            reg.as_ref().map(|r| r.teamtalk_username.as_str()),
+            reg.as_ref().map(|r| r.server_id.as_str()),
+            None,
+            Some(reason),
+        )
+        .await
+    {
+        Ok(()) => {
+            let target = tg_id.to_string();
+            audit::record(
+                &state.db,
+                Actor::Dashboard,
+                AuditAction::Ban,
+                &target,
+                Some(reason),
+            )
+            .await;
+            redirect(&state, "/bans", "banned")
+        }
+        Err(e) => internal_error(&state, &e),
+    }
+}
+
+/// Remove a user from the banlist.
+pub(super) async fn unban(State(state): State<Arc<WebState>>, Path(tg_id): Path<i64>) -> Response {
+    match state.db.unban_user(TelegramId::new(tg_id)).await {
+        Ok(true) => {
+            let target = tg_id.to_string();
+            audit::record(
+                &state.db,
+                Actor::Dashboard,
+                AuditAction::Unban,
+                &target,
+                None,
+            )
+            .await;
+            redirect(&state, "/bans", "unbanned")
+        }
+        Ok(false) => redirect(&state, "/bans", "not-found"),
+        Err(e) => internal_error(&state, &e),
+    }
+}
+
+/// Ban a `TeamTalk` username or IP network.
+pub(super) async fn ban_entry(
+    State(state): State<Arc<WebState>>,
+    Form(form): Form<BanEntryForm>,
+) -> Response {
+    let Some((kind, value)) = BanKind::try_from(form.kind.as_str())
+        .ok()
+        .and_then(|kind| ban_service::normalize(kind, &form.value).map(|value| (kind, value)))
+    else {
+        return redirect(&state, "/bans", "invalid-ban");
+    };
+    let reason = form.reason.trim();
+    let reason = if reason.is_empty() {
+        "Banned via web dashboard"
+    } else {
+        reason
+    };
+    match state.db.add_ban(kind.as_str(), &value, Some(reason)).await {
+        Ok(_) => {
+            let target = ban_service::target(kind, &value);
+            audit::record(
+                &state.db,
+                Actor::Dashboard,
+                AuditAction::Ban,
+                &target,
+                Some(reason),
+            )
+            .await;
+            redirect(&state, "/bans", "ban-added")
+        }
+        Err(e) => internal_error(&state, &e),
+    }
+}
+
+/// Remove a username or IP network ban.
+pub(super) async fn unban_entry(
+    State(state): State<Arc<WebState>>,
+    Path(id): Path<i64>,
+) -> Response {
+    match ban_service::lift(&state.db, id, Actor::Dashboard).await {
+        Ok(Some(_)) => redirect(&state, "/bans", "ban-removed"),
+        Ok(None) => redirect(&state, "/bans", "not-found"),
+        Err(e) => internal_error(&state, &e),
+    }
+}
+
+/// List Telegram and web registrations waiting for approval.
+pub(super) async fn pending(
+    State(state): State<Arc<WebState>>,
+    Query(query): Query<NoticeQuery>,
+) -> Response {
+    let telegram = match state.db.get_all_pending_registrations().await {
+        Ok(rows) => rows,
+        Err(e) => return internal_error(&state, &e),
+    };
+    match state.db.get_undecided_web_registrations().await {
+        Ok(web) => AdminPendingTemplate {
+            layout: layout(&state, "admin-web-nav-pending", query.notice),
+            telegram,
+            web,
+        }
+        .into_response(),
+        Err(e) => internal_error(&state, &e),
+    }
+}
+
+/// List download tokens that can still be used.
+pub(super) async fn tokens(
+    State(state): State<Arc<WebState>>,
+    Query(query): Query<NoticeQuery>,
+) -> Response {
+    match state.db.get_active_download_tokens().await {
+        Ok(rows) => AdminTokensTemplate {
+            layout: layout(&state, "admin-web-nav-tokens", query.notice),
+            rows,
+        }
+        .into_response(),
+        Err(e) => internal_error(&state, &e),
+    }
+}
+
+/// Revoke a download link, stored or signed, and delete its file.
+pub(super) async fn revoke_link(
+    State(state): State<Arc<WebState>>,
+    Form(form): Form<RevokeLinkForm>,
+) -> Response {
+    let token = link_token(&form.link);
+    let revoked =
+        if SignedLinks::is_signed(token) {
+            let Some(download) = state.signed_links.verify(token) else {
+                return redirect(&state, "/tokens", "invalid-link");
+            };
+            state
+                .db
+                .revoke_signed_link(&download.id, download.expires_at())
+                .await
+                .map(|revoked| revoked.then_some((download.file, download.original_name)))
+        } else {
+            match state.db.get_download_token(token).await {
+                Ok(Some(tok)) => state.db.revoke_download_token(token).await.map(|revoked| {
+                    revoked.then_some((tok.filepath_on_server, tok.original_filename))
+                }),
+                Ok(None) => Ok(None),
+                Err(e) => Err(e),
+            }
+        };
+    match revoked {
+        Ok(Some((file, original_name))) => {
+            registration::remove_generated_file(state.config.get_temp_dir(), &file).await;
+            audit::record(
+                &state.db,
+                Actor::Dashboard,
+                AuditAction::RevokeDownload,
+                &original_name,
+                None,
+            )
+            .await;
+            redirect(&state, "/tokens", "link-revoked")
+        }
+        Ok(None) => redirect(&state, "/tokens", "not-found"),
+        Err(e) => internal_error(&state, &e),
+    }
+}
+
+/// The token of a pasted download link, or the input if it is a bare token.
+fn link_token(link: &str) -> &str {
+    let path = link.trim().split(['?', '#']).next().unwrap_or_default();
+    path.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+}
+
+/// Show online users and all accounts of the top-level server.
+pub(super) async fn teamtalk(
+    State(state): State<Arc<WebState>>,
+    Query(query): Query<NoticeQuery>,
+) -> Response {
+    let online = state
+        .tx_tt
+        .request(|resp| TTWorkerCommand::GetOnlineUsers { resp })
+        .await;
+    let accounts = state
+        .tx_tt
+        .request(|resp| TTWorkerCommand::GetAllUsers { resp })
+        .await;
+    match (online, accounts) {
+        (Ok(online), Ok(accounts)) => AdminTeamTalkTemplate {
+            layout: layout(&state, "admin-web-nav-teamtalk", query.notice),
+            online,
+            accounts,
+        }
+        .into_response(),
+        (Err(e), _) | (_, Err(e)) => {
+            error!(error = %e, "TeamTalk user list request failed");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                t(
+                    state.config.telegram.bot_admin_lang.as_str(),
+                    "admin-web-tt-unavailable",
+                ),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Delete a `TeamTalk` account on the top-level server.
+pub(super) async fn delete_account(
+    State(state): State<Arc<WebState>>,
+    Form(form): Form<DeleteAccountForm>,
+) -> Response {
+    let Some(username) = Username::parse(&form.username) else {
+        return redirect(&state, "/teamtalk", "tt-delete-failed");
+    };
+    let name = username.to_string();
+    match admin_service::delete_teamtalk_account(&state.tx_tt, username).await {
+        Ok(()) => {
+            let action = AuditAction::DeleteTeamTalkAccount;
+            audit::record(&state.db, Actor::Dashboard, action, &name, None).await;
+            redirect(&state, "/teamtalk", "tt-deleted")
+        }
+        Err(err) => {
+            warn!(username = %name, error = %err, "TeamTalk account deletion failed");
+            redirect(&state, "/teamtalk", "tt-delete-failed")
+        }
+    }
+}
+
+/// Browse the audit log.
+pub(super) async fn audit(
+    State(state): State<Arc<WebState>>,
+    Query(query): Query<AuditQuery>,
+) -> Response {
+    let offset = query.page.saturating_mul(AUDIT_PAGE_SIZE);
+    let (Ok(limit), Ok(offset)) = (i64::try_from(AUDIT_PAGE_SIZE + 1), i64::try_from(offset))
+    else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    match state.db.get_audit_entries(limit, offset).await {
+        Ok(mut rows) => {
+            let has_more = rows.len() > AUDIT_PAGE_SIZE;
+            rows.truncate(AUDIT_PAGE_SIZE);
+            AdminAuditTemplate {
+                layout: layout(&state, "admin-web-nav-audit", None),
+                rows,
+                prev_page: query.page.checked_sub(1),
+                next_page: has_more.then_some(query.page + 1),
+            }
+            .into_response()
+        }
+        Err(e) => internal_error(&state, &e),
+    }
+}
@@ -0,0 +1,362 @@
+use super::WebState;
+use super::admin::{admin_base, layout};
+use super::client_ip::resolve_client_ip;
+use super::constant_time_eq;
+use super::templates::AdminLoginTemplate;
+use crate::config::WebAdminConfig;
+use crate::services::audit::{self, Actor, AuditAction};
+use argon2::password_hash::SaltString;
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use axum::extract::{ConnectInfo, Form, Request, State};
+use axum::http::{HeaderMap, HeaderValue, Method, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Redirect, Response};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use chrono::Utc;
+use ring::hmac;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+const SESSION_COOKIE: &str = "admin_session";
+/// TOTP time step, and how many steps of clock drift are accepted either way.
+const TOTP_STEP_SECONDS: i64 = 30;
+const TOTP_SKEW_STEPS: i64 = 1;
+
+/// Form for `POST /admin/login`.
+#[derive(Deserialize)]
+pub(super) struct LoginForm {
+    username: String,
+    password: String,
+    #[serde(default)]
+    code: String,
+}
+
+/// Signs dashboard sessions and throttles failed sign-ins.
+pub(super) struct AdminAuth {
+    key: hmac::Key,
+    failures: Mutex<HashMap<IpAddr, FailedLogins>>,
+    /// Last TOTP step used to sign in, so a code works only once.
+    last_totp_step: Mutex<i64>,
+}
+
+struct FailedLogins {
+    count: u32,
+    last: Instant,
+    locked_until: Option<Instant>,
+}
+
+impl AdminAuth {
+    /// Build from `[web.admin]`, with a per-process key unless
+    /// `session_secret` is set.
+    pub(super) fn new(admin: &WebAdminConfig) -> Self {
+        let secret = admin.session_secret.as_ref().map_or_else(
+            || rand::random::<[u8; 32]>().to_vec(),
+            |secret| secret.as_bytes().to_vec(),
+        );
+        Self {
+            key: hmac::Key::new(hmac::HMAC_SHA256, &secret),
+            failures: Mutex::new(HashMap::new()),
+            last_totp_step: Mutex::new(i64::MIN),
+        }
+    }
+
+    /// `Set-Cookie` value of a session for `username` lasting `ttl` seconds,
+    /// sent back only to the dashboard under `path`.
+    fn issue_session(&self, username: &str, ttl: u64, path: &str, secure: bool) -> String {
+        let expires = Utc::now().timestamp() + i64::try_from(ttl).unwrap_or(i64::MAX / 2);
+        let payload = BASE64_URL.encode(format!("{expires}:{username}"));
+        let tag = hmac::sign(&self.key, payload.as_bytes());
+        let value = format!("{payload}.{}", BASE64_URL.encode(tag.as_ref()));
+        session_cookie(&value, path, ttl, secure)
+    }
+
+    /// Whether the request carries an unexpired session for `username`.
+    fn has_session(&self, headers: &HeaderMap, username: &str) -> bool {
+        let Some(token) = headers
+            .get(header::COOKIE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|cookies| {
+                cookies
+                    .split(';')
+                    .find_map(|part| part.trim().strip_prefix(SESSION_COOKIE)?.strip_prefix('='))
+            })
+        else {
+            return false;
+        };
+        let Some((payload, tag)) = token.split_once('.') else {
+            return false;
+        };
+        let signed = BASE64_URL
+            .decode(tag)
+            .is_ok_and(|tag| hmac::verify(&self.key, payload.as_bytes(), &tag).is_ok());
+        let Some(decoded) = BASE64_URL
+            .decode(payload)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .filter(|_| signed)
+        else {
+            return false;
+        };
+        decoded.split_once(':').is_some_and(|(expires, name)| {
+            name == username
+                && expires
+                    .parse::<i64>()
+                    .is_ok_and(|expires| expires > Utc::now().timestamp())
+        })
+    }
+
+    /// Whether `ip` is locked out after too many failed sign-ins.
+    fn is_locked(&self, ip: IpAddr) -> bool {
+        let Ok(failures) = self.failures.lock() else {
+            return false;
+        };
+        failures
+            .get(&ip)
+            .and_then(|entry| entry.locked_until)
+            .is_some_and(|until| until > Instant::now())
+    }
+
+    /// Count a failed sign-in; `true` when it locks `ip` out.
+    fn record_failure(&self, ip: IpAddr, admin: &WebAdminConfig) -> bool {
+        let Ok(mut failures) = self.failures.lock() else {
+            return false;
+        };
+        let now = Instant::now();
+        let window = Duration::from_secs(admin.lockout_seconds);
+        failures.retain(|_, entry| {
+            entry.locked_until.is_some_and(|until| until > now)
+                || now.duration_since(entry.last) < window
+        });
+        let entry = failures.entry(ip).or_insert(FailedLogins {
+            count: 0,
+            last: now,
+            locked_until: None,
+        });
+        entry.count += 1;
+        entry.last = now;
+        if admin.max_failed_logins > 0 && entry.count >= admin.max_failed_logins {
+            entry.count = 0;
+            entry.locked_until = Some(now + window);
+            return true;
+        }
+        false
+    }
+
+    fn clear_failures(&self, ip: IpAddr) {
+        if let Ok(mut failures) = self.failures.lock() {
+            failures.remove(&ip);
+        }
+    }
+
+    /// Check a TOTP code against the current time, refusing reuse.
+    fn totp_matches(&self, key: &[u8], code: &str) -> bool {
+        let code = code.trim();
+        if code.len() != 6 || !code.bytes().all(|c| c.is_ascii_digit()) {
+            return false;
+        }
+        let current = Utc::now().timestamp() / TOTP_STEP_SECONDS;
+        let Some(step) = (current - TOTP_SKEW_STEPS..=current + TOTP_SKEW_STEPS)
+            .find(|&step| hotp(key, step).is_some_and(|expected| expected == code))
+        else {
+            return false;
+        };
+        let Ok(mut last) = self.last_totp_step.lock() else {
+            return false;
+        };
+        if step <= *last {
+            return false;
+        }
+        *last = step;
+        true
+    }
+}
+
+/// RFC 4226 six-digit code for `counter`.
+fn hotp(key: &[u8], counter: i64) -> Option<String> {
+    let counter = u64::try_from(counter).ok()?;
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, key);
+    let tag = hmac::sign(&key, &counter.to_be_bytes());
+    let digest = tag.as_ref();
+    let offset = usize::from(digest.last()? & 0x0f);
+    let bytes: [u8; 4] = digest.get(offset..offset + 4)?.try_into().ok()?;
+    let value = u32::from_be_bytes(bytes) & 0x7fff_ffff;
+    Some(format!("{:06}", value % 1_000_000))
+}
+
+/// Require a dashboard session and reject cross-site form posts.
+pub(super) async fn require_admin(
+    State(state): State<Arc<WebState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let (Some(admin), Some(auth)) = (&state.config.web.sections.admin, &state.admin_auth) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if !auth.has_session(req.headers(), &admin.username) {
+        if req.method() == Method::GET {
+            return Redirect::to(&format!("{}/login", admin_base(&state))).into_response();
+        }
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    if req.method() != Method::GET && !is_same_origin(req.headers()) {
+        warn!(method = %req.method(), uri = %req.uri(), "Rejected cross-origin admin request");
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    next.run(req).await
+}
+
+/// Browsers send `Origin` (or at least `Referer`) on form posts; both must match `Host`.
+fn is_same_origin(headers: &HeaderMap) -> bool {
+    let Some(host) = headers.get(header::HOST).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    let source = headers
+        .get(header::ORIGIN)
+        .or_else(|| headers.get(header::REFERER))
+        .and_then(|v| v.to_str().ok());
+    source.is_none_or(|source| {
+        source
+            .split_once("://")
+            .map(|(_, rest)| rest.split('/').next().unwrap_or_default())
+            .is_some_and(|authority| authority.eq_ignore_ascii_case(host))
+    })
+}
+
+fn login_template(state: &WebState, error_key: Option<&str>) -> AdminLoginTemplate {
+    let layout = layout(state, "admin-web-login-title", None);
+    let error = error_key.map(|key| layout.t(key));
+    AdminLoginTemplate {
+        totp: state
+            .config
+            .web
+            .sections
+            .admin
+            .as_ref()
+            .is_some_and(|admin| admin.totp_secret.is_some()),
+        layout,
+        error,
+    }
+}
+
+/// Show the sign-in form.
+pub(super) async fn login_page(State(state): State<Arc<WebState>>) -> Response {
+    login_template(&state, None).into_response()
+}
+
+/// Check the credentials and start a session.
+pub(super) async fn login(
+    State(state): State<Arc<WebState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Form(form): Form<LoginForm>,
+) -> Response {
+    let (Some(admin), Some(auth)) = (&state.config.web.sections.admin, &state.admin_auth) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if !is_same_origin(&headers) {
+        warn!("Rejected cross-origin admin sign-in");
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let ip = resolve_client_ip(&state, &headers, addr.ip());
+    if auth.is_locked(ip) {
+        let page = login_template(&state, Some("admin-web-login-locked"));
+        return (StatusCode::TOO_MANY_REQUESTS, page).into_response();
+    }
+
+    let username_ok = constant_time_eq(form.username.as_bytes(), admin.username.as_bytes());
+    let password_ok = password_matches(admin, form.password).await;
+    // The code is only checked (and used up) behind the right password.
+    let signed_in = username_ok
+        && password_ok
+        && admin
+            .totp_key()
+            .is_none_or(|key| auth.totp_matches(&key, &form.code));
+    if !signed_in {
+        warn!(ip = %ip, "Failed admin dashboard sign-in");
+        if auth.record_failure(ip, admin) {
+            warn!(ip = %ip, "Admin dashboard sign-in locked after repeated failures");
+        }
+        let page = login_template(&state, Some("admin-web-login-failed"));
+        return (StatusCode::UNAUTHORIZED, page).into_response();
+    }
+
+    auth.clear_failures(ip);
+    info!(ip = %ip, "Admin signed in to the web dashboard");
+    let details = format!("IP {ip}");
+    audit::record(
+        &state.db,
+        Actor::Dashboard,
+        AuditAction::DashboardLogin,
+        &admin.username,
+        Some(&details),
+    )
+    .await;
+    let base = admin_base(&state);
+    let cookie = auth.issue_session(
+        &admin.username,
+        admin.session_ttl_seconds,
+        &base,
+        state.config.web.web_app_ssl_enabled,
+    );
+    session_redirect(&base, &cookie)
+}
+
+/// End the session.
+pub(super) async fn logout(State(state): State<Arc<WebState>>) -> Response {
+    let base = admin_base(&state);
+    let cookie = session_cookie("", &base, 0, state.config.web.web_app_ssl_enabled);
+    session_redirect(&format!("{base}/login"), &cookie)
+}
+
+/// `Set-Cookie` value of the session cookie; signing in and out share the
+/// attributes so browsers replace one with the other.
+fn session_cookie(value: &str, path: &str, max_age: u64, secure: bool) -> String {
+    let secure = if secure { "; Secure" } else { "" };
+    format!(
+        "{SESSION_COOKIE}={value}; Path={path}; Max-Age={max_age}; HttpOnly; SameSite=Strict{secure}"
+    )
+}
+
+fn session_redirect(target: &str, cookie: &str) -> Response {
+    let mut response = Redirect::to(target).into_response();
+    match HeaderValue::from_str(cookie) {
+        Ok(value) => {
+            response.headers_mut().insert(header::SET_COOKIE, value);
+        }
+        Err(e) => warn!(error = %e, "Failed to build admin session cookie header"),
+    }
+    response
+}
+
+/// Check `password` without blocking the runtime on the Argon2 hash.
+async fn password_matches(admin: &WebAdminConfig, password: String) -> bool {
+    if admin.password_hash.is_empty() {
+        return !admin.password.is_empty()
+            && constant_time_eq(password.as_bytes(), admin.password.as_bytes());
+    }
+    let hash = admin.password_hash.clone();
+    tokio::task::spawn_blocking(move || {
+        PasswordHash::new(&hash).is_ok_and(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+    })
+    .await
+    .unwrap_or(false)
+}
+
+/// Argon2 hash of `password` for `[web.admin] password_hash`.
+pub fn hash_password(password: &str) -> anyhow::Result<String> {
+    let salt = SaltString::encode_b64(&rand::random::<[u8; 16]>())
+        .map_err(|e| anyhow::anyhow!("Failed to encode salt: {e}"))?;
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| anyhow::anyhow!("Failed to hash password: {e}"))
+}
@@ -0,0 +1,295 @@
+use super::WebState;
+use super::client_ip::resolve_client_ip;
+use super::download::open_download;
+use super::handlers::{resolve_web_lang, user_agent};
+use super::submit::{
+    self, CreatedAccount, RequestStatus, WebRegisterError, WebRegisterInput, WebRegistration,
+};
+use crate::domain::Username;
+use crate::i18n::t;
+use crate::types::TTWorkerCommand;
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{ConnectInfo, Path, Query, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tracing::error;
+
+/// JSON body for `POST /api/v1/register`.
+#[derive(Deserialize)]
+pub(super) struct ApiRegisterRequest {
+    username: String,
+    password: String,
+    #[serde(default)]
+    nickname: String,
+    #[serde(default)]
+    captcha_token: String,
+    /// Server id; the top-level server when left out.
+    #[serde(default)]
+    server: String,
+    /// One of `requestable_rights_profiles`; the default profile when left out.
+    #[serde(default)]
+    rights_profile: String,
+    /// Invite token; required while `web_public_registration_enabled` is off.
+    #[serde(default)]
+    invite: String,
+}
+
+/// Query for `GET /api/v1/username_available`.
+#[derive(Deserialize)]
+pub(super) struct UsernameQuery {
+    username: String,
+    #[serde(default)]
+    server: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum ApiRegisterResponse {
+    Created {
+        username: String,
+        tt_link: String,
+        downloads: ApiDownloads,
+    },
+    Pending {
+        request_key: String,
+        status_url: String,
+    },
+    Queued {
+        username: String,
+    },
+}
+
+/// Body of `GET /api/v1/status/{key}`.
+#[derive(Serialize)]
+struct ApiStatusResponse {
+    /// `pending`, `approved`, `rejected` or `failed`.
+    status: &'static str,
+    /// Reason given with a rejection.
+    reason: Option<String>,
+    tt_link: Option<String>,
+    /// Set once, on the first request after the approval.
+    downloads: Option<ApiDownloads>,
+}
+
+#[derive(Serialize)]
+struct ApiDownloads {
+    tt_config: ApiDownload,
+    /// First entry of `client_zips`, kept for older clients.
+    client_zip: Option<ApiDownload>,
+    client_zips: Vec<ApiClientZip>,
+    /// Password of the `.tt` file inside the client ZIPs, when encrypted.
+    zip_password: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ApiClientZip {
+    name: String,
+    label: String,
+    url: String,
+    filename: String,
+}
+
+#[derive(Serialize)]
+struct ApiDownload {
+    url: String,
+    filename: String,
+}
+
+#[derive(Serialize)]
+struct UsernameAvailability {
+    username: String,
+    available: bool,
+}
+
+#[derive(Serialize)]
+struct ApiErrorBody {
+    error: ApiErrorDetail,
+}
+
+#[derive(Serialize)]
+struct ApiErrorDetail {
+    code: &'static str,
+    message: String,
+}
+
+fn api_error(status: StatusCode, code: &'static str, message: String) -> Response {
+    (
+        status,
+        Json(ApiErrorBody {
+            error: ApiErrorDetail { code, message },
+        }),
+    )
+        .into_response()
+}
+
+fn register_error(lang: &str, err: WebRegisterError) -> Response {
+    api_error(err.status(), err.code(), err.message(lang))
+}
+
+/// Register an account from a JSON request.
+pub(super) async fn register(
+    State(state): State<Arc<WebState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    body: Result<Json<ApiRegisterRequest>, JsonRejection>,
+) -> Response {
+    let body = match body {
+        Ok(Json(body)) => body,
+        Err(rejection) => {
+            return api_error(rejection.status(), "invalid_request", rejection.body_text());
+        }
+    };
+    let ip = resolve_client_ip(&state, &headers, addr.ip());
+    let (lang, _) = resolve_web_lang(&state.config, &headers);
+    let result = submit::register(WebRegisterInput {
+        state: &state,
+        ip,
+        user_agent: user_agent(&headers),
+        lang: lang.as_str(),
+        username: &body.username,
+        password: &body.password,
+        nickname: &body.nickname,
+        server: &body.server,
+        rights_profile: &body.rights_profile,
+        invite: &body.invite,
+        captcha_token: &body.captcha_token,
+    })
+    .await;
+
+    let base = state.config.web.base_path();
+    match result {
+        Ok(WebRegistration::Created(account)) => {
+            let response = ApiRegisterResponse::Created {
+                username: account.username.as_str().to_string(),
+                tt_link: account.tt_link.clone(),
+                downloads: api_downloads(base, account),
+            };
+            (StatusCode::CREATED, Json(response)).into_response()
+        }
+        Ok(WebRegistration::Pending { request_key }) => {
+            let response = ApiRegisterResponse::Pending {
+                status_url: format!("{base}/status/{request_key}"),
+                request_key,
+            };
+            (StatusCode::ACCEPTED, Json(response)).into_response()
+        }
+        Ok(WebRegistration::Queued { username }) => {
+            let response = ApiRegisterResponse::Queued {
+                username: username.as_str().to_string(),
+            };
+            (StatusCode::ACCEPTED, Json(response)).into_response()
+        }
+        Err(e) => register_error(lang.as_str(), e),
+    }
+}
+
+/// Report the review state of a web registration, with its downloads once
+/// approved; they are issued only on the first look after the approval.
+pub(super) async fn status(
+    State(state): State<Arc<WebState>>,
+    headers: HeaderMap,
+    Path(key): Path<String>,
+) -> Response {
+    let (lang, _) = resolve_web_lang(&state.config, &headers);
+    let RequestStatus {
+        status,
+        reason,
+        account,
+    } = submit::request_status(&state, &key, lang.as_str()).await;
+    let Some(status) = status else {
+        return api_error(
+            StatusCode::NOT_FOUND,
+            "not_found",
+            t(lang.as_str(), "web-status-not-found"),
+        );
+    };
+    let base = state.config.web.base_path();
+    Json(ApiStatusResponse {
+        status: status.as_str(),
+        reason,
+        tt_link: account.as_ref().map(|account| account.tt_link.clone()),
+        downloads: account.map(|account| api_downloads(base, account)),
+    })
+    .into_response()
+}
+
+fn api_downloads(base: &str, account: CreatedAccount) -> ApiDownloads {
+    ApiDownloads {
+        tt_config: ApiDownload {
+            url: format!("{base}/api/v1/download/{}", account.tt_token),
+            filename: account.tt_filename,
+        },
+        client_zip: account.client_zips.first().map(|zip| ApiDownload {
+            url: format!("{base}/api/v1/download/{}", zip.token),
+            filename: zip.filename.clone(),
+        }),
+        client_zips: account
+            .client_zips
+            .into_iter()
+            .map(|zip| ApiClientZip {
+                url: format!("{base}/api/v1/download/{}", zip.token),
+                filename: zip.filename,
+                name: zip.name,
+                label: zip.label,
+            })
+            .collect(),
+        zip_password: account.zip_password,
+    }
+}
+
+/// Report whether a username can still be registered.
+pub(super) async fn username_available(
+    State(state): State<Arc<WebState>>,
+    headers: HeaderMap,
+    Query(query): Query<UsernameQuery>,
+) -> Response {
+    let (lang, _) = resolve_web_lang(&state.config, &headers);
+    let Some(username) = Username::parse(&query.username) else {
+        return register_error(lang.as_str(), WebRegisterError::UsernameInvalid);
+    };
+    let policy = &state.config.username_policy;
+    if let Err(violation) = policy.check(&username) {
+        return register_error(lang.as_str(), WebRegisterError::UsernamePolicy(violation));
+    }
+    let Some(server) = state.servers.resolve(query.server.as_deref()) else {
+        return register_error(lang.as_str(), WebRegisterError::UnknownServer);
+    };
+    match server
+        .tx_tt
+        .request(|resp| TTWorkerCommand::CheckUserExists {
+            username: username.clone(),
+            matching: policy.matching(),
+            resp,
+        })
+        .await
+    {
+        Ok(exists) => Json(UsernameAvailability {
+            username: username.as_str().to_string(),
+            available: !exists,
+        })
+        .into_response(),
+        Err(e) => {
+            error!(error = %e, "TeamTalk user existence check failed");
+            register_error(lang.as_str(), WebRegisterError::Unavailable)
+        }
+    }
+}
+
+/// Download a generated file by token, with JSON errors.
+pub(super) async fn download(
+    State(state): State<Arc<WebState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(token): Path<String>,
+) -> Response {
+    let (lang, _) = resolve_web_lang(&state.config, &headers);
+    let ip = resolve_client_ip(&state, &headers, addr.ip());
+    match open_download(&state, ip, &token, None, headers.get(header::RANGE)).await {
+        Ok(response) => response,
+        Err(e) => api_error(e.status(), e.code(), t(lang.as_str(), e.key())),
+    }
+}
@@ -0,0 +1,99 @@
+use crate::config::{CaptchaConfig, CaptchaProvider};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::time::Duration;
+use tracing::{error, warn};
+
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Server-side verifier for CAPTCHA widget tokens.
+pub(super) struct CaptchaVerifier {
+    config: CaptchaConfig,
+    client: reqwest::Client,
+}
+
+/// Widget settings rendered into the registration form.
+#[derive(Serialize)]
+pub struct CaptchaWidget {
+    pub script_url: &'static str,
+    pub widget_class: &'static str,
+    pub site_key: String,
+}
+
+#[derive(Deserialize)]
+struct VerifyResponse {
+    success: bool,
+    #[serde(default, rename = "error-codes")]
+    error_codes: Vec<String>,
+}
+
+impl CaptchaVerifier {
+    /// Build a verifier for the configured provider.
+    pub(super) fn new(config: CaptchaConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(VERIFY_TIMEOUT)
+            .build()
+            .unwrap_or_else(|e| {
+                warn!(error = %e, "Failed to build CAPTCHA HTTP client, using defaults");
+                reqwest::Client::new()
+            });
+        Self { config, client }
+    }
+
+    /// Widget description for the HTML template.
+    pub(super) fn widget(&self) -> CaptchaWidget {
+        let (script_url, widget_class) = match self.config.provider {
+            CaptchaProvider::Turnstile => (
+                "https://challenges.cloudflare.com/turnstile/v0/api.js",
+                "cf-turnstile",
+            ),
+            CaptchaProvider::Hcaptcha => ("https://js.hcaptcha.com/1/api.js", "h-captcha"),
+        };
+        CaptchaWidget {
+            script_url,
+            widget_class,
+            site_key: self.config.site_key.clone(),
+        }
+    }
+
+    /// Check a widget token with the provider.
+    pub(super) async fn verify(&self, token: &str, remote_ip: IpAddr) -> bool {
+        if token.is_empty() {
+            return false;
+        }
+        let url = match self.config.provider {
+            CaptchaProvider::Turnstile => {
+                "https://challenges.cloudflare.com/turnstile/v0/siteverify"
+            }
+            CaptchaProvider::Hcaptcha => "https://api.hcaptcha.com/siteverify",
+        };
+        let remote_ip = remote_ip.to_string();
+        let mut params = vec![
+            ("secret", self.config.secret_key.as_str()),
+            ("response", token),
+            ("remoteip", remote_ip.as_str()),
+        ];
+        if self.config.provider == CaptchaProvider::Hcaptcha {
+            params.push(("sitekey", self.config.site_key.as_str()));
+        }
+        let response = match self.client.post(url).form(&params).send().await {
+            Ok(resp) => resp,
+            Err(e) => {
+                error!(error = %e, "CAPTCHA verification request failed");
+                return false;
+            }
+        };
+        match response.json::<VerifyResponse>().await {
+            Ok(body) => {
+                if !body.success {
+                    warn!(errors = ?body.error_codes, ip = %remote_ip, "CAPTCHA verification rejected");
+                }
+                body.success
+            }
+            Err(e) => {
+                error!(error = %e, "Invalid CAPTCHA verification response");
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use super::WebState;
+use crate::config::ProxyHeader;
+use crate::domain::IpNetwork;
+use axum::http::HeaderMap;
+use std::net::{IpAddr, SocketAddr};
+use tracing::warn;
+
+/// Proxies allowed to report the original client address.
+pub(super) enum TrustedProxies {
+    /// Trust every peer (`*`).
+    Any,
+    /// Trust peers inside these networks.
+    Networks(Vec<IpNetwork>),
+}
+
+impl TrustedProxies {
+    /// Parse `web_app_forwarded_allow_ips` (`*` or a comma-separated list of IPs/CIDRs).
+    pub(super) fn parse(value: &str) -> Self {
+        let value = value.trim();
+        if value == "*" {
+            return Self::Any;
+        }
+        let networks = value
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .filter_map(|entry| {
+                let parsed = IpNetwork::parse(entry);
+                if parsed.is_none() {
+                    warn!(entry, "Ignoring invalid web_app_forwarded_allow_ips entry");
+                }
+                parsed
+            })
+            .collect();
+        Self::Networks(networks)
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Networks(networks) => networks.iter().any(|net| net.contains(ip)),
+        }
+    }
+}
+
+/// Resolve the real client IP, honoring proxy headers only from trusted peers.
+///
+/// The forwarding chain is walked from the nearest hop outwards and the first
+/// address that is not itself a trusted proxy is returned.
+pub(super) fn resolve_client_ip(state: &WebState, headers: &HeaderMap, peer: IpAddr) -> IpAddr {
+    if !state.config.web.web_app_proxy_headers || !state.trusted_proxies.contains(peer) {
+        return peer;
+    }
+
+    let chain = forwarded_chain(headers, state.config.web.web_app_proxy_header);
+    if chain.is_empty() {
+        return peer;
+    }
+    chain
+        .iter()
+        .rev()
+        .find(|ip| !state.trusted_proxies.contains(**ip))
+        .or_else(|| chain.first())
+        .copied()
+        .unwrap_or(peer)
+}
+
+/// Client addresses from the configured proxy header only, so a client cannot
+/// inject another header the proxy passes through untouched.
+fn forwarded_chain(headers: &HeaderMap, header: ProxyHeader) -> Vec<IpAddr> {
+    match header {
+        ProxyHeader::Forwarded => header_values(headers, "forwarded")
+            .flat_map(|value| value.split(',').map(str::to_string).collect::<Vec<_>>())
+            .filter_map(|element| {
+                element
+                    .split(';')
+                    .find_map(|pair| {
+                        let (key, value) = pair.trim().split_once('=')?;
+                        key.trim()
+                            .eq_ignore_ascii_case("for")
+                            .then(|| value.trim().to_string())
+                    })
+                    .and_then(|node| parse_node(&node))
+            })
+            .collect(),
+        ProxyHeader::XForwardedFor => header_values(headers, "x-forwarded-for")
+            .flat_map(|value| value.split(',').map(str::to_string).collect::<Vec<_>>())
+            .filter_map(|node| parse_node(&node))
+            .collect(),
+        ProxyHeader::XRealIp => header_values(headers, "x-real-ip")
+            .filter_map(|value| parse_node(&value))
+            .collect(),
+    }
+}
+
+fn header_values<'a>(headers: &'a HeaderMap, name: &'a str) -> impl Iterator<Item = String> + 'a {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(move |raw| match raw.to_str() {
+            Ok(value) => Some(value.to_string()),
+            Err(e) => {
+                warn!(error = %e, header = name, "Invalid proxy header encoding");
+                None
+            }
+        })
+}
+
+/// Parse a node such as `203.0.113.7`, `"[2001:db8::1]:4711"` or `192.0.2.1:80`.
+fn parse_node(raw: &str) -> Option<IpAddr> {
+    let node = raw.trim().trim_matches('"');
+    if let Ok(ip) = node.parse::<IpAddr>() {
+        return Some(ip);
+    }
+    if let Ok(addr) = node.parse::<SocketAddr>() {
+        return Some(addr.ip());
+    }
+    node.strip_prefix('[')
+        .and_then(|rest| rest.split_once(']'))
+        .and_then(|(ip, _)| ip.parse().ok())
+}
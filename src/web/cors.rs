@@ -0,0 +1,37 @@
+use crate::config::WebCorsConfig;
+use axum::http::{HeaderName, HeaderValue, Method};
+use std::time::Duration;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+/// `CorsLayer` for the JSON API from `[web.cors]`; entries were checked when
+/// the config was loaded.
+pub(super) fn layer(config: &WebCorsConfig) -> CorsLayer {
+    let origin = if config.allows_any_origin() {
+        AllowOrigin::from(Any)
+    } else {
+        AllowOrigin::list(
+            config
+                .allowed_origins
+                .iter()
+                .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+        )
+    };
+    CorsLayer::new()
+        .allow_origin(origin)
+        .allow_methods(
+            config
+                .allowed_methods
+                .iter()
+                .filter_map(|method| method.parse::<Method>().ok())
+                .collect::<Vec<_>>(),
+        )
+        .allow_headers(
+            config
+                .allowed_headers
+                .iter()
+                .filter_map(|name| name.parse::<HeaderName>().ok())
+                .collect::<Vec<_>>(),
+        )
+        .allow_credentials(config.allow_credentials)
+        .max_age(Duration::from_secs(config.max_age_seconds))
+}
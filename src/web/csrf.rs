@@ -0,0 +1,88 @@
+use crate::config::WebConfig;
+use axum::http::{HeaderMap, HeaderValue, header};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use ring::hmac;
+use tracing::warn;
+
+/// Name of the cookie and of the hidden form field carrying the token.
+const CSRF_NAME: &str = "csrf_token";
+
+/// Double-submit CSRF tokens for the HTML forms: a signed random token is
+/// set as a `SameSite=Strict` cookie and repeated in a hidden field, and a
+/// POST is accepted only when both match and the signature holds.
+pub(super) struct CsrfTokens {
+    /// `None` when `web_csrf_protection` is off.
+    key: Option<hmac::Key>,
+    /// `Path` of the cookie.
+    path: String,
+    secure: bool,
+}
+
+impl CsrfTokens {
+    /// Build with a per-process key; forms rendered before a restart are
+    /// refused once and rendered again with a new token.
+    pub(super) fn new(config: &WebConfig) -> Self {
+        Self {
+            key: config
+                .web_csrf_protection
+                .then(|| hmac::Key::new(hmac::HMAC_SHA256, &rand::random::<[u8; 32]>())),
+            path: config.cookie_path().to_string(),
+            secure: config.web_app_ssl_enabled,
+        }
+    }
+
+    /// Token for a page with a form, reusing the visitor's cookie when it is
+    /// still valid, and the `Set-Cookie` header for a new one.
+    pub(super) fn issue(&self, headers: &HeaderMap) -> (Option<String>, HeaderMap) {
+        let mut response_headers = HeaderMap::new();
+        let Some(key) = &self.key else {
+            return (None, response_headers);
+        };
+        if let Some(token) = cookie_token(headers).filter(|token| valid(key, token)) {
+            return (Some(token.to_string()), response_headers);
+        }
+        let nonce = BASE64_URL.encode(rand::random::<[u8; 16]>());
+        let tag = hmac::sign(key, nonce.as_bytes());
+        let token = format!("{nonce}.{}", BASE64_URL.encode(tag.as_ref()));
+        let secure = if self.secure { "; Secure" } else { "" };
+        match HeaderValue::from_str(&format!(
+            "{CSRF_NAME}={token}; Path={}; HttpOnly; SameSite=Strict{secure}",
+            self.path
+        )) {
+            Ok(value) => {
+                response_headers.insert(header::SET_COOKIE, value);
+            }
+            Err(e) => {
+                warn!(error = %e, "Failed to build CSRF cookie header");
+            }
+        }
+        (Some(token), response_headers)
+    }
+
+    /// Whether a form post carries the token of its cookie; always `true`
+    /// when protection is off.
+    pub(super) fn verify(&self, headers: &HeaderMap, submitted: &str) -> bool {
+        let Some(key) = &self.key else {
+            return true;
+        };
+        cookie_token(headers).is_some_and(|token| token == submitted && valid(key, token))
+    }
+}
+
+fn cookie_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::COOKIE)?
+        .to_str()
+        .ok()?
+        .split(';')
+        .find_map(|part| part.trim().strip_prefix(CSRF_NAME)?.strip_prefix('='))
+}
+
+fn valid(key: &hmac::Key, token: &str) -> bool {
+    token.split_once('.').is_some_and(|(nonce, tag)| {
+        BASE64_URL
+            .decode(tag)
+            .is_ok_and(|tag| hmac::verify(key, nonce.as_bytes(), &tag).is_ok())
+    })
+}
@@ -0,0 +1,628 @@
+use super::WebState;
+use super::signed_links::{SignedDownload, SignedLinks};
+use crate::db::Database;
+use crate::services::audit::{self, Actor, AuditAction};
+use crate::services::registration::{self, ClientZipSource};
+use crate::types::DownloadTokenType;
+use axum::body::Body;
+use axum::http::{HeaderValue, StatusCode, header};
+use axum::response::Response;
+use std::io::SeekFrom;
+use std::net::IpAddr;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, ReadBuf};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio_util::io::ReaderStream;
+use tracing::{error, info, warn};
+
+/// A download token resolved to a file in the temp directory.
+struct ResolvedDownload {
+    file_name: String,
+    original_name: String,
+    signed: Option<SignedDownload>,
+    /// Whether the token counts its uses rather than only expiring by time.
+    limited: bool,
+    zip_source: Option<String>,
+    zip_readme: Option<String>,
+    zip_password: Option<String>,
+    client_template: Option<String>,
+}
+
+/// Why a download was refused.
+pub(super) enum DownloadError {
+    /// The link is invalid or its file is gone; carries the translation key.
+    Unavailable(&'static str),
+    /// The client is blocked after trying too many invalid links.
+    Blocked,
+}
+
+impl DownloadError {
+    /// HTTP status to answer with.
+    pub(super) const fn status(&self) -> StatusCode {
+        match self {
+            Self::Unavailable(_) => StatusCode::NOT_FOUND,
+            Self::Blocked => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+
+    /// Machine-readable code for the JSON API.
+    pub(super) const fn code(&self) -> &'static str {
+        match self {
+            Self::Unavailable(_) => "not_found",
+            Self::Blocked => "rate_limited",
+        }
+    }
+
+    /// Translation key of the message shown to the client.
+    pub(super) const fn key(&self) -> &'static str {
+        match self {
+            Self::Unavailable(key) => key,
+            Self::Blocked => "web-err-rate-limited",
+        }
+    }
+}
+
+/// A use of a token taken as its transfer starts, so concurrent requests
+/// cannot both stream a single-use link.
+struct Reservation {
+    token: String,
+    /// Signature of a signed link; `None` for a stored token.
+    signed_id: Option<String>,
+    file_name: String,
+    /// Whether the use taken was the last; `None` when the link only expires
+    /// by time and no use was taken.
+    last_use: Option<bool>,
+}
+
+/// Part of the file a request asks for.
+enum ByteRange {
+    Full,
+    Partial { start: u64, end: u64 },
+    Unsatisfiable,
+}
+
+/// Resolve a download token and stream its file, honoring a single-range
+/// `Range` header so interrupted downloads can resume. Client ZIPs stored
+/// with their `.tt` source are generated on the fly instead.
+///
+/// A response reaching the last byte of the file takes a use of the token
+/// when it starts and gives it back if the transfer is aborted; the file is
+/// deleted once the transfer holding the token's last use completes. Invalid
+/// links are counted against the client `ip`, which is refused outright once
+/// blocked.
+pub(super) async fn open_download(
+    state: &Arc<WebState>,
+    ip: IpAddr,
+    token: &str,
+    expected_type: Option<DownloadTokenType>,
+    range: Option<&HeaderValue>,
+) -> Result<Response, DownloadError> {
+    if state.download_guard.is_blocked(ip) {
+        return Err(DownloadError::Blocked);
+    }
+    let mut resolved = match resolve(state, token, expected_type).await {
+        Ok(resolved) => resolved,
+        Err(key) => {
+            record_invalid_link(state, ip).await;
+            return Err(DownloadError::Unavailable(key));
+        }
+    };
+    if let Some(source) = resolved.zip_source.take() {
+        return stream_zip(state, token, resolved, source)
+            .await
+            .map_err(DownloadError::Unavailable);
+    }
+    serve_file(state, token, resolved, range)
+        .await
+        .map_err(DownloadError::Unavailable)
+}
+
+/// Count an invalid link from `ip`, blocking and auditing it once it has
+/// tried too many.
+async fn record_invalid_link(state: &WebState, ip: IpAddr) {
+    info!(ip = %ip, "Invalid download link");
+    if !state.download_guard.record_failure(ip) {
+        return;
+    }
+    let web = &state.config.web;
+    warn!(ip = %ip, "Downloads blocked after repeated invalid links");
+    let details = format!(
+        "{} invalid links, blocked for {}s",
+        web.web_download_max_failures, web.web_download_block_seconds
+    );
+    audit::record(
+        &state.db,
+        Actor::Visitor(ip),
+        AuditAction::DownloadBlocked,
+        &ip.to_string(),
+        Some(&details),
+    )
+    .await;
+}
+
+async fn serve_file(
+    state: &Arc<WebState>,
+    token: &str,
+    resolved: ResolvedDownload,
+    range: Option<&HeaderValue>,
+) -> Result<Response, &'static str> {
+    let path = state.config.get_temp_dir().join(&resolved.file_name);
+    let mut file = match File::open(&path).await {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err("web-err-invalid-link");
+        }
+        Err(e) => {
+            error!(error = %e, path = %path.display(), "Failed to open download file");
+            return Err("web-err-file-not-found");
+        }
+    };
+    let len = match file.metadata().await {
+        Ok(metadata) => metadata.len(),
+        Err(e) => {
+            error!(error = %e, path = %path.display(), "Failed to read download file size");
+            return Err("web-err-file-not-found");
+        }
+    };
+
+    let (start, end) = match byte_range(range, len) {
+        ByteRange::Full => (0, len.saturating_sub(1)),
+        ByteRange::Partial { start, end } => (start, end),
+        ByteRange::Unsatisfiable => {
+            return Response::builder()
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{len}"))
+                .body(Body::empty())
+                .map_err(|e| {
+                    error!(error = %e, "Failed to build response");
+                    "web-err-invalid-link"
+                });
+        }
+    };
+    let partial = start > 0 || end + 1 < len;
+    if start > 0
+        && let Err(e) = file.seek(SeekFrom::Start(start)).await
+    {
+        error!(error = %e, path = %path.display(), "Failed to seek download file");
+        return Err("web-err-file-not-found");
+    }
+    let content_length = if len == 0 { 0 } else { end - start + 1 };
+
+    let mime = mime_guess::from_path(&path).first_or_octet_stream();
+    let original_name = resolved.original_name.clone();
+    let completion = if end + 1 >= len {
+        Some(on_complete(
+            state,
+            reserve(&state.db, token, &resolved).await?,
+            None,
+        ))
+    } else {
+        None
+    };
+    let reader = CompletionReader {
+        inner: file.take(content_length),
+        remaining: content_length,
+        completion,
+    };
+    let body = Body::from_stream(ReaderStream::new(reader));
+
+    let mut builder = Response::builder()
+        .header(header::CONTENT_TYPE, mime.as_ref())
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{original_name}\""),
+        )
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CONTENT_LENGTH, content_length);
+    if partial {
+        builder = builder
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CONTENT_RANGE, format!("bytes {start}-{end}/{len}"));
+    }
+    builder.body(body).map_err(|e| {
+        error!(error = %e, "Failed to build response");
+        "web-err-invalid-link"
+    })
+}
+
+/// Generate a client ZIP into the response; ranges are not supported here.
+async fn stream_zip(
+    state: &Arc<WebState>,
+    token: &str,
+    mut resolved: ResolvedDownload,
+    source: String,
+) -> Result<Response, &'static str> {
+    if source.is_empty() {
+        return Err("web-err-file-not-found");
+    }
+    // Tokens issued before templates were named fall back to the first one.
+    let template = resolved.client_template.as_deref().map_or_else(
+        || {
+            registration::client_templates(&state.config)
+                .into_iter()
+                .next()
+        },
+        |name| registration::find_client_template(&state.config, name),
+    );
+    let Some(template) = template else {
+        warn!(template = ?resolved.client_template, "Client template is gone; cannot generate ZIP");
+        return Err("web-err-file-not-found");
+    };
+    let reservation = reserve(&state.db, token, &resolved).await?;
+    let (reader, writer) = registration::stream_client_zip_async(
+        &state.config,
+        template,
+        ClientZipSource {
+            tt_content: source,
+            readme: resolved.zip_readme.take(),
+            password: resolved.zip_password.take(),
+        },
+    );
+    let original_name = resolved.original_name.clone();
+    let reader = CompletionReader {
+        inner: reader,
+        remaining: u64::MAX,
+        completion: Some(on_complete(state, reservation, Some(writer))),
+    };
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/zip")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{original_name}\""),
+        )
+        .header(header::ACCEPT_RANGES, "none")
+        .body(Body::from_stream(ReaderStream::new(reader)))
+        .map_err(|e| {
+            error!(error = %e, "Failed to build response");
+            "web-err-invalid-link"
+        })
+}
+
+/// Finish the download once the returned sender fires and `writer`, if any,
+/// produced the whole archive; give the reserved use back when the sender is
+/// dropped first or the archive failed.
+fn on_complete(
+    state: &Arc<WebState>,
+    reservation: Reservation,
+    writer: Option<JoinHandle<bool>>,
+) -> oneshot::Sender<()> {
+    let (tx, rx) = oneshot::channel();
+    let state = Arc::clone(state);
+    tokio::spawn(async move {
+        let finished = rx.await.is_ok()
+            && match writer {
+                Some(writer) => writer.await.unwrap_or(false),
+                None => true,
+            };
+        if finished {
+            finish_download(state.config.get_temp_dir(), &reservation).await;
+        } else {
+            release(&state.db, &reservation).await;
+        }
+    });
+    tx
+}
+
+async fn resolve(
+    state: &WebState,
+    token: &str,
+    expected_type: Option<DownloadTokenType>,
+) -> Result<ResolvedDownload, &'static str> {
+    let (resolved, stored_type) = if SignedLinks::is_signed(token) {
+        let Some(download) = state.signed_links.verify(token) else {
+            return Err("web-err-invalid-link");
+        };
+        match state.db.signed_link_usable(&download.id).await {
+            Ok(true) => {}
+            Ok(false) => return Err("web-err-invalid-link"),
+            Err(e) => {
+                error!(error = %e, "Failed to check signed download link");
+                return Err("web-err-invalid-link");
+            }
+        }
+        let stored_type = download.token_type.clone();
+        let resolved = ResolvedDownload {
+            file_name: download.file.clone(),
+            original_name: download.original_name.clone(),
+            limited: download.max_uses.is_some(),
+            signed: Some(download),
+            zip_source: None,
+            zip_readme: None,
+            zip_password: None,
+            client_template: None,
+        };
+        (resolved, stored_type)
+    } else {
+        match state.db.get_download_token(token).await {
+            Ok(Some(tok_data)) => (
+                ResolvedDownload {
+                    file_name: tok_data.filepath_on_server,
+                    original_name: tok_data.original_filename,
+                    signed: None,
+                    limited: tok_data.uses_remaining.is_some(),
+                    zip_source: tok_data.zip_source,
+                    zip_readme: tok_data.zip_readme,
+                    zip_password: tok_data.zip_password,
+                    client_template: tok_data.client_template,
+                },
+                tok_data.token_type,
+            ),
+            Ok(None) => return Err("web-err-invalid-link"),
+            Err(e) => {
+                error!(error = %e, "Failed to load download token");
+                return Err("web-err-invalid-link");
+            }
+        }
+    };
+    if let Some(token_type) = expected_type {
+        let Ok(parsed_type) = DownloadTokenType::try_from(stored_type.as_str()) else {
+            warn!(token_type = %stored_type, "Invalid download token type");
+            return Err("web-err-invalid-link");
+        };
+        if parsed_type != token_type {
+            return Err("web-err-invalid-link");
+        }
+    }
+    Ok(resolved)
+}
+
+/// Take a use of the token for a transfer about to start; refused when
+/// another request took the last one.
+async fn reserve(
+    db: &Database,
+    token: &str,
+    resolved: &ResolvedDownload,
+) -> Result<Reservation, &'static str> {
+    let mut reservation = Reservation {
+        token: token.to_string(),
+        signed_id: resolved.signed.as_ref().map(|download| download.id.clone()),
+        file_name: resolved.file_name.clone(),
+        last_use: None,
+    };
+    if !resolved.limited {
+        return Ok(reservation);
+    }
+    let consumed = match &resolved.signed {
+        Some(download) => {
+            let max_uses = download.max_uses.map_or(1, i64::from);
+            db.consume_signed_link(&download.id, max_uses, download.expires_at())
+                .await
+        }
+        None => db.consume_download_token(token).await,
+    };
+    match consumed {
+        Ok(Some(last)) => {
+            reservation.last_use = Some(last);
+            Ok(reservation)
+        }
+        Ok(None) => Err("web-err-invalid-link"),
+        Err(e) => {
+            error!(error = %e, "Failed to reserve download token");
+            Err("web-err-invalid-link")
+        }
+    }
+}
+
+/// Delete the file once the transfer holding its token's last use completed.
+async fn finish_download(temp_dir: &Path, reservation: &Reservation) {
+    if reservation.last_use == Some(true) {
+        registration::remove_generated_file(temp_dir, &reservation.file_name).await;
+    }
+}
+
+/// Give back the use taken by a transfer that did not complete.
+async fn release(db: &Database, reservation: &Reservation) {
+    if reservation.last_use.is_none() {
+        return;
+    }
+    let restored = match &reservation.signed_id {
+        Some(id) => db.restore_signed_link(id).await,
+        None => db.restore_download_token(&reservation.token).await,
+    };
+    if let Err(e) = restored {
+        warn!(error = %e, "Failed to give back download token use");
+    }
+}
+
+/// Parse a `Range` header against a file of `len` bytes. Anything other than
+/// a single `bytes=` range is ignored and the whole file is served.
+fn byte_range(header: Option<&HeaderValue>, len: u64) -> ByteRange {
+    let Some(spec) = header
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().strip_prefix("bytes="))
+    else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((first, last)) = spec.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+    let (first, last) = (first.trim(), last.trim());
+    if first.is_empty() {
+        let Ok(suffix) = last.parse::<u64>() else {
+            return ByteRange::Full;
+        };
+        if suffix == 0 || len == 0 {
+            return ByteRange::Unsatisfiable;
+        }
+        return ByteRange::Partial {
+            start: len.saturating_sub(suffix),
+            end: len - 1,
+        };
+    }
+    let Ok(start) = first.parse::<u64>() else {
+        return ByteRange::Full;
+    };
+    let end = if last.is_empty() {
+        len.saturating_sub(1)
+    } else {
+        match last.parse::<u64>() {
+            Ok(end) if end >= start => end.min(len.saturating_sub(1)),
+            _ => return ByteRange::Full,
+        }
+    };
+    if start >= len {
+        return ByteRange::Unsatisfiable;
+    }
+    ByteRange::Partial { start, end }
+}
+
+/// Signals `completion` once all `remaining` bytes have been read or the
+/// input ends; dropping it earlier (an aborted transfer) gives the reserved
+/// use back.
+struct CompletionReader<R> {
+    inner: R,
+    remaining: u64,
+    completion: Option<oneshot::Sender<()>>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for CompletionReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let filled = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if matches!(poll, Poll::Ready(Ok(()))) {
+            let read = (buf.filled().len() - filled) as u64;
+            self.remaining = self.remaining.saturating_sub(read);
+            if (self.remaining == 0 || (read == 0 && buf.remaining() > 0))
+                && let Some(completion) = self.completion.take()
+            {
+                let _ = completion.send(());
+            }
+        }
+        poll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeDelta, Utc};
+
+    fn in_an_hour() -> chrono::NaiveDateTime {
+        (Utc::now() + TimeDelta::hours(1)).naive_utc()
+    }
+
+    fn stored(file: &str) -> ResolvedDownload {
+        ResolvedDownload {
+            file_name: file.to_string(),
+            original_name: file.to_string(),
+            signed: None,
+            limited: true,
+            zip_source: None,
+            zip_readme: None,
+            zip_password: None,
+            client_template: None,
+        }
+    }
+
+    async fn reserved(
+        db: &Database,
+        token: &str,
+        resolved: &ResolvedDownload,
+    ) -> anyhow::Result<Reservation> {
+        reserve(db, token, resolved)
+            .await
+            .map_err(anyhow::Error::msg)
+    }
+
+    #[tokio::test]
+    async fn last_use_deletes_file() -> anyhow::Result<()> {
+        let (db, dir) = Database::open_temp().await?;
+        std::fs::write(dir.join("a.tt"), "tt")?;
+        db.add_test_download_token("tok", "a.tt", Some(1), in_an_hour())
+            .await?;
+
+        let reservation = reserved(&db, "tok", &stored("a.tt")).await?;
+        finish_download(&dir, &reservation).await;
+
+        assert!(!dir.join("a.tt").exists());
+        assert!(db.get_download_token("tok").await?.is_none());
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn file_kept_while_uses_remain() -> anyhow::Result<()> {
+        let (db, dir) = Database::open_temp().await?;
+        std::fs::write(dir.join("a.tt"), "tt")?;
+        db.add_test_download_token("tok", "a.tt", Some(2), in_an_hour())
+            .await?;
+
+        let reservation = reserved(&db, "tok", &stored("a.tt")).await?;
+        finish_download(&dir, &reservation).await;
+        assert!(dir.join("a.tt").exists());
+        let reservation = reserved(&db, "tok", &stored("a.tt")).await?;
+        finish_download(&dir, &reservation).await;
+        assert!(!dir.join("a.tt").exists());
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn concurrent_transfer_of_single_use_link_refused() -> anyhow::Result<()> {
+        let (db, dir) = Database::open_temp().await?;
+        db.add_test_download_token("tok", "a.tt", Some(1), in_an_hour())
+            .await?;
+
+        let first = reserved(&db, "tok", &stored("a.tt")).await?;
+        assert!(reserve(&db, "tok", &stored("a.tt")).await.is_err());
+        assert_eq!(first.last_use, Some(true));
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn aborted_transfer_gives_use_back() -> anyhow::Result<()> {
+        let (db, dir) = Database::open_temp().await?;
+        std::fs::write(dir.join("a.tt"), "tt")?;
+        db.add_test_download_token("tok", "a.tt", Some(1), in_an_hour())
+            .await?;
+
+        let reservation = reserved(&db, "tok", &stored("a.tt")).await?;
+        release(&db, &reservation).await;
+
+        assert!(dir.join("a.tt").exists());
+        assert!(db.get_download_token("tok").await?.is_some());
+        reserved(&db, "tok", &stored("a.tt")).await?;
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn used_signed_link_deletes_file() -> anyhow::Result<()> {
+        let (db, dir) = Database::open_temp().await?;
+        std::fs::write(dir.join("s.tt"), "tt")?;
+        let download = SignedDownload {
+            file: "s.tt".to_string(),
+            original_name: "s.tt".to_string(),
+            token_type: DownloadTokenType::TtConfig.as_str().to_string(),
+            expires: in_an_hour().and_utc().timestamp(),
+            max_uses: Some(1),
+            id: "signature".to_string(),
+        };
+        let resolved = ResolvedDownload {
+            signed: Some(download),
+            ..stored("s.tt")
+        };
+
+        let reservation = reserved(&db, "s.token", &resolved).await?;
+        assert!(reserve(&db, "s.token", &resolved).await.is_err());
+        finish_download(&dir, &reservation).await;
+
+        assert!(!dir.join("s.tt").exists());
+        assert!(!db.signed_link_usable("signature").await?);
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
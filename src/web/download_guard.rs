@@ -0,0 +1,71 @@
+use crate::config::WebConfig;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Counts invalid download links per client IP and blocks those guessing
+/// tokens.
+pub(super) struct DownloadGuard {
+    max_failures: u32,
+    block_for: Duration,
+    failures: Mutex<HashMap<IpAddr, FailedLookups>>,
+}
+
+struct FailedLookups {
+    count: u32,
+    last: Instant,
+    blocked_until: Option<Instant>,
+}
+
+impl DownloadGuard {
+    /// Build from `web_download_max_failures` and
+    /// `web_download_block_seconds`.
+    pub(super) fn new(config: &WebConfig) -> Self {
+        Self {
+            max_failures: config.web_download_max_failures,
+            block_for: Duration::from_secs(config.web_download_block_seconds),
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether `ip` is blocked from downloads after too many invalid links.
+    pub(super) fn is_blocked(&self, ip: IpAddr) -> bool {
+        let Ok(failures) = self.failures.lock() else {
+            return false;
+        };
+        failures
+            .get(&ip)
+            .and_then(|entry| entry.blocked_until)
+            .is_some_and(|until| until > Instant::now())
+    }
+
+    /// Count an invalid link from `ip`; `true` when it blocks `ip`.
+    pub(super) fn record_failure(&self, ip: IpAddr) -> bool {
+        if self.max_failures == 0 {
+            return false;
+        }
+        let Ok(mut failures) = self.failures.lock() else {
+            return false;
+        };
+        let now = Instant::now();
+        let window = self.block_for;
+        failures.retain(|_, entry| {
+            entry.blocked_until.is_some_and(|until| until > now)
+                || now.duration_since(entry.last) < window
+        });
+        let entry = failures.entry(ip).or_insert(FailedLookups {
+            count: 0,
+            last: now,
+            blocked_until: None,
+        });
+        entry.count += 1;
+        entry.last = now;
+        if entry.count < self.max_failures {
+            return false;
+        }
+        entry.count = 0;
+        entry.blocked_until = Some(now + window);
+        true
+    }
+}
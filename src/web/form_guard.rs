@@ -0,0 +1,73 @@
+use crate::config::WebConfig;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use chrono::Utc;
+use ring::hmac;
+
+/// Bot checks of the registration form: a hidden honeypot field people never
+/// fill, and a signed render time so instant posts can be told apart.
+pub(super) struct FormGuard {
+    key: hmac::Key,
+    honeypot: bool,
+    min_fill_seconds: u64,
+}
+
+/// Why a form post looks automated.
+#[derive(Debug, Clone, Copy)]
+pub(super) enum BotSign {
+    HoneypotFilled,
+    /// The render time is missing or its signature does not hold.
+    BadStamp,
+    TooFast,
+}
+
+impl FormGuard {
+    /// Build with a per-process key; forms rendered before a restart fail the
+    /// time check once and come back with a new stamp.
+    pub(super) fn new(config: &WebConfig) -> Self {
+        Self {
+            key: hmac::Key::new(hmac::HMAC_SHA256, &rand::random::<[u8; 32]>()),
+            honeypot: config.web_form_honeypot,
+            min_fill_seconds: config.web_form_min_fill_seconds,
+        }
+    }
+
+    /// Whether the form carries the honeypot field.
+    pub(super) const fn honeypot(&self) -> bool {
+        self.honeypot
+    }
+
+    /// Signed render time for the form; `None` when the time check is off.
+    pub(super) fn stamp(&self) -> Option<String> {
+        if self.min_fill_seconds == 0 {
+            return None;
+        }
+        let now = Utc::now().timestamp().to_string();
+        let tag = hmac::sign(&self.key, now.as_bytes());
+        Some(format!("{now}.{}", BASE64_URL.encode(tag.as_ref())))
+    }
+
+    /// Check a post's honeypot value and render stamp.
+    pub(super) fn check(&self, honeypot: &str, stamp: &str) -> Result<(), BotSign> {
+        if self.honeypot && !honeypot.is_empty() {
+            return Err(BotSign::HoneypotFilled);
+        }
+        if self.min_fill_seconds == 0 {
+            return Ok(());
+        }
+        let rendered = stamp
+            .split_once('.')
+            .filter(|(time, tag)| {
+                BASE64_URL
+                    .decode(tag)
+                    .is_ok_and(|tag| hmac::verify(&self.key, time.as_bytes(), &tag).is_ok())
+            })
+            .and_then(|(time, _)| time.parse::<i64>().ok())
+            .ok_or(BotSign::BadStamp)?;
+        let elapsed = u64::try_from(Utc::now().timestamp() - rendered).unwrap_or(0);
+        if elapsed < self.min_fill_seconds {
+            return Err(BotSign::TooFast);
+        }
+        Ok(())
+    }
+}
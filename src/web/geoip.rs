@@ -0,0 +1,94 @@
+use crate::config::WebGeoIpConfig;
+use maxminddb::{Reader, geoip2};
+use std::net::IpAddr;
+use std::path::Path;
+use tracing::{error, info, warn};
+
+/// What the `MaxMind` databases know about a registrant IP.
+#[derive(Default)]
+pub(super) struct GeoInfo {
+    /// ISO country code, uppercase.
+    pub country: Option<String>,
+    pub asn: Option<u32>,
+    pub as_org: Option<String>,
+}
+
+impl GeoInfo {
+    /// One line for admins, e.g. `DE, AS3320 Deutsche Telekom AG`; `None`
+    /// when nothing is known.
+    pub(super) fn summary(&self) -> Option<String> {
+        let network = self.asn.map(|asn| {
+            self.as_org
+                .as_ref()
+                .map_or_else(|| format!("AS{asn}"), |org| format!("AS{asn} {org}"))
+        });
+        match (&self.country, network) {
+            (Some(country), Some(network)) => Some(format!("{country}, {network}")),
+            (Some(country), None) => Some(country.clone()),
+            (None, network) => network,
+        }
+    }
+}
+
+/// Readers for the databases configured in `[web.geoip]`.
+pub(super) struct GeoIp {
+    country: Option<Reader<Vec<u8>>>,
+    asn: Option<Reader<Vec<u8>>>,
+}
+
+impl GeoIp {
+    /// Open the configured databases; `None` when none of them loads, so
+    /// registrations go on without lookups.
+    pub(super) fn load(config: &WebGeoIpConfig) -> Option<Self> {
+        let geoip = Self {
+            country: config.country_database.as_deref().and_then(open),
+            asn: config.asn_database.as_deref().and_then(open),
+        };
+        (geoip.country.is_some() || geoip.asn.is_some()).then_some(geoip)
+    }
+
+    /// Look `ip` up; private and unknown addresses yield an empty result.
+    pub(super) fn lookup(&self, ip: IpAddr) -> GeoInfo {
+        let mut info = GeoInfo::default();
+        if let Some(reader) = &self.country {
+            match reader
+                .lookup(ip)
+                .and_then(|result| result.decode::<geoip2::Country>())
+            {
+                Ok(record) => {
+                    info.country = record
+                        .and_then(|record| record.country.iso_code)
+                        .map(str::to_ascii_uppercase);
+                }
+                Err(e) => warn!(error = %e, ip = %ip, "GeoIP country lookup failed"),
+            }
+        }
+        if let Some(reader) = &self.asn {
+            match reader
+                .lookup(ip)
+                .and_then(|result| result.decode::<geoip2::Asn>())
+            {
+                Ok(Some(record)) => {
+                    info.asn = record.autonomous_system_number;
+                    info.as_org = record.autonomous_system_organization.map(str::to_string);
+                }
+                Ok(None) => {}
+                Err(e) => warn!(error = %e, ip = %ip, "GeoIP ASN lookup failed"),
+            }
+        }
+        info
+    }
+}
+
+fn open(path: &Path) -> Option<Reader<Vec<u8>>> {
+    match Reader::open_readfile(path) {
+        Ok(reader) => {
+            info!(path = %path.display(), kind = %reader.metadata().database_type, "Loaded GeoIP database");
+            Some(reader)
+        }
+        Err(e) => {
+            error!(error = %e, path = %path.display(), "Failed to open GeoIP database, skipping it");
+            None
+        }
+    }
+}
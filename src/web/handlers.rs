@@ -1,35 +1,63 @@
 use super::WebState;
-use super::templates::{RegisterForm, RegisterTemplate};
-use crate::domain::{Nickname, Password, Username};
-use crate::i18n::t;
+use super::captcha::CaptchaVerifier;
+use super::client_ip::resolve_client_ip;
+use super::download::open_download;
+use super::submit::{
+    self, CreatedAccount, RequestStatus, WebRegisterError, WebRegisterInput, WebRegistration,
+};
+use super::templates::{
+    ClientZipLink, MessageClass, RegisterForm, RegisterQuery, RegisterTemplate,
+};
+use crate::i18n::{t, t_args};
 use crate::services::registration;
-use crate::types::{DownloadTokenType, LanguageCode, RegistrationSource, TTWorkerCommand};
-use axum::body::Body;
-use axum::extract::{ConnectInfo, Form, Path, State};
-use axum::http::{HeaderMap, HeaderValue};
-use axum::response::{IntoResponse, Redirect, Response};
-use chrono::{Duration, Utc};
+use crate::types::{DownloadTokenType, LanguageCode, WebRequestStatus};
+use axum::extract::{ConnectInfo, Form, Path, Query, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::{AppendHeaders, IntoResponse, Redirect, Response};
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::fs::File;
-use tokio_util::io::ReaderStream;
-use tracing::{error, warn};
-use uuid::Uuid;
+use tracing::{info, warn};
 
-/// Render the registration page.
+/// Lifetime of the language cookie: one year.
+const LANG_COOKIE_MAX_AGE: u64 = 365 * 24 * 60 * 60;
+
+/// Render the registration page, or why it needs a (different) invite.
 pub(super) async fn register_page(
     State(state): State<Arc<WebState>>,
+    Query(query): Query<RegisterQuery>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
-    let (lang, language_forced) = resolve_web_lang(&state.config, &headers);
-    let available_languages = state.available_languages.as_ref().clone();
-    RegisterTemplate::new(
-        state.config.teamtalk.server_name.as_str(),
-        &lang,
-        available_languages,
-        language_forced,
-        state.config.database.generated_file_ttl_seconds,
+    let (mut lang, language_forced) = resolve_web_lang(&state.config, &headers);
+    // `?lang=xx` picks the language like the switcher and is remembered.
+    let chosen = if language_forced {
+        None
+    } else {
+        available_language(&state, &query.lang)
+    };
+    if let Some(chosen) = &chosen {
+        lang = chosen.clone();
+    }
+    let mut tpl = base_template(&state, &lang, language_forced);
+    match submit::check_invite(&state, &query.invite).await {
+        Ok(invite) => {
+            let pinned = invite
+                .and_then(|invite| invite.server_id)
+                .is_some_and(|id| state.servers.get(&id).is_some());
+            if pinned {
+                tpl.servers.clear();
+            }
+            tpl.invite = Some(query.invite).filter(|token| !token.is_empty());
+        }
+        Err(e) => {
+            tpl.registration_complete = true;
+            tpl.show_message(e.message(lang.as_str()), MessageClass::Error);
+        }
+    }
+    let cookie = chosen.and_then(|lang| lang_cookie(&state, &lang));
+    (
+        AppendHeaders(cookie.map(|value| (header::SET_COOKIE, value))),
+        with_csrf(&state, &headers, tpl),
     )
 }
 
@@ -39,455 +67,292 @@ pub(super) async fn register_post(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     headers: HeaderMap,
     Form(form): Form<RegisterForm>,
-) -> impl IntoResponse {
+) -> Response {
     let ip = resolve_client_ip(&state, &headers, addr.ip());
     let (lang, language_forced) = resolve_web_lang(&state.config, &headers);
-    if state
-        .db
-        .is_ip_registered(&ip.to_string())
+    let guard = state.form_guard.check(&form.website, &form.form_stamp);
+    let result = if !state.csrf.verify(&headers, &form.csrf_token) {
+        Err(WebRegisterError::FormExpired)
+    } else if let Err(sign) = guard {
+        // Refused without saying why, so bots learn nothing to adapt to.
+        info!(ip = %ip, sign = ?sign, "Refused automated-looking registration post");
+        Err(WebRegisterError::SubmitFailed)
+    } else {
+        submit::register(WebRegisterInput {
+            state: &state,
+            ip,
+            user_agent: user_agent(&headers),
+            lang: lang.as_str(),
+            username: &form.username,
+            password: &form.password,
+            nickname: &form.nickname,
+            server: &form.server,
+            rights_profile: &form.rights_profile,
+            invite: &form.invite,
+            captcha_token: form.captcha_token(),
+        })
         .await
-        .unwrap_or(false)
-    {
-        return error_template(&state, &lang, language_forced, &form, "web-err-ip-limit");
-    }
-
-    let (username, password, nickname) =
-        match parse_registration_form(&state, &lang, language_forced, &form) {
-            Ok(parsed) => parsed,
-            Err(tpl) => return *tpl,
-        };
-
-    let (tx, rx) = tokio::sync::oneshot::channel();
-    let cmd = TTWorkerCommand::CreateAccount {
-        username: username.clone(),
-        password: password.clone(),
-        nickname: nickname.clone(),
-        account_type: crate::types::TTAccountType::Default,
-        source: RegistrationSource::Web(ip),
-        source_info: None,
-        resp: tx,
     };
 
-    if let Err(e) = state.tx_tt.send(cmd) {
-        error!(error = %e, ip = %ip, "Failed to enqueue TeamTalk create command");
-        return error_template(&state, &lang, language_forced, &form, "web-err-timeout");
-    }
-
-    match rx.await {
-        Ok(Ok(true)) => {
-            build_success_template(WebSuccessParams {
-                state: &state,
-                lang: &lang,
-                language_forced,
-                ip,
-                form: &form,
-                username: &username,
-                password: &password,
-                nickname: &nickname,
-            })
-            .await
+    let mut tpl = base_template(&state, &lang, language_forced);
+    let (status, tpl) = match result {
+        Ok(WebRegistration::Created(account)) => {
+            tpl.registration_complete = true;
+            tpl.show_message(t(lang.as_str(), "web-success-title"), MessageClass::Success);
+            show_downloads(&mut tpl, account);
+            (StatusCode::OK, tpl)
         }
-        Ok(Ok(false)) => {
-            warn!("TeamTalk create account returned false");
-            error_template(
-                &state,
-                &lang,
-                language_forced,
-                &form,
-                "web-err-username-taken",
-            )
+        Ok(WebRegistration::Pending { request_key }) => {
+            tpl.registration_complete = true;
+            tpl.show_message(t(lang.as_str(), "web-pending-title"), MessageClass::Info);
+            tpl.status_key = Some(request_key);
+            (StatusCode::OK, tpl)
         }
-        _ => {
-            warn!("TeamTalk create account response failed");
-            error_template(&state, &lang, language_forced, &form, "web-err-timeout")
+        Ok(WebRegistration::Queued { .. }) => {
+            tpl.registration_complete = true;
+            tpl.show_message(t(lang.as_str(), "web-queued-title"), MessageClass::Info);
+            (StatusCode::OK, tpl)
         }
+        Err(e) => {
+            // The form comes back filled in, with the error above it.
+            tpl.show_message(e.message(lang.as_str()), MessageClass::Error);
+            tpl.keep_form(&form);
+            (e.status(), tpl)
+        }
+    };
+    (status, with_csrf(&state, &headers, tpl)).into_response()
+}
+
+/// Show the review state of a web registration awaiting approval.
+pub(super) async fn status_page(
+    State(state): State<Arc<WebState>>,
+    Path(key): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let (lang, language_forced) = resolve_web_lang(&state.config, &headers);
+    let mut tpl = base_template(&state, &lang, language_forced);
+    tpl.registration_complete = true;
+    tpl.return_to = format!("{}/status/{key}", tpl.base_path);
+
+    let RequestStatus {
+        status,
+        reason,
+        account,
+    } = submit::request_status(&state, &key, lang.as_str()).await;
+    let (message_key, class) = match status {
+        Some(WebRequestStatus::Pending) => ("web-status-pending", MessageClass::Info),
+        Some(WebRequestStatus::Approved) => ("web-status-approved", MessageClass::Success),
+        Some(WebRequestStatus::Rejected) => ("web-status-rejected", MessageClass::Error),
+        Some(WebRequestStatus::Failed) => ("web-status-failed", MessageClass::Error),
+        None => ("web-status-not-found", MessageClass::Error),
+    };
+    let mut message = t(lang.as_str(), message_key);
+    if let Some(reason) = reason {
+        let line = t_args(
+            lang.as_str(),
+            "decision-reason",
+            &HashMap::from([("reason".to_string(), reason)]),
+        );
+        message = format!("{message} {line}");
+    }
+    if account.is_some() {
+        message = format!(
+            "{message} {}",
+            t(lang.as_str(), "web-status-downloads-once")
+        );
+    }
+    tpl.show_message(message, class);
+    if status == Some(WebRequestStatus::Pending) {
+        tpl.status_key = Some(key);
     }
+    if let Some(account) = account {
+        show_downloads(&mut tpl, account);
+    }
+    with_csrf(&state, &headers, tpl)
+}
+
+/// Offer the files of a newly created account.
+fn show_downloads(tpl: &mut RegisterTemplate, account: CreatedAccount) {
+    tpl.download_tt_token = Some(account.tt_token);
+    tpl.tt_link_qr = registration::link_qr_svg(&account.tt_link);
+    tpl.tt_link = Some(account.tt_link);
+    tpl.actual_tt_filename_for_user = Some(account.tt_filename);
+    tpl.zip_password_note = account.zip_password_note;
+    tpl.client_zips = account
+        .client_zips
+        .into_iter()
+        .map(|zip| ClientZipLink {
+            token: zip.token,
+            filename: zip.filename,
+            label: zip.label,
+        })
+        .collect();
 }
 
 fn base_template(state: &WebState, lang: &LanguageCode, language_forced: bool) -> RegisterTemplate {
-    RegisterTemplate::new(
+    let mut tpl = RegisterTemplate::new(
         state.config.teamtalk.server_name.as_str(),
         lang,
         state.available_languages.as_ref().clone(),
         language_forced,
         state.config.database.generated_file_ttl_seconds,
-    )
-}
-
-fn error_template(
-    state: &WebState,
-    lang: &LanguageCode,
-    language_forced: bool,
-    form: &RegisterForm,
-    message_key: &str,
-) -> RegisterTemplate {
-    let mut tpl = base_template(state, lang, language_forced);
-    tpl.message = Some(t(lang.as_str(), message_key));
-    tpl.message_class = Some("error".to_string());
-    tpl.message_class_safe = "error".to_string();
-    tpl.username_val.clone_from(&form.username);
-    tpl.nickname_val.clone_from(&form.nickname);
+    );
+    tpl.captcha = state.captcha.as_ref().map(CaptchaVerifier::widget);
+    tpl.base_path = state.config.web.base_path().to_string();
+    tpl.return_to = format!("{}/register", tpl.base_path);
+    if state.servers.has_choice() {
+        tpl.servers = state
+            .servers
+            .all()
+            .iter()
+            .map(|server| (server.id().to_string(), server.label().to_string()))
+            .collect();
+    }
+    tpl.rights_profiles
+        .clone_from(&state.config.teamtalk.requestable_rights_profiles);
     tpl
 }
 
-fn parse_registration_form(
-    state: &WebState,
-    lang: &LanguageCode,
-    language_forced: bool,
-    form: &RegisterForm,
-) -> Result<(Username, Password, Nickname), Box<RegisterTemplate>> {
-    let Some(username) = Username::parse(&form.username) else {
-        return Err(Box::new(error_template(
-            state,
-            lang,
-            language_forced,
-            form,
-            "web-err-username-invalid",
-        )));
+/// Render the page with the visitor's CSRF token and the bot checks in its
+/// forms, setting the cookie when the token is new, through the custom theme
+/// if there is one.
+fn with_csrf(state: &WebState, headers: &HeaderMap, mut tpl: RegisterTemplate) -> Response {
+    let (token, response_headers) = state.csrf.issue(headers);
+    tpl.csrf_token = token;
+    tpl.honeypot = state.form_guard.honeypot();
+    tpl.form_stamp = state.form_guard.stamp();
+    let page = match &state.theme {
+        Some(theme) => theme.render(tpl),
+        None => tpl.into_response(),
     };
-    let Some(password) = Password::parse(&form.password) else {
-        return Err(Box::new(error_template(
-            state,
-            lang,
-            language_forced,
-            form,
-            "web-err-password-invalid",
-        )));
-    };
-    let nickname = if form.nickname.is_empty() {
-        let Some(n) = Nickname::parse(username.as_str()) else {
-            return Err(Box::new(error_template(
-                state,
-                lang,
-                language_forced,
-                form,
-                "web-err-nickname-invalid",
-            )));
-        };
-        n
-    } else {
-        let Some(n) = Nickname::parse(&form.nickname) else {
-            return Err(Box::new(error_template(
-                state,
-                lang,
-                language_forced,
-                form,
-                "web-err-nickname-invalid",
-            )));
-        };
-        n
-    };
-    Ok((username, password, nickname))
-}
-
-struct WebSuccessParams<'a> {
-    state: &'a WebState,
-    lang: &'a LanguageCode,
-    language_forced: bool,
-    ip: std::net::IpAddr,
-    form: &'a RegisterForm,
-    username: &'a Username,
-    password: &'a Password,
-    nickname: &'a Nickname,
+    (response_headers, page).into_response()
 }
 
-struct WebBuildContext<'a> {
-    state: &'a WebState,
-    lang: &'a LanguageCode,
-    language_forced: bool,
-    form: &'a RegisterForm,
+pub(super) fn user_agent(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(axum::http::header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
 }
 
-async fn build_success_template(params: WebSuccessParams<'_>) -> RegisterTemplate {
-    let WebSuccessParams {
-        state,
-        lang,
-        language_forced,
-        ip,
-        form,
-        username,
-        password,
-        nickname,
-    } = params;
-    let ctx = WebBuildContext {
-        state,
-        lang,
-        language_forced,
-        form,
-    };
-    if let Err(e) = state
-        .db
-        .add_registered_ip(&ip.to_string(), Some(username.as_str()))
-        .await
+/// Persist selected language and redirect back to the page it was picked
+/// on; posts without the page's CSRF token only reload it.
+pub(super) async fn set_language_and_reload(
+    State(state): State<Arc<WebState>>,
+    request_headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    let base = state.config.web.base_path();
+    let mut target = form
+        .get("return_to")
+        .filter(|path| is_own_page(base, path))
+        .map_or_else(|| format!("{base}/register"), String::clone);
+    // Invite tokens are generated hex strings; anything else is dropped
+    // rather than put into the redirect.
+    if let Some(invite) = form
+        .get("invite")
+        .filter(|invite| !invite.is_empty() && invite.chars().all(|c| c.is_ascii_alphanumeric()))
     {
-        warn!(error = %e, ip = %ip, "Failed to store registered IP");
+        target = format!("{target}?invite={invite}");
     }
-
-    let temp_dir = match temp_dir_or_error(&ctx) {
-        Ok(dir) => dir,
-        Err(tpl) => return *tpl,
-    };
-
-    let unique_id = Uuid::new_v4().to_string();
-    let assets = registration::build_assets(
-        &state.config,
-        username.as_str(),
-        password.as_str(),
-        nickname.as_str(),
-    );
-    let safe_tt_path = match write_tt_file(&ctx, &temp_dir, &unique_id, &assets).await {
-        Ok(path) => path,
-        Err(tpl) => return tpl,
-    };
-    let expires = build_token_expiry(state);
-    let token_tt = persist_tt_token(state, &safe_tt_path, &assets, expires).await;
-
-    let zip_token =
-        match try_create_zip_token(&ctx, &temp_dir, &unique_id, username, &assets, expires).await {
-            Ok(token) => token,
-            Err(tpl) => return tpl,
-        };
-
-    let mut tpl = base_template(state, lang, language_forced);
-    tpl.registration_complete = true;
-    tpl.message = Some(t(lang.as_str(), "web-success-title"));
-    tpl.message_class = Some("success".to_string());
-    tpl.message_class_safe = "success".to_string();
-    tpl.download_tt_token = Some(token_tt);
-    tpl.tt_link = Some(assets.link);
-    tpl.actual_tt_filename_for_user = Some(assets.filename);
-    if let Some(zt) = zip_token {
-        tpl.download_client_zip_token = Some(zt);
-        tpl.actual_client_zip_filename_for_user = Some(format!("{username}_TeamTalk.zip"));
+    let token = form.get("csrf_token").map_or("", String::as_str);
+    if !state.csrf.verify(&request_headers, token) {
+        return (headers, Redirect::to(&target));
     }
-    tpl
-}
-
-fn temp_dir_or_error(
-    ctx: &WebBuildContext<'_>,
-) -> Result<std::path::PathBuf, Box<RegisterTemplate>> {
-    match std::env::current_dir() {
-        Ok(dir) => Ok(dir.join("temp_files")),
-        Err(e) => {
-            error!(error = %e, "Failed to resolve temp dir");
-            Err(Box::new(error_template(
-                ctx.state,
-                ctx.lang,
-                ctx.language_forced,
-                ctx.form,
-                "web-err-timeout",
-            )))
-        }
+    let lang = form
+        .get("lang_code")
+        .and_then(|code| available_language(&state, code))
+        .unwrap_or_default();
+    if let Some(value) = lang_cookie(&state, &lang) {
+        headers.insert(header::SET_COOKIE, value);
     }
+    (headers, Redirect::to(&target))
 }
 
-async fn write_tt_file(
-    ctx: &WebBuildContext<'_>,
-    temp_dir: &std::path::Path,
-    unique_id: &str,
-    assets: &registration::RegistrationAssets,
-) -> Result<std::path::PathBuf, RegisterTemplate> {
-    let safe_tt_path = temp_dir.join(format!("{unique_id}_{}", assets.filename));
-    let tt_content = assets.content.clone();
-    if let Err(e) = tokio::fs::write(&safe_tt_path, &tt_content).await {
-        error!(error = %e, path = ?safe_tt_path, "Failed to write TT file");
-        return Err(error_template(
-            ctx.state,
-            ctx.lang,
-            ctx.language_forced,
-            ctx.form,
-            "web-err-timeout",
-        ));
-    }
-    Ok(safe_tt_path)
+/// Whether `path` is one of this app's pages, so redirecting there cannot
+/// leave the site.
+fn is_own_page(base: &str, path: &str) -> bool {
+    path.strip_prefix(base)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .is_some_and(|rest| {
+            !rest.is_empty()
+                && !rest.starts_with('/')
+                && rest
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '-' | '_'))
+        })
 }
 
-fn build_token_expiry(state: &WebState) -> chrono::NaiveDateTime {
-    let ttl_seconds = i64::try_from(state.config.database.generated_file_ttl_seconds)
-        .unwrap_or_else(|_| {
-            warn!(
-                ttl = state.config.database.generated_file_ttl_seconds,
-                "generated_file_ttl_seconds too large for i64, clamping"
-            );
-            i64::MAX
-        });
-    Utc::now().naive_utc() + Duration::seconds(ttl_seconds)
+/// `code` if the web UI is translated into it.
+fn available_language(state: &WebState, code: &str) -> Option<LanguageCode> {
+    state
+        .available_languages
+        .iter()
+        .any(|(available, _)| available == code)
+        .then(|| LanguageCode::parse_or_default(code))
 }
 
-async fn persist_tt_token(
-    state: &WebState,
-    safe_tt_path: &std::path::Path,
-    assets: &registration::RegistrationAssets,
-    expires: chrono::NaiveDateTime,
-) -> String {
-    let token_tt = Uuid::new_v4().to_string();
-    let Some(tt_path_name) = safe_tt_path.file_name().and_then(|n| n.to_str()) else {
-        error!(path = ?safe_tt_path, "Invalid TT file name");
-        return token_tt;
+/// `Set-Cookie` remembering the visitor's language for a year.
+fn lang_cookie(state: &WebState, lang: &LanguageCode) -> Option<HeaderValue> {
+    let secure = if state.config.web.web_app_ssl_enabled {
+        "; Secure"
+    } else {
+        ""
     };
-    if let Err(e) = state
-        .db
-        .add_download_token(
-            &token_tt,
-            tt_path_name,
-            &assets.filename,
-            DownloadTokenType::TtConfig,
-            expires,
-        )
-        .await
-    {
-        warn!(error = %e, "Failed to persist download token");
-    }
-    token_tt
-}
-
-async fn try_create_zip_token(
-    ctx: &WebBuildContext<'_>,
-    temp_dir: &std::path::Path,
-    unique_id: &str,
-    username: &Username,
-    assets: &registration::RegistrationAssets,
-    expires: chrono::NaiveDateTime,
-) -> Result<Option<String>, RegisterTemplate> {
-    let zip_name = format!("{username}_TeamTalk.zip");
-    let safe_zip_path = temp_dir.join(format!("{unique_id}_{zip_name}"));
-    if registration::try_create_client_zip_async(&ctx.state.config, &safe_zip_path, assets).await {
-        let z_tok = Uuid::new_v4().to_string();
-        let Some(zip_path_name) = safe_zip_path.file_name().and_then(|n| n.to_str()) else {
-            error!(path = ?safe_zip_path, "Invalid ZIP file name");
-            return Err(error_template(
-                ctx.state,
-                ctx.lang,
-                ctx.language_forced,
-                ctx.form,
-                "web-err-timeout",
-            ));
-        };
-        if let Err(e) = ctx
-            .state
-            .db
-            .add_download_token(
-                &z_tok,
-                zip_path_name,
-                &zip_name,
-                DownloadTokenType::ClientZip,
-                expires,
-            )
-            .await
-        {
-            warn!(error = %e, "Failed to persist ZIP token");
-        }
-        return Ok(Some(z_tok));
-    }
-    Ok(None)
-}
-
-/// Persist selected language and redirect back to the form.
-pub(super) async fn set_language_and_reload(
-    State(_state): State<Arc<WebState>>,
-    Form(form): Form<HashMap<String, String>>,
-) -> impl IntoResponse {
-    let lang = form
-        .get("lang_code")
-        .map(|v| LanguageCode::parse_or_default(v))
-        .unwrap_or_default();
-    let mut headers = HeaderMap::new();
-    match HeaderValue::from_str(&format!("user_web_lang={}; Path=/", lang.as_str())) {
-        Ok(value) => {
-            headers.insert(axum::http::header::SET_COOKIE, value);
-        }
-        Err(e) => {
-            warn!(error = %e, "Failed to build user_web_lang cookie header");
-        }
-    }
-    (headers, Redirect::to("/register"))
+    HeaderValue::from_str(&format!(
+        "user_web_lang={}; Path={}; Max-Age={LANG_COOKIE_MAX_AGE}; SameSite=Lax{secure}",
+        lang.as_str(),
+        state.config.web.cookie_path()
+    ))
+    .inspect_err(|e| warn!(error = %e, "Failed to build user_web_lang cookie header"))
+    .ok()
 }
 
 /// Download handler for generic tokens.
 pub(super) async fn download_handler(
     State(state): State<Arc<WebState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Path(token): Path<String>,
 ) -> Response {
-    if let Ok(Some(tok_data)) = state.db.get_download_token(&token).await {
-        let temp_dir = match std::env::current_dir() {
-            Ok(dir) => dir.join("temp_files"),
-            Err(e) => {
-                error!(error = %e, "Failed to resolve temp dir");
-                return (
-                    axum::http::StatusCode::NOT_FOUND,
-                    t("en", "web-err-invalid-link"),
-                )
-                    .into_response();
-            }
-        };
-        let path = temp_dir.join(&tok_data.filepath_on_server);
-
-        if path.exists() {
-            if let Err(e) = state.db.mark_token_used(&token).await {
-                warn!(error = %e, "Failed to mark token used");
-            }
-
-            let file = match File::open(&path).await {
-                Ok(f) => f,
-                Err(e) => {
-                    error!(error = %e, path = %path.display(), "Failed to open file for download");
-                    return (
-                        axum::http::StatusCode::NOT_FOUND,
-                        t("en", "web-err-file-not-found"),
-                    )
-                        .into_response();
-                }
-            };
-
-            let stream = ReaderStream::new(file);
-            let body = Body::from_stream(stream);
-
-            let mime = mime_guess::from_path(&path).first_or_octet_stream();
-
-            let response = axum::response::Response::builder()
-                .header("Content-Type", mime.as_ref())
-                .header(
-                    "Content-Disposition",
-                    format!("attachment; filename=\"{}\"", tok_data.original_filename),
-                )
-                .body(body);
-            return match response {
-                Ok(resp) => resp,
-                Err(e) => {
-                    error!(error = %e, "Failed to build response");
-                    (
-                        axum::http::StatusCode::NOT_FOUND,
-                        t("en", "web-err-invalid-link"),
-                    )
-                        .into_response()
-                }
-            };
-        }
-    }
-    (
-        axum::http::StatusCode::NOT_FOUND,
-        t("en", "web-err-invalid-link"),
-    )
-        .into_response()
+    download_response(&state, addr, &headers, &token, None).await
 }
 
 /// Download handler for `TeamTalk` `.tt` config files.
 pub(super) async fn download_tt_handler(
     State(state): State<Arc<WebState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Path(token): Path<String>,
 ) -> Response {
-    download_by_type(state, token, DownloadTokenType::TtConfig).await
+    download_response(
+        &state,
+        addr,
+        &headers,
+        &token,
+        Some(DownloadTokenType::TtConfig),
+    )
+    .await
 }
 
 /// Download handler for client ZIP.
 pub(super) async fn download_client_zip_handler(
     State(state): State<Arc<WebState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Path(token): Path<String>,
 ) -> Response {
-    download_by_type(state, token, DownloadTokenType::ClientZip).await
+    download_response(
+        &state,
+        addr,
+        &headers,
+        &token,
+        Some(DownloadTokenType::ClientZip),
+    )
+    .await
 }
 
-fn resolve_web_lang(
+pub(super) fn resolve_web_lang(
     config: &crate::config::AppConfig,
     headers: &HeaderMap,
 ) -> (LanguageCode, bool) {
@@ -509,140 +374,54 @@ fn resolve_web_lang(
         }
     }
 
-    (LanguageCode::default(), false)
+    let preferred = headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| preferred_language(value, &crate::i18n::available_languages()));
+    (preferred.unwrap_or_default(), false)
 }
 
-fn resolve_client_ip(
-    state: &WebState,
-    headers: &HeaderMap,
-    fallback: std::net::IpAddr,
-) -> std::net::IpAddr {
-    if !state.config.web.web_app_proxy_headers {
-        return fallback;
-    }
-
-    let allow = state.config.web.web_app_forwarded_allow_ips.trim();
-    if allow != "*"
-        && !allow
-            .split(',')
-            .map(str::trim)
-            .any(|s| s == fallback.to_string())
-    {
-        return fallback;
-    }
-
-    if let Some(raw) = headers.get("x-forwarded-for") {
-        match raw.to_str() {
-            Ok(value) => {
-                if let Some(first) = value.split(',').next().map(str::trim)
-                    && let Ok(ip) = first.parse()
-                {
-                    return ip;
-                }
-            }
-            Err(e) => {
-                warn!(error = %e, "Invalid x-forwarded-for header encoding");
-            }
-        }
-    }
-
-    if let Some(raw) = headers.get("x-real-ip") {
-        match raw.to_str() {
-            Ok(value) => {
-                if let Ok(ip) = value.parse() {
-                    return ip;
-                }
-            }
-            Err(e) => {
-                warn!(error = %e, "Invalid x-real-ip header encoding");
-            }
-        }
-    }
-
-    fallback
+/// The available language the `Accept-Language` header ranks highest; a
+/// regional tag such as `ru-RU` also matches `ru`.
+fn preferred_language(header: &str, available: &[(String, String)]) -> Option<LanguageCode> {
+    let mut ranges: Vec<(&str, f32)> = header
+        .split(',')
+        .filter_map(|part| {
+            let mut params = part.split(';');
+            let tag = params.next()?.trim();
+            let quality = params
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            (!tag.is_empty() && tag != "*" && quality > 0.0).then_some((tag, quality))
+        })
+        .collect();
+    // Stable, so equally ranked languages keep the order the browser sent.
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranges.into_iter().find_map(|(tag, _)| {
+        let primary = tag.split('-').next().unwrap_or(tag);
+        available
+            .iter()
+            .map(|(code, _)| code.as_str())
+            .find(|code| code.eq_ignore_ascii_case(tag))
+            .or_else(|| {
+                available
+                    .iter()
+                    .map(|(code, _)| code.as_str())
+                    .find(|code| code.eq_ignore_ascii_case(primary))
+            })
+            .and_then(LanguageCode::parse)
+    })
 }
 
-async fn download_by_type(
-    state: Arc<WebState>,
-    token: String,
-    token_type: DownloadTokenType,
+async fn download_response(
+    state: &Arc<WebState>,
+    addr: SocketAddr,
+    headers: &HeaderMap,
+    token: &str,
+    token_type: Option<DownloadTokenType>,
 ) -> Response {
-    if let Ok(Some(tok_data)) = state.db.get_download_token(&token).await {
-        let Ok(stored_type) = DownloadTokenType::try_from(tok_data.token_type.as_str()) else {
-            warn!(
-                token_type = %tok_data.token_type,
-                "Invalid download token type"
-            );
-            return (
-                axum::http::StatusCode::NOT_FOUND,
-                t("en", "web-err-invalid-link"),
-            )
-                .into_response();
-        };
-        if stored_type != token_type {
-            return (
-                axum::http::StatusCode::NOT_FOUND,
-                t("en", "web-err-invalid-link"),
-            )
-                .into_response();
-        }
-        let temp_dir = match std::env::current_dir() {
-            Ok(dir) => dir.join("temp_files"),
-            Err(e) => {
-                error!(error = %e, "Failed to resolve temp dir");
-                return (
-                    axum::http::StatusCode::NOT_FOUND,
-                    t("en", "web-err-invalid-link"),
-                )
-                    .into_response();
-            }
-        };
-        let path = temp_dir.join(&tok_data.filepath_on_server);
-
-        if path.exists() {
-            if let Err(e) = state.db.mark_token_used(&token).await {
-                warn!(error = %e, "Failed to mark token used");
-            }
-
-            let file = match File::open(&path).await {
-                Ok(f) => f,
-                Err(e) => {
-                    error!(error = %e, path = %path.display(), "Failed to open download file");
-                    return (
-                        axum::http::StatusCode::NOT_FOUND,
-                        t("en", "web-err-file-not-found"),
-                    )
-                        .into_response();
-                }
-            };
-
-            let stream = ReaderStream::new(file);
-            let body = Body::from_stream(stream);
-            let mime = mime_guess::from_path(&path).first_or_octet_stream();
-
-            let response = axum::response::Response::builder()
-                .header("Content-Type", mime.as_ref())
-                .header(
-                    "Content-Disposition",
-                    format!("attachment; filename=\"{}\"", tok_data.original_filename),
-                )
-                .body(body);
-            return match response {
-                Ok(resp) => resp,
-                Err(e) => {
-                    error!(error = %e, "Failed to build response");
-                    (
-                        axum::http::StatusCode::NOT_FOUND,
-                        t("en", "web-err-invalid-link"),
-                    )
-                        .into_response()
-                }
-            };
-        }
-    }
-    (
-        axum::http::StatusCode::NOT_FOUND,
-        t("en", "web-err-invalid-link"),
-    )
-        .into_response()
+    let ip = resolve_client_ip(state, headers, addr.ip());
+    open_download(state, ip, token, token_type, headers.get(header::RANGE))
+        .await
+        .unwrap_or_else(|e| (e.status(), t("en", e.key())).into_response())
 }
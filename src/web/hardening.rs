@@ -0,0 +1,72 @@
+use crate::config::WebConfig;
+use axum::Router;
+use axum::body::Body;
+use axum::extract::MatchedPath;
+use axum::http::{HeaderValue, Request, header};
+use tower_http::LatencyUnit;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::set_header::SetResponseHeaderLayer;
+use tower_http::trace::{DefaultOnResponse, TraceLayer};
+use tracing::Level;
+
+/// Add the `[web.hardening]` response headers and request logging to `app`.
+pub(super) fn apply<S>(mut app: Router<S>, config: &WebConfig) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let hardening = &config.sections.hardening;
+    if hardening.security_headers {
+        let mut headers = vec![
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            (
+                header::CONTENT_SECURITY_POLICY,
+                hardening.content_security_policy.clone(),
+            ),
+            (header::REFERRER_POLICY, hardening.referrer_policy.clone()),
+        ];
+        if config.web_app_ssl_enabled && hardening.hsts_max_age_seconds > 0 {
+            headers.push((
+                header::STRICT_TRANSPORT_SECURITY,
+                format!("max-age={}", hardening.hsts_max_age_seconds),
+            ));
+        }
+        for (name, value) in headers {
+            // Values are checked when the config is loaded; an empty one
+            // leaves its header out.
+            if let Ok(value) = HeaderValue::from_str(&value)
+                && !value.is_empty()
+            {
+                app = app.layer(SetResponseHeaderLayer::if_not_present(name, value));
+            }
+        }
+    }
+    if hardening.request_tracing {
+        app = app
+            .layer(PropagateRequestIdLayer::x_request_id())
+            .layer(
+                TraceLayer::new_for_http()
+                    .make_span_with(request_span)
+                    .on_response(
+                        DefaultOnResponse::new()
+                            .level(Level::INFO)
+                            .latency_unit(LatencyUnit::Millis),
+                    ),
+            )
+            .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid));
+    }
+    app
+}
+
+fn request_span(request: &Request<Body>) -> tracing::Span {
+    // The route pattern keeps download and status tokens out of the logs.
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or("-", MatchedPath::as_str);
+    let request_id = request
+        .headers()
+        .get("x-request-id")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("-");
+    tracing::info_span!("http", method = %request.method(), route, request_id)
+}
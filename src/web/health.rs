@@ -0,0 +1,107 @@
+use super::WebState;
+use crate::tt::TTServer;
+use crate::types::{TTStatus, TTWorkerCommand};
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use futures_util::future::join_all;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// How long each worker gets to report its status.
+const STATUS_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Serialize)]
+struct HealthReport {
+    status: &'static str,
+    database: DatabaseHealth,
+    /// One entry per configured server, the top-level one first.
+    teamtalk: Vec<TeamTalkHealth>,
+}
+
+#[derive(Serialize)]
+struct DatabaseHealth {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+struct TeamTalkHealth {
+    server: String,
+    ok: bool,
+    #[serde(flatten)]
+    status: Option<TTStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'static str>,
+    queues: QueueHealth,
+}
+
+#[derive(Serialize)]
+struct QueueHealth {
+    tt_commands: usize,
+    tt_commands_capacity: usize,
+}
+
+/// Report database and `TeamTalk` health; `503` unless the database and every
+/// server are usable. Failure details only go to the log.
+pub(super) async fn healthz(State(state): State<Arc<WebState>>) -> Response {
+    let database = match state.db.ping().await {
+        Ok(()) => DatabaseHealth {
+            ok: true,
+            error: None,
+        },
+        Err(e) => {
+            warn!(error = %e, "Health check: database unreachable");
+            DatabaseHealth {
+                ok: false,
+                error: Some("unreachable"),
+            }
+        }
+    };
+    let teamtalk = join_all(state.servers.all().iter().map(server_health)).await;
+    let healthy = database.ok && teamtalk.iter().all(|server| server.ok);
+    let report = HealthReport {
+        status: if healthy { "ok" } else { "unavailable" },
+        database,
+        teamtalk,
+    };
+    let code = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (code, Json(report)).into_response()
+}
+
+async fn server_health(server: &TTServer) -> TeamTalkHealth {
+    let tx_tt = &server.tx_tt;
+    let request = tx_tt.request(|resp| TTWorkerCommand::GetStatus { resp });
+    let (status, error) = match tokio::time::timeout(STATUS_TIMEOUT, request).await {
+        Ok(Ok(status)) => (Some(status), None),
+        Ok(Err(e)) => {
+            warn!(server = server.id(), error = %e, "Health check: TeamTalk worker did not answer");
+            (None, Some("unreachable"))
+        }
+        Err(_) => {
+            warn!(
+                server = server.id(),
+                "Health check: TeamTalk worker status timed out"
+            );
+            (None, Some("timeout"))
+        }
+    };
+    TeamTalkHealth {
+        server: server.id().to_string(),
+        ok: status.as_ref().is_some_and(|status| status.logged_in),
+        status,
+        error,
+        queues: QueueHealth {
+            tt_commands: tx_tt.queue_len(),
+            tt_commands_capacity: tx_tt.queue_capacity(),
+        },
+    }
+}
@@ -1,40 +1,136 @@
-use crate::config::AppConfig;
+use crate::config::{AppConfig, WebAcmeConfig};
 use crate::db::Database;
-use crate::types::TTWorkerCommand;
-use axum::Router;
+use crate::tt::{TTServers, TTWorkerHandle};
 use axum::routing::{get, post};
+use axum::{Router, middleware};
 use axum_server::tls_rustls::RustlsConfig;
+use futures_util::StreamExt;
+use rustls_acme::AcmeConfig;
+use rustls_acme::caches::DirCache;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use std::sync::mpsc::Sender;
+use teloxide::Bot;
 use tokio::net::TcpListener;
+use tower_http::services::ServeDir;
 use tracing::{error, info, warn};
 
+mod admin;
+mod admin_auth;
+mod api;
+mod captcha;
+mod client_ip;
+mod cors;
+mod csrf;
+mod download;
+mod download_guard;
+mod form_guard;
+mod geoip;
 mod handlers;
+mod hardening;
+mod health;
+mod rate_limit;
+mod signed_links;
+mod submit;
 mod templates;
+mod theme;
+mod tls_reload;
+#[cfg(unix)]
+mod uds;
+
+pub use admin_auth::hash_password;
 
 struct WebState {
     config: AppConfig,
+    bot: Bot,
     db: Database,
-    tx_tt: Sender<TTWorkerCommand>,
+    /// Link to the top-level server, used by the admin pages and health check.
+    tx_tt: TTWorkerHandle,
+    servers: TTServers,
     available_languages: Arc<Vec<(String, String)>>,
+    rate_limiter: rate_limit::RateLimiter,
+    trusted_proxies: client_ip::TrustedProxies,
+    captcha: Option<captcha::CaptchaVerifier>,
+    signed_links: signed_links::SignedLinks,
+    download_guard: download_guard::DownloadGuard,
+    csrf: csrf::CsrfTokens,
+    form_guard: form_guard::FormGuard,
+    /// Set when the admin dashboard is enabled.
+    admin_auth: Option<admin_auth::AdminAuth>,
+    /// Custom registration page from `web_templates_dir`.
+    theme: Option<theme::Theme>,
+    /// Set when `[web.geoip]` databases are loaded.
+    geoip: Option<geoip::GeoIp>,
 }
 
-/// Run the web server for public registration endpoints.
+/// Run the web server for public registration endpoints, merging in the
+/// Telegram webhook route when it shares this server.
 pub async fn run_server(
     config: AppConfig,
+    bot: Bot,
     db: Database,
-    tx_tt: Sender<TTWorkerCommand>,
+    servers: TTServers,
     shutdown: tokio_util::sync::CancellationToken,
+    webhook: Option<Router>,
 ) {
+    let rate_limiter = rate_limit::RateLimiter::new(&config.web);
+    rate_limiter.restore(&db).await;
     let state = Arc::new(WebState {
         config: config.clone(),
+        bot,
         db,
-        tx_tt,
+        tx_tt: servers.primary().tx_tt.clone(),
+        servers,
         available_languages: crate::i18n::available_languages(),
+        rate_limiter,
+        trusted_proxies: client_ip::TrustedProxies::parse(&config.web.web_app_forwarded_allow_ips),
+        captcha: config
+            .web
+            .sections
+            .captcha
+            .clone()
+            .map(captcha::CaptchaVerifier::new),
+        signed_links: signed_links::SignedLinks::new(&config.web),
+        download_guard: download_guard::DownloadGuard::new(&config.web),
+        csrf: csrf::CsrfTokens::new(&config.web),
+        form_guard: form_guard::FormGuard::new(&config.web),
+        admin_auth: config
+            .web
+            .sections
+            .admin
+            .as_ref()
+            .map(admin_auth::AdminAuth::new),
+        theme: config
+            .web
+            .web_templates_dir
+            .as_deref()
+            .and_then(theme::Theme::load),
+        geoip: config
+            .web
+            .sections
+            .geoip
+            .as_ref()
+            .and_then(geoip::GeoIp::load),
     });
 
-    let app = build_router(state, &config.web.root_path);
+    let mut app = build_router(state, config.web.base_path());
+    if let Some(webhook) = webhook {
+        app = app.merge(webhook);
+    }
+
+    #[cfg(unix)]
+    if let Some(path) = &config.web.web_app_uds_path {
+        info!(path = %path, "Web server listening on Unix socket");
+        if !config.web.web_app_proxy_headers {
+            warn!(
+                "web_app_proxy_headers is off: every client on the Unix socket is seen as 127.0.0.1, so per-IP limits and IP bans apply to all of them at once"
+            );
+        }
+        let mode = config.web.uds_mode().unwrap_or(0o660);
+        if let Err(e) = uds::serve(std::path::Path::new(path), mode, app, shutdown).await {
+            error!(error = %e, "HTTP server failed");
+        }
+        return;
+    }
 
     let addr_str = format!("{}:{}", config.web.web_app_host, config.web.web_app_port);
     info!(addr = %addr_str, "Web server listening");
@@ -60,27 +156,79 @@ pub async fn run_server(
 }
 
 fn build_router(state: Arc<WebState>, root_path: &str) -> Router {
-    let app = Router::new()
-        .route(
-            "/register",
-            get(handlers::register_page).post(handlers::register_post),
-        )
-        .route(
-            "/set_lang_and_reload",
-            post(handlers::set_language_and_reload),
-        )
-        .route("/download/{token}", get(handlers::download_handler))
-        .route("/download_tt/{token}", get(handlers::download_tt_handler))
-        .route(
-            "/download_client_zip/{token}",
-            get(handlers::download_client_zip_handler),
-        )
-        .with_state(state);
+    let mut app = Router::new();
+    if state.config.web.web_registration_enabled {
+        app = app
+            .route(
+                "/register",
+                get(handlers::register_page).post(handlers::register_post),
+            )
+            .route(
+                "/set_lang_and_reload",
+                post(handlers::set_language_and_reload),
+            )
+            .route("/status/{key}", get(handlers::status_page))
+            .route("/download/{token}", get(handlers::download_handler))
+            .route("/download_tt/{token}", get(handlers::download_tt_handler))
+            .route(
+                "/download_client_zip/{token}",
+                get(handlers::download_client_zip_handler),
+            );
+        let mut api_routes = Router::new()
+            .route("/api/v1/register", post(api::register))
+            .route("/api/v1/username_available", get(api::username_available))
+            .route("/api/v1/status/{key}", get(api::status))
+            .route("/api/v1/download/{token}", get(api::download));
+        if let Some(cors) = &state.config.web.sections.cors {
+            api_routes = api_routes.layer(cors::layer(cors));
+        }
+        app = app.merge(api_routes);
+    }
+    if state.config.web.sections.admin.is_some() {
+        let admin_routes = Router::new()
+            .route("/", get(admin::index))
+            .route("/registrations", get(admin::registrations))
+            .route(
+                "/registrations/{tg_id}/delete",
+                post(admin::delete_registration),
+            )
+            .route("/bans", get(admin::bans).post(admin::ban))
+            .route("/bans/{tg_id}/unban", post(admin::unban))
+            .route("/bans/add", post(admin::ban_entry))
+            .route("/bans/entry/{id}/remove", post(admin::unban_entry))
+            .route("/pending", get(admin::pending))
+            .route("/tokens", get(admin::tokens))
+            .route("/tokens/revoke", post(admin::revoke_link))
+            .route("/teamtalk", get(admin::teamtalk))
+            .route("/teamtalk/delete", post(admin::delete_account))
+            .route("/audit", get(admin::audit))
+            .route("/logout", post(admin_auth::logout))
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                admin_auth::require_admin,
+            ))
+            .route(
+                "/login",
+                get(admin_auth::login_page).post(admin_auth::login),
+            );
+        app = app.nest("/admin", admin_routes);
+    }
+    if state.rate_limiter.is_enabled() {
+        app = app.layer(middleware::from_fn_with_state(
+            state.clone(),
+            rate_limit::limit_requests,
+        ));
+    }
+    if let Some(dir) = &state.config.web.web_static_dir {
+        app = app.nest_service("/static", ServeDir::new(dir));
+    }
+    let app = app.route("/healthz", get(health::healthz));
+    let app = hardening::apply(app, &state.config.web).with_state(state);
 
-    if !root_path.is_empty() && root_path != "/" {
-        Router::new().nest(root_path, app)
-    } else {
+    if root_path.is_empty() {
         app
+    } else {
+        Router::new().nest(root_path, app)
     }
 }
 
@@ -112,6 +260,9 @@ async fn serve_https(
     app: Router,
     shutdown: tokio_util::sync::CancellationToken,
 ) -> anyhow::Result<()> {
+    if let Some(acme) = &config.web.sections.acme {
+        return serve_acme(acme, addr, app, shutdown).await;
+    }
     let cert_path = config.web.web_app_ssl_cert_path.clone().unwrap_or_default();
     let key_path = config.web.web_app_ssl_key_path.clone().unwrap_or_default();
     if cert_path.is_empty() || key_path.is_empty() {
@@ -123,7 +274,7 @@ async fn serve_https(
         return serve_http(addr_str, app, shutdown).await;
     }
 
-    let tls_config = match RustlsConfig::from_pem_file(cert_path, key_path).await {
+    let tls_config = match RustlsConfig::from_pem_file(&cert_path, &key_path).await {
         Ok(cfg) => cfg,
         Err(e) => {
             warn!(error = %e, "Failed to load TLS config. Falling back to HTTP");
@@ -131,6 +282,11 @@ async fn serve_https(
         }
     };
 
+    tokio::spawn(
+        tls_reload::CertReloader::new(tls_config.clone(), cert_path, key_path)
+            .run(config.web.web_app_ssl_reload_seconds, shutdown.clone()),
+    );
+
     let shutdown_wait = shutdown.clone();
     tokio::select! {
         res = axum_server::bind_rustls(addr, tls_config)
@@ -140,3 +296,69 @@ async fn serve_https(
         () = shutdown_wait.cancelled() => Ok(()),
     }
 }
+
+/// Serve HTTPS with certificates obtained and renewed over ACME.
+async fn serve_acme(
+    acme: &WebAcmeConfig,
+    addr: SocketAddr,
+    app: Router,
+    shutdown: tokio_util::sync::CancellationToken,
+) -> anyhow::Result<()> {
+    tokio::fs::create_dir_all(&acme.cache_dir)
+        .await
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to create ACME cache dir {}: {e}",
+                acme.cache_dir.display()
+            )
+        })?;
+    let domains: Vec<&str> = acme
+        .domains
+        .iter()
+        .map(|domain| domain.trim())
+        .filter(|domain| !domain.is_empty())
+        .collect();
+    let acme_config = AcmeConfig::new(&domains)
+        .contact(
+            acme.contact_email
+                .iter()
+                .map(|email| format!("mailto:{email}")),
+        )
+        .cache(DirCache::new(acme.cache_dir.clone()));
+    let acme_config = match &acme.directory_url {
+        Some(url) => acme_config.directory(url),
+        None => acme_config.directory_lets_encrypt(!acme.staging),
+    };
+    let mut state = acme_config.state();
+    let acceptor = state.axum_acceptor(state.default_rustls_config());
+    info!(domains = ?domains, staging = acme.staging, "Serving HTTPS with ACME certificates");
+
+    let events = tokio::spawn(async move {
+        while let Some(event) = state.next().await {
+            match event {
+                Ok(event) => info!(event = ?event, "ACME event"),
+                Err(e) => error!(error = ?e, "ACME certificate renewal failed"),
+            }
+        }
+    });
+
+    let shutdown_wait = shutdown.clone();
+    let result = tokio::select! {
+        res = axum_server::bind(addr)
+            .acceptor(acceptor)
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>()) => {
+            res.map_err(|e| anyhow::anyhow!("HTTPS server failed: {e}"))
+        }
+        () = shutdown_wait.cancelled() => Ok(()),
+    };
+    events.abort();
+    result
+}
+
+/// Compare secrets without leaking where they first differ.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,202 @@
+use super::WebState;
+use super::client_ip::resolve_client_ip;
+use super::handlers::resolve_web_lang;
+use crate::config::WebConfig;
+use crate::db::Database;
+use crate::i18n::t;
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{HeaderValue, Method, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{debug, error, warn};
+
+const WINDOW: Duration = Duration::from_mins(1);
+const MAX_TRACKED_WINDOWS: usize = 10_000;
+
+/// Fixed-window request limiter keyed by client IP and subnet.
+pub(super) struct RateLimiter {
+    per_ip: u32,
+    per_subnet: u32,
+    block_for: Duration,
+    inner: Mutex<LimiterState>,
+}
+
+#[derive(Default)]
+struct LimiterState {
+    windows: HashMap<String, Window>,
+    blocked: HashMap<String, Instant>,
+}
+
+struct Window {
+    started: Instant,
+    count: u32,
+}
+
+enum Decision {
+    Allowed,
+    Limited {
+        subject: String,
+        retry_after: Duration,
+        newly_blocked: bool,
+    },
+}
+
+impl RateLimiter {
+    /// Build a limiter from web settings.
+    pub(super) fn new(config: &WebConfig) -> Self {
+        Self {
+            per_ip: config.web_rate_limit_per_minute,
+            per_subnet: config.web_rate_limit_subnet_per_minute,
+            block_for: Duration::from_secs(config.web_rate_limit_block_seconds),
+            inner: Mutex::new(LimiterState::default()),
+        }
+    }
+
+    /// Whether any limit is configured.
+    pub(super) const fn is_enabled(&self) -> bool {
+        self.per_ip > 0 || self.per_subnet > 0
+    }
+
+    /// Load blocks that are still active from the database.
+    pub(super) async fn restore(&self, db: &Database) {
+        let rows = match db.get_active_rate_limit_blocks().await {
+            Ok(rows) => rows,
+            Err(e) => {
+                error!(error = %e, "Failed to load rate limit blocks");
+                return;
+            }
+        };
+        let now_utc = chrono::Utc::now().naive_utc();
+        let now = Instant::now();
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        for row in rows {
+            if let Ok(remaining) = (row.blocked_until - now_utc).to_std() {
+                inner.blocked.insert(row.subject, now + remaining);
+            }
+        }
+        debug!(count = inner.blocked.len(), "Restored rate limit blocks");
+    }
+
+    fn check(&self, ip: IpAddr) -> Decision {
+        let now = Instant::now();
+        let Ok(mut inner) = self.inner.lock() else {
+            return Decision::Allowed;
+        };
+        let subjects = [
+            (format!("ip:{ip}"), self.per_ip),
+            (format!("net:{}", subnet_of(ip)), self.per_subnet),
+        ];
+
+        for (subject, _) in &subjects {
+            if let Some(&until) = inner.blocked.get(subject) {
+                if until > now {
+                    return Decision::Limited {
+                        subject: subject.clone(),
+                        retry_after: until - now,
+                        newly_blocked: false,
+                    };
+                }
+                inner.blocked.remove(subject);
+            }
+        }
+
+        if inner.windows.len() > MAX_TRACKED_WINDOWS {
+            inner
+                .windows
+                .retain(|_, w| now.duration_since(w.started) < WINDOW);
+        }
+
+        for (subject, limit) in subjects {
+            if limit == 0 {
+                continue;
+            }
+            let window = inner.windows.entry(subject.clone()).or_insert(Window {
+                started: now,
+                count: 0,
+            });
+            if now.duration_since(window.started) >= WINDOW {
+                window.started = now;
+                window.count = 0;
+            }
+            window.count = window.count.saturating_add(1);
+            if window.count > limit {
+                inner.windows.remove(&subject);
+                inner.blocked.insert(subject.clone(), now + self.block_for);
+                return Decision::Limited {
+                    subject,
+                    retry_after: self.block_for,
+                    newly_blocked: true,
+                };
+            }
+        }
+        Decision::Allowed
+    }
+}
+
+/// Group an address into its /24 (IPv4) or /64 (IPv6) network.
+fn subnet_of(ip: IpAddr) -> String {
+    match ip.to_canonical() {
+        IpAddr::V4(v4) => {
+            let [a, b, c, _] = v4.octets();
+            format!("{a}.{b}.{c}.0/24")
+        }
+        IpAddr::V6(v6) => {
+            let s = v6.segments();
+            format!("{}/64", Ipv6Addr::new(s[0], s[1], s[2], s[3], 0, 0, 0, 0))
+        }
+    }
+}
+
+/// Reject form and API posts from clients that exceeded the configured rate
+/// limits. Page views, static assets and downloads are not counted; downloads
+/// have their own guard against guessed links.
+pub(super) async fn limit_requests(
+    State(state): State<Arc<WebState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if req.method() != Method::POST {
+        return next.run(req).await;
+    }
+    let ip = resolve_client_ip(&state, req.headers(), addr.ip());
+    let (lang, _) = resolve_web_lang(&state.config, req.headers());
+    match state.rate_limiter.check(ip) {
+        Decision::Allowed => next.run(req).await,
+        Decision::Limited {
+            subject,
+            retry_after,
+            newly_blocked,
+        } => {
+            if newly_blocked {
+                warn!(subject = %subject, ip = %ip, "Rate limit exceeded, blocking client");
+                let block = chrono::Duration::from_std(retry_after).unwrap_or_default();
+                let blocked_until = chrono::Utc::now().naive_utc() + block;
+                if let Err(e) = state
+                    .db
+                    .record_rate_limit_offender(&subject, blocked_until)
+                    .await
+                {
+                    error!(error = %e, "Failed to persist rate limit offender");
+                }
+            }
+            let mut response = (
+                StatusCode::TOO_MANY_REQUESTS,
+                t(lang.as_str(), "web-err-rate-limited"),
+            )
+                .into_response();
+            response.headers_mut().insert(
+                header::RETRY_AFTER,
+                HeaderValue::from(retry_after.as_secs().max(1)),
+            );
+            response
+        }
+    }
+}
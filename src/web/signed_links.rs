@@ -0,0 +1,116 @@
+use crate::config::{DownloadLinkMode, WebConfig};
+use crate::types::DownloadTokenType;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Prefix that tells signed tokens apart from database tokens.
+const SIGNED_PREFIX: &str = "s.";
+
+/// What a signed download link grants access to.
+#[derive(Serialize, Deserialize)]
+pub(super) struct SignedDownload {
+    /// File name inside the temp directory.
+    #[serde(rename = "f")]
+    pub file: String,
+    /// Name offered to the browser.
+    #[serde(rename = "n")]
+    pub original_name: String,
+    /// Stored form of the download token type.
+    #[serde(rename = "t")]
+    pub token_type: String,
+    /// Expiry as a Unix timestamp.
+    #[serde(rename = "e")]
+    pub expires: i64,
+    /// Downloads allowed; absent when the link only expires by time.
+    #[serde(rename = "u", default, skip_serializing_if = "Option::is_none")]
+    pub max_uses: Option<u32>,
+    /// The token's signature, which identifies it in `signed_download_links`.
+    #[serde(skip)]
+    pub id: String,
+}
+
+impl SignedDownload {
+    /// Expiry as a timestamp, for the uses and revocations stored until then.
+    pub(super) fn expires_at(&self) -> NaiveDateTime {
+        DateTime::from_timestamp(self.expires, 0)
+            .unwrap_or_default()
+            .naive_utc()
+    }
+}
+
+/// Issues and verifies HMAC-signed download tokens that need no database row.
+///
+/// Uses of limited tokens and revocations are kept in the database's
+/// `signed_download_links` until the tokens expire; checking them is left to
+/// the caller, as only the signature and expiry are verified here.
+pub(super) struct SignedLinks {
+    key: hmac::Key,
+}
+
+impl SignedLinks {
+    /// Build from `web_download_signing_key`, or a per-process random key.
+    pub(super) fn new(config: &WebConfig) -> Self {
+        let secret = config.web_download_signing_key.as_ref().map_or_else(
+            || {
+                if config.web_download_links == DownloadLinkMode::Signed {
+                    warn!(
+                        "web_download_signing_key is not set; signed download links stop working after a restart"
+                    );
+                }
+                rand::random::<[u8; 32]>().to_vec()
+            },
+            |secret| secret.as_bytes().to_vec(),
+        );
+        Self {
+            key: hmac::Key::new(hmac::HMAC_SHA256, &secret),
+        }
+    }
+
+    /// Whether `token` looks like a signed token rather than a database one.
+    pub(super) fn is_signed(token: &str) -> bool {
+        token.starts_with(SIGNED_PREFIX)
+    }
+
+    /// Sign a link to `file` that stays valid until `expires_at`.
+    pub(super) fn issue(
+        &self,
+        file: &str,
+        original_name: &str,
+        token_type: DownloadTokenType,
+        expires_at: NaiveDateTime,
+        max_uses: Option<u32>,
+    ) -> anyhow::Result<String> {
+        let payload = serde_json::to_vec(&SignedDownload {
+            file: file.to_string(),
+            original_name: original_name.to_string(),
+            token_type: token_type.as_str().to_string(),
+            expires: expires_at.and_utc().timestamp(),
+            max_uses,
+            id: String::new(),
+        })?;
+        let payload = BASE64_URL.encode(payload);
+        let tag = hmac::sign(&self.key, payload.as_bytes());
+        Ok(format!(
+            "{SIGNED_PREFIX}{payload}.{}",
+            BASE64_URL.encode(tag.as_ref())
+        ))
+    }
+
+    /// Check the signature and expiry of `token`.
+    pub(super) fn verify(&self, token: &str) -> Option<SignedDownload> {
+        let (payload, encoded_tag) = token.strip_prefix(SIGNED_PREFIX)?.split_once('.')?;
+        let tag = BASE64_URL.decode(encoded_tag).ok()?;
+        hmac::verify(&self.key, payload.as_bytes(), &tag).ok()?;
+        let mut download: SignedDownload =
+            serde_json::from_slice(&BASE64_URL.decode(payload).ok()?).ok()?;
+        if download.expires <= Utc::now().timestamp() {
+            return None;
+        }
+        download.id = encoded_tag.to_string();
+        Some(download)
+    }
+}
@@ -0,0 +1,829 @@
+use super::WebState;
+use super::geoip::GeoInfo;
+use crate::config::{AppConfig, ClientTemplate, DEFAULT_RIGHTS_PROFILE, DownloadLinkMode};
+use crate::db::schema::{DeeplinkToken, PendingWebRegistration};
+use crate::db::{NewDownloadToken, NewPendingWebRegistration};
+use crate::domain::{Nickname, Password, PasswordViolation, Username, UsernameViolation};
+use crate::i18n::{t, t_args};
+use crate::services::audit::{self, Actor, AuditAction};
+use crate::services::{bans, registration};
+use crate::tg_bot::handlers::{WebApprovalRequest, notify_admins_web_request};
+use crate::tt::TTServer;
+use crate::types::{
+    DownloadTokenType, LanguageCode, RegistrationSource, SourceInfo, SourceKind, TTAccountType,
+    TTCommandError, TTWorkerCommand, WebRequestStatus,
+};
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Reasons a web registration attempt can be refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum WebRegisterError {
+    /// The form post lacked the CSRF token of its page.
+    FormExpired,
+    InviteRequired,
+    InviteInvalid,
+    CaptchaFailed,
+    IpLimit,
+    /// The username or the client address is banned.
+    Banned,
+    UsernameInvalid,
+    UsernamePolicy(UsernameViolation),
+    PasswordInvalid,
+    PasswordPolicy(PasswordViolation),
+    NicknameInvalid,
+    UnknownServer,
+    RightsProfileInvalid,
+    UsernameTaken,
+    Unavailable,
+    SubmitFailed,
+    Internal,
+}
+
+impl WebRegisterError {
+    /// Translation key of the user-facing message.
+    pub(super) const fn message_key(self) -> &'static str {
+        match self {
+            Self::FormExpired => "web-err-form-expired",
+            Self::InviteRequired => "web-err-invite-required",
+            Self::InviteInvalid => "web-err-invite-invalid",
+            Self::CaptchaFailed => "web-err-captcha",
+            Self::IpLimit => "web-err-ip-limit",
+            Self::Banned => "web-err-banned",
+            Self::UsernameInvalid => "web-err-username-invalid",
+            Self::UsernamePolicy(violation) => violation.message_key(),
+            Self::PasswordInvalid => "web-err-password-invalid",
+            Self::PasswordPolicy(violation) => violation.message_key(),
+            Self::NicknameInvalid => "web-err-nickname-invalid",
+            Self::UnknownServer => "web-err-server-invalid",
+            Self::RightsProfileInvalid => "web-err-rights-profile-invalid",
+            Self::UsernameTaken => "web-err-username-taken",
+            Self::SubmitFailed => "web-err-submit",
+            Self::Unavailable | Self::Internal => "web-err-timeout",
+        }
+    }
+
+    /// Localized user-facing message, including policy details where relevant.
+    pub(super) fn message(self, lang: &str) -> String {
+        match self {
+            Self::UsernamePolicy(violation) => {
+                t_args(lang, violation.message_key(), &violation.message_args())
+            }
+            Self::PasswordPolicy(violation) => {
+                t_args(lang, violation.message_key(), &violation.message_args())
+            }
+            _ => t(lang, self.message_key()),
+        }
+    }
+
+    /// HTTP status of the form page or API response reporting the error.
+    pub(super) const fn status(self) -> StatusCode {
+        match self {
+            Self::IpLimit
+            | Self::Banned
+            | Self::FormExpired
+            | Self::InviteRequired
+            | Self::InviteInvalid => StatusCode::FORBIDDEN,
+            Self::CaptchaFailed => StatusCode::BAD_REQUEST,
+            Self::UsernameInvalid
+            | Self::UsernamePolicy(_)
+            | Self::PasswordInvalid
+            | Self::PasswordPolicy(_)
+            | Self::NicknameInvalid
+            | Self::UnknownServer
+            | Self::RightsProfileInvalid => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::UsernameTaken => StatusCode::CONFLICT,
+            Self::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            Self::SubmitFailed | Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Stable machine-readable error code.
+    pub(super) const fn code(self) -> &'static str {
+        match self {
+            Self::FormExpired => "form_expired",
+            Self::InviteRequired => "invite_required",
+            Self::InviteInvalid => "invite_invalid",
+            Self::CaptchaFailed => "captcha_failed",
+            Self::IpLimit => "ip_limit",
+            Self::Banned => "banned",
+            Self::UsernameInvalid => "username_invalid",
+            Self::UsernamePolicy(_) => "username_policy",
+            Self::PasswordInvalid => "password_invalid",
+            Self::PasswordPolicy(_) => "password_policy",
+            Self::NicknameInvalid => "nickname_invalid",
+            Self::UnknownServer => "unknown_server",
+            Self::RightsProfileInvalid => "rights_profile_invalid",
+            Self::UsernameTaken => "username_taken",
+            Self::Unavailable => "teamtalk_unavailable",
+            Self::SubmitFailed => "submit_failed",
+            Self::Internal => "internal_error",
+        }
+    }
+}
+
+/// Raw registration input collected by the HTML form or the JSON API.
+pub(super) struct WebRegisterInput<'a> {
+    pub state: &'a WebState,
+    pub ip: IpAddr,
+    pub user_agent: Option<&'a str>,
+    pub lang: &'a str,
+    pub username: &'a str,
+    pub password: &'a str,
+    pub nickname: &'a str,
+    /// Id of the chosen server; empty for the top-level one.
+    pub server: &'a str,
+    /// Requested rights profile; empty for the default one.
+    pub rights_profile: &'a str,
+    /// Invite token; empty when registering without one.
+    pub invite: &'a str,
+    pub captcha_token: &'a str,
+}
+
+/// Successful outcome of a web registration.
+pub(super) enum WebRegistration {
+    /// The account exists and its files are ready for download.
+    Created(CreatedAccount),
+    /// The request waits for admin approval.
+    Pending { request_key: String },
+    /// `TeamTalk` is unreachable; the account is created once it is back.
+    Queued { username: Username },
+}
+
+/// Download details for a freshly created account.
+pub(super) struct CreatedAccount {
+    pub username: Username,
+    pub tt_link: String,
+    pub tt_filename: String,
+    pub tt_token: String,
+    pub client_zips: Vec<ClientZipDownload>,
+    /// Password the client ZIPs are encrypted with, if any.
+    pub zip_password: Option<String>,
+    /// Localized hint about `zip_password`.
+    pub zip_password_note: Option<String>,
+}
+
+/// Download details for one pre-configured client archive variant.
+pub(super) struct ClientZipDownload {
+    pub token: String,
+    pub filename: String,
+    pub name: String,
+    pub label: String,
+}
+
+/// Validate raw form values into domain types.
+pub(super) fn parse_credentials(
+    config: &AppConfig,
+    username: &str,
+    password: &str,
+    nickname: &str,
+) -> Result<(Username, Password, Nickname), WebRegisterError> {
+    let username = Username::parse(username).ok_or(WebRegisterError::UsernameInvalid)?;
+    config
+        .username_policy
+        .check(&username)
+        .map_err(WebRegisterError::UsernamePolicy)?;
+    let password = Password::parse(password).ok_or(WebRegisterError::PasswordInvalid)?;
+    config
+        .password_policy
+        .check(&password)
+        .map_err(WebRegisterError::PasswordPolicy)?;
+    let nickname = if nickname.is_empty() {
+        Nickname::parse(username.as_str())
+    } else {
+        Nickname::parse(nickname)
+    }
+    .ok_or(WebRegisterError::NicknameInvalid)?;
+    Ok((username, password, nickname))
+}
+
+/// The rights profile asked for, if it is one users may request.
+fn requested_profile<'a>(
+    config: &AppConfig,
+    name: &'a str,
+) -> Result<Option<&'a str>, WebRegisterError> {
+    match name {
+        "" | DEFAULT_RIGHTS_PROFILE => Ok(None),
+        name if config.is_requestable_profile(name) => Ok(Some(name)),
+        _ => Err(WebRegisterError::RightsProfileInvalid),
+    }
+}
+
+/// The invite `token` stands for; required while
+/// `web_public_registration_enabled` is off.
+pub(super) async fn check_invite(
+    state: &WebState,
+    token: &str,
+) -> Result<Option<DeeplinkToken>, WebRegisterError> {
+    if token.is_empty() {
+        return if state.config.web.web_public_registration_enabled {
+            Ok(None)
+        } else {
+            Err(WebRegisterError::InviteRequired)
+        };
+    }
+    match state.db.get_valid_deeplink(token).await {
+        Ok(Some(invite)) => Ok(Some(invite)),
+        Ok(None) => Err(WebRegisterError::InviteInvalid),
+        Err(e) => {
+            error!(error = %e, "Failed to look up web invite");
+            Err(WebRegisterError::Internal)
+        }
+    }
+}
+
+/// Run a web registration: either create the account or queue it for approval.
+pub(super) async fn register(
+    input: WebRegisterInput<'_>,
+) -> Result<WebRegistration, WebRegisterError> {
+    let state = input.state;
+    let ip = input.ip;
+    let invite = check_invite(state, input.invite).await?;
+    // An invite tied to a server decides it; one removed from the config since
+    // leaves the choice to the visitor.
+    let server = invite
+        .as_ref()
+        .and_then(|invite| invite.server_id.as_deref())
+        .and_then(|id| state.servers.get(id))
+        .or_else(|| {
+            state
+                .servers
+                .resolve(Some(input.server).filter(|id| !id.is_empty()))
+        })
+        .ok_or(WebRegisterError::UnknownServer)?;
+    let rights_profile = requested_profile(&state.config, input.rights_profile)?;
+    if let Some(captcha) = &state.captcha
+        && !captcha.verify(input.captcha_token, ip).await
+    {
+        return Err(WebRegisterError::CaptchaFailed);
+    }
+    if registration::ip_limit_reached(&state.db, &state.config, &ip.to_string()).await {
+        return Err(WebRegisterError::IpLimit);
+    }
+
+    let (username, password, nickname) = parse_credentials(
+        &state.config,
+        input.username,
+        input.password,
+        input.nickname,
+    )?;
+    if let Some(ban) =
+        bans::find_ban(&state.db, &state.config, Some(username.as_str()), Some(ip)).await
+    {
+        info!(%ip, username = %username, ban = ban.id, "Refused banned web registration");
+        return Err(WebRegisterError::Banned);
+    }
+    ensure_username_free(state, server, &username, ip).await?;
+    if let Some(invite) = &invite
+        && !state
+            .db
+            .consume_deeplink(&invite.token)
+            .await
+            .unwrap_or(false)
+    {
+        return Err(WebRegisterError::InviteInvalid);
+    }
+
+    let account = (&username, &password, &nickname);
+    let outcome = create_or_submit(&input, server, account, rights_profile).await;
+    // The invite's use is given back when neither an account nor a request came of it.
+    if let (Some(invite), Err(_)) = (&invite, &outcome)
+        && let Err(e) = state.db.restore_deeplink(&invite.token).await
+    {
+        error!(error = %e, "Failed to restore invite use");
+    }
+    match outcome? {
+        Some(registration) => Ok(registration),
+        None => {
+            finalize_created(
+                state, server, ip, input.lang, username, &password, &nickname,
+            )
+            .await
+        }
+    }
+}
+
+/// Submit the account for approval, or create or queue it on the server;
+/// `None` once it was created, for the caller to issue its downloads.
+async fn create_or_submit(
+    input: &WebRegisterInput<'_>,
+    server: &TTServer,
+    (username, password, nickname): (&Username, &Password, &Nickname),
+    rights_profile: Option<&str>,
+) -> Result<Option<WebRegistration>, WebRegisterError> {
+    let state = input.state;
+    let ip = input.ip;
+    let geo = state
+        .geoip
+        .as_ref()
+        .map(|geoip| geoip.lookup(ip))
+        .unwrap_or_default();
+    let account = (username, password, nickname);
+    if state.config.web.web_verify_registration || needs_approval(state, &geo) {
+        return submit_for_approval(input, server, account, rights_profile, &geo)
+            .await
+            .map(Some);
+    }
+
+    let result = server
+        .tx_tt
+        .request(|resp| TTWorkerCommand::CreateAccount {
+            username: username.clone(),
+            password: password.clone(),
+            nickname: nickname.clone(),
+            account_type: TTAccountType::Default,
+            rights_profile: rights_profile.unwrap_or(DEFAULT_RIGHTS_PROFILE).to_string(),
+            source: RegistrationSource::Web(ip),
+            source_info: None,
+            resp,
+        })
+        .await;
+
+    match result {
+        Ok(Ok(true)) => Ok(None),
+        Ok(Ok(false)) => {
+            warn!("TeamTalk create account returned false");
+            Err(WebRegisterError::UsernameTaken)
+        }
+        Ok(Err(TTCommandError::NotConnected)) if state.config.teamtalk.tt_offline_queue_enabled => {
+            finalize_queued(state, server, ip, account, rights_profile)
+                .await
+                .map(Some)
+        }
+        Ok(Err(TTCommandError::AccountExists)) => Err(WebRegisterError::UsernameTaken),
+        Ok(Err(TTCommandError::InvalidUsername)) => Err(WebRegisterError::UsernameInvalid),
+        Ok(Err(e)) => {
+            warn!(error = %e, "TeamTalk create account failed");
+            Err(WebRegisterError::Unavailable)
+        }
+        Err(e) => {
+            error!(error = %e, ip = %ip, "TeamTalk create account request failed");
+            Err(WebRegisterError::Unavailable)
+        }
+    }
+}
+
+/// Refuse names already on the server, ignoring case if the policy asks for it.
+async fn ensure_username_free(
+    state: &WebState,
+    server: &TTServer,
+    username: &Username,
+    ip: IpAddr,
+) -> Result<(), WebRegisterError> {
+    match server
+        .tx_tt
+        .request(|resp| TTWorkerCommand::CheckUserExists {
+            username: username.clone(),
+            matching: state.config.username_policy.matching(),
+            resp,
+        })
+        .await
+    {
+        Ok(false) => Ok(()),
+        Ok(true) => Err(WebRegisterError::UsernameTaken),
+        Err(e) => {
+            error!(error = %e, ip = %ip, "TeamTalk user existence check failed");
+            Err(WebRegisterError::Unavailable)
+        }
+    }
+}
+
+/// Whether `[web.geoip]` sends registrations from this country to admins.
+fn needs_approval(state: &WebState, geo: &GeoInfo) -> bool {
+    let (Some(geoip), Some(country)) = (&state.config.web.sections.geoip, &geo.country) else {
+        return false;
+    };
+    geoip.requires_approval(country)
+}
+
+async fn submit_for_approval(
+    input: &WebRegisterInput<'_>,
+    server: &TTServer,
+    (username, password, nickname): (&Username, &Password, &Nickname),
+    rights_profile: Option<&str>,
+    geo: &GeoInfo,
+) -> Result<WebRegistration, WebRegisterError> {
+    let state = input.state;
+    let ip = input.ip;
+    let request_key = Uuid::new_v4().to_string();
+    let ip_str = ip.to_string();
+    let source_info = SourceInfo {
+        server: Some(server.id().to_string()),
+        rights_profile: rights_profile.map(str::to_string),
+        ip: Some(ip),
+        user_agent: input.user_agent.map(str::to_string),
+        country: geo.country.clone(),
+        asn: geo.asn,
+        ..SourceInfo::new(SourceKind::Web, LanguageCode::parse_or_default(input.lang))
+    };
+    if let Err(e) = state
+        .db
+        .add_pending_web_registration(&NewPendingWebRegistration {
+            key: &request_key,
+            username: username.as_str(),
+            password: password.as_str(),
+            nickname: nickname.as_str(),
+            ip_address: &ip_str,
+            user_agent: input.user_agent,
+            source_info: &source_info,
+        })
+        .await
+    {
+        error!(error = %e, ip = %ip, "Failed to store pending web registration");
+        return Err(WebRegisterError::SubmitFailed);
+    }
+    if let Err(e) = state
+        .db
+        .add_registered_ip(&ip_str, Some(username.as_str()))
+        .await
+    {
+        warn!(error = %e, ip = %ip, "Failed to store registered IP");
+    }
+    audit::record(
+        &state.db,
+        Actor::Visitor(ip),
+        AuditAction::WebRegistration,
+        username.as_str(),
+        Some("Awaiting approval"),
+    )
+    .await;
+
+    notify_admins_web_request(
+        &state.bot,
+        &state.db,
+        &state.config,
+        &WebApprovalRequest {
+            request_key: &request_key,
+            username,
+            nickname,
+            ip,
+            user_agent: input.user_agent,
+            server: server.config.server_label(),
+            via_teamtalk: false,
+            rights_profile,
+            location: geo.summary().as_deref(),
+        },
+    )
+    .await;
+
+    Ok(WebRegistration::Pending { request_key })
+}
+
+async fn finalize_queued(
+    state: &WebState,
+    server: &TTServer,
+    ip: IpAddr,
+    (username, password, nickname): (&Username, &Password, &Nickname),
+    rights_profile: Option<&str>,
+) -> Result<WebRegistration, WebRegisterError> {
+    let result = registration::queue_account(&registration::CreateAccountParams {
+        username,
+        password,
+        nickname,
+        account_type: TTAccountType::Default,
+        rights_profile,
+        source: RegistrationSource::Web(ip),
+        source_info: None,
+        telegram_id: None,
+        language: None,
+        queued_id: None,
+        tx_tt: server.tx_tt.clone(),
+        db: &state.db,
+        config: &server.config,
+    })
+    .await;
+    if !result.queued {
+        return Err(WebRegisterError::Unavailable);
+    }
+    if let Err(e) = state
+        .db
+        .add_registered_ip(&ip.to_string(), Some(username.as_str()))
+        .await
+    {
+        warn!(error = %e, ip = %ip, "Failed to store registered IP");
+    }
+    audit::record(
+        &state.db,
+        Actor::Visitor(ip),
+        AuditAction::WebRegistration,
+        username.as_str(),
+        Some("Queued until TeamTalk is reachable"),
+    )
+    .await;
+    Ok(WebRegistration::Queued {
+        username: username.clone(),
+    })
+}
+
+async fn finalize_created(
+    state: &WebState,
+    server: &TTServer,
+    ip: IpAddr,
+    lang: &str,
+    username: Username,
+    password: &Password,
+    nickname: &Nickname,
+) -> Result<WebRegistration, WebRegisterError> {
+    if let Err(e) = state
+        .db
+        .add_registered_ip(&ip.to_string(), Some(username.as_str()))
+        .await
+    {
+        warn!(error = %e, ip = %ip, "Failed to store registered IP");
+    }
+    registration::schedule_welcome(&state.db, &server.config, username.as_str(), lang).await;
+    audit::record(
+        &state.db,
+        Actor::Visitor(ip),
+        AuditAction::WebRegistration,
+        username.as_str(),
+        None,
+    )
+    .await;
+
+    issue_downloads(state, server, lang, username, password, nickname)
+        .await
+        .map(WebRegistration::Created)
+}
+
+/// Generate the `.tt` file and client ZIPs of an account and their download
+/// tokens.
+async fn issue_downloads(
+    state: &WebState,
+    server: &TTServer,
+    lang: &str,
+    username: Username,
+    password: &Password,
+    nickname: &Nickname,
+) -> Result<CreatedAccount, WebRegisterError> {
+    let temp_dir = state.config.get_temp_dir();
+    let unique_id = Uuid::new_v4().to_string();
+    let assets = registration::build_assets(
+        &server.config,
+        lang,
+        username.as_str(),
+        password.as_str(),
+        nickname.as_str(),
+    );
+    let safe_tt_path = write_tt_file(temp_dir, &unique_id, &assets).await?;
+    let expires = build_token_expiry(state);
+    let tt_token = persist_tt_token(state, &safe_tt_path, &assets, expires).await;
+    let mut client_zips = Vec::new();
+    for template in registration::client_templates(&state.config) {
+        client_zips.extend(
+            try_create_zip_token(state, template, &unique_id, &username, &assets, expires).await?,
+        );
+    }
+
+    let (zip_password, zip_password_note) = if client_zips.is_empty() {
+        (None, None)
+    } else {
+        (
+            assets.zip_password.clone(),
+            registration::zip_password_note(&state.config, lang, &assets),
+        )
+    };
+
+    Ok(CreatedAccount {
+        username,
+        tt_link: assets.link,
+        tt_filename: assets.filename,
+        tt_token,
+        client_zips,
+        zip_password,
+        zip_password_note,
+    })
+}
+
+/// Review state of a web registration awaiting or past approval.
+pub(super) struct RequestStatus {
+    /// `None` when the request is unknown or was cleaned up.
+    pub status: Option<WebRequestStatus>,
+    /// Reason given with a rejection.
+    pub reason: Option<String>,
+    /// Downloads of an approved request, issued on the first look after the
+    /// approval.
+    pub account: Option<CreatedAccount>,
+}
+
+/// Look up a request by its status key, issuing the downloads of an approved
+/// one that has not collected them yet.
+pub(super) async fn request_status(state: &WebState, key: &str, lang: &str) -> RequestStatus {
+    let req = match state.db.get_pending_web_registration(key).await {
+        Ok(req) => req,
+        Err(e) => {
+            error!(error = %e, "Failed to load web registration status");
+            None
+        }
+    };
+    let Some(req) = req else {
+        return RequestStatus {
+            status: None,
+            reason: None,
+            account: None,
+        };
+    };
+    let status = WebRequestStatus::try_from(req.status.as_str()).ok();
+    let account = if status == Some(WebRequestStatus::Approved) {
+        approved_downloads(state, key, lang, &req).await
+    } else {
+        None
+    };
+    RequestStatus {
+        reason: req
+            .decision_reason
+            .filter(|_| status == Some(WebRequestStatus::Rejected)),
+        status,
+        account,
+    }
+}
+
+async fn approved_downloads(
+    state: &WebState,
+    key: &str,
+    lang: &str,
+    req: &PendingWebRegistration,
+) -> Option<CreatedAccount> {
+    let password = match state.db.take_web_registration_password(key).await {
+        Ok(password) => password?,
+        Err(e) => {
+            error!(error = %e, "Failed to load approved web registration");
+            return None;
+        }
+    };
+    let parsed = (
+        Username::parse(&req.username),
+        Password::parse(&password),
+        Nickname::parse(&req.nickname),
+        state.servers.resolve(req.source_info.server.as_deref()),
+    );
+    let (Some(username), Some(password), Some(nickname), Some(server)) = parsed else {
+        warn!(key, "Approved web registration has invalid stored details");
+        return None;
+    };
+    issue_downloads(state, server, lang, username, &password, &nickname)
+        .await
+        .ok()
+}
+
+async fn write_tt_file(
+    temp_dir: &Path,
+    unique_id: &str,
+    assets: &registration::RegistrationAssets,
+) -> Result<PathBuf, WebRegisterError> {
+    let safe_tt_path = temp_dir.join(format!("{unique_id}_{}", assets.filename));
+    if let Err(e) = tokio::fs::write(&safe_tt_path, &assets.content).await {
+        error!(error = %e, path = ?safe_tt_path, "Failed to write TT file");
+        return Err(WebRegisterError::Internal);
+    }
+    Ok(safe_tt_path)
+}
+
+fn build_token_expiry(state: &WebState) -> chrono::NaiveDateTime {
+    let ttl_seconds = i64::try_from(state.config.database.generated_file_ttl_seconds)
+        .unwrap_or_else(|_| {
+            warn!(
+                ttl = state.config.database.generated_file_ttl_seconds,
+                "generated_file_ttl_seconds too large for i64, clamping"
+            );
+            i64::MAX
+        });
+    Utc::now().naive_utc() + Duration::seconds(ttl_seconds)
+}
+
+async fn persist_tt_token(
+    state: &WebState,
+    safe_tt_path: &Path,
+    assets: &registration::RegistrationAssets,
+    expires: chrono::NaiveDateTime,
+) -> String {
+    let Some(tt_path_name) = safe_tt_path.file_name().and_then(|n| n.to_str()) else {
+        error!(path = ?safe_tt_path, "Invalid TT file name");
+        return Uuid::new_v4().to_string();
+    };
+    issue_download_token(
+        state,
+        tt_path_name,
+        &assets.filename,
+        DownloadTokenType::TtConfig,
+        expires,
+        None,
+        None,
+    )
+    .await
+}
+
+/// Issue a download token in the configured mode; failures are logged and
+/// leave a token that will not resolve. With `zip_source` the client ZIP is
+/// generated from those assets on download.
+async fn issue_download_token(
+    state: &WebState,
+    path_name: &str,
+    original_name: &str,
+    token_type: DownloadTokenType,
+    expires: chrono::NaiveDateTime,
+    zip_source: Option<&registration::RegistrationAssets>,
+    client_template: Option<&str>,
+) -> String {
+    let max_uses = Some(state.config.web.web_download_max_uses).filter(|uses| *uses > 0);
+    if state.config.web.web_download_links == DownloadLinkMode::Signed {
+        return match state.signed_links.issue(
+            path_name,
+            original_name,
+            token_type,
+            expires,
+            max_uses,
+        ) {
+            Ok(token) => token,
+            Err(e) => {
+                warn!(error = %e, "Failed to sign download link");
+                Uuid::new_v4().to_string()
+            }
+        };
+    }
+    let token = Uuid::new_v4().to_string();
+    if let Err(e) = state
+        .db
+        .add_download_token(&NewDownloadToken {
+            token: &token,
+            filepath: path_name,
+            original_name,
+            token_type,
+            expires_at: expires,
+            max_uses,
+            zip_source: zip_source.map(|assets| assets.content.as_str()),
+            zip_readme: zip_source.map(|assets| assets.readme.as_str()),
+            zip_password: zip_source.and_then(|assets| assets.zip_password.as_deref()),
+            client_template,
+        })
+        .await
+    {
+        warn!(error = %e, "Failed to persist download token");
+    }
+    token
+}
+
+async fn try_create_zip_token(
+    state: &WebState,
+    template: &ClientTemplate,
+    unique_id: &str,
+    username: &Username,
+    assets: &registration::RegistrationAssets,
+    expires: chrono::NaiveDateTime,
+) -> Result<Option<ClientZipDownload>, WebRegisterError> {
+    let zip_name = registration::client_zip_filename(&state.config, template, username.as_str());
+    // Signed links cannot carry the `.tt` source, so they always use a file.
+    let z_tok = if state.config.web.web_download_links == DownloadLinkMode::Database
+        && registration::client_zip_streamable(&state.config, template).await
+    {
+        issue_download_token(
+            state,
+            "",
+            &zip_name,
+            DownloadTokenType::ClientZip,
+            expires,
+            Some(assets),
+            Some(&template.name),
+        )
+        .await
+    } else {
+        let safe_zip_path = state
+            .config
+            .get_temp_dir()
+            .join(format!("{unique_id}_{zip_name}"));
+        if !registration::try_create_client_zip_async(
+            &state.config,
+            template,
+            &safe_zip_path,
+            assets,
+        )
+        .await
+        {
+            return Ok(None);
+        }
+        let Some(zip_path_name) = safe_zip_path.file_name().and_then(|n| n.to_str()) else {
+            error!(path = ?safe_zip_path, "Invalid ZIP file name");
+            return Err(WebRegisterError::Internal);
+        };
+        issue_download_token(
+            state,
+            zip_path_name,
+            &zip_name,
+            DownloadTokenType::ClientZip,
+            expires,
+            None,
+            Some(&template.name),
+        )
+        .await
+    };
+    Ok(Some(ClientZipDownload {
+        token: z_tok,
+        filename: zip_name,
+        name: template.name.clone(),
+        label: template.label().to_string(),
+    }))
+}
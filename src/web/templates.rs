@@ -1,31 +1,57 @@
+use super::captcha::CaptchaWidget;
+use crate::db::schema::{
+    AuditEntry, Ban, BannedUser, FastapiDownloadToken, PendingTelegramRegistration,
+    PendingWebRegistration, TelegramRegistration,
+};
 use crate::i18n::{t, t_args};
-use crate::types::LanguageCode;
+use crate::types::{LanguageCode, OnlineUser};
 use askama::Template;
 use askama_derive_axum::IntoResponse;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-/// Template context for the registration page.
-#[derive(Template, IntoResponse)]
+/// Template context for the registration page, also handed to custom Tera
+/// templates.
+#[derive(Template, IntoResponse, Serialize)]
 #[template(path = "register.html")]
 pub struct RegisterTemplate {
     pub message: Option<String>,
-    pub message_class: Option<String>,
-    pub message_class_safe: String,
+    pub message_class: MessageClass,
     pub additional_message_info: Option<String>,
     pub registration_complete: bool,
     pub server_name: String,
     pub username_val: String,
     pub nickname_val: String,
+    /// `(id, name)` of each server; empty when there is only one.
+    pub servers: Vec<(String, String)>,
+    pub server_val: String,
+    /// Profiles users may request; empty when there is nothing to choose.
+    pub rights_profiles: Vec<String>,
+    pub rights_profile_val: String,
     pub tt_link: Option<String>,
+    pub tt_link_qr: Option<String>,
     pub download_tt_token: Option<String>,
-    pub download_client_zip_token: Option<String>,
     pub actual_tt_filename_for_user: Option<String>,
-    pub actual_client_zip_filename_for_user: Option<String>,
+    pub client_zips: Vec<ClientZipLink>,
+    pub zip_password_note: Option<String>,
     pub available_languages: Vec<(String, String)>,
     pub current_lang: String,
     pub language_forced: bool,
     pub generated_file_ttl_seconds: u64,
+    pub status_key: Option<String>,
+    pub captcha: Option<CaptchaWidget>,
+    /// Hidden field value for the forms; `None` when CSRF protection is off.
+    pub csrf_token: Option<String>,
+    /// Invite token carried through the forms.
+    pub invite: Option<String>,
+    /// Render the hidden honeypot field.
+    pub honeypot: bool,
+    /// Signed render time of the form; `None` when the fill time check is off.
+    pub form_stamp: Option<String>,
+    /// `root_path` prefix of the page's links and form actions.
+    pub base_path: String,
+    /// Page the language switcher returns to.
+    pub return_to: String,
 
     pub page_title: String,
     pub page_header: String,
@@ -34,6 +60,9 @@ pub struct RegisterTemplate {
     pub label_username: String,
     pub label_nickname: String,
     pub placeholder_nickname: String,
+    pub label_server: String,
+    pub label_rights_profile: String,
+    pub rights_profile_default: String,
     pub label_password: String,
     pub show_password: String,
     pub btn_register: String,
@@ -44,11 +73,13 @@ pub struct RegisterTemplate {
     pub link_tt_text: String,
     pub link_zip_text: String,
     pub quick_link_text: String,
+    pub qr_text: String,
     pub countdown_text: String,
     pub expired_text: String,
     pub second_text: String,
     pub seconds_few_text: String,
     pub seconds_text: String,
+    pub status_info_text: String,
 }
 
 impl RegisterTemplate {
@@ -65,22 +96,34 @@ impl RegisterTemplate {
 
         Self {
             message: None,
-            message_class: None,
-            message_class_safe: "info".to_string(),
+            message_class: MessageClass::Info,
             additional_message_info: None,
             registration_complete: false,
             server_name: server_name.to_string(),
             username_val: String::new(),
             nickname_val: String::new(),
+            servers: Vec::new(),
+            server_val: String::new(),
+            rights_profiles: Vec::new(),
+            rights_profile_val: String::new(),
             tt_link: None,
+            tt_link_qr: None,
+            zip_password_note: None,
             download_tt_token: None,
-            download_client_zip_token: None,
             actual_tt_filename_for_user: None,
-            actual_client_zip_filename_for_user: None,
+            client_zips: Vec::new(),
             available_languages,
             current_lang: lang.to_string(),
             language_forced,
             generated_file_ttl_seconds,
+            status_key: None,
+            captcha: None,
+            csrf_token: None,
+            invite: None,
+            honeypot: false,
+            form_stamp: None,
+            base_path: String::new(),
+            return_to: String::new(),
 
             page_title: t_args(lang.as_str(), "web-title", &args),
             page_header: t_args(lang.as_str(), "web-header", &args),
@@ -89,6 +132,9 @@ impl RegisterTemplate {
             label_username: t(lang.as_str(), "web-label-username"),
             label_nickname: t(lang.as_str(), "web-label-nickname"),
             placeholder_nickname: t(lang.as_str(), "web-placeholder-nickname"),
+            label_server: t(lang.as_str(), "web-label-server"),
+            label_rights_profile: t(lang.as_str(), "web-label-rights-profile"),
+            rights_profile_default: t(lang.as_str(), "rights-profile-default"),
             label_password: t(lang.as_str(), "web-label-password"),
             show_password: t(lang.as_str(), "web-show-password"),
             btn_register: t(lang.as_str(), "web-btn-register"),
@@ -99,11 +145,56 @@ impl RegisterTemplate {
             link_tt_text: t(lang.as_str(), "web-link-tt"),
             link_zip_text: t(lang.as_str(), "web-link-zip"),
             quick_link_text: t(lang.as_str(), "web-quick-link"),
+            qr_text: t(lang.as_str(), "web-qr-text"),
             countdown_text: t(lang.as_str(), "web-countdown-text"),
             expired_text: t(lang.as_str(), "web-expired"),
             second_text: t(lang.as_str(), "web-second"),
             seconds_few_text: t(lang.as_str(), "web-seconds-few"),
             seconds_text: t(lang.as_str(), "web-seconds"),
+            status_info_text: t(lang.as_str(), "web-status-info"),
+        }
+    }
+
+    /// Show `message` above the form or the registration result.
+    pub fn show_message(&mut self, message: String, class: MessageClass) {
+        self.message = Some(message);
+        self.message_class = class;
+    }
+
+    /// Fill the form with what the visitor sent, except the password.
+    pub fn keep_form(&mut self, form: &RegisterForm) {
+        self.username_val.clone_from(&form.username);
+        self.nickname_val.clone_from(&form.nickname);
+        self.server_val.clone_from(&form.server);
+        self.rights_profile_val.clone_from(&form.rights_profile);
+        self.invite = Some(form.invite.clone()).filter(|token| !token.is_empty());
+    }
+}
+
+/// A client ZIP variant offered on the success page.
+#[derive(Serialize)]
+pub struct ClientZipLink {
+    pub token: String,
+    pub filename: String,
+    pub label: String,
+}
+
+/// Style of the page message.
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageClass {
+    Info,
+    Success,
+    Error,
+}
+
+impl MessageClass {
+    /// CSS class of the message box.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Success => "success",
+            Self::Error => "error",
         }
     }
 }
@@ -114,4 +205,136 @@ pub struct RegisterForm {
     pub username: String,
     pub nickname: String,
     pub password: String,
+    #[serde(default)]
+    pub server: String,
+    #[serde(default)]
+    pub rights_profile: String,
+    #[serde(default)]
+    pub csrf_token: String,
+    #[serde(default)]
+    pub invite: String,
+    /// Honeypot field, left empty by people.
+    #[serde(default)]
+    pub website: String,
+    #[serde(default)]
+    pub form_stamp: String,
+    #[serde(default, rename = "cf-turnstile-response")]
+    pub turnstile_response: String,
+    #[serde(default, rename = "h-captcha-response")]
+    pub hcaptcha_response: String,
+}
+
+/// Query of the registration page.
+#[derive(Deserialize)]
+pub struct RegisterQuery {
+    #[serde(default)]
+    pub invite: String,
+    /// Language to show and remember, e.g. `ru`.
+    #[serde(default)]
+    pub lang: String,
+}
+
+impl RegisterForm {
+    /// Token posted by whichever CAPTCHA widget is embedded.
+    pub fn captcha_token(&self) -> &str {
+        if self.turnstile_response.is_empty() {
+            &self.hcaptcha_response
+        } else {
+            &self.turnstile_response
+        }
+    }
+}
+
+/// Shared header data for admin dashboard pages.
+pub struct AdminLayout {
+    pub lang: String,
+    pub title: String,
+    pub server_name: String,
+    pub base: String,
+    pub notice: Option<String>,
+}
+
+impl AdminLayout {
+    /// Translate a key into the admin language.
+    pub fn t(&self, key: &str) -> String {
+        t(&self.lang, key)
+    }
+}
+
+/// Dashboard sign-in form.
+#[derive(Template, IntoResponse)]
+#[template(path = "admin/login.html")]
+pub struct AdminLoginTemplate {
+    pub layout: AdminLayout,
+    pub error: Option<String>,
+    /// Ask for a TOTP code too.
+    pub totp: bool,
+}
+
+/// Admin dashboard overview.
+#[derive(Template, IntoResponse)]
+#[template(path = "admin/index.html")]
+pub struct AdminIndexTemplate {
+    pub layout: AdminLayout,
+    pub registrations: usize,
+    pub bans: usize,
+    pub pending: usize,
+    pub tokens: usize,
+}
+
+/// Telegram registrations list.
+#[derive(Template, IntoResponse)]
+#[template(path = "admin/registrations.html")]
+pub struct AdminRegistrationsTemplate {
+    pub layout: AdminLayout,
+    pub rows: Vec<TelegramRegistration>,
+}
+
+/// Banlist with ban/unban forms.
+#[derive(Template, IntoResponse)]
+#[template(path = "admin/bans.html")]
+pub struct AdminBansTemplate {
+    pub layout: AdminLayout,
+    pub rows: Vec<BannedUser>,
+    /// Username and IP network bans.
+    pub entries: Vec<Ban>,
+    /// Offer deleting the linked `TeamTalk` account next to each ban whose
+    /// account is on the top-level server.
+    pub propagate: bool,
+}
+
+/// Registrations waiting for admin approval.
+#[derive(Template, IntoResponse)]
+#[template(path = "admin/pending.html")]
+pub struct AdminPendingTemplate {
+    pub layout: AdminLayout,
+    pub telegram: Vec<PendingTelegramRegistration>,
+    pub web: Vec<PendingWebRegistration>,
+}
+
+/// Unused, unexpired download tokens.
+#[derive(Template, IntoResponse)]
+#[template(path = "admin/tokens.html")]
+pub struct AdminTokensTemplate {
+    pub layout: AdminLayout,
+    pub rows: Vec<FastapiDownloadToken>,
+}
+
+/// Online users and server accounts.
+#[derive(Template, IntoResponse)]
+#[template(path = "admin/teamtalk.html")]
+pub struct AdminTeamTalkTemplate {
+    pub layout: AdminLayout,
+    pub online: Vec<OnlineUser>,
+    pub accounts: Vec<String>,
+}
+
+/// Audit log, newest first.
+#[derive(Template, IntoResponse)]
+#[template(path = "admin/audit.html")]
+pub struct AdminAuditTemplate {
+    pub layout: AdminLayout,
+    pub rows: Vec<AuditEntry>,
+    pub prev_page: Option<usize>,
+    pub next_page: Option<usize>,
 }
@@ -0,0 +1,48 @@
+use super::templates::RegisterTemplate;
+use axum::response::{Html, IntoResponse, Response};
+use std::path::Path;
+use tracing::{error, info, warn};
+
+/// Name of the registration page inside `web_templates_dir`.
+const REGISTER_PAGE: &str = "register.html";
+
+/// Operator templates from `web_templates_dir`, rendered with Tera instead of
+/// the built-in pages.
+pub(super) struct Theme {
+    tera: tera::Tera,
+}
+
+impl Theme {
+    /// Load every `*.html` under `dir`; `None` when it has no `register.html`
+    /// or fails to parse, so the built-in page is served.
+    pub(super) fn load(dir: &Path) -> Option<Self> {
+        let glob = dir.join("**").join("*.html");
+        let tera = match tera::Tera::new(&glob.to_string_lossy()) {
+            Ok(tera) => tera,
+            Err(e) => {
+                error!(error = ?e, dir = %dir.display(), "Failed to load web templates, using the built-in ones");
+                return None;
+            }
+        };
+        if !tera.get_template_names().any(|name| name == REGISTER_PAGE) {
+            warn!(dir = %dir.display(), "web_templates_dir has no register.html, using the built-in one");
+            return None;
+        }
+        info!(dir = %dir.display(), "Using custom web templates");
+        Some(Self { tera })
+    }
+
+    /// Render the registration page, falling back to the built-in template
+    /// when the custom one fails.
+    pub(super) fn render(&self, page: RegisterTemplate) -> Response {
+        let rendered = tera::Context::from_serialize(&page)
+            .and_then(|context| self.tera.render(REGISTER_PAGE, &context));
+        match rendered {
+            Ok(html) => Html(html).into_response(),
+            Err(e) => {
+                error!(error = ?e, "Failed to render custom register.html");
+                page.into_response()
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use axum_server::tls_rustls::RustlsConfig;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+/// Keeps a [`RustlsConfig`] in step with the certificate and key on disk, so
+/// renewed certificates (e.g. by certbot) are served without a restart.
+pub(super) struct CertReloader {
+    tls_config: RustlsConfig,
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    modified: Option<(SystemTime, SystemTime)>,
+}
+
+impl CertReloader {
+    pub(super) fn new(tls_config: RustlsConfig, cert_path: String, key_path: String) -> Self {
+        let mut reloader = Self {
+            tls_config,
+            cert_path: cert_path.into(),
+            key_path: key_path.into(),
+            modified: None,
+        };
+        reloader.modified = reloader.modified_times();
+        reloader
+    }
+
+    /// Reload when either file changes, checked every `interval_seconds`
+    /// (0 disables polling), and on `SIGHUP`, until `shutdown`.
+    pub(super) async fn run(mut self, interval_seconds: u64, shutdown: CancellationToken) {
+        let mut hangup = hangup_signal();
+        let mut poll = (interval_seconds > 0).then(|| {
+            let mut poll = tokio::time::interval(Duration::from_secs(interval_seconds));
+            poll.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            poll
+        });
+        loop {
+            let forced = tokio::select! {
+                () = shutdown.cancelled() => return,
+                () = hangup.recv() => true,
+                _ = async {
+                    match poll.as_mut() {
+                        Some(poll) => poll.tick().await,
+                        None => std::future::pending().await,
+                    }
+                } => false,
+            };
+            self.reload(forced).await;
+        }
+    }
+
+    async fn reload(&mut self, forced: bool) {
+        let modified = self.modified_times();
+        if !forced && (modified.is_none() || modified == self.modified) {
+            return;
+        }
+        match self
+            .tls_config
+            .reload_from_pem_file(&self.cert_path, &self.key_path)
+            .await
+        {
+            Ok(()) => {
+                self.modified = modified;
+                info!(cert_path = %self.cert_path.display(), "Reloaded TLS certificate");
+            }
+            Err(e) => {
+                // Keep the old certificate; a half-written renewal is picked
+                // up on the next check.
+                warn!(error = %e, "Failed to reload TLS certificate, keeping the current one");
+            }
+        }
+    }
+
+    fn modified_times(&self) -> Option<(SystemTime, SystemTime)> {
+        let modified = |path: &PathBuf| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        Some((modified(&self.cert_path)?, modified(&self.key_path)?))
+    }
+}
+
+/// `SIGHUP` notifications; never fires on platforms without it.
+struct HangupSignal {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl HangupSignal {
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = self.signal.as_mut()
+            && signal.recv().await.is_some()
+        {
+            return;
+        }
+        std::future::pending::<()>().await;
+    }
+}
+
+fn hangup_signal() -> HangupSignal {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        let signal = signal(SignalKind::hangup())
+            .inspect_err(|e| warn!(error = %e, "Failed to register SIGHUP handler"))
+            .ok();
+        HangupSignal { signal }
+    }
+    #[cfg(not(unix))]
+    {
+        HangupSignal {}
+    }
+}
@@ -0,0 +1,49 @@
+use axum::Router;
+use axum::extract::{ConnectInfo, Extension};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::path::Path;
+use tokio::net::UnixListener;
+use tracing::warn;
+
+/// Connections on a Unix socket come from the local reverse proxy; they are
+/// reported as this loopback peer so `web_app_forwarded_allow_ips` decides
+/// whether their forwarded headers are trusted, as for a proxy on TCP.
+const PROXY_PEER: SocketAddr = SocketAddr::new(std::net::IpAddr::V4(Ipv4Addr::LOCALHOST), 0);
+
+/// Serve plain HTTP on the Unix socket at `path`, replacing a stale socket
+/// left by a previous run and removing it on shutdown. Every request carries
+/// [`PROXY_PEER`] as its `ConnectInfo<SocketAddr>`, so the handlers work
+/// unchanged.
+pub(super) async fn serve(
+    path: &Path,
+    mode: u32,
+    app: Router,
+    shutdown: tokio_util::sync::CancellationToken,
+) -> anyhow::Result<()> {
+    if std::fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+        std::fs::remove_file(path).map_err(|e| {
+            anyhow::anyhow!("Failed to remove stale socket {}: {e}", path.display())
+        })?;
+    }
+    let listener = UnixListener::bind(path)
+        .map_err(|e| anyhow::anyhow!("Failed to bind Unix socket {}: {e}", path.display()))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+        .map_err(|e| anyhow::anyhow!("Failed to set permissions of {}: {e}", path.display()))?;
+
+    let shutdown_wait = shutdown.clone();
+    let result = axum::serve(
+        listener,
+        app.layer(Extension(ConnectInfo(PROXY_PEER)))
+            .into_make_service(),
+    )
+    .with_graceful_shutdown(async move {
+        shutdown_wait.cancelled().await;
+    })
+    .await
+    .map_err(|e| anyhow::anyhow!("HTTP server failed: {e}"));
+    if let Err(e) = std::fs::remove_file(path) {
+        warn!(error = %e, path = %path.display(), "Failed to remove Unix socket");
+    }
+    result
+}